@@ -0,0 +1,183 @@
+//! Desktop build of intonation-toy
+//!
+//! Runs the model and the renderer of the web app in a native window, on the
+//! default input device of the system or the one named as the first argument.
+//! The sidebar is browser-only; a strip of controls along the bottom of the
+//! window changes the tonal center, concert pitch and display, and pauses the
+//! input. Run with
+//! `cargo run -p intonation-toy-desktop --features desktop --release`.
+
+use intonation_toy::app_config::{DEFAULT_DISPLAY_RANGE, DEFAULT_THEME, MAX_CONCERT_PITCH_HZ, MIN_CONCERT_PITCH_HZ, WINDOW_TITLE};
+use intonation_toy::common::interval_axis::{wrap_octaves, IntervalAxis};
+use intonation_toy::common::localization::current_language;
+use intonation_toy::common::music_theory::midi_note_to_frequency;
+use intonation_toy::common::pane_layout::{PaneKind, PaneLayout};
+use intonation_toy::common::shared_types::{
+    decrement_midi_note, increment_midi_note, DisplayScene, IntervalMode, ModelUpdateResult, Pitch, PresentationContext,
+};
+use intonation_toy::common::theme::initialize_theme;
+use intonation_toy::common::utils::now_ms;
+use intonation_toy::engine::desktop::{input_device_names, CpalAudioBackend};
+use intonation_toy::engine::AudioBackend;
+use intonation_toy::model::DataModel;
+use intonation_toy::presentation::{
+    AudioAnalysis, ConfigureConcertPitch, ConfigureTonalCenter, PresentationLayerActions, Renderer,
+};
+use three_d::egui;
+
+const SCENES: [(DisplayScene, &str); 4] = [
+    (DisplayScene::Intonation, "Intonation"),
+    (DisplayScene::PitchGraph, "Pitch Graph"),
+    (DisplayScene::Strobe, "Strobe"),
+    (DisplayScene::Needle, "Needle"),
+];
+
+fn main() {
+    let device_name = std::env::args().nth(1);
+    let mut backend = match CpalAudioBackend::new(device_name.as_deref()) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Input devices: {}", input_device_names().join(", "));
+            std::process::exit(1);
+        }
+    };
+
+    let window = three_d::Window::new(three_d::WindowSettings {
+        title: WINDOW_TITLE.to_string(),
+        min_size: (480, 360),
+        ..Default::default()
+    })
+    .expect("Failed to open a window");
+    let context = window.gl();
+    initialize_theme(DEFAULT_THEME);
+    let mut renderer = match Renderer::new(&context, window.viewport()) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            eprintln!("Failed to create the renderer: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let pane_layout = PaneLayout::default();
+    let mut gui = three_d::GUI::new(&context);
+    let mut model = DataModel::default();
+    let mut pending_actions = PresentationLayerActions::default();
+    let mut scene = DisplayScene::default();
+    let mut paused = false;
+
+    window.render_loop(move |mut frame_input| {
+        let engine_data = AudioBackend::update(&mut backend);
+        for error in &engine_data.audio_errors {
+            eprintln!("{:?}", error);
+        }
+        let model_data = model.update(engine_data);
+        let model_actions = model.process_user_actions(std::mem::take(&mut pending_actions));
+        AudioBackend::execute_actions(&mut backend, model_actions);
+
+        let viewport = frame_input.viewport;
+        renderer.set_pane_layout(&pane_layout, viewport);
+        let display_viewport = renderer.pane_viewport(PaneKind::Display).unwrap_or(viewport);
+        update_renderer(&mut renderer, &model_data, scene, display_viewport);
+
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                egui::TopBottomPanel::bottom("controls").show(gui_context, |ui| {
+                    ui.horizontal(|ui| {
+                        let tonal_center_note = model_data.tonal_center_note;
+                        let mut configure_tonal_center = |note| {
+                            pending_actions.tonal_center_configuration = Some(ConfigureTonalCenter { note, volume: 0.0 });
+                        };
+                        if ui.button("−").clicked()
+                            && let Some(note) = decrement_midi_note(tonal_center_note)
+                        {
+                            configure_tonal_center(note);
+                        }
+                        ui.label(model_data.note_namer().name(tonal_center_note));
+                        if ui.button("+").clicked()
+                            && let Some(note) = increment_midi_note(tonal_center_note)
+                        {
+                            configure_tonal_center(note);
+                        }
+
+                        ui.separator();
+                        ui.label("A4 (Hz)");
+                        let mut concert_pitch_hz = model_data.concert_pitch_hz;
+                        let drag = egui::DragValue::new(&mut concert_pitch_hz).range(MIN_CONCERT_PITCH_HZ..=MAX_CONCERT_PITCH_HZ).speed(0.1);
+                        if ui.add(drag).changed() {
+                            pending_actions.concert_pitch_configuration = Some(ConfigureConcertPitch { frequency_hz: concert_pitch_hz });
+                        }
+
+                        ui.separator();
+                        egui::ComboBox::from_id_salt("scene")
+                            .selected_text(SCENES.iter().find(|(value, _)| *value == scene).map_or("", |(_, label)| *label))
+                            .show_ui(ui, |ui| {
+                                for (value, label) in SCENES {
+                                    ui.selectable_value(&mut scene, value, label);
+                                }
+                            });
+
+                        ui.separator();
+                        if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                            let toggled = if paused { backend.resume() } else { backend.suspend() };
+                            match toggled {
+                                Ok(()) => paused = !paused,
+                                Err(e) => eprintln!("{}", e),
+                            }
+                        }
+                        ui.label(format!("{} · {} Hz", backend.device_name(), backend.sample_rate()));
+                    });
+                });
+            },
+        );
+
+        let mut screen = frame_input.screen();
+        renderer.render(&mut screen, viewport);
+        screen.write(|| gui.render()).unwrap();
+        three_d::FrameOutput::default()
+    });
+}
+
+/// Hand the model data to the renderer the way the presenter of the web app
+/// does, on the full display range
+fn update_renderer(renderer: &mut Renderer, model_data: &ModelUpdateResult, scene: DisplayScene, viewport: three_d::Viewport) {
+    let tonal_center_frequency = midi_note_to_frequency(model_data.tonal_center_note, model_data.concert_pitch_hz);
+    let frequency = match model_data.pitch {
+        Pitch::Detected(frequency) => Some(frequency),
+        Pitch::NotDetected => None,
+    };
+    let interval = frequency.map_or(0.0, |frequency| {
+        let octaves = (frequency / tonal_center_frequency).log2();
+        match model_data.interval_mode {
+            IntervalMode::Absolute => octaves,
+            IntervalMode::Wrapped => wrap_octaves(octaves, DEFAULT_DISPLAY_RANGE.center_octaves()),
+        }
+    });
+
+    renderer.update_presentation_context(&PresentationContext {
+        tonal_center_note: model_data.tonal_center_note,
+        concert_pitch_hz: model_data.concert_pitch_hz,
+        tuning_system: model_data.tuning_system.clone(),
+        current_scale: model_data.scale,
+        interval_axis: IntervalAxis::of_range(&DEFAULT_DISPLAY_RANGE),
+        transposition: model_data.transposition,
+        note_naming: model_data.note_naming,
+        language: current_language(),
+    }, viewport);
+    renderer.update_audio_analysis(AudioAnalysis {
+        pitch_detected: frequency.is_some(),
+        cents_offset: model_data.cents_offset,
+        tolerance_cents: model_data.tolerance_profile.cents(),
+        interval,
+        volume_peak: model_data.is_peaking,
+        frequency: frequency.unwrap_or(0.0),
+        tonal_center_frequency,
+    });
+    renderer.set_scene(scene);
+    if scene == DisplayScene::PitchGraph {
+        renderer.update_pitch_graph(&model_data.pitch_trail, None, now_ms(), viewport);
+    }
+}
@@ -0,0 +1,216 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgKind {
+    Int,
+    Float,
+    /// One of a fixed set of values, matched case-insensitively; when optional,
+    /// a word it does not match is left to the next argument
+    Choice(Vec<String>),
+    /// Boolean `--name` switch
+    Flag,
+    Text,
+    /// All remaining words, flags included, joined by spaces
+    Rest,
+}
+
+/// Declaration of one command argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+}
+
+impl ArgSpec {
+    pub fn int(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, description, ArgKind::Int)
+    }
+
+    pub fn float(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, description, ArgKind::Float)
+    }
+
+    pub fn choice(name: &'static str, description: &'static str, choices: &[&str]) -> Self {
+        Self::new(name, description, ArgKind::Choice(choices.iter().map(|c| c.to_string()).collect()))
+    }
+
+    pub fn text(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, description, ArgKind::Text)
+    }
+
+    /// Trailing words are always optional
+    pub fn rest(name: &'static str, description: &'static str) -> Self {
+        Self { required: false, ..Self::new(name, description, ArgKind::Rest) }
+    }
+
+    /// Flags are always optional
+    pub fn flag(name: &'static str, description: &'static str) -> Self {
+        Self { required: false, ..Self::new(name, description, ArgKind::Flag) }
+    }
+
+    pub fn optional(self) -> Self {
+        Self { required: false, ..self }
+    }
+
+    fn new(name: &'static str, description: &'static str, kind: ArgKind) -> Self {
+        Self { name, description, kind, required: true }
+    }
+
+    fn usage(&self) -> String {
+        match (&self.kind, self.required) {
+            (ArgKind::Flag, _) => format!("[--{}]", self.name),
+            (ArgKind::Rest, _) => format!("[{}...]", self.name),
+            (_, true) => format!("<{}>", self.name),
+            (_, false) => format!("[{}]", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Flag,
+}
+
+/// Arguments parsed and validated against a command's `ArgSpec` list,
+/// along with the words they were parsed from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedArgs {
+    values: HashMap<&'static str, ArgValue>,
+    words: Vec<String>,
+}
+
+impl ParsedArgs {
+    /// Arguments of a command that declares none, kept only as words
+    pub fn words_only(args: &[&str]) -> Self {
+        Self { values: HashMap::new(), words: args.iter().map(|arg| arg.to_string()).collect() }
+    }
+
+    /// Match `args` against `specs`: `--name` tokens set flags, the rest fill
+    /// the non-flag specs in order
+    pub fn parse(specs: &[ArgSpec], args: &[&str]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        let mut positional = specs.iter().filter(|spec| spec.kind != ArgKind::Flag).peekable();
+
+        for (index, &arg) in args.iter().enumerate() {
+            if !arg.starts_with("--") {
+                positional.next_if(|spec| matches!(
+                    &spec.kind,
+                    ArgKind::Choice(choices) if !spec.required && !choices.contains(&arg.to_lowercase())
+                ));
+            }
+            if let Some(spec) = positional.next_if(|spec| spec.kind == ArgKind::Rest) {
+                values.insert(spec.name, ArgValue::Text(args[index..].join(" ")));
+                break;
+            }
+
+            if let Some(flag_name) = arg.strip_prefix("--") {
+                let Some(spec) = specs.iter().find(|spec| spec.kind == ArgKind::Flag && spec.name == flag_name) else {
+                    return Err(format!("Unknown flag: {}", arg));
+                };
+                values.insert(spec.name, ArgValue::Flag);
+                continue;
+            }
+
+            let Some(spec) = positional.next() else {
+                return Err(format!("Unexpected argument: {}", arg));
+            };
+            values.insert(spec.name, parse_value(spec, arg)?);
+        }
+
+        if let Some(missing) = positional.find(|spec| spec.required) {
+            return Err(format!("Missing argument: <{}>", missing.name));
+        }
+
+        Ok(Self { values, words: args.iter().map(|arg| arg.to_string()).collect() })
+    }
+
+    /// The arguments as typed, for commands that parse their own
+    pub fn words(&self) -> Vec<&str> {
+        self.words.iter().map(String::as_str).collect()
+    }
+
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.values.get(name) {
+            Some(ArgValue::Int(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f64> {
+        match self.values.get(name) {
+            Some(ArgValue::Float(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of a text, choice or rest argument; choices are returned lowercase
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.values.get(name) {
+            Some(ArgValue::Text(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.values.get(name) == Some(&ArgValue::Flag)
+    }
+}
+
+fn parse_value(spec: &ArgSpec, arg: &str) -> Result<ArgValue, String> {
+    match &spec.kind {
+        ArgKind::Int => arg.parse()
+            .map(ArgValue::Int)
+            .map_err(|_| format!("<{}> must be an integer, got '{}'", spec.name, arg)),
+        ArgKind::Float => arg.parse()
+            .map(ArgValue::Float)
+            .map_err(|_| format!("<{}> must be a number, got '{}'", spec.name, arg)),
+        ArgKind::Choice(choices) => {
+            let value = arg.to_lowercase();
+            if choices.contains(&value) {
+                Ok(ArgValue::Text(value))
+            } else {
+                Err(format!("<{}> must be one of {}, got '{}'", spec.name, choices.join("|"), arg))
+            }
+        }
+        ArgKind::Text => Ok(ArgValue::Text(arg.to_string())),
+        ArgKind::Flag => unreachable!("flags are not positional"),
+        ArgKind::Rest => unreachable!("trailing words are taken whole"),
+    }
+}
+
+/// One-line usage such as `theme [name]`
+pub fn usage_line(command_name: &str, specs: &[ArgSpec]) -> String {
+    std::iter::once(command_name.to_string())
+        .chain(specs.iter().map(ArgSpec::usage))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Usage line followed by one line per argument
+pub fn help_text(command_name: &str, description: &str, specs: &[ArgSpec]) -> String {
+    let mut lines = vec![
+        description.to_string(),
+        format!("Usage: {}", usage_line(command_name, specs)),
+    ];
+
+    for spec in specs {
+        let kind = match &spec.kind {
+            ArgKind::Int => "integer".to_string(),
+            ArgKind::Float => "number".to_string(),
+            ArgKind::Choice(choices) => choices.join("|"),
+            ArgKind::Flag => "flag".to_string(),
+            ArgKind::Text => "text".to_string(),
+            ArgKind::Rest => "words".to_string(),
+        };
+        lines.push(format!("  {:<16} {} ({})", spec.usage(), spec.description, kind));
+    }
+
+    lines.join("\n")
+}
@@ -1,16 +1,57 @@
 #![cfg(target_arch = "wasm32")]
 
 use crate::output::ConsoleOutput;
+use crate::args::{ArgKind, ArgSpec, ParsedArgs};
+use crate::stream::ConsoleOutputReceiver;
 
 #[derive(Debug)]
 pub enum ConsoleCommandResult {
     Output(ConsoleOutput),
     ClearAndOutput(ConsoleOutput),
     MultipleOutputs(Vec<ConsoleOutput>),
+    /// Output that arrives over time from an async task, see `ConsoleCommandResult::spawn`
+    Stream(ConsoleOutputReceiver),
+}
+
+/// What a command may do, ordered from least to most powerful; `help` tags
+/// the commands above `Safe`, and release builds leave out `Privileged` ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommandCategory {
+    /// Changes only how the console or UI looks
+    Safe,
+    /// Reads application state or tunes its analysis
+    Diagnostic,
+    /// Injects test signals or simulated failures
+    Privileged,
 }
 
 pub trait ConsoleCommand: Send + Sync {
     fn name(&self) -> &str;
     fn description(&self) -> &str;
-    fn execute(&self, args: Vec<&str>, registry: &crate::command_registry::ConsoleCommandRegistry) -> ConsoleCommandResult;
+    /// Run the command with arguments already validated against `arguments`
+    fn execute(&self, args: &ParsedArgs, registry: &crate::command_registry::ConsoleCommandRegistry) -> ConsoleCommandResult;
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Safe
+    }
+
+    /// Declared arguments; when non-empty the registry validates input against
+    /// them before calling `execute` and `help <command>` lists them
+    fn arguments(&self) -> Vec<ArgSpec> {
+        Vec::new()
+    }
+
+    /// Values offered by tab completion for the argument at `arg_index`,
+    /// taken from declared choice arguments by default
+    fn argument_candidates(&self, arg_index: usize) -> Vec<String> {
+        declared_choices(&self.arguments(), arg_index)
+    }
+}
+
+/// Choices of the positional argument at `arg_index` in `specs`, if it is a choice
+pub(crate) fn declared_choices(specs: &[ArgSpec], arg_index: usize) -> Vec<String> {
+    match specs.iter().filter(|spec| spec.kind != ArgKind::Flag).nth(arg_index).map(|spec| &spec.kind) {
+        Some(ArgKind::Choice(choices)) => choices.clone(),
+        _ => Vec::new(),
+    }
 }
\ No newline at end of file
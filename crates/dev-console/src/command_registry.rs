@@ -1,17 +1,20 @@
 #![cfg(target_arch = "wasm32")]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use crate::output::ConsoleOutput;
-use crate::command::{ConsoleCommand, ConsoleCommandResult};
+use crate::command::{CommandCategory, ConsoleCommand, ConsoleCommandResult};
+use crate::args::{self, ArgSpec, ParsedArgs};
 
 pub struct ConsoleCommandRegistry {
     commands: HashMap<String, Box<dyn ConsoleCommand>>,
+    watches: BTreeMap<String, Box<dyn Fn() -> String>>,
 }
 
 impl Default for ConsoleCommandRegistry {
     fn default() -> Self {
         let mut registry = Self {
             commands: HashMap::new(),
+            watches: BTreeMap::new(),
         };
         
         registry.register(Box::new(HelpCommand));
@@ -23,7 +26,12 @@ impl Default for ConsoleCommandRegistry {
 }
 
 impl ConsoleCommandRegistry {
+    /// Add a command; in release builds `Privileged` commands are ignored,
+    /// so a console that ships cannot inject test data
     pub fn register(&mut self, command: Box<dyn ConsoleCommand>) {
+        if cfg!(not(debug_assertions)) && command.category() == CommandCategory::Privileged {
+            return;
+        }
         self.commands.insert(command.name().to_string(), command);
     }
     
@@ -33,15 +41,75 @@ impl ConsoleCommandRegistry {
             return ConsoleCommandResult::Output(ConsoleOutput::error("Empty command"));
         };
         
-        match self.commands.get(command_name) {
-            Some(command) => command.execute(args.to_vec(), self),
-            None => ConsoleCommandResult::Output(ConsoleOutput::error(format!("Unknown command: {}", command_name))),
-        }
+        let Some(command) = self.commands.get(command_name) else {
+            return ConsoleCommandResult::Output(ConsoleOutput::error(format!("Unknown command: {}", command_name)));
+        };
+
+        let specs = command.arguments();
+        let parsed = if specs.is_empty() {
+            ParsedArgs::words_only(args)
+        } else {
+            match ParsedArgs::parse(&specs, args) {
+                Ok(parsed) => parsed,
+                Err(e) => return ConsoleCommandResult::MultipleOutputs(vec![
+                    ConsoleOutput::error(e),
+                    ConsoleOutput::info(format!("Usage: {}", args::usage_line(command_name, &specs))),
+                ]),
+            }
+        };
+
+        command.execute(&parsed, self)
     }
     
+    /// Complete the last word of `input`
+    ///
+    /// The first word is completed against registered command names, later words
+    /// against the command's argument candidates. Returns whole input lines.
+    pub fn complete(&self, input: &str) -> Vec<String> {
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        let (completed, partial) = match parts.split_last() {
+            Some((&last, rest)) if !input.ends_with(char::is_whitespace) => (rest, last),
+            _ => (parts.as_slice(), ""),
+        };
+
+        let mut candidates: Vec<String> = match completed.split_first() {
+            None => self.commands.keys()
+                .filter(|name| name.starts_with(partial))
+                .cloned()
+                .collect(),
+            Some((&command_name, args)) => {
+                let Some(command) = self.commands.get(command_name) else {
+                    return Vec::new();
+                };
+                let prefix = completed.join(" ");
+                command.argument_candidates(args.len())
+                    .into_iter()
+                    .filter(|candidate| candidate.starts_with(partial))
+                    .map(|candidate| format!("{} {}", prefix, candidate))
+                    .collect()
+            }
+        };
+
+        candidates.sort();
+        candidates
+    }
+
     pub fn get_commands(&self) -> Vec<&dyn ConsoleCommand> {
         self.commands.values().map(|cmd| cmd.as_ref()).collect()
     }
+
+    /// Register a named value that the `watch` command can display live
+    pub fn register_watch(&mut self, name: impl Into<String>, value: impl Fn() -> String + 'static) {
+        self.watches.insert(name.into(), Box::new(value));
+    }
+
+    pub fn watch_names(&self) -> Vec<&str> {
+        self.watches.keys().map(|name| name.as_str()).collect()
+    }
+
+    pub fn evaluate_watch(&self, name: &str) -> Option<String> {
+        self.watches.get(name).map(|value| value())
+    }
 }
 
 struct HelpCommand;
@@ -49,13 +117,31 @@ struct HelpCommand;
 impl ConsoleCommand for HelpCommand {
     fn name(&self) -> &str { "help" }
     fn description(&self) -> &str { "Display available commands and usage" }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::text("command", "Command to describe").optional()]
+    }
     
-    fn execute(&self, _args: Vec<&str>, registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+    fn execute(&self, args: &ParsedArgs, registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        if let Some(command_name) = args.text("command") {
+            return match registry.commands.get(command_name) {
+                Some(command) => ConsoleCommandResult::Output(ConsoleOutput::info(
+                    args::help_text(command.name(), command.description(), &command.arguments())
+                )),
+                None => ConsoleCommandResult::Output(ConsoleOutput::error(format!("Unknown command: {}", command_name))),
+            };
+        }
+
         let mut commands = registry.get_commands();
         commands.sort_by(|a, b| a.name().cmp(b.name()));
         
         let mut help_lines = vec!["Available commands:".to_string()];
-        help_lines.extend(commands.iter().map(|cmd| format!("  {} - {}", cmd.name(), cmd.description())));
+        help_lines.extend(commands.iter().map(|cmd| match cmd.category() {
+            CommandCategory::Privileged => format!("  {} - {} [privileged]", cmd.name(), cmd.description()),
+            CommandCategory::Diagnostic => format!("  {} - {} [diagnostic]", cmd.name(), cmd.description()),
+            CommandCategory::Safe => format!("  {} - {}", cmd.name(), cmd.description()),
+        }));
+        help_lines.push("Type 'help <command>' for details".to_string());
         
         ConsoleCommandResult::Output(ConsoleOutput::info(help_lines.join("\n")))
     }
@@ -67,7 +153,7 @@ impl ConsoleCommand for ClearCommand {
     fn name(&self) -> &str { "clear" }
     fn description(&self) -> &str { "Clear console output" }
     
-    fn execute(&self, _args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
         ConsoleCommandResult::ClearAndOutput(ConsoleOutput::info("Console cleared"))
     }
 }
@@ -78,7 +164,7 @@ impl ConsoleCommand for TestCommand {
     fn name(&self) -> &str { "test" }
     fn description(&self) -> &str { "Show examples of all console output types" }
     
-    fn execute(&self, _args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
         ConsoleCommandResult::MultipleOutputs(vec![
             ConsoleOutput::info("Console Output Examples:"),
             ConsoleOutput::empty(),
@@ -1,34 +1,90 @@
 #![cfg(target_arch = "wasm32")]
 
+use std::sync::{Arc, Mutex};
+use crate::output::SpanStyle;
 use crate::{ConsoleCommandRegistry, ConsoleOutput, ConsoleCommandResult, ConsoleHistory, ConsoleOutputManager, ConsoleCommand};
+use crate::history::HistoryCommand;
+use crate::log_capture::LogPanel;
+use crate::script::{ScriptCommand, ScriptStore};
+use crate::watch::WatchCommand;
+use crate::stream::ConsoleOutputReceiver;
+use crate::settings::{ConsoleLayout, ConsoleSettings, ConsoleSettingsCommand};
+use serde::{Serialize, de::DeserializeOwned};
 use web_sys::Storage;
 const CONSOLE_HISTORY_STORAGE_KEY: &str = "dev_console_history";
+const CONSOLE_SCRIPTS_STORAGE_KEY: &str = "dev_console_scripts";
+const CONSOLE_SETTINGS_STORAGE_KEY: &str = "dev_console_settings";
+const DOCKED_DEFAULT_HEIGHT: f32 = 300.0;
+const OUTPUT_PAGE_SIZE: usize = 200;
+const SEARCH_MATCH_COLOR: three_d::egui::Color32 = three_d::egui::Color32::from_rgb(70, 70, 20);
+const SEARCH_CURRENT_MATCH_COLOR: three_d::egui::Color32 = three_d::egui::Color32::from_rgb(130, 110, 20);
 
 pub struct DevConsole {
     command_registry: ConsoleCommandRegistry,
     output_manager: ConsoleOutputManager,
-    history: ConsoleHistory,
+    history: Arc<Mutex<ConsoleHistory>>,
+    scripts: Arc<Mutex<ScriptStore>>,
+    log_panel: LogPanel,
+    active_watches: Arc<Mutex<Vec<String>>>,
+    pending_streams: Vec<ConsoleOutputReceiver>,
+    settings: Arc<Mutex<ConsoleSettings>>,
+    output_search: String,
+    current_match: usize,
+    output_page: usize,
+    scroll_to_match: bool,
     input_text: String,
+    suggestions: Vec<String>,
+    selected_suggestion: usize,
     is_visible: bool
 }
 
 impl DevConsole {
-    pub fn new(registry: ConsoleCommandRegistry) -> Self {
+    pub fn new(mut registry: ConsoleCommandRegistry) -> Self {
         let mut output_manager = ConsoleOutputManager::default();
         output_manager.add_output(ConsoleOutput::info("Dev Console initialized"));
         output_manager.add_output(ConsoleOutput::info("Type 'help' for available commands"));
         
-        let command_history = Self::load_history_from_storage();
+        let settings: ConsoleSettings = Self::load_from_storage(CONSOLE_SETTINGS_STORAGE_KEY);
+        let mut command_history: ConsoleHistory = Self::load_from_storage(CONSOLE_HISTORY_STORAGE_KEY);
+        command_history.set_max_size(settings.history_size);
         if !command_history.is_empty() {
             output_manager.add_output(ConsoleOutput::info(format!("Restored {} commands from history", command_history.len())));
         }
+
+        let history = Arc::new(Mutex::new(command_history));
+        registry.register(Box::new(HistoryCommand { history: Arc::clone(&history) }));
+
+        let script_store: ScriptStore = Self::load_from_storage(CONSOLE_SCRIPTS_STORAGE_KEY);
+        if !script_store.is_empty() {
+            output_manager.add_output(ConsoleOutput::info(format!("Restored {} scripts", script_store.len())));
+        }
+        let scripts = Arc::new(Mutex::new(script_store));
+        registry.register(Box::new(ScriptCommand { scripts: Arc::clone(&scripts) }));
+
+        let active_watches = Arc::new(Mutex::new(Vec::new()));
+        registry.register(Box::new(WatchCommand { active: Arc::clone(&active_watches) }));
+
+        let settings = Arc::new(Mutex::new(settings));
+        registry.register(Box::new(ConsoleSettingsCommand { settings: Arc::clone(&settings), history: Arc::clone(&history) }));
         
         Self {
             command_registry: registry,
             output_manager,
-            history: command_history,
+            history,
+            scripts,
+            log_panel: LogPanel::default(),
+            active_watches,
+            pending_streams: Vec::new(),
+            settings,
+            output_search: String::new(),
+            current_match: 0,
+            output_page: 0,
+            scroll_to_match: false,
             input_text: String::new(),
-            is_visible: true
+            suggestions: Vec::new(),
+            selected_suggestion: 0,
+            // Users of a release build open the console with the toggle key
+            is_visible: cfg!(debug_assertions)
         }
     }
 
@@ -41,72 +97,296 @@ impl DevConsole {
     }
 
     pub fn render(&mut self, ctx: &three_d::egui::Context) {
+        self.receive_streamed_output();
+        self.handle_toggle_key(ctx);
+
         if !self.is_visible {
             return;
         }
 
-        let screen_rect = ctx.screen_rect();
-        three_d::egui::Window::new("Dev Console")
-            .default_pos([screen_rect.width() - 600.0, 0.0])
-            .default_size([600.0, screen_rect.height()])
-            .resizable(true)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    three_d::egui::ScrollArea::vertical()
-                        .max_height(600.0)
-                        .stick_to_bottom(true)
-                        .show(ui, |ui| {
-                            self.render_output(ui);
-                        });
-
-                    ui.separator();
-
-                    ui.horizontal(|ui| {
-                        ui.label(">");
-                        
-                        let response = ui.text_edit_singleline(&mut self.input_text);
-                        
-                        if response.lost_focus() && ui.input(|i| i.key_pressed(three_d::egui::Key::Enter)) {
-                            self.execute_command();
-                            response.request_focus();
+        let settings = self.settings.lock().unwrap().clone();
+        let style = ctx.style();
+
+        match settings.layout {
+            ConsoleLayout::Floating => {
+                let screen_rect = ctx.screen_rect();
+                three_d::egui::Window::new("Dev Console")
+                    .default_pos([screen_rect.width() - 600.0, 0.0])
+                    .default_size([600.0, screen_rect.height()])
+                    .resizable(true)
+                    .frame(three_d::egui::Frame::window(&style).multiply_with_opacity(settings.opacity))
+                    .show(ctx, |ui| self.render_contents(ui, &settings));
+            }
+            ConsoleLayout::DockedBottom => {
+                three_d::egui::TopBottomPanel::bottom("dev_console_panel")
+                    .resizable(true)
+                    .default_height(DOCKED_DEFAULT_HEIGHT)
+                    .frame(three_d::egui::Frame::side_top_panel(&style).multiply_with_opacity(settings.opacity))
+                    .show(ctx, |ui| self.render_contents(ui, &settings));
+            }
+        }
+    }
+
+    /// Toggle visibility when the configured hotkey is pressed
+    fn handle_toggle_key(&mut self, ctx: &three_d::egui::Context) {
+        let Some(key) = self.settings.lock().unwrap().toggle_key() else {
+            return;
+        };
+        if ctx.input_mut(|i| i.consume_key(three_d::egui::Modifiers::NONE, key)) {
+            self.toggle_visibility();
+        }
+    }
+
+    fn render_contents(&mut self, ui: &mut three_d::egui::Ui, settings: &ConsoleSettings) {
+        for font_id in ui.style_mut().text_styles.values_mut() {
+            font_id.size = settings.font_size;
+        }
+
+        ui.vertical(|ui| {
+            three_d::egui::CollapsingHeader::new("Logs")
+                .default_open(false)
+                .show(ui, |ui| {
+                    self.log_panel.render(ui);
+                });
+
+            self.render_watches(ui);
+
+            self.render_output_toolbar(ui);
+
+            three_d::egui::ScrollArea::vertical()
+                .max_height(600.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    self.render_output(ui);
+                });
+
+            if !self.pending_streams.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("{} command(s) running", self.pending_streams.len()));
+                });
+            }
+
+            if self.scripts.lock().unwrap().is_recording() {
+                ui.colored_label(three_d::egui::Color32::LIGHT_RED, "Recording script, 'script end' to save");
+            }
+
+            ui.separator();
+
+            let input_id = ui.horizontal(|ui| {
+                ui.label(">");
+            
+                let response = ui.add(three_d::egui::TextEdit::singleline(&mut self.input_text).lock_focus(true));
+
+                if response.changed() {
+                    if self.input_text.trim().is_empty() {
+                        self.suggestions.clear();
+                    } else {
+                        self.update_suggestions();
+                    }
+                }
+            
+                if response.lost_focus() && ui.input(|i| i.key_pressed(three_d::egui::Key::Enter)) {
+                    self.execute_command();
+                    response.request_focus();
+                }
+
+                if ui.input(|i| i.key_pressed(three_d::egui::Key::Escape)) {
+                    self.suggestions.clear();
+                }
+
+                if response.has_focus() {
+                    if ui.input(|i| i.key_pressed(three_d::egui::Key::Tab)) {
+                        if self.suggestions.is_empty() {
+                            self.update_suggestions();
+                            if self.suggestions.len() == 1 {
+                                self.apply_suggestion(ui.ctx(), response.id);
+                            }
+                        } else {
+                            self.apply_suggestion(ui.ctx(), response.id);
                         }
+                    }
 
-                        if response.has_focus() {
-                            if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowUp)) {
-                                if let Some(cmd) = self.history.navigate_previous() {
-                                    self.input_text = cmd.to_string();
-                                }
+                    if !self.suggestions.is_empty() {
+                        let count = self.suggestions.len();
+                        if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowUp)) {
+                            self.selected_suggestion = (self.selected_suggestion + count - 1) % count;
+                        }
+                        if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowDown)) {
+                            self.selected_suggestion = (self.selected_suggestion + 1) % count;
+                        }
+                    } else {
+                        if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowUp)) {
+                            if let Some(cmd) = self.history.lock().unwrap().navigate_previous() {
+                                self.input_text = cmd.to_string();
                             }
-                            if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowDown)) {
-                                if let Some(cmd) = self.history.navigate_next() {
-                                    self.input_text = cmd.to_string();
-                                }
+                        }
+                        if ui.input(|i| i.key_pressed(three_d::egui::Key::ArrowDown)) {
+                            if let Some(cmd) = self.history.lock().unwrap().navigate_next() {
+                                self.input_text = cmd.to_string();
                             }
                         }
-                    });
-                });
-            });
+                    }
+                }
+
+                response.id
+            }).inner;
+
+            self.render_suggestions(ui, input_id);
+        });
     }
 
+    fn render_suggestions(&mut self, ui: &mut three_d::egui::Ui, input_id: three_d::egui::Id) {
+        if self.suggestions.is_empty() {
+            return;
+        }
+
+        let mut clicked = None;
+        three_d::egui::Frame::popup(ui.style()).show(ui, |ui| {
+            for (index, suggestion) in self.suggestions.iter().enumerate() {
+                if ui.selectable_label(index == self.selected_suggestion, suggestion).clicked() {
+                    clicked = Some(index);
+                }
+            }
+        });
 
-    fn render_output(&self, ui: &mut three_d::egui::Ui) {
-        for output in self.output_manager.entries().iter().rev() {
-            
+        if let Some(index) = clicked {
+            self.selected_suggestion = index;
+            self.apply_suggestion(ui.ctx(), input_id);
+            ui.ctx().memory_mut(|memory| memory.request_focus(input_id));
+        }
+    }
+
+    fn update_suggestions(&mut self) {
+        self.selected_suggestion = 0;
+        self.suggestions = self.command_registry.complete(&self.input_text);
+    }
+
+    /// Replace the input with the selected suggestion and move the cursor to the end
+    fn apply_suggestion(&mut self, ctx: &three_d::egui::Context, input_id: three_d::egui::Id) {
+        let Some(suggestion) = self.suggestions.get(self.selected_suggestion) else {
+            return;
+        };
+
+        self.input_text = format!("{} ", suggestion);
+        self.suggestions.clear();
+        self.selected_suggestion = 0;
+
+        if let Some(mut state) = three_d::egui::TextEdit::load_state(ctx, input_id) {
+            let end = three_d::egui::text::CCursor::new(self.input_text.chars().count());
+            state.cursor.set_char_range(Some(three_d::egui::text::CCursorRange::one(end)));
+            three_d::egui::TextEdit::store_state(ctx, input_id, state);
+        }
+    }
+
+
+    fn render_output_toolbar(&mut self, ui: &mut three_d::egui::Ui) {
+        let matches = self.search_matches();
+        let entry_count = self.output_manager.entries().len();
+        let page_count = entry_count.div_ceil(OUTPUT_PAGE_SIZE).max(1);
+        self.output_page = self.output_page.min(page_count - 1);
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            if ui.text_edit_singleline(&mut self.output_search).changed() {
+                self.current_match = 0;
+                self.scroll_to_match = true;
+            }
+
+            if !self.output_search.is_empty() {
+                if matches.is_empty() {
+                    ui.label("no matches");
+                } else {
+                    self.current_match = self.current_match.min(matches.len() - 1);
+                    ui.label(format!("{}/{}", self.current_match + 1, matches.len()));
+                    if ui.small_button("▲").clicked() {
+                        self.current_match = (self.current_match + matches.len() - 1) % matches.len();
+                        self.scroll_to_match = true;
+                    }
+                    if ui.small_button("▼").clicked() {
+                        self.current_match = (self.current_match + 1) % matches.len();
+                        self.scroll_to_match = true;
+                    }
+                }
+            }
+
+            if page_count > 1 {
+                ui.separator();
+                if ui.add_enabled(self.output_page + 1 < page_count, three_d::egui::Button::new("Older")).clicked() {
+                    self.output_page += 1;
+                }
+                ui.label(format!("Page {}/{}", page_count - self.output_page, page_count));
+                if ui.add_enabled(self.output_page > 0, three_d::egui::Button::new("Newer")).clicked() {
+                    self.output_page -= 1;
+                }
+            }
+        });
+
+        // Show the page holding the current match
+        if self.scroll_to_match && let Some(&entry) = matches.get(self.current_match) {
+            self.output_page = (entry_count - 1 - entry) / OUTPUT_PAGE_SIZE;
+        }
+    }
+
+    /// Indices, oldest first, of output entries containing the search text
+    fn search_matches(&self) -> Vec<usize> {
+        if self.output_search.is_empty() {
+            return Vec::new();
+        }
+
+        let needle = self.output_search.to_lowercase();
+        self.output_manager.entries()
+            .iter()
+            .rev()
+            .enumerate()
+            .filter(|(_, output)| output.message().to_lowercase().contains(&needle))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn render_output(&mut self, ui: &mut three_d::egui::Ui) {
+        let entries = self.output_manager.entries();
+        let end = entries.len().saturating_sub(self.output_page * OUTPUT_PAGE_SIZE);
+        let start = end.saturating_sub(OUTPUT_PAGE_SIZE);
+
+        let matches = self.search_matches();
+        let current_match = matches.get(self.current_match).copied();
+
+        for (index, output) in entries.iter().rev().enumerate().take(end).skip(start) {
             let color = match output {
                 ConsoleOutput::Info(_) => three_d::egui::Color32::WHITE,
                 ConsoleOutput::Success(_) => three_d::egui::Color32::GREEN,
                 ConsoleOutput::Warning(_) => three_d::egui::Color32::YELLOW,
                 ConsoleOutput::Error(_) => three_d::egui::Color32::RED,
                 ConsoleOutput::Echo(_) => three_d::egui::Color32::LIGHT_BLUE,
+                ConsoleOutput::Styled { .. } => three_d::egui::Color32::WHITE,
                 ConsoleOutput::Empty => three_d::egui::Color32::WHITE,
             };
 
-            if !output.message().is_empty() {
-                ui.colored_label(color, output.message());
+            let background = if Some(index) == current_match {
+                SEARCH_CURRENT_MATCH_COLOR
+            } else if matches.binary_search(&index).is_ok() {
+                SEARCH_MATCH_COLOR
             } else {
-                ui.label("");
+                three_d::egui::Color32::TRANSPARENT
+            };
+
+            let response = match output {
+                ConsoleOutput::Styled { text, spans } => {
+                    ui.label(styled_layout_job(ui, text, spans, color, background))
+                }
+                _ if !output.message().is_empty() => {
+                    ui.label(three_d::egui::RichText::new(output.message()).color(color).background_color(background))
+                }
+                _ => ui.label(""),
+            };
+
+            if self.scroll_to_match && Some(index) == current_match {
+                response.scroll_to_me(Some(three_d::egui::Align::Center));
             }
         }
+
+        self.scroll_to_match = false;
     }
 
     fn execute_command(&mut self) {
@@ -115,10 +395,13 @@ impl DevConsole {
             return;
         }
 
-        self.history.add_command(command.clone());
-        self.save_history_to_storage();
+        self.history.lock().unwrap().add_command(command.clone());
         self.output_manager.add_output(ConsoleOutput::echo(&command));
 
+        if command.split_whitespace().next() != Some("script") {
+            self.scripts.lock().unwrap().record_line(&command);
+        }
+
         let result = self.command_registry.execute(&command);
         match result {
             ConsoleCommandResult::Output(output) => self.output_manager.add_output(output),
@@ -131,26 +414,75 @@ impl DevConsole {
                 self.output_manager.clear();
                 self.output_manager.add_output(output);
             }
+            ConsoleCommandResult::Stream(receiver) => self.pending_streams.push(receiver),
         }
 
         self.input_text.clear();
-        self.history.reset_navigation();
+        self.suggestions.clear();
+        self.history.lock().unwrap().reset_navigation();
+        self.save_history_to_storage();
+        Self::save_to_storage(CONSOLE_SCRIPTS_STORAGE_KEY, &*self.scripts.lock().unwrap());
+        Self::save_to_storage(CONSOLE_SETTINGS_STORAGE_KEY, &*self.settings.lock().unwrap());
+    }
+
+    fn receive_streamed_output(&mut self) {
+        for receiver in &self.pending_streams {
+            for output in receiver.drain() {
+                self.output_manager.add_output(output);
+            }
+        }
+        let running = self.pending_streams.len();
+        self.pending_streams.retain(|receiver| !receiver.is_finished());
+        // Streamed commands such as 'script load' may have changed the scripts
+        if self.pending_streams.len() < running {
+            Self::save_to_storage(CONSOLE_SCRIPTS_STORAGE_KEY, &*self.scripts.lock().unwrap());
+        }
     }
 
     pub fn register_command(&mut self, command: Box<dyn ConsoleCommand>) {
         self.command_registry.register(command);
     }
 
-    fn load_history_from_storage() -> ConsoleHistory {
+    pub fn register_watch(&mut self, name: impl Into<String>, value: impl Fn() -> String + 'static) {
+        self.command_registry.register_watch(name, value);
+    }
+
+    fn render_watches(&self, ui: &mut three_d::egui::Ui) {
+        let active = self.active_watches.lock().unwrap();
+        if active.is_empty() {
+            return;
+        }
+
+        three_d::egui::CollapsingHeader::new("Watches")
+            .default_open(true)
+            .show(ui, |ui| {
+                three_d::egui::Grid::new("dev_console_watches")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for name in active.iter() {
+                            ui.label(name);
+                            ui.monospace(self.command_registry.evaluate_watch(name).unwrap_or_default());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn save_history_to_storage(&self) {
+        Self::save_to_storage(CONSOLE_HISTORY_STORAGE_KEY, &*self.history.lock().unwrap());
+    }
+
+    fn load_from_storage<T: DeserializeOwned + Default>(key: &str) -> T {
         Self::get_local_storage()
-            .and_then(|storage| storage.get_item(CONSOLE_HISTORY_STORAGE_KEY).ok()?)
-            .and_then(|history_json| serde_json::from_str(&history_json).ok())
+            .and_then(|storage| storage.get_item(key).ok()?)
+            .and_then(|json| serde_json::from_str(&json).ok())
             .unwrap_or_default()
     }
 
-    fn save_history_to_storage(&self) {
-        if let (Some(storage), Ok(history_json)) = (Self::get_local_storage(), serde_json::to_string(&self.history)) {
-            let _ = storage.set_item(CONSOLE_HISTORY_STORAGE_KEY, &history_json);
+    fn save_to_storage<T: Serialize>(key: &str, value: &T) {
+        if let (Some(storage), Ok(json)) = (Self::get_local_storage(), serde_json::to_string(value)) {
+            let _ = storage.set_item(key, &json);
         }
     }
 
@@ -159,3 +491,38 @@ impl DevConsole {
     }
 }
 
+fn styled_layout_job(
+    ui: &three_d::egui::Ui,
+    text: &str,
+    spans: &[(std::ops::Range<usize>, SpanStyle)],
+    default_color: three_d::egui::Color32,
+    background: three_d::egui::Color32,
+) -> three_d::egui::text::LayoutJob {
+    let font_id = three_d::egui::TextStyle::Body.resolve(ui.style());
+    let format = |style: SpanStyle| three_d::egui::TextFormat {
+        font_id: font_id.clone(),
+        color: style.color.map_or(default_color, |[r, g, b]| three_d::egui::Color32::from_rgb(r, g, b)),
+        background,
+        // egui has no bold variant of the default font, so bold text is underlined
+        underline: if style.bold {
+            three_d::egui::Stroke::new(1.0, default_color)
+        } else {
+            three_d::egui::Stroke::NONE
+        },
+        ..Default::default()
+    };
+
+    let mut job = three_d::egui::text::LayoutJob::default();
+    let mut position = 0;
+    for (range, style) in spans {
+        if range.start > position {
+            job.append(&text[position..range.start], 0.0, format(SpanStyle::default()));
+        }
+        job.append(&text[range.clone()], 0.0, format(*style));
+        position = range.end;
+    }
+    if position < text.len() {
+        job.append(&text[position..], 0.0, format(SpanStyle::default()));
+    }
+    job
+}
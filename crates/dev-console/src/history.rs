@@ -1,13 +1,31 @@
 #![cfg(target_arch = "wasm32")]
 
+use std::sync::{Arc, Mutex};
 use serde::{Serialize, Deserialize};
+use crate::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandResult, ConsoleCommandRegistry, ConsoleOutput, ParsedArgs};
 
-const MAX_HISTORY_SIZE: usize = 100;
+pub const DEFAULT_MAX_HISTORY_SIZE: usize = 100;
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+fn default_max_size() -> usize {
+    DEFAULT_MAX_HISTORY_SIZE
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsoleHistory {
     commands: Vec<String>,
     current_position: Option<usize>,
+    #[serde(skip, default = "default_max_size")]
+    max_size: usize,
+}
+
+impl Default for ConsoleHistory {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            current_position: None,
+            max_size: DEFAULT_MAX_HISTORY_SIZE,
+        }
+    }
 }
 
 impl ConsoleHistory {
@@ -17,12 +35,19 @@ impl ConsoleHistory {
         }
 
         self.commands.insert(0, command);
-        if self.commands.len() > MAX_HISTORY_SIZE {
-            self.commands.truncate(MAX_HISTORY_SIZE);
+        if self.commands.len() > self.max_size {
+            self.commands.truncate(self.max_size);
         }
         self.current_position = None;
     }
 
+    /// Set the maximum number of stored commands, dropping the oldest ones if needed
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.commands.truncate(max_size);
+        self.current_position = None;
+    }
+
     pub fn navigate_previous(&mut self) -> Option<&str> {
         if self.commands.is_empty() {
             return None;
@@ -60,6 +85,19 @@ impl ConsoleHistory {
         self.current_position = None;
     }
 
+    /// Commands containing `term`, most recent first
+    pub fn search(&self, term: &str) -> Vec<&str> {
+        self.commands.iter()
+            .filter(|command| command.contains(term))
+            .map(|command| command.as_str())
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.current_position = None;
+    }
+
     pub fn len(&self) -> usize {
         self.commands.len()
     }
@@ -67,4 +105,45 @@ impl ConsoleHistory {
     pub fn is_empty(&self) -> bool {
         self.commands.is_empty()
     }
-}
\ No newline at end of file
+}
+
+/// Lists, searches and clears the console's command history
+pub(crate) struct HistoryCommand {
+    pub(crate) history: Arc<Mutex<ConsoleHistory>>,
+}
+
+impl ConsoleCommand for HistoryCommand {
+    fn name(&self) -> &str { "history" }
+    fn category(&self) -> CommandCategory { CommandCategory::Diagnostic }
+    fn description(&self) -> &str { "Show command history (history [search term] | history clear)" }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::choice("action", "Clear the history instead of listing it", &["clear"]).optional(),
+            ArgSpec::rest("term", "Text the listed commands contain"),
+        ]
+    }
+
+    fn execute(&self, args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let mut history = self.history.lock().unwrap();
+
+        if args.text("action") == Some("clear") {
+            history.clear();
+            return ConsoleCommandResult::Output(ConsoleOutput::success("History cleared"));
+        }
+
+        let term = args.text("term").unwrap_or_default();
+        let matches = history.search(term);
+        if matches.is_empty() {
+            return ConsoleCommandResult::Output(ConsoleOutput::info(format!("No history entries matching '{}'", term)));
+        }
+
+        let lines: Vec<String> = matches.iter()
+            .rev()
+            .enumerate()
+            .map(|(index, command)| format!("  {:>3}  {}", index + 1, command))
+            .collect();
+
+        ConsoleCommandResult::Output(ConsoleOutput::info(lines.join("\n")))
+    }
+}
@@ -1,12 +1,23 @@
 #![cfg(target_arch = "wasm32")]
 
+pub mod args;
 pub mod command;
 pub mod command_registry;
 pub mod console;
 pub mod history;
+pub mod log_capture;
 pub mod output;
-pub use command::{ConsoleCommand, ConsoleCommandResult};
+pub mod script;
+pub mod settings;
+pub mod stream;
+pub mod watch;
+pub use args::{ArgKind, ArgSpec, ArgValue, ParsedArgs};
+pub use command::{CommandCategory, ConsoleCommand, ConsoleCommandResult};
 pub use command_registry::ConsoleCommandRegistry;
-pub use output::{ConsoleOutput, ConsoleOutputManager};
+pub use output::{ConsoleOutput, ConsoleOutputManager, SpanStyle};
 pub use history::ConsoleHistory;
+pub use log_capture::{LogLevel, capture as capture_log};
+pub use script::ScriptStore;
+pub use settings::{ConsoleLayout, ConsoleSettings};
+pub use stream::{ConsoleOutputReceiver, ConsoleOutputSender, output_stream};
 pub use console::DevConsole;
@@ -0,0 +1,187 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use regex::Regex;
+
+const MAX_LOG_RECORDS: usize = 2000;
+
+static LOG_RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+static CAPTURE_PAUSED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    #[default]
+    Trace,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [LogLevel::Trace, LogLevel::Info, LogLevel::Warning, LogLevel::Error];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    fn color(&self) -> three_d::egui::Color32 {
+        match self {
+            LogLevel::Trace => three_d::egui::Color32::GRAY,
+            LogLevel::Info => three_d::egui::Color32::WHITE,
+            LogLevel::Warning => three_d::egui::Color32::YELLOW,
+            LogLevel::Error => three_d::egui::Color32::RED,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub module: String,
+    pub message: String,
+    /// Milliseconds since page load
+    pub timestamp_ms: f64,
+}
+
+impl LogRecord {
+    fn format(&self) -> String {
+        format!("{:>10.3}s {:<5} [{}] {}", self.timestamp_ms / 1000.0, self.level.label(), self.module, self.message)
+    }
+}
+
+/// Store a log message in the capture buffer, dropping the oldest one when full
+///
+/// `module` is usually `module_path!()`; the leading crate name is stripped.
+pub fn capture(level: LogLevel, module: &str, message: &str) {
+    if CAPTURE_PAUSED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let module = module.split_once("::").map_or(module, |(_, rest)| rest);
+    let timestamp_ms = web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now());
+
+    let Ok(mut records) = LOG_RECORDS.lock() else {
+        return;
+    };
+    if records.len() >= MAX_LOG_RECORDS {
+        records.pop_front();
+    }
+    records.push_back(LogRecord {
+        level,
+        module: module.to_string(),
+        message: message.to_string(),
+        timestamp_ms,
+    });
+}
+
+pub fn is_capture_paused() -> bool {
+    CAPTURE_PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_capture_paused(paused: bool) {
+    CAPTURE_PAUSED.store(paused, Ordering::Relaxed);
+}
+
+pub fn clear_captured_logs() {
+    if let Ok(mut records) = LOG_RECORDS.lock() {
+        records.clear();
+    }
+}
+
+/// Filterable view of the captured log records
+#[derive(Default)]
+pub struct LogPanel {
+    min_level: LogLevel,
+    filter_text: String,
+    filter: Option<Regex>,
+    filter_error: Option<String>,
+}
+
+impl LogPanel {
+    pub fn render(&mut self, ui: &mut three_d::egui::Ui) {
+        ui.horizontal(|ui| {
+            three_d::egui::ComboBox::from_id_salt("log_panel_level")
+                .selected_text(self.min_level.label())
+                .show_ui(ui, |ui| {
+                    for level in LogLevel::ALL {
+                        ui.selectable_value(&mut self.min_level, level, level.label());
+                    }
+                });
+
+            ui.label("Filter:");
+            if ui.text_edit_singleline(&mut self.filter_text).changed() {
+                self.update_filter();
+            }
+
+            let paused = is_capture_paused();
+            if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                set_capture_paused(!paused);
+            }
+            if ui.button("Copy").clicked() {
+                let text = self.filtered_lines().join("\n");
+                ui.ctx().copy_text(text);
+            }
+            if ui.button("Clear").clicked() {
+                clear_captured_logs();
+            }
+        });
+
+        if let Some(error) = &self.filter_error {
+            ui.colored_label(three_d::egui::Color32::RED, error);
+        }
+
+        three_d::egui::ScrollArea::vertical()
+            .id_salt("log_panel_records")
+            .max_height(250.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                let Ok(records) = LOG_RECORDS.lock() else {
+                    return;
+                };
+                for record in records.iter().filter(|record| self.matches(record)) {
+                    ui.colored_label(record.level.color(), record.format());
+                }
+            });
+    }
+
+    fn update_filter(&mut self) {
+        if self.filter_text.is_empty() {
+            self.filter = None;
+            self.filter_error = None;
+            return;
+        }
+
+        match Regex::new(&self.filter_text) {
+            Ok(regex) => {
+                self.filter = Some(regex);
+                self.filter_error = None;
+            }
+            Err(e) => self.filter_error = Some(format!("Invalid regex: {}", e)),
+        }
+    }
+
+    /// Level and regex filter; the regex is matched against the module tag and the message
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level >= self.min_level
+            && self.filter.as_ref().is_none_or(|filter| filter.is_match(&record.module) || filter.is_match(&record.message))
+    }
+
+    fn filtered_lines(&self) -> Vec<String> {
+        let Ok(records) = LOG_RECORDS.lock() else {
+            return Vec::new();
+        };
+        records.iter()
+            .filter(|record| self.matches(record))
+            .map(LogRecord::format)
+            .collect()
+    }
+}
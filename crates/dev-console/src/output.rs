@@ -1,6 +1,15 @@
 #![cfg(target_arch = "wasm32")]
 
 use std::fmt;
+use std::ops::Range;
+
+/// Style of a span within a styled output line
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpanStyle {
+    /// RGB color, or the default text color when `None`
+    pub color: Option<[u8; 3]>,
+    pub bold: bool,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConsoleOutput {
@@ -9,6 +18,8 @@ pub enum ConsoleOutput {
     Warning(String),
     Error(String),
     Echo(String),
+    /// Text with styled byte ranges, for commands that highlight parts of a line
+    Styled { text: String, spans: Vec<(Range<usize>, SpanStyle)> },
     Empty,
 }
 
@@ -44,6 +55,7 @@ impl ConsoleOutput {
             Self::Warning(_) => "warning",
             Self::Error(_) => "error",
             Self::Echo(_) => "command",
+            Self::Styled { .. } => "styled",
             Self::Empty => "empty",
         }
     }
@@ -52,6 +64,7 @@ impl ConsoleOutput {
         match self {
             Self::Info(msg) | Self::Success(msg) | Self::Warning(msg) 
             | Self::Error(msg) | Self::Echo(msg) => msg,
+            Self::Styled { text, .. } => text,
             Self::Empty => "",
         }
     }
@@ -65,6 +78,7 @@ impl fmt::Display for ConsoleOutput {
             Self::Warning(msg) => write!(f, "[WARNING] {}", msg),
             Self::Error(msg) => write!(f, "[ERROR] {}", msg),
             Self::Echo(msg) => write!(f, "> {}", msg),
+            Self::Styled { text, .. } => write!(f, "{}", text),
             Self::Empty => write!(f, ""),
         }
     }
@@ -0,0 +1,356 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use wasm_bindgen::JsCast;
+use crate::command::declared_choices;
+use crate::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandResult, ConsoleCommandRegistry, ConsoleOutput, ParsedArgs};
+
+/// Named command scripts and the variables they share
+///
+/// Script lines are regular console commands with two additions:
+/// - `set <name> <value>` assigns a variable
+/// - `if <a> == <b> then <command>` (or `!=`) runs a command conditionally
+///
+/// `$name` expands to a variable and `$1`, `$2`, ... to the arguments given to `script run`;
+/// a script stops at a line naming a variable that is not set.
+///
+/// Scripts can also be loaded from `.rc` text files, one command per line,
+/// with blank lines and lines starting with `#` skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptStore {
+    scripts: BTreeMap<String, Vec<String>>,
+    variables: BTreeMap<String, String>,
+    #[serde(skip)]
+    recording: Option<(String, Vec<String>)>,
+}
+
+impl ScriptStore {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Append a command to the script being recorded
+    pub fn record_line(&mut self, line: &str) {
+        if let Some((_, lines)) = &mut self.recording {
+            lines.push(line.to_string());
+        }
+    }
+
+    /// Save `.rc` text as the script `name`, returning its number of commands
+    pub fn load_rc(&mut self, name: &str, text: &str) -> usize {
+        let lines: Vec<String> = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        let count = lines.len();
+        self.scripts.insert(name.to_string(), lines);
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+}
+
+const SUBCOMMANDS: [&str; 10] = ["record", "end", "define", "load", "run", "list", "show", "delete", "set", "help"];
+
+/// Records, defines and runs command scripts
+pub(crate) struct ScriptCommand {
+    pub(crate) scripts: Arc<Mutex<ScriptStore>>,
+}
+
+impl ConsoleCommand for ScriptCommand {
+    fn name(&self) -> &str { "script" }
+    fn category(&self) -> CommandCategory { CommandCategory::Diagnostic }
+    fn description(&self) -> &str { "Record and run command scripts (script help for usage)" }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::choice("subcommand", "What to do, see script help", &SUBCOMMANDS),
+            ArgSpec::text("name", "Script or variable name").optional(),
+            ArgSpec::rest("arguments", "Commands, URL, script arguments or value"),
+        ]
+    }
+
+    fn execute(&self, args: &ParsedArgs, registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let name = args.text("name");
+        let rest: Vec<&str> = args.text("arguments").unwrap_or_default().split_whitespace().collect();
+
+        match args.text("subcommand").unwrap_or_default() {
+            "record" => self.record(name),
+            "end" => self.end(),
+            "define" => self.define(name, &rest),
+            "load" => self.load(name, &rest),
+            "run" => self.run(name, &rest, registry),
+            "list" => self.list(),
+            "show" => self.show(name),
+            "delete" => self.delete(name),
+            "set" => self.set(name, &rest),
+            _ => usage(),
+        }
+    }
+
+    fn argument_candidates(&self, arg_index: usize) -> Vec<String> {
+        match arg_index {
+            1 => self.scripts.lock().unwrap().scripts.keys().cloned().collect(),
+            _ => declared_choices(&self.arguments(), arg_index),
+        }
+    }
+}
+
+impl ScriptCommand {
+    fn record(&self, name: Option<&str>) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script record <name>");
+        };
+
+        let mut store = self.scripts.lock().unwrap();
+        if let Some((current, _)) = &store.recording {
+            return error(format!("Already recording '{}'. Finish it with 'script end'", current));
+        }
+
+        store.recording = Some((name.to_string(), Vec::new()));
+        ConsoleCommandResult::Output(ConsoleOutput::info(format!(
+            "Recording script '{}'. Commands run from now on are added to it until 'script end'", name
+        )))
+    }
+
+    fn end(&self) -> ConsoleCommandResult {
+        let mut store = self.scripts.lock().unwrap();
+        let Some((name, lines)) = store.recording.take() else {
+            return error("Not recording a script");
+        };
+
+        let count = lines.len();
+        store.scripts.insert(name.clone(), lines);
+        ConsoleCommandResult::Output(ConsoleOutput::success(format!("Saved script '{}' with {} commands", name, count)))
+    }
+
+    fn define(&self, name: Option<&str>, body: &[&str]) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script define <name> <command>; <command>; ...");
+        };
+
+        let lines: Vec<String> = body.join(" ")
+            .split(';')
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        if lines.is_empty() {
+            return error("Usage: script define <name> <command>; <command>; ...");
+        }
+
+        let count = lines.len();
+        self.scripts.lock().unwrap().scripts.insert(name.to_string(), lines);
+        ConsoleCommandResult::Output(ConsoleOutput::success(format!("Defined script '{}' with {} commands", name, count)))
+    }
+
+    fn load(&self, name: Option<&str>, args: &[&str]) -> ConsoleCommandResult {
+        let (Some(name), [url]) = (name, args) else {
+            return error("Usage: script load <name> <url of .rc file>");
+        };
+
+        let (name, url) = (name.to_string(), url.to_string());
+        let scripts = Arc::clone(&self.scripts);
+        ConsoleCommandResult::spawn(move |sender| async move {
+            match fetch_text(&url).await {
+                Ok(text) => {
+                    let count = scripts.lock().unwrap().load_rc(&name, &text);
+                    sender.send(ConsoleOutput::success(format!("Loaded script '{}' with {} commands from {}", name, count, url)));
+                }
+                Err(message) => sender.send(ConsoleOutput::error(format!("Failed to load {}: {}", url, message))),
+            }
+        })
+    }
+
+    fn run(&self, name: Option<&str>, script_args: &[&str], registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script run <name> [arguments...]");
+        };
+
+        // Copy what the run needs so the lock is not held while commands execute
+        let (lines, mut variables) = {
+            let store = self.scripts.lock().unwrap();
+            let Some(lines) = store.scripts.get(name) else {
+                return error(format!("Unknown script: {}", name));
+            };
+            (lines.clone(), store.variables.clone())
+        };
+
+        let mut outputs = vec![ConsoleOutput::info(format!("Running script '{}'", name))];
+        for line in &lines {
+            match expand_variables(line, script_args, &variables) {
+                Ok(expanded) => run_line(&expanded, registry, &mut variables, &mut outputs),
+                Err(message) => {
+                    outputs.push(ConsoleOutput::error(format!("{} in: {}. Script stopped", message, line)));
+                    break;
+                }
+            }
+        }
+
+        self.scripts.lock().unwrap().variables = variables;
+        ConsoleCommandResult::MultipleOutputs(outputs)
+    }
+
+    fn list(&self) -> ConsoleCommandResult {
+        let store = self.scripts.lock().unwrap();
+        if store.scripts.is_empty() {
+            return ConsoleCommandResult::Output(ConsoleOutput::info("No scripts defined"));
+        }
+
+        let mut lines = vec!["Scripts:".to_string()];
+        lines.extend(store.scripts.iter().map(|(name, commands)| format!("  {} ({} commands)", name, commands.len())));
+        if !store.variables.is_empty() {
+            lines.push("Variables:".to_string());
+            lines.extend(store.variables.iter().map(|(name, value)| format!("  {} = {}", name, value)));
+        }
+        ConsoleCommandResult::Output(ConsoleOutput::info(lines.join("\n")))
+    }
+
+    fn show(&self, name: Option<&str>) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script show <name>");
+        };
+
+        let store = self.scripts.lock().unwrap();
+        match store.scripts.get(name) {
+            Some(lines) => {
+                let mut output = vec![format!("Script '{}':", name)];
+                output.extend(lines.iter().map(|line| format!("  {}", line)));
+                ConsoleCommandResult::Output(ConsoleOutput::info(output.join("\n")))
+            }
+            None => error(format!("Unknown script: {}", name)),
+        }
+    }
+
+    fn delete(&self, name: Option<&str>) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script delete <name>");
+        };
+
+        match self.scripts.lock().unwrap().scripts.remove(name) {
+            Some(_) => ConsoleCommandResult::Output(ConsoleOutput::success(format!("Deleted script '{}'", name))),
+            None => error(format!("Unknown script: {}", name)),
+        }
+    }
+
+    fn set(&self, name: Option<&str>, value: &[&str]) -> ConsoleCommandResult {
+        let Some(name) = name else {
+            return error("Usage: script set <variable> <value>");
+        };
+
+        let value = value.join(" ");
+        let message = format!("{} = {}", name, value);
+        self.scripts.lock().unwrap().variables.insert(name.to_string(), value);
+        ConsoleCommandResult::Output(ConsoleOutput::success(message))
+    }
+}
+
+fn run_line(
+    line: &str,
+    registry: &ConsoleCommandRegistry,
+    variables: &mut BTreeMap<String, String>,
+    outputs: &mut Vec<ConsoleOutput>,
+) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["set", name, value @ ..] => {
+            variables.insert(name.to_string(), value.join(" "));
+        }
+        ["if", lhs, op, rhs, "then", command @ ..] if !command.is_empty() => {
+            let condition = match *op {
+                "==" => lhs == rhs,
+                "!=" => lhs != rhs,
+                _ => {
+                    outputs.push(ConsoleOutput::error(format!("Unknown operator '{}' in: {}", op, line)));
+                    return;
+                }
+            };
+            if condition {
+                run_line(&command.join(" "), registry, variables, outputs);
+            }
+        }
+        ["script", ..] => {
+            outputs.push(ConsoleOutput::warning(format!("Skipped nested script command: {}", line)));
+        }
+        [] => {}
+        _ => {
+            outputs.push(ConsoleOutput::echo(line));
+            match registry.execute(line) {
+                ConsoleCommandResult::Output(output) | ConsoleCommandResult::ClearAndOutput(output) => outputs.push(output),
+                ConsoleCommandResult::MultipleOutputs(results) => outputs.extend(results),
+                ConsoleCommandResult::Stream(_) => {
+                    outputs.push(ConsoleOutput::warning("Async command output is not shown when run from a script"));
+                }
+            }
+        }
+    }
+}
+
+/// Replace `$name` words with variable values and `$1`, `$2`, ... with script arguments
+fn expand_variables(line: &str, args: &[&str], variables: &BTreeMap<String, String>) -> Result<String, String> {
+    let positional: HashMap<String, &str> = args.iter()
+        .enumerate()
+        .map(|(index, arg)| ((index + 1).to_string(), *arg))
+        .collect();
+
+    line.split_whitespace()
+        .map(|word| match word.strip_prefix('$') {
+            Some(name) => positional.get(name)
+                .copied()
+                .or_else(|| variables.get(name).map(String::as_str))
+                .map(str::to_string)
+                .ok_or_else(|| format!("Undefined variable ${}", name)),
+            None => Ok(word.to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|words| words.join(" "))
+}
+
+async fn fetch_text(url: &str) -> Result<String, String> {
+    let window = web_sys::window().ok_or("no window")?;
+    let response: web_sys::Response = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .dyn_into()
+        .map_err(|_| "not a response".to_string())?;
+    if !response.ok() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+    let text = response.text().map_err(|e| format!("{:?}", e))?;
+    wasm_bindgen_futures::JsFuture::from(text)
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .as_string()
+        .ok_or_else(|| "not text".to_string())
+}
+
+fn error(message: impl Into<String>) -> ConsoleCommandResult {
+    ConsoleCommandResult::Output(ConsoleOutput::error(message))
+}
+
+fn usage() -> ConsoleCommandResult {
+    ConsoleCommandResult::Output(ConsoleOutput::info([
+        "Usage:",
+        "  script record <name>              - record the following commands",
+        "  script end                        - stop recording and save",
+        "  script define <name> <cmd>; <cmd> - define a script inline",
+        "  script load <name> <url>          - load a script from a .rc file",
+        "  script run <name> [args...]       - run a script ($1, $2, ... are arguments)",
+        "  script list                       - list scripts and variables",
+        "  script show <name>                - print a script",
+        "  script delete <name>              - remove a script",
+        "  script set <variable> <value>     - set a variable used as $variable",
+        "An undefined $variable stops the script.",
+        "Script lines may also use 'set <variable> <value>' and",
+        "'if <a> == <b> then <command>' (or !=).",
+    ].join("\n")))
+}
@@ -0,0 +1,131 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use crate::{ArgSpec, ParsedArgs, ConsoleCommand, ConsoleCommandResult, ConsoleCommandRegistry, ConsoleHistory, ConsoleOutput};
+use crate::history::DEFAULT_MAX_HISTORY_SIZE;
+
+const MIN_OPACITY: f32 = 0.2;
+const MIN_FONT_SIZE: f32 = 8.0;
+const MAX_FONT_SIZE: f32 = 32.0;
+const MAX_HISTORY_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConsoleLayout {
+    Floating,
+    DockedBottom,
+}
+
+/// Appearance of the console, persisted between sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsoleSettings {
+    pub layout: ConsoleLayout,
+    pub opacity: f32,
+    pub font_size: f32,
+    /// egui key name, e.g. `Backtick` or `F2`
+    pub toggle_key: String,
+    /// Number of commands kept in the persisted history
+    pub history_size: usize,
+}
+
+impl Default for ConsoleSettings {
+    fn default() -> Self {
+        Self {
+            layout: ConsoleLayout::Floating,
+            opacity: 1.0,
+            font_size: 12.0,
+            toggle_key: "Backtick".to_string(),
+            history_size: DEFAULT_MAX_HISTORY_SIZE,
+        }
+    }
+}
+
+impl ConsoleSettings {
+    pub fn toggle_key(&self) -> Option<three_d::egui::Key> {
+        three_d::egui::Key::from_name(&self.toggle_key)
+    }
+}
+
+/// Shows and changes the console settings
+pub(crate) struct ConsoleSettingsCommand {
+    pub(crate) settings: Arc<Mutex<ConsoleSettings>>,
+    pub(crate) history: Arc<Mutex<ConsoleHistory>>,
+}
+
+impl ConsoleCommand for ConsoleSettingsCommand {
+    fn name(&self) -> &str { "console" }
+    fn description(&self) -> &str { "Show or change console layout, opacity, font size, toggle hotkey and history size" }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::choice("setting", "Setting to change", &["layout", "opacity", "font-size", "hotkey", "history-size", "reset"]).optional(),
+            ArgSpec::text("value", "floating|docked, 0.2-1.0, 8-32, an egui key name or 1-1000").optional(),
+        ]
+    }
+
+    fn execute(&self, args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let mut settings = self.settings.lock().unwrap();
+
+        let result = match (args.text("setting"), args.text("value")) {
+            (None, _) => {
+                let layout = match settings.layout {
+                    ConsoleLayout::Floating => "floating",
+                    ConsoleLayout::DockedBottom => "docked",
+                };
+                return ConsoleCommandResult::Output(ConsoleOutput::info(format!(
+                    "layout: {}\nopacity: {:.2}\nfont-size: {}\nhotkey: {}\nhistory-size: {}",
+                    layout, settings.opacity, settings.font_size, settings.toggle_key, settings.history_size
+                )));
+            }
+            (Some("reset"), None) => {
+                *settings = ConsoleSettings::default();
+                self.history.lock().unwrap().set_max_size(settings.history_size);
+                Ok("Console settings reset".to_string())
+            }
+            (Some("layout"), Some("floating")) => {
+                settings.layout = ConsoleLayout::Floating;
+                Ok("Console is now a floating window".to_string())
+            }
+            (Some("layout"), Some("docked")) => {
+                settings.layout = ConsoleLayout::DockedBottom;
+                Ok("Console is now docked at the bottom".to_string())
+            }
+            (Some("opacity"), Some(value)) => match value.parse::<f32>() {
+                Ok(opacity) if (MIN_OPACITY..=1.0).contains(&opacity) => {
+                    settings.opacity = opacity;
+                    Ok(format!("Opacity set to {:.2}", opacity))
+                }
+                _ => Err(format!("Opacity must be a number between {} and 1.0", MIN_OPACITY)),
+            },
+            (Some("font-size"), Some(value)) => match value.parse::<f32>() {
+                Ok(size) if (MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&size) => {
+                    settings.font_size = size;
+                    Ok(format!("Font size set to {}", size))
+                }
+                _ => Err(format!("Font size must be between {} and {}", MIN_FONT_SIZE, MAX_FONT_SIZE)),
+            },
+            (Some("hotkey"), Some(name)) => match three_d::egui::Key::from_name(name) {
+                Some(key) => {
+                    settings.toggle_key = key.name().to_string();
+                    Ok(format!("Console toggles with {}", key.name()))
+                }
+                None => Err(format!("Unknown key: {}", name)),
+            },
+            (Some("history-size"), Some(value)) => match value.parse::<usize>() {
+                Ok(size) if (1..=MAX_HISTORY_SIZE).contains(&size) => {
+                    settings.history_size = size;
+                    self.history.lock().unwrap().set_max_size(size);
+                    Ok(format!("History keeps the last {} commands", size))
+                }
+                _ => Err(format!("History size must be between 1 and {}", MAX_HISTORY_SIZE)),
+            },
+            _ => Err("Usage: console [layout floating|docked | opacity <0.2-1.0> | font-size <8-32> | hotkey <key> | history-size <1-1000> | reset]".to_string()),
+        };
+
+        match result {
+            Ok(message) => ConsoleCommandResult::Output(ConsoleOutput::success(message)),
+            Err(message) => ConsoleCommandResult::Output(ConsoleOutput::error(message)),
+        }
+    }
+}
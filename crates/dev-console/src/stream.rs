@@ -0,0 +1,55 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::rc::Rc;
+use crate::{ConsoleCommandResult, ConsoleOutput};
+
+/// Sending half of an output stream, used by async commands to report progress
+#[derive(Debug, Clone)]
+pub struct ConsoleOutputSender {
+    queue: Rc<RefCell<VecDeque<ConsoleOutput>>>,
+}
+
+impl ConsoleOutputSender {
+    pub fn send(&self, output: ConsoleOutput) {
+        self.queue.borrow_mut().push_back(output);
+    }
+}
+
+/// Receiving half of an output stream, drained by the console every frame
+///
+/// The stream is finished once every sender is dropped and the queue is empty.
+#[derive(Debug)]
+pub struct ConsoleOutputReceiver {
+    queue: Rc<RefCell<VecDeque<ConsoleOutput>>>,
+}
+
+impl ConsoleOutputReceiver {
+    pub fn drain(&self) -> Vec<ConsoleOutput> {
+        self.queue.borrow_mut().drain(..).collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        Rc::strong_count(&self.queue) == 1 && self.queue.borrow().is_empty()
+    }
+}
+
+pub fn output_stream() -> (ConsoleOutputSender, ConsoleOutputReceiver) {
+    let queue = Rc::new(RefCell::new(VecDeque::new()));
+    (ConsoleOutputSender { queue: Rc::clone(&queue) }, ConsoleOutputReceiver { queue })
+}
+
+impl ConsoleCommandResult {
+    /// Run `task` on the browser event loop and stream what it sends to the console
+    pub fn spawn<F, Fut>(task: F) -> Self
+    where
+        F: FnOnce(ConsoleOutputSender) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let (sender, receiver) = output_stream();
+        wasm_bindgen_futures::spawn_local(task(sender));
+        Self::Stream(receiver)
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::sync::{Arc, Mutex};
+use crate::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandResult, ConsoleCommandRegistry, ConsoleOutput, ParsedArgs};
+
+/// Selects which registered watches are shown in the console
+pub(crate) struct WatchCommand {
+    pub(crate) active: Arc<Mutex<Vec<String>>>,
+}
+
+impl ConsoleCommand for WatchCommand {
+    fn name(&self) -> &str { "watch" }
+    fn category(&self) -> CommandCategory { CommandCategory::Diagnostic }
+    fn description(&self) -> &str { "Show live values (watch | watch <name> | watch remove <name> | watch clear)" }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::choice("action", "Stop watching <name>, or all watches", &["remove", "clear"]).optional(),
+            ArgSpec::text("name", "Watch to show or stop showing").optional(),
+        ]
+    }
+
+    fn execute(&self, args: &ParsedArgs, registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let mut active = self.active.lock().unwrap();
+
+        match (args.text("action"), args.text("name")) {
+            (None, None) => {
+                let names = registry.watch_names();
+                if names.is_empty() {
+                    return ConsoleCommandResult::Output(ConsoleOutput::info("No watches registered"));
+                }
+
+                let mut lines = vec!["Available watches:".to_string()];
+                lines.extend(names.iter().map(|name| {
+                    let marker = if active.iter().any(|a| a == name) { "*" } else { " " };
+                    format!("  {} {}", marker, name)
+                }));
+                ConsoleCommandResult::Output(ConsoleOutput::info(lines.join("\n")))
+            }
+            (Some("clear"), None) => {
+                active.clear();
+                ConsoleCommandResult::Output(ConsoleOutput::success("Removed all watches"))
+            }
+            (Some("remove"), Some(name)) => {
+                let count = active.len();
+                active.retain(|a| a != name);
+                if active.len() < count {
+                    ConsoleCommandResult::Output(ConsoleOutput::success(format!("Stopped watching {}", name)))
+                } else {
+                    ConsoleCommandResult::Output(ConsoleOutput::error(format!("Not watching {}", name)))
+                }
+            }
+            (None, Some(name)) => {
+                if registry.evaluate_watch(name).is_none() {
+                    return ConsoleCommandResult::Output(ConsoleOutput::error(format!("Unknown watch: {}", name)));
+                }
+                if !active.iter().any(|a| a == name) {
+                    active.push(name.to_string());
+                }
+                ConsoleCommandResult::Output(ConsoleOutput::success(format!("Watching {}", name)))
+            }
+            _ => ConsoleCommandResult::Output(ConsoleOutput::error("Usage: watch | watch <name> | watch remove <name> | watch clear")),
+        }
+    }
+}
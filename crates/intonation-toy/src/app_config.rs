@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 //! Application configuration constants
 //! 
 //! This module contains all configuration constants used throughout the application
@@ -15,6 +13,12 @@ pub const DEFAULT_THEME: Theme = Theme::Dark;
 /// C4 = 60
 pub const DEFAULT_TONAL_CENTER_NOTE: MidiNote = 60;
 
+/// Frequency of A4 from which all note frequencies are derived
+pub const DEFAULT_CONCERT_PITCH_HZ: f32 = 440.0;
+/// Accepted concert pitches, from below baroque pitch (415 Hz) to above modern orchestral pitch
+pub const MIN_CONCERT_PITCH_HZ: f32 = 400.0;
+pub const MAX_CONCERT_PITCH_HZ: f32 = 480.0;
+
 /// Default scale for pitch visualization and analysis.
 /// Set to Chromatic scale, which includes all 12 semitones and provides
 /// the most comprehensive pitch reference for users. Other scales can be
@@ -27,6 +31,8 @@ pub const DEFAULT_DISPLAY_RANGE: DisplayRange = DisplayRange::TwoOctaves;
 /// Viewport configuration
 pub const VIEWPORT_RENDER_SIZE: u32 = 1024;
 pub const VIEWPORT_RENDER_SIZE_RETINA: u32 = 512;
+/// Largest render size with reduced rendering
+pub const VIEWPORT_RENDER_SIZE_REDUCED: u32 = 384;
 pub const CANVAS_MIN_SIZE: i32 = 384;
 pub const CANVAS_MAX_SIZE: i32 = 4096;
 
@@ -37,6 +43,178 @@ pub const WINDOW_TITLE: &str = "intonation-toy";
 pub const AUDIO_CHUNK_SIZE: usize = 128;                // AudioWorklet fixed chunk size
 pub const BUFFER_SIZE: usize = AUDIO_CHUNK_SIZE * 16;   // IMPORTANT: Also update BUFFER_SIZE in static/audio-processor.js
 
+/// Range of pitch analysis window sizes selectable at runtime.
+/// Larger windows resolve lower voices at the cost of latency.
+pub const MIN_ANALYSIS_WINDOW_SIZE: usize = 1024;
+pub const MAX_ANALYSIS_WINDOW_SIZE: usize = 8192;
+
+/// Largest worklet batch; larger batches mean fewer messages but more latency
+pub const MAX_BATCH_SIZE: usize = 8192;
+/// Adaptive batching grows batches above and shrinks them below these smoothed frame times
+pub const ADAPTIVE_BATCH_SLOW_FRAME_MS: f32 = 40.0;
+pub const ADAPTIVE_BATCH_FAST_FRAME_MS: f32 = 25.0;
+/// Frames to wait after a batch size change before judging it
+pub const ADAPTIVE_BATCH_SETTLE_FRAMES: u32 = 60;
+/// Samples held by the shared ring buffer transport; must be a power of two
+pub const SAMPLE_RING_CAPACITY: usize = 32768;
+/// Samples per callback of the ScriptProcessorNode used without AudioWorklet;
+/// a power of two from 256 to 16384
+pub const SCRIPT_PROCESSOR_BUFFER_SIZE: u32 = 1024;
+
+/// Audio recovery: the worklet counts as stalled after this long without a batch
+pub const WORKLET_STALL_TIMEOUT_MS: f64 = 1500.0;
+/// Restart delays double from the initial delay up to the maximum
+pub const RECOVERY_INITIAL_DELAY_MS: u32 = 500;
+pub const RECOVERY_MAX_DELAY_MS: u32 = 16000;
+/// Audio must run this long after a restart before the delay starts over
+pub const RECOVERY_STABLE_MS: f64 = 10000.0;
+
+/// Input conditioning defaults, used when the stages are switched on
+pub const NOISE_GATE_THRESHOLD_DB: f32 = -50.0;
+pub const NOISE_GATE_ATTACK_MS: f32 = 5.0;
+pub const NOISE_GATE_RELEASE_MS: f32 = 150.0;
+/// Below the lowest sung fundamentals (~65 Hz for bass voices)
+pub const HIGH_PASS_CUTOFF_HZ: f32 = 50.0;
+
+/// Reference tone defaults
+pub const REFERENCE_TONE_ATTACK_MS: f32 = 20.0;
+pub const REFERENCE_TONE_DECAY_MS: f32 = 150.0;
+pub const REFERENCE_TONE_SUSTAIN_LEVEL: f32 = 0.7;
+pub const REFERENCE_TONE_RELEASE_MS: f32 = 300.0;
+pub const REFERENCE_TONE_VOLUME: f32 = 0.5;
+/// How long an on-demand reference tone is held before its release
+pub const REFERENCE_TONE_DURATION_MS: f32 = 1000.0;
+
+/// Spectrum analysis; the bin count must divide SPECTRUM_FFT_SIZE / 2
+pub const SPECTRUM_FFT_SIZE: u32 = 4096;
+pub const DEFAULT_SPECTRUM_BIN_COUNT: usize = 256;
+pub const MIN_SPECTRUM_BIN_COUNT: usize = 16;
+
+/// Spectrogram panel: one column per frame, seven octaves from A1 on a log axis
+pub const SPECTROGRAM_COLUMNS: usize = 300;
+pub const SPECTROGRAM_ROWS: usize = 168;
+pub const SPECTROGRAM_MIN_HZ: f32 = 55.0;
+pub const SPECTROGRAM_MAX_HZ: f32 = 7040.0;
+/// Share of the canvas height the spectrogram covers, along the bottom
+pub const SPECTROGRAM_HEIGHT_FRACTION: f32 = 0.25;
+/// Decibels below the loudest level that the spectrogram still shades
+pub const DEFAULT_SPECTROGRAM_DYNAMIC_RANGE_DB: f32 = 50.0;
+
+/// Number of harmonics, including the fundamental, measured for timbre feedback
+pub const HARMONIC_COUNT: usize = 8;
+
+/// Voice activity detection; pitch is only reported for voiced input
+pub const VAD_MIN_RMS: f32 = 0.005;
+/// Spectral flatness, 0.0 (tonal) to 1.0 (white noise), above which input counts as unvoiced
+pub const VAD_MAX_SPECTRAL_FLATNESS: f32 = 0.3;
+/// Milliseconds that voicing is held after the input stops qualifying
+pub const VAD_HANGOVER_MS: f64 = 50.0;
+/// Frequency band in which spectral flatness is measured
+pub const VAD_VOICE_BAND_HZ: (f32, f32) = (80.0, 4000.0);
+
+/// Pitch tracking applied to raw detector output
+/// Consecutive detections needed before a pitch is reported
+pub const PITCH_ONSET_FRAMES: u32 = 3;
+/// Frames a pitch is held through dropouts
+pub const PITCH_RELEASE_FRAMES: u32 = 4;
+/// Readings in the median filter
+pub const PITCH_MEDIAN_FRAMES: usize = 5;
+/// How close to a whole number of octaves a jump must be to count as an octave error
+pub const PITCH_OCTAVE_TOLERANCE_CENTS: f32 = 50.0;
+/// Frames an octave jump must persist before it is accepted
+pub const PITCH_OCTAVE_CONFIRM_FRAMES: u32 = 8;
+
+/// Input gain limits and automatic gain control
+pub const MIN_INPUT_GAIN: f32 = 0.25;
+pub const MAX_INPUT_GAIN: f32 = 16.0;
+pub const AGC_TARGET_RMS: f32 = 0.1;
+/// Fastest gain change the AGC makes
+pub const AGC_MAX_DB_PER_SECOND: f32 = 6.0;
+/// RMS below which the AGC holds its gain, when the device is not calibrated
+pub const AGC_DEFAULT_NOISE_FLOOR_RMS: f32 = 0.003;
+
+/// Input level calibration
+pub const CALIBRATION_NOISE_DURATION_MS: f64 = 3000.0;
+pub const CALIBRATION_VOICE_DURATION_MS: f64 = 5000.0;
+/// Calibrated gain brings the loudest sung peak to this level
+pub const CALIBRATION_TARGET_PEAK: f32 = 0.5;
+/// Peaking starts this factor above the loudest calibrated peak
+pub const CALIBRATION_PEAK_HEADROOM: f32 = 1.8;
+
+/// MIDI output channel (0-based) and note-on velocity
+pub const MIDI_OUTPUT_CHANNEL: u8 = 0;
+pub const MIDI_OUTPUT_VELOCITY: u8 = 100;
+
+/// Length of the input recording ring buffer
+pub const RECORDING_MAX_SECONDS: f32 = 60.0;
+pub const RECORDING_EXPORT_FILENAME: &str = "intonation-toy-recording.wav";
+/// Time between two pitch analyses when a recording is turned into a melody
+pub const RECORDING_ANALYSIS_HOP_MS: f64 = 10.0;
+pub const SCALA_EXPORT_FILENAME: &str = "intonation-toy-custom.scl";
+pub const PITCH_TIMELINE_EXPORT_FILENAME: &str = "intonation-toy-pitch.csv";
+pub const DEGREE_STATISTICS_EXPORT_FILENAME: &str = "intonation-toy-statistics.csv";
+pub const SESSION_EXPORT_FILENAME: &str = "intonation-toy-session.json";
+pub const SHARE_IMAGE_FILENAME: &str = "intonation-toy.png";
+/// Distance from the title of the share image to the statistics beneath it, in pixels
+pub const SHARE_IMAGE_LINE_SPACING: f64 = 26.0;
+
+/// IndexedDB database of the saved sessions, and the version of its object stores
+pub const SESSION_DATABASE_NAME: &str = "intonation-toy";
+pub const SESSION_DATABASE_VERSION: u32 = 1;
+
+/// Count-in before a recording or an exercise starts, and how fast the
+/// recording lamp blinks
+pub const COUNT_IN_BEATS: u32 = 3;
+pub const COUNT_IN_BEAT_MS: f64 = 1000.0;
+pub const RECORDING_LAMP_BLINK_MS: f64 = 1000.0;
+
+/// How long a toast for an error that is not fatal stays up, and how many
+/// stay up at once
+pub const TOAST_DURATION_MS: f64 = 8000.0;
+pub const MAX_TOASTS: usize = 3;
+
+/// Detected pitches kept by the model for trails and statistics
+pub const PITCH_HISTORY_RETENTION_MS: f64 = 30_000.0;
+/// Span and resolution of the pitch trail handed to the presentation layer,
+/// shown by the pitch graph
+pub const PITCH_TRAIL_WINDOW_MS: f64 = 10_000.0;
+pub const PITCH_TRAIL_POINTS: usize = 300;
+/// Opacity of the previous exercise attempt drawn behind the pitch graph
+pub const GHOST_TRAIL_ALPHA: f32 = 0.35;
+/// Longest exercise attempt kept whole for its ghost; earlier parts of a
+/// longer one are dropped
+pub const GHOST_ATTEMPT_RETENTION_MS: f64 = 180_000.0;
+/// Span of recent pitch analyzed for vibrato; several cycles at typical rates
+pub const VIBRATO_WINDOW_MS: f64 = 1_000.0;
+
+/// Range of a custom tolerance profile
+pub const MIN_IN_TUNE_TOLERANCE_CENTS: f32 = 1.0;
+pub const MAX_IN_TUNE_TOLERANCE_CENTS: f32 = 50.0;
+/// Longest time between two updates that session statistics account for
+pub const SESSION_MAX_FRAME_GAP_MS: f64 = 100.0;
+/// Span of sung notes the suggested root is inferred from
+pub const ROOT_SUGGESTION_WINDOW_MS: f64 = 60_000.0;
+/// How often the session statistics are saved for restoring after a reload,
+/// and the snapshot for bug reports is taken
+pub const SESSION_SNAPSHOT_INTERVAL_MS: f64 = 2_000.0;
+
+/// Exercises move on once a target is held in tune this long
+pub const EXERCISE_HOLD_MS: f64 = 1_000.0;
+/// Targets in a random interval exercise
+pub const EXERCISE_RANDOM_TARGET_COUNT: usize = 8;
+
+/// Tempo range of the metronome, and the pitches and length of its clicks;
+/// the click on the beat is higher than those on its subdivisions
+pub const DEFAULT_METRONOME_TEMPO_BPM: f32 = 80.0;
+pub const MIN_METRONOME_TEMPO_BPM: f32 = 40.0;
+pub const MAX_METRONOME_TEMPO_BPM: f32 = 208.0;
+pub const METRONOME_ACCENT_CLICK_HZ: f32 = 1760.0;
+pub const METRONOME_CLICK_HZ: f32 = 1320.0;
+pub const METRONOME_CLICK_MS: f64 = 40.0;
+/// How far ahead the clicks of the metronome are scheduled on the audio clock;
+/// longer than the frames are apart, so no click waits on a frame
+pub const METRONOME_LOOKAHEAD_MS: f64 = 150.0;
+
 /// Pitch detection configuration
 pub const POWER_THRESHOLD: f32 = 0.3;
 pub const CLARITY_THRESHOLD: f32 = 0.2;
@@ -88,14 +266,13 @@ pub const ADAPTIVE_EMA_DEADBAND: f32 = 1.0;
 pub const ADAPTIVE_EMA_HYSTERESIS_DOWN: f32 = 0.25;
 pub const ADAPTIVE_EMA_HYSTERESIS_UP: f32 = 0.45;
 
-/// Enable adaptive EMA smoothing (set to false to use simple EMA)
-pub const USE_ADAPTIVE_EMA: bool = false;
+/// Readings in the median smoothing window
+pub const DEFAULT_MEDIAN_SMOOTHING_WINDOW: usize = 5;
+pub const MAX_MEDIAN_SMOOTHING_WINDOW: usize = 31;
 
-/// Intonation accuracy configuration
-/// Threshold in cents for considering pitch "accurate" and showing accent color
-/// When the detected pitch is within ±INTONATION_ACCURACY_THRESHOLD cents of a note,
-/// the user pitch line will display in accent color (unless volume is peaking)
-pub const INTONATION_ACCURACY_THRESHOLD: f32 = 15.0;
+/// One-euro smoothing: cutoff for a steady pitch, and its increase per cent per second of pitch change
+pub const DEFAULT_ONE_EURO_MIN_CUTOFF_HZ: f32 = 1.0;
+pub const DEFAULT_ONE_EURO_BETA: f32 = 0.01;
 
 /// Volume peak threshold configuration
 /// (Since we don't calculate true peak)
@@ -104,8 +281,33 @@ pub const INTONATION_ACCURACY_THRESHOLD: f32 = 15.0;
 /// Set to -0.1dB converted to amplitude: 10^(-0.1/20) ≈ 0.9886
 pub const VOLUME_PEAK_THRESHOLD: f32 = 0.9886;
 
+/// Short-term loudness above which the volume counts as peaking when the
+/// peak flag follows loudness rather than sample peaks
+pub const LOUDNESS_PEAK_THRESHOLD_LUFS: f32 = -12.0;
+/// Loudness reported for silence; the BS.1770 absolute gate
+pub const LOUDNESS_FLOOR_LUFS: f32 = -70.0;
+
 /// User pitch line thickness configuration
 pub const USER_PITCH_LINE_THICKNESS: f32 = 10.0;
+pub const PITCH_GRAPH_LINE_THICKNESS: f32 = 4.0;
+
+/// Strobe configuration: bands around each ring, and rings from the inside
+/// out, each showing the next octave up so it turns twice as fast
+pub const STROBE_BAND_COUNT: u32 = 12;
+pub const STROBE_RING_COUNT: u32 = 4;
+
+/// Staff configuration: distance between staff lines, and the panel around
+/// the staff at the top of the canvas
+pub const STAFF_LINE_SPACING: f32 = 12.0;
+pub const STAFF_PANEL_WIDTH: f32 = 180.0;
+pub const STAFF_PANEL_HEIGHT: f32 = 150.0;
+pub const STAFF_PANEL_MARGIN: f32 = 10.0;
+
+/// On-screen piano configuration: the range of keys, and the part of the
+/// canvas height it takes along the bottom
+pub const PIANO_FIRST_NOTE: u8 = 36;
+pub const PIANO_LAST_NOTE: u8 = 96;
+pub const PIANO_HEIGHT_FRACTION: f32 = 0.12;
 
 /// Note label configuration
 pub const NOTE_LABEL_FONT_SIZE: f32 = 22.0;
@@ -128,3 +330,95 @@ pub const DEFAULT_LINE_THICKNESS: f32 = 1.0;
 /// Overlay alpha configuration
 pub const OVERLAY_BACKGROUND_ALPHA: f32 = 0.8;
 
+
+/// Input meter configuration: the level range the bar covers below full
+/// scale, how long the peak marker and the clip light stay up, and how fast
+/// the peak marker falls afterwards, in parts of the bar per second
+pub const LEVEL_METER_RANGE_DB: f32 = 60.0;
+pub const LEVEL_METER_PEAK_HOLD_MS: f64 = 1000.0;
+pub const LEVEL_METER_CLIP_HOLD_MS: f64 = 1500.0;
+pub const LEVEL_METER_PEAK_FALL_RATE: f32 = 0.5;
+
+/// Tuner needle configuration: the offset at either end of the scale, and
+/// the frequency the needle would swing at without damping
+pub const NEEDLE_RANGE_CENTS: f32 = 50.0;
+pub const NEEDLE_NATURAL_FREQUENCY_HZ: f32 = 2.5;
+
+/// Time without pointer movement after which the immersive layout hides the sidebar
+pub const IMMERSIVE_CONTROLS_HIDE_MS: f64 = 2500.0;
+
+/// Touch layout configuration
+pub const TOUCH_CANVAS_MARGIN: i32 = 8;
+pub const TOUCH_CANVAS_MIN_SIZE: i32 = 240;
+/// Height of the closed controls drawer, which leaves its toggle showing.
+/// IMPORTANT: Also update --touch-controls-handle-height in static/style.css
+pub const TOUCH_CONTROLS_HANDLE_HEIGHT: i32 = 48;
+
+/// Time after which the stored tuning and session are forgotten
+pub const TUNING_SETTINGS_EXPIRATION_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Mini view configuration: the size of its canvas, in pixels, and how often
+/// the app updates while the page is hidden and the mini view is open
+pub const MINI_VIEW_WIDTH: u32 = 240;
+pub const MINI_VIEW_HEIGHT: u32 = 120;
+pub const MINI_VIEW_BACKGROUND_UPDATE_MS: u32 = 50;
+
+/// Time before a refused screen wake lock is asked for again
+pub const WAKE_LOCK_RETRY_MS: f64 = 5000.0;
+
+/// Telemetry configuration: the endpoint that receives consented usage
+/// events, set at build time through `INTONATION_TOY_TELEMETRY_ENDPOINT`
+/// and without which nothing is sent, the most events kept until they are
+/// sent, and how often they are sent and the frame rate is sampled
+pub const TELEMETRY_ENDPOINT: Option<&str> = option_env!("INTONATION_TOY_TELEMETRY_ENDPOINT");
+pub const TELEMETRY_MAX_PENDING_EVENTS: usize = 100;
+pub const TELEMETRY_FLUSH_INTERVAL_MS: f64 = 5.0 * 60.0 * 1000.0;
+
+/// Bug report configuration: how many log entries a report lists, and the
+/// length in characters beyond which an entry is cut short
+pub const BUG_REPORT_LOG_CAPACITY: usize = 50;
+pub const BUG_REPORT_MAX_MESSAGE_LENGTH: usize = 300;
+
+/// Service worker caching the app for offline use, relative to the page
+pub const SERVICE_WORKER_URL: &str = "service-worker.js";
+
+/// Pane layout configuration: the default width of the side column and the
+/// smallest pane, as parts of the canvas, how close to a splitter the
+/// pointer grabs it, and how thick splitters are drawn, in pixels
+pub const DEFAULT_SIDE_COLUMN_FRACTION: f32 = 0.35;
+pub const MIN_PANE_FRACTION: f32 = 0.15;
+pub const SPLITTER_GRAB_DISTANCE: f32 = 8.0;
+pub const SPLITTER_THICKNESS: f32 = 3.0;
+
+/// Pitch announcement configuration: how long a reading must hold before it
+/// is announced, and the offset beyond which a pitch is called far off when
+/// the tolerance is narrower
+pub const ANNOUNCEMENT_STABLE_MS: f64 = 700.0;
+pub const ANNOUNCEMENT_FAR_OFF_CENTS: f32 = 25.0;
+
+/// Animation configuration: how long the display background fades into a new
+/// tonal center, scale or theme, how long the display takes to zoom to a new
+/// interval range, how long piano keys and the detected notehead take to
+/// change color, and how long and how large the target notehead pulses when
+/// the target note is hit
+pub const BACKGROUND_FADE_MS: f64 = 350.0;
+pub const INTERVAL_ZOOM_MS: f64 = 400.0;
+pub const PIANO_KEY_FADE_MS: f64 = 200.0;
+pub const NOTEHEAD_FADE_MS: f64 = 150.0;
+pub const TARGET_HIT_PULSE_MS: f64 = 450.0;
+pub const TARGET_HIT_PULSE_SCALE: f32 = 1.6;
+
+/// Tab coordination configuration: how often a tab announces itself to the
+/// other tabs of the app, and how long after its last announcement a tab is
+/// taken as closed
+pub const TAB_HEARTBEAT_MS: u32 = 2000;
+pub const TAB_TIMEOUT_MS: f64 = 5000.0;
+
+/// Duet configuration: the STUN servers through which the peers find a way
+/// to each other, how often the student's pitch is sent, how long the
+/// teacher still shows it after the last arrived, and how often the
+/// teacher's reference is sent again while unchanged
+pub const DUET_ICE_SERVERS: [&str; 1] = ["stun:stun.l.google.com:19302"];
+pub const DUET_PITCH_INTERVAL_MS: f64 = 100.0;
+pub const DUET_PITCH_TIMEOUT_MS: f64 = 1000.0;
+pub const DUET_REFERENCE_RESEND_MS: f64 = 1000.0;
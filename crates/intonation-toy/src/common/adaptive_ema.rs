@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 use std::collections::VecDeque;
 
 /// Adaptive EMA that smooths small jitter strongly while staying responsive on larger moves.
@@ -218,10 +216,10 @@ impl AdaptiveEMA {
         let delta = (x - y_prev).abs();
 
         // Optional deadband override
-        if let Some(deadband) = self.deadband {
-            if delta < deadband {
-                return self.alpha_min;
-            }
+        if let Some(deadband) = self.deadband
+            && delta < deadband
+        {
+            return self.alpha_min;
         }
 
         // Optional hysteresis by shifting the effective threshold d
@@ -0,0 +1,122 @@
+//! Pitch readings in words for screen readers
+//!
+//! The display is a canvas that assistive technology cannot see, so the note
+//! closest to the detected pitch and how far it is off are announced instead.
+//! Offsets are put in coarse buckets and a reading must hold for a while
+//! before it is announced, so a wavering voice does not flood the screen
+//! reader with every change.
+
+use crate::app_config::{ANNOUNCEMENT_FAR_OFF_CENTS, ANNOUNCEMENT_STABLE_MS};
+use crate::common::shared_types::MidiNote;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deviation {
+    InTune,
+    Sharp,
+    FarSharp,
+    Flat,
+    FarFlat,
+}
+
+impl Deviation {
+    /// Bucket of an offset from the closest note; pitches within the tolerance
+    /// are in tune, and those beyond the wider of the tolerance and
+    /// `ANNOUNCEMENT_FAR_OFF_CENTS` are far off
+    pub fn from_cents(cents: f32, tolerance_cents: f32) -> Self {
+        let far = cents.abs() > tolerance_cents.max(ANNOUNCEMENT_FAR_OFF_CENTS);
+        match cents {
+            cents if cents.abs() <= tolerance_cents => Deviation::InTune,
+            cents if cents > 0.0 => if far { Deviation::FarSharp } else { Deviation::Sharp },
+            _ => if far { Deviation::FarFlat } else { Deviation::Flat },
+        }
+    }
+
+    /// Localization key of the words for the bucket
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            Deviation::InTune => "announcement-in-tune",
+            Deviation::Sharp => "announcement-sharp",
+            Deviation::FarSharp => "announcement-far-sharp",
+            Deviation::Flat => "announcement-flat",
+            Deviation::FarFlat => "announcement-far-flat",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reading {
+    pub note: MidiNote,
+    pub deviation: Deviation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Announcement {
+    Pitch(Reading),
+    /// The pitch that was announced has ended
+    NoPitch,
+}
+
+/// Decides when a reading has held long enough to announce
+#[derive(Debug, Clone, Default)]
+pub struct PitchAnnouncer {
+    /// Latest reading, `None` while nothing is detected, and the time it started
+    candidate: Option<(Option<Reading>, f64)>,
+    announced: Option<Reading>,
+}
+
+impl PitchAnnouncer {
+    /// Feed the reading of this frame, returning what to announce if a
+    /// different reading has held for `ANNOUNCEMENT_STABLE_MS`
+    pub fn update(&mut self, reading: Option<Reading>, now_ms: f64) -> Option<Announcement> {
+        let since_ms = match self.candidate {
+            Some((candidate, since_ms)) if candidate == reading => since_ms,
+            _ => {
+                self.candidate = Some((reading, now_ms));
+                now_ms
+            }
+        };
+        if reading == self.announced || now_ms - since_ms < ANNOUNCEMENT_STABLE_MS {
+            return None;
+        }
+        self.announced = reading;
+        Some(reading.map_or(Announcement::NoPitch, Announcement::Pitch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviation_buckets() {
+        assert_eq!(Deviation::from_cents(-8.0, 10.0), Deviation::InTune);
+        assert_eq!(Deviation::from_cents(12.0, 10.0), Deviation::Sharp);
+        assert_eq!(Deviation::from_cents(30.0, 10.0), Deviation::FarSharp);
+        assert_eq!(Deviation::from_cents(-20.0, 10.0), Deviation::Flat);
+        assert_eq!(Deviation::from_cents(-26.0, 10.0), Deviation::FarFlat);
+        // A wide tolerance keeps the in-tune bucket from swallowing the near one
+        assert_eq!(Deviation::from_cents(-40.0, 30.0), Deviation::FarFlat);
+    }
+
+    #[test]
+    fn test_readings_are_announced_once_they_hold() {
+        let a4_reading = Reading { note: 69, deviation: Deviation::InTune };
+        let a4 = Some(a4_reading);
+        let a4_sharp = Some(Reading { note: 69, deviation: Deviation::Sharp });
+        let mut announcer = PitchAnnouncer::default();
+
+        assert_eq!(announcer.update(None, 0.0), None);
+        assert_eq!(announcer.update(a4, 100.0), None);
+        assert_eq!(announcer.update(a4, 100.0 + ANNOUNCEMENT_STABLE_MS), Some(Announcement::Pitch(a4_reading)));
+        assert_eq!(announcer.update(a4, 2000.0), None);
+
+        // A short wobble is not announced
+        assert_eq!(announcer.update(a4_sharp, 2100.0), None);
+        assert_eq!(announcer.update(a4, 2200.0), None);
+        assert_eq!(announcer.update(a4, 5000.0), None);
+
+        assert_eq!(announcer.update(None, 6000.0), None);
+        assert_eq!(announcer.update(None, 6000.0 + ANNOUNCEMENT_STABLE_MS), Some(Announcement::NoPitch));
+        assert_eq!(announcer.update(None, 9000.0), None);
+    }
+}
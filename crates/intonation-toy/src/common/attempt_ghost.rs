@@ -0,0 +1,136 @@
+//! Pitch trail of the previous exercise attempt, replayed beside the running one
+//!
+//! The pitch of the running attempt is recorded from its start. An attempt
+//! that reaches its last target becomes the ghost, while a stopped one is
+//! dropped, so the ghost is always a complete run. The ghost is replayed from
+//! the start of the running attempt, as if both had begun together, and only
+//! behind an attempt of the same kind.
+
+use crate::app_config::GHOST_ATTEMPT_RETENTION_MS;
+use crate::common::pitch_history::{PitchHistory, PitchSample};
+use crate::common::shared_types::ExerciseKind;
+
+#[derive(Debug, Clone)]
+struct RunningAttempt {
+    kind: ExerciseKind,
+    start_ms: f64,
+    history: PitchHistory,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AttemptGhost {
+    running: Option<RunningAttempt>,
+    /// Kind of the last finished attempt and its samples, timed from its start
+    previous: Option<(ExerciseKind, Vec<PitchSample>)>,
+}
+
+impl AttemptGhost {
+    /// Start recording an attempt, dropping one still running
+    pub fn start(&mut self, kind: ExerciseKind, now_ms: f64) {
+        self.running = Some(RunningAttempt { kind, start_ms: now_ms, history: PitchHistory::new(GHOST_ATTEMPT_RETENTION_MS) });
+    }
+
+    /// Advance the running attempt to `now_ms`, appending `sample` when a
+    /// pitch was detected
+    pub fn record(&mut self, now_ms: f64, sample: Option<PitchSample>) {
+        if let Some(running) = &mut self.running {
+            running.history.record(now_ms, sample);
+        }
+    }
+
+    /// Keep the running attempt as the ghost of the next one
+    pub fn finish(&mut self) {
+        let Some(running) = self.running.take() else {
+            return;
+        };
+        let samples = running.history
+            .history(f64::INFINITY)
+            .map(|sample| PitchSample { timestamp_ms: sample.timestamp_ms - running.start_ms, ..*sample })
+            .collect();
+        self.previous = Some((running.kind, samples));
+    }
+
+    /// Drop the running attempt, keeping the ghost
+    pub fn abandon(&mut self) {
+        self.running = None;
+    }
+
+    /// The previous attempt over the last `window_ms` of the running one, timed
+    /// as if it had started with it and averaged down like `PitchHistory::decimated`;
+    /// empty unless an attempt of the kind of the ghost is running
+    pub fn trail(&self, now_ms: f64, window_ms: f64, points: usize) -> Vec<PitchSample> {
+        let (Some(running), Some((kind, samples))) = (&self.running, &self.previous) else {
+            return Vec::new();
+        };
+        if running.kind != *kind {
+            return Vec::new();
+        }
+        let elapsed_ms = now_ms - running.start_ms;
+        let first = samples.partition_point(|sample| sample.timestamp_ms < elapsed_ms - window_ms);
+        let last = samples.partition_point(|sample| sample.timestamp_ms <= elapsed_ms);
+        let mut replay = PitchHistory::new(window_ms);
+        for sample in &samples[first..last] {
+            let timestamp_ms = running.start_ms + sample.timestamp_ms;
+            replay.record(timestamp_ms, Some(PitchSample { timestamp_ms, ..*sample }));
+        }
+        replay.record(now_ms, None);
+        replay.decimated(window_ms, points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp_ms: f64, frequency: f32) -> PitchSample {
+        PitchSample { timestamp_ms, frequency, clarity: 0.9, cents_offset: 0.0 }
+    }
+
+    /// Record one sample every 100 ms from `start_ms` to `end_ms`
+    fn sing(ghost: &mut AttemptGhost, start_ms: f64, end_ms: f64, frequency: f32) {
+        let mut now_ms = start_ms;
+        while now_ms <= end_ms {
+            ghost.record(now_ms, Some(sample(now_ms, frequency)));
+            now_ms += 100.0;
+        }
+    }
+
+    #[test]
+    fn test_finished_attempt_is_replayed_from_the_next_start() {
+        let mut ghost = AttemptGhost::default();
+        ghost.start(ExerciseKind::AscendingScale, 1000.0);
+        sing(&mut ghost, 1000.0, 3000.0, 440.0);
+        assert!(ghost.trail(3000.0, 10_000.0, 100).is_empty());
+        ghost.finish();
+
+        ghost.start(ExerciseKind::AscendingScale, 50_000.0);
+        let trail = ghost.trail(50_500.0, 10_000.0, 1000);
+        // Samples from the first 500 ms of the previous attempt, moved to the running one
+        assert_eq!(trail.len(), 6);
+        assert!(trail.iter().all(|sample| sample.frequency == 440.0));
+        assert!(trail.iter().all(|sample| (50_000.0..=50_500.0).contains(&sample.timestamp_ms)));
+
+        let trail = ghost.trail(60_000.0, 1000.0, 10);
+        assert!(trail.is_empty());
+    }
+
+    #[test]
+    fn test_stopped_attempts_and_other_kinds_keep_the_ghost() {
+        let mut ghost = AttemptGhost::default();
+        ghost.start(ExerciseKind::DescendingScale, 0.0);
+        sing(&mut ghost, 0.0, 1000.0, 330.0);
+        ghost.finish();
+
+        ghost.start(ExerciseKind::DescendingScale, 5000.0);
+        sing(&mut ghost, 5000.0, 5500.0, 220.0);
+        ghost.abandon();
+        assert!(ghost.trail(5500.0, 10_000.0, 100).is_empty());
+
+        ghost.start(ExerciseKind::RandomIntervals, 8000.0);
+        assert!(ghost.trail(8500.0, 10_000.0, 100).is_empty());
+
+        ghost.start(ExerciseKind::DescendingScale, 9000.0);
+        let trail = ghost.trail(9500.0, 10_000.0, 100);
+        assert!(!trail.is_empty() && trail.iter().all(|sample| sample.frequency == 330.0));
+    }
+}
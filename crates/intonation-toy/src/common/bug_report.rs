@@ -0,0 +1,261 @@
+//! Bug reports to paste into an issue
+//!
+//! A report names the build and the browser, describes the crash or error
+//! with its stack, lists the latest entries of the event log and ends with
+//! the last session snapshot. The log keeps the warnings and errors of the
+//! app and its usage events; none of it leaves the page unless the user
+//! copies a report.
+//!
+//! Stacks of wasm frames name Rust functions either mangled, as
+//! `_ZN14intonation_toy6engine6update17h0123456789abcdefE`, or demangled
+//! with a hash, as `intonation_toy::engine::update::h0123456789abcdef`.
+//! `symbolicate` turns both into `intonation_toy::engine::update` and drops
+//! the frames of the panic machinery above the function that panicked.
+//! Frames without a name, `wasm-function[1234]`, are left as they are.
+
+use std::collections::VecDeque;
+use crate::app_config::{BUG_REPORT_LOG_CAPACITY, BUG_REPORT_MAX_MESSAGE_LENGTH};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub time_ms: f64,
+    pub message: String,
+}
+
+/// The latest log entries, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+}
+
+impl EventLog {
+    /// Add an entry, shortened to the longest message kept, and forget the
+    /// oldest beyond the capacity
+    pub fn push(&mut self, time_ms: f64, message: &str) {
+        let message = match message.char_indices().nth(BUG_REPORT_MAX_MESSAGE_LENGTH) {
+            Some((end, _)) => format!("{}…", &message[..end]),
+            None => message.to_string(),
+        };
+        if self.entries.len() == BUG_REPORT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry { time_ms, message });
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+}
+
+/// What went wrong: a panic, or an error the browser reported
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrashInfo {
+    pub message: String,
+    /// Source file, line and column
+    pub location: Option<String>,
+    pub stack: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BugReport<'a> {
+    pub version: &'a str,
+    pub user_agent: &'a str,
+    pub crash: Option<&'a CrashInfo>,
+    pub log: &'a EventLog,
+    /// Session snapshot as JSON
+    pub snapshot: Option<&'a str>,
+    /// Time of the report, from which the log entries are dated
+    pub now_ms: f64,
+}
+
+impl BugReport<'_> {
+    /// The report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("## Bug report\n\nVersion: {}\nBrowser: {}\n", self.version, self.user_agent);
+
+        if let Some(crash) = self.crash {
+            text.push_str(&format!("\n### Error\n\n{}\n", crash.message));
+            if let Some(location) = &crash.location {
+                text.push_str(&format!("at {}\n", location));
+            }
+            if let Some(stack) = &crash.stack {
+                text.push_str(&format!("\n```\n{}\n```\n", symbolicate(stack)));
+            }
+        }
+
+        text.push_str("\n### Recent log\n\n");
+        let mut empty = true;
+        for entry in self.log.entries() {
+            text.push_str(&format!("- {:.1} s ago: {}\n", (self.now_ms - entry.time_ms).max(0.0) / 1000.0, entry.message));
+            empty = false;
+        }
+        if empty {
+            text.push_str("(empty)\n");
+        }
+
+        if let Some(snapshot) = self.snapshot {
+            text.push_str(&format!("\n### Session snapshot\n\n```json\n{}\n```\n", snapshot));
+        }
+        text
+    }
+}
+
+/// Frames of the panic machinery, which sit above the frame that panicked
+const PANIC_FRAMES: [&str; 5] = [
+    "console_error_panic_hook::",
+    "std::panicking::",
+    "std::panic::",
+    "core::panicking::",
+    "__rust_start_panic",
+];
+
+/// Readable Rust names in a stack, without the frames of the panic machinery
+pub fn symbolicate(stack: &str) -> String {
+    stack
+        .lines()
+        .map(symbolicate_frame)
+        .filter(|frame| !PANIC_FRAMES.iter().any(|panic_frame| frame.contains(panic_frame)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn symbolicate_frame(frame: &str) -> String {
+    let mut result = String::new();
+    let mut rest = frame;
+    while let Some(start) = rest.find("_ZN") {
+        result.push_str(&rest[..start]);
+        match demangle_legacy(&rest[start..]) {
+            Some((name, length)) => {
+                result.push_str(&name);
+                rest = &rest[start + length..];
+            }
+            None => {
+                result.push_str("_ZN");
+                rest = &rest[start + 3..];
+            }
+        }
+    }
+    result.push_str(rest);
+    strip_hashes(&result)
+}
+
+/// Demangle a legacy Rust symbol at the start of `symbol`, returning the
+/// path and the length of the mangled symbol
+fn demangle_legacy(symbol: &str) -> Option<(String, usize)> {
+    let bytes = symbol.as_bytes();
+    let mut position = 3;
+    let mut components = Vec::new();
+    while *bytes.get(position)? != b'E' {
+        let digits = bytes[position..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+        if digits == 0 {
+            return None;
+        }
+        let length: usize = symbol[position..position + digits].parse().ok()?;
+        position += digits;
+        let component = symbol.get(position..position + length)?;
+        position += length;
+        if !is_hash(component) {
+            components.push(unescape(component));
+        }
+    }
+    (!components.is_empty()).then(|| (components.join("::"), position + 1))
+}
+
+fn is_hash(component: &str) -> bool {
+    component.len() == 17 && component.starts_with('h') && component[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Undo the escapes of legacy mangling, as `$LT$` for `<`
+fn unescape(component: &str) -> String {
+    const ESCAPES: [(&str, &str); 13] = [
+        ("$SP$", "@"), ("$BP$", "*"), ("$RF$", "&"), ("$LT$", "<"), ("$GT$", ">"), ("$LP$", "("), ("$RP$", ")"),
+        ("$C$", ","), ("$u20$", " "), ("$u27$", "'"), ("$u5b$", "["), ("$u5d$", "]"), ("$u7e$", "~"),
+    ];
+    let mut text = component.strip_prefix("_$").map(|rest| format!("${}", rest)).unwrap_or_else(|| component.to_string());
+    for (escape, replacement) in ESCAPES {
+        text = text.replace(escape, replacement);
+    }
+    text.replace("$u7b$", "{").replace("$u7d$", "}").replace("..", "::")
+}
+
+/// Remove the `::h0123456789abcdef` suffixes of demangled names
+fn strip_hashes(frame: &str) -> String {
+    let mut result = String::new();
+    let mut rest = frame;
+    while let Some(start) = rest.find("::h") {
+        let candidate = &rest[start + 2..];
+        let is_suffix = candidate.get(..17).is_some_and(is_hash)
+            && !candidate[17..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+        result.push_str(&rest[..start]);
+        if is_suffix {
+            rest = &candidate[17..];
+        } else {
+            result.push_str("::h");
+            rest = &rest[start + 3..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbolicate_names_rust_frames() {
+        let stack = "\
+Error
+    at console_error_panic_hook::hook::h3f2a8b7c6d5e4f30 (intonation_toy_bg.wasm:0x1a2b)
+    at _ZN3std9panicking20rust_panic_with_hook17h0123456789abcdefE (intonation_toy_bg.wasm:0x2b3c)
+    at _ZN14intonation_toy6engine11AudioEngine6update17h0123456789abcdefE (intonation_toy_bg.wasm:0x3c4d)
+    at _ZN75_$LT$intonation_toy..model..DataModel$u20$as$u20$core..default..Default$GT$7default17hfedcba9876543210E (intonation_toy_bg.wasm:0x4d5e)
+    at intonation_toy::presentation::Presenter::render::h0a1b2c3d4e5f6a7b (intonation_toy_bg.wasm:0x5e6f)
+    at wasm-function[1234]:0x6f70";
+        assert_eq!(
+            symbolicate(stack),
+            "\
+Error
+    at intonation_toy::engine::AudioEngine::update (intonation_toy_bg.wasm:0x3c4d)
+    at <intonation_toy::model::DataModel as core::default::Default>::default (intonation_toy_bg.wasm:0x4d5e)
+    at intonation_toy::presentation::Presenter::render (intonation_toy_bg.wasm:0x5e6f)
+    at wasm-function[1234]:0x6f70"
+        );
+        assert_eq!(symbolicate_frame("_ZN broken"), "_ZN broken");
+        assert_eq!(symbolicate_frame("a::helper::hidden"), "a::helper::hidden");
+    }
+
+    #[test]
+    fn test_log_keeps_latest_short_entries() {
+        let mut log = EventLog::default();
+        for i in 0..BUG_REPORT_LOG_CAPACITY + 3 {
+            log.push(i as f64, &format!("entry {}", i));
+        }
+        assert_eq!(log.entries().count(), BUG_REPORT_LOG_CAPACITY);
+        assert_eq!(log.entries().next().unwrap().message, "entry 3");
+
+        log.push(0.0, &"é".repeat(BUG_REPORT_MAX_MESSAGE_LENGTH + 10));
+        assert_eq!(log.entries().last().unwrap().message.chars().count(), BUG_REPORT_MAX_MESSAGE_LENGTH + 1);
+    }
+
+    #[test]
+    fn test_report_lists_error_log_and_snapshot() {
+        let mut log = EventLog::default();
+        log.push(1000.0, "FeatureUsed(\"recording\")");
+        let crash = CrashInfo {
+            message: "index out of bounds".to_string(),
+            location: Some("src/model/mod.rs:12:5".to_string()),
+            stack: None,
+        };
+        let report = BugReport { version: "1.0.2", user_agent: "Firefox", crash: Some(&crash), log: &log, snapshot: Some("{}"), now_ms: 3500.0 };
+        let text = report.to_markdown();
+        assert!(text.contains("Version: 1.0.2\nBrowser: Firefox\n"));
+        assert!(text.contains("index out of bounds\nat src/model/mod.rs:12:5\n"));
+        assert!(text.contains("- 2.5 s ago: FeatureUsed(\"recording\")\n"));
+        assert!(text.ends_with("```json\n{}\n```\n"));
+
+        let report = BugReport { crash: None, log: &EventLog::default(), snapshot: None, ..report };
+        assert!(!report.to_markdown().contains("### Error"));
+        assert!(report.to_markdown().ends_with("(empty)\n"));
+    }
+}
@@ -0,0 +1,179 @@
+//! What the browser can do, and what the app does without the rest
+//!
+//! Without a required API the app cannot start. Every other API is optional:
+//! the app starts without it, with less, and tells the user what is limited.
+//! - Without AudioWorklet, input is captured with a ScriptProcessorNode on
+//!   the main thread, with more latency and without the input filters.
+//! - Without SharedArrayBuffer, samples reach the analyzers in messages
+//!   instead of a shared ring.
+//! - Without OffscreenCanvas, which only older browsers on slower machines
+//!   lack, the display renders at a lower resolution.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserApi {
+    WebAudioApi,
+    GetUserMedia,
+    AudioWorklet,
+    SharedArrayBuffer,
+    Canvas,
+    WebGL2,
+    OffscreenCanvas,
+}
+
+impl BrowserApi {
+    pub const ALL: [BrowserApi; 7] = [
+        BrowserApi::WebAudioApi,
+        BrowserApi::GetUserMedia,
+        BrowserApi::AudioWorklet,
+        BrowserApi::SharedArrayBuffer,
+        BrowserApi::Canvas,
+        BrowserApi::WebGL2,
+        BrowserApi::OffscreenCanvas,
+    ];
+
+    pub fn is_required(&self) -> bool {
+        self.limitation().is_none()
+    }
+
+    /// What is limited without an optional API; `None` for a required one
+    pub fn limitation(&self) -> Option<Limitation> {
+        match self {
+            BrowserApi::AudioWorklet => Some(Limitation::MainThreadInput),
+            BrowserApi::SharedArrayBuffer => Some(Limitation::MessageBatches),
+            BrowserApi::OffscreenCanvas => Some(Limitation::ReducedRendering),
+            BrowserApi::WebAudioApi | BrowserApi::GetUserMedia | BrowserApi::Canvas | BrowserApi::WebGL2 => None,
+        }
+    }
+}
+
+impl fmt::Display for BrowserApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrowserApi::WebAudioApi => write!(f, "Web Audio API"),
+            BrowserApi::GetUserMedia => write!(f, "getUserMedia API"),
+            BrowserApi::AudioWorklet => write!(f, "AudioWorklet"),
+            BrowserApi::SharedArrayBuffer => write!(f, "SharedArrayBuffer"),
+            BrowserApi::Canvas => write!(f, "Canvas"),
+            BrowserApi::WebGL2 => write!(f, "WebGL2"),
+            BrowserApi::OffscreenCanvas => write!(f, "OffscreenCanvas"),
+        }
+    }
+}
+
+/// Something the app does with less because an optional API is missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limitation {
+    /// Input is captured on the main thread, without the input filters
+    MainThreadInput,
+    /// Samples are passed in messages rather than shared memory
+    MessageBatches,
+    /// The display renders at a lower resolution
+    ReducedRendering,
+}
+
+impl Limitation {
+    /// Localization key of the description shown to the user
+    pub fn key(&self) -> &'static str {
+        match self {
+            Limitation::MainThreadInput => "limitation-main-thread-input",
+            Limitation::MessageBatches => "limitation-message-batches",
+            Limitation::ReducedRendering => "limitation-reduced-rendering",
+        }
+    }
+}
+
+/// How fully the app runs in this browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegradationTier {
+    Full,
+    /// Running with the limitations of the missing optional APIs
+    Reduced,
+    /// A required API is missing
+    Unsupported,
+}
+
+/// The APIs the browser lacks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapabilityReport {
+    missing: Vec<BrowserApi>,
+}
+
+impl CapabilityReport {
+    /// Report from whether each API is supported
+    pub fn from_support(support: impl IntoIterator<Item = (BrowserApi, bool)>) -> Self {
+        let mut report = Self::default();
+        for (api, _) in support.into_iter().filter(|(_, supported)| !supported) {
+            report.mark_missing(api);
+        }
+        report
+    }
+
+    /// Count `api` as missing, as when it turns out unusable after the check
+    pub fn mark_missing(&mut self, api: BrowserApi) {
+        if !self.missing.contains(&api) {
+            self.missing.push(api);
+        }
+    }
+
+    pub fn is_supported(&self, api: BrowserApi) -> bool {
+        !self.missing.contains(&api)
+    }
+
+    pub fn missing_required(&self) -> Vec<BrowserApi> {
+        self.missing.iter().copied().filter(BrowserApi::is_required).collect()
+    }
+
+    /// Limitations of the missing optional APIs, in the order of `BrowserApi::ALL`
+    pub fn limitations(&self) -> Vec<Limitation> {
+        BrowserApi::ALL
+            .iter()
+            .filter(|api| !self.is_supported(**api))
+            .filter_map(BrowserApi::limitation)
+            .collect()
+    }
+
+    pub fn has_limitation(&self, limitation: Limitation) -> bool {
+        self.limitations().contains(&limitation)
+    }
+
+    pub fn tier(&self) -> DegradationTier {
+        if !self.missing_required().is_empty() {
+            DegradationTier::Unsupported
+        } else if self.missing.is_empty() {
+            DegradationTier::Full
+        } else {
+            DegradationTier::Reduced
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(missing: &[BrowserApi]) -> CapabilityReport {
+        CapabilityReport::from_support(BrowserApi::ALL.map(|api| (api, !missing.contains(&api))))
+    }
+
+    #[test]
+    fn test_tiers() {
+        assert_eq!(report(&[]).tier(), DegradationTier::Full);
+        assert_eq!(report(&[BrowserApi::SharedArrayBuffer]).tier(), DegradationTier::Reduced);
+        assert_eq!(report(&[BrowserApi::AudioWorklet, BrowserApi::WebGL2]).tier(), DegradationTier::Unsupported);
+        assert_eq!(report(&[BrowserApi::AudioWorklet, BrowserApi::WebGL2]).missing_required(), vec![BrowserApi::WebGL2]);
+    }
+
+    #[test]
+    fn test_limitations_follow_missing_apis() {
+        let mut report = report(&[BrowserApi::OffscreenCanvas, BrowserApi::SharedArrayBuffer]);
+        assert_eq!(report.limitations(), vec![Limitation::MessageBatches, Limitation::ReducedRendering]);
+        assert!(!report.has_limitation(Limitation::MainThreadInput));
+
+        report.mark_missing(BrowserApi::AudioWorklet);
+        report.mark_missing(BrowserApi::AudioWorklet);
+        assert!(!report.is_supported(BrowserApi::AudioWorklet));
+        assert_eq!(report.limitations().len(), 3);
+    }
+}
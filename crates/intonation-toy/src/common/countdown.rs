@@ -0,0 +1,50 @@
+//! Count-in before a recording or an exercise starts
+//!
+//! A count-in shows whole beats counting down to 1 and is over once the last
+//! beat has passed. Time is in milliseconds, as from `js_sys::Date::now`.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Countdown {
+    start_ms: f64,
+    beats: u32,
+    beat_ms: f64,
+}
+
+impl Countdown {
+    pub fn new(now_ms: f64, beats: u32, beat_ms: f64) -> Self {
+        Self { start_ms: now_ms, beats, beat_ms }
+    }
+
+    /// Beat to show, from the number of beats down to 1; `None` once the
+    /// count-in is over
+    pub fn remaining_beats(&self, now_ms: f64) -> Option<u32> {
+        let passed = ((now_ms - self.start_ms) / self.beat_ms).max(0.0).floor() as u32;
+        (passed < self.beats).then(|| self.beats - passed)
+    }
+
+    /// Part of the current beat that has passed, 0.0 to 1.0
+    pub fn beat_progress(&self, now_ms: f64) -> f32 {
+        ((now_ms - self.start_ms) / self.beat_ms).max(0.0).fract() as f32
+    }
+
+    pub fn is_over(&self, now_ms: f64) -> bool {
+        self.remaining_beats(now_ms).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_down_whole_beats() {
+        let countdown = Countdown::new(1000.0, 3, 500.0);
+        let beats: Vec<_> = [900.0, 1000.0, 1499.0, 1500.0, 2250.0, 2499.0, 2500.0]
+            .into_iter()
+            .map(|now_ms| countdown.remaining_beats(now_ms))
+            .collect();
+        assert_eq!(beats, [Some(3), Some(3), Some(3), Some(2), Some(1), Some(1), None]);
+        assert!((countdown.beat_progress(1750.0) - 0.5).abs() < 1e-6);
+        assert!(!countdown.is_over(2000.0) && countdown.is_over(2500.0));
+    }
+}
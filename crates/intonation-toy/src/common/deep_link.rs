@@ -0,0 +1,122 @@
+//! Settings carried in the address of the page
+//!
+//! A link sets the tonal center, the scale and the tuning system, and may
+//! start an exercise, through parameters in the query or the fragment:
+//! `?root=D4&scale=minor&tuning=just&exercise=ascending-scale`. The root is
+//! a letter name with an octave, as `Bb3` or `C#5` with the sharp written
+//! `%23`, or a MIDI note number. Parameters that are missing or not
+//! understood leave the setting alone.
+
+use crate::common::shared_types::{ExerciseKind, MidiNote, Scale, TuningSystem, midi_note_to_name};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeepLink {
+    pub tonal_center_note: Option<MidiNote>,
+    pub scale: Option<Scale>,
+    pub tuning_system: Option<TuningSystem>,
+    pub exercise: Option<ExerciseKind>,
+}
+
+impl DeepLink {
+    /// Settings of a query or fragment, with or without its leading `?` or `#`
+    pub fn parse(params: &str) -> Self {
+        let mut link = Self::default();
+        let params = params.trim_start_matches(['?', '#']);
+        for (name, value) in params.split('&').filter_map(|param| param.split_once('=')) {
+            let value = value.replace("%23", "#");
+            match name {
+                "root" => link.tonal_center_note = parse_note(&value),
+                "scale" => link.scale = Scale::from_id(&value),
+                "tuning" => link.tuning_system = TuningSystem::from_id(&value),
+                "exercise" => link.exercise = ExerciseKind::from_id(&value),
+                _ => {}
+            }
+        }
+        link
+    }
+
+    /// Whether the link sets anything
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Query for the settings, with its leading `?`; custom scales and tuning
+    /// systems are left out, since links cannot carry them
+    pub fn to_query(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(note) = self.tonal_center_note {
+            params.push(format!("root={}", midi_note_to_name(note)));
+        }
+        if let Some(scale) = self.scale.filter(|scale| !matches!(scale, Scale::Custom(_))) {
+            params.push(format!("scale={}", scale.id()));
+        }
+        if let Some(tuning_system) = self.tuning_system.as_ref().filter(|tuning_system| !matches!(tuning_system, TuningSystem::Custom(_))) {
+            params.push(format!("tuning={}", tuning_system.id()));
+        }
+        if let Some(exercise) = self.exercise {
+            params.push(format!("exercise={}", exercise.id()));
+        }
+        format!("?{}", params.join("&"))
+    }
+}
+
+/// MIDI note of a letter name with an octave, or of a MIDI note number
+fn parse_note(text: &str) -> Option<MidiNote> {
+    if let Ok(note) = text.parse::<MidiNote>() {
+        return (note <= 127).then_some(note);
+    }
+    let mut chars = text.chars();
+    let letter = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.chars().next() {
+        Some('#') => (1, &rest[1..]),
+        Some('b') => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let note = (octave.parse::<i32>().ok()? + 1) * 12 + letter + accidental;
+    MidiNote::try_from(note).ok().filter(|note| *note <= 127)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let link = DeepLink::parse("?root=D4&scale=minor&tuning=just&exercise=ascending-scale&unknown=1");
+        assert_eq!(link.tonal_center_note, Some(62));
+        assert_eq!(link.scale, Some(Scale::Minor));
+        assert_eq!(link.tuning_system.as_ref().map(TuningSystem::id), Some("just"));
+        assert_eq!(link.exercise, Some(ExerciseKind::AscendingScale));
+
+        assert_eq!(DeepLink::parse("#root=Bb3").tonal_center_note, Some(58));
+        assert_eq!(DeepLink::parse("root=F%233").tonal_center_note, Some(54));
+        assert_eq!(DeepLink::parse("root=69").tonal_center_note, Some(69));
+        assert!(DeepLink::parse("root=H2&scale=nonsense&tuning=").is_empty());
+        assert!(DeepLink::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_query_round_trip() {
+        let link = DeepLink {
+            tonal_center_note: Some(61),
+            scale: Some(Scale::HarmonicMinor),
+            tuning_system: Some(TuningSystem::Pythagorean),
+            exercise: Some(ExerciseKind::RandomIntervals),
+        };
+        assert_eq!(link.to_query(), "?root=Db4&scale=harmonic_minor&tuning=pythagorean&exercise=random-intervals");
+        assert_eq!(DeepLink::parse(&link.to_query()), link);
+
+        let custom = DeepLink { scale: Some(Scale::Custom(0b1001)), ..DeepLink::default() };
+        assert_eq!(custom.to_query(), "?");
+    }
+}
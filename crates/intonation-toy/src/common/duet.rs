@@ -0,0 +1,259 @@
+//! Duet mode: a teacher and a student playing together from afar
+//!
+//! The two apps connect peer to peer and talk over a data channel. The
+//! teacher's app sends its reference, the tonal center with the concert pitch
+//! and the volume of its drone, which the student's app plays as its own
+//! drone; sending the reference instead of the sound keeps the drone clean
+//! whatever the connection. The student's app sends back the detected pitch
+//! a few times a second, which the teacher's app shows.
+//!
+//! Messages are a few bytes each, a tag followed by little-endian fields,
+//! and are sent unordered without retransmission: a lost pitch is soon
+//! replaced, and a late one is dropped for the newer already received. The
+//! reference is sent as it changes and again at an interval, so the student's
+//! drone catches up with a reference that was lost or overtaken.
+//!
+//! Before the peers connect they exchange session descriptions through a
+//! `Signaling`, which may be the user copying codes between the two apps.
+
+use serde::{Deserialize, Serialize};
+use crate::app_config::{DUET_PITCH_INTERVAL_MS, DUET_PITCH_TIMEOUT_MS, DUET_REFERENCE_RESEND_MS};
+use crate::common::shared_types::MidiNote;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuetRole {
+    /// Sends the reference and sees the pitch of the student
+    Teacher,
+    /// Plays the reference of the teacher and sends its pitch
+    Student,
+}
+
+const REFERENCE_TAG: u8 = 1;
+const PITCH_TAG: u8 = 2;
+
+/// Message on the data channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuetMessage {
+    Reference { tonal_center_note: MidiNote, concert_pitch_hz: f32, drone_amplitude: f32 },
+    /// Pitch of the student, `None` while silent, at a time in milliseconds
+    /// since the student's first pitch
+    Pitch { time_ms: u32, frequency_hz: Option<f32> },
+}
+
+impl DuetMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        match *self {
+            DuetMessage::Reference { tonal_center_note, concert_pitch_hz, drone_amplitude } => {
+                let mut bytes = vec![REFERENCE_TAG, tonal_center_note];
+                bytes.extend_from_slice(&concert_pitch_hz.to_le_bytes());
+                bytes.extend_from_slice(&drone_amplitude.to_le_bytes());
+                bytes
+            }
+            DuetMessage::Pitch { time_ms, frequency_hz } => {
+                let mut bytes = vec![PITCH_TAG];
+                bytes.extend_from_slice(&time_ms.to_le_bytes());
+                bytes.extend_from_slice(&frequency_hz.unwrap_or(0.0).to_le_bytes());
+                bytes
+            }
+        }
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let f32_at = |index: usize| -> Result<f32, String> {
+            let field = bytes.get(index..index + 4).ok_or_else(|| format!("Duet message of {} bytes is too short", bytes.len()))?;
+            Ok(f32::from_le_bytes(field.try_into().unwrap()))
+        };
+        match bytes.first() {
+            Some(&REFERENCE_TAG) => Ok(DuetMessage::Reference {
+                tonal_center_note: *bytes.get(1).ok_or("Duet reference is too short")?,
+                concert_pitch_hz: f32_at(2)?,
+                drone_amplitude: f32_at(6)?,
+            }),
+            Some(&PITCH_TAG) => {
+                let time = bytes.get(1..5).ok_or("Duet pitch is too short")?;
+                let frequency_hz = f32_at(5)?;
+                Ok(DuetMessage::Pitch {
+                    time_ms: u32::from_le_bytes(time.try_into().unwrap()),
+                    frequency_hz: (frequency_hz > 0.0).then_some(frequency_hz),
+                })
+            }
+            Some(tag) => Err(format!("Unknown duet message {}", tag)),
+            None => Err("Empty duet message".to_string()),
+        }
+    }
+}
+
+/// Session description passed between the peers before they connect. It is
+/// sent once all its ICE candidates are gathered, so no more follow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "sdp", rename_all = "lowercase")]
+pub enum SignalMessage {
+    Offer(String),
+    Answer(String),
+}
+
+impl SignalMessage {
+    /// The message as a code to copy to the other app
+    pub fn to_code(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_code(code: &str) -> Result<Self, String> {
+        serde_json::from_str(code.trim()).map_err(|e| format!("Not a duet code: {}", e))
+    }
+}
+
+/// Way of passing signal messages to the other peer, which delivers the
+/// messages of the other peer to the connection as they arrive
+pub trait Signaling {
+    fn send(&self, message: &SignalMessage);
+}
+
+/// What the app does about a message of the other peer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuetAction {
+    /// Play the teacher's reference
+    ApplyReference { tonal_center_note: MidiNote, concert_pitch_hz: f32, drone_amplitude: f32 },
+}
+
+/// One side of a connected duet
+#[derive(Debug, Clone)]
+pub struct DuetLink {
+    role: DuetRole,
+    /// Reference last sent, and when
+    last_reference: Option<(DuetMessage, f64)>,
+    first_pitch_sent_ms: Option<f64>,
+    last_pitch_sent_ms: Option<f64>,
+    /// Latest pitch of the student, with its time as sent and when it arrived
+    remote_pitch: Option<(u32, Option<f32>, f64)>,
+}
+
+impl DuetLink {
+    pub fn new(role: DuetRole) -> Self {
+        Self { role, last_reference: None, first_pitch_sent_ms: None, last_pitch_sent_ms: None, remote_pitch: None }
+    }
+
+    pub fn role(&self) -> DuetRole {
+        self.role
+    }
+
+    /// The teacher's reference, when it changed since last sent or was last
+    /// sent a resend interval ago
+    pub fn reference_to_send(&mut self, now_ms: f64, tonal_center_note: MidiNote, concert_pitch_hz: f32, drone_amplitude: f32) -> Option<DuetMessage> {
+        if self.role != DuetRole::Teacher {
+            return None;
+        }
+        let reference = DuetMessage::Reference { tonal_center_note, concert_pitch_hz, drone_amplitude };
+        let due = self.last_reference.is_none_or(|(last, sent_ms)| last != reference || now_ms - sent_ms >= DUET_REFERENCE_RESEND_MS);
+        due.then(|| {
+            self.last_reference = Some((reference, now_ms));
+            reference
+        })
+    }
+
+    /// The student's pitch, at most once every pitch interval
+    pub fn pitch_to_send(&mut self, now_ms: f64, frequency_hz: Option<f32>) -> Option<DuetMessage> {
+        if self.role != DuetRole::Student || self.last_pitch_sent_ms.is_some_and(|last| now_ms - last < DUET_PITCH_INTERVAL_MS) {
+            return None;
+        }
+        self.last_pitch_sent_ms = Some(now_ms);
+        let first_ms = *self.first_pitch_sent_ms.get_or_insert(now_ms);
+        Some(DuetMessage::Pitch { time_ms: (now_ms - first_ms) as u32, frequency_hz })
+    }
+
+    /// Send the reference again, as when the connection opens anew
+    pub fn resend_reference(&mut self) {
+        self.last_reference = None;
+    }
+
+    pub fn on_message(&mut self, message: DuetMessage, now_ms: f64) -> Option<DuetAction> {
+        match (self.role, message) {
+            (DuetRole::Student, DuetMessage::Reference { tonal_center_note, concert_pitch_hz, drone_amplitude }) => {
+                Some(DuetAction::ApplyReference { tonal_center_note, concert_pitch_hz, drone_amplitude })
+            }
+            (DuetRole::Teacher, DuetMessage::Pitch { time_ms, frequency_hz }) => {
+                if self.remote_pitch.is_none_or(|(latest, _, _)| time_ms > latest) {
+                    self.remote_pitch = Some((time_ms, frequency_hz, now_ms));
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// The student's latest pitch, unless it is silent or not heard from lately
+    pub fn remote_pitch(&self, now_ms: f64) -> Option<f32> {
+        self.remote_pitch
+            .filter(|(_, _, received_ms)| now_ms - received_ms <= DUET_PITCH_TIMEOUT_MS)
+            .and_then(|(_, frequency_hz, _)| frequency_hz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_round_trip_compactly() {
+        let reference = DuetMessage::Reference { tonal_center_note: 57, concert_pitch_hz: 442.0, drone_amplitude: 0.25 };
+        assert_eq!(reference.encode().len(), 10);
+        assert_eq!(DuetMessage::decode(&reference.encode()), Ok(reference));
+
+        let pitch = DuetMessage::Pitch { time_ms: 123_456, frequency_hz: Some(220.5) };
+        assert_eq!(pitch.encode().len(), 9);
+        assert_eq!(DuetMessage::decode(&pitch.encode()), Ok(pitch));
+        let silent = DuetMessage::Pitch { time_ms: 1, frequency_hz: None };
+        assert_eq!(DuetMessage::decode(&silent.encode()), Ok(silent));
+
+        assert!(DuetMessage::decode(&[]).is_err());
+        assert!(DuetMessage::decode(&[9, 0]).is_err());
+        assert!(DuetMessage::decode(&pitch.encode()[..6]).is_err());
+    }
+
+    #[test]
+    fn test_student_sends_throttled_pitch_and_plays_reference() {
+        let mut teacher = DuetLink::new(DuetRole::Teacher);
+        let mut student = DuetLink::new(DuetRole::Student);
+        assert_eq!(teacher.pitch_to_send(0.0, Some(440.0)), None);
+
+        let reference = teacher.reference_to_send(0.0, 60, 440.0, 0.5).unwrap();
+        assert_eq!(teacher.reference_to_send(0.0, 60, 440.0, 0.5), None);
+        assert_eq!(
+            student.on_message(reference, 0.0),
+            Some(DuetAction::ApplyReference { tonal_center_note: 60, concert_pitch_hz: 440.0, drone_amplitude: 0.5 })
+        );
+
+        let first = student.pitch_to_send(1000.0, Some(261.6)).unwrap();
+        assert_eq!(student.pitch_to_send(1000.0 + DUET_PITCH_INTERVAL_MS / 2.0, Some(262.0)), None);
+        let second = student.pitch_to_send(1000.0 + DUET_PITCH_INTERVAL_MS, Some(262.0)).unwrap();
+
+        // The newer pitch stays when the older arrives late
+        assert_eq!(teacher.on_message(second, 5000.0), None);
+        teacher.on_message(first, 5000.0);
+        assert_eq!(teacher.remote_pitch(5000.0), Some(262.0));
+        assert_eq!(teacher.remote_pitch(5000.0 + DUET_PITCH_TIMEOUT_MS + 1.0), None);
+    }
+
+    #[test]
+    fn test_teacher_resends_unchanged_reference() {
+        let mut teacher = DuetLink::new(DuetRole::Teacher);
+        let reference = teacher.reference_to_send(0.0, 60, 440.0, 0.5).unwrap();
+        assert_eq!(teacher.reference_to_send(DUET_REFERENCE_RESEND_MS / 2.0, 60, 440.0, 0.5), None);
+        // A lost reference reaches the student with the next resend
+        assert_eq!(teacher.reference_to_send(DUET_REFERENCE_RESEND_MS, 60, 440.0, 0.5), Some(reference));
+        assert_eq!(teacher.reference_to_send(DUET_REFERENCE_RESEND_MS + 1.0, 60, 440.0, 0.5), None);
+
+        // A change goes out at once, and restarts the interval
+        let changed = teacher.reference_to_send(DUET_REFERENCE_RESEND_MS + 2.0, 62, 440.0, 0.5);
+        assert_eq!(changed, Some(DuetMessage::Reference { tonal_center_note: 62, concert_pitch_hz: 440.0, drone_amplitude: 0.5 }));
+        assert_eq!(teacher.reference_to_send(DUET_REFERENCE_RESEND_MS * 1.5, 62, 440.0, 0.5), None);
+    }
+
+    #[test]
+    fn test_signal_codes() {
+        let offer = SignalMessage::Offer("v=0\r\n".to_string());
+        assert_eq!(offer.to_code(), r#"{"type":"offer","sdp":"v=0\r\n"}"#);
+        assert_eq!(SignalMessage::from_code(&format!("  {}\n", offer.to_code())), Ok(offer));
+        assert!(SignalMessage::from_code("hello").is_err());
+    }
+}
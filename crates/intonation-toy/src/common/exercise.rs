@@ -0,0 +1,225 @@
+//! Target-note drills
+//!
+//! An exercise is a sequence of targets, each a number of semitones above the
+//! tonal center, so changing the tonal center mid-exercise moves the targets
+//! with it. A target is reached once the pitch stays within the tolerance for
+//! the hold time without interruption; the exercise then moves on to the next.
+
+use crate::common::shared_types::{ExerciseKind, ExerciseStatus, ExerciseTargetResult, MidiNote, Scale};
+
+#[derive(Debug, Clone)]
+pub struct Exercise {
+    kind: ExerciseKind,
+    /// Semitones above the tonal center
+    targets: Vec<i32>,
+    hold_ms: f64,
+    results: Vec<ExerciseTargetResult>,
+    /// When the current target was first presented
+    target_started_ms: Option<f64>,
+    /// When the pitch last entered the tolerance, and the cents sum and count since
+    hold_started_ms: Option<f64>,
+    hold_cents_sum: f32,
+    hold_frames: u32,
+    hold_progress: f32,
+}
+
+impl Exercise {
+    /// `seed` picks the random intervals; the other kinds ignore it
+    pub fn new(kind: ExerciseKind, scale: Scale, target_count: usize, hold_ms: f64, seed: u32) -> Self {
+        let degrees: Vec<i32> = (0..12).filter(|&semitone| scale.pattern()[semitone as usize]).collect();
+        let targets = match kind {
+            ExerciseKind::AscendingScale => degrees.iter().copied().chain([12]).collect(),
+            ExerciseKind::DescendingScale => [12].into_iter().chain(degrees.iter().rev().copied()).collect(),
+            ExerciseKind::RandomIntervals => random_intervals(&degrees, target_count, seed),
+        };
+        Self {
+            kind,
+            targets,
+            hold_ms,
+            results: Vec::new(),
+            target_started_ms: None,
+            hold_started_ms: None,
+            hold_cents_sum: 0.0,
+            hold_frames: 0,
+            hold_progress: 0.0,
+        }
+    }
+
+    /// Number of targets reached so far
+    pub fn reached_count(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.results.len() >= self.targets.len()
+    }
+
+    /// Note to sing now, or `None` when the exercise is finished or the target
+    /// falls outside the MIDI range; `update` drops such targets
+    pub fn current_target(&self, tonal_center_note: MidiNote) -> Option<MidiNote> {
+        let semitones = self.targets.get(self.results.len())?;
+        MidiNote::try_from(tonal_center_note as i32 + semitones).ok().filter(|&note| note <= 127)
+    }
+
+    /// Advance to `now_ms`; `target_cents_offset` is the deviation of the
+    /// detected pitch from the current target, if a pitch was detected
+    pub fn update(&mut self, now_ms: f64, target_cents_offset: Option<f32>, tolerance_cents: f32, tonal_center_note: MidiNote) {
+        // Targets the tonal center put outside the MIDI range cannot be sung
+        while !self.is_finished() && self.current_target(tonal_center_note).is_none() {
+            self.targets.remove(self.results.len());
+            self.target_started_ms = None;
+            self.reset_hold();
+        }
+        let Some(note) = self.current_target(tonal_center_note) else {
+            return;
+        };
+        let target_started_ms = *self.target_started_ms.get_or_insert(now_ms);
+
+        let Some(cents) = target_cents_offset.filter(|cents| cents.abs() <= tolerance_cents) else {
+            self.reset_hold();
+            return;
+        };
+        let hold_started_ms = *self.hold_started_ms.get_or_insert(now_ms);
+        self.hold_cents_sum += cents;
+        self.hold_frames += 1;
+        self.hold_progress = ((now_ms - hold_started_ms) / self.hold_ms).min(1.0) as f32;
+
+        if now_ms - hold_started_ms >= self.hold_ms {
+            self.results.push(ExerciseTargetResult {
+                note,
+                seconds_to_reach: ((hold_started_ms - target_started_ms) / 1000.0) as f32,
+                mean_cents: self.hold_cents_sum / self.hold_frames as f32,
+            });
+            self.target_started_ms = Some(now_ms);
+            self.reset_hold();
+        }
+    }
+
+    fn reset_hold(&mut self) {
+        self.hold_started_ms = None;
+        self.hold_cents_sum = 0.0;
+        self.hold_frames = 0;
+        self.hold_progress = 0.0;
+    }
+
+    pub fn status(&self, tonal_center_note: MidiNote) -> ExerciseStatus {
+        ExerciseStatus {
+            kind: self.kind,
+            target_count: self.targets.len(),
+            current_target: self.current_target(tonal_center_note),
+            hold_progress: self.hold_progress,
+            results: self.results.clone(),
+        }
+    }
+}
+
+/// `count` degrees of the scale up to the octave, never the same one twice in
+/// a row and never the unison, which needs no ear
+fn random_intervals(degrees: &[i32], count: usize, seed: u32) -> Vec<i32> {
+    let choices: Vec<i32> = degrees.iter().copied().filter(|&semitones| semitones != 0).chain([12]).collect();
+    // xorshift32 needs a non-zero state
+    let mut state = seed.max(1);
+    let mut targets: Vec<i32> = Vec::with_capacity(count);
+    while targets.len() < count {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let semitones = choices[state as usize % choices.len()];
+        if choices.len() == 1 || targets.last() != Some(&semitones) {
+            targets.push(semitones);
+        }
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: f64 = 10.0;
+    const HOLD_MS: f64 = 500.0;
+    const TOLERANCE_CENTS: f32 = 10.0;
+
+    /// Sing `cents` off the current target for `frames` frames starting at `*now_ms`
+    fn sing(exercise: &mut Exercise, now_ms: &mut f64, frames: usize, cents: Option<f32>) {
+        for _ in 0..frames {
+            exercise.update(*now_ms, cents, TOLERANCE_CENTS, 60);
+            *now_ms += FRAME_MS;
+        }
+    }
+
+    #[test]
+    fn test_scale_targets() {
+        let ascending = Exercise::new(ExerciseKind::AscendingScale, Scale::MajorPentatonic, 0, HOLD_MS, 1);
+        assert_eq!(ascending.targets, vec![0, 2, 4, 7, 9, 12]);
+        let descending = Exercise::new(ExerciseKind::DescendingScale, Scale::MajorPentatonic, 0, HOLD_MS, 1);
+        assert_eq!(descending.targets, vec![12, 9, 7, 4, 2, 0]);
+        assert_eq!(ascending.current_target(60), Some(60));
+        assert_eq!(descending.current_target(120), None);
+    }
+
+    #[test]
+    fn test_random_intervals() {
+        let exercise = Exercise::new(ExerciseKind::RandomIntervals, Scale::Major, 20, HOLD_MS, 12345);
+        assert_eq!(exercise.targets.len(), 20);
+        assert!(exercise.targets.iter().all(|semitones| [2, 4, 5, 7, 9, 11, 12].contains(semitones)));
+        assert!(exercise.targets.windows(2).all(|pair| pair[0] != pair[1]));
+        let again = Exercise::new(ExerciseKind::RandomIntervals, Scale::Major, 20, HOLD_MS, 12345);
+        assert_eq!(exercise.targets, again.targets);
+    }
+
+    #[test]
+    fn test_holding_in_tune_advances() {
+        let mut exercise = Exercise::new(ExerciseKind::AscendingScale, Scale::Major, 0, HOLD_MS, 1);
+        let mut now_ms = 0.0;
+        sing(&mut exercise, &mut now_ms, 30, None);
+        sing(&mut exercise, &mut now_ms, 20, Some(30.0));
+        assert!(exercise.results.is_empty());
+
+        sing(&mut exercise, &mut now_ms, 51, Some(4.0));
+        assert_eq!(exercise.results.len(), 1);
+        let result = exercise.results[0];
+        assert_eq!(result.note, 60);
+        assert!((result.seconds_to_reach - 0.5).abs() < 1e-6);
+        assert!((result.mean_cents - 4.0).abs() < 1e-6);
+        assert_eq!(exercise.current_target(60), Some(62));
+    }
+
+    #[test]
+    fn test_interruption_restarts_the_hold() {
+        let mut exercise = Exercise::new(ExerciseKind::AscendingScale, Scale::Major, 0, HOLD_MS, 1);
+        let mut now_ms = 0.0;
+        sing(&mut exercise, &mut now_ms, 40, Some(0.0));
+        assert!((exercise.status(60).hold_progress - 0.78).abs() < 1e-6);
+        sing(&mut exercise, &mut now_ms, 1, Some(-20.0));
+        assert_eq!(exercise.status(60).hold_progress, 0.0);
+        sing(&mut exercise, &mut now_ms, 40, Some(0.0));
+        assert!(exercise.results.is_empty());
+    }
+
+    #[test]
+    fn test_finishes_after_last_target() {
+        let mut exercise = Exercise::new(ExerciseKind::RandomIntervals, Scale::Major, 2, HOLD_MS, 7);
+        let mut now_ms = 0.0;
+        sing(&mut exercise, &mut now_ms, 200, Some(0.0));
+        assert!(exercise.is_finished());
+        let status = exercise.status(60);
+        assert_eq!(status.results.len(), 2);
+        assert_eq!(status.current_target, None);
+    }
+
+    #[test]
+    fn test_skips_targets_outside_the_midi_range() {
+        let mut exercise = Exercise::new(ExerciseKind::AscendingScale, Scale::Major, 0, HOLD_MS, 1);
+        let mut now_ms = 0.0;
+        // From the tonal center 120 the sixth, seventh and octave lie above 127
+        for _ in 0..300 {
+            exercise.update(now_ms, Some(0.0), TOLERANCE_CENTS, 120);
+            now_ms += FRAME_MS;
+        }
+        assert!(exercise.is_finished());
+        let status = exercise.status(120);
+        assert_eq!(status.target_count, 5);
+        assert_eq!(status.results.iter().map(|result| result.note).collect::<Vec<_>>(), vec![120, 122, 124, 125, 127]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Vertical axis of the display, placing intervals from the tonal center
+//!
+//! An axis is the interval at the middle of the display and the part of the
+//! display height an octave takes. Each display range has an axis of its own,
+//! and a zoom narrows it around a note. Axes are tweened, so the tuning lines
+//! and the pitch glide to their new places when the range or zoom changes.
+
+use crate::common::shared_types::{DisplayRange, IntervalZoom};
+use crate::common::tween::Lerp;
+
+/// Part of the display height an octave takes in the two octave range, whose
+/// octaves either side of the tonal center leave a margin at the edges
+const TWO_OCTAVES_HEIGHT_PER_OCTAVE: f32 = 0.46;
+/// Zooming in stays within the octave either side of the tonal center, where
+/// the tuning lines are drawn
+const MAX_ZOOM_CENTER_OCTAVES: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalAxis {
+    /// Interval from the tonal center, in octaves, at the middle of the display
+    pub center_octaves: f32,
+    pub height_per_octave: f32,
+}
+
+impl IntervalAxis {
+    pub fn of_range(display_range: &DisplayRange) -> Self {
+        let full_octave = 2.0 * TWO_OCTAVES_HEIGHT_PER_OCTAVE;
+        match display_range {
+            DisplayRange::TwoOctaves => Self { center_octaves: 0.0, height_per_octave: TWO_OCTAVES_HEIGHT_PER_OCTAVE },
+            DisplayRange::OneFullOctave => Self { center_octaves: 0.5, height_per_octave: full_octave },
+            // Lifted a little so the octave below is not cut off by the bottom edge
+            DisplayRange::TwoHalfOctaves => Self { center_octaves: 0.077 / full_octave, height_per_octave: full_octave },
+        }
+    }
+
+    /// Axis of the display range, or with a zoom the axis showing its span
+    /// either side of `focus_octaves` with the margins of the two octave range
+    pub fn zoomed(display_range: &DisplayRange, zoom: IntervalZoom, focus_octaves: f32) -> Self {
+        let Some(half_span_semitones) = zoom.half_span_semitones() else {
+            return Self::of_range(display_range);
+        };
+        Self {
+            center_octaves: focus_octaves.clamp(-MAX_ZOOM_CENTER_OCTAVES, MAX_ZOOM_CENTER_OCTAVES),
+            height_per_octave: TWO_OCTAVES_HEIGHT_PER_OCTAVE * 12.0 / half_span_semitones,
+        }
+    }
+
+    /// Height of an interval as a part of the display height, from the bottom
+    pub fn y_fraction(&self, interval_octaves: f32) -> f32 {
+        0.5 + (interval_octaves - self.center_octaves) * self.height_per_octave
+    }
+}
+
+impl Lerp for IntervalAxis {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            center_octaves: self.center_octaves.lerp(other.center_octaves, t),
+            height_per_octave: self.height_per_octave.lerp(other.height_per_octave, t),
+        }
+    }
+}
+
+/// Move an interval by whole octaves to within half an octave of `center`
+pub fn wrap_octaves(octaves: f32, center: f32) -> f32 {
+    octaves - (octaves - center).round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_display_ranges() {
+        let two_octaves = IntervalAxis::of_range(&DisplayRange::TwoOctaves);
+        assert!(approx(two_octaves.y_fraction(-1.0), 0.04) && approx(two_octaves.y_fraction(1.0), 0.96));
+
+        let one_octave = IntervalAxis::of_range(&DisplayRange::OneFullOctave);
+        assert!(approx(one_octave.y_fraction(0.0), 0.04) && approx(one_octave.y_fraction(1.0), 0.96));
+
+        let two_half_octaves = IntervalAxis::of_range(&DisplayRange::TwoHalfOctaves);
+        assert!(approx(two_half_octaves.y_fraction(0.0), 0.423));
+        assert!(approx(two_half_octaves.y_fraction(0.5), 0.883));
+    }
+
+    #[test]
+    fn test_zoom_around_focus() {
+        let fifth = 7.0 / 12.0;
+        let zoomed = IntervalAxis::zoomed(&DisplayRange::OneFullOctave, IntervalZoom::ThreeSemitones, fifth);
+        assert!(approx(zoomed.y_fraction(fifth), 0.5));
+        assert!(approx(zoomed.y_fraction(fifth - 0.25), 0.04) && approx(zoomed.y_fraction(fifth + 0.25), 0.96));
+
+        assert_eq!(IntervalAxis::zoomed(&DisplayRange::OneFullOctave, IntervalZoom::Full, fifth), IntervalAxis::of_range(&DisplayRange::OneFullOctave));
+        assert!(approx(IntervalAxis::zoomed(&DisplayRange::TwoOctaves, IntervalZoom::SixSemitones, 3.0).center_octaves, 1.0));
+
+        // Halfway through a zoom the axis lies between the two
+        let full = IntervalAxis::of_range(&DisplayRange::TwoOctaves);
+        let halfway = full.lerp(zoomed, 0.5);
+        assert!(halfway.height_per_octave > full.height_per_octave && halfway.height_per_octave < zoomed.height_per_octave);
+        assert!(approx(halfway.center_octaves, fifth * 0.5));
+    }
+
+    #[test]
+    fn test_wrap_octaves() {
+        assert!(approx(wrap_octaves(1.25, 0.5), 0.25));
+        assert!(approx(wrap_octaves(-0.75, 0.5), 0.25));
+        assert!(approx(wrap_octaves(0.75, 0.5), 0.75));
+    }
+}
@@ -0,0 +1,137 @@
+//! Key of recently sung notes
+//!
+//! The time spent on each pitch class is compared with the Krumhansl-Kessler
+//! key profiles, the perceived stability of each degree in major and minor
+//! keys, for all 24 keys. The key whose profile correlates best wins, provided
+//! enough was sung and the fit is clear.
+
+use std::collections::VecDeque;
+
+use crate::common::shared_types::SungNote;
+
+const MAJOR_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+/// Sung time needed before a key is estimated
+const MIN_SUNG_MS: f64 = 5_000.0;
+/// Weakest correlation with the key profile that counts as a key
+const MIN_CORRELATION: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMode {
+    Major,
+    Minor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEstimate {
+    /// 0 for C up to 11 for B
+    pub root_pitch_class: u8,
+    pub mode: KeyMode,
+    /// Correlation of the sung pitch classes with the key profile, up to 1.0
+    pub correlation: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyEstimator {
+    window_ms: f64,
+    /// End time, pitch class and duration of each note, oldest first
+    notes: VecDeque<(f64, u8, f64)>,
+}
+
+impl KeyEstimator {
+    /// Consider notes that ended within the last `window_ms`
+    pub fn new(window_ms: f64) -> Self {
+        Self {
+            window_ms,
+            notes: VecDeque::new(),
+        }
+    }
+
+    pub fn add(&mut self, note: &SungNote) {
+        self.notes.push_back((note.start_ms + note.duration_ms, note.note % 12, note.duration_ms));
+    }
+
+    pub fn clear(&mut self) {
+        self.notes.clear();
+    }
+
+    /// Most likely key at `now_ms`, or `None` without enough clear evidence
+    pub fn estimate(&mut self, now_ms: f64) -> Option<KeyEstimate> {
+        while self.notes.front().is_some_and(|&(end_ms, _, _)| now_ms - end_ms > self.window_ms) {
+            self.notes.pop_front();
+        }
+
+        let mut durations = [0.0f32; 12];
+        for &(_, pitch_class, duration_ms) in &self.notes {
+            durations[pitch_class as usize] += duration_ms as f32;
+        }
+        if durations.iter().sum::<f32>() < MIN_SUNG_MS as f32 {
+            return None;
+        }
+
+        (0..12u8)
+            .flat_map(|root| [(root, KeyMode::Major, &MAJOR_PROFILE), (root, KeyMode::Minor, &MINOR_PROFILE)])
+            .filter_map(|(root, mode, profile)| {
+                let rotated: [f32; 12] = std::array::from_fn(|pitch_class| profile[(pitch_class + 12 - root as usize) % 12]);
+                correlation(&durations, &rotated).map(|correlation| KeyEstimate { root_pitch_class: root, mode, correlation })
+            })
+            .max_by(|a, b| a.correlation.total_cmp(&b.correlation))
+            .filter(|estimate| estimate.correlation >= MIN_CORRELATION)
+    }
+}
+
+/// Pearson correlation, `None` when either side is constant
+fn correlation(x: &[f32; 12], y: &[f32; 12]) -> Option<f32> {
+    let mean_x = x.iter().sum::<f32>() / 12.0;
+    let mean_y = y.iter().sum::<f32>() / 12.0;
+    let covariance: f32 = x.iter().zip(y).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance_x: f32 = x.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let variance_y: f32 = y.iter().map(|y| (y - mean_y).powi(2)).sum();
+    (variance_x > 0.0 && variance_y > 0.0).then(|| covariance / (variance_x * variance_y).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::MidiNote;
+
+    /// Estimator fed `notes` back to back, each lasting `duration_ms`
+    fn sung(notes: &[MidiNote], duration_ms: f64) -> (KeyEstimator, f64) {
+        let mut estimator = KeyEstimator::new(60_000.0);
+        let mut now_ms = 0.0;
+        for &note in notes {
+            estimator.add(&SungNote { note, start_ms: now_ms, duration_ms, frequency: 0.0, cents_offset: 0.0, stability_cents: 0.0 });
+            now_ms += duration_ms;
+        }
+        (estimator, now_ms)
+    }
+
+    #[test]
+    fn test_major_melody() {
+        // D major scale up and down, with the tonic and fifth held longer
+        let (mut estimator, now_ms) = sung(&[62, 64, 66, 67, 69, 69, 71, 73, 74, 74, 69, 66, 62, 62], 500.0);
+        let estimate = estimator.estimate(now_ms).expect("a key");
+        assert_eq!((estimate.root_pitch_class, estimate.mode), (2, KeyMode::Major));
+    }
+
+    #[test]
+    fn test_minor_melody() {
+        // A harmonic minor, the raised seventh sets it apart from C major
+        let (mut estimator, now_ms) = sung(&[57, 59, 60, 62, 64, 64, 65, 68, 69, 69, 64, 60, 57, 57], 500.0);
+        let estimate = estimator.estimate(now_ms).expect("a key");
+        assert_eq!((estimate.root_pitch_class, estimate.mode), (9, KeyMode::Minor));
+    }
+
+    #[test]
+    fn test_needs_enough_recent_singing() {
+        let (mut estimator, now_ms) = sung(&[60, 62, 64], 500.0);
+        assert_eq!(estimator.estimate(now_ms), None);
+
+        // A chromatic line fits no key
+        let (mut estimator, now_ms) = sung(&(60..72).collect::<Vec<_>>(), 1000.0);
+        assert_eq!(estimator.estimate(now_ms), None);
+
+        let (mut estimator, now_ms) = sung(&[62, 64, 66, 67, 69, 69, 71, 73, 74, 74, 69, 66, 62, 62], 500.0);
+        assert_eq!(estimator.estimate(now_ms + 61_000.0), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Placement of the labels beside the tuning lines
+//!
+//! Lines closer together than a label is tall would have their labels drawn
+//! over each other. Labels are placed in order of priority, octave lines
+//! first, each moved the least it takes to clear the labels already placed,
+//! and left out when that would move it too far from its line. Labels of
+//! crowded lines also fade as the lines close in, so zooming out thins them
+//! gradually rather than all at once.
+
+/// How far a label may move from its line, as a part of the label height
+const MAX_SHIFT: f32 = 0.5;
+/// Spacing to the nearest line, as a part of the label height, at which a
+/// label starts to fade and at which it is gone
+const FADE_START_SPACING: f32 = 1.5;
+const FADE_END_SPACING: f32 = 0.75;
+
+/// Line a label belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelAnchor {
+    pub y: f32,
+    /// Placed first and never faded
+    pub priority: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelPlacement {
+    pub y: f32,
+    pub alpha: f32,
+}
+
+/// Placement of the label of every anchor, `None` for a label left out
+pub fn place_labels(anchors: &[LabelAnchor], label_height: f32) -> Vec<Option<LabelPlacement>> {
+    let mut order: Vec<usize> = (0..anchors.len()).collect();
+    order.sort_by(|&a, &b| anchors[b].priority.cmp(&anchors[a].priority).then(anchors[a].y.total_cmp(&anchors[b].y)));
+
+    let max_shift = MAX_SHIFT * label_height;
+    let clears = |y: f32, placed: &[f32]| placed.iter().all(|other| (y - other).abs() >= label_height - 1e-3);
+    let mut placed: Vec<f32> = Vec::new();
+    let mut placements = vec![None; anchors.len()];
+    for index in order {
+        let anchor = anchors[index];
+        let alpha = if anchor.priority { 1.0 } else { fade(nearest_spacing(anchors, index) / label_height) };
+        if alpha <= 0.0 {
+            continue;
+        }
+        // The closest free spot is at the line or right next to a placed label
+        let candidates = std::iter::once(anchor.y).chain(placed.iter().flat_map(|other| [other - label_height, other + label_height]));
+        let Some(y) = candidates
+            .filter(|y| (y - anchor.y).abs() <= max_shift && clears(*y, &placed))
+            .min_by(|a, b| (a - anchor.y).abs().total_cmp(&(b - anchor.y).abs()))
+        else {
+            continue;
+        };
+        placed.push(y);
+        placements[index] = Some(LabelPlacement { y, alpha });
+    }
+    placements
+}
+
+fn nearest_spacing(anchors: &[LabelAnchor], index: usize) -> f32 {
+    anchors.iter()
+        .enumerate()
+        .filter(|&(other, _)| other != index)
+        .map(|(_, other)| (other.y - anchors[index].y).abs())
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn fade(spacing: f32) -> f32 {
+    ((spacing - FADE_END_SPACING) / (FADE_START_SPACING - FADE_END_SPACING)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchors(lines: &[(f32, bool)]) -> Vec<LabelAnchor> {
+        lines.iter().map(|&(y, priority)| LabelAnchor { y, priority }).collect()
+    }
+
+    #[test]
+    fn test_spaced_labels_stay_at_their_lines() {
+        let placements = place_labels(&anchors(&[(100.0, true), (140.0, false), (180.0, false)]), 20.0);
+        assert_eq!(placements, [
+            Some(LabelPlacement { y: 100.0, alpha: 1.0 }),
+            Some(LabelPlacement { y: 140.0, alpha: 1.0 }),
+            Some(LabelPlacement { y: 180.0, alpha: 1.0 }),
+        ]);
+    }
+
+    #[test]
+    fn test_crowded_labels_move_fade_or_go() {
+        // 17 apart: the label fades a little and moves clear of the octave label
+        let placements = place_labels(&anchors(&[(100.0, true), (117.0, false)]), 20.0);
+        assert_eq!(placements[0], Some(LabelPlacement { y: 100.0, alpha: 1.0 }));
+        let moved = placements[1].unwrap();
+        assert_eq!(moved.y, 120.0);
+        assert!(moved.alpha > 0.0 && moved.alpha < 1.0);
+
+        // Too close to fit: the octave label wins wherever it lies
+        let placements = place_labels(&anchors(&[(100.0, false), (102.0, false), (104.0, true)]), 20.0);
+        assert_eq!(placements, [None, None, Some(LabelPlacement { y: 104.0, alpha: 1.0 })]);
+
+        // A run of lines closer than the labels are tall thins out and fades,
+        // without labels overlapping
+        let run: Vec<_> = (0..5).map(|line| (line as f32 * 8.0, false)).collect();
+        let placements = place_labels(&anchors(&run), 10.0);
+        let mut shown: Vec<_> = placements.iter().flatten().collect();
+        assert!(!shown.is_empty() && shown.len() < run.len());
+        assert!(shown.iter().all(|placement| placement.alpha < 1.0));
+        shown.sort_by(|a, b| a.y.total_cmp(&b.y));
+        assert!(shown.windows(2).all(|pair| pair[1].y - pair[0].y >= 10.0 - 1e-3));
+    }
+}
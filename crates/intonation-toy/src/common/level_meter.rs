@@ -0,0 +1,95 @@
+//! Input level bar with a peak marker and a clip light
+//!
+//! Levels are placed on a decibel scale so the quiet end of the range gets as
+//! much of the bar as the loud end. The peak marker holds the highest recent
+//! peak for a moment before falling, and the clip light stays on for a while
+//! after the input peaks so a short clip is not missed.
+
+use crate::app_config::{LEVEL_METER_CLIP_HOLD_MS, LEVEL_METER_PEAK_FALL_RATE, LEVEL_METER_PEAK_HOLD_MS, LEVEL_METER_RANGE_DB};
+use crate::common::shared_types::Volume;
+
+/// Parts of the bar, 0.0 at the bottom of the range and 1.0 at full scale
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelReading {
+    pub rms: f32,
+    pub peak: f32,
+    pub peak_hold: f32,
+    pub clipping: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LevelMeter {
+    peak_hold: f32,
+    peak_hold_since_ms: f64,
+    clip_until_ms: f64,
+    last_ms: Option<f64>,
+}
+
+impl LevelMeter {
+    pub fn update(&mut self, now_ms: f64, volume: &Volume, is_peaking: bool) -> LevelReading {
+        let elapsed_seconds = self.last_ms.map_or(0.0, |last_ms| (now_ms - last_ms).max(0.0) / 1000.0) as f32;
+        self.last_ms = Some(now_ms);
+
+        let peak = bar_fraction(volume.peak_amplitude);
+        if peak >= self.peak_hold {
+            self.peak_hold = peak;
+            self.peak_hold_since_ms = now_ms;
+        } else if now_ms - self.peak_hold_since_ms > LEVEL_METER_PEAK_HOLD_MS {
+            self.peak_hold = (self.peak_hold - LEVEL_METER_PEAK_FALL_RATE * elapsed_seconds).max(peak);
+        }
+        if is_peaking {
+            self.clip_until_ms = now_ms + LEVEL_METER_CLIP_HOLD_MS;
+        }
+
+        LevelReading {
+            rms: bar_fraction(volume.rms_amplitude),
+            peak,
+            peak_hold: self.peak_hold,
+            clipping: now_ms < self.clip_until_ms,
+        }
+    }
+}
+
+fn bar_fraction(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    (1.0 + 20.0 * amplitude.log10() / LEVEL_METER_RANGE_DB).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(peak_amplitude: f32, rms_amplitude: f32) -> Volume {
+        Volume { peak_amplitude, rms_amplitude, ..Volume::SILENCE }
+    }
+
+    #[test]
+    fn test_levels_on_decibel_scale() {
+        let reading = LevelMeter::default().update(0.0, &volume(1.0, 0.001), false);
+        assert_eq!(reading.peak, 1.0);
+        assert!(reading.rms.abs() < 1e-4);
+        let half_range = 10f32.powf(-LEVEL_METER_RANGE_DB / 40.0);
+        let reading = LevelMeter::default().update(0.0, &volume(half_range, 0.0), false);
+        assert!((reading.peak - 0.5).abs() < 1e-4);
+        assert_eq!(reading.rms, 0.0);
+    }
+
+    #[test]
+    fn test_peak_hold_and_clip_light() {
+        let mut meter = LevelMeter::default();
+        meter.update(0.0, &volume(1.0, 0.5), true);
+
+        let held = meter.update(LEVEL_METER_PEAK_HOLD_MS, &volume(0.0, 0.0), false);
+        assert_eq!(held.peak_hold, 1.0);
+        assert!(held.clipping);
+
+        // One second after the hold ends
+        let falling_ms = LEVEL_METER_CLIP_HOLD_MS.max(LEVEL_METER_PEAK_HOLD_MS + 1000.0);
+        let falling = meter.update(falling_ms, &volume(0.0, 0.0), false);
+        let fallen = LEVEL_METER_PEAK_FALL_RATE * ((falling_ms - LEVEL_METER_PEAK_HOLD_MS) / 1000.0) as f32;
+        assert!((falling.peak_hold - (1.0 - fallen)).abs() < 1e-4);
+        assert!(!falling.clipping);
+    }
+}
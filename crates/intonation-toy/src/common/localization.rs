@@ -0,0 +1,185 @@
+//! Texts of the user interface in the language chosen by the user
+//!
+//! Every text is looked up by a key in a table per language. Texts of the page
+//! are keyed by the `data-i18n` attributes in `index.html`, and attributes by
+//! `data-i18n-title`, `data-i18n-alt` and `data-i18n-aria-label`; texts set
+//! from code use keys of their own. A key missing from a table falls back to
+//! English, so a new text can ship before it is translated.
+//!
+//! To add a language, add a variant to `Language` and a table in a file of
+//! its own; `html_keys` lists the keys of the page and `missing_keys` those
+//! still to translate.
+
+mod english;
+mod swedish;
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Stored by its code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "sv")]
+    Swedish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Swedish];
+
+    /// Language code as in the `lang` attribute
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Swedish => "sv",
+        }
+    }
+
+    /// Language of a code, ignoring any region, so `sv-SE` is Swedish
+    pub fn from_code(code: &str) -> Option<Language> {
+        let primary = code.split(['-', '_']).next().unwrap_or(code);
+        Self::ALL.into_iter().find(|language| language.code().eq_ignore_ascii_case(primary))
+    }
+
+    /// Name of the language in the language itself
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Swedish => "Svenska",
+        }
+    }
+
+    /// Whether letter names write B natural as H and B flat as B
+    pub fn writes_b_natural_as_h(&self) -> bool {
+        matches!(self, Language::Swedish)
+    }
+
+    fn texts(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => english::TEXTS,
+            Language::Swedish => swedish::TEXTS,
+        }
+    }
+}
+
+static CURRENT_LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+pub fn current_language() -> Language {
+    Language::ALL
+        .get(CURRENT_LANGUAGE.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+pub fn set_current_language(language: Language) {
+    CURRENT_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// Text of a key in a language, without falling back
+pub fn translate_in(language: Language, key: &str) -> Option<&'static str> {
+    language.texts().iter().find(|(text_key, _)| *text_key == key).map(|(_, text)| *text)
+}
+
+/// Text of a key in the current language, else in English, else the key
+/// itself so a missing text shows where it belongs
+pub fn translate(key: &'static str) -> &'static str {
+    translate_in(current_language(), key)
+        .or_else(|| translate_in(Language::English, key))
+        .unwrap_or(key)
+}
+
+/// Text of a key with its `{}` placeholders filled in order
+pub fn translate_with(key: &'static str, params: &[&str]) -> String {
+    fill_placeholders(translate(key), params)
+}
+
+pub fn fill_placeholders(template: &str, params: &[&str]) -> String {
+    let mut result = template.to_string();
+    let mut from = 0;
+    for param in params {
+        let Some(pos) = result[from..].find("{}").map(|pos| pos + from) else {
+            break;
+        };
+        result.replace_range(pos..pos + 2, param);
+        from = pos + param.len();
+    }
+    result
+}
+
+/// English keys without a text in a language
+pub fn missing_keys(language: Language) -> Vec<&'static str> {
+    english::TEXTS
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| translate_in(language, key).is_none())
+        .collect()
+}
+
+/// Keys of the translated texts and attributes of a page
+pub fn html_keys(html: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    for attribute in ["data-i18n=\"", "data-i18n-title=\"", "data-i18n-alt=\"", "data-i18n-aria-label=\""] {
+        for (start, _) in html.match_indices(attribute) {
+            let value = &html[start + attribute.len()..];
+            if let Some(end) = value.find('"') {
+                keys.push(&value[..end]);
+            }
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX_HTML: &str = include_str!("../../index.html");
+
+    #[test]
+    fn test_every_page_key_has_english_text() {
+        let keys = html_keys(INDEX_HTML);
+        assert!(keys.len() > 100);
+        for key in keys {
+            let text = translate_in(Language::English, key);
+            assert!(text.is_some(), "no English text for {}", key);
+            // The page itself shows English until a language is applied
+            let text = text.unwrap();
+            assert!(INDEX_HTML.contains(&format!(">{}<", text)) || INDEX_HTML.contains(&format!("\"{}\"", text)),
+                "English text of {} differs from the page", key);
+        }
+    }
+
+    #[test]
+    fn test_tables_are_complete_and_unique() {
+        for language in Language::ALL {
+            assert_eq!(missing_keys(language), Vec::<&str>::new(), "{:?}", language);
+            let mut keys: Vec<_> = language.texts().iter().map(|(key, _)| *key).collect();
+            keys.sort_unstable();
+            keys.dedup();
+            assert_eq!(keys.len(), language.texts().len(), "duplicate keys in {:?}", language);
+            for (key, text) in language.texts() {
+                let english = translate_in(Language::English, key).unwrap();
+                assert_eq!(text.matches("{}").count(), english.matches("{}").count(), "placeholders of {} in {:?}", key, language);
+            }
+        }
+    }
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(Language::from_code("sv-SE"), Some(Language::Swedish));
+        assert_eq!(Language::from_code("EN_gb"), Some(Language::English));
+        assert_eq!(Language::from_code("de"), None);
+        for language in Language::ALL {
+            assert_eq!(Language::from_code(language.code()), Some(language));
+        }
+    }
+
+    #[test]
+    fn test_placeholders_and_fallback() {
+        assert_eq!(fill_placeholders("In tune {}% within {} cents", &["80", "{}"]), "In tune 80% within {} cents");
+        assert_eq!(fill_placeholders("{} and {}", &["a"]), "a and {}");
+        assert_eq!(translate_in(Language::Swedish, "no-such-key"), None);
+        assert_eq!(translate("no-such-key"), "no-such-key");
+    }
+}
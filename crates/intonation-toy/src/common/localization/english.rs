@@ -0,0 +1,381 @@
+//! English texts, the source every other language translates from
+
+pub const TEXTS: &[(&str, &str)] = &[
+    // Page, by the data-i18n keys of index.html
+    ("section-tonal-center", "Tonal Center"),
+    ("tonal-center-octave-down-title", "Octave down"),
+    ("tonal-center-octave-up-title", "Octave up"),
+    ("root-suggestion-apply", "Use"),
+    ("volume-icon-label", "Mute"),
+    ("tonal-center-volume-label", "Tonal center volume"),
+    ("tonal-center-help", "Match the note with your voice or instrument to explore intonation. Leave the volume at zero for visual-only tuning mode."),
+    ("section-reference-tone", "Reference Tone"),
+    ("reference-tone-timbre-sine", "Sine Tone"),
+    ("reference-tone-timbre-triangle", "Triangle Tone"),
+    ("reference-tone-timbre-organ", "Organ Tone"),
+    ("reference-tone-volume-label", "Volume"),
+    ("reference-tone-attack-label", "Attack"),
+    ("reference-tone-release-label", "Release"),
+    ("reference-tone-degrees-label", "Degree"),
+    ("reference-interval-label", "Semitones from the tonal center"),
+    ("reference-interval-play", "Play Interval"),
+    ("reference-tone-help", "Play a degree of the scale, or the note a number of semitones from the tonal center, to hear where to aim. The attack and release shape how the tone starts and fades."),
+    ("section-tuning-system", "Tuning System"),
+    ("tuning-system-equal", "Equal Temperament"),
+    ("tuning-system-just", "Just Intonation (5-limit)"),
+    ("tuning-system-just-7", "Just Intonation (7-limit)"),
+    ("tuning-system-pythagorean", "Pythagorean"),
+    ("tuning-system-meantone", "Quarter-Comma Meantone"),
+    ("tuning-system-werckmeister3", "Werckmeister III"),
+    ("tuning-system-custom", "Custom"),
+    ("just-ratios-help", "Ratio of each degree above the tonal center. The ratios must rise from 1/1 and stay below the octave 2/1."),
+    ("custom-tuning-help", "Offset of each degree from equal temperament in cents. Paste the contents of a Scala (.scl) file below to import a tuning, or export this one."),
+    ("scala-text-label", "Scala file"),
+    ("scala-import", "Import"),
+    ("scala-export", "Export"),
+    ("tuning-system-help", "Choose how notes are tuned: Equal Temperament divides the octave evenly, Just Intonation uses pure whole-number ratios built from primes up to 5 or 7, and the historical temperaments trade purity between keys."),
+    ("section-concert-pitch", "Concert Pitch"),
+    ("concert-pitch-415", "A4 = 415 Hz (Baroque)"),
+    ("concert-pitch-430", "A4 = 430 Hz (Classical)"),
+    ("concert-pitch-435", "A4 = 435 Hz (French)"),
+    ("concert-pitch-440", "A4 = 440 Hz (Standard)"),
+    ("concert-pitch-466", "A4 = 466 Hz (Chorton)"),
+    ("concert-pitch-help", "Choose the frequency of A4 that all other notes are tuned from. Many orchestras tune slightly above 440 Hz, and period ensembles often play at 415 Hz."),
+    ("section-instrument-key", "Instrument Key"),
+    ("transposition-c", "C (concert pitch)"),
+    ("transposition-b-flat", "B♭ (clarinet, trumpet, tenor sax)"),
+    ("transposition-e-flat", "E♭ (alto and baritone sax)"),
+    ("transposition-f", "F (horn, English horn)"),
+    ("transposition-a", "A (clarinet in A)"),
+    ("transposition-help", "Show note names as written for a transposing instrument. Tuning and frequencies stay at concert pitch."),
+    ("section-note-names", "Note Names"),
+    ("note-naming-flats", "Flats (B♭)"),
+    ("note-naming-sharps", "Sharps (A♯)"),
+    ("note-naming-by-key", "Sharps or flats by key"),
+    ("note-naming-solfege", "Solfège (movable do)"),
+    ("note-naming-sargam", "Sargam"),
+    ("note-naming-help", "Solfège and sargam name notes by their degree above the tonal center, with ' for each octave above and , for each octave below."),
+    ("section-tolerance", "Tolerance"),
+    ("tolerance-profile-beginner", "Beginner (±25¢)"),
+    ("tolerance-profile-intermediate", "Intermediate (±15¢)"),
+    ("tolerance-profile-advanced", "Advanced (±8¢)"),
+    ("tolerance-profile-custom", "Custom"),
+    ("tolerance-help", "Choose how close to a note your pitch must be to count as in tune. Moving the slider switches to a custom tolerance."),
+    ("section-scale", "Scale"),
+    ("scale-chromatic", "Chromatic"),
+    ("scale-major", "Major"),
+    ("scale-minor", "Minor"),
+    ("scale-harmonic-minor", "Harmonic Minor"),
+    ("scale-melodic-minor", "Melodic Minor"),
+    ("scale-major-pentatonic", "Major Pentatonic"),
+    ("scale-minor-pentatonic", "Minor Pentatonic"),
+    ("scale-blues", "Blues"),
+    ("scale-dorian", "Dorian"),
+    ("scale-phrygian", "Phrygian"),
+    ("scale-lydian", "Lydian"),
+    ("scale-mixolydian", "Mixolydian"),
+    ("scale-locrian", "Locrian"),
+    ("scale-whole-tone", "Whole Tone"),
+    ("scale-augmented", "Augmented"),
+    ("scale-diminished-half-whole", "Diminished (Half-Whole)"),
+    ("scale-diminished-whole-half", "Diminished (Whole-Half)"),
+    ("scale-hungarian-minor", "Hungarian Minor"),
+    ("scale-neapolitan-minor", "Neapolitan Minor"),
+    ("scale-neapolitan-major", "Neapolitan Major"),
+    ("scale-enigmatic", "Enigmatic"),
+    ("scale-persian", "Persian"),
+    ("scale-double-harmonic-major", "Double Harmonic Major"),
+    ("scale-altered", "Altered"),
+    ("scale-bebop-major", "Bebop Major"),
+    ("scale-bebop-dominant", "Bebop Dominant"),
+    ("scale-custom", "Custom"),
+    ("custom-scale-help", "Tick the degrees above the tonal center that belong to the scale. The tonal center always does."),
+    ("scale-help", "Choose a musical scale to define which notes are displayed."),
+    ("section-display", "Display"),
+    ("display-range-two-octaves", "Two Octaves"),
+    ("display-range-one-octave", "One Octave"),
+    ("display-range-two-half-octaves", "Two Half Octaves"),
+    ("display-range-help", "Choose the display range: Two full octaves, a single full octave, or a single octave centered around the tonal center."),
+    ("interval-zoom-full", "Full Range"),
+    ("interval-zoom-six-semitones", "Zoom to ±6 Semitones"),
+    ("interval-zoom-three-semitones", "Zoom to ±3 Semitones"),
+    ("interval-zoom-help", "Zoom in around the target note, or the note you are closest to when there is none, to see small deviations more clearly. The display glides along as the note changes."),
+    ("display-scene-intonation", "Current Pitch"),
+    ("display-scene-pitch-graph", "Pitch Graph"),
+    ("display-scene-strobe", "Strobe"),
+    ("display-scene-needle", "Needle"),
+    ("display-scene-help", "The pitch graph draws the last 10 seconds of your pitch against the scale lines, colored by how far it strays from them. The strobe turns clockwise when you are sharp and anticlockwise when flat, and stands still when in tune; the outer rings turn faster. The needle points at your offset from the nearest note, up to 50 cents either way."),
+    ("needle-damping-light", "Light Needle Damping"),
+    ("needle-damping-critical", "Critical Needle Damping"),
+    ("needle-damping-heavy", "Heavy Needle Damping"),
+    ("needle-damping-help", "How the needle follows your pitch. Light damping swings past the reading and back, critical damping settles quickest without overshooting, and heavy damping moves slowly and hides small wobbles."),
+    ("smoothing-ema", "Steady Smoothing"),
+    ("smoothing-adaptive-ema", "Adaptive Smoothing"),
+    ("smoothing-median", "Median Smoothing"),
+    ("smoothing-one-euro", "Speed-Dependent Smoothing"),
+    ("smoothing-none", "No Smoothing"),
+    ("smoothing-help", "How the detected pitch is smoothed. Steady smoothing evens out every reading alike, adaptive smoothing follows large changes faster, median smoothing drops single stray readings, and speed-dependent smoothing holds sustained tones still while following fast passages. Without smoothing you see every reading."),
+    ("pitch-detector-mcleod", "McLeod Pitch Detector"),
+    ("pitch-detector-yin", "YIN Pitch Detector"),
+    ("pitch-detector-autocorrelation", "Autocorrelation Pitch Detector"),
+    ("pitch-detector-power-label", "Power"),
+    ("pitch-detector-clarity-label", "Clarity"),
+    ("pitch-detector-help", "How the pitch is found in the sound. YIN suits most voices and instruments, McLeod follows fast passages closely, and autocorrelation is the simplest. Quieter sounds than the power and less clearly pitched sounds than the clarity show no pitch; raise them to ignore background noise, lower them for soft playing."),
+    ("ghost-trail-toggle", "Ghost of Previous Attempt"),
+    ("ghost-trail-help", "Draw your previous exercise attempt faintly behind the pitch graph, lined up from its start, to compare this attempt with the last one."),
+    ("piano-mode-none", "No Piano"),
+    ("piano-mode-tonal-center", "Piano Sets Tonal Center"),
+    ("piano-mode-target", "Piano Sets Target Note"),
+    ("piano-mode-help", "Show a piano along the bottom of the display with the notes of the scale lit. Click a key to make it the tonal center or the note to match; click the target again to clear it."),
+    ("staff-clef-none", "No Staff"),
+    ("staff-clef-treble", "Treble Clef Staff"),
+    ("staff-clef-bass", "Bass Clef Staff"),
+    ("staff-clef-alto", "Alto Clef Staff"),
+    ("staff-clef-help", "Show the target note and the note you are playing on a staff, written for the selected transposition."),
+    ("input-meters-toggle", "Input Meters"),
+    ("input-meters-help", "Show the input level with its recent peak and a clip light, and a ring that fills with the clarity of the detected pitch. A low bar means the microphone is too quiet; a full bar with a ring that keeps emptying means the pitch is unsteady or noisy."),
+    ("announce-pitch-toggle", "Announce Pitch"),
+    ("announce-pitch-help", "Have screen readers read out the closest note and whether you are in tune, sharp or flat once it holds for a moment. Every control in the sidebar works from the keyboard, and the display takes the keyboard shortcuts once it has focus."),
+    ("spectrogram-toggle", "Spectrogram"),
+    ("spectrogram-help", "Show the spectrum of the input over time along the bottom of the display. The slider sets how far below the loudest sound is still shaded."),
+    ("interval-mode-absolute", "Absolute Intervals"),
+    ("interval-mode-wrapped", "Wrapped Intervals"),
+    ("interval-mode-help", "Absolute intervals count every octave between your pitch and the tonal center. Wrapped intervals fold them into one octave, so the tonal center can sit far above or below your range."),
+    ("fullscreen-button", "Fullscreen"),
+    ("immersive-layout-toggle", "Immersive Layout"),
+    ("layout-help", "Fullscreen hides the browser around the page. The immersive layout lets the display fill the window and hides the sidebar until you move the pointer."),
+    ("mini-view-button", "Mini View"),
+    ("mini-view-help", "Keep the closest note and how far off it is in a small window over other tabs and windows, as when reading music in another tab."),
+    ("pitch-history-pane-toggle", "Pitch History Pane"),
+    ("spectrogram-pane-toggle", "Spectrogram Pane"),
+    ("statistics-pane-toggle", "Statistics Pane"),
+    ("panes-help", "Show more views in a column beside the display. Drag the lines between the panes to resize them; the layout is remembered. The spectrogram pane uses the range of the spectrogram slider."),
+    ("theme-dark", "Dark Theme"),
+    ("theme-light", "Light Theme"),
+    ("theme-autumn", "Autumn Theme"),
+    ("theme-sunset", "Sunset Theme"),
+    ("theme-deuteranopia", "Deuteranopia Theme"),
+    ("theme-protanopia", "Protanopia Theme"),
+    ("theme-tritanopia", "Tritanopia Theme"),
+    ("theme-high-contrast", "High Contrast Theme"),
+    ("theme-help", "Colors of the display and the sidebar. The deuteranopia, protanopia and tritanopia themes keep in tune and out of tune apart for the matching kind of color blindness."),
+    ("language-help", "Language of the sidebar, the overlays and the display. Note names follow the language, so Swedish names B natural H and B flat B."),
+    ("tutorial-replay-button", "Show Tutorial"),
+    ("tutorial-replay-help", "Walk through the tonal center, the pitch line and the drone again, as on the first start."),
+    ("install-app-button", "Install App"),
+    ("install-app-help", "Install the app to open it in a window of its own. Once loaded it also works without a network."),
+    ("section-input", "Input"),
+    ("input-device-label", "Microphone"),
+    ("input-device-help", "Choose the microphone to listen to. The list follows microphones as they are plugged in and out."),
+    ("peak-measure-sample-peak", "Flag Sample Peaks"),
+    ("peak-measure-loudness", "Flag Loudness"),
+    ("peak-measure-help", "Flag the input as too loud when its samples come close to clipping, or when its short-term loudness is high, which follows how loud it sounds."),
+    ("automatic-gain-toggle", "Automatic Gain"),
+    ("input-level-help", "Automatic gain keeps the input at a steady level. Calibrating measures the room noise and your voice once for this microphone, to tell them apart."),
+    ("section-midi", "MIDI"),
+    ("midi-toggle", "Use MIDI Devices"),
+    ("midi-input-mode-label", "MIDI keys set"),
+    ("midi-input-mode-tonal-center", "Keys Set the Tonal Center"),
+    ("midi-input-mode-target-pitch", "Keys Set the Target Note"),
+    ("midi-output-label", "MIDI output"),
+    ("midi-output-source-label", "Notes sent to the MIDI output"),
+    ("midi-output-source-off", "Send No Notes"),
+    ("midi-output-source-detected-note", "Send the Detected Note"),
+    ("midi-output-source-reference-tone", "Send Reference Tones"),
+    ("midi-help", "Set the tonal center or the target note from a MIDI keyboard, and send notes to a MIDI instrument. The browser asks for access the first time."),
+    ("section-exercise", "Exercise"),
+    ("exercise-ascending-scale", "Ascending Scale"),
+    ("exercise-descending-scale", "Descending Scale"),
+    ("exercise-random-intervals", "Random Intervals"),
+    ("count-in-toggle", "Count In"),
+    ("exercise-help", "Sing each target of the scale in turn and hold it in tune to move on to the next. The targets follow the tonal center and the scale. Count In counts three beats down on the display first."),
+    ("section-reference-melody", "Reference Melody"),
+    ("reference-melody-text-label", "Melody"),
+    ("reference-melody-from-recording", "Sing Along with Recording"),
+    ("reference-melody-help", "Sing along with a melody written as MIDI note:duration pairs in milliseconds, with r for a rest, or with the notes sung in the recording. Each note is played as it comes up, and the deviation of your pitch is shown once it ends."),
+    ("section-metronome", "Metronome"),
+    ("metronome-toggle", "Flash Beat"),
+    ("metronome-subdivision-beats", "Beats Only"),
+    ("metronome-subdivision-eighths", "Eighths"),
+    ("metronome-subdivision-triplets", "Triplets"),
+    ("metronome-subdivision-sixteenths", "Sixteenths"),
+    ("metronome-click-toggle", "Audible Click"),
+    ("metronome-help", "Flash the beat in the corner of the display, with a dot for every subdivision, and click along if you like. An exercise starts the beat over on each new target."),
+    ("section-recording", "Recording"),
+    ("recording-export-from-label", "From"),
+    ("recording-export-to-label", "To"),
+    ("recording-export-button", "Export WAV"),
+    ("recording-help", "Record the microphone for up to a minute and download a part of it as a WAV file. From and To count seconds back from the end of the recording. Count In counts three beats down on the display first."),
+    ("section-export", "Export"),
+    ("export-format-pitch-csv", "Pitch timeline (CSV)"),
+    ("export-format-statistics-csv", "Statistics per degree (CSV)"),
+    ("export-format-json", "Session (JSON)"),
+    ("export-button", "Export"),
+    ("export-help", "Download the pitch of the last 30 seconds or the statistics of the session for use in a spreadsheet or other tools."),
+    ("session-summary-button", "Show Summary"),
+    ("end-session-button", "End Session"),
+    ("session-summary-help", "Show charts of the session so far, or end the session to see them one last time and start over once they are closed."),
+    ("share-image-button", "Share Image"),
+    ("share-image-help", "Share a picture of the display with the statistics of the session, pressing the button again once the picture is ready, or download it where the browser cannot share files."),
+    ("share-link-button", "Copy Link"),
+    ("share-link-help", "Copy a link that opens the app with this tonal center, scale and tuning system. The address of the page always holds them too."),
+    ("section-saved-sessions", "Saved Sessions"),
+    ("save-session-button", "Save Session"),
+    ("saved-sessions-help", "Keep the settings, statistics, pitch history and exercise results of the session in the browser, with the input recording if one is running. When space runs out, delete saved sessions to make room."),
+    ("section-keyboard-shortcuts", "Keyboard Shortcuts"),
+    ("shortcut-tonal-center-up-label", "Tonal center up"),
+    ("shortcut-tonal-center-down-label", "Tonal center down"),
+    ("shortcut-tonal-center-octave-up-label", "Tonal center octave up"),
+    ("shortcut-tonal-center-octave-down-label", "Tonal center octave down"),
+    ("shortcut-toggle-drone-label", "Drone on/off"),
+    ("shortcut-scale-chromatic-label", "Chromatic scale"),
+    ("shortcut-scale-major-label", "Major scale"),
+    ("shortcut-scale-minor-label", "Minor scale"),
+    ("shortcut-scale-harmonicminor-label", "Harmonic Minor scale"),
+    ("shortcut-scale-melodicminor-label", "Melodic Minor scale"),
+    ("shortcut-scale-majorpentatonic-label", "Major Pentatonic scale"),
+    ("shortcut-scale-minorpentatonic-label", "Minor Pentatonic scale"),
+    ("shortcut-scale-blues-label", "Blues scale"),
+    ("shortcut-scale-dorian-label", "Dorian scale"),
+    ("shortcuts-help", "Shortcuts work while the display has focus. Click a key to change it, then press the new key, or Escape to keep the old one."),
+    ("session-summary-export", "Export Session (JSON)"),
+    ("session-summary-close", "Close"),
+    ("three-d-canvas-label", "Pitch display. The keyboard shortcuts work while it has focus."),
+    ("loading", "Loading..."),
+    ("section-about", "About"),
+    ("about-text", "is a real-time pitch analysis and visualization tool. Explore musical intonation by analyzing audio input and see how your pitch relates to different tuning systems. Headphones are recommended for listening to the tonal center."),
+    ("session-summary-title", "Session Summary"),
+    ("session-summary-degrees-title", "Time in tune on each degree"),
+    ("session-summary-histogram-title", "Time at each offset from the closest note, in cents"),
+    ("first-click-start", "Click anywhere to start"),
+    ("first-click-permission-note", "(Microphone permission will be requested)"),
+    ("tutorial-skip", "Skip"),
+    ("tutorial-back", "Back"),
+    ("touch-controls-toggle", "Controls"),
+    ("telemetry-toggle", "Share Anonymous Usage Data"),
+    ("telemetry-consent-text", "May Intonation Toy send anonymous usage data, such as which tuning systems and scales you use and how smoothly it runs? Nothing in it identifies you, and you can change your answer in the About section."),
+    ("telemetry-consent-accept", "Allow"),
+    ("telemetry-consent-decline", "No Thanks"),
+    ("bug-report-copy", "Copy Bug Report"),
+    ("bug-report-text-label", "Bug report"),
+    ("tab-role-standalone", "Standalone Tab"),
+    ("tab-role-controller", "Controller Tab"),
+    ("tab-role-display", "Display Tab"),
+    ("tab-role-help", "With the app open in two tabs or windows, make one the controller and the other its display, as when projecting the display while working the controls on a laptop. The display follows the tuning of the controller."),
+    ("tab-contention-warning", "The app listens to the microphone in another tab too. Close one of them, or make one the controller and the other its display."),
+    ("section-duet", "Duet (Experimental)"),
+    ("duet-host-button", "Teach"),
+    ("duet-connect-button", "Connect"),
+    ("duet-leave-button", "Leave"),
+    ("duet-local-code-label", "Your code"),
+    ("duet-remote-code-label", "Code from the other app"),
+    ("duet-help", "Play together from afar. The teacher presses Teach and sends the code to the student, who pastes it and presses Connect, then sends back the code that appears for the teacher to paste and connect. The student hears the teacher's drone, and the teacher sees the student's pitch."),
+    ("capability-limits-title", "Limited in this browser:"),
+    ("permission-guidance-title", "Microphone Blocked"),
+    ("permission-guidance-other", "Allow the microphone for this site in the settings of your browser, then click Try Again."),
+    ("permission-guidance-note", "Should the browser report the change, listening starts without a click."),
+    ("permission-guidance-retry", "Try Again"),
+    // Set from code
+    ("fullscreen-exit", "Exit Fullscreen"),
+    ("mini-view-close", "Close Mini View"),
+    ("share-link-copied", "Link copied"),
+    ("bug-report-copied", "Bug report copied"),
+    ("duet-status-offer", "Send this code to the student, then paste their code and press Connect."),
+    ("duet-status-answer", "Send this code back to the teacher."),
+    ("duet-status-connected", "Connected"),
+    ("duet-status-disconnected", "Not connected"),
+    ("duet-invalid-code", "This is not a duet code."),
+    ("duet-remote-pitch", "Student: {} {} cents"),
+    ("duet-remote-silent", "Student: silent"),
+    ("input-device-unnamed", "Microphone {}"),
+    ("recording-start", "Start Recording"),
+    ("recording-stop", "Stop Recording"),
+    ("recording-status-recording", "Recording {} s"),
+    ("recording-status-recorded", "Recorded {} s"),
+    ("midi-output-none", "No MIDI Output"),
+    ("calibration-start", "Calibrate"),
+    ("calibration-cancel", "Cancel Calibration"),
+    ("calibration-measuring-noise", "Stay quiet while the room noise is measured ({}%)"),
+    ("calibration-measuring-voice", "Sing or play at your loudest normal level ({}%)"),
+    ("calibration-failed", "Calibration failed: no clear voice above the noise"),
+    ("calibration-done", "Calibrated for this microphone"),
+    ("calibration-none", "Not calibrated"),
+    ("just-ratio-numerator", "Numerator of degree {}"),
+    ("just-ratio-denominator", "Denominator of degree {}"),
+    ("custom-tuning-offset", "Offset of degree {} in cents"),
+    ("scala-import-failed", "Could not read the Scala file: {}"),
+    ("exercise-start", "Start Exercise"),
+    ("exercise-stop", "Stop Exercise"),
+    ("exercise-status", "{} of {} reached"),
+    ("reference-melody-start", "Start Melody"),
+    ("reference-melody-stop", "Stop Melody"),
+    ("reference-melody-invalid", "Could not read the melody: {}"),
+    ("reference-melody-progress", "{}% played"),
+    ("count-in-cancel", "Cancel Count-In"),
+    ("share-image-ready", "Share Now"),
+    ("saved-sessions-saved", "Session saved"),
+    ("saved-sessions-full", "Not enough storage space to save the session. Delete saved sessions to make room."),
+    ("saved-sessions-failed", "The session could not be saved"),
+    ("saved-sessions-empty", "No saved sessions yet."),
+    ("saved-sessions-usage", "{} MB of {} MB of browser storage in use."),
+    ("saved-session-download", "Download"),
+    ("saved-session-delete", "Delete"),
+    ("saved-session-recording", "recording"),
+    ("root-suggestion", "Suggested root: {}"),
+    ("shortcut-unbound", "None"),
+    ("shortcut-press-key", "Press a key"),
+    ("session-summary-score", "In tune {}% of the time, within {} cents"),
+    ("session-summary-nothing-sung", "Nothing was sung in this session"),
+    ("session-summary-streak", "Longest in-tune streak: {} s"),
+    ("statistics-score", "In tune {}%"),
+    ("statistics-nothing-sung", "Nothing sung yet"),
+    ("statistics-streak", "Longest streak {} s"),
+    ("audio-paused-background", "Paused in background"),
+    ("audio-paused-suspended", "Audio paused, click to resume"),
+    ("audio-stopped", "Audio stopped"),
+    ("audio-worklet-stalled", "Audio processing stopped, restarting"),
+    ("audio-input-ended", "Microphone disconnected, reconnecting"),
+    ("audio-restart-failed", "Audio restart failed, retrying (attempt {})"),
+    ("announcement-pitch", "{}, {}"),
+    ("announcement-in-tune", "in tune"),
+    ("announcement-sharp", "sharp"),
+    ("announcement-far-sharp", "far sharp"),
+    ("announcement-flat", "flat"),
+    ("announcement-far-flat", "far flat"),
+    ("announcement-no-pitch", "No pitch"),
+    ("recording-lamp", "REC {}"),
+    ("exercise-lamp", "Target {} of {}"),
+    ("tutorial-progress", "Step {} of {}"),
+    ("tutorial-next", "Next"),
+    ("tutorial-done", "Done"),
+    ("tutorial-root-selector-title", "Tonal center"),
+    ("tutorial-root-selector-text", "Choose the note your music is centered on. The display shows every interval from it, and the tuning system and scale are built on it."),
+    ("tutorial-pitch-marker-title", "Your pitch"),
+    ("tutorial-pitch-marker-text", "Sing or play, and a line across the display marks your pitch. It turns from out of tune to in tune as you reach a note of the scale."),
+    ("tutorial-drone-button-title", "Drone"),
+    ("tutorial-drone-button-text", "Click the speaker or raise the volume to hear the tonal center as a drone to tune against. Headphones keep it out of the microphone."),
+    ("permission-guidance-chrome", "Click the site settings icon to the left of the address, turn on Microphone, then click Try Again."),
+    ("permission-guidance-edge", "Click the lock icon to the left of the address, set Microphone to Allow under Permissions for this site, then click Try Again."),
+    ("permission-guidance-firefox", "Click the crossed-out microphone in the address bar and clear the blocked permission, then click Try Again and allow the microphone."),
+    ("permission-guidance-safari", "Choose Safari > Settings for This Website and set Microphone to Allow, then click Try Again. On iPhone and iPad, tap aA in the address bar and open Website Settings."),
+    ("limitation-main-thread-input", "Audio is analyzed with more delay, and without the noise gate and input filters."),
+    ("limitation-message-batches", "Audio reaches the analysis in messages, with a little more delay."),
+    ("limitation-reduced-rendering", "The display is drawn at a lower resolution."),
+    // Error box
+    ("error-microphone-permission-denied-title", "Microphone Access Required"),
+    ("error-microphone-permission-denied-details", "Please allow microphone access to use the pitch detection features. Refresh the page and click 'Allow' when prompted. (Mac users: the microphone may be blocked in System Settings.)"),
+    ("error-microphone-not-available-title", "Microphone Not Available"),
+    ("error-microphone-not-available-details", "No microphone device found. Please ensure a microphone is connected and try again."),
+    ("error-processing-title", "Processing Error"),
+    ("error-browser-api-not-supported-title", "Browser Not Supported"),
+    ("error-browser-api-not-supported-details", "This browser doesn't support the required audio features ({}). Please try Chrome, Firefox, or Edge."),
+    ("error-browser-title", "Browser Error"),
+    ("error-browser-details", "An unexpected browser error occurred. Please try refreshing the page."),
+    ("error-graphics-context-lost-title", "Graphics Context Lost"),
+    ("error-graphics-context-lost-details", "The browser discarded the WebGL context, so the visualization can no longer be drawn. Please refresh the page."),
+    ("error-internal-title", "Something Went Wrong"),
+    ("error-internal-details", "Intonation Toy stopped after an internal error. Copy the bug report into an issue on GitHub to help fix it, then refresh the page."),
+    ("error-input-device-changed-title", "Microphone Changed"),
+    ("error-input-device-changed-details", "Now listening to {}."),
+    ("toast-dismiss", "Dismiss"),
+];
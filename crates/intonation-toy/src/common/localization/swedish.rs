@@ -0,0 +1,381 @@
+//! Swedish texts
+
+pub const TEXTS: &[(&str, &str)] = &[
+    // Page, by the data-i18n keys of index.html
+    ("section-tonal-center", "Tonalt centrum"),
+    ("tonal-center-octave-down-title", "Oktav ned"),
+    ("tonal-center-octave-up-title", "Oktav upp"),
+    ("root-suggestion-apply", "Använd"),
+    ("volume-icon-label", "Tyst"),
+    ("tonal-center-volume-label", "Tonikans volym"),
+    ("tonal-center-help", "Matcha tonen med rösten eller instrumentet för att utforska intonation. Lämna volymen på noll för att bara stämma efter bilden."),
+    ("section-reference-tone", "Referenston"),
+    ("reference-tone-timbre-sine", "Sinuston"),
+    ("reference-tone-timbre-triangle", "Triangelton"),
+    ("reference-tone-timbre-organ", "Orgelton"),
+    ("reference-tone-volume-label", "Volym"),
+    ("reference-tone-attack-label", "Attack"),
+    ("reference-tone-release-label", "Avklingning"),
+    ("reference-tone-degrees-label", "Steg"),
+    ("reference-interval-label", "Halvtoner från tonalt centrum"),
+    ("reference-interval-play", "Spela intervall"),
+    ("reference-tone-help", "Spela ett steg i skalan, eller tonen ett antal halvtoner från tonalt centrum, för att höra vart du ska sikta. Attacken och avklingningen formar hur tonen börjar och tonar ut."),
+    ("section-tuning-system", "Stämningssystem"),
+    ("tuning-system-equal", "Liksvävande temperatur"),
+    ("tuning-system-just", "Ren stämning (5-gräns)"),
+    ("tuning-system-just-7", "Ren stämning (7-gräns)"),
+    ("tuning-system-pythagorean", "Pythagoreisk"),
+    ("tuning-system-meantone", "Kvartskommamedeltonstemperatur"),
+    ("tuning-system-werckmeister3", "Werckmeister III"),
+    ("tuning-system-custom", "Egen"),
+    ("just-ratios-help", "Förhållandet för varje steg ovanför tonikan. Förhållandena måste stiga från 1/1 och stanna under oktaven 2/1."),
+    ("custom-tuning-help", "Avvikelsen för varje steg från liksvävande temperatur i cent. Klistra in innehållet i en Scala-fil (.scl) nedan för att importera en stämning, eller exportera den här."),
+    ("scala-text-label", "Scala-fil"),
+    ("scala-import", "Importera"),
+    ("scala-export", "Exportera"),
+    ("tuning-system-help", "Välj hur tonerna stäms: liksvävande temperatur delar oktaven jämnt, ren stämning använder rena heltalsförhållanden byggda av primtal upp till 5 eller 7, och de historiska temperaturerna fördelar renheten olika mellan tonarterna."),
+    ("section-concert-pitch", "Kammarton"),
+    ("concert-pitch-415", "A4 = 415 Hz (barock)"),
+    ("concert-pitch-430", "A4 = 430 Hz (klassisk)"),
+    ("concert-pitch-435", "A4 = 435 Hz (fransk)"),
+    ("concert-pitch-440", "A4 = 440 Hz (standard)"),
+    ("concert-pitch-466", "A4 = 466 Hz (korton)"),
+    ("concert-pitch-help", "Välj frekvensen för A4 som alla andra toner stäms efter. Många orkestrar stämmer något över 440 Hz, och tidigmusikensembler spelar ofta på 415 Hz."),
+    ("section-instrument-key", "Instrumentets stämning"),
+    ("transposition-c", "C (klingande)"),
+    ("transposition-b-flat", "B (klarinett, trumpet, tenorsax)"),
+    ("transposition-e-flat", "Ess (alt- och barytonsax)"),
+    ("transposition-f", "F (valthorn, engelskt horn)"),
+    ("transposition-a", "A (klarinett i A)"),
+    ("transposition-help", "Visa tonnamn som de skrivs för ett transponerande instrument. Stämning och frekvenser förblir klingande."),
+    ("section-note-names", "Tonnamn"),
+    ("note-naming-flats", "Med b (B)"),
+    ("note-naming-sharps", "Med kors (A♯)"),
+    ("note-naming-by-key", "Kors eller b efter tonart"),
+    ("note-naming-solfege", "Solmisation (rörligt do)"),
+    ("note-naming-sargam", "Sargam"),
+    ("note-naming-help", "Solmisation och sargam namnger toner efter deras steg över det tonala centrumet, med ' för varje oktav över och , för varje oktav under."),
+    ("section-tolerance", "Tolerans"),
+    ("tolerance-profile-beginner", "Nybörjare (±25¢)"),
+    ("tolerance-profile-intermediate", "Medel (±15¢)"),
+    ("tolerance-profile-advanced", "Avancerad (±8¢)"),
+    ("tolerance-profile-custom", "Egen"),
+    ("tolerance-help", "Välj hur nära en ton tonhöjden måste ligga för att räknas som ren. Flyttar du reglaget blir toleransen egen."),
+    ("section-scale", "Skala"),
+    ("scale-chromatic", "Kromatisk"),
+    ("scale-major", "Dur"),
+    ("scale-minor", "Moll"),
+    ("scale-harmonic-minor", "Harmonisk moll"),
+    ("scale-melodic-minor", "Melodisk moll"),
+    ("scale-major-pentatonic", "Pentatonisk dur"),
+    ("scale-minor-pentatonic", "Pentatonisk moll"),
+    ("scale-blues", "Blues"),
+    ("scale-dorian", "Dorisk"),
+    ("scale-phrygian", "Frygisk"),
+    ("scale-lydian", "Lydisk"),
+    ("scale-mixolydian", "Mixolydisk"),
+    ("scale-locrian", "Lokrisk"),
+    ("scale-whole-tone", "Heltonsskala"),
+    ("scale-augmented", "Överstigande"),
+    ("scale-diminished-half-whole", "Förminskad (halv-hel)"),
+    ("scale-diminished-whole-half", "Förminskad (hel-halv)"),
+    ("scale-hungarian-minor", "Ungersk moll"),
+    ("scale-neapolitan-minor", "Neapolitansk moll"),
+    ("scale-neapolitan-major", "Neapolitansk dur"),
+    ("scale-enigmatic", "Enigmatisk"),
+    ("scale-persian", "Persisk"),
+    ("scale-double-harmonic-major", "Dubbelharmonisk dur"),
+    ("scale-altered", "Alterad"),
+    ("scale-bebop-major", "Bebop-dur"),
+    ("scale-bebop-dominant", "Bebop-dominant"),
+    ("scale-custom", "Egen"),
+    ("custom-scale-help", "Kryssa för de steg ovanför tonikan som hör till skalan. Tonikan hör alltid dit."),
+    ("scale-help", "Välj en skala som bestämmer vilka toner som visas."),
+    ("section-display", "Visning"),
+    ("display-range-two-octaves", "Två oktaver"),
+    ("display-range-one-octave", "En oktav"),
+    ("display-range-two-half-octaves", "Två halva oktaver"),
+    ("display-range-help", "Välj visningsomfång: två hela oktaver, en hel oktav eller en oktav centrerad kring det tonala centrumet."),
+    ("interval-zoom-full", "Hela omfånget"),
+    ("interval-zoom-six-semitones", "Zooma till ±6 halvtoner"),
+    ("interval-zoom-three-semitones", "Zooma till ±3 halvtoner"),
+    ("interval-zoom-help", "Zooma in kring måltonen, eller tonen du ligger närmast när det inte finns någon, för att se små avvikelser tydligare. Bilden glider med när tonen byts."),
+    ("display-scene-intonation", "Aktuell tonhöjd"),
+    ("display-scene-pitch-graph", "Tonhöjdsgraf"),
+    ("display-scene-strobe", "Stroboskop"),
+    ("display-scene-needle", "Visare"),
+    ("display-scene-help", "Tonhöjdsgrafen ritar de senaste 10 sekunderna av din tonhöjd mot skallinjerna, färgad efter hur långt den avviker från dem. Stroboskopet snurrar medurs när du ligger högt och moturs när du ligger lågt, och står still när du är ren; de yttre ringarna snurrar fortare. Visaren pekar på din avvikelse från närmaste ton, upp till 50 cent åt vardera hållet."),
+    ("needle-damping-light", "Lätt dämpad visare"),
+    ("needle-damping-critical", "Kritiskt dämpad visare"),
+    ("needle-damping-heavy", "Kraftigt dämpad visare"),
+    ("needle-damping-help", "Hur visaren följer din tonhöjd. Lätt dämpning svänger förbi värdet och tillbaka, kritisk dämpning ställer in sig snabbast utan att slå över, och kraftig dämpning rör sig långsamt och döljer små svajningar."),
+    ("smoothing-ema", "Jämn utjämning"),
+    ("smoothing-adaptive-ema", "Anpassad utjämning"),
+    ("smoothing-median", "Medianutjämning"),
+    ("smoothing-one-euro", "Hastighetsberoende utjämning"),
+    ("smoothing-none", "Ingen utjämning"),
+    ("smoothing-help", "Hur den uppmätta tonhöjden jämnas ut. Jämn utjämning behandlar varje mätning lika, anpassad utjämning följer stora ändringar snabbare, medianutjämning tar bort enstaka avvikande mätningar och hastighetsberoende utjämning håller långa toner stilla men följer snabba passager. Utan utjämning ser du varje mätning."),
+    ("pitch-detector-mcleod", "McLeod-tonhöjdsmätning"),
+    ("pitch-detector-yin", "YIN-tonhöjdsmätning"),
+    ("pitch-detector-autocorrelation", "Autokorrelationsmätning"),
+    ("pitch-detector-power-label", "Styrka"),
+    ("pitch-detector-clarity-label", "Tydlighet"),
+    ("pitch-detector-help", "Hur tonhöjden hittas i ljudet. YIN passar de flesta röster och instrument, McLeod följer snabba passager nära och autokorrelation är den enklaste. Ljud svagare än styrkan och mindre tydliga toner än tydligheten visar ingen tonhöjd; höj dem för att bortse från bakgrundsljud, sänk dem för svagt spel."),
+    ("ghost-trail-toggle", "Spöke av förra försöket"),
+    ("ghost-trail-help", "Rita ditt förra övningsförsök svagt bakom tonhöjdsgrafen, räknat från dess start, för att jämföra detta försök med det förra."),
+    ("piano-mode-none", "Inget piano"),
+    ("piano-mode-tonal-center", "Pianot väljer tonalt centrum"),
+    ("piano-mode-target", "Pianot väljer måltonen"),
+    ("piano-mode-help", "Visa ett piano längst ned i bilden med skalans toner tända. Klicka på en tangent för att göra den till tonalt centrum eller till tonen att träffa; klicka på målet igen för att ta bort det."),
+    ("staff-clef-none", "Inget notsystem"),
+    ("staff-clef-treble", "Notsystem med G-klav"),
+    ("staff-clef-bass", "Notsystem med F-klav"),
+    ("staff-clef-alto", "Notsystem med altklav"),
+    ("staff-clef-help", "Visa måltonen och tonen du spelar i ett notsystem, skrivna för den valda transponeringen."),
+    ("input-meters-toggle", "Ingångsmätare"),
+    ("input-meters-help", "Visa ingångsnivån med dess senaste topp och en överstyrningslampa, och en ring som fylls efter hur tydlig den uppfattade tonhöjden är. En låg stapel betyder att mikrofonen är för svag; en full stapel med en ring som hela tiden töms betyder att tonhöjden är ostadig eller brusig."),
+    ("announce-pitch-toggle", "Läs upp tonhöjd"),
+    ("announce-pitch-help", "Låt skärmläsare läsa upp närmaste ton och om du är ren, hög eller låg när den har hållit en stund. Alla kontroller i sidopanelen går att använda med tangentbordet, och bilden tar emot kortkommandona när den har fokus."),
+    ("spectrogram-toggle", "Spektrogram"),
+    ("spectrogram-help", "Visa ingångens spektrum över tid längst ned i bilden. Reglaget anger hur långt under det starkaste ljudet som fortfarande skuggas."),
+    ("interval-mode-absolute", "Absoluta intervall"),
+    ("interval-mode-wrapped", "Hopvikta intervall"),
+    ("interval-mode-help", "Absoluta intervall räknar varje oktav mellan din tonhöjd och det tonala centrumet. Hopvikta intervall viker in dem i en oktav, så att det tonala centrumet kan ligga långt över eller under ditt omfång."),
+    ("fullscreen-button", "Helskärm"),
+    ("immersive-layout-toggle", "Uppslukande layout"),
+    ("layout-help", "Helskärm döljer webbläsaren runt sidan. Den uppslukande layouten låter bilden fylla fönstret och döljer sidopanelen tills du rör pekaren."),
+    ("mini-view-button", "Minivy"),
+    ("mini-view-help", "Håll den närmaste tonen och hur långt ifrån den du ligger i ett litet fönster över andra flikar och fönster, som när du läser noter i en annan flik."),
+    ("pitch-history-pane-toggle", "Ruta med tonhöjdshistorik"),
+    ("spectrogram-pane-toggle", "Ruta med spektrogram"),
+    ("statistics-pane-toggle", "Ruta med statistik"),
+    ("panes-help", "Visa fler vyer i en kolumn bredvid bilden. Dra i linjerna mellan rutorna för att ändra deras storlek; layouten sparas. Spektrogramrutan använder omfånget från spektrogrammets reglage."),
+    ("theme-dark", "Mörkt tema"),
+    ("theme-light", "Ljust tema"),
+    ("theme-autumn", "Hösttema"),
+    ("theme-sunset", "Solnedgångstema"),
+    ("theme-deuteranopia", "Deuteranopitema"),
+    ("theme-protanopia", "Protanopitema"),
+    ("theme-tritanopia", "Tritanopitema"),
+    ("theme-high-contrast", "Tema med hög kontrast"),
+    ("theme-help", "Färgerna i bilden och sidopanelen. Deuteranopi-, protanopi- och tritanopitemana håller isär rent och orent för motsvarande typ av färgblindhet."),
+    ("language-help", "Språk i sidopanelen, överläggen och bilden. Tonnamnen följer språket, så på svenska heter B H och Bb heter B."),
+    ("tutorial-replay-button", "Visa introduktionen"),
+    ("tutorial-replay-help", "Gå igenom det tonala centrumet, tonhöjdslinjen och bordunen igen, som vid första starten."),
+    ("install-app-button", "Installera appen"),
+    ("install-app-help", "Installera appen för att öppna den i ett eget fönster. När den har laddats fungerar den även utan nätverk."),
+    ("section-input", "Ingång"),
+    ("input-device-label", "Mikrofon"),
+    ("input-device-help", "Välj mikrofonen att lyssna på. Listan följer mikrofoner när de kopplas in och ur."),
+    ("peak-measure-sample-peak", "Flagga samplingstoppar"),
+    ("peak-measure-loudness", "Flagga ljudstyrka"),
+    ("peak-measure-help", "Flagga ingången som för stark när dess sampel närmar sig klippning, eller när dess korttidsljudstyrka är hög, vilket följer hur starkt det låter."),
+    ("automatic-gain-toggle", "Automatisk förstärkning"),
+    ("input-level-help", "Automatisk förstärkning håller ingången på en jämn nivå. Kalibreringen mäter rummets brus och din röst en gång för den här mikrofonen, för att skilja dem åt."),
+    ("section-midi", "MIDI"),
+    ("midi-toggle", "Använd MIDI-enheter"),
+    ("midi-input-mode-label", "MIDI-tangenter ställer in"),
+    ("midi-input-mode-tonal-center", "Tangenter ställer in tonalt centrum"),
+    ("midi-input-mode-target-pitch", "Tangenter ställer in måltonen"),
+    ("midi-output-label", "MIDI-utgång"),
+    ("midi-output-source-label", "Toner som skickas till MIDI-utgången"),
+    ("midi-output-source-off", "Skicka inga toner"),
+    ("midi-output-source-detected-note", "Skicka den upptäckta tonen"),
+    ("midi-output-source-reference-tone", "Skicka referenstoner"),
+    ("midi-help", "Ställ in tonalt centrum eller måltonen från ett MIDI-klaviatur, och skicka toner till ett MIDI-instrument. Webbläsaren ber om åtkomst första gången."),
+    ("section-exercise", "Övning"),
+    ("exercise-ascending-scale", "Stigande skala"),
+    ("exercise-descending-scale", "Fallande skala"),
+    ("exercise-random-intervals", "Slumpade intervall"),
+    ("count-in-toggle", "Räkna in"),
+    ("exercise-help", "Sjung varje mål i skalan i tur och ordning och håll det rent för att gå vidare till nästa. Målen följer tonikan och skalan. Räkna in räknar ner tre slag på bilden först."),
+    ("section-reference-melody", "Referensmelodi"),
+    ("reference-melody-text-label", "Melodi"),
+    ("reference-melody-from-recording", "Sjung med inspelningen"),
+    ("reference-melody-help", "Sjung med en melodi skriven som par av MIDI-not:längd i millisekunder, med r för paus, eller med tonerna som sjöngs i inspelningen. Varje ton spelas när den kommer, och avvikelsen i din tonhöjd visas när den är slut."),
+    ("section-metronome", "Metronom"),
+    ("metronome-toggle", "Blinka takten"),
+    ("metronome-subdivision-beats", "Bara slag"),
+    ("metronome-subdivision-eighths", "Åttondelar"),
+    ("metronome-subdivision-triplets", "Trioler"),
+    ("metronome-subdivision-sixteenths", "Sextondelar"),
+    ("metronome-click-toggle", "Hörbart klick"),
+    ("metronome-help", "Blinka slaget i hörnet av bilden, med en prick för varje underdelning, och klicka med om du vill. En övning börjar om på slaget vid varje nytt mål."),
+    ("section-recording", "Inspelning"),
+    ("recording-export-from-label", "Från"),
+    ("recording-export-to-label", "Till"),
+    ("recording-export-button", "Exportera WAV"),
+    ("recording-help", "Spela in mikrofonen i upp till en minut och ladda ner en del av inspelningen som en WAV-fil. Från och Till räknar sekunder bakåt från inspelningens slut. Räkna in räknar ner tre slag på bilden först."),
+    ("section-export", "Export"),
+    ("export-format-pitch-csv", "Tonhöjd över tid (CSV)"),
+    ("export-format-statistics-csv", "Statistik per steg (CSV)"),
+    ("export-format-json", "Session (JSON)"),
+    ("export-button", "Exportera"),
+    ("export-help", "Ladda ned tonhöjden för de senaste 30 sekunderna eller statistiken för sessionen för användning i ett kalkylark eller andra verktyg."),
+    ("session-summary-button", "Visa sammanfattning"),
+    ("end-session-button", "Avsluta session"),
+    ("session-summary-help", "Visa diagram över sessionen hittills, eller avsluta sessionen för att se dem en sista gång och börja om när de stängs."),
+    ("share-image-button", "Dela bild"),
+    ("share-image-help", "Dela en skärmbild med statistiken för sessionen genom att trycka på knappen igen när bilden är klar, eller ladda ned den där webbläsaren inte kan dela filer."),
+    ("share-link-button", "Kopiera länk"),
+    ("share-link-help", "Kopiera en länk som öppnar appen med detta tonala centrum, denna skala och detta stämningssystem. Sidans adress innehåller dem alltid också."),
+    ("section-saved-sessions", "Sparade sessioner"),
+    ("save-session-button", "Spara sessionen"),
+    ("saved-sessions-help", "Spara sessionens inställningar, statistik, tonhöjdshistorik och övningsresultat i webbläsaren, med inspelningen av ingången om en pågår. När utrymmet tar slut, ta bort sparade sessioner för att ge plats."),
+    ("section-keyboard-shortcuts", "Kortkommandon"),
+    ("shortcut-tonal-center-up-label", "Tonalt centrum upp"),
+    ("shortcut-tonal-center-down-label", "Tonalt centrum ned"),
+    ("shortcut-tonal-center-octave-up-label", "Tonalt centrum en oktav upp"),
+    ("shortcut-tonal-center-octave-down-label", "Tonalt centrum en oktav ned"),
+    ("shortcut-toggle-drone-label", "Bordun av/på"),
+    ("shortcut-scale-chromatic-label", "Kromatisk skala"),
+    ("shortcut-scale-major-label", "Durskala"),
+    ("shortcut-scale-minor-label", "Mollskala"),
+    ("shortcut-scale-harmonicminor-label", "Harmonisk mollskala"),
+    ("shortcut-scale-melodicminor-label", "Melodisk mollskala"),
+    ("shortcut-scale-majorpentatonic-label", "Pentatonisk durskala"),
+    ("shortcut-scale-minorpentatonic-label", "Pentatonisk mollskala"),
+    ("shortcut-scale-blues-label", "Bluesskala"),
+    ("shortcut-scale-dorian-label", "Dorisk skala"),
+    ("shortcuts-help", "Kortkommandon fungerar när bilden har fokus. Klicka på en tangent för att ändra den och tryck sedan på den nya tangenten, eller Escape för att behålla den gamla."),
+    ("session-summary-export", "Exportera session (JSON)"),
+    ("session-summary-close", "Stäng"),
+    ("three-d-canvas-label", "Tonhöjdsvisning. Kortkommandona fungerar när den har fokus."),
+    ("loading", "Laddar..."),
+    ("section-about", "Om"),
+    ("about-text", "är ett verktyg för analys och visualisering av tonhöjd i realtid. Utforska musikalisk intonation genom att analysera ljud och se hur din tonhöjd förhåller sig till olika stämningssystem. Hörlurar rekommenderas för att lyssna på det tonala centrumet."),
+    ("session-summary-title", "Sammanfattning av sessionen"),
+    ("session-summary-degrees-title", "Tid ren på varje steg"),
+    ("session-summary-histogram-title", "Tid på varje avvikelse från närmaste ton, i cent"),
+    ("first-click-start", "Klicka var som helst för att börja"),
+    ("first-click-permission-note", "(Tillstånd att använda mikrofonen kommer att efterfrågas)"),
+    ("tutorial-skip", "Hoppa över"),
+    ("tutorial-back", "Tillbaka"),
+    ("touch-controls-toggle", "Kontroller"),
+    ("telemetry-toggle", "Dela anonym användningsdata"),
+    ("telemetry-consent-text", "Får Intonation Toy skicka anonym användningsdata, till exempel vilka stämningssystem och skalor du använder och hur jämnt programmet går? Inget i den identifierar dig, och du kan ändra ditt svar under Om."),
+    ("telemetry-consent-accept", "Tillåt"),
+    ("telemetry-consent-decline", "Nej tack"),
+    ("bug-report-copy", "Kopiera felrapport"),
+    ("bug-report-text-label", "Felrapport"),
+    ("tab-role-standalone", "Fristående flik"),
+    ("tab-role-controller", "Styrande flik"),
+    ("tab-role-display", "Visningsflik"),
+    ("tab-role-help", "Med programmet öppet i två flikar eller fönster kan du göra den ena styrande och den andra till dess visning, som när du projicerar visningen medan du sköter reglagen på en bärbar dator. Visningen följer stämningen i den styrande fliken."),
+    ("tab-contention-warning", "Programmet lyssnar på mikrofonen i en annan flik också. Stäng en av dem, eller gör den ena styrande och den andra till dess visning."),
+    ("section-duet", "Duett (experimentell)"),
+    ("duet-host-button", "Undervisa"),
+    ("duet-connect-button", "Anslut"),
+    ("duet-leave-button", "Lämna"),
+    ("duet-local-code-label", "Din kod"),
+    ("duet-remote-code-label", "Kod från det andra programmet"),
+    ("duet-help", "Spela tillsammans på avstånd. Läraren trycker på Undervisa och skickar koden till eleven, som klistrar in den och trycker på Anslut och sedan skickar tillbaka koden som visas, så att läraren kan klistra in den och ansluta. Eleven hör lärarens bordun och läraren ser elevens tonhöjd."),
+    ("capability-limits-title", "Begränsat i den här webbläsaren:"),
+    ("permission-guidance-title", "Mikrofonen är blockerad"),
+    ("permission-guidance-other", "Tillåt mikrofonen för den här webbplatsen i webbläsarens inställningar och klicka sedan på Försök igen."),
+    ("permission-guidance-note", "Om webbläsaren meddelar ändringen börjar lyssnandet utan klick."),
+    ("permission-guidance-retry", "Försök igen"),
+    // Set from code
+    ("fullscreen-exit", "Lämna helskärm"),
+    ("mini-view-close", "Stäng minivy"),
+    ("share-link-copied", "Länken kopierades"),
+    ("bug-report-copied", "Felrapporten kopierades"),
+    ("duet-status-offer", "Skicka den här koden till eleven, klistra sedan in elevens kod och tryck på Anslut."),
+    ("duet-status-answer", "Skicka tillbaka den här koden till läraren."),
+    ("duet-status-connected", "Ansluten"),
+    ("duet-status-disconnected", "Inte ansluten"),
+    ("duet-invalid-code", "Det här är ingen duettkod."),
+    ("duet-remote-pitch", "Eleven: {} {} cent"),
+    ("duet-remote-silent", "Eleven: tyst"),
+    ("input-device-unnamed", "Mikrofon {}"),
+    ("recording-start", "Starta inspelning"),
+    ("recording-stop", "Stoppa inspelning"),
+    ("recording-status-recording", "Spelar in {} s"),
+    ("recording-status-recorded", "Inspelat {} s"),
+    ("midi-output-none", "Ingen MIDI-utgång"),
+    ("calibration-start", "Kalibrera"),
+    ("calibration-cancel", "Avbryt kalibrering"),
+    ("calibration-measuring-noise", "Var tyst medan rummets brus mäts ({}%)"),
+    ("calibration-measuring-voice", "Sjung eller spela på din starkaste normala nivå ({}%)"),
+    ("calibration-failed", "Kalibreringen misslyckades: ingen tydlig röst över bruset"),
+    ("calibration-done", "Kalibrerad för den här mikrofonen"),
+    ("calibration-none", "Inte kalibrerad"),
+    ("just-ratio-numerator", "Täljare för steg {}"),
+    ("just-ratio-denominator", "Nämnare för steg {}"),
+    ("custom-tuning-offset", "Avvikelse för steg {} i cent"),
+    ("scala-import-failed", "Kunde inte läsa Scala-filen: {}"),
+    ("exercise-start", "Starta övning"),
+    ("exercise-stop", "Stoppa övning"),
+    ("exercise-status", "{} av {} nådda"),
+    ("reference-melody-start", "Starta melodi"),
+    ("reference-melody-stop", "Stoppa melodi"),
+    ("reference-melody-invalid", "Kunde inte läsa melodin: {}"),
+    ("reference-melody-progress", "{} % spelat"),
+    ("count-in-cancel", "Avbryt inräkning"),
+    ("share-image-ready", "Dela nu"),
+    ("saved-sessions-saved", "Sessionen sparades"),
+    ("saved-sessions-full", "Inte tillräckligt med lagringsutrymme för att spara sessionen. Ta bort sparade sessioner för att ge plats."),
+    ("saved-sessions-failed", "Sessionen kunde inte sparas"),
+    ("saved-sessions-empty", "Inga sparade sessioner ännu."),
+    ("saved-sessions-usage", "{} MB av {} MB av webbläsarens lagring används."),
+    ("saved-session-download", "Ladda ner"),
+    ("saved-session-delete", "Ta bort"),
+    ("saved-session-recording", "inspelning"),
+    ("root-suggestion", "Föreslagen grundton: {}"),
+    ("shortcut-unbound", "Ingen"),
+    ("shortcut-press-key", "Tryck på en tangent"),
+    ("session-summary-score", "Ren {}% av tiden, inom {} cent"),
+    ("session-summary-nothing-sung", "Inget sjöngs under sessionen"),
+    ("session-summary-streak", "Längsta rena svit: {} s"),
+    ("statistics-score", "Ren {}%"),
+    ("statistics-nothing-sung", "Inget sjunget ännu"),
+    ("statistics-streak", "Längsta svit {} s"),
+    ("audio-paused-background", "Pausad i bakgrunden"),
+    ("audio-paused-suspended", "Ljudet är pausat, klicka för att fortsätta"),
+    ("audio-stopped", "Ljudet har stoppats"),
+    ("audio-worklet-stalled", "Ljudbehandlingen stannade, startar om"),
+    ("audio-input-ended", "Mikrofonen kopplades från, ansluter igen"),
+    ("audio-restart-failed", "Omstarten av ljudet misslyckades, försöker igen (försök {})"),
+    ("announcement-pitch", "{}, {}"),
+    ("announcement-in-tune", "ren"),
+    ("announcement-sharp", "hög"),
+    ("announcement-far-sharp", "mycket hög"),
+    ("announcement-flat", "låg"),
+    ("announcement-far-flat", "mycket låg"),
+    ("announcement-no-pitch", "Ingen tonhöjd"),
+    ("recording-lamp", "INSP {}"),
+    ("exercise-lamp", "Mål {} av {}"),
+    ("tutorial-progress", "Steg {} av {}"),
+    ("tutorial-next", "Nästa"),
+    ("tutorial-done", "Klar"),
+    ("tutorial-root-selector-title", "Tonalt centrum"),
+    ("tutorial-root-selector-text", "Välj tonen som musiken utgår från. Bilden visar varje intervall från den, och stämningssystemet och skalan byggs på den."),
+    ("tutorial-pitch-marker-title", "Din tonhöjd"),
+    ("tutorial-pitch-marker-text", "Sjung eller spela, så markerar en linje över bilden din tonhöjd. Den skiftar från oren till ren när du når en ton i skalan."),
+    ("tutorial-drone-button-title", "Bordun"),
+    ("tutorial-drone-button-text", "Klicka på högtalaren eller höj volymen för att höra det tonala centrumet som en bordun att stämma mot. Hörlurar håller den borta från mikrofonen."),
+    ("permission-guidance-chrome", "Klicka på ikonen för webbplatsinställningar till vänster om adressen, slå på Mikrofon och klicka sedan på Försök igen."),
+    ("permission-guidance-edge", "Klicka på låsikonen till vänster om adressen, välj Tillåt för Mikrofon under Behörigheter för den här webbplatsen och klicka sedan på Försök igen."),
+    ("permission-guidance-firefox", "Klicka på den överstrukna mikrofonen i adressfältet och ta bort blockeringen, klicka sedan på Försök igen och tillåt mikrofonen."),
+    ("permission-guidance-safari", "Välj Safari > Inställningar för den här webbplatsen och välj Tillåt för Mikrofon, klicka sedan på Försök igen. På iPhone och iPad trycker du på aA i adressfältet och öppnar Webbplatsinställningar."),
+    ("limitation-main-thread-input", "Ljudet analyseras med större fördröjning, och utan brusspärr och ingångsfilter."),
+    ("limitation-message-batches", "Ljudet når analysen i meddelanden, med något större fördröjning."),
+    ("limitation-reduced-rendering", "Visningen ritas med lägre upplösning."),
+    // Error box
+    ("error-microphone-permission-denied-title", "Mikrofonåtkomst krävs"),
+    ("error-microphone-permission-denied-details", "Tillåt åtkomst till mikrofonen för att använda tonhöjdsanalysen. Ladda om sidan och klicka på 'Tillåt' när du tillfrågas. (Mac-användare: mikrofonen kan vara blockerad i Systeminställningar.)"),
+    ("error-microphone-not-available-title", "Ingen mikrofon tillgänglig"),
+    ("error-microphone-not-available-details", "Ingen mikrofon hittades. Se till att en mikrofon är ansluten och försök igen."),
+    ("error-processing-title", "Bearbetningsfel"),
+    ("error-browser-api-not-supported-title", "Webbläsaren stöds inte"),
+    ("error-browser-api-not-supported-details", "Den här webbläsaren saknar stöd för de ljudfunktioner som krävs ({}). Prova Chrome, Firefox eller Edge."),
+    ("error-browser-title", "Webbläsarfel"),
+    ("error-browser-details", "Ett oväntat fel uppstod i webbläsaren. Prova att ladda om sidan."),
+    ("error-graphics-context-lost-title", "Grafikkontexten förlorades"),
+    ("error-graphics-context-lost-details", "Webbläsaren kastade WebGL-kontexten, så visualiseringen kan inte längre ritas. Ladda om sidan."),
+    ("error-internal-title", "Något gick fel"),
+    ("error-internal-details", "Intonation Toy stannade efter ett internt fel. Kopiera felrapporten till ett ärende på GitHub för att hjälpa till att rätta det, och ladda sedan om sidan."),
+    ("error-input-device-changed-title", "Mikrofonen bytt"),
+    ("error-input-device-changed-details", "Lyssnar nu på {}."),
+    ("toast-dismiss", "Stäng"),
+];
@@ -1,10 +1,18 @@
-#![cfg(target_arch = "wasm32")]
+//! Logging to the browser console, or to stderr on native targets
 
 #[macro_export]
 macro_rules! dev_log {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
-        web_sys::console::log_1(&format!($($arg)*).into());
+        {
+            let message = format!($($arg)*);
+            #[cfg(target_arch = "wasm32")]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Info, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::log_1(&message.into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", message);
+        }
     };
 }
 
@@ -12,10 +20,18 @@ macro_rules! dev_log {
 macro_rules! dev_log_bold {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
-        web_sys::console::log_2(
-            &format!("%c{}", format!($($arg)*)).into(),
-            &"font-weight: bold;".into()
-        );
+        {
+            let message = format!($($arg)*);
+            #[cfg(target_arch = "wasm32")]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Info, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::log_2(
+                &format!("%c{}", message).into(),
+                &"font-weight: bold;".into()
+            );
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", message);
+        }
     };
 }
 
@@ -23,28 +39,64 @@ macro_rules! dev_log_bold {
 macro_rules! trace_log {
     ($($arg:tt)*) => {
         #[cfg(debug_assertions)]
-        web_sys::console::debug_1(&format!("[TRACE] {}", format!($($arg)*)).into());
+        {
+            let message = format!($($arg)*);
+            #[cfg(target_arch = "wasm32")]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Trace, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::debug_1(&format!("[TRACE] {}", message).into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("[TRACE] {}", message);
+        }
     };
 }
 
 #[macro_export]
 macro_rules! log {
     ($($arg:tt)*) => {
-        web_sys::console::log_1(&format!($($arg)*).into());
+        {
+            let message = format!($($arg)*);
+            #[cfg(all(debug_assertions, target_arch = "wasm32"))]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Info, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::log_1(&message.into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", message);
+        }
     };
 }
 
 #[macro_export]
 macro_rules! error_log {
     ($($arg:tt)*) => {
-        web_sys::console::error_1(&format!($($arg)*).into());
+        {
+            let message = format!($($arg)*);
+            #[cfg(all(debug_assertions, target_arch = "wasm32"))]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Error, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            $crate::web::bug_report::log_event(&message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::error_1(&message.into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", message);
+        }
     };
 }
 
 #[macro_export]
 macro_rules! warn_log {
     ($($arg:tt)*) => {
-        web_sys::console::warn_1(&format!($($arg)*).into());
+        {
+            let message = format!($($arg)*);
+            #[cfg(all(debug_assertions, target_arch = "wasm32"))]
+            ::egui_dev_console::capture_log(::egui_dev_console::LogLevel::Warning, module_path!(), &message);
+            #[cfg(target_arch = "wasm32")]
+            $crate::web::bug_report::log_event(&message);
+            #[cfg(target_arch = "wasm32")]
+            web_sys::console::warn_1(&message.into());
+            #[cfg(not(target_arch = "wasm32"))]
+            eprintln!("{}", message);
+        }
     };
 }
 
@@ -0,0 +1,130 @@
+//! Beats of the metronome
+//!
+//! Beats follow each other at the tempo, each split into equal ticks by the
+//! subdivision. A restart puts the first beat at the time given, which keeps
+//! the beats in step with the targets of an exercise: every new target starts
+//! on a beat. Time is in milliseconds, as from `js_sys::Date::now`.
+//!
+//! Ticks are taken a little ahead of time, with the time they fall on, so
+//! that the clicks can be scheduled on the audio clock rather than played
+//! whenever a frame happens to come.
+
+use crate::common::shared_types::{MetronomeSettings, MetronomeStatus};
+
+/// A tick that is coming up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetronomeTick {
+    pub time_ms: f64,
+    /// On the beat rather than on a subdivision of it
+    pub accent: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metronome {
+    settings: MetronomeSettings,
+    start_ms: f64,
+    /// Index of the next tick to take, counted from the start
+    next_tick: u64,
+    /// Started or restarted since the ticks were last taken, so that ticks
+    /// taken before are no longer on the beat
+    restarted: bool,
+}
+
+impl Metronome {
+    /// Starting with a beat at `now_ms`
+    pub fn new(settings: MetronomeSettings, now_ms: f64) -> Self {
+        Self { settings, start_ms: now_ms, next_tick: 0, restarted: true }
+    }
+
+    pub fn settings(&self) -> MetronomeSettings {
+        self.settings
+    }
+
+    /// Put the next beat at `now_ms`
+    pub fn restart(&mut self, now_ms: f64) {
+        *self = Self::new(self.settings, now_ms);
+    }
+
+    fn tick_ms(&self) -> f64 {
+        60_000.0 / self.settings.tempo_bpm as f64 / self.settings.subdivision.max(1) as f64
+    }
+
+    /// Whether the metronome started over since this was last asked
+    pub fn take_restarted(&mut self) -> bool {
+        std::mem::take(&mut self.restarted)
+    }
+
+    /// The ticks from `now_ms` until `until_ms` that were not taken yet; ticks
+    /// that already passed, as while the page was hidden, are dropped
+    pub fn take_ticks(&mut self, now_ms: f64, until_ms: f64) -> Vec<MetronomeTick> {
+        let tick_ms = self.tick_ms();
+        let first_upcoming = ((now_ms - self.start_ms) / tick_ms).max(0.0).ceil() as u64;
+        self.next_tick = self.next_tick.max(first_upcoming);
+        let subdivision = self.settings.subdivision.max(1) as u64;
+        let mut ticks = Vec::new();
+        loop {
+            let time_ms = self.start_ms + self.next_tick as f64 * tick_ms;
+            if time_ms >= until_ms {
+                return ticks;
+            }
+            ticks.push(MetronomeTick { time_ms, accent: self.next_tick.is_multiple_of(subdivision) });
+            self.next_tick += 1;
+        }
+    }
+
+    pub fn status(&self, now_ms: f64) -> MetronomeStatus {
+        let ticks = (now_ms - self.start_ms).max(0.0) / self.tick_ms();
+        let subdivision = self.settings.subdivision.max(1);
+        MetronomeStatus {
+            settings: self.settings,
+            tick: (ticks as u64 % subdivision as u64) as u32,
+            tick_progress: ticks.fract() as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(tempo_bpm: f32, subdivision: u32) -> MetronomeSettings {
+        MetronomeSettings { tempo_bpm, subdivision, click: false }
+    }
+
+    fn tick(time_ms: f64, accent: bool) -> MetronomeTick {
+        MetronomeTick { time_ms, accent }
+    }
+
+    #[test]
+    fn test_ticks_come_once_ahead_of_time() {
+        // 120 bpm in eighths: a tick every 250 ms, every other one on the beat
+        let mut metronome = Metronome::new(settings(120.0, 2), 1000.0);
+        assert!(metronome.take_restarted());
+        assert!(!metronome.take_restarted());
+        assert_eq!(metronome.take_ticks(900.0, 1000.0), vec![]);
+        assert_eq!(metronome.take_ticks(1000.0, 1100.0), vec![tick(1000.0, true)]);
+        assert_eq!(metronome.take_ticks(1050.0, 1150.0), vec![]);
+        assert_eq!(metronome.take_ticks(1160.0, 1560.0), vec![tick(1250.0, false), tick(1500.0, true)]);
+
+        // Ticks that passed while no frames came are not made up for
+        assert_eq!(metronome.take_ticks(2760.0, 2860.0), vec![]);
+        assert_eq!(metronome.take_ticks(2900.0, 3000.0), vec![]);
+        assert_eq!(metronome.take_ticks(2950.0, 3050.0), vec![tick(3000.0, true)]);
+
+        let status = metronome.status(2875.0);
+        assert_eq!(status.tick, 1);
+        assert!((status.tick_progress - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_restart_puts_a_beat_at_once() {
+        let mut metronome = Metronome::new(settings(60.0, 1), 0.0);
+        metronome.take_restarted();
+        assert_eq!(metronome.take_ticks(0.0, 100.0), vec![tick(0.0, true)]);
+        metronome.restart(400.0);
+        assert!(metronome.take_restarted());
+        assert_eq!(metronome.take_ticks(400.0, 500.0), vec![tick(400.0, true)]);
+        assert_eq!(metronome.take_ticks(1300.0, 1399.0), vec![]);
+        assert_eq!(metronome.take_ticks(1300.0, 1500.0), vec![tick(1400.0, true)]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Asking for the microphone until it is granted
+//!
+//! The Permissions API reports whether the browser will prompt for the
+//! microphone, has granted it, or has blocked it; browsers without it for the
+//! microphone are taken to prompt. A prompt needs a click, so `getUserMedia`
+//! runs in a user gesture. A blocked microphone shows how to allow it again in
+//! the settings of the browser at hand, and the request is repeated once the
+//! user asks to try again or the reported state changes.
+
+use crate::common::shared_types::Error;
+
+/// Microphone permission as the Permissions API reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionState {
+    Prompt,
+    Granted,
+    Denied,
+}
+
+impl PermissionState {
+    /// The state of a `PermissionStatus`
+    pub fn parse(state: &str) -> Option<PermissionState> {
+        match state {
+            "prompt" => Some(PermissionState::Prompt),
+            "granted" => Some(PermissionState::Granted),
+            "denied" => Some(PermissionState::Denied),
+            _ => None,
+        }
+    }
+}
+
+/// What to do to get the microphone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionStep {
+    /// Ask for a click and request the microphone in it
+    WaitForClick,
+    /// Request the microphone right away; a granted permission needs no click
+    Request,
+    /// Show how to allow the microphone again, and wait for a retry
+    ShowGuidance,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicPermission {
+    state: PermissionState,
+    /// Whether the first click, which also starts audio, has happened
+    started: bool,
+}
+
+impl MicPermission {
+    /// Start from the state the browser reports, if it reports one
+    pub fn new(reported: Option<PermissionState>) -> Self {
+        Self { state: reported.unwrap_or(PermissionState::Prompt), started: false }
+    }
+
+    pub fn state(&self) -> PermissionState {
+        self.state
+    }
+
+    pub fn next_step(&self) -> PermissionStep {
+        match self.state {
+            _ if !self.started => PermissionStep::WaitForClick,
+            PermissionState::Prompt => PermissionStep::WaitForClick,
+            PermissionState::Granted => PermissionStep::Request,
+            PermissionState::Denied => PermissionStep::ShowGuidance,
+        }
+    }
+
+    /// The user clicked to request the microphone
+    pub fn on_click(&mut self) {
+        self.started = true;
+    }
+
+    /// The browser reports a new state; true when it calls for another request
+    pub fn on_state_changed(&mut self, reported: PermissionState) -> bool {
+        let retry = self.state == PermissionState::Denied && reported != PermissionState::Denied;
+        self.state = reported;
+        retry
+    }
+
+    /// The user asked to try again after allowing the microphone; the request
+    /// runs in that click, so the browser may prompt again
+    pub fn on_retry(&mut self) {
+        self.state = PermissionState::Prompt;
+    }
+
+    pub fn on_granted(&mut self) {
+        self.state = PermissionState::Granted;
+    }
+
+    /// A request failed with the `name` of its `DOMException`. A missing or
+    /// unusable microphone cannot be granted, so it ends the requests; any
+    /// other failure is taken as the microphone being blocked.
+    pub fn on_request_failed(&mut self, name: &str) -> Result<(), Error> {
+        match name {
+            "NotFoundError" | "NotReadableError" | "OverconstrainedError" => Err(Error::MicrophoneNotAvailable),
+            _ => {
+                self.state = PermissionState::Denied;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Browser whose settings the guidance walks through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+    Safari,
+    Other,
+}
+
+impl Browser {
+    pub fn from_user_agent(user_agent: &str) -> Browser {
+        // Edge and Chrome also claim Safari, and Edge also claims Chrome
+        if user_agent.contains("Edg/") {
+            Browser::Edge
+        } else if user_agent.contains("Firefox/") || user_agent.contains("FxiOS/") {
+            Browser::Firefox
+        } else if user_agent.contains("Chrome/") || user_agent.contains("CriOS/") {
+            Browser::Chrome
+        } else if user_agent.contains("Safari/") {
+            Browser::Safari
+        } else {
+            Browser::Other
+        }
+    }
+
+    /// Localization key of the steps to allow the microphone again
+    pub fn guidance_key(&self) -> &'static str {
+        match self {
+            Browser::Chrome => "permission-guidance-chrome",
+            Browser::Edge => "permission-guidance-edge",
+            Browser::Firefox => "permission-guidance-firefox",
+            Browser::Safari => "permission-guidance-safari",
+            Browser::Other => "permission-guidance-other",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denied_waits_for_a_change_or_retry() {
+        let mut permission = MicPermission::new(None);
+        assert_eq!(permission.next_step(), PermissionStep::WaitForClick);
+        permission.on_click();
+        assert_eq!(permission.on_request_failed("NotAllowedError"), Ok(()));
+        assert_eq!(permission.next_step(), PermissionStep::ShowGuidance);
+        assert!(!permission.on_state_changed(PermissionState::Denied));
+        assert!(permission.on_state_changed(PermissionState::Granted));
+        assert_eq!(permission.next_step(), PermissionStep::Request);
+
+        permission.on_request_failed("NotAllowedError").unwrap();
+        assert!(permission.on_state_changed(PermissionState::Prompt));
+        assert_eq!(permission.next_step(), PermissionStep::WaitForClick);
+
+        permission.on_request_failed("SecurityError").unwrap();
+        permission.on_retry();
+        assert_eq!(permission.state(), PermissionState::Prompt);
+        assert_eq!(permission.on_request_failed("NotFoundError"), Err(Error::MicrophoneNotAvailable));
+    }
+
+    #[test]
+    fn test_first_request_waits_for_a_click() {
+        let mut permission = MicPermission::new(Some(PermissionState::Granted));
+        assert_eq!(permission.next_step(), PermissionStep::WaitForClick);
+        permission.on_click();
+        assert_eq!(permission.next_step(), PermissionStep::Request);
+        assert_eq!(PermissionState::parse("denied"), Some(PermissionState::Denied));
+        assert_eq!(PermissionState::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_browser_from_user_agent() {
+        let browsers = [
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36", Browser::Chrome),
+            ("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0", Browser::Edge),
+            ("Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0", Browser::Firefox),
+            ("Mozilla/5.0 (Macintosh; Intel Mac OS X 14_2) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15", Browser::Safari),
+            ("Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) CriOS/120.0 Mobile/15E148 Safari/604.1", Browser::Chrome),
+            ("curl/8.4.0", Browser::Other),
+        ];
+        for (user_agent, browser) in browsers {
+            assert_eq!(Browser::from_user_agent(user_agent), browser, "{}", user_agent);
+        }
+    }
+}
@@ -1,12 +1,52 @@
-#![cfg(target_arch = "wasm32")]
-
 pub mod adaptive_ema;
+pub mod announcements;
+pub mod attempt_ghost;
+pub mod bug_report;
+pub mod capabilities;
+pub mod countdown;
+pub mod deep_link;
+pub mod duet;
+pub mod exercise;
+pub mod interval_axis;
+pub mod key_estimation;
+pub mod label_layout;
+pub mod level_meter;
+pub mod localization;
 pub mod logging;
+pub mod metronome;
+pub mod mic_permission;
 pub mod music_theory;
+pub mod needle;
+pub mod note_naming;
+pub mod note_segmentation;
+pub mod notifications;
+pub mod pane_layout;
+pub mod piano_keyboard;
+pub mod pitch_filters;
+pub mod pitch_history;
+pub mod pitch_tracker;
+pub mod presentation_actions;
+pub mod reference_track;
+pub mod saved_session;
+pub mod scala;
+pub mod session_export;
+pub mod session_statistics;
+pub mod session_summary;
+pub mod settings;
 pub mod shared_types;
+pub mod shortcuts;
 pub mod smoothing;
+pub mod spectrogram;
+pub mod staff_notation;
+pub mod strobe;
+pub mod tab_coordination;
+pub mod telemetry;
 pub mod theme;
+pub mod touch_layout;
+pub mod tutorial;
+pub mod tween;
 pub mod utils;
+pub mod vibrato;
 pub mod error_handling;
 pub mod fps_counter;
 
@@ -1,6 +1,4 @@
-#![cfg(target_arch = "wasm32")]
-
-use crate::common::shared_types::{MidiNote, TuningSystem, Scale, semitone_in_scale, is_valid_midi_note};
+use crate::common::shared_types::{MidiNote, TuningSystem, Scale, IntervalReadout, JustIntonationRatios, semitone_in_scale, is_valid_midi_note};
 use crate::common::warn_log;
 
 /// Represents an interval as a base semitone with cents deviation
@@ -10,33 +8,45 @@ pub struct IntervalSemitones {
     pub cents: f32,
 }
 
-/// Just Intonation frequency ratios for the 12-tone chromatic scale
-/// 
-/// These ratios represent the harmonic relationships between notes in Just Intonation.
-/// Index corresponds to semitones from the root (0-11).
-const JUST_INTONATION_RATIOS: [(i32, f32); 12] = [
-    (0, 1.0),           // Unison
-    (1, 16.0 / 15.0),   // Minor second
-    (2, 9.0 / 8.0),     // Major second
-    (3, 6.0 / 5.0),     // Minor third
-    (4, 5.0 / 4.0),     // Major third
-    (5, 4.0 / 3.0),     // Perfect fourth
-    (6, 45.0 / 32.0),   // Tritone
-    (7, 3.0 / 2.0),     // Perfect fifth
-    (8, 8.0 / 5.0),     // Minor sixth
-    (9, 5.0 / 3.0),     // Major sixth
-    (10, 9.0 / 5.0),    // Minor seventh
-    (11, 15.0 / 8.0),   // Major seventh
+/// Fifths of the regular temperaments are stacked from three below the
+/// tonal center (the minor third) to eight above (the augmented fifth), so the
+/// leftover wolf fifth lies between those two degrees
+const FIFTHS_BELOW_TONAL_CENTER: i32 = 3;
+const PYTHAGOREAN_FIFTH_CENTS: f32 = 701.955;
+/// A quarter of the 5/4 major third above two octaves
+const QUARTER_COMMA_MEANTONE_FIFTH_CENTS: f32 = 696.578_4;
+/// Werckmeister III narrows C-G, G-D, D-A and B-F# by a quarter of the Pythagorean comma
+const WERCKMEISTER_III_OFFSETS_CENTS: [f32; 12] = [
+    0.0, -9.775, -7.82, -5.865, -9.775, -1.955, -11.73, -3.91, -7.82, -11.73, -3.91, -7.82,
 ];
 
-/// Get the Just Intonation ratio for a given semitone interval
-fn get_just_intonation_ratio(semitone: i32) -> f32 {
-    let semitone_in_octave = semitone.rem_euclid(12) as usize;
-    JUST_INTONATION_RATIOS[semitone_in_octave].1
+/// Deviation of each chromatic degree above the tonal center from equal temperament, in cents
+pub fn degree_offsets_cents(tuning_system: &TuningSystem) -> [f32; 12] {
+    match tuning_system {
+        TuningSystem::EqualTemperament => [0.0; 12],
+        TuningSystem::JustIntonation(ratios) => {
+            std::array::from_fn(|degree| 1200.0 * ratios.0[degree].value().log2() - 100.0 * degree as f32)
+        }
+        TuningSystem::Pythagorean => regular_temperament_offsets(PYTHAGOREAN_FIFTH_CENTS),
+        TuningSystem::QuarterCommaMeantone => regular_temperament_offsets(QUARTER_COMMA_MEANTONE_FIFTH_CENTS),
+        TuningSystem::WerckmeisterIII => WERCKMEISTER_III_OFFSETS_CENTS,
+        TuningSystem::Custom(offsets) => std::array::from_fn(|degree| offsets.get(degree).copied().unwrap_or(0.0)),
+    }
+}
+
+/// Offsets of a chain of eleven equal fifths, reduced into the octave
+fn regular_temperament_offsets(fifth_cents: f32) -> [f32; 12] {
+    let mut offsets = [0.0; 12];
+    for fifths in -FIFTHS_BELOW_TONAL_CENTER..12 - FIFTHS_BELOW_TONAL_CENTER {
+        let degree = (7 * fifths).rem_euclid(12);
+        let cents = (fifths as f32 * fifth_cents).rem_euclid(1200.0);
+        offsets[degree as usize] = cents - 100.0 * degree as f32;
+    }
+    offsets
 }
 
 pub fn interval_frequency(
-    tuning_system: TuningSystem,
+    tuning_system: &TuningSystem,
     root_frequency_hz: f32,
     interval_semitones: i32,
 ) -> f32 {
@@ -44,18 +54,25 @@ pub fn interval_frequency(
         TuningSystem::EqualTemperament => {
             root_frequency_hz * 2.0_f32.powf(interval_semitones as f32 / 12.0)
         }
-        TuningSystem::JustIntonation => {
-            let octaves = interval_semitones.div_euclid(12);
-            let ratio = get_just_intonation_ratio(interval_semitones);
-            root_frequency_hz * ratio * 2.0_f32.powi(octaves)
+        TuningSystem::JustIntonation(ratios) => {
+            root_frequency_hz * ratios.interval_ratio(interval_semitones)
+        }
+        TuningSystem::Pythagorean | TuningSystem::QuarterCommaMeantone | TuningSystem::WerckmeisterIII | TuningSystem::Custom(_) => {
+            let offset = degree_offsets_cents(tuning_system)[interval_semitones.rem_euclid(12) as usize];
+            root_frequency_hz * 2.0_f32.powf((100.0 * interval_semitones as f32 + offset) / 1200.0)
         }
     }
 }
 
+/// Equal temperament frequency of a MIDI note with A4 at `concert_pitch_hz`
+pub fn midi_note_to_frequency(midi_note: MidiNote, concert_pitch_hz: f32) -> f32 {
+    concert_pitch_hz * 2.0_f32.powf((midi_note as f32 - 69.0) / 12.0)
+}
+
 /// We refer to Equal Temperament A4=440 as "Standard Tuning"
 /// and the frequencies of the notes as "standard frequencies"
 pub fn midi_note_to_standard_frequency(midi_note: MidiNote) -> f32 {
-    440.0 * 2.0_f32.powf((midi_note as f32 - 69.0) / 12.0)
+    midi_note_to_frequency(midi_note, crate::app_config::DEFAULT_CONCERT_PITCH_HZ)
 }
 
 /// Convert a frequency to its interval relative to a root frequency
@@ -64,9 +81,9 @@ pub fn midi_note_to_standard_frequency(midi_note: MidiNote) -> f32 {
 /// This handles cases where cents deviation may exceed ±100 cents.
 /// 
 /// For Equal Temperament: Base semitone is rounded to nearest, cents show deviation
-/// For Just Intonation: Base semitone is closest just interval, cents show deviation from that
+/// For other tuning systems: Base semitone is the closest degree of the tuning, cents show deviation from that
 pub fn frequency_to_interval_semitones(
-    tuning_system: TuningSystem,
+    tuning_system: &TuningSystem,
     root_frequency_hz: f32,
     target_frequency_hz: f32,
 ) -> IntervalSemitones {
@@ -82,26 +99,22 @@ pub fn frequency_to_interval_semitones(
                 cents: cents_deviation,
             }
         }
-        TuningSystem::JustIntonation => {
+        _ => {
             let ratio = target_frequency_hz / root_frequency_hz;
             let octaves = ratio.log2().floor() as i32;
-            let ratio_in_octave = ratio / 2.0_f32.powf(octaves as f32);
-            
-            let (closest_semitone, closest_ratio) = JUST_INTONATION_RATIOS
-                .iter()
-                .min_by(|(_, r1), (_, r2)| {
-                    let target_ratio_freq = root_frequency_hz * ratio_in_octave;
-                    let just_freq1 = root_frequency_hz * r1;
-                    let just_freq2 = root_frequency_hz * r2;
-                    let cents_diff1 = cents_delta(just_freq1, target_ratio_freq).abs();
-                    let cents_diff2 = cents_delta(just_freq2, target_ratio_freq).abs();
-                    cents_diff1.partial_cmp(&cents_diff2).unwrap()
+            let cents_in_octave = 1200.0 * (ratio / 2.0_f32.powi(octaves)).log2();
+
+            // The octave above is a candidate too, for pitches just below it
+            let offsets = degree_offsets_cents(tuning_system);
+            let closest_semitone = (0..=12)
+                .min_by(|&a, &b| {
+                    let distance = |semitone: i32| (100.0 * semitone as f32 + offsets[semitone as usize % 12] - cents_in_octave).abs();
+                    distance(a).total_cmp(&distance(b))
                 })
-                .unwrap();
-            
-            let base_semitones = octaves * 12 + *closest_semitone;
-            let just_intonation_freq = root_frequency_hz * closest_ratio * 2.0_f32.powf(octaves as f32);
-            let cents_deviation = cents_delta(just_intonation_freq, target_frequency_hz);
+                .unwrap_or(0);
+
+            let base_semitones = octaves * 12 + closest_semitone;
+            let cents_deviation = cents_delta(interval_frequency(tuning_system, root_frequency_hz, base_semitones), target_frequency_hz);
             
             IntervalSemitones {
                 semitones: base_semitones,
@@ -140,7 +153,7 @@ pub fn cents_delta(frequency1_hz: f32, frequency2_hz: f32) -> f32 {
 /// rather than first rounding to chromatic semitones, which prevents issues where
 /// non-scale chromatic notes are closer in semitone count but further in frequency.
 pub fn frequency_to_interval_semitones_scale_aware(
-    tuning_system: TuningSystem,
+    tuning_system: &TuningSystem,
     root_frequency_hz: f32,
     target_frequency_hz: f32,
     scale: Scale,
@@ -158,9 +171,10 @@ pub fn frequency_to_interval_semitones_scale_aware(
     let mut closest_semitone = 0;
     let mut smallest_cents_distance = f32::INFINITY;
     
-    // Search across a reasonable range of octaves (±4 octaves = ±48 semitones)
-    // This covers the typical range of musical instruments and human voice
-    for semitone in -48..=48 {
+    // Every scale contains the root, so a scale note lies within an octave of
+    // the nearest chromatic semitone however far the target is from the root
+    let nearest_semitone = (cents_delta(root_frequency_hz, target_frequency_hz) / 100.0).round() as i32;
+    for semitone in nearest_semitone - 12..=nearest_semitone + 12 {
         // Skip notes not in the scale
         if !semitone_in_scale(scale, semitone) {
             continue;
@@ -199,12 +213,13 @@ pub fn frequency_to_interval_semitones_scale_aware(
 
 /// Convert a frequency to the closest MIDI note and cents offset
 /// 
-/// Takes into account the tuning system, tonal center note, and current scale.
+/// Takes into account the concert pitch, tuning system, tonal center note, and current scale.
 /// Returns None if the frequency is invalid or the resulting MIDI note is out of range.
 pub fn frequency_to_midi_note_and_cents(
     frequency: f32,
     tonal_center_note: MidiNote,
-    tuning_system: TuningSystem,
+    concert_pitch_hz: f32,
+    tuning_system: &TuningSystem,
     current_scale: Scale,
 ) -> Option<(MidiNote, f32)> {
     if frequency <= 0.0 {
@@ -212,7 +227,7 @@ pub fn frequency_to_midi_note_and_cents(
         return None;
     }
     
-    let root_pitch = midi_note_to_standard_frequency(tonal_center_note);
+    let root_pitch = midi_note_to_frequency(tonal_center_note, concert_pitch_hz);
     let interval_result = frequency_to_interval_semitones_scale_aware(
         tuning_system,
         root_pitch,
@@ -252,3 +267,198 @@ pub fn semitone_to_interval_name(semitone: i32) -> String {
     interval_name.to_string()
 }
 
+/// Short name of an interval, folded into the octave except for octaves themselves
+pub fn interval_abbreviation(semitones: i32) -> &'static str {
+    if semitones != 0 && semitones % 12 == 0 {
+        return "P8";
+    }
+    match semitones.rem_euclid(12) {
+        0 => "P1",
+        1 => "m2",
+        2 => "M2",
+        3 => "m3",
+        4 => "M3",
+        5 => "P4",
+        6 => "TT",
+        7 => "P5",
+        8 => "m6",
+        9 => "M6",
+        10 => "m7",
+        _ => "M7",
+    }
+}
+
+/// The closest interval of `frequency_hz` above `tonal_center_hz` and how far the
+/// pitch is from its equal-tempered and its just version, taken from `just_ratios`
+pub fn interval_readout(frequency_hz: f32, tonal_center_hz: f32, just_ratios: &JustIntonationRatios) -> IntervalReadout {
+    let cents = cents_delta(tonal_center_hz, frequency_hz);
+    let semitones = (cents / 100.0).round() as i32;
+    IntervalReadout {
+        semitones,
+        abbreviation: interval_abbreviation(semitones),
+        equal_tempered_cents: cents - 100.0 * semitones as f32,
+        just_cents: cents - 1200.0 * just_ratios.interval_ratio(semitones).log2(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::JustIntonationLimit;
+
+    const ROOT_HZ: f32 = 220.0;
+    const TOLERANCE_CENTS: f32 = 0.01;
+
+    fn just(limit: JustIntonationLimit) -> TuningSystem {
+        TuningSystem::JustIntonation(limit.ratios())
+    }
+
+    #[test]
+    fn test_five_limit_known_ratios() {
+        let tuning = just(JustIntonationLimit::FiveLimit);
+        for (semitones, ratio) in [(4, 5.0 / 4.0), (7, 3.0 / 2.0), (9, 5.0 / 3.0), (11, 15.0 / 8.0), (12, 2.0), (19, 3.0), (-5, 3.0 / 4.0)] {
+            let frequency = interval_frequency(&tuning, ROOT_HZ, semitones);
+            assert!(cents_delta(ROOT_HZ * ratio, frequency).abs() < TOLERANCE_CENTS, "{} semitones", semitones);
+        }
+    }
+
+    #[test]
+    fn test_seven_limit_known_ratios() {
+        let tuning = just(JustIntonationLimit::SevenLimit);
+        for (semitones, ratio) in [(3, 7.0 / 6.0), (6, 7.0 / 5.0), (10, 7.0 / 4.0), (7, 3.0 / 2.0)] {
+            let frequency = interval_frequency(&tuning, ROOT_HZ, semitones);
+            assert!(cents_delta(ROOT_HZ * ratio, frequency).abs() < TOLERANCE_CENTS, "{} semitones", semitones);
+        }
+    }
+
+    #[test]
+    fn test_just_major_third_is_flat_of_equal_temperament() {
+        let just_third = interval_frequency(&just(JustIntonationLimit::FiveLimit), ROOT_HZ, 4);
+        let equal_third = interval_frequency(&TuningSystem::EqualTemperament, ROOT_HZ, 4);
+        assert!((cents_delta(equal_third, just_third) + 13.69).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_cents_offset_from_just_interval() {
+        let tuning = just(JustIntonationLimit::FiveLimit);
+        let fifth = ROOT_HZ * 1.5;
+
+        let exact = frequency_to_interval_semitones(&tuning, ROOT_HZ, fifth);
+        assert_eq!(exact.semitones, 7);
+        assert!(exact.cents.abs() < TOLERANCE_CENTS);
+
+        let sharp = frequency_to_interval_semitones(&tuning, ROOT_HZ, fifth * 2.0_f32.powf(10.0 / 1200.0));
+        assert_eq!(sharp.semitones, 7);
+        assert!((sharp.cents - 10.0).abs() < TOLERANCE_CENTS);
+
+        // An equal tempered fifth reads about two cents sharp
+        let equal_fifth = frequency_to_interval_semitones(&tuning, ROOT_HZ, interval_frequency(&TuningSystem::EqualTemperament, ROOT_HZ, 7));
+        assert!((equal_fifth.cents + 1.96).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pitch_just_below_octave_maps_to_octave() {
+        let tuning = just(JustIntonationLimit::FiveLimit);
+        let result = frequency_to_interval_semitones(&tuning, ROOT_HZ, ROOT_HZ * 2.0 * 2.0_f32.powf(-20.0 / 1200.0));
+        assert_eq!(result.semitones, 12);
+        assert!((result.cents + 20.0).abs() < TOLERANCE_CENTS);
+    }
+
+    /// Size of the fifth from `degree` upwards
+    fn fifth_cents(tuning: &TuningSystem, degree: i32) -> f32 {
+        cents_delta(interval_frequency(tuning, ROOT_HZ, degree), interval_frequency(tuning, ROOT_HZ, degree + 7))
+    }
+
+    #[test]
+    fn test_pythagorean_fifths_and_wolf() {
+        let tuning = TuningSystem::Pythagorean;
+        for degree in (0..12).filter(|&degree| degree != 8) {
+            assert!((fifth_cents(&tuning, degree) - 701.955).abs() < TOLERANCE_CENTS, "fifth on {}", degree);
+        }
+        assert!((fifth_cents(&tuning, 8) - 678.495).abs() < TOLERANCE_CENTS);
+        let major_third = interval_frequency(&tuning, ROOT_HZ, 4);
+        assert!(cents_delta(ROOT_HZ * 81.0 / 64.0, major_third).abs() < TOLERANCE_CENTS);
+    }
+
+    #[test]
+    fn test_quarter_comma_meantone_pure_thirds_and_wolf() {
+        let tuning = TuningSystem::QuarterCommaMeantone;
+        for degree in [0, 2, 5, 7, 10] {
+            let third = cents_delta(interval_frequency(&tuning, ROOT_HZ, degree), interval_frequency(&tuning, ROOT_HZ, degree + 4));
+            assert!((third - 386.314).abs() < TOLERANCE_CENTS, "third on {}", degree);
+        }
+        assert!((fifth_cents(&tuning, 0) - 696.578).abs() < TOLERANCE_CENTS);
+        assert!((fifth_cents(&tuning, 8) - 737.637).abs() < TOLERANCE_CENTS);
+    }
+
+    #[test]
+    fn test_werckmeister_iii_has_no_wolf() {
+        let tuning = TuningSystem::WerckmeisterIII;
+        for degree in 0..12 {
+            let fifth = fifth_cents(&tuning, degree);
+            assert!((694.0..=702.0).contains(&fifth), "fifth on {} is {}", degree, fifth);
+        }
+        let major_third = interval_frequency(&tuning, ROOT_HZ, 4);
+        assert!((cents_delta(ROOT_HZ, major_third) - 390.225).abs() < TOLERANCE_CENTS);
+    }
+
+    #[test]
+    fn test_temperament_cents_offset() {
+        let tuning = TuningSystem::QuarterCommaMeantone;
+        let sharp_sixth = interval_frequency(&tuning, ROOT_HZ, 8) * 2.0_f32.powf(15.0 / 1200.0);
+        let result = frequency_to_interval_semitones(&tuning, ROOT_HZ, sharp_sixth);
+        assert_eq!(result.semitones, 8);
+        assert!((result.cents - 15.0).abs() < TOLERANCE_CENTS);
+    }
+
+    #[test]
+    fn test_custom_ratio_table() {
+        let ratios = JustIntonationRatios::FIVE_LIMIT.with_degree(10, crate::common::shared_types::Ratio::new(7, 4));
+        assert_eq!(ratios.limit(), None);
+        let frequency = interval_frequency(&TuningSystem::JustIntonation(ratios), ROOT_HZ, 10);
+        assert!(cents_delta(ROOT_HZ * 1.75, frequency).abs() < TOLERANCE_CENTS);
+    }
+
+    #[test]
+    fn test_concert_pitch() {
+        assert_eq!(midi_note_to_frequency(69, 415.0), 415.0);
+        assert!((midi_note_to_frequency(81, 442.0) - 884.0).abs() < 0.001);
+
+        // 440 Hz reads about 8 cents sharp of A4 when the orchestra tunes to 442 Hz
+        let (note, cents) = frequency_to_midi_note_and_cents(440.0, 69, 442.0, &TuningSystem::EqualTemperament, Scale::Chromatic).unwrap();
+        assert_eq!(note, 69);
+        assert!((cents + 7.85).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_scale_aware_interval_far_from_root() {
+        // G6 above a C1 root is five octaves and a fifth
+        let frequency = midi_note_to_standard_frequency(91) * 2.0_f32.powf(-3.0 / 1200.0);
+        let (note, cents) = frequency_to_midi_note_and_cents(frequency, 24, 440.0, &TuningSystem::EqualTemperament, Scale::Major).unwrap();
+        assert_eq!(note, 91);
+        assert!((cents + 3.0).abs() < TOLERANCE_CENTS);
+
+        let below = frequency_to_interval_semitones_scale_aware(&TuningSystem::EqualTemperament, 1000.0, 1000.0 / 2.0_f32.powf(61.0 / 12.0), Scale::Major);
+        assert_eq!(below.semitones, -61);
+    }
+
+    #[test]
+    fn test_interval_readout() {
+        // A fifth sung 4 cents wide of equal temperament is 2 cents wide of 3/2
+        let frequency = ROOT_HZ * 2.0_f32.powf(704.0 / 1200.0);
+        let readout = interval_readout(frequency, ROOT_HZ, &JustIntonationRatios::FIVE_LIMIT);
+        assert_eq!(readout.semitones, 7);
+        assert!((readout.equal_tempered_cents - 4.0).abs() < TOLERANCE_CENTS);
+        assert!((readout.just_cents - 2.045).abs() < TOLERANCE_CENTS);
+        assert_eq!(readout.to_string(), "P5 +4¢ (just: +2¢)");
+
+        // A just major third an octave up is 14 cents flat of equal temperament
+        let readout = interval_readout(ROOT_HZ * 2.5, ROOT_HZ, &JustIntonationRatios::FIVE_LIMIT);
+        assert_eq!((readout.semitones, readout.abbreviation), (16, "M3"));
+        assert!((readout.equal_tempered_cents + 13.686).abs() < TOLERANCE_CENTS);
+        assert!(readout.just_cents.abs() < TOLERANCE_CENTS);
+
+        assert_eq!(interval_abbreviation(-12), "P8");
+        assert_eq!(interval_abbreviation(-5), "P5");
+    }
+}
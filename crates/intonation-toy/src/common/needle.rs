@@ -0,0 +1,98 @@
+//! Motion of an analog tuner needle
+//!
+//! The needle is a damped spring pulled towards the cents offset, so the
+//! damping ratio decides whether it overshoots and swings back, settles as fast
+//! as it can, or creeps towards the reading. Offsets beyond the scale pin it
+//! at the end stop, and without a pitch it returns to the center.
+
+use crate::app_config::{NEEDLE_NATURAL_FREQUENCY_HZ, NEEDLE_RANGE_CENTS};
+use crate::common::shared_types::NeedleDamping;
+
+/// Longest step of the simulation, to keep it stable at low frame rates
+const MAX_STEP_SECONDS: f32 = 0.004;
+/// Longest time simulated in one update, so a paused tab does not replay the gap
+const MAX_ELAPSED_SECONDS: f32 = 0.1;
+
+impl NeedleDamping {
+    pub fn damping_ratio(&self) -> f32 {
+        match self {
+            NeedleDamping::Light => 0.35,
+            NeedleDamping::Critical => 1.0,
+            NeedleDamping::Heavy => 2.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NeedleMotion {
+    /// Position in cents
+    position: f32,
+    /// Speed in cents per second
+    velocity: f32,
+    last_ms: Option<f64>,
+}
+
+impl NeedleMotion {
+    /// Advance to `now_ms` towards `cents_offset`, returning the position in cents
+    pub fn update(&mut self, now_ms: f64, cents_offset: Option<f32>, damping: NeedleDamping) -> f32 {
+        let elapsed_seconds = self.last_ms.map_or(0.0, |last_ms| ((now_ms - last_ms).max(0.0) / 1000.0) as f32);
+        self.last_ms = Some(now_ms);
+
+        let target = cents_offset.unwrap_or(0.0).clamp(-NEEDLE_RANGE_CENTS, NEEDLE_RANGE_CENTS);
+        let angular_frequency = std::f32::consts::TAU * NEEDLE_NATURAL_FREQUENCY_HZ;
+        let damping_ratio = damping.damping_ratio();
+        let mut remaining = elapsed_seconds.min(MAX_ELAPSED_SECONDS);
+        while remaining > 0.0 {
+            let step = remaining.min(MAX_STEP_SECONDS);
+            let acceleration = angular_frequency * angular_frequency * (target - self.position)
+                - 2.0 * damping_ratio * angular_frequency * self.velocity;
+            self.velocity += acceleration * step;
+            self.position += self.velocity * step;
+            remaining -= step;
+        }
+        // The end stops take all the speed out of the needle
+        if self.position.abs() > NEEDLE_RANGE_CENTS {
+            self.position = self.position.clamp(-NEEDLE_RANGE_CENTS, NEEDLE_RANGE_CENTS);
+            self.velocity = 0.0;
+        }
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Positions every 10 ms for two seconds after a jump to 20 cents
+    fn step_response(damping: NeedleDamping) -> Vec<f32> {
+        let mut needle = NeedleMotion::default();
+        needle.update(0.0, Some(0.0), damping);
+        (1..=200).map(|frame| needle.update(frame as f64 * 10.0, Some(20.0), damping)).collect()
+    }
+
+    #[test]
+    fn test_damping_shapes_step_response() {
+        let light = step_response(NeedleDamping::Light);
+        let critical = step_response(NeedleDamping::Critical);
+        let heavy = step_response(NeedleDamping::Heavy);
+
+        assert!(light.iter().any(|&position| position > 22.0));
+        assert!(critical.iter().all(|&position| position <= 20.0 + 1e-3));
+        // A quarter of a second in, heavier damping lags further behind
+        assert!(heavy[24] < critical[24]);
+        for response in [light, critical, heavy] {
+            assert!((response.last().unwrap() - 20.0).abs() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_end_stops_and_rest() {
+        let mut needle = NeedleMotion::default();
+        needle.update(0.0, Some(200.0), NeedleDamping::Light);
+        let pinned = (1..=100).map(|frame| needle.update(frame as f64 * 10.0, Some(200.0), NeedleDamping::Light)).last();
+        assert_eq!(pinned, Some(NEEDLE_RANGE_CENTS));
+
+        let rest = (101..=400).map(|frame| needle.update(frame as f64 * 10.0, None, NeedleDamping::Critical)).last().unwrap();
+        assert!(rest.abs() < 0.1);
+    }
+}
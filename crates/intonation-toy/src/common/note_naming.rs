@@ -0,0 +1,173 @@
+//! Note names in the naming system chosen by the user
+//!
+//! Letter names carry an octave number and are spelled with flats, with
+//! sharps, or with the accidentals of the key signature of the tonal center
+//! and scale. Solfège and sargam name notes by their degree above the tonal
+//! center, so the octave is shown relative to it: one `'` per octave above and
+//! one `,` per octave below the octave starting at the tonal center.
+//!
+//! Names are taken from written notes, so a transposition moves the notes and
+//! the tonal center together. Letter names follow the language, so in Swedish
+//! B natural is H and B flat is B.
+
+use crate::common::localization::Language;
+use crate::common::shared_types::{MidiNote, NoteNaming, Scale, Transposition};
+
+const FLAT_NAMES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B"];
+const SHARP_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+/// Movable do, with the lowered or raised syllables of the common modes
+const SOLFEGE_NAMES: [&str; 12] = ["Do", "Ra", "Re", "Me", "Mi", "Fa", "Fi", "Sol", "Le", "La", "Te", "Ti"];
+/// Komal swaras in lower case, shuddha in upper case; `Ma` is tivra
+const SARGAM_NAMES: [&str; 12] = ["Sa", "re", "Re", "ga", "Ga", "ma", "Ma", "Pa", "dha", "Dha", "ni", "Ni"];
+/// Pitch classes of the major keys with flats in their key signature
+const FLAT_MAJOR_KEYS: [i32; 6] = [5, 10, 3, 8, 1, 6];
+
+/// Everything that decides the name of a note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteNamer {
+    pub naming: NoteNaming,
+    pub transposition: Transposition,
+    pub tonal_center_note: MidiNote,
+    pub scale: Scale,
+    pub language: Language,
+}
+
+impl NoteNamer {
+    /// Name of a sounding note as written for the instrument
+    pub fn name(&self, concert_note: MidiNote) -> String {
+        let note = self.written(concert_note);
+        let tonal_center = self.written(self.tonal_center_note);
+        match self.naming {
+            NoteNaming::Flats => letter_name_in(note, false, self.language),
+            NoteNaming::Sharps => letter_name_in(note, true, self.language),
+            NoteNaming::ByKey => letter_name_in(note, key_uses_sharps(tonal_center, self.scale), self.language),
+            NoteNaming::Solfege => degree_name(&SOLFEGE_NAMES, note, tonal_center),
+            NoteNaming::Sargam => degree_name(&SARGAM_NAMES, note, tonal_center),
+        }
+    }
+
+    /// Letter name of a sounding note, for naming keys and tonal centers where
+    /// a name relative to the tonal center says nothing
+    pub fn letter_name(&self, concert_note: MidiNote) -> String {
+        letter_name_in(self.written(concert_note), self.spells_with_sharps(), self.language)
+    }
+
+    /// Whether letter names use sharps rather than flats
+    pub fn spells_with_sharps(&self) -> bool {
+        match self.naming {
+            NoteNaming::Flats => false,
+            NoteNaming::Sharps => true,
+            NoteNaming::ByKey | NoteNaming::Solfege | NoteNaming::Sargam => {
+                key_uses_sharps(self.written(self.tonal_center_note), self.scale)
+            }
+        }
+    }
+
+    /// Note number of a sounding note as written for the instrument
+    pub fn written(&self, concert_note: MidiNote) -> i32 {
+        concert_note as i32 + self.transposition.written_offset_semitones()
+    }
+}
+
+/// Letter name and octave of a note number on the MIDI scale, which may lie
+/// outside the MIDI range
+pub fn letter_name(note: i32, sharps: bool) -> String {
+    let names = if sharps { &SHARP_NAMES } else { &FLAT_NAMES };
+    format!("{}{}", names[note.rem_euclid(12) as usize], note.div_euclid(12) - 1)
+}
+
+/// Letter name as written in a language
+fn letter_name_in(note: i32, sharps: bool, language: Language) -> String {
+    let name = letter_name(note, sharps);
+    if !language.writes_b_natural_as_h() {
+        return name;
+    }
+    match note.rem_euclid(12) {
+        10 if !sharps => name.replacen("Bb", "B", 1),
+        11 => name.replacen('B', "H", 1),
+        _ => name,
+    }
+}
+
+fn degree_name(names: &[&str; 12], note: i32, tonal_center: i32) -> String {
+    let semitones = note - tonal_center;
+    let octave = semitones.div_euclid(12);
+    let mark = if octave < 0 { "," } else { "'" };
+    format!("{}{}", names[semitones.rem_euclid(12) as usize], mark.repeat(octave.unsigned_abs() as usize))
+}
+
+/// Whether the key signature of the scale on this tonal center has sharps;
+/// keys without accidentals are spelled with flats
+fn key_uses_sharps(tonal_center: i32, scale: Scale) -> bool {
+    let major_key = (tonal_center + semitones_to_relative_major(scale)).rem_euclid(12);
+    major_key != 0 && !FLAT_MAJOR_KEYS.contains(&major_key)
+}
+
+/// Semitones from the tonal center up to the major key sharing its key signature
+fn semitones_to_relative_major(scale: Scale) -> i32 {
+    match scale {
+        Scale::Dorian => 10,
+        Scale::Phrygian => 8,
+        Scale::Lydian => 7,
+        Scale::Mixolydian => 5,
+        Scale::Locrian => 1,
+        scale => {
+            let pattern = scale.pattern();
+            // Scales with a minor third but no major third read as minor keys
+            if pattern[3] && !pattern[4] { 3 } else { 0 }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namer(naming: NoteNaming, tonal_center_note: MidiNote, scale: Scale) -> NoteNamer {
+        NoteNamer { naming, transposition: Transposition::C, tonal_center_note, scale, language: Language::English }
+    }
+
+    #[test]
+    fn test_letter_spelling() {
+        assert_eq!(namer(NoteNaming::Flats, 60, Scale::Major).name(70), "Bb4");
+        assert_eq!(namer(NoteNaming::Sharps, 60, Scale::Major).name(70), "A#4");
+        // D major has sharps, F major and D minor have flats
+        assert_eq!(namer(NoteNaming::ByKey, 62, Scale::Major).name(66), "F#4");
+        assert_eq!(namer(NoteNaming::ByKey, 65, Scale::Major).name(70), "Bb4");
+        assert_eq!(namer(NoteNaming::ByKey, 62, Scale::Minor).name(70), "Bb4");
+        // E dorian shares the signature of D major
+        assert_eq!(namer(NoteNaming::ByKey, 64, Scale::Dorian).name(73), "C#5");
+    }
+
+    #[test]
+    fn test_degree_names() {
+        let solfege = namer(NoteNaming::Solfege, 62, Scale::Major);
+        assert_eq!(solfege.name(62), "Do");
+        assert_eq!(solfege.name(69), "Sol");
+        assert_eq!(solfege.name(74), "Do'");
+        assert_eq!(solfege.name(61), "Ti,");
+        assert_eq!(solfege.letter_name(62), "D4");
+
+        let sargam = namer(NoteNaming::Sargam, 60, Scale::Major);
+        assert_eq!(sargam.name(63), "ga");
+        assert_eq!(sargam.name(90), "Ma''");
+    }
+
+    #[test]
+    fn test_transposition_moves_tonal_center_too() {
+        let namer = NoteNamer { naming: NoteNaming::Solfege, transposition: Transposition::BFlat, tonal_center_note: 58, scale: Scale::Major, language: Language::English };
+        assert_eq!(namer.name(58), "Do");
+        assert_eq!(namer.letter_name(58), "C4");
+    }
+
+    #[test]
+    fn test_swedish_letter_names() {
+        let swedish = |naming| NoteNamer { language: Language::Swedish, ..namer(naming, 60, Scale::Major) };
+        assert_eq!(swedish(NoteNaming::Flats).name(70), "B4");
+        assert_eq!(swedish(NoteNaming::Flats).name(71), "H4");
+        assert_eq!(swedish(NoteNaming::Sharps).name(70), "A#4");
+        assert_eq!(swedish(NoteNaming::Flats).name(68), "Ab4");
+        assert_eq!(swedish(NoteNaming::Solfege).letter_name(59), "H3");
+        assert_eq!(swedish(NoteNaming::Solfege).name(59), "Ti,");
+    }
+}
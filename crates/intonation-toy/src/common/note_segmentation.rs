@@ -0,0 +1,235 @@
+//! Grouping of pitch frames into sung notes
+//!
+//! A note starts with the first detected pitch and continues while the pitch
+//! stays near the note's running mean, so vibrato and scoops around a held
+//! note do not split it. A jump must last `CHANGE_MS` before it starts the
+//! next note, and short dropouts of the detector are bridged. Onsets are
+//! reported once a note has lasted `MIN_DURATION_MS`, so blips produce no
+//! events at all.
+
+use crate::common::shared_types::{MidiNote, SungNote, SungNoteEvent};
+
+/// Distance from the running mean at which a pitch belongs to another note
+const CHANGE_CENTS: f64 = 80.0;
+/// How long a new pitch must hold before it replaces the current note
+const CHANGE_MS: f64 = 60.0;
+/// Detector dropouts up to this long do not end a note
+const MAX_GAP_MS: f64 = 80.0;
+const MIN_DURATION_MS: f64 = 100.0;
+
+/// Frames of one note so far
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start_ms: f64,
+    last_ms: f64,
+    count: u32,
+    /// Running mean and sum of squared deviations of the pitch in absolute cents
+    mean_cents: f64,
+    squared_deviations: f64,
+    onset_reported: bool,
+}
+
+impl Segment {
+    fn start(now_ms: f64, cents: f64) -> Self {
+        Self {
+            start_ms: now_ms,
+            last_ms: now_ms,
+            count: 1,
+            mean_cents: cents,
+            squared_deviations: 0.0,
+            onset_reported: false,
+        }
+    }
+
+    fn add(&mut self, now_ms: f64, cents: f64) {
+        self.last_ms = now_ms;
+        self.count += 1;
+        let delta = cents - self.mean_cents;
+        self.mean_cents += delta / self.count as f64;
+        self.squared_deviations += delta * (cents - self.mean_cents);
+    }
+
+    fn frequency(&self) -> f32 {
+        2f64.powf(self.mean_cents / 1200.0) as f32
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NoteSegmenter {
+    current: Option<Segment>,
+    /// Frames that left the current note, waiting to hold for `CHANGE_MS`
+    candidate: Option<Segment>,
+}
+
+impl NoteSegmenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance to `now_ms` with the detected frequency, if any. `classify`
+    /// gives the closest note and the cents offset from it for a frequency;
+    /// notes it cannot place produce no events.
+    pub fn update(
+        &mut self,
+        now_ms: f64,
+        frequency: Option<f32>,
+        classify: impl Fn(f32) -> Option<(MidiNote, f32)>,
+    ) -> Vec<SungNoteEvent> {
+        let mut events = Vec::new();
+
+        let Some(frequency) = frequency.filter(|&frequency| frequency > 0.0) else {
+            if self.current.is_some_and(|current| now_ms - current.last_ms > MAX_GAP_MS) {
+                let ended = self.current.take();
+                self.candidate = None;
+                self.finish_segment(ended, &classify, &mut events);
+            }
+            return events;
+        };
+        let cents = 1200.0 * (frequency as f64).log2();
+
+        match (&mut self.current, &mut self.candidate) {
+            (None, _) => self.current = Some(Segment::start(now_ms, cents)),
+            (Some(current), _) if (cents - current.mean_cents).abs() <= CHANGE_CENTS => {
+                current.add(now_ms, cents);
+                self.candidate = None;
+            }
+            (Some(_), Some(candidate)) if (cents - candidate.mean_cents).abs() <= CHANGE_CENTS => {
+                candidate.add(now_ms, cents);
+            }
+            (Some(_), candidate) => *candidate = Some(Segment::start(now_ms, cents)),
+        }
+
+        if let Some(candidate) = self.candidate
+            && now_ms - candidate.start_ms >= CHANGE_MS
+        {
+            let mut ended = self.current.replace(candidate);
+            self.candidate = None;
+            // The previous note ends where the new one starts
+            if let Some(ended) = &mut ended {
+                ended.last_ms = ended.last_ms.min(candidate.start_ms);
+            }
+            self.finish_segment(ended, &classify, &mut events);
+        }
+
+        if let Some(current) = &mut self.current
+            && !current.onset_reported
+            && current.last_ms - current.start_ms >= MIN_DURATION_MS
+            && let Some((note, _)) = classify(current.frequency())
+        {
+            current.onset_reported = true;
+            events.push(SungNoteEvent::Onset { note, start_ms: current.start_ms });
+        }
+        events
+    }
+
+    /// End the current note at the end of the input
+    pub fn finish(&mut self, classify: impl Fn(f32) -> Option<(MidiNote, f32)>) -> Vec<SungNoteEvent> {
+        let mut events = Vec::new();
+        let ended = self.current.take();
+        self.candidate = None;
+        self.finish_segment(ended, &classify, &mut events);
+        events
+    }
+
+    fn finish_segment(
+        &self,
+        segment: Option<Segment>,
+        classify: &impl Fn(f32) -> Option<(MidiNote, f32)>,
+        events: &mut Vec<SungNoteEvent>,
+    ) {
+        let Some(segment) = segment.filter(|segment| segment.onset_reported) else {
+            return;
+        };
+        let frequency = segment.frequency();
+        if let Some((note, cents_offset)) = classify(frequency) {
+            events.push(SungNoteEvent::Offset(SungNote {
+                note,
+                start_ms: segment.start_ms,
+                duration_ms: segment.last_ms - segment.start_ms,
+                frequency,
+                cents_offset,
+                stability_cents: (segment.squared_deviations / segment.count as f64).sqrt() as f32,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::music_theory::{frequency_to_midi_note_and_cents, midi_note_to_standard_frequency};
+    use crate::common::shared_types::{Scale, TuningSystem};
+
+    const FRAME_MS: f64 = 1000.0 / 60.0;
+
+    fn classify(frequency: f32) -> Option<(MidiNote, f32)> {
+        frequency_to_midi_note_and_cents(frequency, 60, 440.0, &TuningSystem::EqualTemperament, Scale::Chromatic)
+    }
+
+    /// Feed one frame per entry of `frames` and collect every event
+    fn run(frames: &[Option<f32>]) -> Vec<SungNoteEvent> {
+        let mut segmenter = NoteSegmenter::new();
+        frames.iter()
+            .enumerate()
+            .flat_map(|(frame, &frequency)| segmenter.update(frame as f64 * FRAME_MS, frequency, classify))
+            .collect()
+    }
+
+    fn held(note: MidiNote, frames: usize) -> Vec<Option<f32>> {
+        vec![Some(midi_note_to_standard_frequency(note)); frames]
+    }
+
+    #[test]
+    fn test_held_note() {
+        let frames: Vec<_> = [held(69, 30), vec![None; 10]].concat();
+        let events = run(&frames);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], SungNoteEvent::Onset { note: 69, start_ms: 0.0 });
+        let SungNoteEvent::Offset(note) = events[1] else { panic!("expected an offset") };
+        assert_eq!(note.note, 69);
+        assert!((note.duration_ms - 29.0 * FRAME_MS).abs() < 1e-9);
+        assert!(note.cents_offset.abs() < 0.01);
+        assert!(note.stability_cents < 0.01);
+    }
+
+    #[test]
+    fn test_note_change_splits_at_the_jump() {
+        let frames: Vec<_> = [held(60, 20), held(64, 20), vec![None; 10]].concat();
+        let events = run(&frames);
+        let notes: Vec<SungNote> = events.iter()
+            .filter_map(|event| match event { SungNoteEvent::Offset(note) => Some(*note), _ => None })
+            .collect();
+        assert_eq!(notes.len(), 2);
+        assert_eq!((notes[0].note, notes[1].note), (60, 64));
+        assert!((notes[1].start_ms - 20.0 * FRAME_MS).abs() < 1e-9);
+        assert!(notes[0].start_ms + notes[0].duration_ms <= notes[1].start_ms);
+    }
+
+    #[test]
+    fn test_vibrato_and_dropouts_stay_one_note() {
+        let base = midi_note_to_standard_frequency(69);
+        let mut frames: Vec<Option<f32>> = (0..60)
+            .map(|frame| {
+                let cents = 50.0 * (2.0 * std::f32::consts::PI * 5.5 * frame as f32 / 60.0).sin();
+                Some(base * 2f32.powf(cents / 1200.0))
+            })
+            .collect();
+        frames[30..33].fill(None);
+        frames.extend([None; 10]);
+        let events = run(&frames);
+        assert_eq!(events.len(), 2);
+        let SungNoteEvent::Offset(note) = events[1] else { panic!("expected an offset") };
+        assert_eq!(note.note, 69);
+        assert!(note.stability_cents > 30.0 && note.stability_cents < 40.0, "stability {}", note.stability_cents);
+    }
+
+    #[test]
+    fn test_blips_are_ignored() {
+        let frames: Vec<_> = [vec![None; 5], held(72, 4), vec![None; 10]].concat();
+        assert!(run(&frames).is_empty());
+
+        // A short jump inside a held note neither splits nor ends it
+        let frames: Vec<_> = [held(60, 20), held(67, 2), held(60, 20), vec![None; 10]].concat();
+        assert_eq!(run(&frames).len(), 2);
+    }
+}
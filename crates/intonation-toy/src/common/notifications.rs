@@ -0,0 +1,124 @@
+//! Errors reported to the user
+//!
+//! A fatal error covers the app with an overlay that stays, and later errors
+//! are not shown over it. Other errors show as toasts that go away after a
+//! while or when dismissed. An error already shown as a toast is not shown
+//! again while its toast is up, so an error repeated every update shows once.
+
+use crate::app_config::{MAX_TOASTS, TOAST_DURATION_MS};
+use crate::common::shared_types::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub id: u32,
+    pub error: Error,
+    shown_ms: f64,
+}
+
+/// What to change on screen
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationChange {
+    ShowFatal(Error),
+    ShowToast(Toast),
+    RemoveToast(u32),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    /// Oldest first
+    toasts: Vec<Toast>,
+    next_id: u32,
+    fatal: Option<Error>,
+}
+
+impl Notifications {
+    /// Report the errors of an update and let toasts shown long enough go
+    pub fn update(&mut self, errors: &[Error], now_ms: f64) -> Vec<NotificationChange> {
+        let mut changes: Vec<_> = self.toasts
+            .extract_if(.., |toast| now_ms - toast.shown_ms >= TOAST_DURATION_MS)
+            .map(|toast| NotificationChange::RemoveToast(toast.id))
+            .collect();
+        for error in errors {
+            if self.fatal.is_some() {
+                break;
+            }
+            if error.is_fatal() {
+                self.fatal = Some(error.clone());
+                changes.push(NotificationChange::ShowFatal(error.clone()));
+                continue;
+            }
+            if self.toasts.iter().any(|toast| &toast.error == error) {
+                continue;
+            }
+            if self.toasts.len() == MAX_TOASTS {
+                changes.push(NotificationChange::RemoveToast(self.toasts.remove(0).id));
+            }
+            let toast = Toast { id: self.next_id, error: error.clone(), shown_ms: now_ms };
+            self.next_id += 1;
+            changes.push(NotificationChange::ShowToast(toast.clone()));
+            self.toasts.push(toast);
+        }
+        changes
+    }
+
+    /// Whether the toast was still up
+    pub fn dismiss(&mut self, id: u32) -> bool {
+        let count = self.toasts.len();
+        self.toasts.retain(|toast| toast.id != id);
+        self.toasts.len() < count
+    }
+
+    pub fn fatal(&self) -> Option<&Error> {
+        self.fatal.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn removed(changes: &[NotificationChange]) -> Vec<u32> {
+        changes.iter().filter_map(|change| match change {
+            NotificationChange::RemoveToast(id) => Some(*id),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_toasts_show_once_and_expire() {
+        let mut notifications = Notifications::default();
+        let glitch = Error::ProcessingError("glitch".to_string());
+        let changes = notifications.update(&[glitch.clone(), glitch.clone()], 0.0);
+        assert_eq!(changes.len(), 1);
+        let NotificationChange::ShowToast(toast) = &changes[0] else {
+            panic!("expected a toast, got {:?}", changes);
+        };
+        assert_eq!(toast.error, glitch);
+
+        assert!(notifications.update(std::slice::from_ref(&glitch), 100.0).is_empty());
+        assert_eq!(removed(&notifications.update(&[], TOAST_DURATION_MS)), [toast.id]);
+        assert_eq!(notifications.update(&[glitch], TOAST_DURATION_MS + 1.0).len(), 1);
+    }
+
+    #[test]
+    fn test_dismissed_and_overflowing_toasts_go() {
+        let mut notifications = Notifications::default();
+        let errors: Vec<_> = (0..MAX_TOASTS + 1).map(|device| Error::InputDeviceChanged(device.to_string())).collect();
+        let changes = notifications.update(&errors, 0.0);
+        assert_eq!(removed(&changes), [0]);
+
+        assert!(notifications.dismiss(1));
+        assert!(!notifications.dismiss(1));
+        // A dismissed error shows again when it happens again
+        assert_eq!(notifications.update(&errors[1..2], 10.0).len(), 1);
+    }
+
+    #[test]
+    fn test_first_fatal_error_stays() {
+        let mut notifications = Notifications::default();
+        let changes = notifications.update(&[Error::ProcessingError("glitch".to_string()), Error::MicrophoneNotAvailable, Error::BrowserError], 0.0);
+        assert!(matches!(changes[..], [NotificationChange::ShowToast(_), NotificationChange::ShowFatal(Error::MicrophoneNotAvailable)]));
+        assert_eq!(notifications.fatal(), Some(&Error::MicrophoneNotAvailable));
+        assert!(notifications.update(&[Error::InputDeviceChanged("USB".to_string()), Error::GraphicsContextLost], 1.0).is_empty());
+    }
+}
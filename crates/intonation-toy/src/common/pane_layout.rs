@@ -0,0 +1,283 @@
+//! Arrangement of the views on the canvas into resizable panes
+//!
+//! The main display fills the left of the canvas and the other views are
+//! stacked top to bottom in a column on its right. Dragging the splitters
+//! between the panes resizes them. Sizes are kept as parts of the canvas, so
+//! a stored layout fits any canvas size. Positions are in pixels from the
+//! bottom left of the canvas.
+
+use serde::{Deserialize, Serialize};
+use crate::app_config::{DEFAULT_SIDE_COLUMN_FRACTION, MIN_PANE_FRACTION, SPLITTER_GRAB_DISTANCE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaneKind {
+    /// The selected display scene with its overlays
+    Display,
+    PitchHistory,
+    Spectrogram,
+    Statistics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PaneRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PaneRect {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Splitter {
+    /// Between the main display and the side column
+    Column,
+    /// Below the side pane at this index
+    Row(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaneLayout {
+    /// Panes of the side column, top to bottom; never the main display
+    side_panes: Vec<PaneKind>,
+    /// Heights of the side panes as parts of the canvas height, summing to one
+    side_heights: Vec<f32>,
+    /// Width of the side column as a part of the canvas width
+    side_width: f32,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            side_panes: Vec::new(),
+            side_heights: Vec::new(),
+            side_width: DEFAULT_SIDE_COLUMN_FRACTION,
+        }
+    }
+}
+
+impl PaneLayout {
+    pub fn side_panes(&self) -> &[PaneKind] {
+        &self.side_panes
+    }
+
+    pub fn shows(&self, kind: PaneKind) -> bool {
+        kind == PaneKind::Display || self.side_panes.contains(&kind)
+    }
+
+    /// Add a pane at the bottom of the side column, taking an equal share of
+    /// its height from the others, or remove it and share out its height;
+    /// the main display is always shown
+    pub fn set_shown(&mut self, kind: PaneKind, shown: bool) {
+        if kind == PaneKind::Display || shown == self.shows(kind) {
+            return;
+        }
+        if shown {
+            let count = self.side_panes.len() as f32;
+            self.side_heights.iter_mut().for_each(|height| *height *= count / (count + 1.0));
+            self.side_panes.push(kind);
+            self.side_heights.push(1.0 / (count + 1.0));
+        } else if let Some(index) = self.side_panes.iter().position(|&pane| pane == kind) {
+            self.side_panes.remove(index);
+            self.side_heights.remove(index);
+            let total: f32 = self.side_heights.iter().sum();
+            if total > 0.0 {
+                self.side_heights.iter_mut().for_each(|height| *height /= total);
+            }
+        }
+    }
+
+    /// Repair a layout read back from storage: duplicate panes are dropped
+    /// and heights that do not fit the panes are shared out equally
+    pub fn normalized(mut self) -> Self {
+        let mut panes = Vec::new();
+        for pane in self.side_panes.drain(..) {
+            if pane != PaneKind::Display && !panes.contains(&pane) {
+                panes.push(pane);
+            }
+        }
+        let heights_fit = self.side_heights.len() == panes.len()
+            && self.side_heights.iter().all(|height| height.is_finite() && *height > 0.0);
+        if !heights_fit {
+            self.side_heights = vec![1.0 / panes.len() as f32; panes.len()];
+        }
+        let total: f32 = self.side_heights.iter().sum();
+        self.side_heights.iter_mut().for_each(|height| *height /= total);
+        self.side_panes = panes;
+        if !self.side_width.is_finite() {
+            self.side_width = DEFAULT_SIDE_COLUMN_FRACTION;
+        }
+        self.side_width = self.side_width.clamp(MIN_PANE_FRACTION, 1.0 - MIN_PANE_FRACTION);
+        self
+    }
+
+    /// Area of every shown pane on a canvas of the given size, the main
+    /// display first
+    pub fn rects(&self, width: f32, height: f32) -> Vec<(PaneKind, PaneRect)> {
+        if self.side_panes.is_empty() {
+            return vec![(PaneKind::Display, PaneRect { x: 0.0, y: 0.0, width, height })];
+        }
+        let main_width = self.column_x(width);
+        let mut rects = vec![(PaneKind::Display, PaneRect { x: 0.0, y: 0.0, width: main_width, height })];
+        let mut top = height;
+        for (&pane, &part) in self.side_panes.iter().zip(&self.side_heights) {
+            let pane_height = part * height;
+            rects.push((pane, PaneRect { x: main_width, y: top - pane_height, width: width - main_width, height: pane_height }));
+            top -= pane_height;
+        }
+        rects
+    }
+
+    /// Splitter within grabbing distance of a point
+    pub fn splitter_at(&self, x: f32, y: f32, width: f32, height: f32) -> Option<Splitter> {
+        if self.side_panes.is_empty() {
+            return None;
+        }
+        let column_x = self.column_x(width);
+        if (x - column_x).abs() <= SPLITTER_GRAB_DISTANCE {
+            return Some(Splitter::Column);
+        }
+        if x < column_x {
+            return None;
+        }
+        (0..self.side_panes.len() - 1)
+            .find(|&index| (y - self.row_y(index, height)).abs() <= SPLITTER_GRAB_DISTANCE)
+            .map(Splitter::Row)
+    }
+
+    /// Move a splitter to a point, keeping every pane at least
+    /// `MIN_PANE_FRACTION` of the canvas wide and high where it fits
+    pub fn drag(&mut self, splitter: Splitter, x: f32, y: f32, width: f32, height: f32) {
+        match splitter {
+            Splitter::Column => {
+                self.side_width = (1.0 - x / width).clamp(MIN_PANE_FRACTION, 1.0 - MIN_PANE_FRACTION);
+            }
+            Splitter::Row(index) if index + 1 < self.side_panes.len() => {
+                let combined = self.side_heights[index] + self.side_heights[index + 1];
+                let start: f32 = self.side_heights[..index].iter().sum();
+                let min = MIN_PANE_FRACTION.min(combined * 0.5);
+                let upper = (1.0 - y / height - start).clamp(min, combined - min);
+                self.side_heights[index] = upper;
+                self.side_heights[index + 1] = combined - upper;
+            }
+            Splitter::Row(_) => {}
+        }
+    }
+
+    /// Splitter lines as thin areas of the given thickness
+    pub fn splitter_rects(&self, width: f32, height: f32, thickness: f32) -> Vec<PaneRect> {
+        if self.side_panes.is_empty() {
+            return Vec::new();
+        }
+        let column_x = self.column_x(width);
+        let mut rects = vec![PaneRect { x: column_x - thickness * 0.5, y: 0.0, width: thickness, height }];
+        rects.extend((0..self.side_panes.len() - 1).map(|index| PaneRect {
+            x: column_x,
+            y: self.row_y(index, height) - thickness * 0.5,
+            width: width - column_x,
+            height: thickness,
+        }));
+        rects
+    }
+
+    fn column_x(&self, width: f32) -> f32 {
+        width * (1.0 - self.side_width)
+    }
+
+    /// Height of the splitter below the side pane at `index`
+    fn row_y(&self, index: usize, height: f32) -> f32 {
+        height * (1.0 - self.side_heights[..=index].iter().sum::<f32>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_rects() {
+        let mut layout = PaneLayout::default();
+        assert_eq!(layout.rects(800.0, 600.0), vec![(PaneKind::Display, PaneRect { x: 0.0, y: 0.0, width: 800.0, height: 600.0 })]);
+
+        layout.set_shown(PaneKind::PitchHistory, true);
+        layout.set_shown(PaneKind::Statistics, true);
+        let rects = layout.rects(1000.0, 600.0);
+        let main_width = 1000.0 * (1.0 - DEFAULT_SIDE_COLUMN_FRACTION);
+        assert_eq!(rects.len(), 3);
+        assert!(approx(rects[0].1.width, main_width));
+        assert_eq!(rects[1].0, PaneKind::PitchHistory);
+        assert!(approx(rects[1].1.y, 300.0) && approx(rects[1].1.height, 300.0));
+        assert_eq!(rects[2].0, PaneKind::Statistics);
+        assert!(approx(rects[2].1.y, 0.0) && approx(rects[2].1.x, main_width));
+        assert!(rects[2].1.contains(main_width + 1.0, 10.0));
+        assert!(!rects[2].1.contains(main_width - 1.0, 10.0));
+    }
+
+    #[test]
+    fn test_showing_and_hiding_panes_shares_heights() {
+        let mut layout = PaneLayout::default();
+        layout.set_shown(PaneKind::Display, false);
+        assert!(layout.shows(PaneKind::Display));
+
+        for pane in [PaneKind::PitchHistory, PaneKind::Spectrogram, PaneKind::Statistics] {
+            layout.set_shown(pane, true);
+        }
+        layout.set_shown(PaneKind::Spectrogram, true);
+        assert_eq!(layout.side_panes().len(), 3);
+        assert!(layout.side_heights.iter().all(|&height| approx(height, 1.0 / 3.0)));
+
+        layout.set_shown(PaneKind::PitchHistory, false);
+        assert_eq!(layout.side_panes(), [PaneKind::Spectrogram, PaneKind::Statistics]);
+        assert!(layout.side_heights.iter().all(|&height| approx(height, 0.5)));
+    }
+
+    #[test]
+    fn test_dragging_splitters() {
+        let mut layout = PaneLayout::default();
+        layout.set_shown(PaneKind::PitchHistory, true);
+        layout.set_shown(PaneKind::Spectrogram, true);
+        let (width, height) = (1000.0, 800.0);
+        let column_x = 1000.0 * (1.0 - DEFAULT_SIDE_COLUMN_FRACTION);
+
+        assert_eq!(layout.splitter_at(column_x + 2.0, 100.0, width, height), Some(Splitter::Column));
+        assert_eq!(layout.splitter_at(column_x + 100.0, 402.0, width, height), Some(Splitter::Row(0)));
+        assert_eq!(layout.splitter_at(column_x - 100.0, 400.0, width, height), None);
+        assert_eq!(layout.splitter_at(column_x + 100.0, 600.0, width, height), None);
+
+        layout.drag(Splitter::Column, 500.0, 0.0, width, height);
+        assert!(approx(layout.rects(width, height)[0].1.width, 500.0));
+        layout.drag(Splitter::Column, 990.0, 0.0, width, height);
+        assert!(approx(layout.rects(width, height)[0].1.width, 1000.0 * (1.0 - MIN_PANE_FRACTION)));
+
+        layout.drag(Splitter::Row(0), 0.0, 200.0, width, height);
+        assert!(approx(layout.side_heights[0], 0.75) && approx(layout.side_heights[1], 0.25));
+        layout.drag(Splitter::Row(0), 0.0, 0.0, width, height);
+        assert!(approx(layout.side_heights[1], MIN_PANE_FRACTION));
+        layout.drag(Splitter::Row(1), 0.0, 0.0, width, height);
+        assert!(approx(layout.side_heights.iter().sum(), 1.0));
+    }
+
+    #[test]
+    fn test_normalized_repairs_stored_layouts() {
+        let stored = PaneLayout {
+            side_panes: vec![PaneKind::Statistics, PaneKind::Display, PaneKind::Statistics, PaneKind::Spectrogram],
+            side_heights: vec![0.5],
+            side_width: 2.0,
+        };
+        let layout = stored.normalized();
+        assert_eq!(layout.side_panes(), [PaneKind::Statistics, PaneKind::Spectrogram]);
+        assert_eq!(layout.side_heights, vec![0.5, 0.5]);
+        assert!(approx(layout.side_width, 1.0 - MIN_PANE_FRACTION));
+
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(serde_json::from_str::<PaneLayout>(&json).unwrap(), layout);
+    }
+}
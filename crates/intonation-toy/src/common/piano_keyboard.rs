@@ -0,0 +1,120 @@
+//! Layout and hit testing of an on-screen piano keyboard
+//!
+//! White keys share the width evenly. Black keys are narrower and shorter,
+//! centered on the edge between their white neighbours and drawn on top, so a
+//! press on a black key never reaches the white key below it.
+
+use crate::common::shared_types::MidiNote;
+
+/// Black key width as a fraction of the white key width
+const BLACK_KEY_WIDTH: f32 = 0.6;
+/// Black key height as a fraction of the keyboard height
+const BLACK_KEY_HEIGHT: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PianoKey {
+    pub note: MidiNote,
+    pub black: bool,
+    pub left: f32,
+    pub bottom: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PianoKey {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        (self.left..self.left + self.width).contains(&x) && (self.bottom..self.bottom + self.height).contains(&y)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PianoLayout {
+    /// White keys, then black keys, in drawing order
+    keys: Vec<PianoKey>,
+}
+
+impl PianoLayout {
+    /// Keys from `first_note` to `last_note`, both white, filling the given rectangle
+    pub fn new(first_note: MidiNote, last_note: MidiNote, left: f32, bottom: f32, width: f32, height: f32) -> Self {
+        assert!(first_note < last_note && !is_black(first_note) && !is_black(last_note), "Keyboard must start and end on white keys");
+        let notes = first_note..=last_note;
+        let white_key_width = width / notes.clone().filter(|&note| !is_black(note)).count() as f32;
+
+        let mut keys = Vec::new();
+        let mut black_keys = Vec::new();
+        let mut white_keys_left = 0;
+        for note in notes {
+            if is_black(note) {
+                let center = left + white_keys_left as f32 * white_key_width;
+                black_keys.push(PianoKey {
+                    note,
+                    black: true,
+                    left: center - 0.5 * BLACK_KEY_WIDTH * white_key_width,
+                    bottom: bottom + (1.0 - BLACK_KEY_HEIGHT) * height,
+                    width: BLACK_KEY_WIDTH * white_key_width,
+                    height: BLACK_KEY_HEIGHT * height,
+                });
+            } else {
+                keys.push(PianoKey {
+                    note,
+                    black: false,
+                    left: left + white_keys_left as f32 * white_key_width,
+                    bottom,
+                    width: white_key_width,
+                    height,
+                });
+                white_keys_left += 1;
+            }
+        }
+        keys.extend(black_keys);
+        Self { keys }
+    }
+
+    pub fn keys(&self) -> &[PianoKey] {
+        &self.keys
+    }
+
+    /// Note of the key at a point, preferring the black keys drawn on top
+    pub fn note_at(&self, x: f32, y: f32) -> Option<MidiNote> {
+        self.keys.iter().rev().find(|key| key.contains(x, y)).map(|key| key.note)
+    }
+}
+
+fn is_black(note: MidiNote) -> bool {
+    matches!(note % 12, 1 | 3 | 6 | 8 | 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One octave from C4 to C5, with 10 units per white key and 100 units high
+    fn octave() -> PianoLayout {
+        PianoLayout::new(60, 72, 0.0, 0.0, 80.0, 100.0)
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = octave();
+        assert_eq!(layout.keys().len(), 13);
+        assert_eq!(layout.keys().iter().filter(|key| !key.black).count(), 8);
+        // C#4 straddles the edge between C4 and D4
+        let c_sharp = layout.keys().iter().find(|key| key.note == 61).unwrap();
+        for (actual, expected) in [(c_sharp.left, 7.0), (c_sharp.width, 6.0), (c_sharp.bottom, 40.0)] {
+            assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_hit_testing() {
+        let layout = octave();
+        // Low on the keyboard only white keys are hit
+        assert_eq!(layout.note_at(9.0, 10.0), Some(60));
+        // Higher up the black key covers the edge of its neighbours
+        assert_eq!(layout.note_at(9.0, 90.0), Some(61));
+        assert_eq!(layout.note_at(5.0, 90.0), Some(60));
+        assert_eq!(layout.note_at(75.0, 50.0), Some(72));
+        assert_eq!(layout.note_at(85.0, 50.0), None);
+        assert_eq!(layout.note_at(5.0, 110.0), None);
+    }
+}
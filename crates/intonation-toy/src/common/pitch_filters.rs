@@ -0,0 +1,139 @@
+//! Filters for smoothing detected pitch
+//!
+//! A running median drops isolated octave errors and other outliers without
+//! lagging behind steady changes. The one-euro filter (Casiez, Roussel and
+//! Vogel, 2012) is a low-pass filter whose cutoff rises with the speed of the
+//! signal, so a held tone is smoothed strongly while a fast passage follows
+//! with little lag.
+
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+/// Cutoff for the speed estimate of the one-euro filter
+const DERIVATIVE_CUTOFF_HZ: f32 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct MedianFilter {
+    window: usize,
+    values: VecDeque<f32>,
+}
+
+impl MedianFilter {
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "Median window must not be empty");
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Median of the last `window` values; the mean of the middle two for even counts
+    pub fn apply(&mut self, value: f32) -> f32 {
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+
+        let mut sorted: Vec<f32> = self.values.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let middle = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            0.5 * (sorted[middle - 1] + sorted[middle])
+        } else {
+            sorted[middle]
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.values.clear();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OneEuroFilter {
+    min_cutoff_hz: f32,
+    /// Cutoff increase per unit of speed
+    beta: f32,
+    /// Time, filtered value and filtered speed of the previous sample
+    previous: Option<(f64, f32, f32)>,
+}
+
+impl OneEuroFilter {
+    pub fn new(min_cutoff_hz: f32, beta: f32) -> Self {
+        Self {
+            min_cutoff_hz,
+            beta,
+            previous: None,
+        }
+    }
+
+    pub fn apply(&mut self, value: f32, timestamp_seconds: f64) -> f32 {
+        let Some((last_seconds, last_value, last_speed)) = self.previous else {
+            self.previous = Some((timestamp_seconds, value, 0.0));
+            return value;
+        };
+        let elapsed = (timestamp_seconds - last_seconds) as f32;
+        if elapsed <= 0.0 {
+            return last_value;
+        }
+
+        let speed_factor = smoothing_factor(DERIVATIVE_CUTOFF_HZ, elapsed);
+        let speed = speed_factor * (value - last_value) / elapsed + (1.0 - speed_factor) * last_speed;
+        let factor = smoothing_factor(self.min_cutoff_hz + self.beta * speed.abs(), elapsed);
+        let filtered = factor * value + (1.0 - factor) * last_value;
+
+        self.previous = Some((timestamp_seconds, filtered, speed));
+        filtered
+    }
+
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+/// Weight of a new sample in a first-order low-pass filter with the given cutoff
+fn smoothing_factor(cutoff_hz: f32, elapsed_seconds: f32) -> f32 {
+    let time_constant = 1.0 / (2.0 * PI * cutoff_hz);
+    1.0 / (1.0 + time_constant / elapsed_seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_SECONDS: f64 = 1.0 / 60.0;
+
+    #[test]
+    fn test_median_drops_outliers() {
+        let mut filter = MedianFilter::new(5);
+        let outputs: Vec<f32> = [440.0, 441.0, 880.0, 439.0, 440.0].into_iter().map(|value| filter.apply(value)).collect();
+        assert_eq!(outputs, vec![440.0, 440.5, 441.0, 440.5, 440.0]);
+
+        filter.reset();
+        assert_eq!(filter.apply(220.0), 220.0);
+    }
+
+    #[test]
+    fn test_one_euro_smooths_jitter() {
+        let mut filter = OneEuroFilter::new(1.0, 0.01);
+        let outputs: Vec<f32> = (0..120)
+            .map(|frame| {
+                let jitter = if frame % 2 == 0 { 10.0 } else { -10.0 };
+                filter.apply(jitter, frame as f64 * FRAME_SECONDS)
+            })
+            .collect();
+        assert_eq!(outputs[0], 10.0);
+        assert!(outputs[60..].iter().all(|value| value.abs() < 2.0));
+    }
+
+    #[test]
+    fn test_one_euro_follows_fast_changes() {
+        // A jump of a whole tone, in cents
+        let settle_frames = |beta: f32| {
+            let mut filter = OneEuroFilter::new(1.0, beta);
+            filter.apply(0.0, 0.0);
+            (1..).find(|&frame| filter.apply(200.0, frame as f64 * FRAME_SECONDS) > 190.0).unwrap()
+        };
+        assert!(settle_frames(0.01) < settle_frames(0.0) / 2);
+    }
+}
@@ -0,0 +1,172 @@
+//! Recent detected pitches with time-windowed queries
+//!
+//! The model records one sample per update while a pitch is detected. Samples
+//! older than the retention period are dropped, so the buffer stays bounded
+//! however long a session runs. Windows are measured back from the time of the
+//! latest update rather than the latest sample, so silence empties a window.
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PitchSample {
+    pub timestamp_ms: f64,
+    /// Frequency as detected, before smoothing
+    pub frequency: f32,
+    /// Clarity reported by the pitch detector, 0-1
+    pub clarity: f32,
+    /// Deviation from the closest note of the tuning system
+    pub cents_offset: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PitchHistory {
+    samples: VecDeque<PitchSample>,
+    retention_ms: f64,
+    now_ms: f64,
+}
+
+impl PitchHistory {
+    pub fn new(retention_ms: f64) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            retention_ms,
+            now_ms: 0.0,
+        }
+    }
+
+    /// Advance to `now_ms`, appending `sample` when a pitch was detected
+    pub fn record(&mut self, now_ms: f64, sample: Option<PitchSample>) {
+        self.now_ms = now_ms;
+        if let Some(sample) = sample {
+            self.samples.push_back(sample);
+        }
+        while self.samples.front().is_some_and(|oldest| now_ms - oldest.timestamp_ms > self.retention_ms) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Time of the latest update
+    pub fn now_ms(&self) -> f64 {
+        self.now_ms
+    }
+
+    /// Samples from the last `window_ms`, oldest first
+    pub fn history(&self, window_ms: f64) -> impl Iterator<Item = &PitchSample> {
+        let start_ms = self.now_ms - window_ms;
+        let first = self.samples.partition_point(|sample| sample.timestamp_ms < start_ms);
+        self.samples.range(first..)
+    }
+
+    /// The last `window_ms` split into `points` equal time slots, each holding
+    /// the mean of its samples; slots without samples are left out
+    pub fn decimated(&self, window_ms: f64, points: usize) -> Vec<PitchSample> {
+        if points == 0 || window_ms <= 0.0 {
+            return Vec::new();
+        }
+        let start_ms = self.now_ms - window_ms;
+        let slot_ms = window_ms / points as f64;
+
+        let mut series: Vec<PitchSample> = Vec::with_capacity(points);
+        let mut current_slot = None;
+        let mut count = 0usize;
+        for sample in self.history(window_ms) {
+            let slot = (((sample.timestamp_ms - start_ms) / slot_ms) as usize).min(points - 1);
+            if current_slot != Some(slot) {
+                if let Some(mean) = series.last_mut() {
+                    divide(mean, count);
+                }
+                series.push(PitchSample { timestamp_ms: 0.0, frequency: 0.0, clarity: 0.0, cents_offset: 0.0 });
+                current_slot = Some(slot);
+                count = 0;
+            }
+            if let Some(sum) = series.last_mut() {
+                sum.timestamp_ms += sample.timestamp_ms;
+                sum.frequency += sample.frequency;
+                sum.clarity += sample.clarity;
+                sum.cents_offset += sample.cents_offset;
+            }
+            count += 1;
+        }
+        if let Some(mean) = series.last_mut() {
+            divide(mean, count);
+        }
+        series
+    }
+}
+
+fn divide(sum: &mut PitchSample, count: usize) {
+    sum.timestamp_ms /= count as f64;
+    sum.frequency /= count as f32;
+    sum.clarity /= count as f32;
+    sum.cents_offset /= count as f32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: f64 = 10.0;
+
+    fn sample(timestamp_ms: f64, frequency: f32) -> PitchSample {
+        PitchSample { timestamp_ms, frequency, clarity: 0.9, cents_offset: frequency - 440.0 }
+    }
+
+    /// History with one sample per frame from 0 to `until_ms`
+    fn filled(until_ms: f64, retention_ms: f64) -> PitchHistory {
+        let mut history = PitchHistory::new(retention_ms);
+        let mut now_ms = 0.0;
+        while now_ms <= until_ms {
+            history.record(now_ms, Some(sample(now_ms, 440.0 + (now_ms / FRAME_MS) as f32)));
+            now_ms += FRAME_MS;
+        }
+        history
+    }
+
+    #[test]
+    fn test_retention_drops_old_samples() {
+        let history = filled(1000.0, 500.0);
+        let first = history.history(f64::INFINITY).next().unwrap();
+        assert_eq!(first.timestamp_ms, 500.0);
+        assert_eq!(history.history(f64::INFINITY).count(), 51);
+    }
+
+    #[test]
+    fn test_window_is_measured_from_latest_update() {
+        let mut history = filled(1000.0, 5000.0);
+        assert_eq!(history.history(100.0).count(), 11);
+
+        // Silence moves the window on without adding samples
+        history.record(1050.0, None);
+        assert_eq!(history.history(100.0).count(), 6);
+        history.record(2000.0, None);
+        assert_eq!(history.history(100.0).count(), 0);
+    }
+
+    #[test]
+    fn test_decimated_averages_each_slot() {
+        let history = filled(1000.0, 5000.0);
+        let series = history.decimated(1000.0, 10);
+        assert_eq!(series.len(), 10);
+        // The first slot covers frames 0 to 9, the last one frames 90 to 100
+        assert!((series[0].frequency - 444.5).abs() < 1e-3);
+        assert!((series[0].timestamp_ms - 45.0).abs() < 1e-9);
+        assert!((series[9].cents_offset - 95.0).abs() < 1e-3);
+        assert!(series.windows(2).all(|pair| pair[0].timestamp_ms < pair[1].timestamp_ms));
+    }
+
+    #[test]
+    fn test_decimated_skips_empty_slots() {
+        let mut history = PitchHistory::new(5000.0);
+        history.record(0.0, Some(sample(0.0, 440.0)));
+        history.record(1900.0, Some(sample(1900.0, 450.0)));
+        let series = history.decimated(2000.0, 10);
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].frequency, 440.0);
+        assert_eq!(series[1].frequency, 450.0);
+        assert!(history.decimated(1000.0, 0).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use crate::app_config::{
+    PITCH_MEDIAN_FRAMES, PITCH_OCTAVE_CONFIRM_FRAMES, PITCH_OCTAVE_TOLERANCE_CENTS,
+    PITCH_ONSET_FRAMES, PITCH_RELEASE_FRAMES,
+};
+
+/// Cleans up raw detector frequencies before they reach the model
+///
+/// A pitch is only reported after a few consecutive detections and is held
+/// through short dropouts. Readings an octave away from the tracked pitch are
+/// folded back into its octave until the jump persists, and the output is the
+/// median of the last few readings.
+pub struct PitchTracker {
+    recent: VecDeque<f32>,
+    tracked: Option<f32>,
+    detected_frames: u32,
+    missed_frames: u32,
+    octave_jump_frames: u32,
+}
+
+impl PitchTracker {
+    pub fn new() -> Self {
+        Self {
+            recent: VecDeque::with_capacity(PITCH_MEDIAN_FRAMES),
+            tracked: None,
+            detected_frames: 0,
+            missed_frames: 0,
+            octave_jump_frames: 0,
+        }
+    }
+
+    /// Feed one frame's raw frequency, `None` when nothing was detected
+    pub fn update(&mut self, frequency: Option<f32>) -> Option<f32> {
+        let Some(frequency) = frequency else {
+            self.detected_frames = 0;
+            self.missed_frames = self.missed_frames.saturating_add(1);
+            if self.missed_frames > PITCH_RELEASE_FRAMES {
+                self.reset();
+            }
+            return self.tracked;
+        };
+
+        self.missed_frames = 0;
+        self.detected_frames = self.detected_frames.saturating_add(1);
+
+        let frequency = self.correct_octave(frequency);
+        if self.recent.len() == PITCH_MEDIAN_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(frequency);
+
+        if self.tracked.is_none() && self.detected_frames < PITCH_ONSET_FRAMES {
+            return None;
+        }
+        self.tracked = Some(median(&self.recent));
+        self.tracked
+    }
+
+    /// Fold a reading an octave away from the tracked pitch back into its
+    /// octave, unless the jump has lasted long enough to be real
+    fn correct_octave(&mut self, frequency: f32) -> f32 {
+        let Some(tracked) = self.tracked else {
+            return frequency;
+        };
+
+        let cents = 1200.0 * (frequency / tracked).log2();
+        let octaves = (cents / 1200.0).round();
+        let is_octave_jump = octaves != 0.0 && (cents - octaves * 1200.0).abs() <= PITCH_OCTAVE_TOLERANCE_CENTS;
+        if !is_octave_jump {
+            self.octave_jump_frames = 0;
+            return frequency;
+        }
+
+        self.octave_jump_frames += 1;
+        if self.octave_jump_frames < PITCH_OCTAVE_CONFIRM_FRAMES {
+            return frequency / 2f32.powf(octaves);
+        }
+
+        // Readings from before the jump would drag the median back
+        self.octave_jump_frames = 0;
+        self.recent.clear();
+        frequency
+    }
+
+    fn reset(&mut self) {
+        self.recent.clear();
+        self.tracked = None;
+        self.octave_jump_frames = 0;
+    }
+}
+
+impl Default for PitchTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn median(values: &VecDeque<f32>) -> f32 {
+    let mut sorted: Vec<f32> = values.iter().copied().collect();
+    sorted.sort_by(f32::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(tracker: &mut PitchTracker, frequencies: &[Option<f32>]) -> Vec<Option<f32>> {
+        frequencies.iter().map(|&frequency| tracker.update(frequency)).collect()
+    }
+
+    #[test]
+    fn test_reports_after_onset_and_holds_through_dropouts() {
+        let mut tracker = PitchTracker::new();
+        let onset = vec![Some(440.0); PITCH_ONSET_FRAMES as usize];
+        let outputs = feed(&mut tracker, &onset);
+        assert!(outputs[..outputs.len() - 1].iter().all(Option::is_none));
+        assert_eq!(outputs.last().copied().flatten(), Some(440.0));
+
+        let dropout = vec![None; PITCH_RELEASE_FRAMES as usize];
+        assert!(feed(&mut tracker, &dropout).iter().all(|output| *output == Some(440.0)));
+        assert_eq!(tracker.update(None), None);
+    }
+
+    #[test]
+    fn test_median_drops_single_outlier() {
+        let mut tracker = PitchTracker::new();
+        feed(&mut tracker, &[Some(440.0), Some(441.0), Some(439.0), Some(440.0)]);
+        assert_eq!(tracker.update(Some(600.0)), Some(440.0));
+    }
+
+    #[test]
+    fn test_folds_octave_jumps_until_confirmed() {
+        let mut tracker = PitchTracker::new();
+        feed(&mut tracker, &[Some(220.0); PITCH_MEDIAN_FRAMES]);
+
+        let jumps = vec![Some(440.0); PITCH_OCTAVE_CONFIRM_FRAMES as usize - 1];
+        assert!(feed(&mut tracker, &jumps).iter().all(|output| *output == Some(220.0)));
+        assert_eq!(tracker.update(Some(440.0)), Some(440.0));
+    }
+}
@@ -0,0 +1,270 @@
+//! Requests the presentation layer collects from the user for the model
+//!
+//! They are kept apart from the presentation layer, which only builds for the
+//! browser, so the model also runs in native builds and tests.
+
+use crate::common::shared_types::{TuningSystem, Scale, MidiNote, MidiOutputSource, BatchSizeMode, PeakMeasure, IntervalMode, Transposition, NoteNaming, ToleranceProfile, SmoothingStrategy, ModelSnapshot, SessionExportFormat, ExerciseKind, MetronomeSettings, ReferenceNote, PitchAlgorithm, InputConditioning, ReferenceToneSettings};
+
+/// Request to change the tuning system
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeTuningSystem {
+    pub tuning_system: TuningSystem,
+}
+
+/// Action for changing the active scale
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleChangeAction {
+    pub scale: Scale,
+}
+
+/// Request to change the pitch detection algorithm and its thresholds
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurePitchDetection {
+    pub algorithm: PitchAlgorithm,
+    pub power_threshold: f32,
+    pub clarity_threshold: f32,
+}
+
+/// Request to change the timbre, envelope and volume of reference tones
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureReferenceTone {
+    pub settings: ReferenceToneSettings,
+}
+
+/// Pitch of an on-demand reference tone, relative to the tonal center
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReferenceToneTarget {
+    Interval(i32),
+    /// 1-based degree of the current scale
+    ScaleDegree(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayReferenceTone {
+    pub target: ReferenceToneTarget,
+}
+
+/// Request to change the number of bins in the spectrum delivered to the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureSpectrum {
+    pub bin_count: usize,
+}
+
+/// Request to start or abandon the input level calibration wizard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationControl {
+    Start,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExerciseControl {
+    Start(ExerciseKind),
+    Stop,
+}
+
+/// Request to start the metronome with new settings, or stop it with `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureMetronome {
+    pub settings: Option<MetronomeSettings>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferenceTrackControl {
+    /// Play the melody from now and compare the sung pitch against it
+    Start(Vec<ReferenceNote>),
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureBatchSize {
+    pub mode: BatchSizeMode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurePeakMeasure {
+    pub measure: PeakMeasure,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureIntervalMode {
+    pub mode: IntervalMode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSession {
+    pub format: SessionExportFormat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestoreSnapshot {
+    pub snapshot: ModelSnapshot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureSmoothing {
+    pub strategy: SmoothingStrategy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureTransposition {
+    pub transposition: Transposition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureNoteNaming {
+    pub naming: NoteNaming,
+}
+
+/// Action for changing how close to a note counts as in tune
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureToleranceProfile {
+    pub profile: ToleranceProfile,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureAutomaticGain {
+    pub enabled: bool,
+}
+
+/// Request to send notes to a MIDI output; `None` stops sending
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureMidiOutput {
+    pub output_id: Option<String>,
+    pub source: MidiOutputSource,
+}
+
+/// Request to set or clear the note the user is trying to match
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetTargetNote {
+    pub note: Option<MidiNote>,
+}
+
+/// Request to start, stop or export the input recording
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordingControl {
+    Start,
+    Stop,
+    /// Export the range between two points in time, counted back from the end of the recording
+    ExportWav { from_seconds_ago: f32, to_seconds_ago: f32 },
+    /// Sing along with the melody of the recording
+    UseAsReferenceMelody,
+}
+
+/// What starts when a count-in is over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CountInTarget {
+    Recording,
+    Exercise(ExerciseKind),
+}
+
+/// Request to change the noise gate and filters applied to the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureInputConditioning {
+    pub config: InputConditioning,
+}
+
+/// Request to switch the audio input to another device
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectInputDevice {
+    pub device_id: String,
+}
+
+/// Request to change the pitch analysis window and hop size, in samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureAnalysisWindow {
+    pub window_size: usize,
+    pub hop_size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureTonalCenter {
+    pub note: MidiNote,
+    pub volume: f32,
+}
+
+/// Action for changing the frequency of A4
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfigureConcertPitch {
+    pub frequency_hz: f32,
+}
+
+/// Container for all collected user actions from the presentation layer
+///
+/// One slot per action category; a request replaces an earlier one of the same
+/// category that has not been collected yet, so the latest one wins.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PresentationLayerActions {
+    pub tuning_system_change: Option<ChangeTuningSystem>,
+    pub scale_change: Option<ScaleChangeAction>,
+    pub tonal_center_configuration: Option<ConfigureTonalCenter>,
+    pub concert_pitch_configuration: Option<ConfigureConcertPitch>,
+    pub pitch_detection_configuration: Option<ConfigurePitchDetection>,
+    pub analysis_window_configuration: Option<ConfigureAnalysisWindow>,
+    pub smoothing_configuration: Option<ConfigureSmoothing>,
+    pub input_device_selection: Option<SelectInputDevice>,
+    pub input_conditioning_configuration: Option<ConfigureInputConditioning>,
+    pub reference_tone_configuration: Option<ConfigureReferenceTone>,
+    pub reference_tone_playback: Option<PlayReferenceTone>,
+    pub recording_control: Option<RecordingControl>,
+    pub spectrum_configuration: Option<ConfigureSpectrum>,
+    pub target_note: Option<SetTargetNote>,
+    pub midi_output_configuration: Option<ConfigureMidiOutput>,
+    pub calibration_control: Option<CalibrationControl>,
+    pub automatic_gain_configuration: Option<ConfigureAutomaticGain>,
+    pub batch_size_configuration: Option<ConfigureBatchSize>,
+    pub peak_measure_configuration: Option<ConfigurePeakMeasure>,
+    pub interval_mode_configuration: Option<ConfigureIntervalMode>,
+    pub transposition_configuration: Option<ConfigureTransposition>,
+    pub note_naming_configuration: Option<ConfigureNoteNaming>,
+    pub tolerance_profile_configuration: Option<ConfigureToleranceProfile>,
+    pub reset_session_statistics: bool,
+    pub snapshot_restore: Option<RestoreSnapshot>,
+    pub session_export: Option<ExportSession>,
+    /// Keep the session in the browser, with the input recording if one ran
+    pub session_save: bool,
+    pub exercise_control: Option<ExerciseControl>,
+    pub reference_track_control: Option<ReferenceTrackControl>,
+    pub metronome_configuration: Option<ConfigureMetronome>,
+    /// Request MIDI access; the browser may ask the user first
+    pub enable_midi: bool,
+    pub resume_audio: bool,
+}
+
+impl PresentationLayerActions {
+    /// Check if there are any actions to process
+    pub fn has_actions(&self) -> bool {
+        self.tuning_system_change.is_some() ||
+        self.scale_change.is_some() ||
+        self.tonal_center_configuration.is_some() ||
+        self.concert_pitch_configuration.is_some() ||
+        self.pitch_detection_configuration.is_some() ||
+        self.analysis_window_configuration.is_some() ||
+        self.smoothing_configuration.is_some() ||
+        self.input_device_selection.is_some() ||
+        self.input_conditioning_configuration.is_some() ||
+        self.reference_tone_configuration.is_some() ||
+        self.reference_tone_playback.is_some() ||
+        self.recording_control.is_some() ||
+        self.spectrum_configuration.is_some() ||
+        self.target_note.is_some() ||
+        self.midi_output_configuration.is_some() ||
+        self.calibration_control.is_some() ||
+        self.automatic_gain_configuration.is_some() ||
+        self.batch_size_configuration.is_some() ||
+        self.peak_measure_configuration.is_some() ||
+        self.interval_mode_configuration.is_some() ||
+        self.transposition_configuration.is_some() ||
+        self.note_naming_configuration.is_some() ||
+        self.tolerance_profile_configuration.is_some() ||
+        self.reset_session_statistics ||
+        self.snapshot_restore.is_some() ||
+        self.session_export.is_some() ||
+        self.session_save ||
+        self.exercise_control.is_some() ||
+        self.reference_track_control.is_some() ||
+        self.metronome_configuration.is_some() ||
+        self.enable_midi ||
+        self.resume_audio
+    }
+}
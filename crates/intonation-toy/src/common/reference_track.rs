@@ -0,0 +1,309 @@
+//! Singing along with a reference melody
+//!
+//! A melody is a list of notes and rests with durations. Playback starts at a
+//! given time, so the note expected at any moment follows from the clock
+//! alone. Each update compares the detected pitch with the note sounding at
+//! that moment; the first `ONSET_GRACE_MS` of every note are left out, which
+//! gives the singer time to react to the change.
+//!
+//! A melody can also be taken from a recording: its pitch track, analyzed
+//! offline, is split into notes the same way live input is.
+
+use crate::common::note_segmentation::NoteSegmenter;
+use crate::common::shared_types::{MidiNote, RecordedPitchTrack, ReferenceNote, ReferenceNoteResult, ReferenceTrackStatus, SungNoteEvent, is_valid_midi_note};
+
+/// Start of each note not scored, for the reaction time of the singer
+const ONSET_GRACE_MS: f64 = 150.0;
+/// Shorter gaps between the notes of a recording are joined to the note before
+const MIN_REST_MS: f64 = 100.0;
+
+/// Parse a melody written as `note:duration` pairs separated by whitespace,
+/// with MIDI note numbers, `r` for a rest and durations in milliseconds,
+/// e.g. `60:500 62:500 r:250 64:1000`
+pub fn parse_reference_notes(text: &str) -> Result<Vec<ReferenceNote>, String> {
+    let notes = text.split_whitespace()
+        .map(|token| {
+            let (note, duration) = token.split_once(':').ok_or_else(|| format!("'{}' is not note:duration", token))?;
+            let duration_ms: f64 = duration.parse().map_err(|_| format!("'{}' is not a duration", duration))?;
+            if !duration_ms.is_finite() || duration_ms <= 0.0 {
+                return Err(format!("Duration of '{}' must be positive", token));
+            }
+            let note = match note {
+                "r" => None,
+                note => Some(note.parse::<i32>()
+                    .ok()
+                    .filter(|&note| is_valid_midi_note(note))
+                    .ok_or_else(|| format!("'{}' is not a MIDI note", note))? as MidiNote),
+            };
+            Ok(ReferenceNote { note, duration_ms })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if notes.iter().all(|note| note.note.is_none()) {
+        return Err("The melody has no notes".to_string());
+    }
+    Ok(notes)
+}
+
+/// Check a melody that did not come from `parse_reference_notes`
+pub fn validate_reference_notes(notes: &[ReferenceNote]) -> Result<(), String> {
+    if notes.iter().all(|note| note.note.is_none()) {
+        return Err("no notes".to_string());
+    }
+    if let Some(note) = notes.iter().find(|note| !note.duration_ms.is_finite() || note.duration_ms <= 0.0) {
+        return Err(format!("duration {} ms is not positive", note.duration_ms));
+    }
+    if let Some(note) = notes.iter().filter_map(|note| note.note).find(|&note| !is_valid_midi_note(note as i32)) {
+        return Err(format!("{} is not a MIDI note", note));
+    }
+    Ok(())
+}
+
+/// Melody sung in a recording: the notes found in its pitch track, with rests
+/// for the gaps between them. `classify` gives the closest note for a
+/// frequency; leading silence is left out.
+pub fn reference_notes_from_pitch_track(
+    track: &RecordedPitchTrack,
+    classify: impl Fn(f32) -> Option<(MidiNote, f32)>,
+) -> Vec<ReferenceNote> {
+    let mut segmenter = NoteSegmenter::new();
+    let mut events: Vec<SungNoteEvent> = track.frequencies.iter()
+        .enumerate()
+        .flat_map(|(frame, &frequency)| segmenter.update(frame as f64 * track.frame_ms, frequency, &classify))
+        .collect();
+    events.extend(segmenter.finish(&classify));
+
+    let mut notes = Vec::new();
+    let mut end_ms = None;
+    for event in events {
+        let SungNoteEvent::Offset(sung) = event else {
+            continue;
+        };
+        if let Some(end_ms) = end_ms {
+            let gap_ms = sung.start_ms - end_ms;
+            if gap_ms >= MIN_REST_MS {
+                notes.push(ReferenceNote { note: None, duration_ms: gap_ms });
+            } else if let Some(previous) = notes.last_mut() {
+                previous.duration_ms += gap_ms.max(0.0);
+            }
+        }
+        notes.push(ReferenceNote { note: Some(sung.note), duration_ms: sung.duration_ms });
+        end_ms = Some(sung.start_ms + sung.duration_ms);
+    }
+    notes
+}
+
+/// Deviation collected over one note
+#[derive(Debug, Clone, Copy, Default)]
+struct NoteAccumulator {
+    frames: u32,
+    voiced_frames: u32,
+    cents_sum: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReferencePlayback {
+    notes: Vec<ReferenceNote>,
+    /// Start of each note relative to the start of the melody
+    offsets_ms: Vec<f64>,
+    start_ms: f64,
+    position_ms: f64,
+    /// First note whose start has not been reported by `take_due_note`
+    next_to_play: usize,
+    accumulators: Vec<NoteAccumulator>,
+}
+
+impl ReferencePlayback {
+    pub fn new(notes: Vec<ReferenceNote>, start_ms: f64) -> Self {
+        let offsets_ms = notes.iter()
+            .scan(0.0, |offset_ms, note| {
+                let start = *offset_ms;
+                *offset_ms += note.duration_ms;
+                Some(start)
+            })
+            .collect();
+        let accumulators = vec![NoteAccumulator::default(); notes.len()];
+        Self {
+            notes,
+            offsets_ms,
+            start_ms,
+            position_ms: 0.0,
+            next_to_play: 0,
+            accumulators,
+        }
+    }
+
+    pub fn duration_ms(&self) -> f64 {
+        self.notes.iter().map(|note| note.duration_ms).sum()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position_ms >= self.duration_ms()
+    }
+
+    /// Index of the note or rest at `position_ms` into the melody
+    fn index_at(&self, position_ms: f64) -> Option<usize> {
+        if position_ms < 0.0 || position_ms >= self.duration_ms() {
+            return None;
+        }
+        Some(self.offsets_ms.partition_point(|&offset_ms| offset_ms <= position_ms) - 1)
+    }
+
+    /// Note sounding at `now_ms`
+    pub fn note_at(&self, now_ms: f64) -> Option<MidiNote> {
+        self.index_at(now_ms - self.start_ms).and_then(|index| self.notes[index].note)
+    }
+
+    /// Latest note that started by `now_ms` and was not returned before, for
+    /// playing it; notes missed in between are skipped
+    pub fn take_due_note(&mut self, now_ms: f64) -> Option<ReferenceNote> {
+        let index = self.index_at(now_ms - self.start_ms)?;
+        if index < self.next_to_play {
+            return None;
+        }
+        self.next_to_play = index + 1;
+        Some(self.notes[index]).filter(|note| note.note.is_some())
+    }
+
+    /// Advance to `now_ms`; `target_cents_offset` is the deviation of the
+    /// detected pitch from the note at `now_ms`, if a pitch was detected
+    pub fn update(&mut self, now_ms: f64, target_cents_offset: Option<f32>) {
+        self.position_ms = now_ms - self.start_ms;
+        let Some(index) = self.index_at(self.position_ms) else {
+            return;
+        };
+        if self.notes[index].note.is_none() || self.position_ms - self.offsets_ms[index] < ONSET_GRACE_MS {
+            return;
+        }
+
+        let accumulator = &mut self.accumulators[index];
+        accumulator.frames += 1;
+        if let Some(cents) = target_cents_offset {
+            accumulator.voiced_frames += 1;
+            accumulator.cents_sum += cents;
+        }
+    }
+
+    pub fn status(&self) -> ReferenceTrackStatus {
+        let ended = self.notes.iter()
+            .zip(&self.offsets_ms)
+            .zip(&self.accumulators)
+            .take_while(|((note, offset_ms), _)| *offset_ms + note.duration_ms <= self.position_ms);
+        let results = ended
+            .filter_map(|((note, _), accumulator)| {
+                let note = note.note?;
+                Some(ReferenceNoteResult {
+                    note,
+                    mean_cents: (accumulator.voiced_frames > 0).then(|| accumulator.cents_sum / accumulator.voiced_frames as f32),
+                    voiced_fraction: accumulator.voiced_frames as f32 / accumulator.frames.max(1) as f32,
+                })
+            })
+            .collect();
+
+        ReferenceTrackStatus {
+            position_ms: self.position_ms.clamp(0.0, self.duration_ms()),
+            duration_ms: self.duration_ms(),
+            current_note: self.index_at(self.position_ms).and_then(|index| self.notes[index].note),
+            results,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::music_theory::{frequency_to_midi_note_and_cents, midi_note_to_standard_frequency};
+    use crate::common::shared_types::{Scale, TuningSystem};
+
+    const FRAME_MS: f64 = 10.0;
+
+    #[test]
+    fn test_parse() {
+        let notes = parse_reference_notes("60:500  r:250\n64:1000").unwrap();
+        assert_eq!(notes, vec![
+            ReferenceNote { note: Some(60), duration_ms: 500.0 },
+            ReferenceNote { note: None, duration_ms: 250.0 },
+            ReferenceNote { note: Some(64), duration_ms: 1000.0 },
+        ]);
+        assert!(parse_reference_notes("60").is_err());
+        assert!(parse_reference_notes("128:500").is_err());
+        assert!(parse_reference_notes("60:0").is_err());
+        assert!(parse_reference_notes("r:500").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate_reference_notes(&parse_reference_notes("60:500 r:250").unwrap()).is_ok());
+        assert!(validate_reference_notes(&[ReferenceNote { note: None, duration_ms: 500.0 }]).is_err());
+        assert!(validate_reference_notes(&[ReferenceNote { note: Some(60), duration_ms: f64::NAN }]).is_err());
+        assert!(validate_reference_notes(&[
+            ReferenceNote { note: Some(60), duration_ms: 500.0 },
+            ReferenceNote { note: Some(128), duration_ms: 500.0 },
+        ]).is_err());
+    }
+
+    #[test]
+    fn test_notes_from_pitch_track() {
+        let frame = |note: Option<MidiNote>| note.map(midi_note_to_standard_frequency);
+        let frequencies = [
+            vec![frame(None); 10],
+            vec![frame(Some(60)); 30],
+            vec![frame(Some(64)); 30],
+            vec![frame(None); 20],
+            vec![frame(Some(67)); 40],
+        ].concat();
+        let track = RecordedPitchTrack { frame_ms: 10.0, frequencies };
+        let classify = |frequency| frequency_to_midi_note_and_cents(frequency, 60, 440.0, &TuningSystem::EqualTemperament, Scale::Chromatic);
+
+        let notes = reference_notes_from_pitch_track(&track, classify);
+        let pitches: Vec<_> = notes.iter().map(|note| note.note).collect();
+        assert_eq!(pitches, vec![Some(60), Some(64), None, Some(67)]);
+        assert!(validate_reference_notes(&notes).is_ok());
+        let duration_ms: f64 = notes.iter().map(|note| note.duration_ms).sum();
+        assert!((duration_ms - 1190.0).abs() <= 20.0, "duration {}", duration_ms);
+    }
+
+    #[test]
+    fn test_due_notes_skip_rests() {
+        let notes = parse_reference_notes("60:100 r:100 62:100").unwrap();
+        let mut playback = ReferencePlayback::new(notes, 1000.0);
+        assert_eq!(playback.take_due_note(990.0), None);
+        assert_eq!(playback.take_due_note(1000.0).and_then(|note| note.note), Some(60));
+        assert_eq!(playback.take_due_note(1050.0), None);
+        assert_eq!(playback.take_due_note(1150.0), None);
+        assert_eq!(playback.note_at(1150.0), None);
+        assert_eq!(playback.take_due_note(1250.0).and_then(|note| note.note), Some(62));
+        assert_eq!(playback.take_due_note(1300.0), None);
+    }
+
+    #[test]
+    fn test_deviation_per_note() {
+        let notes = parse_reference_notes("60:500 62:500").unwrap();
+        let mut playback = ReferencePlayback::new(notes, 0.0);
+        let mut now_ms = 0.0;
+        while now_ms < 1000.0 {
+            let cents = match now_ms {
+                // Scooping into the first note is within the grace period
+                t if t < 100.0 => Some(-80.0),
+                t if t < 500.0 => Some(6.0),
+                // The second note is sung only in its second half
+                t if t < 750.0 => None,
+                _ => Some(-10.0),
+            };
+            playback.update(now_ms, cents);
+            now_ms += FRAME_MS;
+        }
+        assert!(!playback.is_finished());
+        assert_eq!(playback.status().results.len(), 1);
+
+        playback.update(1000.0, None);
+        assert!(playback.is_finished());
+        let status = playback.status();
+        assert_eq!(status.current_note, None);
+        assert_eq!(status.results[0].note, 60);
+        assert_eq!(status.results[0].mean_cents, Some(6.0));
+        assert_eq!(status.results[0].voiced_fraction, 1.0);
+        assert_eq!(status.results[1].mean_cents, Some(-10.0));
+        assert!((status.results[1].voiced_fraction - 25.0 / 35.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,115 @@
+//! Sessions kept in the browser to look at again later
+//!
+//! A saved session holds what the JSON export holds and the results of the
+//! exercise; the input recording, when one ran, is kept apart from it as a
+//! WAV file. Each session also has a short summary, kept apart as well, so
+//! listing the saved sessions does not load their pitch histories and
+//! recordings. Sessions are keyed by the time they were saved.
+
+use serde::{Deserialize, Serialize};
+use crate::common::pitch_history::PitchSample;
+use crate::common::shared_types::{ExerciseKind, ExerciseStatus, ModelSnapshot};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSession {
+    #[serde(flatten)]
+    pub settings: ModelSnapshot,
+    /// Running or last finished exercise
+    pub exercise: Option<ExerciseStatus>,
+    pub pitch_history: Vec<PitchSample>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSessionSummary {
+    /// Time of saving in milliseconds, as from `js_sys::Date::now`; the key of the session
+    pub saved_ms: f64,
+    /// Time a pitch was detected during the session
+    pub sung_seconds: f32,
+    pub score: Option<f32>,
+    pub exercise: Option<ExerciseKind>,
+    pub has_recording: bool,
+    /// Size of the stored session and recording
+    pub bytes: usize,
+}
+
+impl SavedSession {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize saved session: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<SavedSession, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to deserialize saved session: {}", e))
+    }
+
+    /// Summary of the session as stored in `json_bytes`, with a recording of
+    /// `recording_bytes`, if any
+    pub fn summary(&self, saved_ms: f64, json_bytes: usize, recording_bytes: Option<usize>) -> SavedSessionSummary {
+        let statistics = &self.settings.session_statistics;
+        SavedSessionSummary {
+            saved_ms,
+            sung_seconds: statistics.degrees.iter().map(|degree| degree.seconds).sum(),
+            score: statistics.score,
+            exercise: self.exercise.as_ref().map(|exercise| exercise.kind),
+            has_recording: recording_bytes.is_some(),
+            bytes: json_bytes + recording_bytes.unwrap_or(0),
+        }
+    }
+}
+
+impl SavedSessionSummary {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Failed to serialize session summary: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<SavedSessionSummary, String> {
+        serde_json::from_str(json).map_err(|e| format!("Failed to deserialize session summary: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::{ExerciseTargetResult, IntervalMode, NoteNaming, Scale, SessionStatistics, SmoothingStrategy, ToleranceProfile, Transposition, TuningSystem};
+
+    fn session() -> SavedSession {
+        let mut statistics = SessionStatistics { score: Some(75.0), ..SessionStatistics::default() };
+        statistics.degrees[0].seconds = 2.0;
+        statistics.degrees[7].seconds = 1.5;
+        SavedSession {
+            settings: ModelSnapshot {
+                tonal_center_note: 60,
+                concert_pitch_hz: 440.0,
+                tuning_system: TuningSystem::EqualTemperament,
+                scale: Scale::Major,
+                transposition: Transposition::C,
+                note_naming: NoteNaming::default(),
+                tolerance_profile: ToleranceProfile::default(),
+                smoothing: SmoothingStrategy::default(),
+                interval_mode: IntervalMode::default(),
+                session_statistics: statistics,
+            },
+            exercise: Some(ExerciseStatus {
+                kind: ExerciseKind::DescendingScale,
+                target_count: 8,
+                current_target: Some(71),
+                hold_progress: 0.5,
+                results: vec![ExerciseTargetResult { note: 72, seconds_to_reach: 1.2, mean_cents: -3.0 }],
+            }),
+            pitch_history: vec![PitchSample { timestamp_ms: 1000.0, frequency: 261.5, clarity: 0.9, cents_offset: -1.0 }],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let session = session();
+        assert_eq!(SavedSession::from_json(&session.to_json().unwrap()).unwrap(), session);
+
+        let summary = session.summary(5000.0, 300, Some(1000));
+        assert_eq!(summary.sung_seconds, 3.5);
+        assert_eq!(summary.score, Some(75.0));
+        assert_eq!(summary.exercise, Some(ExerciseKind::DescendingScale));
+        assert!(summary.has_recording);
+        assert_eq!(summary.bytes, 1300);
+        assert_eq!(SavedSessionSummary::from_json(&summary.to_json().unwrap()).unwrap(), summary);
+    }
+}
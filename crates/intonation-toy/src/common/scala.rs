@@ -0,0 +1,141 @@
+//! Scala tuning files (.scl)
+//!
+//! A file lists the pitches of one period above the tonal center, either in
+//! cents (any value containing a '.') or as ratios, with the period last.
+//! Only 12-note octave-repeating tunings fit the chromatic note layout, so
+//! other files are rejected. Tunings are exchanged as cent offsets from equal
+//! temperament per degree, as stored in `TuningSystem::Custom`.
+
+const DEGREES: usize = 12;
+const OCTAVE_CENTS: f32 = 1200.0;
+const OCTAVE_TOLERANCE_CENTS: f32 = 0.01;
+
+/// Cent offsets of the 12 degrees from the contents of a .scl file
+pub fn parse_scl(text: &str) -> Result<Vec<f32>, String> {
+    let mut lines = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('!'));
+
+    // The description may be empty
+    lines.next().ok_or("missing description line")?;
+    let count_line = lines.next().ok_or("missing note count")?;
+    let count: usize = first_word(count_line).parse()
+        .map_err(|_| format!("invalid note count '{}'", count_line))?;
+    if count != DEGREES {
+        return Err(format!("{} notes per period, only {} are supported", count, DEGREES));
+    }
+
+    let pitches = lines
+        .filter(|line| !line.is_empty())
+        .take(count)
+        .map(parse_pitch)
+        .collect::<Result<Vec<f32>, String>>()?;
+    if pitches.len() != count {
+        return Err(format!("expected {} pitches, found {}", count, pitches.len()));
+    }
+    if (pitches[DEGREES - 1] - OCTAVE_CENTS).abs() > OCTAVE_TOLERANCE_CENTS {
+        return Err(format!("period is {:.2} cents, only the octave is supported", pitches[DEGREES - 1]));
+    }
+
+    let mut offsets = vec![0.0; DEGREES];
+    for (degree, cents) in pitches[..DEGREES - 1].iter().enumerate() {
+        offsets[degree + 1] = cents - 100.0 * (degree + 1) as f32;
+    }
+    Ok(offsets)
+}
+
+/// Contents of a .scl file for the given cent offsets of the 12 degrees
+pub fn format_scl(description: &str, offsets: &[f32]) -> String {
+    let mut text = format!("! intonation-toy custom tuning\n!\n{}\n {}\n!\n", description, DEGREES);
+    for degree in 1..DEGREES {
+        let offset = offsets.get(degree).copied().unwrap_or(0.0);
+        text.push_str(&format!(" {:.5}\n", 100.0 * degree as f32 + offset));
+    }
+    text.push_str(" 2/1\n");
+    text
+}
+
+fn first_word(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+/// Cents above the tonal center of one pitch line
+fn parse_pitch(line: &str) -> Result<f32, String> {
+    let value = first_word(line);
+    let invalid = || format!("invalid pitch '{}'", value);
+
+    let cents = if value.contains('.') {
+        value.parse::<f32>().map_err(|_| invalid())?
+    } else {
+        let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+        let numerator: u32 = numerator.parse().map_err(|_| invalid())?;
+        let denominator: u32 = denominator.parse().map_err(|_| invalid())?;
+        if numerator == 0 || denominator == 0 {
+            return Err(invalid());
+        }
+        1200.0 * (numerator as f32 / denominator as f32).log2()
+    };
+    if !cents.is_finite() {
+        return Err(invalid());
+    }
+    Ok(cents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE_CENTS: f32 = 0.001;
+
+    /// Werckmeister III mixing ratio and cent pitches
+    const WERCKMEISTER_III: &str = "! werck3.scl
+!
+Andreas Werckmeister's temperament III (the most famous one, 1681)
+ 12
+!
+ 256/243
+ 192.18000
+ 32/27
+ 390.22500
+ 4/3
+ 1024/729
+ 696.09000
+ 128/81
+ 888.27000
+ 16/9
+ 1092.18000
+ 2/1
+";
+
+    #[test]
+    fn test_parse_scl() {
+        let offsets = parse_scl(WERCKMEISTER_III).unwrap();
+        assert_eq!(offsets.len(), 12);
+        assert_eq!(offsets[0], 0.0);
+        assert!((offsets[1] + 9.775).abs() < 0.01);
+        assert!((offsets[2] + 7.82).abs() < TOLERANCE_CENTS);
+        assert!((offsets[3] + 5.865).abs() < 0.01);
+        assert!((offsets[4] + 9.775).abs() < TOLERANCE_CENTS);
+        assert!((offsets[5] + 1.955).abs() < 0.01);
+        assert!((offsets[6] + 11.73).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let offsets = vec![0.0, -9.775, -7.82, -5.865, -9.775, -1.955, -11.73, -3.91, -7.82, -11.73, -3.91, -7.82];
+        let parsed = parse_scl(&format_scl("Werckmeister III", &offsets)).unwrap();
+        for (expected, actual) in offsets.iter().zip(&parsed) {
+            assert!((expected - actual).abs() < TOLERANCE_CENTS);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unsupported_files() {
+        assert!(parse_scl("").is_err());
+        assert!(parse_scl("Five notes\n 5\n 240.0\n 480.0\n 720.0\n 960.0\n 2/1\n").is_err());
+        let tritave = WERCKMEISTER_III.replace(" 2/1", " 3/1");
+        assert!(parse_scl(&tritave).is_err());
+        let garbage = WERCKMEISTER_III.replace(" 4/3", " four/three");
+        assert!(parse_scl(&garbage).is_err());
+    }
+}
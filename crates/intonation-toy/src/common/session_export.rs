@@ -0,0 +1,100 @@
+//! Session data for analysis in external tools
+//!
+//! The pitch timeline holds the retained pitch history, one row per update in
+//! which a pitch was detected. Degree statistics cover the whole session, one
+//! row per semitone above the tonal center, with the note named as on screen.
+//! The JSON export combines both with the settings they were measured under.
+
+use std::fmt::Write;
+
+use crate::common::music_theory::semitone_to_interval_name;
+use crate::common::note_naming::NoteNamer;
+use crate::common::pitch_history::PitchSample;
+use crate::common::shared_types::{ModelSnapshot, SessionStatistics};
+
+#[derive(serde::Serialize)]
+struct SessionJson<'a> {
+    #[serde(flatten)]
+    settings: &'a ModelSnapshot,
+    pitch_history: &'a [PitchSample],
+}
+
+pub fn pitch_timeline_csv(samples: &[PitchSample]) -> String {
+    let mut csv = String::from("timestamp_ms,frequency_hz,clarity,cents_offset\n");
+    for sample in samples {
+        let _ = writeln!(csv, "{:.1},{},{},{}", sample.timestamp_ms, sample.frequency, sample.clarity, sample.cents_offset);
+    }
+    csv
+}
+
+pub fn degree_statistics_csv(statistics: &SessionStatistics, namer: &NoteNamer) -> String {
+    let mut csv = String::from("semitones,degree,note,seconds,in_tune_seconds,mean_cents,std_cents,attempts\n");
+    for (semitones, degree) in statistics.degrees.iter().enumerate() {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{}",
+            semitones,
+            semitone_to_interval_name(semitones as i32),
+            namer.name(namer.tonal_center_note + semitones as u8),
+            degree.seconds,
+            degree.in_tune_seconds,
+            degree.mean_cents,
+            degree.std_cents,
+            degree.attempts,
+        );
+    }
+    csv
+}
+
+pub fn session_json(settings: &ModelSnapshot, samples: &[PitchSample]) -> Result<String, String> {
+    serde_json::to_string_pretty(&SessionJson { settings, pitch_history: samples })
+        .map_err(|e| format!("Failed to serialize session: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::{DegreeStatistics, IntervalMode, NoteNaming, Scale, SmoothingStrategy, ToleranceProfile, Transposition, TuningSystem};
+
+    fn samples() -> Vec<PitchSample> {
+        vec![
+            PitchSample { timestamp_ms: 1000.0, frequency: 440.5, clarity: 0.9, cents_offset: 2.0 },
+            PitchSample { timestamp_ms: 1016.7, frequency: 441.0, clarity: 0.8, cents_offset: 3.9 },
+        ]
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            pitch_timeline_csv(&samples()),
+            "timestamp_ms,frequency_hz,clarity,cents_offset\n1000.0,440.5,0.9,2\n1016.7,441,0.8,3.9\n"
+        );
+
+        let mut statistics = SessionStatistics::default();
+        statistics.degrees[7] = DegreeStatistics { seconds: 4.5, in_tune_seconds: 3.0, mean_cents: -2.5, std_cents: 6.0, attempts: 2 };
+        let namer = NoteNamer { naming: NoteNaming::Solfege, transposition: Transposition::C, tonal_center_note: 62, scale: Scale::Major, language: crate::common::localization::Language::English };
+        let csv = degree_statistics_csv(&statistics, &namer);
+        assert_eq!(csv.lines().count(), 13);
+        assert_eq!(csv.lines().nth(8), Some("7,5,Sol,4.5,3,-2.5,6,2"));
+    }
+
+    #[test]
+    fn test_json_holds_settings_and_samples() {
+        let settings = ModelSnapshot {
+            tonal_center_note: 62,
+            concert_pitch_hz: 440.0,
+            tuning_system: TuningSystem::EqualTemperament,
+            scale: Scale::Major,
+            transposition: Transposition::C,
+            note_naming: NoteNaming::Flats,
+            tolerance_profile: ToleranceProfile::Beginner,
+            smoothing: SmoothingStrategy::None,
+            interval_mode: IntervalMode::Absolute,
+            session_statistics: SessionStatistics::default(),
+        };
+        let json: serde_json::Value = serde_json::from_str(&session_json(&settings, &samples()).unwrap()).unwrap();
+        assert_eq!(json["tonal_center_note"], 62);
+        assert_eq!(json["pitch_history"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["pitch_history"][1]["frequency"], 441.0);
+    }
+}
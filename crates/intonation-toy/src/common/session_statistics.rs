@@ -0,0 +1,197 @@
+use crate::app_config::SESSION_MAX_FRAME_GAP_MS;
+use crate::common::session_summary::cents_histogram_bin;
+use crate::common::shared_types::{DegreeStatistics, SessionStatistics, CENTS_HISTOGRAM_BINS};
+
+/// Time-weighted running mean and variance (West's weighted Welford update)
+#[derive(Debug, Clone, Copy, Default)]
+struct WeightedMoments {
+    weight: f64,
+    mean: f64,
+    squared_deviations: f64,
+}
+
+impl WeightedMoments {
+    fn add(&mut self, value: f64, weight: f64) {
+        self.weight += weight;
+        let delta = value - self.mean;
+        self.mean += delta * weight / self.weight;
+        self.squared_deviations += weight * delta * (value - self.mean);
+    }
+
+    fn std(&self) -> f64 {
+        if self.weight > 0.0 {
+            (self.squared_deviations / self.weight).sqrt()
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct DegreeAccumulator {
+    in_tune_ms: f64,
+    cents: WeightedMoments,
+    attempts: u32,
+}
+
+/// Aggregates how well each degree is sung over a practice session
+///
+/// Each update is weighted by the time since the previous one, so the
+/// statistics do not depend on the frame rate. Longer gaps, such as while
+/// the tab is hidden, are shortened to `SESSION_MAX_FRAME_GAP_MS`.
+pub struct SessionTracker {
+    degrees: [DegreeAccumulator; 12],
+    tolerance_cents: f32,
+    last_update_ms: Option<f64>,
+    /// Degree sung in the previous update, to count new attempts
+    current_degree: Option<usize>,
+    cents_histogram_ms: [f64; CENTS_HISTOGRAM_BINS],
+    streak_ms: f64,
+    longest_streak_ms: f64,
+}
+
+impl SessionTracker {
+    pub fn new(tolerance_cents: f32) -> Self {
+        Self {
+            degrees: [DegreeAccumulator::default(); 12],
+            tolerance_cents,
+            last_update_ms: None,
+            current_degree: None,
+            cents_histogram_ms: [0.0; CENTS_HISTOGRAM_BINS],
+            streak_ms: 0.0,
+            longest_streak_ms: 0.0,
+        }
+    }
+
+    /// Continue a session from its statistics, as taken from `statistics`
+    pub fn from_statistics(statistics: &SessionStatistics) -> Self {
+        let degrees = statistics.degrees.map(|degree| {
+            let weight = degree.seconds as f64 * 1000.0;
+            DegreeAccumulator {
+                in_tune_ms: degree.in_tune_seconds as f64 * 1000.0,
+                cents: WeightedMoments {
+                    weight,
+                    mean: degree.mean_cents as f64,
+                    squared_deviations: (degree.std_cents as f64).powi(2) * weight,
+                },
+                attempts: degree.attempts,
+            }
+        });
+        Self {
+            degrees,
+            tolerance_cents: statistics.tolerance_cents,
+            last_update_ms: None,
+            current_degree: None,
+            cents_histogram_ms: statistics.cents_histogram.map(|seconds| seconds as f64 * 1000.0),
+            streak_ms: 0.0,
+            longest_streak_ms: statistics.longest_streak_seconds as f64 * 1000.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.tolerance_cents);
+    }
+
+    pub fn set_tolerance_cents(&mut self, tolerance_cents: f32) {
+        self.tolerance_cents = tolerance_cents;
+    }
+
+    /// Account for the time since the last update; `sung` is the degree above
+    /// the tonal center and the cents offset from it, if a note was detected
+    pub fn update(&mut self, now_ms: f64, sung: Option<(usize, f32)>) {
+        let elapsed_ms = self.last_update_ms
+            .map_or(0.0, |last_ms| (now_ms - last_ms).clamp(0.0, SESSION_MAX_FRAME_GAP_MS));
+        self.last_update_ms = Some(now_ms);
+
+        let Some((degree, cents)) = sung else {
+            self.current_degree = None;
+            return;
+        };
+        let accumulator = &mut self.degrees[degree % 12];
+        if self.current_degree != Some(degree) {
+            accumulator.attempts += 1;
+            self.current_degree = Some(degree);
+        }
+        if elapsed_ms > 0.0 {
+            accumulator.cents.add(cents as f64, elapsed_ms);
+            self.cents_histogram_ms[cents_histogram_bin(cents)] += elapsed_ms;
+            if cents.abs() <= self.tolerance_cents {
+                accumulator.in_tune_ms += elapsed_ms;
+                self.streak_ms += elapsed_ms;
+                self.longest_streak_ms = self.longest_streak_ms.max(self.streak_ms);
+            } else {
+                self.streak_ms = 0.0;
+            }
+        }
+    }
+
+    pub fn statistics(&self) -> SessionStatistics {
+        let degrees = self.degrees.map(|accumulator| DegreeStatistics {
+            seconds: (accumulator.cents.weight / 1000.0) as f32,
+            in_tune_seconds: (accumulator.in_tune_ms / 1000.0) as f32,
+            mean_cents: accumulator.cents.mean as f32,
+            std_cents: accumulator.cents.std() as f32,
+            attempts: accumulator.attempts,
+        });
+        let total_ms: f64 = self.degrees.iter().map(|accumulator| accumulator.cents.weight).sum();
+        let in_tune_ms: f64 = self.degrees.iter().map(|accumulator| accumulator.in_tune_ms).sum();
+        SessionStatistics {
+            degrees,
+            tolerance_cents: self.tolerance_cents,
+            score: (total_ms > 0.0).then(|| (100.0 * in_tune_ms / total_ms) as f32),
+            cents_histogram: self.cents_histogram_ms.map(|ms| (ms / 1000.0) as f32),
+            longest_streak_seconds: (self.longest_streak_ms / 1000.0) as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates_are_weighted_by_time() {
+        let mut tracker = SessionTracker::new(10.0);
+        tracker.update(0.0, Some((7, 0.0)));
+        tracker.update(50.0, Some((7, 4.0)));
+        tracker.update(100.0, Some((7, 20.0)));
+        // A gap while the tab was hidden counts as one long frame at most
+        tracker.update(10_000.0, Some((7, 20.0)));
+
+        let fifth = tracker.statistics().degrees[7];
+        assert!((fifth.seconds - 0.2).abs() < 1e-6, "{}", fifth.seconds);
+        assert!((fifth.in_tune_seconds - 0.05).abs() < 1e-6, "{}", fifth.in_tune_seconds);
+        assert!((fifth.mean_cents - 16.0).abs() < 1e-4, "{}", fifth.mean_cents);
+        assert_eq!(fifth.attempts, 1);
+    }
+
+    #[test]
+    fn test_counts_attempts_score_and_streak() {
+        let mut tracker = SessionTracker::new(10.0);
+        assert_eq!(tracker.statistics().score, None);
+        for (now_ms, sung) in [(0.0, Some((0, 0.0))), (100.0, Some((0, 2.0))), (200.0, None), (300.0, Some((0, 30.0))), (400.0, Some((4, 1.0))), (500.0, Some((0, 0.0)))] {
+            tracker.update(now_ms, sung);
+        }
+
+        let statistics = tracker.statistics();
+        assert_eq!(statistics.degrees[0].attempts, 3);
+        assert_eq!(statistics.degrees[4].attempts, 1);
+        assert_eq!(statistics.score, Some(75.0));
+        assert!((statistics.longest_streak_seconds - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_continues_from_statistics() {
+        let mut tracker = SessionTracker::new(10.0);
+        for (now_ms, cents) in [(0.0, 0.0), (100.0, -6.0), (200.0, 12.0), (300.0, 3.0)] {
+            tracker.update(now_ms, Some((2, cents)));
+        }
+        let statistics = tracker.statistics();
+
+        let restored = SessionTracker::from_statistics(&statistics).statistics();
+        assert_eq!(restored.degrees[2].attempts, statistics.degrees[2].attempts);
+        assert!((restored.degrees[2].std_cents - statistics.degrees[2].std_cents).abs() < 1e-4);
+        assert_eq!(restored.score, statistics.score);
+        assert_eq!(restored.cents_histogram, statistics.cents_histogram);
+    }
+}
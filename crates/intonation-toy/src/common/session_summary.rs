@@ -0,0 +1,62 @@
+//! Chart data for the summary of a practice session
+//!
+//! Bars are given as parts of the chart height. Degrees that were never sung
+//! have no bar, so they can be told apart from degrees never sung in tune.
+
+use crate::common::shared_types::{SessionStatistics, CENTS_HISTOGRAM_BINS, CENTS_HISTOGRAM_BIN_CENTS};
+
+/// Histogram bin of a cents offset; offsets beyond the range go to the outer bins
+pub fn cents_histogram_bin(cents: f32) -> usize {
+    let half_range = CENTS_HISTOGRAM_BINS as f32 * CENTS_HISTOGRAM_BIN_CENTS * 0.5;
+    (((cents + half_range) / CENTS_HISTOGRAM_BIN_CENTS).floor().max(0.0) as usize).min(CENTS_HISTOGRAM_BINS - 1)
+}
+
+/// Cents offset at the lower edge of a histogram bin
+pub fn cents_histogram_bin_start(bin: usize) -> f32 {
+    (bin as f32 - CENTS_HISTOGRAM_BINS as f32 * 0.5) * CENTS_HISTOGRAM_BIN_CENTS
+}
+
+/// Part of the time on each degree above the tonal center spent in tune
+pub fn in_tune_bars(statistics: &SessionStatistics) -> [Option<f32>; 12] {
+    statistics.degrees.map(|degree| (degree.seconds > 0.0).then(|| degree.in_tune_seconds / degree.seconds))
+}
+
+/// Time in each histogram bin relative to the fullest bin
+pub fn histogram_bars(statistics: &SessionStatistics) -> [f32; CENTS_HISTOGRAM_BINS] {
+    let fullest = statistics.cents_histogram.iter().copied().fold(0.0, f32::max);
+    statistics.cents_histogram.map(|seconds| if fullest > 0.0 { seconds / fullest } else { 0.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::DegreeStatistics;
+
+    #[test]
+    fn test_histogram_bins() {
+        assert_eq!(cents_histogram_bin(-50.0), 0);
+        assert_eq!(cents_histogram_bin(-0.5), 4);
+        assert_eq!(cents_histogram_bin(0.0), 5);
+        assert_eq!(cents_histogram_bin(49.9), 9);
+        assert_eq!(cents_histogram_bin(-80.0), 0);
+        assert_eq!(cents_histogram_bin(80.0), 9);
+        assert_eq!(cents_histogram_bin_start(5), 0.0);
+        assert_eq!(cents_histogram_bin_start(0), -50.0);
+    }
+
+    #[test]
+    fn test_bars() {
+        let mut statistics = SessionStatistics::default();
+        statistics.degrees[7] = DegreeStatistics { seconds: 4.0, in_tune_seconds: 3.0, ..Default::default() };
+        statistics.degrees[4] = DegreeStatistics { seconds: 2.0, ..Default::default() };
+        statistics.cents_histogram[4] = 1.0;
+        statistics.cents_histogram[5] = 4.0;
+
+        let in_tune = in_tune_bars(&statistics);
+        assert_eq!(in_tune[7], Some(0.75));
+        assert_eq!(in_tune[4], Some(0.0));
+        assert_eq!(in_tune[0], None);
+        assert_eq!(histogram_bars(&statistics)[4..6], [0.25, 1.0]);
+        assert_eq!(histogram_bars(&SessionStatistics::default()), [0.0; CENTS_HISTOGRAM_BINS]);
+    }
+}
@@ -0,0 +1,293 @@
+//! Settings kept between visits
+//!
+//! All settings are stored together as one JSON document with a version.
+//! Documents of older versions are brought up to date by running the
+//! migrations from their version on, one version at a time, before they are
+//! read; a document of a newer version than this build knows is not read at
+//! all. Settings missing from a document take their defaults, as do those
+//! that cannot be read, without costing the others.
+//!
+//! Version 0 is the layout from before the settings were unified, with the
+//! tuning under `config` and a `timestamp` in it; the storage assembles such
+//! a document from the separate keys it used to keep. Its just intonation is
+//! the unit `"JustIntonation"` from before the ratio tables, which
+//! `TuningSystem` reads as the default table itself.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::app_config::TUNING_SETTINGS_EXPIRATION_MS;
+use crate::common::localization::Language;
+use crate::common::pane_layout::PaneLayout;
+use crate::common::shared_types::{DisplayRange, InputCalibration, IntervalMode, MidiNote, NoteNaming, PitchAlgorithm, Scale, SmoothingStrategy, Theme, ToleranceProfile, Transposition, TuningSystem};
+use crate::common::shortcuts::KeyBindings;
+
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Migration of a document from the version at its index to the next
+const MIGRATIONS: [fn(&mut Value); SETTINGS_VERSION as usize] = [migrate_v0_to_v1];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    /// `None` until the tuning was first changed, or once it expired
+    pub tuning: Option<TuningSettings>,
+    /// Built-in theme; `None` for the default
+    pub theme: Option<Theme>,
+    /// `None` to follow the browser
+    pub language: Option<Language>,
+    pub key_bindings: Option<KeyBindings>,
+    pub pane_layout: Option<PaneLayout>,
+    /// Cent offsets of the last custom tuning, kept while other tuning systems are selected
+    pub custom_tuning: Option<Vec<f32>>,
+    /// Semitone mask of the last custom scale, kept while other scales are selected
+    pub custom_scale: Option<u16>,
+    /// Calibrations keyed by input device id
+    pub input_calibrations: HashMap<String, InputCalibration>,
+    pub tutorial_seen: bool,
+    /// Whether the user enabled MIDI, to connect again on the next visit
+    pub midi_enabled: bool,
+    /// Pitch smoothing chosen in the sidebar; `None` for the default
+    pub smoothing: Option<SmoothingStrategy>,
+    /// Pitch detector chosen in the sidebar; `None` for the default
+    pub pitch_detection: Option<PitchDetectionSettings>,
+    /// Interval display chosen in the sidebar; `None` for the default
+    pub interval_mode: Option<IntervalMode>,
+    /// Input device chosen in the sidebar; `None` to let the browser pick
+    pub input_device_id: Option<String>,
+    /// Whether usage telemetry may be sent; `None` until the user answered
+    pub telemetry_consent: Option<bool>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            tuning: None,
+            theme: None,
+            language: None,
+            key_bindings: None,
+            pane_layout: None,
+            custom_tuning: None,
+            custom_scale: None,
+            input_calibrations: HashMap::new(),
+            tutorial_seen: false,
+            midi_enabled: false,
+            smoothing: None,
+            pitch_detection: None,
+            interval_mode: None,
+            input_device_id: None,
+            telemetry_consent: None,
+        }
+    }
+}
+
+/// Tuning of the last visit; unlike the other settings it is forgotten a
+/// while after it was saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TuningSettings {
+    pub tonal_center_note: MidiNote,
+    pub tuning_system: TuningSystem,
+    pub scale: Scale,
+    pub display_range: DisplayRange,
+    #[serde(default = "default_concert_pitch_hz")]
+    pub concert_pitch_hz: f32,
+    #[serde(default)]
+    pub transposition: Transposition,
+    #[serde(default)]
+    pub note_naming: NoteNaming,
+    #[serde(default)]
+    pub tolerance_profile: ToleranceProfile,
+    /// Time of saving in milliseconds, as from `js_sys::Date::now`
+    pub saved_ms: f64,
+}
+
+/// Pitch detection algorithm and the thresholds a reading must pass
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PitchDetectionSettings {
+    pub algorithm: PitchAlgorithm,
+    pub power_threshold: f32,
+    pub clarity_threshold: f32,
+}
+
+fn default_concert_pitch_hz() -> f32 {
+    crate::app_config::DEFAULT_CONCERT_PITCH_HZ
+}
+
+/// Stored concert pitch within the range the sidebar accepts
+fn clamp_concert_pitch_hz(concert_pitch_hz: f32) -> f32 {
+    concert_pitch_hz.clamp(crate::app_config::MIN_CONCERT_PITCH_HZ, crate::app_config::MAX_CONCERT_PITCH_HZ)
+}
+
+impl TuningSettings {
+    pub fn is_expired(&self, now_ms: f64) -> bool {
+        now_ms - self.saved_ms > TUNING_SETTINGS_EXPIRATION_MS
+    }
+}
+
+impl Settings {
+    /// Settings of a stored document of this or an older version
+    pub fn from_json(json: &str) -> Result<Settings, String> {
+        Self::from_json_dropping(json).map(|(settings, _)| settings)
+    }
+
+    /// Settings of a stored document, with the names of the fields that could
+    /// not be read and took their defaults
+    pub fn from_json_dropping(json: &str) -> Result<(Settings, Vec<String>), String> {
+        let mut document: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if !document.is_object() {
+            return Err("Settings are not an object".to_string());
+        }
+        let version = document.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > SETTINGS_VERSION as u64 {
+            return Err(format!("Settings version {} is newer than {}", version, SETTINGS_VERSION));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(&mut document);
+        }
+        document["version"] = SETTINGS_VERSION.into();
+
+        // One field at a time over the defaults, so one that cannot be read
+        // costs only itself
+        let mut merged = serde_json::to_value(Settings::default()).map_err(|e| e.to_string())?;
+        let mut dropped = Vec::new();
+        let Value::Object(fields) = document else { unreachable!("checked to be an object") };
+        for (field, value) in fields {
+            let mut candidate = merged.clone();
+            candidate[field.as_str()] = value;
+            if serde_json::from_value::<Settings>(candidate.clone()).is_ok() {
+                merged = candidate;
+            } else {
+                dropped.push(field);
+            }
+        }
+        let mut settings: Settings = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+        if let Some(tuning) = &mut settings.tuning {
+            tuning.concert_pitch_hz = clamp_concert_pitch_hz(tuning.concert_pitch_hz);
+        }
+        Ok((settings, dropped))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| e.to_string())
+    }
+
+    /// Forget the tuning once it expired
+    pub fn expire_tuning(&mut self, now_ms: f64) {
+        if self.tuning.as_ref().is_some_and(|tuning| tuning.is_expired(now_ms)) {
+            self.tuning = None;
+        }
+    }
+}
+
+/// The tuning moved from `config` to `tuning`, and its `timestamp` became `saved_ms`
+fn migrate_v0_to_v1(document: &mut Value) {
+    let Some(document) = document.as_object_mut() else { return };
+    if let Some(mut config) = document.remove("config") {
+        if let Some(config) = config.as_object_mut()
+            && let Some(timestamp) = config.remove("timestamp")
+        {
+            config.insert("saved_ms".to_string(), timestamp);
+        }
+        document.insert("tuning".to_string(), config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = Settings {
+            tuning: Some(TuningSettings {
+                tonal_center_note: 62,
+                tuning_system: TuningSystem::Pythagorean,
+                scale: Scale::Minor,
+                display_range: DisplayRange::OneFullOctave,
+                concert_pitch_hz: 442.0,
+                transposition: Transposition::default(),
+                note_naming: NoteNaming::default(),
+                tolerance_profile: ToleranceProfile::default(),
+                saved_ms: 1000.0,
+            }),
+            theme: Some(Theme::HighContrast),
+            language: Some(Language::Swedish),
+            custom_scale: Some(0b1010_1101_0101),
+            tutorial_seen: true,
+            midi_enabled: true,
+            smoothing: Some(SmoothingStrategy::Median { window: 7 }),
+            pitch_detection: Some(PitchDetectionSettings { algorithm: PitchAlgorithm::McLeod, power_threshold: 0.5, clarity_threshold: 0.4 }),
+            interval_mode: Some(IntervalMode::Wrapped),
+            input_device_id: Some("usb-mic".to_string()),
+            ..Settings::default()
+        };
+        settings.input_calibrations.insert("mic".to_string(), InputCalibration { gain: 1.5, noise_floor_rms: 0.001, peak_threshold: 0.9 });
+        assert_eq!(Settings::from_json(&settings.to_json().unwrap()).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_migrates_legacy_document() {
+        let json = r#"{
+            "config": {"tonal_center_note": 57, "tuning_system": "EqualTemperament", "scale": "Major", "display_range": "TwoOctaves", "timestamp": 5000},
+            "theme": "light",
+            "language": "sv",
+            "tutorial_seen": true
+        }"#;
+        let settings = Settings::from_json(json).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        let tuning = settings.tuning.unwrap();
+        assert_eq!(tuning.tonal_center_note, 57);
+        assert_eq!(tuning.concert_pitch_hz, crate::app_config::DEFAULT_CONCERT_PITCH_HZ);
+        assert_eq!(tuning.saved_ms, 5000.0);
+        assert_eq!(settings.theme, Some(Theme::Light));
+        assert_eq!(settings.language, Some(Language::Swedish));
+        assert!(settings.tutorial_seen);
+        assert_eq!(settings.key_bindings, None);
+    }
+
+    #[test]
+    fn test_migrates_unit_just_intonation() {
+        let json = r#"{"config": {"tonal_center_note": 57, "tuning_system": "JustIntonation", "scale": "Major", "display_range": "TwoOctaves", "timestamp": 0}}"#;
+        let tuning = Settings::from_json(json).unwrap().tuning.unwrap();
+        assert_eq!(tuning.tuning_system, TuningSystem::JustIntonation(Default::default()));
+    }
+
+    #[test]
+    fn test_drops_only_unreadable_fields() {
+        let json = r#"{"version": 1, "theme": "light", "language": 7, "custom_scale": "all", "tutorial_seen": true, "unknown": 1}"#;
+        let (settings, mut dropped) = Settings::from_json_dropping(json).unwrap();
+        dropped.sort();
+        assert_eq!(dropped, vec!["custom_scale", "language"]);
+        assert_eq!(settings.theme, Some(Theme::Light));
+        assert_eq!(settings.language, None);
+        assert_eq!(settings.custom_scale, None);
+        assert!(settings.tutorial_seen);
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let json = format!(r#"{{"version": {}}}"#, SETTINGS_VERSION + 1);
+        assert!(Settings::from_json(&json).is_err());
+        assert!(Settings::from_json("[]").is_err());
+    }
+
+    #[test]
+    fn test_clamps_concert_pitch() {
+        let json = |concert_pitch_hz: &str| format!(r#"{{"version": 1, "tuning": {{"tonal_center_note": 57, "tuning_system": "EqualTemperament", "scale": "Major", "display_range": "TwoOctaves", "concert_pitch_hz": {}, "saved_ms": 0}}}}"#, concert_pitch_hz);
+        let concert_pitch_hz = |json: String| Settings::from_json(&json).unwrap().tuning.unwrap().concert_pitch_hz;
+        assert_eq!(concert_pitch_hz(json("1000")), crate::app_config::MAX_CONCERT_PITCH_HZ);
+        assert_eq!(concert_pitch_hz(json("0")), crate::app_config::MIN_CONCERT_PITCH_HZ);
+        assert_eq!(concert_pitch_hz(json("442")), 442.0);
+    }
+
+    #[test]
+    fn test_tuning_expires() {
+        let mut settings = Settings::from_json(r#"{"version": 1, "tuning": {"tonal_center_note": 57, "tuning_system": "EqualTemperament", "scale": "Major", "display_range": "TwoOctaves", "saved_ms": 0}}"#).unwrap();
+        settings.expire_tuning(TUNING_SETTINGS_EXPIRATION_MS);
+        assert!(settings.tuning.is_some());
+        settings.expire_tuning(TUNING_SETTINGS_EXPIRATION_MS + 1.0);
+        assert!(settings.tuning.is_none());
+    }
+}
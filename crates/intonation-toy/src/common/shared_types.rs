@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 //! Shared data types for the intonation-toy application.
 //!
 //! This module contains all shared data structures used for communication
@@ -10,10 +8,25 @@
 //! The types are organized to facilitate clear data flow and minimize
 //! duplication across the application layers.
 
+use crate::common::localization::translate;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Volume {
     pub peak_amplitude: f32,
     pub rms_amplitude: f32,
+    /// RMS after A-weighting, which follows the ear's sensitivity at moderate levels
+    pub a_weighted_rms_amplitude: f32,
+    /// ITU-R BS.1770 loudness over the last three seconds
+    pub short_term_lufs: f32,
+}
+
+impl Volume {
+    pub const SILENCE: Volume = Volume {
+        peak_amplitude: 0.0,
+        rms_amplitude: 0.0,
+        a_weighted_rms_amplitude: 0.0,
+        short_term_lufs: crate::app_config::LOUDNESS_FLOOR_LUFS,
+    };
 }
 
 /// MIDI note number type (0-127).
@@ -36,7 +49,7 @@ pub fn is_valid_midi_note(value: i32) -> bool {
 /// 
 /// # Examples
 /// ```
-/// use pitch_toy::shared_types::increment_midi_note;
+/// use intonation_toy::common::shared_types::increment_midi_note;
 /// 
 /// assert_eq!(increment_midi_note(69), Some(70));  // A4 to Bb4
 /// assert_eq!(increment_midi_note(127), None);     // G9 cannot increment
@@ -55,7 +68,7 @@ pub fn increment_midi_note(midi_note: MidiNote) -> Option<MidiNote> {
 /// 
 /// # Examples
 /// ```
-/// use pitch_toy::shared_types::decrement_midi_note;
+/// use intonation_toy::common::shared_types::decrement_midi_note;
 /// 
 /// assert_eq!(decrement_midi_note(69), Some(68));  // A4 to Ab4
 /// assert_eq!(decrement_midi_note(0), None);       // C-1 cannot decrement
@@ -68,6 +81,27 @@ pub fn decrement_midi_note(midi_note: MidiNote) -> Option<MidiNote> {
     }
 }
 
+/// Move a MIDI note by whole octaves.
+/// 
+/// Returns None if the result would leave the valid MIDI range.
+/// 
+/// # Examples
+/// ```
+/// use intonation_toy::common::shared_types::shift_midi_note_octaves;
+/// 
+/// assert_eq!(shift_midi_note_octaves(60, 2), Some(84));  // C4 to C6
+/// assert_eq!(shift_midi_note_octaves(60, -5), Some(0));  // C4 to C-1
+/// assert_eq!(shift_midi_note_octaves(120, 1), None);     // C9 has no octave above
+/// ```
+pub fn shift_midi_note_octaves(midi_note: MidiNote, octaves: i32) -> Option<MidiNote> {
+    let shifted = midi_note as i32 + 12 * octaves;
+    if is_valid_midi_note(shifted) {
+        Some(shifted as MidiNote)
+    } else {
+        None
+    }
+}
+
 /// Converts a MIDI note number (0-127) to its standard note name with octave.
 /// 
 /// Uses the standard MIDI mapping where:
@@ -78,47 +112,86 @@ pub fn decrement_midi_note(midi_note: MidiNote) -> Option<MidiNote> {
 /// 
 /// # Examples
 /// ```
+/// use intonation_toy::common::shared_types::midi_note_to_name;
+/// 
 /// assert_eq!(midi_note_to_name(60), "C4");  // Middle C
 /// assert_eq!(midi_note_to_name(69), "A4");  // Concert A
 /// assert_eq!(midi_note_to_name(0), "C-1");  // Lowest MIDI note
 /// assert_eq!(midi_note_to_name(127), "G9"); // Highest MIDI note
 /// ```
 pub fn midi_note_to_name(midi_note: MidiNote) -> String {
-    const NOTE_NAMES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B"];
-    
-    let octave = (midi_note as i32 / 12) - 1;
-    let note_index = midi_note % 12;
-    let note_name = NOTE_NAMES[note_index as usize];
-    
-    format!("{}{}", note_name, octave)
+    note_number_to_name(midi_note as i32)
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Name of a note number on the MIDI scale, which may lie outside the MIDI range
+fn note_number_to_name(note: i32) -> String {
+    crate::common::note_naming::letter_name(note, false)
+}
+
+/// Stored by name; custom themes are not stored
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Theme {
     Dark,
     Light,
     Autumn,
     Sunset,
+    /// Red-green safe for missing green cones
+    Deuteranopia,
+    /// Red-green safe for missing red cones, which also darkens reds
+    Protanopia,
+    /// Blue-yellow safe
+    Tritanopia,
+    HighContrast,
+    #[serde(skip)]
     Custom(ColorScheme),
 }
 
 impl Theme {
+    /// Names of the built-in themes, in the order of the sidebar
+    pub const BUILT_IN_NAMES: [&'static str; 8] = [
+        "dark", "light", "autumn", "sunset", "deuteranopia", "protanopia", "tritanopia", "high-contrast",
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             Theme::Dark => "dark",
             Theme::Light => "light",
             Theme::Autumn => "autumn",
             Theme::Sunset => "sunset",
+            Theme::Deuteranopia => "deuteranopia",
+            Theme::Protanopia => "protanopia",
+            Theme::Tritanopia => "tritanopia",
+            Theme::HighContrast => "high-contrast",
             Theme::Custom(_) => "custom",
         }
     }
 
+    /// Built-in theme with this name
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            "autumn" => Some(Theme::Autumn),
+            "sunset" => Some(Theme::Sunset),
+            "deuteranopia" => Some(Theme::Deuteranopia),
+            "protanopia" => Some(Theme::Protanopia),
+            "tritanopia" => Some(Theme::Tritanopia),
+            "high-contrast" => Some(Theme::HighContrast),
+            _ => None,
+        }
+    }
+
     pub fn color_scheme(&self) -> ColorScheme {
         match self {
             Theme::Dark => ColorScheme::dark(),
             Theme::Light => ColorScheme::light(),
             Theme::Autumn => ColorScheme::autumn(),
             Theme::Sunset => ColorScheme::sunset(),
+            Theme::Deuteranopia => ColorScheme::deuteranopia(),
+            Theme::Protanopia => ColorScheme::protanopia(),
+            Theme::Tritanopia => ColorScheme::tritanopia(),
+            Theme::HighContrast => ColorScheme::high_contrast(),
             Theme::Custom(color_scheme) => color_scheme.clone(),
         }
     }
@@ -200,15 +273,438 @@ impl ColorScheme {
             error:      [0.90, 0.35, 0.38],  // #E65A60 (Desaturated red for errors)
         }
     }
+
+    /// Dark theme with the Okabe-Ito colors; in tune and out of tune differ
+    /// along the blue-orange axis, which red-green color blindness keeps
+    pub const fn deuteranopia() -> Self {
+        Self {
+            background:  [0.160, 0.180, 0.210], // #282D35
+            surface:     [0.198, 0.229, 0.292], // #323A4A
+            primary:     [0.337, 0.706, 0.914], // #56B4E9
+            secondary:   [0.000, 0.447, 0.698], // #0072B2
+            accent:      [0.902, 0.624, 0.000], // #E69F00
+            text:        [0.890, 0.910, 0.950], // #E2E8F2
+            muted:       [0.464, 0.504, 0.578], // #768093
+            border:      [0.374, 0.374, 0.429], // #5F5F6D
+            error:       [0.800, 0.475, 0.655], // #CC79A7
+        }
+    }
+
+    /// Like the deuteranopia theme with yellow for in tune, since orange
+    /// looks dim without red cones
+    pub const fn protanopia() -> Self {
+        Self {
+            accent:      [0.941, 0.894, 0.259], // #F0E442
+            ..Self::deuteranopia()
+        }
+    }
+
+    /// Dark theme where in tune and out of tune differ along the red-cyan
+    /// axis, which blue-yellow color blindness keeps
+    pub const fn tritanopia() -> Self {
+        Self {
+            background:  [0.160, 0.180, 0.210], // #282D35
+            surface:     [0.198, 0.229, 0.292], // #323A4A
+            primary:     [0.890, 0.910, 0.950], // #E2E8F2
+            secondary:   [0.000, 0.620, 0.451], // #009E73
+            accent:      [0.300, 0.850, 0.850], // #4DD9D9
+            text:        [0.890, 0.910, 0.950], // #E2E8F2
+            muted:       [0.464, 0.504, 0.578], // #768093
+            border:      [0.374, 0.374, 0.429], // #5F5F6D
+            error:       [0.900, 0.200, 0.250], // #E63340
+        }
+    }
+
+    /// Saturated colors and white text on black
+    pub const fn high_contrast() -> Self {
+        Self {
+            background:  [0.000, 0.000, 0.000], // #000000
+            surface:     [0.100, 0.100, 0.100], // #1A1A1A
+            primary:     [1.000, 1.000, 1.000], // #FFFFFF
+            secondary:   [0.000, 0.800, 1.000], // #00CCFF
+            accent:      [1.000, 1.000, 0.000], // #FFFF00
+            text:        [1.000, 1.000, 1.000], // #FFFFFF
+            muted:       [0.750, 0.750, 0.750], // #BFBFBF
+            border:      [1.000, 1.000, 1.000], // #FFFFFF
+            error:       [1.000, 0.250, 0.500], // #FF4080
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(from = "StoredTuningSystem")]
 pub enum TuningSystem {
     EqualTemperament,
+    JustIntonation(JustIntonationRatios),
+    /// Pure fifths; the wolf fifth is about 24 cents narrow
+    Pythagorean,
+    /// Fifths narrowed so four make a pure major third; the wolf fifth is about 35 cents wide
+    QuarterCommaMeantone,
+    /// Well temperament with four narrow fifths and no wolf
+    WerckmeisterIII,
+    /// User-defined deviation from equal temperament of each of the 12 degrees, in cents
+    Custom(Vec<f32>),
+}
+
+/// `TuningSystem` as stored; settings and sessions saved before just
+/// intonation took a ratio table hold it as the unit `"JustIntonation"`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StoredTuningSystem {
+    Current(#[serde(with = "CurrentTuningSystem")] TuningSystem),
+    UnitJustIntonation(UnitJustIntonation),
+}
+
+#[derive(serde::Deserialize)]
+#[serde(remote = "TuningSystem")]
+enum CurrentTuningSystem {
+    EqualTemperament,
+    JustIntonation(JustIntonationRatios),
+    Pythagorean,
+    QuarterCommaMeantone,
+    WerckmeisterIII,
+    Custom(Vec<f32>),
+}
+
+#[derive(serde::Deserialize)]
+enum UnitJustIntonation {
     JustIntonation,
 }
 
+impl From<StoredTuningSystem> for TuningSystem {
+    fn from(stored: StoredTuningSystem) -> Self {
+        match stored {
+            StoredTuningSystem::Current(tuning_system) => tuning_system,
+            StoredTuningSystem::UnitJustIntonation(UnitJustIntonation::JustIntonation) => {
+                TuningSystem::JustIntonation(JustIntonationRatios::default())
+            }
+        }
+    }
+}
+
+impl TuningSystem {
+    /// Identifier of the tuning system in the sidebar and in links
+    pub fn id(&self) -> &'static str {
+        match self {
+            TuningSystem::EqualTemperament => "equal",
+            TuningSystem::JustIntonation(ratios) => match ratios.limit() {
+                Some(JustIntonationLimit::SevenLimit) => "just-7",
+                // Edited ratio tables show as the default preset
+                Some(JustIntonationLimit::FiveLimit) | None => "just",
+            },
+            TuningSystem::Pythagorean => "pythagorean",
+            TuningSystem::QuarterCommaMeantone => "meantone",
+            TuningSystem::WerckmeisterIII => "werckmeister3",
+            TuningSystem::Custom(_) => "custom",
+        }
+    }
+
+    /// Built-in tuning system with this identifier
+    pub fn from_id(id: &str) -> Option<TuningSystem> {
+        match id {
+            "equal" => Some(TuningSystem::EqualTemperament),
+            "just" => Some(TuningSystem::JustIntonation(JustIntonationLimit::FiveLimit.ratios())),
+            "just-7" => Some(TuningSystem::JustIntonation(JustIntonationLimit::SevenLimit.ratios())),
+            "pythagorean" => Some(TuningSystem::Pythagorean),
+            "meantone" => Some(TuningSystem::QuarterCommaMeantone),
+            "werckmeister3" => Some(TuningSystem::WerckmeisterIII),
+            _ => None,
+        }
+    }
+}
+
+/// Whole-number frequency ratio of an interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Ratio {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Ratio {
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Self { numerator, denominator }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.numerator as f32 / self.denominator as f32
+    }
+}
+
+/// Just intonation ratio of each chromatic degree above the tonal center,
+/// indexed by semitones (0-11); the tuning repeats every octave
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct JustIntonationRatios(pub [Ratio; 12]);
+
+impl JustIntonationRatios {
+    pub const FIVE_LIMIT: JustIntonationRatios = JustIntonationRatios([
+        Ratio::new(1, 1),   // Unison
+        Ratio::new(16, 15), // Minor second
+        Ratio::new(9, 8),   // Major second
+        Ratio::new(6, 5),   // Minor third
+        Ratio::new(5, 4),   // Major third
+        Ratio::new(4, 3),   // Perfect fourth
+        Ratio::new(45, 32), // Tritone
+        Ratio::new(3, 2),   // Perfect fifth
+        Ratio::new(8, 5),   // Minor sixth
+        Ratio::new(5, 3),   // Major sixth
+        Ratio::new(9, 5),   // Minor seventh
+        Ratio::new(15, 8),  // Major seventh
+    ]);
+
+    /// Five-limit with the septimal tritone, minor third and harmonic seventh
+    pub const SEVEN_LIMIT: JustIntonationRatios = JustIntonationRatios([
+        Ratio::new(1, 1),
+        Ratio::new(16, 15),
+        Ratio::new(9, 8),
+        Ratio::new(7, 6),
+        Ratio::new(5, 4),
+        Ratio::new(4, 3),
+        Ratio::new(7, 5),
+        Ratio::new(3, 2),
+        Ratio::new(8, 5),
+        Ratio::new(5, 3),
+        Ratio::new(7, 4),
+        Ratio::new(15, 8),
+    ]);
+
+    /// Ratio of an interval in semitones, including whole octaves
+    pub fn interval_ratio(&self, semitones: i32) -> f32 {
+        let degree = self.0[semitones.rem_euclid(12) as usize];
+        degree.value() * 2.0_f32.powi(semitones.div_euclid(12))
+    }
+
+    /// Copy with the ratio of one degree replaced
+    pub fn with_degree(mut self, semitone: usize, ratio: Ratio) -> Self {
+        self.0[semitone] = ratio;
+        self
+    }
+
+    /// The preset limit these ratios match, if any
+    pub fn limit(&self) -> Option<JustIntonationLimit> {
+        JustIntonationLimit::ALL.into_iter().find(|limit| limit.ratios() == *self)
+    }
+}
+
+impl Default for JustIntonationRatios {
+    fn default() -> Self {
+        JustIntonationLimit::default().ratios()
+    }
+}
+
+/// Largest prime allowed in the ratios of a just intonation preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JustIntonationLimit {
+    #[default]
+    FiveLimit,
+    SevenLimit,
+}
+
+impl JustIntonationLimit {
+    pub const ALL: [JustIntonationLimit; 2] = [JustIntonationLimit::FiveLimit, JustIntonationLimit::SevenLimit];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JustIntonationLimit::FiveLimit => "5-limit",
+            JustIntonationLimit::SevenLimit => "7-limit",
+        }
+    }
+
+    pub fn ratios(&self) -> JustIntonationRatios {
+        match self {
+            JustIntonationLimit::FiveLimit => JustIntonationRatios::FIVE_LIMIT,
+            JustIntonationLimit::SevenLimit => JustIntonationRatios::SEVEN_LIMIT,
+        }
+    }
+}
+
+/// Pitch detection algorithms selectable at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum PitchAlgorithm {
+    /// McLeod Pitch Method (normalized square difference)
+    McLeod,
+    #[default]
+    Yin,
+    Autocorrelation,
+}
+
+impl PitchAlgorithm {
+    pub const ALL: [PitchAlgorithm; 3] = [PitchAlgorithm::McLeod, PitchAlgorithm::Yin, PitchAlgorithm::Autocorrelation];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PitchAlgorithm::McLeod => "mcleod",
+            PitchAlgorithm::Yin => "yin",
+            PitchAlgorithm::Autocorrelation => "autocorrelation",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.name() == name)
+    }
+}
+
+/// How the detected pitch is smoothed before display and analysis
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum SmoothingStrategy {
+    /// Exponential moving average; `alpha` is the weight of each new reading
+    Ema { alpha: f32 },
+    /// EMA whose weight grows with the size of the change, after outlier removal
+    AdaptiveEma,
+    /// Median of the last `window` readings
+    Median { window: usize },
+    /// Low-pass filter on the pitch in cents whose cutoff rises with the speed
+    /// of the pitch by `beta` Hz per cent per second
+    OneEuro { min_cutoff_hz: f32, beta: f32 },
+    None,
+}
+
+impl Default for SmoothingStrategy {
+    fn default() -> Self {
+        SmoothingStrategy::Ema { alpha: crate::app_config::PITCH_SMOOTHING_FACTOR }
+    }
+}
+
+impl SmoothingStrategy {
+    /// Every strategy with its default parameters
+    pub const DEFAULTS: [SmoothingStrategy; 5] = [
+        SmoothingStrategy::Ema { alpha: crate::app_config::PITCH_SMOOTHING_FACTOR },
+        SmoothingStrategy::AdaptiveEma,
+        SmoothingStrategy::Median { window: crate::app_config::DEFAULT_MEDIAN_SMOOTHING_WINDOW },
+        SmoothingStrategy::OneEuro {
+            min_cutoff_hz: crate::app_config::DEFAULT_ONE_EURO_MIN_CUTOFF_HZ,
+            beta: crate::app_config::DEFAULT_ONE_EURO_BETA,
+        },
+        SmoothingStrategy::None,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SmoothingStrategy::Ema { .. } => "EMA",
+            SmoothingStrategy::AdaptiveEma => "Adaptive EMA",
+            SmoothingStrategy::Median { .. } => "Median",
+            SmoothingStrategy::OneEuro { .. } => "One-euro",
+            SmoothingStrategy::None => "None",
+        }
+    }
+
+    /// Identifier of the strategy in the sidebar
+    pub fn id(&self) -> &'static str {
+        match self {
+            SmoothingStrategy::Ema { .. } => "ema",
+            SmoothingStrategy::AdaptiveEma => "adaptive-ema",
+            SmoothingStrategy::Median { .. } => "median",
+            SmoothingStrategy::OneEuro { .. } => "one-euro",
+            SmoothingStrategy::None => "none",
+        }
+    }
+
+    /// Strategy with this identifier and its default parameters
+    pub fn from_id(id: &str) -> Option<SmoothingStrategy> {
+        SmoothingStrategy::DEFAULTS.into_iter().find(|strategy| strategy.id() == id)
+    }
+}
+
+/// Waveform used for the reference tones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneTimbre {
+    Sine,
+    Triangle,
+    /// Additive waveform with decaying harmonics
+    #[default]
+    Organ,
+}
+
+impl ToneTimbre {
+    pub const ALL: [ToneTimbre; 3] = [ToneTimbre::Sine, ToneTimbre::Triangle, ToneTimbre::Organ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneTimbre::Sine => "sine",
+            ToneTimbre::Triangle => "triangle",
+            ToneTimbre::Organ => "organ",
+        }
+    }
+}
+
+/// Attack/decay/release times in milliseconds and sustain level (0-1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdsrEnvelope {
+    pub attack_ms: f32,
+    pub decay_ms: f32,
+    pub sustain_level: f32,
+    pub release_ms: f32,
+}
+
+/// Sound of the reference tones played on demand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceToneSettings {
+    pub timbre: ToneTimbre,
+    pub envelope: AdsrEnvelope,
+    /// Peak amplitude, 0-1
+    pub volume: f32,
+}
+
+impl Default for ReferenceToneSettings {
+    fn default() -> Self {
+        Self {
+            timbre: ToneTimbre::default(),
+            envelope: AdsrEnvelope {
+                attack_ms: crate::app_config::REFERENCE_TONE_ATTACK_MS,
+                decay_ms: crate::app_config::REFERENCE_TONE_DECAY_MS,
+                sustain_level: crate::app_config::REFERENCE_TONE_SUSTAIN_LEVEL,
+                release_ms: crate::app_config::REFERENCE_TONE_RELEASE_MS,
+            },
+            volume: crate::app_config::REFERENCE_TONE_VOLUME,
+        }
+    }
+}
+
+/// Mains frequency whose hum the input notch filter removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainsFrequency {
+    Hz50,
+    Hz60,
+}
+
+impl MainsFrequency {
+    pub fn hz(&self) -> f32 {
+        match self {
+            MainsFrequency::Hz50 => 50.0,
+            MainsFrequency::Hz60 => 60.0,
+        }
+    }
+}
+
+/// Pre-processing applied to the input in the AudioWorklet before pitch detection
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputConditioning {
+    pub gate_enabled: bool,
+    pub gate_threshold_db: f32,
+    pub gate_attack_ms: f32,
+    pub gate_release_ms: f32,
+    pub high_pass_enabled: bool,
+    pub high_pass_hz: f32,
+    pub notch: Option<MainsFrequency>,
+}
+
+impl Default for InputConditioning {
+    fn default() -> Self {
+        Self {
+            gate_enabled: false,
+            gate_threshold_db: crate::app_config::NOISE_GATE_THRESHOLD_DB,
+            gate_attack_ms: crate::app_config::NOISE_GATE_ATTACK_MS,
+            gate_release_ms: crate::app_config::NOISE_GATE_RELEASE_MS,
+            high_pass_enabled: false,
+            high_pass_hz: crate::app_config::HIGH_PASS_CUTOFF_HZ,
+            notch: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[derive(serde::Serialize, serde::Deserialize)]
 pub enum Scale {
@@ -238,9 +734,77 @@ pub enum Scale {
     Altered,
     BebopMajor,
     BebopDominant,
+    /// User-defined scale; bit `n` is set when the semitone `n` above the root is included
+    Custom(u16),
 }
 
 impl Scale {
+    /// Identifier of the scale in the sidebar and in links
+    pub fn id(&self) -> &'static str {
+        match self {
+            Scale::Chromatic => "chromatic",
+            Scale::Major => "major",
+            Scale::Minor => "minor",
+            Scale::HarmonicMinor => "harmonic_minor",
+            Scale::MelodicMinor => "melodic_minor",
+            Scale::MajorPentatonic => "major_pentatonic",
+            Scale::MinorPentatonic => "minor_pentatonic",
+            Scale::Blues => "blues",
+            Scale::Dorian => "dorian",
+            Scale::Phrygian => "phrygian",
+            Scale::Lydian => "lydian",
+            Scale::Mixolydian => "mixolydian",
+            Scale::Locrian => "locrian",
+            Scale::WholeTone => "whole_tone",
+            Scale::Augmented => "augmented",
+            Scale::DiminishedHalfWhole => "diminished_half_whole",
+            Scale::DiminishedWholeHalf => "diminished_whole_half",
+            Scale::HungarianMinor => "hungarian_minor",
+            Scale::NeapolitanMinor => "neapolitan_minor",
+            Scale::NeapolitanMajor => "neapolitan_major",
+            Scale::Enigmatic => "enigmatic",
+            Scale::Persian => "persian",
+            Scale::DoubleHarmonicMajor => "double_harmonic_major",
+            Scale::Altered => "altered",
+            Scale::BebopMajor => "bebop_major",
+            Scale::BebopDominant => "bebop_dominant",
+            Scale::Custom(_) => "custom",
+        }
+    }
+
+    /// Built-in scale with this identifier
+    pub fn from_id(id: &str) -> Option<Scale> {
+        match id {
+            "chromatic" => Some(Scale::Chromatic),
+            "major" => Some(Scale::Major),
+            "minor" => Some(Scale::Minor),
+            "harmonic_minor" => Some(Scale::HarmonicMinor),
+            "melodic_minor" => Some(Scale::MelodicMinor),
+            "major_pentatonic" => Some(Scale::MajorPentatonic),
+            "minor_pentatonic" => Some(Scale::MinorPentatonic),
+            "blues" => Some(Scale::Blues),
+            "dorian" => Some(Scale::Dorian),
+            "phrygian" => Some(Scale::Phrygian),
+            "lydian" => Some(Scale::Lydian),
+            "mixolydian" => Some(Scale::Mixolydian),
+            "locrian" => Some(Scale::Locrian),
+            "whole_tone" => Some(Scale::WholeTone),
+            "augmented" => Some(Scale::Augmented),
+            "diminished_half_whole" => Some(Scale::DiminishedHalfWhole),
+            "diminished_whole_half" => Some(Scale::DiminishedWholeHalf),
+            "hungarian_minor" => Some(Scale::HungarianMinor),
+            "neapolitan_minor" => Some(Scale::NeapolitanMinor),
+            "neapolitan_major" => Some(Scale::NeapolitanMajor),
+            "enigmatic" => Some(Scale::Enigmatic),
+            "persian" => Some(Scale::Persian),
+            "double_harmonic_major" => Some(Scale::DoubleHarmonicMajor),
+            "altered" => Some(Scale::Altered),
+            "bebop_major" => Some(Scale::BebopMajor),
+            "bebop_dominant" => Some(Scale::BebopDominant),
+            _ => None,
+        }
+    }
+
     /// Returns a boolean array indicating which semitones (0-11) from the root are included in the scale.
     /// Index 0 represents the root note (always true), index 1 represents +1 semitone from root, etc.
     pub fn pattern(&self) -> [bool; 12] {
@@ -271,8 +835,17 @@ impl Scale {
             Scale::Altered =>          [true, true, false, true, false, true, true, false, true, true, true, false],
             Scale::BebopMajor =>       [true, false, true, false, true, true, true, false, true, false, true, false],
             Scale::BebopDominant =>    [true, false, true, false, true, true, false, true, true, false, true, false],
+            Scale::Custom(mask) => std::array::from_fn(|semitone| mask & (1 << semitone) != 0),
         }
     }
+
+    /// User-defined scale containing the semitones set in `pattern`
+    pub fn custom_from_pattern(pattern: [bool; 12]) -> Self {
+        let mask = (0..12)
+            .filter(|&semitone| pattern[semitone])
+            .fold(0u16, |mask, semitone| mask | (1 << semitone));
+        Scale::Custom(mask)
+    }
 }
 
 /// Check if a semitone offset from the root is included in the given scale.
@@ -285,6 +858,15 @@ pub fn semitone_in_scale(scale: Scale, semitone_offset: i32) -> bool {
     scale.pattern()[normalized_offset as usize]
 }
 
+/// Semitones above the root of a 1-based scale degree; degrees past the
+/// end of the scale continue into the following octaves
+pub fn scale_degree_to_semitones(scale: Scale, degree: usize) -> Option<i32> {
+    let semitones: Vec<i32> = (0..12).filter(|&semitone| scale.pattern()[semitone as usize]).collect();
+    let index = degree.checked_sub(1)?;
+    let octave = (index / semitones.len()) as i32;
+    Some(semitones[index % semitones.len()] + 12 * octave)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct IntonationData {
     pub closest_midi_note: Option<MidiNote>,
@@ -302,7 +884,38 @@ pub enum Pitch {
 pub struct AudioAnalysis {
     pub volume_level: Volume,
     pub pitch: Pitch,
-    pub fft_data: Option<Vec<f32>>, // roadmap
+    /// Clarity of the latest pitch estimate, 0-1
+    pub clarity: Option<f32>,
+    pub fft_data: Option<Spectrum>,
+    pub harmonics: Option<HarmonicAnalysis>,
+    /// Only estimated with the `formant-estimation` feature
+    pub formants: Option<Formants>,
+}
+
+/// Resonances of the vocal tract that distinguish vowels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Formants {
+    pub f1: f32,
+    pub f2: f32,
+}
+
+/// Overtone content of the detected pitch
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarmonicAnalysis {
+    /// Amplitude of each harmonic relative to the fundamental, starting with the fundamental itself
+    pub relative_amplitudes: Vec<f32>,
+    /// Inharmonicity coefficient `B` of `f_k = k * f_1 * sqrt(1 + B * k^2)`; 0 for a harmonic tone
+    pub inharmonicity: f32,
+}
+
+/// Magnitude spectrum of the input, normalized to 0.0-1.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    pub magnitudes: Vec<f32>,
+    /// Width of each bin; bin `i` starts at `i * bin_width_hz`
+    pub bin_width_hz: f32,
+    /// Decibels between magnitudes 0.0 and 1.0
+    pub range_db: f32,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -311,34 +924,47 @@ pub enum Error {
     MicrophoneNotAvailable,
     ProcessingError(String),
     BrowserApiNotSupported,
-    MobileDeviceNotSupported,
     BrowserError,
+    GraphicsContextLost,
+    /// The app panicked; the error overlay offers a bug report
+    InternalError,
+    /// Input moved to another device, named by its label
+    InputDeviceChanged(String),
 }
 
 impl Error {
-    /// Returns the error dialog title for this error variant.
+    /// Whether the app cannot go on; other errors are reported and left behind
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Error::ProcessingError(_) | Error::InputDeviceChanged(_))
+    }
+
+    /// Returns the error dialog title for this error variant, in the current language.
     pub fn title(&self) -> &'static str {
         match self {
-            Error::MicrophonePermissionDenied => "Microphone Access Required",
-            Error::MicrophoneNotAvailable => "Microphone Not Available",
-            Error::ProcessingError(_) => "Processing Error",
-            Error::BrowserApiNotSupported => "Browser Not Supported",
-            Error::MobileDeviceNotSupported => "Mobile Devices Not Supported",
-            Error::BrowserError => "Browser Error",
+            Error::MicrophonePermissionDenied => translate("error-microphone-permission-denied-title"),
+            Error::MicrophoneNotAvailable => translate("error-microphone-not-available-title"),
+            Error::ProcessingError(_) => translate("error-processing-title"),
+            Error::BrowserApiNotSupported => translate("error-browser-api-not-supported-title"),
+            Error::BrowserError => translate("error-browser-title"),
+            Error::GraphicsContextLost => translate("error-graphics-context-lost-title"),
+            Error::InternalError => translate("error-internal-title"),
+            Error::InputDeviceChanged(_) => translate("error-input-device-changed-title"),
         }
     }
 
-    /// Returns the error message template for this error variant.
+    /// Returns the error message template for this error variant, in the current language.
     /// Some messages contain `{}` placeholders for dynamic content.
     /// Note: ProcessingError returns a dynamically allocated string, not a static string.
     pub fn details(&self) -> &str {
         match self {
-            Error::MicrophonePermissionDenied => "Please allow microphone access to use the pitch detection features. Refresh the page and click 'Allow' when prompted. (Mac users: the microphone may be blocked in System Settings.)",
-            Error::MicrophoneNotAvailable => "No microphone device found. Please ensure a microphone is connected and try again.",
+            Error::MicrophonePermissionDenied => translate("error-microphone-permission-denied-details"),
+            Error::MicrophoneNotAvailable => translate("error-microphone-not-available-details"),
             Error::ProcessingError(msg) => msg,
-            Error::BrowserApiNotSupported => "This browser doesn't support the required audio features ({}). Please try Chrome, Firefox, or Edge.",
-            Error::MobileDeviceNotSupported => "This application is not optimized for mobile devices. Please use a desktop computer.",
-            Error::BrowserError => "An unexpected browser error occurred. Please try refreshing the page.",
+            Error::BrowserApiNotSupported => translate("error-browser-api-not-supported-details"),
+            Error::BrowserError => translate("error-browser-details"),
+            Error::GraphicsContextLost => translate("error-graphics-context-lost-details"),
+            Error::InternalError => translate("error-internal-details"),
+            Error::InputDeviceChanged(_) => translate("error-input-device-changed-details"),
         }
     }
 
@@ -353,34 +979,596 @@ impl Error {
         }
         
         // Replace {} placeholders with provided parameters
-        let mut result = template.to_string();
-        for param in params {
-            if let Some(pos) = result.find("{}") {
-                result.replace_range(pos..pos+2, param);
-            }
+        crate::common::localization::fill_placeholders(template, params)
+    }
+
+    /// Details with what the error carries filled in
+    pub fn message(&self) -> String {
+        match self {
+            Error::InputDeviceChanged(label) => self.details_with(&[label]),
+            _ => self.details().to_string(),
         }
-        result
     }
 }
 
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct EngineUpdateResult {
+    /// Wall-clock time of the update in milliseconds, as from `js_sys::Date::now`
+    pub timestamp_ms: f64,
     pub audio_analysis: Option<AudioAnalysis>,
     pub audio_errors: Vec<Error>,
+    pub input_devices: InputDevices,
+    /// Whether the input stream is live and not muted
+    pub input_listening: bool,
+    pub recording: RecordingStatus,
+    pub midi_events: Vec<MidiNoteEvent>,
+    pub midi_outputs: MidiOutputs,
+    pub input_gain: InputGainStatus,
+    pub batching: BatchingStatus,
+    pub lifecycle: AudioLifecycleState,
+    pub recovery_events: Vec<RecoveryEvent>,
+    /// Recording analyzed on request, to be sung along with
+    pub analyzed_recording: Option<RecordedPitchTrack>,
+}
+
+/// Whether audio is flowing, and if not, why
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioLifecycleState {
+    #[default]
+    Running,
+    /// Suspended while the tab is hidden; resumes when it is shown again
+    Hidden,
+    /// Suspended by the browser; resuming needs a user gesture
+    Suspended,
+    Closed,
+}
+
+/// What made the engine rebuild part of the audio graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryReason {
+    /// The worklet stopped delivering audio batches
+    WorkletStalled,
+    /// The input stream ended, e.g. because the device was unplugged
+    InputEnded,
+}
+
+/// Progress of automatic audio recovery, reported as it happens
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryEvent {
+    Detected { reason: RecoveryReason },
+    RestartScheduled { reason: RecoveryReason, attempt: u32, delay_ms: u32 },
+    Restarted { reason: RecoveryReason, attempt: u32 },
+    RestartFailed { reason: RecoveryReason, attempt: u32, error: String },
+}
+
+/// What the volume-peak flag measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PeakMeasure {
+    /// Sample peaks against the clipping or calibrated threshold
+    #[default]
+    SamplePeak,
+    /// Short-term loudness against `LOUDNESS_PEAK_THRESHOLD_LUFS`
+    Loudness,
+}
+
+impl PeakMeasure {
+    pub const ALL: [PeakMeasure; 2] = [PeakMeasure::SamplePeak, PeakMeasure::Loudness];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PeakMeasure::SamplePeak => "Sample peak",
+            PeakMeasure::Loudness => "Loudness",
+        }
+    }
+}
+
+/// How the AudioWorklet batch size is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchSizeMode {
+    /// One batch per analysis hop
+    #[default]
+    Hop,
+    Fixed(usize),
+    /// Start at the hop size and grow batches while the main thread falls behind
+    Adaptive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchingStatus {
+    pub mode: BatchSizeMode,
+    pub batch_size: usize,
+}
+
+/// Result of the input level calibration for one device
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct InputCalibration {
+    pub gain: f32,
+    /// RMS of the room noise after `gain` is applied
+    pub noise_floor_rms: f32,
+    /// Peak amplitude, after `gain`, from which the input counts as peaking
+    pub peak_threshold: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputGainStatus {
+    pub gain: f32,
+    pub automatic: bool,
+    /// Calibration of the active input device, if it has been calibrated
+    pub calibration: Option<InputCalibration>,
+}
+
+/// Progress of the calibration wizard
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CalibrationStep {
+    #[default]
+    Idle,
+    /// The user should stay quiet; `progress` runs from 0.0 to 1.0
+    MeasuringNoise { progress: f32 },
+    /// The user should sing or play at a normal loud level
+    MeasuringVoice { progress: f32 },
+    Failed,
+}
+
+/// Periodic pitch oscillation around a held note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vibrato {
+    pub rate_hz: f32,
+    /// Deviation from the center pitch to either extreme
+    pub depth_cents: f32,
+    /// 1.0 when every cycle has the same length and depth, falling towards 0.0
+    pub regularity: f32,
+    /// Mean offset from the closest note over the analyzed cycles
+    pub center_cents_offset: f32,
+}
+
+/// Detected pitch named by its closest interval above the tonal center
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalReadout {
+    pub semitones: i32,
+    /// Short interval name such as "m3" or "P5"
+    pub abbreviation: &'static str,
+    /// Deviation from the equal-tempered interval
+    pub equal_tempered_cents: f32,
+    /// Deviation from the just ratio of the interval
+    pub just_cents: f32,
+}
+
+impl std::fmt::Display for IntervalReadout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:+.0}¢ (just: {:+.0}¢)", self.abbreviation, self.equal_tempered_cents, self.just_cents)
+    }
+}
+
+/// How far from a note the pitch may be and still count as in tune, for
+/// the pitch line color, session statistics and exercises
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ToleranceProfile {
+    Beginner,
+    #[default]
+    Intermediate,
+    Advanced,
+    /// Tolerance in cents
+    Custom(f32),
+}
+
+impl ToleranceProfile {
+    pub const PRESETS: [ToleranceProfile; 3] = [ToleranceProfile::Beginner, ToleranceProfile::Intermediate, ToleranceProfile::Advanced];
+
+    pub fn cents(&self) -> f32 {
+        match self {
+            ToleranceProfile::Beginner => 25.0,
+            ToleranceProfile::Intermediate => 15.0,
+            ToleranceProfile::Advanced => 8.0,
+            ToleranceProfile::Custom(cents) => *cents,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToleranceProfile::Beginner => "Beginner",
+            ToleranceProfile::Intermediate => "Intermediate",
+            ToleranceProfile::Advanced => "Advanced",
+            ToleranceProfile::Custom(_) => "Custom",
+        }
+    }
+}
+
+/// Intonation over the session for one semitone above the tonal center
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct DegreeStatistics {
+    /// Time this degree was the closest note to the detected pitch
+    pub seconds: f32,
+    /// Part of `seconds` spent within the in-tune tolerance
+    pub in_tune_seconds: f32,
+    /// Time-weighted mean and standard deviation of the cents offset
+    pub mean_cents: f32,
+    pub std_cents: f32,
+    /// Separate stretches of singing on this degree
+    pub attempts: u32,
+}
+
+/// Bins of the cents offset histogram, each `CENTS_HISTOGRAM_BIN_CENTS` wide,
+/// covering the offsets from -50 to +50 cents
+pub const CENTS_HISTOGRAM_BINS: usize = 10;
+pub const CENTS_HISTOGRAM_BIN_CENTS: f32 = 10.0;
+
+/// Intonation statistics since the session was last reset
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct SessionStatistics {
+    /// Indexed by semitones above the tonal center, folded into one octave
+    pub degrees: [DegreeStatistics; 12],
+    pub tolerance_cents: f32,
+    /// Percentage of the detected time spent in tune, once anything was sung
+    pub score: Option<f32>,
+    /// Seconds sung at each cents offset from the closest note, from flat to sharp
+    #[serde(default)]
+    pub cents_histogram: [f32; CENTS_HISTOGRAM_BINS],
+    /// Longest time sung in tune without going out of tune; pauses in the
+    /// singing do not break a streak
+    #[serde(default)]
+    pub longest_streak_seconds: f32,
+}
+
+/// Order in which an exercise presents its targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExerciseKind {
+    /// Every degree of the scale from the tonal center up to the octave
+    #[default]
+    AscendingScale,
+    DescendingScale,
+    /// Random degrees of the scale above the tonal center
+    RandomIntervals,
+}
+
+impl ExerciseKind {
+    pub const ALL: [ExerciseKind; 3] = [ExerciseKind::AscendingScale, ExerciseKind::DescendingScale, ExerciseKind::RandomIntervals];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExerciseKind::AscendingScale => "Ascending scale",
+            ExerciseKind::DescendingScale => "Descending scale",
+            ExerciseKind::RandomIntervals => "Random intervals",
+        }
+    }
+
+    /// Identifier of the exercise in links
+    pub fn id(&self) -> &'static str {
+        match self {
+            ExerciseKind::AscendingScale => "ascending-scale",
+            ExerciseKind::DescendingScale => "descending-scale",
+            ExerciseKind::RandomIntervals => "random-intervals",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<ExerciseKind> {
+        Self::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExerciseTargetResult {
+    pub note: MidiNote,
+    /// Time from presenting the target to the start of the successful hold
+    pub seconds_to_reach: f32,
+    /// Mean deviation from the target during the hold
+    pub mean_cents: f32,
+}
+
+/// Progress of the running or last finished exercise
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExerciseStatus {
+    pub kind: ExerciseKind,
+    pub target_count: usize,
+    /// `None` once every target was reached
+    pub current_target: Option<MidiNote>,
+    /// Part of the hold time the current target has been held, 0.0 to 1.0
+    pub hold_progress: f32,
+    /// One entry per reached target, in order
+    pub results: Vec<ExerciseTargetResult>,
+}
+
+/// Tempo and subdivision of the metronome, and whether it clicks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetronomeSettings {
+    pub tempo_bpm: f32,
+    /// Ticks per beat, 1 for beats only
+    pub subdivision: u32,
+    pub click: bool,
+}
+
+impl Default for MetronomeSettings {
+    fn default() -> Self {
+        Self {
+            tempo_bpm: crate::app_config::DEFAULT_METRONOME_TEMPO_BPM,
+            subdivision: 1,
+            click: false,
+        }
+    }
+}
+
+/// Where the running metronome is within the beat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetronomeStatus {
+    pub settings: MetronomeSettings,
+    /// Tick within the beat, 0 on the beat itself
+    pub tick: u32,
+    /// Part of the current tick that has passed, 0.0 to 1.0
+    pub tick_progress: f32,
+}
+
+/// One note of a reference melody
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceNote {
+    /// `None` for a rest
+    pub note: Option<MidiNote>,
+    pub duration_ms: f64,
+}
+
+/// How closely one note of a reference melody was followed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceNoteResult {
+    pub note: MidiNote,
+    /// Mean deviation from the note while a pitch was detected, `None` if none was
+    pub mean_cents: Option<f32>,
+    /// Part of the note during which a pitch was detected, 0.0 to 1.0
+    pub voiced_fraction: f32,
+}
+
+/// Progress through the running or last finished reference melody
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceTrackStatus {
+    pub position_ms: f64,
+    pub duration_ms: f64,
+    /// Note sounding now; `None` during rests and once the melody ended
+    pub current_note: Option<MidiNote>,
+    /// One entry per note that has ended, rests excluded
+    pub results: Vec<ReferenceNoteResult>,
+}
+
+/// Pitch of a recording analyzed offline, one entry per analysis hop
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RecordedPitchTrack {
+    /// Time between two consecutive entries
+    pub frame_ms: f64,
+    pub frequencies: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiPortInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// Available MIDI outputs and the one notes are sent to
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MidiOutputs {
+    /// Whether MIDI access was requested; outputs are listed once it is granted
+    pub enabled: bool,
+    pub available: Vec<MidiPortInfo>,
+    pub active_output_id: Option<String>,
+}
+
+/// Which notes are sent to the MIDI output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MidiOutputSource {
+    #[default]
+    Off,
+    /// The closest note to the detected pitch, held while it is detected
+    DetectedNote,
+    /// Reference tones, for as long as they sound
+    ReferenceTone,
+}
+
+impl MidiOutputSource {
+    pub const ALL: [MidiOutputSource; 3] = [MidiOutputSource::Off, MidiOutputSource::DetectedNote, MidiOutputSource::ReferenceTone];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MidiOutputSource::Off => "Off",
+            MidiOutputSource::DetectedNote => "Detected note",
+            MidiOutputSource::ReferenceTone => "Reference tone",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiNoteEvent {
+    NoteOn { note: MidiNote, velocity: u8 },
+    NoteOff { note: MidiNote },
+}
+
+/// A note sung without a break or a change of pitch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SungNote {
+    /// Closest note to the mean pitch
+    pub note: MidiNote,
+    pub start_ms: f64,
+    pub duration_ms: f64,
+    /// Mean frequency in Hz
+    pub frequency: f32,
+    /// Deviation of the mean pitch from `note`
+    pub cents_offset: f32,
+    /// Standard deviation of the pitch in cents
+    pub stability_cents: f32,
+}
+
+/// Start and end of sung notes, reported once a note has lasted long enough
+/// not to be a blip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SungNoteEvent {
+    Onset { note: MidiNote, start_ms: f64 },
+    Offset(SungNote),
+}
+
+/// What a key pressed on a MIDI keyboard selects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MidiInputMode {
+    #[default]
+    TonalCenter,
+    /// The held key is the pitch to match
+    TargetPitch,
+}
+
+impl MidiInputMode {
+    pub const ALL: [MidiInputMode; 2] = [MidiInputMode::TonalCenter, MidiInputMode::TargetPitch];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MidiInputMode::TonalCenter => "Tonal center",
+            MidiInputMode::TargetPitch => "Target pitch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RecordingStatus {
+    pub is_recording: bool,
+    /// Length of the recording buffer, which is kept after recording stops
+    pub recorded_seconds: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioInputDevice {
+    pub device_id: String,
+    /// Empty until microphone permission has been granted
+    pub label: String,
+}
+
+/// Available audio inputs and the one currently feeding the analysis
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InputDevices {
+    pub available: Vec<AudioInputDevice>,
+    pub active_device_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ModelUpdateResult {
     pub volume: Volume,
     pub is_peaking: bool,
+    pub peak_measure: PeakMeasure,
     pub pitch: Pitch,
+    /// Clarity of the latest pitch estimate, 0-1
+    pub clarity: Option<f32>,
     pub tuning_system: TuningSystem,
     pub scale: Scale,
     pub closest_midi_note: Option<MidiNote>,
     pub cents_offset: f32,
+    /// Semitones from the tonal center, counted according to `interval_mode`
     pub interval_semitones: i32,
+    pub interval_mode: IntervalMode,
+    /// Key note names are written in
+    pub transposition: Transposition,
+    pub note_naming: NoteNaming,
+    /// Detected pitch relative to the tonal center, as heard against a drone
+    pub interval_readout: Option<IntervalReadout>,
+    pub tonal_center_note: MidiNote,
+    /// Frequency of A4 in Hz
+    pub concert_pitch_hz: f32,
+    pub input_devices: InputDevices,
+    pub recording: RecordingStatus,
+    pub spectrum: Option<Spectrum>,
+    pub harmonics: Option<HarmonicAnalysis>,
+    pub midi_events: Vec<MidiNoteEvent>,
+    pub target_note: Option<MidiNote>,
+    /// Deviation of the detected pitch from the target note in the current tuning system
+    pub target_cents_offset: Option<f32>,
+    /// Recent detected pitches, oldest first, averaged down for drawing a trail
+    pub pitch_trail: Vec<crate::common::pitch_history::PitchSample>,
+    /// Previous attempt of the running exercise over the span of `pitch_trail`,
+    /// timed as if it had started with the running one; empty without one
+    pub ghost_trail: Vec<crate::common::pitch_history::PitchSample>,
+    pub vibrato: Option<Vibrato>,
+    /// Sung notes that started or ended in this update
+    pub note_events: Vec<SungNoteEvent>,
+    /// Tonal center matching the key of the recently sung notes, when it
+    /// differs from the current one
+    pub suggested_tonal_center: Option<MidiNote>,
+    pub midi_outputs: MidiOutputs,
+    pub midi_output_source: MidiOutputSource,
+    pub input_gain: InputGainStatus,
+    pub calibration_step: CalibrationStep,
+    pub tolerance_profile: ToleranceProfile,
+    pub smoothing: SmoothingStrategy,
+    pub session_statistics: SessionStatistics,
+    pub exercise: Option<ExerciseStatus>,
+    pub reference_track: Option<ReferenceTrackStatus>,
+    /// `None` while the metronome is off
+    pub metronome: Option<MetronomeStatus>,
+    /// Export requested in the previous user actions
+    pub session_export: Option<SessionExport>,
+    pub batching: BatchingStatus,
+    pub lifecycle: AudioLifecycleState,
+    pub recovery_events: Vec<RecoveryEvent>,
+    /// Errors of this update, fatal or to be reported and left behind
+    pub errors: Vec<Error>,
+}
+
+impl ModelUpdateResult {
+    pub fn note_namer(&self) -> crate::common::note_naming::NoteNamer {
+        crate::common::note_naming::NoteNamer {
+            naming: self.note_naming,
+            transposition: self.transposition,
+            tonal_center_note: self.tonal_center_note,
+            scale: self.scale,
+            language: crate::common::localization::current_language(),
+        }
+    }
+}
+
+/// File formats for exporting session data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionExportFormat {
+    /// Retained pitch history, one row per detected pitch
+    PitchTimelineCsv,
+    /// Session statistics, one row per semitone above the tonal center
+    DegreeStatisticsCsv,
+    /// Settings, session statistics and pitch history
+    Json,
+}
+
+impl SessionExportFormat {
+    pub fn filename(&self) -> &'static str {
+        match self {
+            SessionExportFormat::PitchTimelineCsv => crate::app_config::PITCH_TIMELINE_EXPORT_FILENAME,
+            SessionExportFormat::DegreeStatisticsCsv => crate::app_config::DEGREE_STATISTICS_EXPORT_FILENAME,
+            SessionExportFormat::Json => crate::app_config::SESSION_EXPORT_FILENAME,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            SessionExportFormat::PitchTimelineCsv | SessionExportFormat::DegreeStatisticsCsv => "text/csv",
+            SessionExportFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Exported session data, ready to be saved under `format.filename()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionExport {
+    pub format: SessionExportFormat,
+    pub contents: String,
+}
+
+/// Model state of a saved session, a session export or a bug report: the
+/// musical settings and the session statistics, but nothing tied to the audio
+/// devices. A reload takes the settings from the settings document instead,
+/// and only the statistics from the last session.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ModelSnapshot {
     pub tonal_center_note: MidiNote,
+    pub concert_pitch_hz: f32,
+    pub tuning_system: TuningSystem,
+    pub scale: Scale,
+    pub transposition: Transposition,
+    #[serde(default)]
+    pub note_naming: NoteNaming,
+    pub tolerance_profile: ToleranceProfile,
+    pub smoothing: SmoothingStrategy,
+    pub interval_mode: IntervalMode,
+    pub session_statistics: SessionStatistics,
 }
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -390,13 +1578,176 @@ pub enum DisplayRange {
     TwoHalfOctaves,
 }
 
+impl DisplayRange {
+    /// Interval from the tonal center, in octaves, at the middle of the display
+    pub fn center_octaves(&self) -> f32 {
+        match self {
+            DisplayRange::TwoOctaves | DisplayRange::TwoHalfOctaves => 0.0,
+            DisplayRange::OneFullOctave => 0.5,
+        }
+    }
+}
+
+/// How closely the display zooms in around the target note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalZoom {
+    /// The whole display range
+    #[default]
+    Full,
+    SixSemitones,
+    ThreeSemitones,
+}
+
+impl IntervalZoom {
+    /// Semitones shown either side of the note zoomed in on, `None` for the
+    /// whole display range
+    pub fn half_span_semitones(&self) -> Option<f32> {
+        match self {
+            IntervalZoom::Full => None,
+            IntervalZoom::SixSemitones => Some(6.0),
+            IntervalZoom::ThreeSemitones => Some(3.0),
+        }
+    }
+}
+
+/// What the main canvas shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayScene {
+    /// The current pitch against the tuning lines
+    #[default]
+    Intonation,
+    /// The pitch trail as a curve scrolling against the tuning lines
+    PitchGraph,
+    /// Strobe tuner rings turning with the offset from the nearest note
+    Strobe,
+    /// Analog tuner needle swinging with the offset from the nearest note
+    Needle,
+}
+
+/// How quickly the tuner needle settles, from bouncy to sluggish
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NeedleDamping {
+    /// Overshoots and swings back like a lightly damped meter
+    Light,
+    /// Settles as fast as possible without overshooting
+    #[default]
+    Critical,
+    /// Creeps towards the reading, hiding small wobbles
+    Heavy,
+}
+
+/// Clef of the staff showing the target and detected notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Clef {
+    #[default]
+    Treble,
+    Bass,
+    Alto,
+}
+
+/// How the interval between the detected pitch and the tonal center is counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum IntervalMode {
+    /// Octaves between the pitch and the tonal center are kept, so a fifth two
+    /// octaves up is 31 semitones
+    #[default]
+    Absolute,
+    /// Intervals are folded into the octave above the tonal center, so any
+    /// fifth is 7 semitones
+    Wrapped,
+}
+
+/// Key of a transposing instrument. Note names are shown as written for the
+/// instrument, while tuning and frequencies stay at concert pitch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Transposition {
+    #[default]
+    C,
+    BFlat,
+    EFlat,
+    F,
+    A,
+}
+
+impl Transposition {
+    pub const ALL: [Transposition; 5] = [Transposition::C, Transposition::BFlat, Transposition::EFlat, Transposition::F, Transposition::A];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transposition::C => "C (concert pitch)",
+            Transposition::BFlat => "B♭",
+            Transposition::EFlat => "E♭",
+            Transposition::F => "F",
+            Transposition::A => "A",
+        }
+    }
+
+    /// Semitones from the sounding note up to the written note
+    pub fn written_offset_semitones(&self) -> i32 {
+        match self {
+            Transposition::C => 0,
+            Transposition::BFlat => 2,
+            Transposition::EFlat => 9,
+            Transposition::F => 7,
+            Transposition::A => 3,
+        }
+    }
+
+    /// Name of a sounding note as written for the instrument
+    ///
+    /// ```
+    /// use intonation_toy::common::shared_types::Transposition;
+    ///
+    /// // Concert Bb is written C on a Bb instrument
+    /// assert_eq!(Transposition::BFlat.written_name(58), "C4");
+    /// assert_eq!(Transposition::C.written_name(58), "Bb3");
+    /// ```
+    pub fn written_name(&self, concert_note: MidiNote) -> String {
+        note_number_to_name(concert_note as i32 + self.written_offset_semitones())
+    }
+}
+
+/// How notes are named on screen and in exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum NoteNaming {
+    /// Letter names with flats, e.g. Bb4
+    #[default]
+    Flats,
+    /// Letter names with sharps, e.g. A#4
+    Sharps,
+    /// Letter names with the accidentals of the key signature of the tonal center and scale
+    ByKey,
+    /// Movable-do solfège with do on the tonal center
+    Solfege,
+    /// Hindustani sargam with Sa on the tonal center
+    Sargam,
+}
+
 /// Context data passed from presentation layer to main scene for rendering calculations
 #[derive(Debug, Clone, PartialEq)]
 pub struct PresentationContext {
     pub tonal_center_note: MidiNote,
+    pub concert_pitch_hz: f32,
     pub tuning_system: TuningSystem,
     pub current_scale: Scale,
-    pub display_range: DisplayRange,
+    /// Placement of intervals on the display, moving while it zooms
+    pub interval_axis: crate::common::interval_axis::IntervalAxis,
+    pub transposition: Transposition,
+    pub note_naming: NoteNaming,
+    /// Language of the note names drawn on the background
+    pub language: crate::common::localization::Language,
+}
+
+impl PresentationContext {
+    pub fn note_namer(&self) -> crate::common::note_naming::NoteNamer {
+        crate::common::note_naming::NoteNamer {
+            naming: self.note_naming,
+            transposition: self.transposition,
+            tonal_center_note: self.tonal_center_note,
+            scale: self.current_scale,
+            language: self.language,
+        }
+    }
 }
 
 /// Converts a semitone interval to a musical interval name.
@@ -441,7 +1792,7 @@ pub struct PresentationContext {
 /// # Examples
 /// 
 /// ```
-/// use pitch_toy::shared_types::interval_name_from_semitones;
+/// use intonation_toy::common::shared_types::interval_name_from_semitones;
 /// 
 /// // Basic intervals
 /// assert_eq!(interval_name_from_semitones(0), "Perfect Unison");
@@ -511,3 +1862,70 @@ pub fn interval_name_from_semitones(semitones: i32) -> String {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_scale_pattern_round_trip() {
+        let major = Scale::Major.pattern();
+        let custom = Scale::custom_from_pattern(major);
+        assert_eq!(custom, Scale::Custom(0b1010_1011_0101));
+        assert_eq!(custom.pattern(), major);
+    }
+
+    #[test]
+    fn test_custom_scale_membership_and_degrees() {
+        // Root, minor third and fifth
+        let scale = Scale::Custom(0b0000_1000_1001);
+        assert!(semitone_in_scale(scale, 3));
+        assert!(semitone_in_scale(scale, -5));
+        assert!(!semitone_in_scale(scale, 4));
+        assert_eq!(scale_degree_to_semitones(scale, 3), Some(7));
+        assert_eq!(scale_degree_to_semitones(scale, 4), Some(12));
+    }
+
+    #[test]
+    fn test_written_names() {
+        // Concert Eb4 is a written C5 on an alto saxophone
+        assert_eq!(Transposition::EFlat.written_name(63), "C5");
+        assert_eq!(Transposition::F.written_name(60), "G4");
+        assert_eq!(Transposition::A.written_name(0), "Eb-1");
+        // Written notes above the MIDI range still get a name
+        assert_eq!(Transposition::EFlat.written_name(127), "E10");
+    }
+
+    #[test]
+    fn test_snapshot_json_round_trip() {
+        let mut session_statistics = SessionStatistics { tolerance_cents: 8.0, score: Some(62.5), longest_streak_seconds: 3.5, ..Default::default() };
+        session_statistics.cents_histogram[4] = 1.5;
+        session_statistics.degrees[7] = DegreeStatistics { seconds: 4.2, in_tune_seconds: 2.6, mean_cents: -3.1, std_cents: 7.4, attempts: 3 };
+        let snapshot = ModelSnapshot {
+            tonal_center_note: 57,
+            concert_pitch_hz: 442.0,
+            tuning_system: TuningSystem::Custom(vec![0.0, -10.0, 3.9, 15.6, -13.7, -2.0, -9.8, 2.0, -27.4, -15.6, 17.6, -11.7]),
+            scale: Scale::Custom(0b0000_1000_1001),
+            transposition: Transposition::BFlat,
+            note_naming: NoteNaming::Sargam,
+            tolerance_profile: ToleranceProfile::Custom(12.5),
+            smoothing: SmoothingStrategy::OneEuro { min_cutoff_hz: 0.8, beta: 0.02 },
+            interval_mode: IntervalMode::Wrapped,
+            session_statistics,
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(serde_json::from_str::<ModelSnapshot>(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_tuning_system_reads_unit_just_intonation() {
+        let tuning_system: TuningSystem = serde_json::from_str(r#""JustIntonation""#).unwrap();
+        assert_eq!(tuning_system, TuningSystem::JustIntonation(JustIntonationRatios::FIVE_LIMIT));
+
+        for tuning_system in [TuningSystem::EqualTemperament, TuningSystem::JustIntonation(JustIntonationRatios::SEVEN_LIMIT), TuningSystem::Custom(vec![0.0; 12])] {
+            let json = serde_json::to_string(&tuning_system).unwrap();
+            assert_eq!(serde_json::from_str::<TuningSystem>(&json).unwrap(), tuning_system);
+        }
+        assert!(serde_json::from_str::<TuningSystem>(r#""Unknown""#).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Keyboard shortcuts and the keys bound to them
+//!
+//! Keys are named like the variants of `three_d::Key`, such as `ArrowUp`,
+//! `Space`, `Num1` or `A`. Browser key codes are translated to these names
+//! when a shortcut is remapped. A shortcut has at most one key, and binding a
+//! key that is in use takes it from its previous shortcut.
+
+use std::collections::BTreeMap;
+use crate::common::shared_types::Scale;
+
+/// Keys with the same name in browser key codes and `three_d::Key`; Escape
+/// is left out since it cancels remapping
+const NAMED_KEYS: [&str; 14] = [
+    "ArrowDown", "ArrowLeft", "ArrowRight", "ArrowUp", "Tab", "Backspace", "Enter", "Space",
+    "Insert", "Delete", "Home", "End", "PageUp", "PageDown",
+];
+/// Scales on the number keys 1 to 9 by default
+const NUMBER_KEY_SCALES: [Scale; 9] = [
+    Scale::Chromatic, Scale::Major, Scale::Minor, Scale::HarmonicMinor, Scale::MelodicMinor,
+    Scale::MajorPentatonic, Scale::MinorPentatonic, Scale::Blues, Scale::Dorian,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Shortcut {
+    TonalCenterUp,
+    TonalCenterDown,
+    TonalCenterOctaveUp,
+    TonalCenterOctaveDown,
+    /// Mute the drone, or bring it back at its previous volume
+    ToggleDrone,
+    SelectScale(Scale),
+}
+
+impl Shortcut {
+    /// Every shortcut, in the order of the sidebar
+    pub fn all() -> impl Iterator<Item = Shortcut> {
+        [
+            Shortcut::TonalCenterUp,
+            Shortcut::TonalCenterDown,
+            Shortcut::TonalCenterOctaveUp,
+            Shortcut::TonalCenterOctaveDown,
+            Shortcut::ToggleDrone,
+        ]
+        .into_iter()
+        .chain(NUMBER_KEY_SCALES.map(Shortcut::SelectScale))
+    }
+
+    /// Name for element ids
+    pub fn id(&self) -> String {
+        match self {
+            Shortcut::TonalCenterUp => "tonal-center-up".to_string(),
+            Shortcut::TonalCenterDown => "tonal-center-down".to_string(),
+            Shortcut::TonalCenterOctaveUp => "tonal-center-octave-up".to_string(),
+            Shortcut::TonalCenterOctaveDown => "tonal-center-octave-down".to_string(),
+            Shortcut::ToggleDrone => "toggle-drone".to_string(),
+            Shortcut::SelectScale(scale) => format!("scale-{:?}", scale).to_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct KeyBindings {
+    shortcuts_by_key: BTreeMap<String, Shortcut>,
+}
+
+impl Default for KeyBindings {
+    /// Left and right step the tonal center by a semitone, up and down by an
+    /// octave; space toggles the drone and the number keys select scales
+    fn default() -> Self {
+        let mut shortcuts_by_key = BTreeMap::from([
+            ("ArrowRight".to_string(), Shortcut::TonalCenterUp),
+            ("ArrowLeft".to_string(), Shortcut::TonalCenterDown),
+            ("ArrowUp".to_string(), Shortcut::TonalCenterOctaveUp),
+            ("ArrowDown".to_string(), Shortcut::TonalCenterOctaveDown),
+            ("Space".to_string(), Shortcut::ToggleDrone),
+        ]);
+        for (digit, scale) in (1..).zip(NUMBER_KEY_SCALES) {
+            shortcuts_by_key.insert(format!("Num{}", digit), Shortcut::SelectScale(scale));
+        }
+        Self { shortcuts_by_key }
+    }
+}
+
+impl KeyBindings {
+    pub fn shortcut(&self, key: &str) -> Option<Shortcut> {
+        self.shortcuts_by_key.get(key).copied()
+    }
+
+    pub fn key(&self, shortcut: Shortcut) -> Option<&str> {
+        self.shortcuts_by_key
+            .iter()
+            .find(|&(_, &bound)| bound == shortcut)
+            .map(|(key, _)| key.as_str())
+    }
+
+    pub fn bind(&mut self, shortcut: Shortcut, key: String) {
+        self.shortcuts_by_key.retain(|_, bound| *bound != shortcut);
+        self.shortcuts_by_key.insert(key, shortcut);
+    }
+}
+
+/// Key name for a browser `KeyboardEvent.code`, for keys that can be bound
+pub fn key_name_from_code(code: &str) -> Option<String> {
+    if let Some(letter) = code.strip_prefix("Key") {
+        return (letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase())).then(|| letter.to_string());
+    }
+    if let Some(digit) = code.strip_prefix("Digit").or_else(|| code.strip_prefix("Numpad")) {
+        return (digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit())).then(|| format!("Num{}", digit));
+    }
+    match code {
+        "NumpadEnter" => Some("Enter".to_string()),
+        code if NAMED_KEYS.contains(&code) => Some(code.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.shortcut("Space"), Some(Shortcut::ToggleDrone));
+        assert_eq!(bindings.shortcut("Num2"), Some(Shortcut::SelectScale(Scale::Major)));
+        assert_eq!(bindings.key(Shortcut::TonalCenterUp), Some("ArrowRight"));
+        assert!(Shortcut::all().all(|shortcut| bindings.key(shortcut).is_some()));
+    }
+
+    #[test]
+    fn test_bind_takes_key_from_previous_shortcut() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Shortcut::TonalCenterUp, "Space".to_string());
+        assert_eq!(bindings.shortcut("Space"), Some(Shortcut::TonalCenterUp));
+        assert_eq!(bindings.shortcut("ArrowRight"), None);
+        assert_eq!(bindings.key(Shortcut::ToggleDrone), None);
+    }
+
+    #[test]
+    fn test_key_names_from_codes() {
+        assert_eq!(key_name_from_code("KeyQ").as_deref(), Some("Q"));
+        assert_eq!(key_name_from_code("Digit7").as_deref(), Some("Num7"));
+        assert_eq!(key_name_from_code("Numpad7").as_deref(), Some("Num7"));
+        assert_eq!(key_name_from_code("PageDown").as_deref(), Some("PageDown"));
+        assert_eq!(key_name_from_code("NumpadAdd"), None);
+        assert_eq!(key_name_from_code("ShiftLeft"), None);
+    }
+}
@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 /// EMA smoother for data smoothing over time
 #[derive(Debug, Clone, PartialEq)]
 pub struct EmaSmoother {
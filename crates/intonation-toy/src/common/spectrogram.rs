@@ -0,0 +1,116 @@
+//! Spectra over time on a log-frequency axis
+//!
+//! Each pushed spectrum becomes one column of levels, the newest at the right.
+//! Rows are spaced evenly in log frequency, so every octave gets the same
+//! height; a row takes the loudest bin within its frequency band, or the bin
+//! containing it where bins are wider than rows. The dynamic range picks how
+//! many decibels below the top of the spectrum still show as above zero.
+
+use crate::common::shared_types::Spectrum;
+
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    columns: usize,
+    rows: usize,
+    min_hz: f32,
+    max_hz: f32,
+    /// Row-major levels from 0.0 to 1.0, row 0 at the lowest frequency
+    levels: Vec<f32>,
+}
+
+impl Spectrogram {
+    pub fn new(columns: usize, rows: usize, min_hz: f32, max_hz: f32) -> Self {
+        assert!(columns > 0 && rows > 0, "Spectrogram must not be empty");
+        assert!(0.0 < min_hz && min_hz < max_hz, "Frequency range must be positive and increasing");
+        Self {
+            columns,
+            rows,
+            min_hz,
+            max_hz,
+            levels: vec![0.0; columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+
+    /// Scroll left by one column and add `spectrum` at the right edge, or an
+    /// empty column without one
+    pub fn push(&mut self, spectrum: Option<&Spectrum>, dynamic_range_db: f32) {
+        for row in 0..self.rows {
+            let level = spectrum.map_or(0.0, |spectrum| {
+                let magnitude = self.band_magnitude(spectrum, row);
+                (1.0 - (1.0 - magnitude) * spectrum.range_db / dynamic_range_db).clamp(0.0, 1.0)
+            });
+            let row_levels = &mut self.levels[row * self.columns..(row + 1) * self.columns];
+            row_levels.rotate_left(1);
+            row_levels[self.columns - 1] = level;
+        }
+    }
+
+    /// Loudest magnitude in the frequency band of `row`
+    fn band_magnitude(&self, spectrum: &Spectrum, row: usize) -> f32 {
+        let ratio = self.max_hz / self.min_hz;
+        let low_hz = self.min_hz * ratio.powf(row as f32 / self.rows as f32);
+        let high_hz = self.min_hz * ratio.powf((row + 1) as f32 / self.rows as f32);
+        let Some(last_bin) = spectrum.magnitudes.len().checked_sub(1) else {
+            return 0.0;
+        };
+        let first = ((low_hz / spectrum.bin_width_hz) as usize).min(last_bin);
+        let last = ((high_hz / spectrum.bin_width_hz).ceil() as usize).saturating_sub(1).clamp(first, last_bin);
+        spectrum.magnitudes[first..=last].iter().copied().fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spectrum with 10 Hz bins, silent except for the given bins
+    fn spectrum(loud_bins: &[(usize, f32)]) -> Spectrum {
+        let mut magnitudes = vec![0.0; 400];
+        for &(bin, magnitude) in loud_bins {
+            magnitudes[bin] = magnitude;
+        }
+        Spectrum { magnitudes, bin_width_hz: 10.0, range_db: 60.0 }
+    }
+
+    #[test]
+    fn test_rows_follow_log_frequency() {
+        // Two rows per octave from 100 Hz to 1600 Hz
+        let mut spectrogram = Spectrogram::new(2, 8, 100.0, 1600.0);
+        spectrogram.push(Some(&spectrum(&[(24, 1.0), (130, 1.0)])), 60.0);
+        let right_column: Vec<f32> = (0..8).map(|row| spectrogram.levels()[row * 2 + 1]).collect();
+        // 240 Hz falls in the band from 200 Hz to 283 Hz, 1300 Hz in the one from 1131 Hz to 1600 Hz
+        assert_eq!(right_column, vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_scrolls_left() {
+        let mut spectrogram = Spectrogram::new(3, 1, 100.0, 200.0);
+        spectrogram.push(Some(&spectrum(&[(10, 1.0)])), 60.0);
+        spectrogram.push(None, 60.0);
+        assert_eq!(spectrogram.levels(), &[0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_dynamic_range() {
+        // Half of the 60 dB range is 30 dB below the top
+        let mut spectrogram = Spectrogram::new(1, 1, 100.0, 200.0);
+        spectrogram.push(Some(&spectrum(&[(10, 0.5)])), 60.0);
+        assert_eq!(spectrogram.levels(), &[0.5]);
+        spectrogram.push(Some(&spectrum(&[(10, 0.5)])), 40.0);
+        assert!((spectrogram.levels()[0] - 0.25).abs() < 1e-6);
+        spectrogram.push(Some(&spectrum(&[(10, 0.5)])), 20.0);
+        assert_eq!(spectrogram.levels(), &[0.0]);
+    }
+}
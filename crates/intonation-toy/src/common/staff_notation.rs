@@ -0,0 +1,136 @@
+//! Placing notes on a five-line staff
+//!
+//! Positions count diatonic steps up from the bottom line, so lines fall on
+//! even steps (0 to 8) and spaces on odd ones. Notes are placed as written for
+//! the instrument and spelled like their letter names, so the accidental
+//! follows the note naming setting.
+
+use crate::common::note_naming::NoteNamer;
+use crate::common::shared_types::{Clef, MidiNote};
+
+/// Step of the top staff line
+pub const TOP_LINE_STEP: i32 = 8;
+
+/// Letter of each pitch class as a step above C, spelled with sharps
+const SHARP_LETTERS: [(i32, Option<Accidental>); 12] = [
+    (0, None), (0, Some(Accidental::Sharp)), (1, None), (1, Some(Accidental::Sharp)), (2, None), (3, None),
+    (3, Some(Accidental::Sharp)), (4, None), (4, Some(Accidental::Sharp)), (5, None), (5, Some(Accidental::Sharp)), (6, None),
+];
+/// Letter of each pitch class as a step above C, spelled with flats
+const FLAT_LETTERS: [(i32, Option<Accidental>); 12] = [
+    (0, None), (1, Some(Accidental::Flat)), (1, None), (2, Some(Accidental::Flat)), (2, None), (3, None),
+    (4, Some(Accidental::Flat)), (4, None), (5, Some(Accidental::Flat)), (5, None), (6, Some(Accidental::Flat)), (6, None),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accidental {
+    Sharp,
+    Flat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaffNote {
+    /// Diatonic steps above the bottom line
+    pub step: i32,
+    pub accidental: Option<Accidental>,
+}
+
+impl StaffNote {
+    /// Place a sounding note on the staff of `clef`
+    pub fn new(namer: &NoteNamer, concert_note: MidiNote, clef: Clef) -> Self {
+        let note = namer.written(concert_note);
+        let letters = if namer.spells_with_sharps() { &SHARP_LETTERS } else { &FLAT_LETTERS };
+        let (letter, accidental) = letters[note.rem_euclid(12) as usize];
+        let diatonic = note.div_euclid(12) * 7 + letter;
+        Self {
+            step: diatonic - bottom_line_diatonic(clef),
+            accidental,
+        }
+    }
+
+    /// Steps of the ledger lines the note needs above or below the staff
+    pub fn ledger_lines(&self) -> Vec<i32> {
+        if self.step < 0 {
+            (self.step..=-2).filter(|step| step % 2 == 0).collect()
+        } else {
+            (TOP_LINE_STEP + 2..=self.step).filter(|step| step % 2 == 0).collect()
+        }
+    }
+}
+
+impl Clef {
+    /// Step of the line the clef marks: G4 for treble, F3 for bass, C4 for alto
+    pub fn line_step(&self) -> i32 {
+        match self {
+            Clef::Treble => 2,
+            Clef::Bass => 6,
+            Clef::Alto => 4,
+        }
+    }
+
+    /// Letter the clef symbol grew from
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Clef::Treble => "G",
+            Clef::Bass => "F",
+            Clef::Alto => "C",
+        }
+    }
+}
+
+/// Diatonic steps from C-1 to the bottom line: E4, G2 or F3
+fn bottom_line_diatonic(clef: Clef) -> i32 {
+    match clef {
+        Clef::Treble => 5 * 7 + 2,
+        Clef::Bass => 3 * 7 + 4,
+        Clef::Alto => 4 * 7 + 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::{NoteNaming, Scale, Transposition};
+
+    fn namer(naming: NoteNaming, transposition: Transposition) -> NoteNamer {
+        NoteNamer { naming, transposition, tonal_center_note: 62, scale: Scale::Major, language: crate::common::localization::Language::English }
+    }
+
+    #[test]
+    fn test_middle_c_in_each_clef() {
+        let namer = namer(NoteNaming::Flats, Transposition::C);
+        let treble = StaffNote::new(&namer, 60, Clef::Treble);
+        assert_eq!(treble, StaffNote { step: -2, accidental: None });
+        assert_eq!(treble.ledger_lines(), vec![-2]);
+
+        let bass = StaffNote::new(&namer, 60, Clef::Bass);
+        assert_eq!(bass.step, 10);
+        assert_eq!(bass.ledger_lines(), vec![10]);
+
+        let alto = StaffNote::new(&namer, 60, Clef::Alto);
+        assert_eq!(alto.step, Clef::Alto.line_step());
+        assert!(alto.ledger_lines().is_empty());
+    }
+
+    #[test]
+    fn test_spelling_follows_note_naming() {
+        // The black key above F4 is F# in D major, and Gb when spelled with flats
+        let by_key = StaffNote::new(&namer(NoteNaming::ByKey, Transposition::C), 66, Clef::Treble);
+        assert_eq!(by_key, StaffNote { step: 1, accidental: Some(Accidental::Sharp) });
+        let flats = StaffNote::new(&namer(NoteNaming::Flats, Transposition::C), 66, Clef::Treble);
+        assert_eq!(flats, StaffNote { step: 2, accidental: Some(Accidental::Flat) });
+    }
+
+    #[test]
+    fn test_written_pitch_and_ledger_lines() {
+        // Concert A3 is written B3 for a B-flat instrument
+        let written = StaffNote::new(&namer(NoteNaming::Flats, Transposition::BFlat), 57, Clef::Treble);
+        assert_eq!(written.step, -3);
+        assert_eq!(written.ledger_lines(), vec![-2]);
+
+        // C6 sits on the second ledger line above the treble staff
+        let high = StaffNote::new(&namer(NoteNaming::Flats, Transposition::C), 84, Clef::Treble);
+        assert_eq!(high.step, 12);
+        assert_eq!(high.ledger_lines(), vec![10, 12]);
+    }
+}
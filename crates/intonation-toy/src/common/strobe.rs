@@ -0,0 +1,56 @@
+//! Phase of a strobe tuner pattern
+//!
+//! A strobe disk lit at the target frequency appears to drift by one band for
+//! every cycle the played pitch gains or loses on the target, so the bands
+//! stand still when in tune and move faster the further off the pitch is, one
+//! way when sharp and the other when flat.
+
+#[derive(Debug, Clone, Default)]
+pub struct StrobePhase {
+    /// Drift in bands, wrapped to 0.0-1.0
+    phase: f64,
+    last_ms: Option<f64>,
+}
+
+impl StrobePhase {
+    /// Advance to `now_ms` for a pitch `cents_offset` away from the target
+    /// `frequency`; without a pitch the pattern holds still
+    pub fn update(&mut self, now_ms: f64, pitch: Option<(f32, f32)>) -> f32 {
+        let elapsed_seconds = self.last_ms.map_or(0.0, |last_ms| (now_ms - last_ms).max(0.0) / 1000.0);
+        self.last_ms = Some(now_ms);
+
+        if let Some((frequency, cents_offset)) = pitch {
+            let target_frequency = frequency as f64 / 2f64.powf(cents_offset as f64 / 1200.0);
+            self.phase = (self.phase + (frequency as f64 - target_frequency) * elapsed_seconds).rem_euclid(1.0);
+        }
+        self.phase as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_tune_stands_still() {
+        let mut strobe = StrobePhase::default();
+        strobe.update(0.0, Some((440.0, 0.0)));
+        assert_eq!(strobe.update(1000.0, Some((440.0, 0.0))), 0.0);
+    }
+
+    #[test]
+    fn test_drift_follows_frequency_difference() {
+        // 2 Hz sharp of 440 Hz drifts two bands a second, a quarter band in 125 ms
+        let sharp_cents = 1200.0 * (442.0f32 / 440.0).log2();
+        let mut strobe = StrobePhase::default();
+        strobe.update(0.0, Some((442.0, sharp_cents)));
+        assert!((strobe.update(125.0, Some((442.0, sharp_cents))) - 0.25).abs() < 1e-3);
+
+        // Flat drifts the other way, and no pitch holds the phase
+        let flat_cents = 1200.0 * (438.0f32 / 440.0).log2();
+        let mut strobe = StrobePhase::default();
+        strobe.update(0.0, None);
+        assert!((strobe.update(125.0, Some((438.0, flat_cents))) - 0.75).abs() < 1e-3);
+        assert!((strobe.update(1000.0, None) - 0.75).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,247 @@
+//! Coordination of the tabs of the app open in one browser
+//!
+//! Every tab announces itself on a shared channel now and then, with its
+//! role and whether it listens to the microphone, and says goodbye as it
+//! closes; a tab not heard from for a while is taken as gone. Two tabs
+//! listening at once analyze the same input twice, which the user is warned
+//! about unless one is the display of the other.
+//!
+//! A tab can act as the controller of others that act as its display, as
+//! when a teacher projects one window while working the sidebar of another.
+//! The controller sends its tuning as it changes, and to every display that
+//! appears, and displays take the tuning of the controller.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::app_config::TAB_TIMEOUT_MS;
+use crate::common::settings::TuningSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TabRole {
+    #[default]
+    Standalone,
+    /// Sends its tuning to the displays
+    Controller,
+    /// Takes the tuning of the controller
+    Display,
+}
+
+impl TabRole {
+    pub const ALL: [TabRole; 3] = [TabRole::Standalone, TabRole::Controller, TabRole::Display];
+
+    /// Identifier in the sidebar
+    pub fn id(&self) -> &'static str {
+        match self {
+            TabRole::Standalone => "standalone",
+            TabRole::Controller => "controller",
+            TabRole::Display => "display",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<TabRole> {
+        TabRole::ALL.into_iter().find(|role| role.id() == id)
+    }
+}
+
+/// Message between tabs, as JSON on the channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TabMessage {
+    Presence { tab_id: String, role: TabRole, listening: bool },
+    Leaving { tab_id: String },
+    Tuning { tab_id: String, tuning: TuningSettings },
+}
+
+/// What a tab does about a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabAction {
+    /// Send the current tuning, for a display that appeared
+    SendTuning,
+    ApplyTuning(TuningSettings),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Peer {
+    role: TabRole,
+    listening: bool,
+    last_seen_ms: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TabCoordinator {
+    tab_id: String,
+    role: TabRole,
+    listening: bool,
+    peers: HashMap<String, Peer>,
+}
+
+impl TabCoordinator {
+    pub fn new(tab_id: String) -> Self {
+        Self { tab_id, role: TabRole::Standalone, listening: false, peers: HashMap::new() }
+    }
+
+    pub fn role(&self) -> TabRole {
+        self.role
+    }
+
+    pub fn set_role(&mut self, role: TabRole) {
+        self.role = role;
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening
+    }
+
+    pub fn set_listening(&mut self, listening: bool) {
+        self.listening = listening;
+    }
+
+    /// Announcement of this tab
+    pub fn presence(&self) -> TabMessage {
+        TabMessage::Presence { tab_id: self.tab_id.clone(), role: self.role, listening: self.listening }
+    }
+
+    pub fn leaving(&self) -> TabMessage {
+        TabMessage::Leaving { tab_id: self.tab_id.clone() }
+    }
+
+    /// The tuning for the displays, if this tab is their controller
+    pub fn tuning(&self, tuning: TuningSettings) -> Option<TabMessage> {
+        (self.role == TabRole::Controller).then(|| TabMessage::Tuning { tab_id: self.tab_id.clone(), tuning })
+    }
+
+    pub fn on_message(&mut self, message: TabMessage, now_ms: f64) -> Option<TabAction> {
+        match message {
+            TabMessage::Presence { tab_id, role, listening } => {
+                let previous = self.peers.insert(tab_id, Peer { role, listening, last_seen_ms: now_ms });
+                let new_display = role == TabRole::Display && previous.is_none_or(|peer| peer.role != TabRole::Display);
+                (new_display && self.role == TabRole::Controller).then_some(TabAction::SendTuning)
+            }
+            TabMessage::Leaving { tab_id } => {
+                self.peers.remove(&tab_id);
+                None
+            }
+            TabMessage::Tuning { tab_id, tuning } => {
+                let from_controller = self.peers.get(&tab_id).is_some_and(|peer| peer.role == TabRole::Controller);
+                (from_controller && self.role == TabRole::Display).then_some(TabAction::ApplyTuning(tuning))
+            }
+        }
+    }
+
+    /// Forget the tabs not heard from for a while
+    pub fn expire(&mut self, now_ms: f64) {
+        self.peers.retain(|_, peer| now_ms - peer.last_seen_ms <= TAB_TIMEOUT_MS);
+    }
+
+    pub fn other_tab_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Whether this tab and another listen to the microphone at once, other
+    /// than a controller and its display
+    pub fn has_microphone_contention(&self) -> bool {
+        let paired = |role| matches!((self.role, role), (TabRole::Controller, TabRole::Display) | (TabRole::Display, TabRole::Controller));
+        self.listening && self.peers.values().any(|peer| peer.listening && !paired(peer.role))
+    }
+
+    pub fn has_controller(&self) -> bool {
+        self.peers.values().any(|peer| peer.role == TabRole::Controller)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::shared_types::{DisplayRange, Scale, TuningSystem};
+
+    fn tuning() -> TuningSettings {
+        TuningSettings {
+            tonal_center_note: 62,
+            tuning_system: TuningSystem::Pythagorean,
+            scale: Scale::Dorian,
+            display_range: DisplayRange::OneFullOctave,
+            concert_pitch_hz: 415.0,
+            transposition: Default::default(),
+            note_naming: Default::default(),
+            tolerance_profile: Default::default(),
+            saved_ms: 0.0,
+        }
+    }
+
+    fn presence(tab_id: &str, role: TabRole, listening: bool) -> TabMessage {
+        TabMessage::Presence { tab_id: tab_id.to_string(), role, listening }
+    }
+
+    #[test]
+    fn test_contention_and_expiry() {
+        let mut tab = TabCoordinator::new("a".to_string());
+        tab.on_message(presence("b", TabRole::Standalone, true), 0.0);
+        assert_eq!(tab.other_tab_count(), 1);
+        assert!(!tab.has_microphone_contention());
+
+        tab.set_listening(true);
+        assert!(tab.has_microphone_contention());
+
+        tab.expire(TAB_TIMEOUT_MS + 1.0);
+        assert_eq!(tab.other_tab_count(), 0);
+        assert!(!tab.has_microphone_contention());
+
+        tab.on_message(presence("c", TabRole::Standalone, true), 0.0);
+        tab.on_message(TabMessage::Leaving { tab_id: "c".to_string() }, 1.0);
+        assert!(!tab.has_microphone_contention());
+
+        tab.set_role(TabRole::Controller);
+        tab.on_message(presence("d", TabRole::Display, true), 0.0);
+        assert!(!tab.has_microphone_contention());
+    }
+
+    #[test]
+    fn test_contention_follows_the_input() {
+        let mut tab = TabCoordinator::new("a".to_string());
+        tab.set_listening(true);
+        tab.on_message(presence("b", TabRole::Standalone, true), 0.0);
+        assert!(tab.has_microphone_contention());
+
+        // The other tab lost its microphone
+        tab.on_message(presence("b", TabRole::Standalone, false), 1.0);
+        assert!(!tab.has_microphone_contention());
+
+        tab.on_message(presence("b", TabRole::Standalone, true), 2.0);
+        tab.set_listening(false);
+        assert!(!tab.is_listening());
+        assert!(!tab.has_microphone_contention());
+        assert_eq!(tab.presence(), presence("a", TabRole::Standalone, false));
+    }
+
+    #[test]
+    fn test_controller_tuning_reaches_displays() {
+        let mut controller = TabCoordinator::new("controller".to_string());
+        assert_eq!(controller.tuning(tuning()), None);
+        controller.set_role(TabRole::Controller);
+        let mut display = TabCoordinator::new("display".to_string());
+        display.set_role(TabRole::Display);
+
+        assert_eq!(controller.on_message(display.presence(), 0.0), Some(TabAction::SendTuning));
+        assert_eq!(controller.on_message(display.presence(), 1.0), None);
+
+        let message = controller.tuning(tuning()).unwrap();
+        // A display only takes the tuning of a tab it knows as controller
+        assert_eq!(display.on_message(message.clone(), 0.0), None);
+        display.on_message(controller.presence(), 0.0);
+        assert!(display.has_controller());
+        assert_eq!(display.on_message(message.clone(), 0.0), Some(TabAction::ApplyTuning(tuning())));
+
+        let mut standalone = TabCoordinator::new("other".to_string());
+        standalone.on_message(controller.presence(), 0.0);
+        assert_eq!(standalone.on_message(message, 0.0), None);
+    }
+
+    #[test]
+    fn test_messages_round_trip_as_json() {
+        let message = TabMessage::Tuning { tab_id: "a".to_string(), tuning: tuning() };
+        let json = serde_json::to_string(&message).unwrap();
+        assert!(json.starts_with(r#"{"type":"Tuning""#));
+        assert_eq!(serde_json::from_str::<TabMessage>(&json).unwrap(), message);
+        assert_eq!(TabRole::from_id("display"), Some(TabRole::Display));
+    }
+}
@@ -0,0 +1,174 @@
+//! Usage telemetry, sent only with the user's consent
+//!
+//! Events tell which features, tuning systems and scales are used, and how
+//! smoothly the app runs. None identifies the user: selections are sent by
+//! their identifiers, which name a custom tuning or scale only as custom, and
+//! the frame rate is rounded down to a multiple of ten. Until the user
+//! answers, events wait in a short queue; declining drops them, consenting
+//! sends them with the rest.
+//!
+//! Where the events go is up to the `TelemetryBackend`. The default,
+//! `NoopBackend`, sends nothing.
+
+use serde_json::{json, Value};
+use crate::app_config::TELEMETRY_MAX_PENDING_EVENTS;
+use crate::common::shared_types::{Scale, TuningSystem};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TelemetryEvent {
+    PageView,
+    /// A feature was used, by a short identifier such as `recording`
+    FeatureUsed(&'static str),
+    /// Identifier of the selected tuning system
+    TuningSystemSelected(&'static str),
+    /// Identifier of the selected scale
+    ScaleSelected(&'static str),
+    /// Frames per second over a while, rounded down to a multiple of ten
+    FrameRate(u32),
+}
+
+impl TelemetryEvent {
+    pub fn tuning_system(tuning_system: &TuningSystem) -> Self {
+        TelemetryEvent::TuningSystemSelected(tuning_system.id())
+    }
+
+    pub fn scale(scale: Scale) -> Self {
+        TelemetryEvent::ScaleSelected(scale.id())
+    }
+
+    pub fn frame_rate(frames_per_second: f64) -> Self {
+        TelemetryEvent::FrameRate((frames_per_second.max(0.0) as u32) / 10 * 10)
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            TelemetryEvent::PageView => json!({ "event": "page_view" }),
+            TelemetryEvent::FeatureUsed(feature) => json!({ "event": "feature_used", "value": feature }),
+            TelemetryEvent::TuningSystemSelected(id) => json!({ "event": "tuning_system", "value": id }),
+            TelemetryEvent::ScaleSelected(id) => json!({ "event": "scale", "value": id }),
+            TelemetryEvent::FrameRate(fps) => json!({ "event": "frame_rate", "value": fps }),
+        }
+    }
+}
+
+/// Where consented events go
+pub trait TelemetryBackend {
+    /// Deliver a batch of events; delivery is best effort and not retried
+    fn send(&self, events: &[TelemetryEvent]);
+}
+
+/// Backend that sends nothing, for builds without a telemetry endpoint
+pub struct NoopBackend;
+
+impl TelemetryBackend for NoopBackend {
+    fn send(&self, _events: &[TelemetryEvent]) {}
+}
+
+pub struct Telemetry {
+    backend: Box<dyn TelemetryBackend>,
+    /// `None` until the user answered
+    consent: Option<bool>,
+    pending: Vec<TelemetryEvent>,
+}
+
+impl Telemetry {
+    pub fn new(backend: Box<dyn TelemetryBackend>, consent: Option<bool>) -> Self {
+        Self { backend, consent, pending: Vec::new() }
+    }
+
+    pub fn consent(&self) -> Option<bool> {
+        self.consent
+    }
+
+    pub fn set_consent(&mut self, consent: bool) {
+        self.consent = Some(consent);
+        if !consent {
+            self.pending.clear();
+        }
+    }
+
+    /// Queue an event; events beyond the limit of the queue are dropped
+    pub fn record(&mut self, event: TelemetryEvent) {
+        if self.consent != Some(false) && self.pending.len() < TELEMETRY_MAX_PENDING_EVENTS {
+            self.pending.push(event);
+        }
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Send the queued events, once the user consented
+    pub fn flush(&mut self) {
+        if self.consent == Some(true) && !self.pending.is_empty() {
+            self.backend.send(&self.pending);
+            self.pending.clear();
+        }
+    }
+}
+
+/// Body of a request carrying `events`
+pub fn batch_to_json(events: &[TelemetryEvent]) -> String {
+    Value::Array(events.iter().map(TelemetryEvent::to_json).collect()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct RecordingBackend(Rc<RefCell<Vec<TelemetryEvent>>>);
+
+    impl TelemetryBackend for RecordingBackend {
+        fn send(&self, events: &[TelemetryEvent]) {
+            self.0.borrow_mut().extend_from_slice(events);
+        }
+    }
+
+    fn telemetry(consent: Option<bool>) -> (Telemetry, Rc<RefCell<Vec<TelemetryEvent>>>) {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        (Telemetry::new(Box::new(RecordingBackend(sent.clone())), consent), sent)
+    }
+
+    #[test]
+    fn test_events_wait_for_consent() {
+        let (mut telemetry, sent) = telemetry(None);
+        telemetry.record(TelemetryEvent::PageView);
+        telemetry.record(TelemetryEvent::scale(Scale::Dorian));
+        telemetry.flush();
+        assert!(sent.borrow().is_empty());
+
+        telemetry.set_consent(true);
+        telemetry.flush();
+        assert_eq!(*sent.borrow(), vec![TelemetryEvent::PageView, TelemetryEvent::ScaleSelected("dorian")]);
+        assert_eq!(telemetry.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_declining_drops_events() {
+        let (mut telemetry, sent) = telemetry(None);
+        telemetry.record(TelemetryEvent::PageView);
+        telemetry.set_consent(false);
+        telemetry.record(TelemetryEvent::FeatureUsed("recording"));
+        telemetry.flush();
+        assert_eq!(telemetry.pending_count(), 0);
+        assert!(sent.borrow().is_empty());
+
+        for _ in 0..TELEMETRY_MAX_PENDING_EVENTS + 5 {
+            telemetry.set_consent(true);
+            telemetry.record(TelemetryEvent::PageView);
+        }
+        assert_eq!(telemetry.pending_count(), TELEMETRY_MAX_PENDING_EVENTS);
+    }
+
+    #[test]
+    fn test_events_are_anonymous() {
+        assert_eq!(TelemetryEvent::tuning_system(&TuningSystem::Custom(vec![3.0; 12])), TelemetryEvent::TuningSystemSelected("custom"));
+        assert_eq!(TelemetryEvent::frame_rate(59.7), TelemetryEvent::FrameRate(50));
+        assert_eq!(
+            batch_to_json(&[TelemetryEvent::FrameRate(60), TelemetryEvent::PageView]),
+            r#"[{"event":"frame_rate","value":60},{"event":"page_view"}]"#
+        );
+    }
+}
@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 use crate::common::shared_types::{Theme, ColorScheme};
 use std::sync::{Mutex, OnceLock};
 
@@ -27,6 +25,7 @@ pub fn get_current_color_scheme() -> ColorScheme {
 pub fn set_current_theme(theme: Theme) {
     if let Some(theme_mutex) = CURRENT_THEME.get() {
         *theme_mutex.lock().unwrap() = theme;
+        #[cfg(target_arch = "wasm32")]
         crate::web::styling::update_css_variables();
     }
 }
@@ -0,0 +1,85 @@
+//! Placement of the display in the touch-first layout
+//!
+//! On a touch screen the controls move into a drawer along the bottom edge,
+//! closed down to its toggle, and the display takes the rest of the window
+//! with a thin margin. In portrait the display sits at the top and the open
+//! drawer fills the space below it, so both stay in view. In landscape there
+//! is no room below, so the display is centered and the open drawer covers
+//! part of it.
+
+use crate::app_config::{CANVAS_MAX_SIZE, TOUCH_CANVAS_MARGIN, TOUCH_CANVAS_MIN_SIZE, TOUCH_CONTROLS_HANDLE_HEIGHT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    pub fn of(width: i32, height: i32) -> Orientation {
+        if height > width { Orientation::Portrait } else { Orientation::Landscape }
+    }
+}
+
+/// Square canvas and open drawer in a window, in CSS pixels from its top left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchPlacement {
+    pub left: i32,
+    pub top: i32,
+    pub size: i32,
+    /// Height of the open controls drawer
+    pub controls_height: i32,
+}
+
+impl TouchPlacement {
+    pub fn new(width: i32, height: i32) -> Self {
+        let margin = TOUCH_CANVAS_MARGIN;
+        let available_height = height - TOUCH_CONTROLS_HANDLE_HEIGHT - margin * 2;
+        let size = (width - margin * 2)
+            .min(available_height)
+            .clamp(TOUCH_CANVAS_MIN_SIZE, CANVAS_MAX_SIZE);
+        let left = (width - size).max(0) / 2;
+
+        match Orientation::of(width, height) {
+            Orientation::Portrait => Self {
+                left,
+                top: margin,
+                size,
+                controls_height: (height - size - margin * 2).max(height / 2),
+            },
+            Orientation::Landscape => Self {
+                left,
+                top: (height - TOUCH_CONTROLS_HANDLE_HEIGHT - size).max(0) / 2,
+                size,
+                controls_height: height * 3 / 5,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portrait_puts_controls_below_the_display() {
+        let placement = TouchPlacement::new(390, 844);
+        assert_eq!(placement.size, 390 - TOUCH_CANVAS_MARGIN * 2);
+        assert_eq!(placement.left, TOUCH_CANVAS_MARGIN);
+        assert_eq!(placement.top, TOUCH_CANVAS_MARGIN);
+        assert!(placement.top + placement.size <= 844 - placement.controls_height);
+    }
+
+    #[test]
+    fn test_landscape_centers_the_display_above_the_toggle() {
+        let placement = TouchPlacement::new(844, 390);
+        assert_eq!(placement.size, 390 - TOUCH_CONTROLS_HANDLE_HEIGHT - TOUCH_CANVAS_MARGIN * 2);
+        assert_eq!(placement.left, (844 - placement.size) / 2);
+        assert_eq!(placement.top, TOUCH_CANVAS_MARGIN);
+
+        let small = TouchPlacement::new(300, 200);
+        assert_eq!(small.size, TOUCH_CANVAS_MIN_SIZE);
+        assert_eq!((small.left, small.top), (30, 0));
+        assert_eq!(Orientation::of(300, 300), Orientation::Landscape);
+    }
+}
@@ -0,0 +1,97 @@
+//! Steps of the tutorial shown to new users
+//!
+//! Every step points out one part of the app by highlighting its element on
+//! the page and explaining it beside the highlight. The tutorial runs once on
+//! the first start and can be shown again from the sidebar.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    RootSelector,
+    PitchMarker,
+    DroneButton,
+}
+
+impl TutorialStep {
+    pub const ALL: [TutorialStep; 3] = [TutorialStep::RootSelector, TutorialStep::PitchMarker, TutorialStep::DroneButton];
+
+    /// Id of the element the step highlights
+    pub fn target_id(&self) -> &'static str {
+        match self {
+            TutorialStep::RootSelector => "tonal-center-display",
+            TutorialStep::PitchMarker => "three-d-canvas",
+            TutorialStep::DroneButton => "volume-icon",
+        }
+    }
+
+    pub fn title_key(&self) -> &'static str {
+        match self {
+            TutorialStep::RootSelector => "tutorial-root-selector-title",
+            TutorialStep::PitchMarker => "tutorial-pitch-marker-title",
+            TutorialStep::DroneButton => "tutorial-drone-button-title",
+        }
+    }
+
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            TutorialStep::RootSelector => "tutorial-root-selector-text",
+            TutorialStep::PitchMarker => "tutorial-pitch-marker-text",
+            TutorialStep::DroneButton => "tutorial-drone-button-text",
+        }
+    }
+
+    /// Whether the element is in the sidebar, which must be shown for it
+    pub fn in_sidebar(&self) -> bool {
+        !matches!(self, TutorialStep::PitchMarker)
+    }
+}
+
+/// Position in a running tutorial
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tutorial {
+    index: usize,
+}
+
+impl Tutorial {
+    pub fn step(&self) -> TutorialStep {
+        TutorialStep::ALL[self.index]
+    }
+
+    /// Number of the step counted from one
+    pub fn number(&self) -> usize {
+        self.index + 1
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.index + 1 == TutorialStep::ALL.len()
+    }
+
+    /// The next step, `None` when the tutorial is over
+    pub fn next(self) -> Option<Tutorial> {
+        (!self.is_last()).then_some(Tutorial { index: self.index + 1 })
+    }
+
+    /// The step before, staying at the first
+    pub fn back(self) -> Tutorial {
+        Tutorial { index: self.index.saturating_sub(1) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tutorial_walks_through_every_step() {
+        let mut tutorial = Tutorial::default();
+        assert_eq!(tutorial.back(), tutorial);
+        let mut steps = vec![tutorial.step()];
+        while let Some(next) = tutorial.next() {
+            assert_eq!(next.back(), tutorial);
+            tutorial = next;
+            steps.push(tutorial.step());
+        }
+        assert_eq!(steps, TutorialStep::ALL);
+        assert!(tutorial.is_last());
+        assert_eq!(tutorial.number(), TutorialStep::ALL.len());
+    }
+}
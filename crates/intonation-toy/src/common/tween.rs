@@ -0,0 +1,155 @@
+//! Values that move to a new target over time instead of jumping
+//!
+//! A tween starts from wherever it is when given a new target, so changing
+//! the target halfway through an animation carries on smoothly from the
+//! current value. Time is in milliseconds, as from `js_sys::Date::now`.
+
+/// Shape of the motion from 0.0 to 1.0 over the duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// Starts fast and slows into the target
+    EaseOutCubic,
+    /// Starts and ends slowly
+    EaseInOutCubic,
+    /// Overshoots the target a little before settling
+    EaseOutBack,
+}
+
+impl Easing {
+    /// Eased progress at `t` between 0.0 and 1.0
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 { 4.0 * t.powi(3) } else { 1.0 - (-2.0 * t + 2.0).powi(3) * 0.5 }
+            }
+            Easing::EaseOutBack => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+/// Values a tween can move between
+pub trait Lerp: Copy + PartialEq {
+    /// The value a part `t` of the way from `self` to `other`
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl<const N: usize> Lerp for [f32; N] {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        std::array::from_fn(|i| self[i].lerp(other[i], t))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    start_ms: f64,
+    duration_ms: f64,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Resting at `value`
+    pub fn new(value: T) -> Self {
+        Self { from: value, to: value, start_ms: 0.0, duration_ms: 0.0, easing: Easing::Linear }
+    }
+
+    /// Move from the current value to `target`; a target it is already
+    /// moving to keeps the running animation
+    pub fn animate_to(&mut self, target: T, now_ms: f64, duration_ms: f64, easing: Easing) {
+        if target == self.to {
+            return;
+        }
+        self.from = self.value(now_ms);
+        self.to = target;
+        self.start_ms = now_ms;
+        self.duration_ms = duration_ms;
+        self.easing = easing;
+    }
+
+    /// Stop at `value` at once
+    pub fn jump_to(&mut self, value: T) {
+        *self = Self::new(value);
+    }
+
+    pub fn value(&self, now_ms: f64) -> T {
+        if self.duration_ms <= 0.0 {
+            return self.to;
+        }
+        let t = ((now_ms - self.start_ms) / self.duration_ms) as f32;
+        self.from.lerp(self.to, self.easing.apply(t))
+    }
+
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn is_running(&self, now_ms: f64) -> bool {
+        now_ms < self.start_ms + self.duration_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_easings_start_at_zero_and_end_at_one() {
+        for easing in [Easing::Linear, Easing::EaseOutCubic, Easing::EaseInOutCubic, Easing::EaseOutBack] {
+            assert!(approx(easing.apply(0.0), 0.0), "{:?}", easing);
+            assert!(approx(easing.apply(1.0), 1.0), "{:?}", easing);
+            assert!(approx(easing.apply(2.0), 1.0), "{:?}", easing);
+        }
+        assert!(approx(Easing::EaseInOutCubic.apply(0.5), 0.5));
+        assert!(Easing::EaseOutCubic.apply(0.5) > 0.5);
+        assert!((0..100).any(|i| Easing::EaseOutBack.apply(i as f32 / 100.0) > 1.0));
+    }
+
+    #[test]
+    fn test_tween_moves_to_target() {
+        let mut tween = Tween::new(0.0_f32);
+        assert!(approx(tween.value(50.0), 0.0));
+
+        tween.animate_to(10.0, 100.0, 200.0, Easing::Linear);
+        assert!(tween.is_running(150.0));
+        assert!(approx(tween.value(100.0), 0.0));
+        assert!(approx(tween.value(200.0), 5.0));
+        assert!(approx(tween.value(400.0), 10.0));
+        assert!(!tween.is_running(300.0));
+
+        // The same target does not restart the animation
+        tween.animate_to(10.0, 200.0, 200.0, Easing::Linear);
+        assert!(approx(tween.value(250.0), 7.5));
+
+        tween.jump_to(3.0);
+        assert!(approx(tween.value(0.0), 3.0) && !tween.is_running(0.0));
+    }
+
+    #[test]
+    fn test_retargeting_starts_from_the_current_value() {
+        let mut color = Tween::new([0.0_f32, 0.0, 1.0]);
+        color.animate_to([1.0, 0.0, 0.0], 0.0, 100.0, Easing::Linear);
+        color.animate_to([0.0, 1.0, 0.0], 50.0, 100.0, Easing::Linear);
+        let start = color.value(50.0);
+        assert!(approx(start[0], 0.5) && approx(start[1], 0.0) && approx(start[2], 0.5));
+        assert_eq!(color.value(150.0), [0.0, 1.0, 0.0]);
+        assert_eq!(color.target(), [0.0, 1.0, 0.0]);
+    }
+}
@@ -1,9 +1,22 @@
-#![cfg(target_arch = "wasm32")]
-
 /// Get high-resolution time in milliseconds
+#[cfg(target_arch = "wasm32")]
 pub fn get_high_resolution_time() -> f64 {
     web_sys::window()
         .and_then(|w| w.performance())
         .map(|p| p.now())
         .unwrap_or(0.0)
-}
\ No newline at end of file
+}
+
+/// Wall clock time in milliseconds, as from `js_sys::Date::now`
+pub fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+    }
+}
@@ -0,0 +1,143 @@
+//! Vibrato detection over recent pitch samples
+//!
+//! The pitch is converted to cents around its mean and a linear trend is
+//! removed, so slow glides do not count as vibrato. The zero crossings of
+//! what remains give the period of the oscillation, and the largest deviation
+//! between two crossings gives its depth. Regularity compares the spread of
+//! the half periods and depths to their means.
+
+use crate::common::pitch_history::PitchSample;
+use crate::common::shared_types::Vibrato;
+
+/// Rates outside this range are tremor, trills or slow drift rather than vibrato
+const MIN_RATE_HZ: f32 = 3.0;
+const MAX_RATE_HZ: f32 = 9.0;
+/// Smaller oscillations are treated as pitch jitter
+const MIN_DEPTH_CENTS: f32 = 10.0;
+const MAX_DEPTH_CENTS: f32 = 200.0;
+/// At least this many crossings, i.e. one and a half cycles
+const MIN_ZERO_CROSSINGS: usize = 4;
+/// The samples must run without a longer break
+const MAX_SAMPLE_GAP_MS: f64 = 100.0;
+
+/// Vibrato in `samples`, oldest first, or `None` when the pitch is steady,
+/// interrupted or moves in a way that is not vibrato
+pub fn analyze_vibrato(samples: &[PitchSample]) -> Option<Vibrato> {
+    if samples.len() < 2 * MIN_ZERO_CROSSINGS
+        || samples.windows(2).any(|pair| pair[1].timestamp_ms - pair[0].timestamp_ms > MAX_SAMPLE_GAP_MS)
+    {
+        return None;
+    }
+
+    let times: Vec<f64> = samples.iter().map(|sample| (sample.timestamp_ms - samples[0].timestamp_ms) / 1000.0).collect();
+    let log_frequencies: Vec<f64> = samples.iter().map(|sample| (sample.frequency as f64).log2()).collect();
+    let (slope, intercept) = linear_fit(&times, &log_frequencies);
+    let cents: Vec<f64> = times.iter()
+        .zip(&log_frequencies)
+        .map(|(time, log_frequency)| 1200.0 * (log_frequency - (intercept + slope * time)))
+        .collect();
+
+    let crossings: Vec<(usize, f64)> = (1..cents.len())
+        .filter(|&i| (cents[i - 1] < 0.0) != (cents[i] < 0.0))
+        .map(|i| {
+            let fraction = cents[i - 1] / (cents[i - 1] - cents[i]);
+            (i, times[i - 1] + fraction * (times[i] - times[i - 1]))
+        })
+        .collect();
+    if crossings.len() < MIN_ZERO_CROSSINGS {
+        return None;
+    }
+
+    let half_periods: Vec<f64> = crossings.windows(2).map(|pair| pair[1].1 - pair[0].1).collect();
+    let depths: Vec<f64> = crossings.windows(2)
+        .map(|pair| cents[pair[0].0..pair[1].0].iter().fold(0.0f64, |peak, value| peak.max(value.abs())))
+        .collect();
+
+    let (mean_half_period, half_period_spread) = mean_and_spread(&half_periods);
+    let (depth, depth_spread) = mean_and_spread(&depths);
+    let rate_hz = (0.5 / mean_half_period) as f32;
+    let depth_cents = depth as f32;
+    if !(MIN_RATE_HZ..=MAX_RATE_HZ).contains(&rate_hz) || !(MIN_DEPTH_CENTS..=MAX_DEPTH_CENTS).contains(&depth_cents) {
+        return None;
+    }
+
+    let variation = 0.5 * (half_period_spread / mean_half_period + depth_spread / depth);
+    Some(Vibrato {
+        rate_hz,
+        depth_cents,
+        regularity: (1.0 - variation as f32).clamp(0.0, 1.0),
+        center_cents_offset: samples.iter().map(|sample| sample.cents_offset).sum::<f32>() / samples.len() as f32,
+    })
+}
+
+/// Least squares line through the points, as slope and intercept
+fn linear_fit(x: &[f64], y: &[f64]) -> (f64, f64) {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+    let covariance: f64 = x.iter().zip(y).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = x.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+    (slope, mean_y - slope * mean_x)
+}
+
+/// Mean and standard deviation
+fn mean_and_spread(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const FRAME_MS: f64 = 1000.0 / 60.0;
+
+    /// One second of frames whose pitch in cents is `cents(seconds)` around 440 Hz
+    fn frames(cents: impl Fn(f64) -> f64) -> Vec<PitchSample> {
+        (0..60)
+            .map(|frame| {
+                let timestamp_ms = frame as f64 * FRAME_MS;
+                let cents = cents(timestamp_ms / 1000.0);
+                PitchSample {
+                    timestamp_ms,
+                    frequency: (440.0 * 2f64.powf(cents / 1200.0)) as f32,
+                    clarity: 0.9,
+                    cents_offset: cents as f32,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_regular_vibrato() {
+        let vibrato = analyze_vibrato(&frames(|t| 5.0 + 50.0 * (2.0 * PI * 5.5 * t).sin())).expect("vibrato");
+        assert!((vibrato.rate_hz - 5.5).abs() < 0.2, "rate {}", vibrato.rate_hz);
+        assert!((vibrato.depth_cents - 50.0).abs() < 3.0, "depth {}", vibrato.depth_cents);
+        assert!(vibrato.regularity > 0.8, "regularity {}", vibrato.regularity);
+        assert!((vibrato.center_cents_offset - 5.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_vibrato_on_a_glide() {
+        let vibrato = analyze_vibrato(&frames(|t| 100.0 * t + 30.0 * (2.0 * PI * 6.0 * t).sin())).expect("vibrato");
+        assert!((vibrato.rate_hz - 6.0).abs() < 0.3, "rate {}", vibrato.rate_hz);
+    }
+
+    #[test]
+    fn test_no_vibrato() {
+        assert_eq!(analyze_vibrato(&frames(|_| 0.0)), None);
+        assert_eq!(analyze_vibrato(&frames(|t| 200.0 * t)), None);
+        // Jitter too shallow to be vibrato
+        assert_eq!(analyze_vibrato(&frames(|t| 3.0 * (2.0 * PI * 5.0 * t).sin())), None);
+        // A trill is too fast
+        assert_eq!(analyze_vibrato(&frames(|t| 100.0 * (2.0 * PI * 12.0 * t).sin())), None);
+
+        let mut interrupted = frames(|t| 50.0 * (2.0 * PI * 5.5 * t).sin());
+        interrupted.drain(20..30);
+        assert_eq!(analyze_vibrato(&interrupted), None);
+    }
+}
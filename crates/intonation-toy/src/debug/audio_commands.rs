@@ -0,0 +1,165 @@
+#![cfg(target_arch = "wasm32")]
+
+use egui_dev_console::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandRegistry, ConsoleCommandResult, ConsoleOutput, ParsedArgs};
+use crate::common::shared_types::{BatchSizeMode, PitchAlgorithm};
+use crate::debug::console_actions::queue_presenter_action;
+
+/// Register console commands that reconfigure audio analysis
+pub fn register_audio_commands(registry: &mut ConsoleCommandRegistry) {
+    registry.register(Box::new(PitchDetectorCommand));
+    registry.register(Box::new(AnalysisWindowCommand));
+    registry.register(Box::new(SpectrumBinsCommand));
+    registry.register(Box::new(BatchSizeCommand));
+}
+
+struct PitchDetectorCommand;
+
+impl ConsoleCommand for PitchDetectorCommand {
+    fn name(&self) -> &str {
+        "pitch-detector"
+    }
+
+    fn description(&self) -> &str {
+        "Select the pitch detection algorithm and thresholds"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        let names: Vec<&str> = PitchAlgorithm::ALL.iter().map(|algorithm| algorithm.name()).collect();
+        vec![
+            ArgSpec::choice("algorithm", "Detection algorithm", &names),
+            ArgSpec::float("power", "Minimum signal power").optional(),
+            ArgSpec::float("clarity", "Minimum clarity, 0-1").optional(),
+        ]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let Some(algorithm) = parsed.text("algorithm").and_then(PitchAlgorithm::from_name) else {
+            return ConsoleCommandResult::Output(ConsoleOutput::error("Unknown algorithm"));
+        };
+        let power_threshold = parsed.float("power").map_or(crate::app_config::POWER_THRESHOLD, |value| value as f32);
+        let clarity_threshold = parsed.float("clarity").map_or(crate::app_config::CLARITY_THRESHOLD, |value| value as f32);
+
+        queue_presenter_action(move |presenter| {
+            presenter.on_pitch_detection_configured(algorithm, power_threshold, clarity_threshold);
+        });
+
+        ConsoleCommandResult::Output(ConsoleOutput::success(format!(
+            "Pitch detection: {} (power {}, clarity {})",
+            algorithm.name(), power_threshold, clarity_threshold
+        )))
+    }
+}
+
+struct AnalysisWindowCommand;
+
+impl ConsoleCommand for AnalysisWindowCommand {
+    fn name(&self) -> &str {
+        "analysis-window"
+    }
+
+    fn description(&self) -> &str {
+        "Set the pitch analysis window and hop size in samples"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::int("window", "Window size, 1024-8192, multiple of 128"),
+            ArgSpec::int("hop", "Samples between analyses, defaults to the window size").optional(),
+        ]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let window_size = parsed.int("window").unwrap_or_default();
+        let hop_size = parsed.int("hop").unwrap_or(window_size);
+        let (Ok(window_size), Ok(hop_size)) = (usize::try_from(window_size), usize::try_from(hop_size)) else {
+            return ConsoleCommandResult::Output(ConsoleOutput::error("Sizes must be positive"));
+        };
+
+        queue_presenter_action(move |presenter| {
+            presenter.on_analysis_window_configured(window_size, hop_size);
+        });
+
+        ConsoleCommandResult::Output(ConsoleOutput::info(format!(
+            "Requested analysis window {} with hop {}; see 'engine status'",
+            window_size, hop_size
+        )))
+    }
+}
+
+struct SpectrumBinsCommand;
+
+impl ConsoleCommand for SpectrumBinsCommand {
+    fn name(&self) -> &str {
+        "spectrum-bins"
+    }
+
+    fn description(&self) -> &str {
+        "Set the number of bins in the magnitude spectrum"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::int("count", "Bin count, a power of two")]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let Ok(bin_count) = usize::try_from(parsed.int("count").unwrap_or_default()) else {
+            return ConsoleCommandResult::Output(ConsoleOutput::error("Bin count must be positive"));
+        };
+
+        queue_presenter_action(move |presenter| {
+            presenter.on_spectrum_configured(bin_count);
+        });
+
+        ConsoleCommandResult::Output(ConsoleOutput::info(format!("Requested {} spectrum bins", bin_count)))
+    }
+}
+
+struct BatchSizeCommand;
+
+impl ConsoleCommand for BatchSizeCommand {
+    fn name(&self) -> &str {
+        "batch-size"
+    }
+
+    fn description(&self) -> &str {
+        "Set the AudioWorklet batch size: 'hop', 'adaptive' or a sample count"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::text("size", "'hop', 'adaptive' or a multiple of 128")]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let mode = match parsed.text("size") {
+            Some("hop") => BatchSizeMode::Hop,
+            Some("adaptive") => BatchSizeMode::Adaptive,
+            Some(size) => match size.parse::<usize>() {
+                Ok(batch_size) => BatchSizeMode::Fixed(batch_size),
+                Err(_) => return ConsoleCommandResult::Output(ConsoleOutput::error(format!("Invalid batch size '{}'", size))),
+            },
+            None => return ConsoleCommandResult::Output(ConsoleOutput::error("Missing batch size")),
+        };
+
+        queue_presenter_action(move |presenter| {
+            presenter.on_batch_size_configured(mode);
+        });
+
+        ConsoleCommandResult::Output(ConsoleOutput::info(format!("Requested batch size {:?}; see 'engine status'", mode)))
+    }
+}
@@ -0,0 +1,24 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use crate::presentation::Presenter;
+
+type PresenterAction = Box<dyn FnOnce(&mut Presenter)>;
+
+thread_local! {
+    // Console commands cannot hold the presenter, so their calls are queued for the render loop
+    static PENDING_PRESENTER_ACTIONS: RefCell<Vec<PresenterAction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Queue a presenter call from a console command, applied on the next frame
+pub fn queue_presenter_action(action: impl FnOnce(&mut Presenter) + 'static) {
+    PENDING_PRESENTER_ACTIONS.with_borrow_mut(|actions| actions.push(Box::new(action)));
+}
+
+/// Apply queued console actions; called once per frame before user actions are collected
+pub fn apply_presenter_actions(presenter: &mut Presenter) {
+    let actions = PENDING_PRESENTER_ACTIONS.with_borrow_mut(std::mem::take);
+    for action in actions {
+        action(presenter);
+    }
+}
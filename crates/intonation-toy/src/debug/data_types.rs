@@ -12,7 +12,9 @@ pub struct PerformanceMetrics {
 pub struct VolumeLevelData {
     pub rms_amplitude: f32,
     pub peak_amplitude: f32,
-    pub fft_data: Option<Vec<f32>>,
+    pub a_weighted_rms_amplitude: f32,
+    pub short_term_lufs: f32,
+    pub fft_data: Option<crate::common::shared_types::Spectrum>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,7 +28,9 @@ impl From<crate::engine::audio::VolumeLevelData> for VolumeLevelData {
         Self { 
             rms_amplitude: data.rms_amplitude, 
             peak_amplitude: data.peak_amplitude,
-            fft_data: data.fft_data.clone(),
+            a_weighted_rms_amplitude: data.a_weighted_rms_amplitude,
+            short_term_lufs: data.short_term_lufs,
+            fft_data: None,
         }
     }
 }
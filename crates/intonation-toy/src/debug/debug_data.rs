@@ -12,6 +12,7 @@ pub struct DebugData {
     pub intonation_data: Option<IntonationData>,
     pub audio_errors: Vec<crate::common::shared_types::Error>,
     pub interval_semitones: Option<i32>,
+    pub interval_readout: Option<crate::common::shared_types::IntervalReadout>,
     pub tonal_center_note: Option<crate::common::shared_types::MidiNote>,
 }
 
@@ -28,6 +29,8 @@ impl DebugData {
             self.volume_level = Some(VolumeLevelData {
                 peak_amplitude: analysis.volume_level.peak_amplitude,
                 rms_amplitude: analysis.volume_level.rms_amplitude,
+                a_weighted_rms_amplitude: analysis.volume_level.a_weighted_rms_amplitude,
+                short_term_lufs: analysis.volume_level.short_term_lufs,
                 fft_data: analysis.fft_data.clone(),
             });
             
@@ -50,6 +53,7 @@ impl DebugData {
                 cents_offset: model.cents_offset,
             });
             self.interval_semitones = Some(model.interval_semitones);
+            self.interval_readout = model.interval_readout;
             self.tonal_center_note = Some(model.tonal_center_note);
         }
     }
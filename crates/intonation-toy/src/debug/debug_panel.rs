@@ -2,9 +2,10 @@
 
 use three_d::egui::{self, Color32, Vec2, Ui};
 use crate::debug::debug_data::DebugData;
-use crate::common::shared_types::{TuningSystem, MidiNote, increment_midi_note, decrement_midi_note};
+use crate::common::shared_types::{TuningSystem, MidiNote, PitchAlgorithm, SmoothingStrategy, ModelSnapshot, InputConditioning, MainsFrequency, ToleranceProfile, increment_midi_note, decrement_midi_note};
 use crate::common::theme::get_current_color_scheme;
 use crate::web::utils::{copy_to_clipboard, rgb_to_hex};
+use crate::presentation::ConfigureTestSignal;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -24,6 +25,14 @@ pub struct DebugPanel {
     test_signal_volume: f32,
     test_signal_midi_note: MidiNote,
     test_signal_nudge_percent: f32,
+    pitch_algorithm: PitchAlgorithm,
+    power_threshold: f32,
+    clarity_threshold: f32,
+    input_conditioning: InputConditioning,
+    smoothing: SmoothingStrategy,
+    snapshot_requested: bool,
+    snapshot_text: String,
+    snapshot_error: Option<String>,
 }
 
 impl DebugPanel {
@@ -38,6 +47,14 @@ impl DebugPanel {
             test_signal_volume: 15.0,
             test_signal_midi_note: crate::app_config::DEFAULT_TONAL_CENTER_NOTE,
             test_signal_nudge_percent: 0.0,
+            pitch_algorithm: PitchAlgorithm::default(),
+            power_threshold: crate::app_config::POWER_THRESHOLD,
+            clarity_threshold: crate::app_config::CLARITY_THRESHOLD,
+            input_conditioning: InputConditioning::default(),
+            smoothing: SmoothingStrategy::default(),
+            snapshot_requested: false,
+            snapshot_text: String::new(),
+            snapshot_error: None,
         }
     }
 
@@ -52,6 +69,11 @@ impl DebugPanel {
         self.debug_data.update_debug_data(performance_metrics, buffer_pool_stats);
     }
     
+    /// Whether the model snapshot should be copied to the clipboard; clears the request
+    pub fn take_snapshot_request(&mut self) -> bool {
+        std::mem::take(&mut self.snapshot_requested)
+    }
+
     /// Render the live data panel
     pub fn render(&mut self, gui_context: &egui::Context, model_data: &crate::common::shared_types::ModelUpdateResult) {
         let screen_rect = gui_context.screen_rect();
@@ -80,6 +102,35 @@ impl DebugPanel {
                 self.render_buffer_pool_stats_section(ui);
                 ui.separator();
                 
+                // Target Note Section (core data via interface)
+                self.render_target_note_section(ui, model_data);
+                ui.separator();
+                
+                
+                // Session Statistics Section (user actions)
+                self.render_session_statistics_section(ui, model_data);
+                ui.separator();
+
+                // Exercise Section (user actions)
+                self.render_exercise_section(ui, model_data);
+                ui.separator();
+
+                // Vibrato Section (core data via interface)
+                self.render_vibrato_section(ui, model_data);
+                ui.separator();
+
+                // Input Conditioning Section (user actions)
+                self.render_input_conditioning_section(ui);
+                ui.separator();
+
+                // Smoothing Section (user actions)
+                self.render_smoothing_section(ui);
+                ui.separator();
+
+                // Snapshot Section (user actions)
+                self.render_snapshot_section(ui);
+                ui.separator();
+                
                 // Volume Level Section (core data via interface)
                 self.render_volume_level_section(ui);
                 ui.separator();
@@ -105,6 +156,147 @@ impl DebugPanel {
     }
     
     
+    /// Render the target note and the detected pitch's offset from it
+    fn render_target_note_section(&mut self, ui: &mut Ui, model_data: &crate::common::shared_types::ModelUpdateResult) {
+        egui::CollapsingHeader::new("Target Note")
+            .default_open(false)
+            .show(ui, |ui| {
+                if let Some(target_note) = model_data.target_note {
+                    ui.label(format!(
+                        "Target {}: {}",
+                        crate::common::shared_types::midi_note_to_name(target_note),
+                        model_data.target_cents_offset.map_or("-".to_string(), |cents| format!("{:+.0} cents", cents))
+                    ));
+                }
+            });
+    }
+    
+    /// Render per-degree intonation statistics and the session score
+    fn render_session_statistics_section(&mut self, ui: &mut Ui, model_data: &crate::common::shared_types::ModelUpdateResult) {
+        egui::CollapsingHeader::new("Session Statistics")
+            .default_open(false)
+            .show(ui, |ui| {
+                let statistics = &model_data.session_statistics;
+                ui.label(statistics.score.map_or("Score: -".to_string(), |score| format!("Score: {:.0}%", score)));
+
+                egui::Grid::new("session_statistics_degrees").striped(true).show(ui, |ui| {
+                    for heading in ["Degree", "Time", "In tune", "Mean", "Std", "Attempts"] {
+                        ui.label(heading);
+                    }
+                    ui.end_row();
+                    for (semitone, degree) in statistics.degrees.iter().enumerate().filter(|(_, degree)| degree.attempts > 0) {
+                        ui.label(crate::common::music_theory::semitone_to_interval_name(semitone as i32));
+                        ui.label(format!("{:.1} s", degree.seconds));
+                        ui.label(format!("{:.0}%", 100.0 * degree.in_tune_seconds / degree.seconds.max(f32::EPSILON)));
+                        ui.label(format!("{:+.1} ¢", degree.mean_cents));
+                        ui.label(format!("{:.1} ¢", degree.std_cents));
+                        ui.label(degree.attempts.to_string());
+                        ui.end_row();
+                    }
+                });
+
+                let Ok(mut presenter) = self.presenter.try_borrow_mut() else {
+                    return;
+                };
+                let mut profile = model_data.tolerance_profile;
+                egui::ComboBox::from_label("Tolerance")
+                    .selected_text(profile.name())
+                    .show_ui(ui, |ui| {
+                        for option in ToleranceProfile::PRESETS {
+                            ui.selectable_value(&mut profile, option, option.name());
+                        }
+                    });
+                let mut tolerance_cents = profile.cents();
+                let tolerance_range = crate::app_config::MIN_IN_TUNE_TOLERANCE_CENTS..=crate::app_config::MAX_IN_TUNE_TOLERANCE_CENTS;
+                if ui.add(egui::Slider::new(&mut tolerance_cents, tolerance_range).text("In tune within").suffix(" ¢")).changed() {
+                    profile = ToleranceProfile::Custom(tolerance_cents);
+                }
+                if profile != model_data.tolerance_profile {
+                    presenter.on_tolerance_profile_configured(profile);
+                }
+                if ui.button("Reset").clicked() {
+                    presenter.on_session_statistics_reset();
+                }
+            });
+    }
+
+    /// Render the current target of the exercise and the results so far
+    fn render_exercise_section(&self, ui: &mut Ui, model_data: &crate::common::shared_types::ModelUpdateResult) {
+        egui::CollapsingHeader::new("Exercise")
+            .default_open(false)
+            .show(ui, |ui| {
+                if let Some(exercise) = &model_data.exercise {
+                    ui.label(format!("{}: {} of {} reached", exercise.kind.name(), exercise.results.len(), exercise.target_count));
+                    if let Some(target) = exercise.current_target {
+                        ui.label(format!("Sing: {}", model_data.note_namer().name(target)));
+                        ui.add(egui::ProgressBar::new(exercise.hold_progress).text("Hold"));
+                    }
+                    egui::Grid::new("exercise_results").striped(true).show(ui, |ui| {
+                        for heading in ["Target", "Reached after", "Mean"] {
+                            ui.label(heading);
+                        }
+                        ui.end_row();
+                        for result in &exercise.results {
+                            ui.label(model_data.note_namer().name(result.note));
+                            ui.label(format!("{:.1} s", result.seconds_to_reach));
+                            ui.label(format!("{:+.1} ¢", result.mean_cents));
+                            ui.end_row();
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Render the vibrato meter
+    fn render_vibrato_section(&self, ui: &mut Ui, model_data: &crate::common::shared_types::ModelUpdateResult) {
+        egui::CollapsingHeader::new("Vibrato")
+            .default_open(false)
+            .show(ui, |ui| {
+                let Some(vibrato) = model_data.vibrato else {
+                    ui.label("No vibrato");
+                    return;
+                };
+                ui.label(format!("Rate: {:.1} Hz", vibrato.rate_hz));
+                ui.label(format!("Depth: ±{:.0} ¢", vibrato.depth_cents));
+                ui.label(format!("Center: {:+.0} ¢", vibrato.center_cents_offset));
+                ui.add(egui::ProgressBar::new(vibrato.regularity).text(format!("Regularity {:.0}%", 100.0 * vibrato.regularity)));
+            });
+    }
+
+    /// Render noise gate and input filter controls
+    fn render_input_conditioning_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Input Conditioning")
+            .default_open(false)
+            .show(ui, |ui| {
+                let config = &mut self.input_conditioning;
+
+                ui.checkbox(&mut config.gate_enabled, "Noise gate");
+                ui.add_enabled_ui(config.gate_enabled, |ui| {
+                    ui.add(egui::Slider::new(&mut config.gate_threshold_db, -100.0..=0.0).text("Threshold (dB)"));
+                    ui.add(egui::Slider::new(&mut config.gate_attack_ms, 1.0..=100.0).text("Attack (ms)"));
+                    ui.add(egui::Slider::new(&mut config.gate_release_ms, 10.0..=1000.0).text("Release (ms)"));
+                });
+
+                ui.checkbox(&mut config.high_pass_enabled, "High-pass filter");
+                ui.add_enabled_ui(config.high_pass_enabled, |ui| {
+                    ui.add(egui::Slider::new(&mut config.high_pass_hz, 20.0..=200.0).text("Cutoff (Hz)"));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Hum notch:");
+                    ui.radio_value(&mut config.notch, None, "Off");
+                    ui.radio_value(&mut config.notch, Some(MainsFrequency::Hz50), "50 Hz");
+                    ui.radio_value(&mut config.notch, Some(MainsFrequency::Hz60), "60 Hz");
+                });
+
+                if ui.button("Apply").clicked()
+                    && let Ok(mut presenter) = self.presenter.try_borrow_mut()
+                {
+                    presenter.on_input_conditioning_configured(self.input_conditioning.clone());
+                }
+            });
+    }
+    
     /// Render performance metrics section (debug-specific data)
     fn render_performance_metrics_section(&mut self, ui: &mut Ui) {
         egui::CollapsingHeader::new("Performance Metrics")
@@ -138,24 +330,28 @@ impl DebugPanel {
                         let status_color = if stats.available_buffers > 0 {
                             Color32::GREEN
                         } else {
-                            Color32::RED
+                            Color32::YELLOW
                         };
-                        ui.colored_label(status_color, format!("{}/{} available", 
-                                                              stats.available_buffers, 
-                                                              stats.pool_size));
+                        ui.colored_label(status_color, format!("{}/{} available, {} in flight",
+                                                              stats.available_buffers,
+                                                              stats.total_buffers,
+                                                              stats.in_flight_buffers));
                     });
-                    
-                    // Pool allocation failures
+
+                    ui.label(format!("Allocations: {}", stats.allocation_count));
+                    ui.label(format!("Recycled: {}", stats.recycle_count));
+
+                    // Batches dropped because the pool was at its limit
                     ui.horizontal(|ui| {
-                        ui.label("Allocation Failures:");
-                        let fail_color = if stats.pool_exhausted_count == 0 {
+                        ui.label("Misses:");
+                        let miss_color = if stats.miss_count == 0 {
                             Color32::GREEN
-                        } else if stats.pool_exhausted_count < 10 {
+                        } else if stats.miss_count < 10 {
                             Color32::YELLOW
                         } else {
                             Color32::RED
                         };
-                        ui.colored_label(fail_color, format!("{}", stats.pool_exhausted_count));
+                        ui.colored_label(miss_color, format!("{}", stats.miss_count));
                     });
                     
                     // Additional stats
@@ -180,6 +376,11 @@ impl DebugPanel {
                     self.render_volume_meter(ui, "Peak Level", volume.peak_amplitude, bar_width, |v| {
                         if v >= 1.0 { Color32::RED } else if v > 0.7 { Color32::YELLOW } else { Color32::GREEN }
                     });
+                    ui.add_space(10.0);
+                    self.render_volume_meter(ui, "A-weighted RMS", volume.a_weighted_rms_amplitude, bar_width, |v| {
+                        if v >= 1.0 { Color32::RED } else if v > 0.5 { Color32::YELLOW } else { Color32::GREEN }
+                    });
+                    ui.label(format!("Short-term loudness: {:.1} LUFS", volume.short_term_lufs));
                 } else {
                     self.render_volume_meter(ui, "RMS Level", 0.0, bar_width, |_| Color32::GRAY);
                     ui.add_space(10.0);
@@ -227,7 +428,7 @@ impl DebugPanel {
     }
     
     /// Render pitch detection section (core data via interface)
-    fn render_pitch_detection_section(&self, ui: &mut Ui) {
+    fn render_pitch_detection_section(&mut self, ui: &mut Ui) {
         egui::CollapsingHeader::new("Pitch Detection")
             .default_open(true)
             .show(ui, |ui| {
@@ -237,9 +438,89 @@ impl DebugPanel {
                 } else {
                     ui.label("Frequency: -- Hz");
                 }
+
+                egui::ComboBox::from_label("Algorithm")
+                    .selected_text(self.pitch_algorithm.name())
+                    .show_ui(ui, |ui| {
+                        for algorithm in PitchAlgorithm::ALL {
+                            ui.selectable_value(&mut self.pitch_algorithm, algorithm, algorithm.name());
+                        }
+                    });
+                ui.add(egui::Slider::new(&mut self.power_threshold, 0.01..=2.0).text("Power threshold"));
+                ui.add(egui::Slider::new(&mut self.clarity_threshold, 0.0..=1.0).text("Clarity threshold"));
+
+                if ui.button("Apply").clicked()
+                    && let Ok(mut presenter) = self.presenter.try_borrow_mut()
+                {
+                    presenter.on_pitch_detection_configured(self.pitch_algorithm, self.power_threshold, self.clarity_threshold);
+                }
             });
     }
     
+    /// Render pitch smoothing section (user actions)
+    fn render_smoothing_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Smoothing")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::ComboBox::from_label("Strategy")
+                    .selected_text(self.smoothing.name())
+                    .show_ui(ui, |ui| {
+                        for strategy in SmoothingStrategy::DEFAULTS {
+                            if ui.selectable_label(self.smoothing.name() == strategy.name(), strategy.name()).clicked() {
+                                self.smoothing = strategy;
+                            }
+                        }
+                    });
+
+                match &mut self.smoothing {
+                    SmoothingStrategy::Ema { alpha } => {
+                        ui.add(egui::Slider::new(alpha, 0.01..=1.0).text("Smoothing factor"));
+                    }
+                    SmoothingStrategy::Median { window } => {
+                        ui.add(egui::Slider::new(window, 1..=crate::app_config::MAX_MEDIAN_SMOOTHING_WINDOW).text("Window"));
+                    }
+                    SmoothingStrategy::OneEuro { min_cutoff_hz, beta } => {
+                        ui.add(egui::Slider::new(min_cutoff_hz, 0.1..=10.0).logarithmic(true).suffix(" Hz").text("Min cutoff"));
+                        ui.add(egui::Slider::new(beta, 0.0..=0.1).text("Beta"));
+                    }
+                    SmoothingStrategy::AdaptiveEma | SmoothingStrategy::None => {}
+                }
+
+                if ui.button("Apply").clicked()
+                    && let Ok(mut presenter) = self.presenter.try_borrow_mut()
+                {
+                    presenter.on_smoothing_configured(self.smoothing);
+                }
+            });
+    }
+
+    /// Render model snapshot copy and restore, for bug reports
+    fn render_snapshot_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("Snapshot")
+            .default_open(false)
+            .show(ui, |ui| {
+                if ui.button("Copy to clipboard").clicked() {
+                    self.snapshot_requested = true;
+                }
+
+                ui.add(egui::TextEdit::multiline(&mut self.snapshot_text).code_editor().desired_rows(4).hint_text("Paste a snapshot"));
+                if ui.button("Restore").clicked() {
+                    match serde_json::from_str::<ModelSnapshot>(&self.snapshot_text) {
+                        Ok(snapshot) => {
+                            self.snapshot_error = None;
+                            if let Ok(mut presenter) = self.presenter.try_borrow_mut() {
+                                presenter.on_snapshot_restore(snapshot);
+                            }
+                        }
+                        Err(error) => self.snapshot_error = Some(error.to_string()),
+                    }
+                }
+                if let Some(error) = &self.snapshot_error {
+                    ui.colored_label(Color32::RED, error);
+                }
+            });
+    }
+
     /// Render accuracy section (core data via interface)
     fn render_accuracy_section(&self, ui: &mut Ui) {
         egui::CollapsingHeader::new("Intonation")
@@ -288,6 +569,7 @@ impl DebugPanel {
                             ui.label("--");
                         }
                     });
+                    ui.label(self.debug_data.interval_readout.map_or("Against drone: --".to_string(), |readout| format!("Against drone: {}", readout)));
                 } else {
                     ui.label("Closest Note: --");
                     ui.label("Cents Offset: --");
@@ -344,8 +626,9 @@ impl DebugPanel {
                     // Display current frequency with error handling
                     match self.calculate_midi_note_frequency_safe(
                         self.test_signal_midi_note, 
-                        model_data.tonal_center_note, 
-                        model_data.tuning_system
+                        model_data.tonal_center_note,
+                        model_data.concert_pitch_hz,
+                        &model_data.tuning_system
                     ) {
                         Ok(frequency) => {
                             ui.label(format!("({:.1} Hz)", frequency));
@@ -399,7 +682,8 @@ impl DebugPanel {
                         self.test_signal_midi_note,
                         self.test_signal_nudge_percent,
                         model_data.tonal_center_note,
-                        model_data.tuning_system
+                        model_data.concert_pitch_hz,
+                        &model_data.tuning_system
                     ) {
                         Ok((base_freq, final_freq)) => {
                             ui.label(format!("({:.1} Hz → {:.1} Hz)", base_freq, final_freq));
@@ -442,45 +726,48 @@ impl DebugPanel {
                 self.test_signal_midi_note,
                 self.test_signal_nudge_percent,
                 model_data.tonal_center_note,
-                model_data.tuning_system
+                model_data.concert_pitch_hz,
+                &model_data.tuning_system
             ) {
                 Ok((_, final_frequency)) => {
                     // Ensure frequency is within audio range
                     let clamped_frequency = final_frequency.clamp(20.0, 20_000.0);
                     
-                    presenter.on_test_signal_configured(
-                        self.test_signal_enabled,
-                        clamped_frequency,
-                        self.test_signal_volume,
-                    );
+                    presenter.on_test_signal_configured(ConfigureTestSignal {
+                        enabled: self.test_signal_enabled,
+                        frequency: clamped_frequency,
+                        volume: self.test_signal_volume,
+                        ..Default::default()
+                    });
                 }
                 Err(e) => {
                     // Log error in debug mode
                     crate::common::warn_log!("[DEBUG_PANEL] Error calculating test signal frequency: {}", e);
                     
                     // Disable test signal on error
-                    presenter.on_test_signal_configured(
-                        false,
-                        440.0, // Default to A4
-                        self.test_signal_volume,
-                    );
+                    presenter.on_test_signal_configured(ConfigureTestSignal {
+                        enabled: false,
+                        frequency: model_data.concert_pitch_hz, // A4
+                        volume: self.test_signal_volume,
+                        ..Default::default()
+                    });
                 }
             }
         }
     }
     
-    fn midi_note_to_frequency_with_tuning(&self, midi_note: MidiNote, tonal_center_note: MidiNote, tuning_system: TuningSystem) -> f32 {
-        let tonal_center_frequency = crate::common::music_theory::midi_note_to_standard_frequency(tonal_center_note);
+    fn midi_note_to_frequency_with_tuning(&self, midi_note: MidiNote, tonal_center_note: MidiNote, concert_pitch_hz: f32, tuning_system: &TuningSystem) -> f32 {
+        let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(tonal_center_note, concert_pitch_hz);
         let interval_semitones = (midi_note as i32) - (tonal_center_note as i32);
         crate::common::music_theory::interval_frequency(tuning_system, tonal_center_frequency, interval_semitones)
     }
     
-    fn calculate_midi_note_frequency_safe(&self, midi_note: MidiNote, tonal_center_note: MidiNote, tuning_system: TuningSystem) -> Result<f32, &'static str> {
+    fn calculate_midi_note_frequency_safe(&self, midi_note: MidiNote, tonal_center_note: MidiNote, concert_pitch_hz: f32, tuning_system: &TuningSystem) -> Result<f32, &'static str> {
         if midi_note > 127 || tonal_center_note > 127 {
             return Err("Invalid MIDI note");
         }
         
-        let frequency = self.midi_note_to_frequency_with_tuning(midi_note, tonal_center_note, tuning_system);
+        let frequency = self.midi_note_to_frequency_with_tuning(midi_note, tonal_center_note, concert_pitch_hz, tuning_system);
         if frequency <= 0.0 || frequency > 20_000.0 {
             return Err("Frequency out of range");
         }
@@ -488,8 +775,8 @@ impl DebugPanel {
         Ok(frequency)
     }
     
-    fn calculate_final_frequency_safe(&self, midi_note: MidiNote, nudge_percent: f32, tonal_center: MidiNote, tuning_system: TuningSystem) -> Result<(f32, f32), &'static str> {
-        let base_frequency = self.calculate_midi_note_frequency_safe(midi_note, tonal_center, tuning_system)?;
+    fn calculate_final_frequency_safe(&self, midi_note: MidiNote, nudge_percent: f32, tonal_center: MidiNote, concert_pitch_hz: f32, tuning_system: &TuningSystem) -> Result<(f32, f32), &'static str> {
+        let base_frequency = self.calculate_midi_note_frequency_safe(midi_note, tonal_center, concert_pitch_hz, tuning_system)?;
         
         if !(-50.0..=50.0).contains(&nudge_percent) {
             return Err("Nudge percentage out of range");
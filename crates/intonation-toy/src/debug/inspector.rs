@@ -0,0 +1,87 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use egui_dev_console::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandRegistry, ConsoleCommandResult, ConsoleOutput, ConsoleOutputSender, ParsedArgs, output_stream};
+
+/// State summary a layer reports to the console inspector commands
+pub trait Inspect {
+    /// Name/value pairs describing the current state
+    fn inspect(&self) -> Vec<(&'static str, String)>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Engine,
+    Model,
+    Presenter,
+}
+
+thread_local! {
+    // Requests are answered from the render loop, which owns the layers
+    static PENDING_REQUESTS: RefCell<Vec<(Layer, ConsoleOutputSender)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Register `engine status`, `model dump` and `presenter scene`
+pub fn register_inspector_commands(registry: &mut ConsoleCommandRegistry) {
+    registry.register(Box::new(InspectCommand { name: "engine", action: "status", layer: Layer::Engine }));
+    registry.register(Box::new(InspectCommand { name: "model", action: "dump", layer: Layer::Model }));
+    registry.register(Box::new(InspectCommand { name: "presenter", action: "scene", layer: Layer::Presenter }));
+}
+
+/// Answer pending inspector requests; called once per frame
+pub fn serve_requests(engine: &dyn Inspect, model: &dyn Inspect, presenter: Option<&dyn Inspect>) {
+    let requests = PENDING_REQUESTS.with_borrow_mut(std::mem::take);
+
+    for (layer, output) in requests {
+        let target = match layer {
+            Layer::Engine => Some(engine),
+            Layer::Model => Some(model),
+            Layer::Presenter => presenter,
+        };
+
+        let Some(target) = target else {
+            output.send(ConsoleOutput::error(format!("{:?} is busy, try again", layer)));
+            continue;
+        };
+
+        let lines: Vec<String> = target.inspect()
+            .into_iter()
+            .map(|(name, value)| format!("  {:<24} {}", name, value))
+            .collect();
+        output.send(ConsoleOutput::info(format!("{:?}:\n{}", layer, lines.join("\n"))));
+    }
+}
+
+struct InspectCommand {
+    name: &'static str,
+    action: &'static str,
+    layer: Layer,
+}
+
+impl ConsoleCommand for InspectCommand {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn description(&self) -> &str {
+        match self.layer {
+            Layer::Engine => "Show audio engine state (engine status)",
+            Layer::Model => "Show data model state (model dump)",
+            Layer::Presenter => "Show presenter and scene state (presenter scene)",
+        }
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::choice("action", "What to show", &[self.action])]
+    }
+
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let (sender, receiver) = output_stream();
+        PENDING_REQUESTS.with_borrow_mut(|requests| requests.push((self.layer, sender)));
+        ConsoleCommandResult::Stream(receiver)
+    }
+}
@@ -6,3 +6,9 @@ pub mod debug_panel;
 pub mod debug_data;
 #[cfg(debug_assertions)]
 pub mod data_types;
+pub mod model_watches;
+pub mod inspector;
+pub mod console_actions;
+pub mod audio_commands;
+#[cfg(debug_assertions)]
+pub mod test_signal_commands;
@@ -0,0 +1,41 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use egui_dev_console::DevConsole;
+use crate::common::shared_types::{ModelUpdateResult, Pitch, midi_note_to_name};
+
+/// Latest model result, refreshed every frame for the console's `watch` command
+pub type WatchedModelData = Rc<RefCell<Option<ModelUpdateResult>>>;
+
+/// Register `model.*` watches that read from `model_data`
+pub fn register_model_watches(dev_console: &mut DevConsole, model_data: &WatchedModelData) {
+    register(dev_console, model_data, "model.pitch", |data| match data.pitch {
+        Pitch::Detected(frequency) => format!("{:.2} Hz", frequency),
+        Pitch::NotDetected => "not detected".to_string(),
+    });
+    register(dev_console, model_data, "model.volume", |data| {
+        format!("peak {:.3}, rms {:.3}", data.volume.peak_amplitude, data.volume.rms_amplitude)
+    });
+    register(dev_console, model_data, "model.is_peaking", |data| data.is_peaking.to_string());
+    register(dev_console, model_data, "model.closest_note", |data| {
+        data.closest_midi_note.map_or("-".to_string(), midi_note_to_name)
+    });
+    register(dev_console, model_data, "model.cents_offset", |data| format!("{:+.1}", data.cents_offset));
+    register(dev_console, model_data, "model.interval_semitones", |data| data.interval_semitones.to_string());
+    register(dev_console, model_data, "model.root_note", |data| midi_note_to_name(data.tonal_center_note));
+    register(dev_console, model_data, "model.tuning_system", |data| format!("{:?}", data.tuning_system));
+    register(dev_console, model_data, "model.scale", |data| format!("{:?}", data.scale));
+}
+
+fn register(
+    dev_console: &mut DevConsole,
+    model_data: &WatchedModelData,
+    name: &str,
+    format_value: impl Fn(&ModelUpdateResult) -> String + 'static,
+) {
+    let model_data = Rc::clone(model_data);
+    dev_console.register_watch(name, move || {
+        model_data.borrow().as_ref().map_or("-".to_string(), &format_value)
+    });
+}
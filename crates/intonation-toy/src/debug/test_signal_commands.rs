@@ -0,0 +1,156 @@
+#![cfg(target_arch = "wasm32")]
+
+use egui_dev_console::{ArgSpec, CommandCategory, ConsoleCommand, ConsoleCommandRegistry, ConsoleCommandResult, ConsoleOutput, ParsedArgs};
+use crate::debug::console_actions::queue_presenter_action;
+use crate::presentation::{ConfigureTestSignal, FrequencySweep, SweepShape, Vibrato};
+
+/// Test signal volume in percent when a command does not give one
+const TEST_SIGNAL_VOLUME: f32 = 15.0;
+
+/// Register console commands that play a test signal in place of the microphone
+pub fn register_test_signal_commands(registry: &mut ConsoleCommandRegistry) {
+    registry.register(Box::new(TestToneCommand));
+    registry.register(Box::new(TestSweepCommand));
+    registry.register(Box::new(TestOffCommand));
+}
+
+/// Check that a frequency is audible and positive
+fn audible_frequency(name: &str, value: Option<f64>) -> Result<f32, String> {
+    match value {
+        Some(hz) if (20.0..=20_000.0).contains(&hz) => Ok(hz as f32),
+        Some(hz) => Err(format!("<{}> must be between 20 and 20000 Hz, got {}", name, hz)),
+        None => Err(format!("Missing argument: <{}>", name)),
+    }
+}
+
+struct TestToneCommand;
+
+impl ConsoleCommand for TestToneCommand {
+    fn name(&self) -> &str {
+        "test-tone"
+    }
+
+    fn description(&self) -> &str {
+        "Play a test tone, optionally with vibrato and noise; see 'engine status' for detection accuracy"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Privileged
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::float("frequency", "Frequency in Hz"),
+            ArgSpec::float("vibrato-rate", "Vibrato rate in Hz, 0 for none").optional(),
+            ArgSpec::float("vibrato-depth", "Vibrato depth in cents").optional(),
+            ArgSpec::float("snr", "Signal-to-noise ratio in dB").optional(),
+        ]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let frequency = match audible_frequency("frequency", parsed.float("frequency")) {
+            Ok(frequency) => frequency,
+            Err(e) => return ConsoleCommandResult::Output(ConsoleOutput::error(e)),
+        };
+        let vibrato = match (parsed.float("vibrato-rate"), parsed.float("vibrato-depth")) {
+            (Some(rate), Some(depth)) if rate > 0.0 => Some(Vibrato { rate_hz: rate as f32, depth_cents: depth as f32 }),
+            (Some(rate), None) if rate > 0.0 => return ConsoleCommandResult::Output(ConsoleOutput::error("Missing argument: <vibrato-depth>")),
+            _ => None,
+        };
+        let config = ConfigureTestSignal {
+            enabled: true,
+            frequency,
+            volume: TEST_SIGNAL_VOLUME,
+            sweep: None,
+            vibrato,
+            snr_db: parsed.float("snr").map(|snr| snr as f32),
+        };
+
+        let message = format!("Test tone: {:?}", config);
+        queue_presenter_action(move |presenter| {
+            presenter.on_test_signal_configured(config);
+        });
+        ConsoleCommandResult::Output(ConsoleOutput::success(message))
+    }
+}
+
+struct TestSweepCommand;
+
+impl ConsoleCommand for TestSweepCommand {
+    fn name(&self) -> &str {
+        "test-sweep"
+    }
+
+    fn description(&self) -> &str {
+        "Sweep the test signal between two frequencies; see 'engine status' for detection accuracy"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Privileged
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![
+            ArgSpec::choice("shape", "Sweep shape", &["linear", "log"]),
+            ArgSpec::float("start", "Start frequency in Hz"),
+            ArgSpec::float("end", "End frequency in Hz"),
+            ArgSpec::float("seconds", "Sweep duration"),
+            ArgSpec::float("snr", "Signal-to-noise ratio in dB").optional(),
+        ]
+    }
+
+    fn execute(&self, parsed: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let shape = match parsed.text("shape") {
+            Some("log") => SweepShape::Logarithmic,
+            _ => SweepShape::Linear,
+        };
+        let (start_frequency, end_frequency) = match (
+            audible_frequency("start", parsed.float("start")),
+            audible_frequency("end", parsed.float("end")),
+        ) {
+            (Ok(start), Ok(end)) => (start, end),
+            (Err(e), _) | (_, Err(e)) => return ConsoleCommandResult::Output(ConsoleOutput::error(e)),
+        };
+        let duration_seconds = match parsed.float("seconds") {
+            Some(seconds) if seconds > 0.0 => seconds as f32,
+            _ => return ConsoleCommandResult::Output(ConsoleOutput::error("<seconds> must be positive")),
+        };
+        let config = ConfigureTestSignal {
+            enabled: true,
+            frequency: end_frequency,
+            volume: TEST_SIGNAL_VOLUME,
+            sweep: Some(FrequencySweep { shape, start_frequency, end_frequency, duration_seconds }),
+            vibrato: None,
+            snr_db: parsed.float("snr").map(|snr| snr as f32),
+        };
+
+        let message = format!("Test sweep: {:?}", config);
+        queue_presenter_action(move |presenter| {
+            presenter.on_test_signal_configured(config);
+        });
+        ConsoleCommandResult::Output(ConsoleOutput::success(message))
+    }
+}
+
+struct TestOffCommand;
+
+impl ConsoleCommand for TestOffCommand {
+    fn name(&self) -> &str {
+        "test-off"
+    }
+
+    fn description(&self) -> &str {
+        "Stop the test signal and listen to the microphone again"
+    }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Privileged
+    }
+
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        queue_presenter_action(|presenter| {
+            presenter.on_test_signal_configured(ConfigureTestSignal::default());
+        });
+        ConsoleCommandResult::Output(ConsoleOutput::success("Test signal off"))
+    }
+}
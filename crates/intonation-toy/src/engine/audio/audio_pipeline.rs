@@ -1,7 +1,23 @@
 #![cfg(target_arch = "wasm32")]
 
-use web_sys::{AudioContext, AudioWorkletNode, AudioWorkletNodeOptions, OscillatorType};
+use web_sys::{AudioContext, AudioWorkletNode, AudioWorkletNodeOptions, OscillatorNode, OscillatorType};
 use crate::{common::dev_log, engine::audio::AudioSignalPath};
+use crate::engine::audio::signal_path::CaptureNode;
+use crate::common::shared_types::{ReferenceToneSettings, ToneTimbre};
+use crate::common::metronome::MetronomeTick;
+
+/// Harmonic amplitudes of the organ timbre, starting with the DC offset
+const ORGAN_HARMONICS: [f32; 9] = [
+    0.0,   // DC offset
+    1.0,   // fundamental
+    0.85,  // 2nd
+    0.55,  // 3rd
+    0.40,  // 4th
+    0.25,  // 5th
+    0.18,  // 6th
+    0.12,  // 7th
+    0.08   // 8th
+];
 
 
 
@@ -19,75 +35,74 @@ pub enum SignalPathMode {
 pub struct NewAudioPipeline {
     pub signal_path: AudioSignalPath,
     audio_context: AudioContext,
+    media_stream: web_sys::MediaStream,
+    reference_tone: ReferenceToneSettings,
+    tonal_center_volume: f32,
+    /// AudioContext time at which the last reference tone has faded out
+    reference_tone_end: f64,
+    /// AudioContext time less the page time, in seconds, taken when the
+    /// metronome starts over and kept until then so its clicks stay evenly spaced
+    metronome_clock_offset: f64,
+    #[cfg(debug_assertions)]
+    test_signal: Option<super::test_signal::ActiveTestSignal>,
 }
 
 impl NewAudioPipeline {
     /// Create a new audio pipeline with simplified signal path
     /// 
     /// Creates all necessary audio nodes and connects them through the AudioSignalPath.
-    /// The worklet module must already be loaded in the AudioContext before calling this method.
+    /// With `use_worklet`, the worklet module must already be loaded in the AudioContext
+    /// before calling this method; without, input is captured by a ScriptProcessorNode.
     /// 
     /// # Parameters
     /// 
     /// * `audio_context` - Reference to the AudioContext with worklet module loaded
     /// * `media_stream` - MediaStream from microphone input
+    /// * `use_worklet` - Whether to capture input with the AudioWorklet
     /// 
     /// # Returns
     /// 
     /// Returns `Result<Self, String>` where:
     /// - On success: NewAudioPipeline ready for audio processing
     /// - On error: String describing what went wrong
-    pub fn new(audio_context: &AudioContext, media_stream: &web_sys::MediaStream) -> Result<Self, String> {
+    pub fn new(audio_context: &AudioContext, media_stream: &web_sys::MediaStream, use_worklet: bool) -> Result<Self, String> {
         let input_node = audio_context.create_media_stream_source(media_stream)
             .map_err(|e| format!("Failed to create media stream source: {:?}", e))?;
 
-        let worklet_node = NewAudioPipeline::create_worklet_node(audio_context)?;
+        let capture_node = NewAudioPipeline::create_capture_node(audio_context, use_worklet)?;
 
-        let signal_path = AudioSignalPath::new(audio_context.clone(), input_node, worklet_node);
+        let signal_path = AudioSignalPath::new(audio_context.clone(), input_node, capture_node);
 
         // Configure analyser with FFT size of 128
         signal_path.analyser.set_fft_size(128);
         signal_path.analyser.set_smoothing_time_constant(0.0);
+        signal_path.spectrum_analyser.set_fft_size(crate::app_config::SPECTRUM_FFT_SIZE);
         
-        {
-            // Configure tonal center oscillator with custom waveform
-            let n = 16;
-            let mut real = vec![0.0f32; n];
-            let mut imag = vec![0.0f32; n];
-            
-            let amps: [f32; 9] = [
-                0.0,   // DC offset
-                1.0,   // fundamental
-                0.85,  // 2nd
-                0.55,  // 3rd
-                0.40,  // 4th
-                0.25,  // 5th
-                0.18,  // 6th
-                0.12,  // 7th
-                0.08   // 8th
-            ];
-
-            for (i, &amp) in amps.iter().enumerate() {
-                real[i] = amp;
-            }
-
-            let periodic_wave = audio_context.create_periodic_wave(&mut real, &mut imag)
-                .map_err(|_| "Failed to create periodic wave".to_string())?;
-            
-            signal_path.tonal_center_osc.set_periodic_wave(&periodic_wave);
-            signal_path.tonal_center_osc.frequency().set_value(440.0); // A4 default
-            signal_path.tonal_center_gain.gain().set_value(0.0); // Start muted
-        }
+        // Configure tonal center and reference tone oscillators; the drone keeps its organ sound
+        apply_timbre(audio_context, &signal_path.tonal_center_osc, ToneTimbre::Organ)?;
+        apply_timbre(audio_context, &signal_path.reference_tone_osc, ReferenceToneSettings::default().timbre)?;
+        signal_path.tonal_center_osc.frequency().set_value(crate::app_config::DEFAULT_CONCERT_PITCH_HZ);
+        signal_path.tonal_center_gain.gain().set_value(0.0); // Start muted
+        signal_path.reference_tone_envelope.gain().set_value(0.0);
+        signal_path.metronome_osc.set_type(OscillatorType::Square);
+        signal_path.metronome_envelope.gain().set_value(0.0);
         
         // Configure test signal oscillator
         signal_path.test_signal_osc.set_type(OscillatorType::Sine);
-        signal_path.test_signal_osc.frequency().set_value(440.0); // A4 default
+        signal_path.test_signal_osc.frequency().set_value(crate::app_config::DEFAULT_CONCERT_PITCH_HZ);
         
         dev_log!("✓ NewAudioPipeline nodes configured");
 
         let mut pipeline = Self {
             signal_path,
             audio_context: audio_context.clone(),
+            media_stream: media_stream.clone(),
+            reference_tone: ReferenceToneSettings::default(),
+            tonal_center_volume: 0.0,
+            reference_tone_end: 0.0,
+            metronome_clock_offset: 0.0,
+            #[cfg(debug_assertions)]
+            test_signal: None,
         };
 
         pipeline.set_signal_path_mode(SignalPathMode::Off);
@@ -96,6 +111,68 @@ impl NewAudioPipeline {
         Ok(pipeline)
     }
 
+    /// Feed the pipeline from a different MediaStream
+    ///
+    /// A new source node is connected in place of the current one, whose
+    /// stream is stopped; the rest of the graph is left untouched.
+    pub fn replace_user_input(&mut self, media_stream: web_sys::MediaStream) -> Result<(), String> {
+        let input_node = self.audio_context.create_media_stream_source(&media_stream)
+            .map_err(|e| format!("Failed to create media stream source: {:?}", e))?;
+        input_node.connect_with_audio_node(&self.signal_path.user_input_mute)
+            .map_err(|e| format!("Failed to connect media stream source: {:?}", e))?;
+
+        let previous_input = std::mem::replace(&mut self.signal_path.user_input, input_node);
+        let _ = previous_input.disconnect();
+        let previous_stream = std::mem::replace(&mut self.media_stream, media_stream);
+        crate::engine::audio::input_devices::stop_stream(&previous_stream);
+        Ok(())
+    }
+
+    /// Whether the input stream is still delivering audio
+    pub fn is_input_live(&self) -> bool {
+        crate::engine::audio::input_devices::is_stream_live(&self.media_stream)
+    }
+
+    /// Whether the input stream is delivering audio the user has not muted
+    pub fn is_input_listening(&self) -> bool {
+        crate::engine::audio::input_devices::is_stream_listening(&self.media_stream)
+    }
+
+    /// Put a fresh capture node of the same kind in place of the current one
+    ///
+    /// The old worklet's port is closed so a processor that is still alive
+    /// stops reaching the main thread. Returns the new node.
+    pub fn replace_capture_node(&mut self) -> Result<CaptureNode, String> {
+        let use_worklet = matches!(self.signal_path.capture, CaptureNode::Worklet(_));
+        let capture_node = NewAudioPipeline::create_capture_node(&self.audio_context, use_worklet)?;
+        self.signal_path.analyser.connect_with_audio_node(capture_node.node())
+            .map_err(|e| format!("Failed to connect capture node: {:?}", e))?;
+        if let CaptureNode::ScriptProcessor(script_processor) = &capture_node {
+            script_processor.connect_with_audio_node(&self.audio_context.destination())
+                .map_err(|e| format!("Failed to connect script processor: {:?}", e))?;
+        }
+
+        let previous = std::mem::replace(&mut self.signal_path.capture, capture_node.clone());
+        let _ = self.signal_path.analyser.disconnect_with_audio_node(previous.node());
+        match previous {
+            CaptureNode::Worklet(worklet) => {
+                if let Ok(port) = worklet.port() {
+                    port.set_onmessage(None);
+                    port.close();
+                }
+            }
+            CaptureNode::ScriptProcessor(script_processor) => {
+                script_processor.set_onaudioprocess(None);
+                let _ = script_processor.disconnect();
+            }
+        }
+        Ok(capture_node)
+    }
+
+    pub fn set_input_gain(&self, gain: f32) {
+        self.signal_path.input_gain.gain().set_value(gain);
+    }
+
     /// Start the audio pipeline
     /// 
     /// Starts the oscillators and sets the initial signal path mode.
@@ -106,6 +183,10 @@ impl NewAudioPipeline {
             .map_err(|_| "Failed to start tonal center oscillator".to_string())?;
         self.signal_path.test_signal_osc.start()
             .map_err(|_| "Failed to start test signal oscillator".to_string())?;
+        self.signal_path.reference_tone_osc.start()
+            .map_err(|_| "Failed to start reference tone oscillator".to_string())?;
+        self.signal_path.metronome_osc.start()
+            .map_err(|_| "Failed to start metronome oscillator".to_string())?;
         
         // Set initial mode to tonal center mode
         self.set_signal_path_mode(SignalPathMode::TonalCenterMode);
@@ -141,6 +222,12 @@ impl NewAudioPipeline {
     pub fn update_tonal_center_config(&mut self, config: super::audio_pipeline_configs::TonalCenterConfig) {
         self.signal_path.tonal_center_osc.frequency().set_value(config.frequency);
         self.ramp_tonal_center_gain(config.volume);
+        self.tonal_center_volume = config.volume;
+    }
+
+    /// Whether the drone or a reference tone is sounding
+    pub fn is_audible(&self) -> bool {
+        self.tonal_center_volume > 0.0 || self.audio_context.current_time() < self.reference_tone_end
     }
 
     fn ramp_tonal_center_gain(&self, target: f32) {
@@ -149,6 +236,74 @@ impl NewAudioPipeline {
         }
     }
 
+    /// Set the timbre, envelope and volume of reference tones played afterwards
+    pub fn configure_reference_tone(&mut self, settings: ReferenceToneSettings) -> Result<(), String> {
+        apply_timbre(&self.audio_context, &self.signal_path.reference_tone_osc, settings.timbre)?;
+        self.reference_tone = settings;
+        Ok(())
+    }
+
+    /// Play a reference tone held for `duration_ms` before its release
+    ///
+    /// A tone that is still sounding is cut short and replaced.
+    pub fn play_reference_tone(&mut self, frequency: f32, duration_ms: f32) -> Result<(), String> {
+        let envelope = self.reference_tone.envelope;
+        let peak = self.reference_tone.volume;
+        let sustain = peak * envelope.sustain_level;
+
+        let now = self.audio_context.current_time();
+        let attack_end = now + envelope.attack_ms as f64 / 1000.0;
+        let decay_end = attack_end + envelope.decay_ms as f64 / 1000.0;
+        let release_start = decay_end.max(now + duration_ms as f64 / 1000.0);
+        let release_end = release_start + envelope.release_ms as f64 / 1000.0;
+
+        let schedule = || -> Result<(), wasm_bindgen::JsValue> {
+            self.signal_path.reference_tone_osc.frequency().set_value_at_time(frequency, now)?;
+
+            let gain = self.signal_path.reference_tone_envelope.gain();
+            gain.cancel_scheduled_values(now)?;
+            gain.set_value_at_time(gain.value(), now)?;
+            gain.linear_ramp_to_value_at_time(peak, attack_end)?;
+            gain.linear_ramp_to_value_at_time(sustain, decay_end)?;
+            gain.set_value_at_time(sustain, release_start)?;
+            gain.linear_ramp_to_value_at_time(0.0, release_end)?;
+            Ok(())
+        };
+        schedule().map_err(|e| format!("Failed to schedule reference tone: {:?}", e))?;
+        self.reference_tone_end = release_end;
+        Ok(())
+    }
+
+    /// Schedule clicks of the metronome at the volume of the reference tones,
+    /// at their times on the page clock as from `js_sys::Date::now`; clicks
+    /// scheduled before are dropped with `cancel_scheduled`
+    pub fn schedule_metronome_clicks(&mut self, cancel_scheduled: bool, clicks: &[MetronomeTick]) -> Result<(), String> {
+        let now = self.audio_context.current_time();
+        if cancel_scheduled {
+            self.metronome_clock_offset = now - js_sys::Date::now() / 1000.0;
+        }
+        let frequency = self.signal_path.metronome_osc.frequency();
+        let gain = self.signal_path.metronome_envelope.gain();
+
+        let schedule = || -> Result<(), wasm_bindgen::JsValue> {
+            if cancel_scheduled {
+                frequency.cancel_scheduled_values(now)?;
+                gain.cancel_scheduled_values(now)?;
+                gain.set_value_at_time(0.0, now)?;
+            }
+            for click in clicks {
+                let start = (click.time_ms / 1000.0 + self.metronome_clock_offset).max(now);
+                let end = start + crate::app_config::METRONOME_CLICK_MS / 1000.0;
+                let pitch = if click.accent { crate::app_config::METRONOME_ACCENT_CLICK_HZ } else { crate::app_config::METRONOME_CLICK_HZ };
+                frequency.set_value_at_time(pitch, start)?;
+                gain.set_value_at_time(self.reference_tone.volume, start)?;
+                gain.linear_ramp_to_value_at_time(0.0, end)?;
+            }
+            Ok(())
+        };
+        schedule().map_err(|e| format!("Failed to schedule metronome clicks: {:?}", e))
+    }
+
     /// Execute test signal configurations with privileged access
     /// 
     /// This method provides direct control over test signal generation,
@@ -166,8 +321,12 @@ impl NewAudioPipeline {
         &mut self,
         config: &crate::presentation::ConfigureTestSignal
     ) -> Result<(), String> {
+        if let Some(previous) = self.test_signal.take() {
+            previous.stop();
+        }
+
         if config.enabled {
-            self.signal_path.test_signal_osc.frequency().set_value(config.frequency);
+            self.test_signal = Some(super::test_signal::ActiveTestSignal::start(&self.audio_context, &self.signal_path, config)?);
             self.signal_path.test_signal_gain.gain().set_value(config.volume / 100.0);
             self.set_signal_path_mode(SignalPathMode::TestSignalMode);
         } else {
@@ -176,6 +335,29 @@ impl NewAudioPipeline {
         Ok(())
     }
 
+    /// Frequency the test signal is producing now, or `None` if it is off
+    #[cfg(debug_assertions)]
+    pub fn test_signal_frequency(&self) -> Option<f32> {
+        self.test_signal.as_ref().map(|test_signal| test_signal.frequency_at(self.audio_context.current_time()))
+    }
+
+    /// Create the node that captures the input
+    ///
+    /// With `use_worklet` this is an AudioWorkletNode, see `create_worklet_node`;
+    /// otherwise a ScriptProcessorNode of `SCRIPT_PROCESSOR_BUFFER_SIZE` samples.
+    fn create_capture_node(audio_context: &AudioContext, use_worklet: bool) -> Result<CaptureNode, String> {
+        if use_worklet {
+            return NewAudioPipeline::create_worklet_node(audio_context).map(CaptureNode::Worklet);
+        }
+        let script_processor = audio_context
+            .create_script_processor_with_buffer_size_and_number_of_input_channels_and_number_of_output_channels(
+                crate::app_config::SCRIPT_PROCESSOR_BUFFER_SIZE, 1, 1,
+            )
+            .map_err(|e| format!("Failed to create ScriptProcessorNode: {:?}", e))?;
+        dev_log!("✓ ScriptProcessorNode created in place of the AudioWorklet");
+        Ok(CaptureNode::ScriptProcessor(script_processor))
+    }
+
     /// Create AudioWorkletNode with standard configuration
     /// 
     /// This method creates an AudioWorkletNode using standard configuration options.
@@ -211,3 +393,21 @@ impl NewAudioPipeline {
         Ok(worklet_node)
     }
 }
+
+fn apply_timbre(audio_context: &AudioContext, oscillator: &OscillatorNode, timbre: ToneTimbre) -> Result<(), String> {
+    match timbre {
+        ToneTimbre::Sine => oscillator.set_type(OscillatorType::Sine),
+        ToneTimbre::Triangle => oscillator.set_type(OscillatorType::Triangle),
+        ToneTimbre::Organ => {
+            let n = 16;
+            let mut real = vec![0.0f32; n];
+            let mut imag = vec![0.0f32; n];
+            real[..ORGAN_HARMONICS.len()].copy_from_slice(&ORGAN_HARMONICS);
+
+            let periodic_wave = audio_context.create_periodic_wave(&mut real, &mut imag)
+                .map_err(|_| "Failed to create periodic wave".to_string())?;
+            oscillator.set_periodic_wave(&periodic_wave);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+#![cfg(target_arch = "wasm32")]
+
+use crate::app_config::{ADAPTIVE_BATCH_FAST_FRAME_MS, ADAPTIVE_BATCH_SETTLE_FRAMES, ADAPTIVE_BATCH_SLOW_FRAME_MS, MAX_BATCH_SIZE};
+use crate::common::shared_types::{BatchSizeMode, BatchingStatus};
+
+/// Frames slower than this are pauses, not load, and are left out
+const MAX_OBSERVED_FRAME_MS: f32 = 1000.0;
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+/// Chooses the worklet batch size
+///
+/// In adaptive mode the batch size doubles while frames are slow, so the main
+/// thread handles fewer messages at the cost of latency, and halves back
+/// towards the hop size once frames are fast again.
+pub struct BatchScheduler {
+    mode: BatchSizeMode,
+    hop_size: usize,
+    batch_size: usize,
+    frame_time_ms: f32,
+    frames_since_change: u32,
+}
+
+impl BatchScheduler {
+    pub fn new(hop_size: usize) -> Self {
+        Self {
+            mode: BatchSizeMode::default(),
+            hop_size,
+            batch_size: hop_size,
+            frame_time_ms: 0.0,
+            frames_since_change: 0,
+        }
+    }
+
+    pub fn status(&self) -> BatchingStatus {
+        BatchingStatus {
+            mode: self.mode,
+            batch_size: self.batch_size,
+        }
+    }
+
+    /// Returns the batch size for the new mode
+    pub fn set_mode(&mut self, mode: BatchSizeMode) -> usize {
+        self.mode = mode;
+        self.reset()
+    }
+
+    /// Returns the batch size for the new hop size
+    pub fn set_hop_size(&mut self, hop_size: usize) -> usize {
+        self.hop_size = hop_size;
+        self.reset()
+    }
+
+    /// Record the duration of a frame; returns a new batch size when it should change
+    pub fn observe_frame(&mut self, frame_ms: f32) -> Option<usize> {
+        if self.mode != BatchSizeMode::Adaptive || frame_ms > MAX_OBSERVED_FRAME_MS {
+            return None;
+        }
+
+        self.frame_time_ms += FRAME_TIME_SMOOTHING * (frame_ms - self.frame_time_ms);
+        self.frames_since_change += 1;
+        if self.frames_since_change < ADAPTIVE_BATCH_SETTLE_FRAMES {
+            return None;
+        }
+
+        let batch_size = if self.frame_time_ms > ADAPTIVE_BATCH_SLOW_FRAME_MS {
+            (self.batch_size * 2).min(MAX_BATCH_SIZE)
+        } else if self.frame_time_ms < ADAPTIVE_BATCH_FAST_FRAME_MS {
+            (self.batch_size / 2).max(self.hop_size)
+        } else {
+            self.batch_size
+        };
+
+        if batch_size == self.batch_size {
+            return None;
+        }
+        self.batch_size = batch_size;
+        self.frames_since_change = 0;
+        Some(batch_size)
+    }
+
+    fn reset(&mut self) -> usize {
+        self.batch_size = match self.mode {
+            BatchSizeMode::Fixed(batch_size) => batch_size,
+            BatchSizeMode::Hop | BatchSizeMode::Adaptive => self.hop_size,
+        };
+        self.frames_since_change = 0;
+        self.batch_size
+    }
+}
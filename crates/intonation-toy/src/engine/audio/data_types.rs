@@ -3,14 +3,13 @@
 
 /// Volume level data for external consumption
 /// 
-/// Contains RMS and peak amplitude measurements, and optionally FFT frequency data.
-/// The FFT data is normalized to 0.0-1.0 range and contains frequency bin magnitudes.
-/// FFT data is None for traditional volume detection and Some(Vec<f32>) for analyser-based detection.
+/// Contains RMS and peak amplitude measurements.
 #[derive(Debug, Clone, PartialEq)]
 pub struct VolumeLevelData {
     pub rms_amplitude: f32,
     pub peak_amplitude: f32,
-    pub fft_data: Option<Vec<f32>>,
+    pub a_weighted_rms_amplitude: f32,
+    pub short_term_lufs: f32,
 }
 
 /// Internal volume analysis result from volume detection
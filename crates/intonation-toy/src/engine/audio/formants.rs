@@ -0,0 +1,147 @@
+#![cfg(target_arch = "wasm32")]
+#![cfg(feature = "formant-estimation")]
+
+//! First and second formant estimation by linear prediction
+//!
+//! The input is decimated to a rate that covers the first formants, then an
+//! all-pole model of the vocal tract is fitted to a short window. Formants are
+//! the peaks of the model's spectral envelope.
+
+use std::f32::consts::PI;
+use crate::common::shared_types::Formants;
+
+/// Analysis window length
+const WINDOW_SECONDS: f32 = 0.04;
+/// Target rate after decimation; F1 and F2 of sung vowels lie below half of it
+const ANALYSIS_RATE_HZ: f32 = 11_025.0;
+/// Linear prediction order; two poles per expected formant plus spectral tilt
+const LPC_ORDER: usize = 12;
+const PRE_EMPHASIS: f32 = 0.97;
+/// Points at which the spectral envelope is evaluated up to the Nyquist frequency
+const ENVELOPE_POINTS: usize = 256;
+/// Windows quieter than this RMS are not analyzed
+const MIN_RMS: f32 = 1e-3;
+
+const F1_RANGE_HZ: (f32, f32) = (200.0, 1100.0);
+const F2_RANGE_HZ: (f32, f32) = (600.0, 3500.0);
+
+pub struct FormantEstimator {
+    decimation: usize,
+    analysis_rate: f32,
+    /// Most recent input samples, oldest first
+    history: Vec<f32>,
+    window_length: usize,
+    latest: Option<Formants>,
+}
+
+impl FormantEstimator {
+    pub fn new(sample_rate: u32) -> Self {
+        let decimation = (sample_rate as f32 / ANALYSIS_RATE_HZ).round().max(1.0) as usize;
+        let window_length = (sample_rate as f32 * WINDOW_SECONDS) as usize;
+        Self {
+            decimation,
+            analysis_rate: sample_rate as f32 / decimation as f32,
+            history: Vec::with_capacity(window_length * 2),
+            window_length,
+            latest: None,
+        }
+    }
+
+    /// Append contiguous input samples and estimate from the newest window
+    pub fn process(&mut self, samples: &[f32]) {
+        self.history.extend_from_slice(samples);
+        if self.history.len() > self.window_length {
+            self.history.drain(..self.history.len() - self.window_length);
+        }
+        self.latest = if self.history.len() == self.window_length {
+            self.estimate()
+        } else {
+            None
+        };
+    }
+
+    pub fn latest(&self) -> Option<Formants> {
+        self.latest
+    }
+
+    fn estimate(&self) -> Option<Formants> {
+        // Averaging each group of samples is a crude but sufficient anti-alias filter
+        let decimated: Vec<f32> = self.history
+            .chunks_exact(self.decimation)
+            .map(|group| group.iter().sum::<f32>() / self.decimation as f32)
+            .collect();
+
+        let rms = (decimated.iter().map(|x| x * x).sum::<f32>() / decimated.len() as f32).sqrt();
+        if rms < MIN_RMS {
+            return None;
+        }
+
+        let length = decimated.len();
+        let windowed: Vec<f32> = decimated.iter()
+            .enumerate()
+            .map(|(n, &x)| {
+                let previous = if n > 0 { decimated[n - 1] } else { 0.0 };
+                let hamming = 0.54 - 0.46 * (2.0 * PI * n as f32 / (length - 1) as f32).cos();
+                (x - PRE_EMPHASIS * previous) * hamming
+            })
+            .collect();
+
+        let coefficients = levinson_durbin(&autocorrelation(&windowed, LPC_ORDER))?;
+        let envelope: Vec<f32> = (0..ENVELOPE_POINTS)
+            .map(|k| envelope_at(&coefficients, PI * k as f32 / ENVELOPE_POINTS as f32))
+            .collect();
+        let bin_hz = self.analysis_rate / 2.0 / ENVELOPE_POINTS as f32;
+        let peaks: Vec<f32> = (1..ENVELOPE_POINTS - 1)
+            .filter(|&k| envelope[k] > envelope[k - 1] && envelope[k] >= envelope[k + 1])
+            .map(|k| k as f32 * bin_hz)
+            .collect();
+
+        let f1 = peaks.iter().copied().find(|&hz| (F1_RANGE_HZ.0..=F1_RANGE_HZ.1).contains(&hz))?;
+        let f2 = peaks.iter().copied().find(|&hz| hz > f1 && (F2_RANGE_HZ.0..=F2_RANGE_HZ.1).contains(&hz))?;
+        Some(Formants { f1, f2 })
+    }
+}
+
+fn autocorrelation(samples: &[f32], order: usize) -> Vec<f32> {
+    (0..=order)
+        .map(|lag| samples[lag..].iter().zip(samples).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// Prediction polynomial `1 + a1 z^-1 + ... + ap z^-p` from autocorrelation,
+/// or `None` when the input has no energy
+fn levinson_durbin(r: &[f32]) -> Option<Vec<f32>> {
+    if r[0] <= 0.0 {
+        return None;
+    }
+    let order = r.len() - 1;
+    let mut a = vec![0.0f32; order + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+
+    for i in 1..=order {
+        let acc: f32 = (1..i).map(|j| a[j] * r[i - j]).sum::<f32>() + r[i];
+        let reflection = -acc / error;
+        let previous = a.clone();
+        for j in 1..i {
+            a[j] = previous[j] + reflection * previous[i - j];
+        }
+        a[i] = reflection;
+        error *= 1.0 - reflection * reflection;
+        if error <= 0.0 {
+            return None;
+        }
+    }
+    Some(a)
+}
+
+/// Magnitude of the all-pole model at angular frequency `omega`
+fn envelope_at(coefficients: &[f32], omega: f32) -> f32 {
+    let (re, im) = coefficients.iter()
+        .enumerate()
+        .fold((0.0f32, 0.0f32), |(re, im), (k, &a)| {
+            let angle = omega * k as f32;
+            (re + a * angle.cos(), im - a * angle.sin())
+        });
+    1.0 / re.hypot(im).max(f32::EPSILON)
+}
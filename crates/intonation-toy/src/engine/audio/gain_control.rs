@@ -0,0 +1,93 @@
+#![cfg(target_arch = "wasm32")]
+
+use crate::app_config::{AGC_DEFAULT_NOISE_FLOOR_RMS, AGC_MAX_DB_PER_SECOND, AGC_TARGET_RMS, MAX_INPUT_GAIN, MIN_INPUT_GAIN};
+use crate::common::shared_types::{InputCalibration, InputGainStatus};
+
+/// Input gain, set by calibration and optionally adjusted by automatic gain control
+///
+/// The AGC moves the gain towards `AGC_TARGET_RMS` no faster than
+/// `AGC_MAX_DB_PER_SECOND`, and holds it while the input is below the noise floor.
+pub struct InputGainControl {
+    gain: f32,
+    automatic: bool,
+    calibrating: bool,
+    calibration: Option<InputCalibration>,
+}
+
+impl InputGainControl {
+    pub fn new(calibration: Option<InputCalibration>) -> Self {
+        Self {
+            gain: calibration.map_or(1.0, |calibration| calibration.gain),
+            automatic: false,
+            calibrating: false,
+            calibration,
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    pub fn status(&self) -> InputGainStatus {
+        InputGainStatus {
+            gain: self.gain,
+            automatic: self.automatic,
+            calibration: self.calibration,
+        }
+    }
+
+    pub fn set_automatic(&mut self, automatic: bool) {
+        self.automatic = automatic;
+        if !automatic {
+            self.reset();
+        }
+    }
+
+    /// Measure at unity gain with the AGC suspended until `finish_calibration`
+    pub fn begin_calibration(&mut self) {
+        self.calibrating = true;
+        self.gain = 1.0;
+    }
+
+    /// End calibration, adopting `calibration` if given or returning to the previous one
+    pub fn finish_calibration(&mut self, calibration: Option<InputCalibration>) {
+        self.calibrating = false;
+        if calibration.is_some() {
+            self.calibration = calibration;
+        }
+        self.reset();
+    }
+
+    /// Switch to the calibration of another input device
+    pub fn set_calibration(&mut self, calibration: Option<InputCalibration>) {
+        self.calibration = calibration;
+        self.reset();
+    }
+
+    /// Adjust the gain for an RMS level measured after the current gain
+    ///
+    /// Returns the new gain when it changed.
+    pub fn update(&mut self, rms: f32, elapsed_seconds: f32) -> Option<f32> {
+        if !self.automatic || self.calibrating {
+            return None;
+        }
+        let noise_floor = self.calibration.map_or(AGC_DEFAULT_NOISE_FLOOR_RMS, |calibration| calibration.noise_floor_rms);
+        if rms <= noise_floor {
+            return None;
+        }
+
+        let wanted_db = 20.0 * (AGC_TARGET_RMS / rms).log10();
+        let max_step_db = AGC_MAX_DB_PER_SECOND * elapsed_seconds;
+        let step_db = wanted_db.clamp(-max_step_db, max_step_db);
+        let gain = (self.gain * 10f32.powf(step_db / 20.0)).clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN);
+        if gain == self.gain {
+            return None;
+        }
+        self.gain = gain;
+        Some(gain)
+    }
+
+    fn reset(&mut self) {
+        self.gain = self.calibration.map_or(1.0, |calibration| calibration.gain);
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(target_arch = "wasm32")]
+
+use crate::common::shared_types::HarmonicAnalysis;
+
+/// Harmonics are searched for within this fraction of their ideal frequency
+const SEARCH_TOLERANCE: f32 = 0.03;
+
+/// Harmonics weaker than this, relative to the fundamental, are left out of the inharmonicity fit
+const MIN_RELATIVE_AMPLITUDE: f32 = 0.01;
+
+/// Measure the first `harmonic_count` harmonics of `fundamental_hz` in a decibel spectrum
+///
+/// Each harmonic is located as the strongest bin near its ideal frequency and
+/// refined by parabolic interpolation. The inharmonicity coefficient `B` is a
+/// least-squares fit of the stiff-string model `f_k = k * f_1 * sqrt(1 + B * k^2)`.
+/// Returns `None` when the fundamental is not present in the spectrum.
+pub fn analyze_harmonics(decibels: &[f32], bin_width_hz: f32, fundamental_hz: f32, harmonic_count: usize) -> Option<HarmonicAnalysis> {
+    let (fundamental_frequency, fundamental_amplitude) = find_peak(decibels, bin_width_hz, fundamental_hz)?;
+    if fundamental_amplitude <= 0.0 {
+        return None;
+    }
+
+    let mut relative_amplitudes = Vec::with_capacity(harmonic_count);
+    let mut weighted_deviation = 0.0;
+    let mut weight_sum = 0.0;
+
+    for number in 1..=harmonic_count {
+        let k = number as f32;
+        let Some((frequency, amplitude)) = find_peak(decibels, bin_width_hz, fundamental_frequency * k) else {
+            break;
+        };
+        let relative_amplitude = amplitude / fundamental_amplitude;
+        relative_amplitudes.push(relative_amplitude);
+
+        if number > 1 && relative_amplitude >= MIN_RELATIVE_AMPLITUDE {
+            let stretch = frequency / (fundamental_frequency * k);
+            weighted_deviation += (stretch * stretch - 1.0) * k * k;
+            weight_sum += k.powi(4);
+        }
+    }
+
+    Some(HarmonicAnalysis {
+        relative_amplitudes,
+        inharmonicity: if weight_sum > 0.0 { (weighted_deviation / weight_sum).max(0.0) } else { 0.0 },
+    })
+}
+
+/// Frequency and linear amplitude of the strongest bin near `target_hz`, or `None` above Nyquist
+fn find_peak(decibels: &[f32], bin_width_hz: f32, target_hz: f32) -> Option<(f32, f32)> {
+    let tolerance_hz = (target_hz * SEARCH_TOLERANCE).max(bin_width_hz);
+    let first = ((target_hz - tolerance_hz) / bin_width_hz).floor().max(1.0) as usize;
+    let last = ((target_hz + tolerance_hz) / bin_width_hz).ceil() as usize;
+    if last + 1 >= decibels.len() {
+        return None;
+    }
+
+    let peak = (first..=last).max_by(|&a, &b| decibels[a].total_cmp(&decibels[b]))?;
+    let (left, center, right) = (decibels[peak - 1], decibels[peak], decibels[peak + 1]);
+    let curvature = left - 2.0 * center + right;
+    // Silent bins read as -Infinity, in which case the bin center is used as is
+    let (offset, peak_db) = if curvature < 0.0 && curvature.is_finite() {
+        let offset = 0.5 * (left - right) / curvature;
+        (offset, center - 0.25 * (left - right) * offset)
+    } else {
+        (0.0, center)
+    };
+
+    let amplitude = if peak_db.is_finite() { 10f32.powf(peak_db / 20.0) } else { 0.0 };
+    Some(((peak as f32 + offset) * bin_width_hz, amplitude))
+}
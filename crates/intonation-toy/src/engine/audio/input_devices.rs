@@ -0,0 +1,225 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{MediaDeviceInfo, MediaDeviceKind, MediaStream};
+use crate::common::shared_types::AudioInputDevice;
+use super::AudioError;
+
+type OpenedStream = Result<(String, MediaStream), AudioError>;
+
+/// Keeps the list of audio inputs current and opens streams for selected devices
+///
+/// Enumeration and `getUserMedia` are asynchronous; their results are stored
+/// here and picked up by the engine on its next update.
+pub struct InputDeviceManager {
+    devices: Rc<RefCell<Vec<AudioInputDevice>>>,
+    opened_stream: Rc<RefCell<Option<OpenedStream>>>,
+    active_device_id: Option<String>,
+    /// Input the user chose, reopened first when its stream ends
+    selected_device_id: Option<String>,
+    _device_change_closure: Option<Closure<dyn FnMut()>>,
+}
+
+impl InputDeviceManager {
+    /// Enumerate the inputs and listen for `devicechange` to re-enumerate
+    pub fn new(initial_stream: &MediaStream) -> Self {
+        let devices = Rc::new(RefCell::new(Vec::new()));
+        refresh_devices(devices.clone());
+
+        let device_change_closure = web_sys::window()
+            .and_then(|window| window.navigator().media_devices().ok())
+            .map(|media_devices| {
+                let devices = devices.clone();
+                let closure = Closure::<dyn FnMut()>::new(move || refresh_devices(devices.clone()));
+                media_devices.set_ondevicechange(Some(closure.as_ref().unchecked_ref()));
+                closure
+            });
+
+        Self {
+            devices,
+            opened_stream: Rc::new(RefCell::new(None)),
+            active_device_id: stream_device_id(initial_stream),
+            selected_device_id: None,
+            _device_change_closure: device_change_closure,
+        }
+    }
+
+    pub fn devices(&self) -> Vec<AudioInputDevice> {
+        self.devices.borrow().clone()
+    }
+
+    pub fn active_device_id(&self) -> Option<&str> {
+        self.active_device_id.as_deref()
+    }
+
+    /// Start opening a stream for `device_id`; see `take_opened_stream`
+    pub fn request_device(&mut self, device_id: String) {
+        self.selected_device_id = Some(device_id.clone());
+        let opened_stream = self.opened_stream.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = open_device_stream(&device_id).await.map(|stream| (device_id, stream));
+            opened_stream.replace(Some(result));
+        });
+    }
+
+    /// Start opening a stream for whichever input the browser picks; see `take_opened_stream`
+    pub fn request_default_device(&self) {
+        let opened_stream = self.opened_stream.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            opened_stream.replace(Some(open_default_device().await));
+        });
+    }
+
+    /// Reopen the input after its stream ended: the input the user chose if
+    /// it can still be opened, otherwise whichever input the browser picks
+    pub fn request_reopen(&self) {
+        let Some(device_id) = self.selected_device_id.clone() else {
+            self.request_default_device();
+            return;
+        };
+        let opened_stream = self.opened_stream.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = match open_device_stream(&device_id).await {
+                Ok(stream) => Ok((device_id, stream)),
+                Err(e) => {
+                    crate::common::warn_log!("Failed to reopen audio input, using the default: {}", e);
+                    open_default_device().await
+                }
+            };
+            opened_stream.replace(Some(result));
+        });
+    }
+
+    /// Result of the last `request_device`, once available
+    pub fn take_opened_stream(&mut self) -> Option<OpenedStream> {
+        let result = self.opened_stream.borrow_mut().take();
+        if let Some(Ok((device_id, _))) = &result {
+            self.active_device_id = Some(device_id.clone());
+        }
+        result
+    }
+}
+
+fn refresh_devices(devices: Rc<RefCell<Vec<AudioInputDevice>>>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match enumerate_input_devices().await {
+            Ok(inputs) => {
+                crate::common::dev_log!("Audio inputs: {} available", inputs.len());
+                devices.replace(inputs);
+            }
+            Err(e) => crate::common::warn_log!("Failed to enumerate audio inputs: {}", e),
+        }
+    });
+}
+
+async fn enumerate_input_devices() -> Result<Vec<AudioInputDevice>, AudioError> {
+    let media_devices = web_sys::window()
+        .ok_or(AudioError::Generic("No window object".to_string()))?
+        .navigator()
+        .media_devices()
+        .map_err(|_| AudioError::NotSupported("MediaDevices not available".to_string()))?;
+
+    let promise = media_devices.enumerate_devices()
+        .map_err(|e| AudioError::Generic(format!("Failed to enumerate devices: {:?}", e)))?;
+    let devices_js = JsFuture::from(promise).await
+        .map_err(|e| AudioError::Generic(format!("Device enumeration failed: {:?}", e)))?;
+
+    Ok(js_sys::Array::from(&devices_js)
+        .iter()
+        .filter_map(|device| device.dyn_into::<MediaDeviceInfo>().ok())
+        .filter(|device| device.kind() == MediaDeviceKind::Audioinput)
+        .map(|device| AudioInputDevice {
+            device_id: device.device_id(),
+            label: device.label(),
+        })
+        .collect())
+}
+
+async fn open_device_stream(device_id: &str) -> Result<MediaStream, AudioError> {
+    let media_devices = web_sys::window()
+        .ok_or(AudioError::Generic("No window object".to_string()))?
+        .navigator()
+        .media_devices()
+        .map_err(|_| AudioError::NotSupported("MediaDevices not available".to_string()))?;
+
+    // { deviceId: { exact: device_id } }
+    let exact = js_sys::Object::new();
+    js_sys::Reflect::set(&exact, &"exact".into(), &device_id.into())
+        .map_err(|e| AudioError::Generic(format!("Failed to build constraints: {:?}", e)))?;
+    let audio = js_sys::Object::new();
+    js_sys::Reflect::set(&audio, &"deviceId".into(), &exact)
+        .map_err(|e| AudioError::Generic(format!("Failed to build constraints: {:?}", e)))?;
+
+    let constraints = web_sys::MediaStreamConstraints::new();
+    constraints.set_audio(&audio);
+    constraints.set_video(&false.into());
+
+    let promise = media_devices.get_user_media_with_constraints(&constraints)
+        .map_err(|e| AudioError::Generic(format!("getUserMedia failed: {:?}", e)))?;
+    let stream = JsFuture::from(promise).await
+        .map_err(|e| AudioError::Generic(format!("Could not open input {}: {:?}", device_id, e)))?;
+
+    stream.dyn_into::<MediaStream>()
+        .map_err(|_| AudioError::Generic("getUserMedia did not return a MediaStream".to_string()))
+}
+
+async fn open_default_device() -> OpenedStream {
+    let stream = open_default_stream().await?;
+    let device_id = stream_device_id(&stream)
+        .ok_or_else(|| AudioError::Generic("Opened stream has no audio track".to_string()))?;
+    Ok((device_id, stream))
+}
+
+async fn open_default_stream() -> Result<MediaStream, AudioError> {
+    let media_devices = web_sys::window()
+        .ok_or(AudioError::Generic("No window object".to_string()))?
+        .navigator()
+        .media_devices()
+        .map_err(|_| AudioError::NotSupported("MediaDevices not available".to_string()))?;
+
+    let constraints = web_sys::MediaStreamConstraints::new();
+    constraints.set_audio(&true.into());
+    constraints.set_video(&false.into());
+
+    let promise = media_devices.get_user_media_with_constraints(&constraints)
+        .map_err(|e| AudioError::Generic(format!("getUserMedia failed: {:?}", e)))?;
+    let stream = JsFuture::from(promise).await
+        .map_err(|e| AudioError::Generic(format!("Could not open an audio input: {:?}", e)))?;
+
+    stream.dyn_into::<MediaStream>()
+        .map_err(|_| AudioError::Generic("getUserMedia did not return a MediaStream".to_string()))
+}
+
+/// Device id of the first audio track of `stream`
+fn stream_device_id(stream: &MediaStream) -> Option<String> {
+    let track = stream.get_audio_tracks().get(0).dyn_into::<web_sys::MediaStreamTrack>().ok()?;
+    js_sys::Reflect::get(&track.get_settings(), &"deviceId".into()).ok()?.as_string()
+}
+
+/// Whether `stream` still has an audio track delivering samples
+pub fn is_stream_live(stream: &MediaStream) -> bool {
+    stream.get_audio_tracks().iter()
+        .filter_map(|track| track.dyn_into::<web_sys::MediaStreamTrack>().ok())
+        .any(|track| track.ready_state() == web_sys::MediaStreamTrackState::Live)
+}
+
+/// Whether `stream` has a live audio track that is neither disabled nor
+/// muted by the system or the browser
+pub fn is_stream_listening(stream: &MediaStream) -> bool {
+    stream.get_audio_tracks().iter()
+        .filter_map(|track| track.dyn_into::<web_sys::MediaStreamTrack>().ok())
+        .any(|track| track.ready_state() == web_sys::MediaStreamTrackState::Live && track.enabled() && !track.muted())
+}
+
+/// Stop all tracks of a stream that is no longer used
+pub fn stop_stream(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        if let Ok(track) = track.dyn_into::<web_sys::MediaStreamTrack>() {
+            track.stop();
+        }
+    }
+}
@@ -0,0 +1,125 @@
+#![cfg(target_arch = "wasm32")]
+
+//! AudioContext lifecycle across tab visibility changes
+//!
+//! Browsers stop animation frames in hidden tabs, so the engine cannot react
+//! to a hidden tab from its update loop. The visibility handler suspends the
+//! AudioContext directly and resumes it when the tab is shown again. Contexts
+//! suspended by the browser itself stay suspended until `resume` is called
+//! after a user gesture. Mobile browsers only resume a context inside the
+//! gesture itself, so a tap anywhere also resumes it right away. While the
+//! mini view shows the pitch over other tabs, or the drone or a reference
+//! tone is sounding, the context keeps running in a hidden tab.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{AudioContext, AudioContextState};
+use crate::common::shared_types::AudioLifecycleState;
+
+pub struct AudioLifecycle {
+    audio_context: AudioContext,
+    hidden: Rc<Cell<bool>>,
+    /// Whether something is sounding that hiding the tab should not cut off
+    audible: Rc<Cell<bool>>,
+    state_changes: Rc<RefCell<Vec<AudioContextState>>>,
+    _visibility_closure: Closure<dyn FnMut()>,
+    _state_change_closure: Closure<dyn FnMut()>,
+    _tap_closure: Closure<dyn FnMut()>,
+}
+
+impl AudioLifecycle {
+    pub fn attach(audio_context: &AudioContext) -> Result<Self, String> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("No document available")?;
+
+        let hidden = Rc::new(Cell::new(document.hidden()));
+        let suspended_while_hidden = Rc::new(Cell::new(false));
+        let audible = Rc::new(Cell::new(false));
+
+        let visibility_document = document.clone();
+        let visibility_context = audio_context.clone();
+        let visibility_hidden = hidden.clone();
+        let visibility_audible = audible.clone();
+        let visibility_closure = Closure::<dyn FnMut()>::new(move || {
+            let is_hidden = visibility_document.hidden();
+            visibility_hidden.set(is_hidden);
+            if is_hidden && visibility_context.state() == AudioContextState::Running
+                && !visibility_audible.get() && !crate::web::picture_in_picture::is_open()
+            {
+                suspended_while_hidden.set(true);
+                let _ = visibility_context.suspend();
+                crate::common::dev_log!("Tab hidden, suspending AudioContext");
+            } else if !is_hidden && suspended_while_hidden.replace(false) {
+                let _ = visibility_context.resume();
+                crate::common::dev_log!("Tab visible, resuming AudioContext");
+            }
+        });
+        document.add_event_listener_with_callback("visibilitychange", visibility_closure.as_ref().unchecked_ref())
+            .map_err(|e| format!("Failed to add visibilitychange listener: {:?}", e))?;
+
+        let state_changes = Rc::new(RefCell::new(Vec::new()));
+        let state_change_context = audio_context.clone();
+        let state_change_queue = state_changes.clone();
+        let state_change_closure = Closure::<dyn FnMut()>::new(move || {
+            state_change_queue.borrow_mut().push(state_change_context.state());
+        });
+        audio_context.set_onstatechange(Some(state_change_closure.as_ref().unchecked_ref()));
+
+        // Safari also leaves a context interrupted, a state without a variant
+        // here, after a call or another app took the audio
+        let tap_context = audio_context.clone();
+        let tap_document = document.clone();
+        let tap_closure = Closure::<dyn FnMut()>::new(move || {
+            if !tap_document.hidden() && !matches!(tap_context.state(), AudioContextState::Running | AudioContextState::Closed) {
+                let _ = tap_context.resume();
+                crate::common::dev_log!("Tap, resuming AudioContext");
+            }
+        });
+        for event in ["touchend", "pointerup"] {
+            document.add_event_listener_with_callback(event, tap_closure.as_ref().unchecked_ref())
+                .map_err(|e| format!("Failed to add {} listener: {:?}", event, e))?;
+        }
+
+        Ok(Self {
+            audio_context: audio_context.clone(),
+            hidden,
+            audible,
+            state_changes,
+            _visibility_closure: visibility_closure,
+            _state_change_closure: state_change_closure,
+            _tap_closure: tap_closure,
+        })
+    }
+
+    pub fn state(&self) -> AudioLifecycleState {
+        match self.audio_context.state() {
+            AudioContextState::Closed => AudioLifecycleState::Closed,
+            AudioContextState::Running => AudioLifecycleState::Running,
+            _ if self.hidden.get() => AudioLifecycleState::Hidden,
+            _ => AudioLifecycleState::Suspended,
+        }
+    }
+
+    /// Keep the context running when the tab is hidden while `audible`
+    pub fn set_audible(&self, audible: bool) {
+        self.audible.set(audible);
+    }
+
+    /// AudioContext states entered since the last call, oldest first
+    pub fn take_state_changes(&self) -> Vec<AudioContextState> {
+        std::mem::take(&mut *self.state_changes.borrow_mut())
+    }
+
+    /// Resume a suspended context; browsers only allow this after a user gesture
+    pub fn resume(&self) -> Result<(), String> {
+        if self.audio_context.state() != AudioContextState::Suspended {
+            return Ok(());
+        }
+        self.audio_context.resume()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to resume AudioContext: {:?}", e))
+    }
+}
@@ -0,0 +1,190 @@
+//! Perceptual loudness of the analyzed input
+//!
+//! Short-term loudness follows ITU-R BS.1770: K-weighted mean square over a
+//! sliding three second window, measured in 100 ms blocks, without gating.
+//! The A-weighted level is the RMS of the latest block after IEC 61672
+//! A-weighting, normalized to unity gain at 1 kHz.
+
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use crate::app_config::LOUDNESS_FLOOR_LUFS;
+
+const BLOCKS_PER_SECOND: usize = 10;
+const SHORT_TERM_BLOCKS: usize = 3 * BLOCKS_PER_SECOND;
+
+/// Offset in the BS.1770 loudness formula
+const LOUDNESS_OFFSET_DB: f64 = -0.691;
+
+/// Pole frequencies of the analog A-weighting curve
+const A_WEIGHTING_POLES_HZ: [f64; 4] = [20.598_997, 107.652_65, 737.862_23, 12_194.217];
+const A_WEIGHTING_REFERENCE_HZ: f64 = 1000.0;
+
+/// Second order IIR section, direct form I
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Digital section for the analog `(B0 s² + B1 s + B2) / (A0 s² + A1 s + A2)`
+    /// via the bilinear transform
+    fn bilinear(analog_b: [f64; 3], analog_a: [f64; 3], sample_rate: f64) -> Self {
+        let c = 2.0 * sample_rate;
+        let digital = |[p2, p1, p0]: [f64; 3]| [
+            p2 * c * c + p1 * c + p0,
+            2.0 * (p0 - p2 * c * c),
+            p2 * c * c - p1 * c + p0,
+        ];
+        let b = digital(analog_b);
+        let a = digital(analog_a);
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            ..Default::default()
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+
+    fn magnitude_at(&self, frequency: f64, sample_rate: f64) -> f64 {
+        let w = 2.0 * PI * frequency / sample_rate;
+        let (cos1, sin1, cos2, sin2) = (w.cos(), w.sin(), (2.0 * w).cos(), (2.0 * w).sin());
+        let num_re = self.b[0] + self.b[1] * cos1 + self.b[2] * cos2;
+        let num_im = -self.b[1] * sin1 - self.b[2] * sin2;
+        let den_re = 1.0 + self.a[0] * cos1 + self.a[1] * cos2;
+        let den_im = -self.a[0] * sin1 - self.a[1] * sin2;
+        num_re.hypot(num_im) / den_re.hypot(den_im)
+    }
+}
+
+/// BS.1770 pre-filter (high shelf) and RLB filter (high pass), using the
+/// coefficient formulas that reproduce the 48 kHz reference at any rate
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    let shelf_frequency = 1_681.974_450_955_533;
+    let shelf_gain_db = 3.999_843_853_973_347;
+    let shelf_q = 0.707_175_236_955_419_6;
+    let k = (PI * shelf_frequency / sample_rate).tan();
+    let vh = 10f64.powf(shelf_gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / shelf_q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / shelf_q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / shelf_q + k * k) / a0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / shelf_q + k * k) / a0],
+        ..Default::default()
+    };
+
+    let high_pass_frequency = 38.135_470_876_024_44;
+    let high_pass_q = 0.500_327_037_323_877_3;
+    let k = (PI * high_pass_frequency / sample_rate).tan();
+    let a0 = 1.0 + k / high_pass_q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / high_pass_q + k * k) / a0],
+        ..Default::default()
+    };
+
+    [shelf, high_pass]
+}
+
+/// A-weighting as three sections plus the gain that makes 1 kHz pass unchanged
+fn a_weighting(sample_rate: f64) -> ([Biquad; 3], f64) {
+    // Prewarped so each pole lands on its frequency despite the bilinear transform
+    let [w1, w2, w3, w4] = A_WEIGHTING_POLES_HZ.map(|frequency| 2.0 * sample_rate * (PI * frequency / sample_rate).tan());
+    let sections = [
+        Biquad::bilinear([1.0, 0.0, 0.0], [1.0, 2.0 * w1, w1 * w1], sample_rate),
+        Biquad::bilinear([1.0, 0.0, 0.0], [1.0, w2 + w3, w2 * w3], sample_rate),
+        Biquad::bilinear([0.0, 0.0, 1.0], [1.0, 2.0 * w4, w4 * w4], sample_rate),
+    ];
+    let reference_magnitude: f64 = sections.iter()
+        .map(|section| section.magnitude_at(A_WEIGHTING_REFERENCE_HZ, sample_rate))
+        .product();
+    (sections, 1.0 / reference_magnitude)
+}
+
+pub struct LoudnessMeter {
+    k_weighting: [Biquad; 2],
+    a_weighting: [Biquad; 3],
+    a_weighting_gain: f64,
+    block_length: usize,
+    block_position: usize,
+    k_block_sum: f64,
+    a_block_sum: f64,
+    /// K-weighted mean square of the most recent blocks, oldest first
+    k_blocks: VecDeque<f64>,
+    a_weighted_rms: f32,
+    short_term_lufs: f32,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        let sample_rate = sample_rate as f64;
+        let (a_weighting, a_weighting_gain) = a_weighting(sample_rate);
+        Self {
+            k_weighting: k_weighting(sample_rate),
+            a_weighting,
+            a_weighting_gain,
+            block_length: (sample_rate as usize / BLOCKS_PER_SECOND).max(1),
+            block_position: 0,
+            k_block_sum: 0.0,
+            a_block_sum: 0.0,
+            k_blocks: VecDeque::with_capacity(SHORT_TERM_BLOCKS),
+            a_weighted_rms: 0.0,
+            short_term_lufs: LOUDNESS_FLOOR_LUFS,
+        }
+    }
+
+    /// Filter contiguous input samples; the levels update at each completed block
+    pub fn process(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            let sample = sample as f64;
+            let k_weighted = self.k_weighting.iter_mut().fold(sample, |x, section| section.process(x));
+            let a_weighted = self.a_weighting.iter_mut().fold(sample, |x, section| section.process(x)) * self.a_weighting_gain;
+            self.k_block_sum += k_weighted * k_weighted;
+            self.a_block_sum += a_weighted * a_weighted;
+
+            self.block_position += 1;
+            if self.block_position == self.block_length {
+                self.finish_block();
+            }
+        }
+    }
+
+    /// RMS amplitude of the latest block after A-weighting
+    pub fn a_weighted_rms(&self) -> f32 {
+        self.a_weighted_rms
+    }
+
+    /// Loudness over the last three seconds in LUFS, no lower than `LOUDNESS_FLOOR_LUFS`
+    pub fn short_term_lufs(&self) -> f32 {
+        self.short_term_lufs
+    }
+
+    fn finish_block(&mut self) {
+        let length = self.block_length as f64;
+        if self.k_blocks.len() == SHORT_TERM_BLOCKS {
+            self.k_blocks.pop_front();
+        }
+        self.k_blocks.push_back(self.k_block_sum / length);
+        self.a_weighted_rms = (self.a_block_sum / length).sqrt() as f32;
+
+        let mean_square = self.k_blocks.iter().sum::<f64>() / self.k_blocks.len() as f64;
+        self.short_term_lufs = if mean_square > 0.0 {
+            ((LOUDNESS_OFFSET_DB + 10.0 * mean_square.log10()) as f32).max(LOUDNESS_FLOOR_LUFS)
+        } else {
+            LOUDNESS_FLOOR_LUFS
+        };
+
+        self.block_position = 0;
+        self.k_block_sum = 0.0;
+        self.a_block_sum = 0.0;
+    }
+}
@@ -4,15 +4,18 @@
 
 use js_sys::{Object, Reflect};
 use wasm_bindgen::{JsValue, JsCast};
+use crate::common::shared_types::{InputConditioning, MainsFrequency};
 
 /// Message types sent from main thread to AudioWorklet
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum ToWorkletMessage {
     StartProcessing,
     StopProcessing,
     UpdateBatchConfig { config: BatchConfig },
+    UpdateInputConditioning { config: InputConditioning },
     ReturnBuffer { buffer_id: u32 },
-    
+    /// Write samples into shared memory instead of posting batches
+    EnableSampleRing { samples: js_sys::SharedArrayBuffer, control: js_sys::SharedArrayBuffer },
 }
 
 /// Message types sent from AudioWorklet to main thread
@@ -40,18 +43,21 @@ pub struct MemoryUsage {
     pub active_buffers: usize,
 }
 
+/// State of the worklet's transferable buffer pool
+///
+/// Buffers are allocated on demand up to a limit, transferred to the main
+/// thread with each batch and recycled when they come back.
 #[derive(Debug, Clone, PartialEq)]
 pub struct BufferPoolStats {
-    pub pool_size: u32,
     pub available_buffers: u32,
-    pub in_use_buffers: u32,
+    /// Buffers held by the main thread
+    pub in_flight_buffers: u32,
     pub total_buffers: u32,
-    pub acquire_count: u32,
+    pub allocation_count: u32,
+    pub recycle_count: u32,
+    /// Batches dropped because the pool was at its limit
+    pub miss_count: u32,
     pub transfer_count: u32,
-    pub pool_exhausted_count: u32,
-    pub consecutive_pool_failures: u32,
-    pub pool_hit_rate: f32,
-    pub pool_efficiency: f32,
     pub buffer_utilization_percent: f32,
     pub total_megabytes_transferred: f32,
 }
@@ -245,10 +251,19 @@ impl ToJsMessage for ToWorkletMessage {
                 set("type", "updateBatchConfig".into())?;
                 set("config", config.to_js_object()?.into())?;
             }
+            ToWorkletMessage::UpdateInputConditioning { config } => {
+                set("type", "updateInputConditioning".into())?;
+                set("config", config.to_js_object()?.into())?;
+            }
             ToWorkletMessage::ReturnBuffer { buffer_id } => {
                 set("type", "returnBuffer".into())?;
                 set("bufferId", (*buffer_id).into())?;
             }
+            ToWorkletMessage::EnableSampleRing { samples, control } => {
+                set("type", "enableSampleRing".into())?;
+                set("samples", samples.clone().into())?;
+                set("control", control.clone().into())?;
+            }
         }
         
         Ok(obj)
@@ -277,12 +292,29 @@ impl FromJsMessage for ToWorkletMessage {
                     config: BatchConfig::from_js_object(&config_obj)? 
                 })
             }
+            "updateInputConditioning" => {
+                let config_obj = get("config")?
+                    .dyn_into::<Object>()
+                    .map_err(|_| SerializationError::InvalidPropertyType("config must be object".to_string()))?;
+                Ok(ToWorkletMessage::UpdateInputConditioning {
+                    config: InputConditioning::from_js_object(&config_obj)?
+                })
+            }
             "returnBuffer" => {
                 let buffer_id = get("bufferId")?
                     .as_f64()
                     .ok_or_else(|| SerializationError::InvalidPropertyType("bufferId must be number".to_string()))? as u32;
                 Ok(ToWorkletMessage::ReturnBuffer { buffer_id })
             }
+            "enableSampleRing" => {
+                let samples = get("samples")?
+                    .dyn_into::<js_sys::SharedArrayBuffer>()
+                    .map_err(|_| SerializationError::InvalidPropertyType("samples must be SharedArrayBuffer".to_string()))?;
+                let control = get("control")?
+                    .dyn_into::<js_sys::SharedArrayBuffer>()
+                    .map_err(|_| SerializationError::InvalidPropertyType("control must be SharedArrayBuffer".to_string()))?;
+                Ok(ToWorkletMessage::EnableSampleRing { samples, control })
+            }
             _ => Err(SerializationError::InvalidPropertyType(format!("Unknown message type: {}", msg_type))),
         }
     }
@@ -293,7 +325,9 @@ impl MessageValidator for ToWorkletMessage {
         match self {
             ToWorkletMessage::StartProcessing | ToWorkletMessage::StopProcessing => Ok(()),
             ToWorkletMessage::UpdateBatchConfig { config } => config.validate(),
+            ToWorkletMessage::UpdateInputConditioning { config } => config.validate(),
             ToWorkletMessage::ReturnBuffer { buffer_id: _ } => Ok(()),
+            ToWorkletMessage::EnableSampleRing { .. } => Ok(()),
         }
     }
 }
@@ -504,140 +538,55 @@ impl MessageValidator for MemoryUsage {
 impl ToJsMessage for BufferPoolStats {
     fn to_js_object(&self) -> SerializationResult<Object> {
         let obj = Object::new();
-        
-        Reflect::set(&obj, &"pool_size".into(), &(self.pool_size as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set pool_size: {:?}", e)))?;
-        Reflect::set(&obj, &"available_buffers".into(), &(self.available_buffers as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set available_buffers: {:?}", e)))?;
-        Reflect::set(&obj, &"in_use_buffers".into(), &(self.in_use_buffers as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set in_use_buffers: {:?}", e)))?;
-        Reflect::set(&obj, &"total_buffers".into(), &(self.total_buffers as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set total_buffers: {:?}", e)))?;
-        Reflect::set(&obj, &"acquire_count".into(), &(self.acquire_count as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set acquire_count: {:?}", e)))?;
-        Reflect::set(&obj, &"transfer_count".into(), &(self.transfer_count as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set transfer_count: {:?}", e)))?;
-        Reflect::set(&obj, &"pool_exhausted_count".into(), &(self.pool_exhausted_count as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set pool_exhausted_count: {:?}", e)))?;
-        Reflect::set(&obj, &"consecutive_pool_failures".into(), &(self.consecutive_pool_failures as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set consecutive_pool_failures: {:?}", e)))?;
-        Reflect::set(&obj, &"pool_hit_rate".into(), &(self.pool_hit_rate as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set pool_hit_rate: {:?}", e)))?;
-        Reflect::set(&obj, &"pool_efficiency".into(), &(self.pool_efficiency as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set pool_efficiency: {:?}", e)))?;
-        Reflect::set(&obj, &"buffer_utilization_percent".into(), &(self.buffer_utilization_percent as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set buffer_utilization_percent: {:?}", e)))?;
-        Reflect::set(&obj, &"total_megabytes_transferred".into(), &(self.total_megabytes_transferred as f64).into())
-            .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set total_megabytes_transferred: {:?}", e)))?;
-        
+        let set = |k: &str, v: f64| {
+            Reflect::set(&obj, &k.into(), &v.into())
+                .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set {}: {:?}", k, e)))
+        };
+
+        set("available_buffers", self.available_buffers as f64)?;
+        set("in_flight_buffers", self.in_flight_buffers as f64)?;
+        set("total_buffers", self.total_buffers as f64)?;
+        set("allocation_count", self.allocation_count as f64)?;
+        set("recycle_count", self.recycle_count as f64)?;
+        set("miss_count", self.miss_count as f64)?;
+        set("transfer_count", self.transfer_count as f64)?;
+        set("buffer_utilization_percent", self.buffer_utilization_percent as f64)?;
+        set("total_megabytes_transferred", self.total_megabytes_transferred as f64)?;
+
         Ok(obj)
     }
 }
 
 impl FromJsMessage for BufferPoolStats {
     fn from_js_object(obj: &Object) -> SerializationResult<Self> {
-        let pool_size = Reflect::get(obj, &"pool_size".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get pool_size: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("pool_size must be number".to_string()))?
-            as u32;
-        
-        let available_buffers = Reflect::get(obj, &"available_buffers".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get available_buffers: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("available_buffers must be number".to_string()))?
-            as u32;
-        
-        let in_use_buffers = Reflect::get(obj, &"in_use_buffers".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get in_use_buffers: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("in_use_buffers must be number".to_string()))?
-            as u32;
-        
-        let total_buffers = Reflect::get(obj, &"total_buffers".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get total_buffers: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("total_buffers must be number".to_string()))?
-            as u32;
-        
-        let acquire_count = Reflect::get(obj, &"acquire_count".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get acquire_count: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("acquire_count must be number".to_string()))?
-            as u32;
-        
-        let transfer_count = Reflect::get(obj, &"transfer_count".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get transfer_count: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("transfer_count must be number".to_string()))?
-            as u32;
-        
-        let pool_exhausted_count = Reflect::get(obj, &"pool_exhausted_count".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get pool_exhausted_count: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("pool_exhausted_count must be number".to_string()))?
-            as u32;
-        
-        let consecutive_pool_failures = Reflect::get(obj, &"consecutive_pool_failures".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get consecutive_pool_failures: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("consecutive_pool_failures must be number".to_string()))?
-            as u32;
-        
-        let pool_hit_rate = Reflect::get(obj, &"pool_hit_rate".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get pool_hit_rate: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("pool_hit_rate must be number".to_string()))?
-            as f32;
-        
-        let pool_efficiency = Reflect::get(obj, &"pool_efficiency".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get pool_efficiency: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("pool_efficiency must be number".to_string()))?
-            as f32;
-        
-        let buffer_utilization_percent = Reflect::get(obj, &"buffer_utilization_percent".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get buffer_utilization_percent: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("buffer_utilization_percent must be number".to_string()))?
-            as f32;
-        
-        let total_megabytes_transferred = Reflect::get(obj, &"total_megabytes_transferred".into())
-            .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get total_megabytes_transferred: {:?}", e)))?
-            .as_f64()
-            .ok_or_else(|| SerializationError::InvalidPropertyType("total_megabytes_transferred must be number".to_string()))?
-            as f32;
-        
+        let get = |k: &str| {
+            Reflect::get(obj, &k.into())
+                .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get {}: {:?}", k, e)))?
+                .as_f64()
+                .ok_or_else(|| SerializationError::InvalidPropertyType(format!("{} must be number", k)))
+        };
+
         Ok(BufferPoolStats {
-            pool_size,
-            available_buffers,
-            in_use_buffers,
-            total_buffers,
-            acquire_count,
-            transfer_count,
-            pool_exhausted_count,
-            consecutive_pool_failures,
-            pool_hit_rate,
-            pool_efficiency,
-            buffer_utilization_percent,
-            total_megabytes_transferred,
+            available_buffers: get("available_buffers")? as u32,
+            in_flight_buffers: get("in_flight_buffers")? as u32,
+            total_buffers: get("total_buffers")? as u32,
+            allocation_count: get("allocation_count")? as u32,
+            recycle_count: get("recycle_count")? as u32,
+            miss_count: get("miss_count")? as u32,
+            transfer_count: get("transfer_count")? as u32,
+            buffer_utilization_percent: get("buffer_utilization_percent")? as f32,
+            total_megabytes_transferred: get("total_megabytes_transferred")? as f32,
         })
     }
 }
 
 impl MessageValidator for BufferPoolStats {
     fn validate(&self) -> SerializationResult<()> {
-        if self.pool_hit_rate < 0.0 || self.pool_hit_rate > 100.0 {
-            return Err(SerializationError::ValidationFailed("pool_hit_rate must be between 0 and 100".to_string()));
-        }
-        if self.pool_efficiency < 0.0 || self.pool_efficiency > 100.0 {
-            return Err(SerializationError::ValidationFailed("pool_efficiency must be between 0 and 100".to_string()));
-        }
         if self.buffer_utilization_percent < 0.0 || self.buffer_utilization_percent > 100.0 {
             return Err(SerializationError::ValidationFailed("buffer_utilization_percent must be between 0 and 100".to_string()));
         }
-        if self.in_use_buffers + self.available_buffers != self.total_buffers {
-            return Err(SerializationError::ValidationFailed("in_use_buffers + available_buffers must equal total_buffers".to_string()));
+        if self.in_flight_buffers + self.available_buffers > self.total_buffers {
+            return Err(SerializationError::ValidationFailed("in_flight_buffers + available_buffers must not exceed total_buffers".to_string()));
         }
         Ok(())
     }
@@ -701,6 +650,76 @@ impl MessageValidator for BatchConfig {
     }
 }
 
+impl ToJsMessage for InputConditioning {
+    fn to_js_object(&self) -> SerializationResult<Object> {
+        let obj = Object::new();
+        let set = |k: &str, v: JsValue| {
+            Reflect::set(&obj, &k.into(), &v)
+                .map_err(|e| SerializationError::PropertySetFailed(format!("Failed to set {}: {:?}", k, e)))
+        };
+
+        set("gateEnabled", self.gate_enabled.into())?;
+        set("gateThresholdDb", self.gate_threshold_db.into())?;
+        set("gateAttackMs", self.gate_attack_ms.into())?;
+        set("gateReleaseMs", self.gate_release_ms.into())?;
+        set("highPassEnabled", self.high_pass_enabled.into())?;
+        set("highPassHz", self.high_pass_hz.into())?;
+        // 0 disables the notch
+        set("notchHz", self.notch.map_or(0.0, |mains| mains.hz()).into())?;
+
+        Ok(obj)
+    }
+}
+
+impl FromJsMessage for InputConditioning {
+    fn from_js_object(obj: &Object) -> SerializationResult<Self> {
+        let get = |k: &str| {
+            Reflect::get(obj, &k.into())
+                .map_err(|e| SerializationError::PropertyGetFailed(format!("Failed to get {}: {:?}", k, e)))
+        };
+        let get_num = |k: &str| {
+            get(k)?.as_f64()
+                .ok_or_else(|| SerializationError::InvalidPropertyType(format!("{} must be number", k)))
+        };
+        let get_bool = |k: &str| {
+            get(k)?.as_bool()
+                .ok_or_else(|| SerializationError::InvalidPropertyType(format!("{} must be boolean", k)))
+        };
+
+        let notch = match get_num("notchHz")? as u32 {
+            0 => None,
+            50 => Some(MainsFrequency::Hz50),
+            60 => Some(MainsFrequency::Hz60),
+            hz => return Err(SerializationError::InvalidPropertyType(format!("notchHz must be 0, 50 or 60, got {}", hz))),
+        };
+
+        Ok(InputConditioning {
+            gate_enabled: get_bool("gateEnabled")?,
+            gate_threshold_db: get_num("gateThresholdDb")? as f32,
+            gate_attack_ms: get_num("gateAttackMs")? as f32,
+            gate_release_ms: get_num("gateReleaseMs")? as f32,
+            high_pass_enabled: get_bool("highPassEnabled")?,
+            high_pass_hz: get_num("highPassHz")? as f32,
+            notch,
+        })
+    }
+}
+
+impl MessageValidator for InputConditioning {
+    fn validate(&self) -> SerializationResult<()> {
+        if self.gate_threshold_db > 0.0 {
+            return Err(SerializationError::ValidationFailed("gate_threshold_db cannot be above 0 dBFS".to_string()));
+        }
+        if self.gate_attack_ms <= 0.0 || self.gate_release_ms <= 0.0 {
+            return Err(SerializationError::ValidationFailed("gate attack and release must be positive".to_string()));
+        }
+        if self.high_pass_hz <= 0.0 {
+            return Err(SerializationError::ValidationFailed("high_pass_hz must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
 // Configuration type implementations
 
 impl ToJsMessage for WorkletError {
@@ -954,9 +973,18 @@ impl ToWorkletMessage {
         Ok(Self::UpdateBatchConfig { config })
     }
     
+    pub fn update_input_conditioning(config: InputConditioning) -> MessageConstructionResult<Self> {
+        config.validate().map_err(|e| MessageConstructionError::ValidationFailed(e.to_string()))?;
+        Ok(Self::UpdateInputConditioning { config })
+    }
+    
     pub fn return_buffer(buffer_id: u32) -> Self {
         Self::ReturnBuffer { buffer_id }
     }
+    
+    pub fn enable_sample_ring(samples: js_sys::SharedArrayBuffer, control: js_sys::SharedArrayBuffer) -> Self {
+        Self::EnableSampleRing { samples, control }
+    }
 }
 
 impl FromWorkletMessage {
@@ -1015,10 +1043,18 @@ impl AudioWorkletMessageFactory {
         Ok(self.create_envelope(ToWorkletMessage::update_batch_config(config)?))
     }
     
+    pub fn update_input_conditioning(&self, config: InputConditioning) -> MessageConstructionResult<ToWorkletEnvelope> {
+        Ok(self.create_envelope(ToWorkletMessage::update_input_conditioning(config)?))
+    }
+    
     pub fn return_buffer(&self, buffer_id: u32) -> MessageConstructionResult<ToWorkletEnvelope> {
         Ok(self.create_envelope(ToWorkletMessage::return_buffer(buffer_id)))
     }
     
+    pub fn enable_sample_ring(&self, samples: js_sys::SharedArrayBuffer, control: js_sys::SharedArrayBuffer) -> MessageConstructionResult<ToWorkletEnvelope> {
+        Ok(self.create_envelope(ToWorkletMessage::enable_sample_ring(samples, control)))
+    }
+    
     // FromWorkletMessage factory methods
     
     pub fn audio_data_batch(&self, data: AudioDataBatch) -> MessageConstructionResult<FromWorkletEnvelope> {
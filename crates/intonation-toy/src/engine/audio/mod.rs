@@ -1,8 +1,7 @@
-#![cfg(target_arch = "wasm32")]
-
 pub mod audio_error;
 pub mod worklet;
 pub mod worklet_message_handling;
+pub mod script_processor;
 pub mod audio_context;
 pub mod permission;
 pub mod pitch_detector;
@@ -14,14 +13,34 @@ pub mod data_types;
 pub mod signal_path;
 pub mod audio_pipeline;
 pub mod analysis;
+pub mod input_devices;
+pub mod recording;
+pub mod spectrum_analyzer;
+pub mod harmonic_analysis;
+pub mod gain_control;
+pub mod voice_activity;
+pub mod batch_scheduler;
+pub mod lifecycle;
+pub mod ring_buffer;
+pub mod recovery;
+pub mod loudness;
+pub mod formants;
+#[cfg(debug_assertions)]
+pub mod test_signal;
 
 
 
+#[cfg(target_arch = "wasm32")]
 pub use audio_pipeline_configs::{SignalGeneratorConfig, TonalCenterConfig};
+#[cfg(target_arch = "wasm32")]
 pub use data_types::{VolumeLevelData, VolumeAnalysis};
 pub use pitch_detector::PitchResult;
+#[cfg(target_arch = "wasm32")]
 pub use permission::AudioPermission;
+#[cfg(target_arch = "wasm32")]
 pub use signal_path::AudioSignalPath;
 
+#[cfg(target_arch = "wasm32")]
 use audio_error::AudioError;
+#[cfg(target_arch = "wasm32")]
 pub use volume_detector::VolumeDetector;
@@ -1,13 +1,20 @@
 #![cfg(target_arch = "wasm32")]
 
+use std::collections::VecDeque;
 use super::pitch_detector::{PitchDetector, PitchDetectorConfig, PitchResult};
 
 pub type PitchAnalysisError = String;
 
 /// Real-time pitch analysis coordinator.
+///
+/// Incoming samples are collected into a sliding window that is analyzed
+/// every `hop_size` samples once it has filled up.
 pub struct PitchAnalyzer {
     pitch_detector: PitchDetector,
     analysis_buffer: Vec<f32>,
+    history: VecDeque<f32>,
+    samples_since_analysis: usize,
+    latest_result: Option<PitchResult>,
 }
 
 impl PitchAnalyzer {
@@ -20,19 +27,54 @@ impl PitchAnalyzer {
         Ok(Self {
             pitch_detector,
             analysis_buffer: vec![0.0; sample_window_size],
+            history: VecDeque::with_capacity(sample_window_size),
+            samples_since_analysis: 0,
+            latest_result: None,
         })
     }
 
+    /// Add samples to the window and return the result of the most recent analysis
     pub fn analyze_samples(&mut self, samples: &[f32]) -> Option<PitchResult> {
-        assert_eq!(samples.len(), self.analysis_buffer.len(), 
-                   "Expected {} samples, got {}", self.analysis_buffer.len(), samples.len());
+        let window_size = self.analysis_buffer.len();
+        let skip = samples.len().saturating_sub(window_size);
+        self.history.extend(&samples[skip..]);
+        let overflow = self.history.len().saturating_sub(window_size);
+        self.history.drain(..overflow);
+        self.samples_since_analysis += samples.len();
 
-        self.analysis_buffer.copy_from_slice(samples);
-        
-        crate::profile!("pitch_detector.analyze", 
-            self.pitch_detector.analyze(&self.analysis_buffer)
-        )
+        if self.history.len() == window_size && self.samples_since_analysis >= self.pitch_detector.config().hop_size {
+            self.samples_since_analysis = 0;
+            for (target, sample) in self.analysis_buffer.iter_mut().zip(&self.history) {
+                *target = *sample;
+            }
+
+            self.latest_result = crate::profile!("pitch_detector.analyze", 
+                self.pitch_detector.analyze(&self.analysis_buffer)
+            );
+        }
+
+        self.latest_result.clone()
     }
 
-}
+    pub fn configure(&mut self, config: PitchDetectorConfig) -> Result<(), PitchAnalysisError> {
+        let sample_window_size = config.sample_window_size;
+        self.pitch_detector.configure(config)?;
+
+        if sample_window_size != self.analysis_buffer.len() {
+            self.analysis_buffer = vec![0.0; sample_window_size];
+            let overflow = self.history.len().saturating_sub(sample_window_size);
+            self.history.drain(..overflow);
+            self.latest_result = None;
+        }
+        Ok(())
+    }
+
+    pub fn config(&self) -> &PitchDetectorConfig {
+        self.pitch_detector.config()
+    }
 
+    pub fn sample_rate(&self) -> u32 {
+        self.pitch_detector.sample_rate()
+    }
+
+}
@@ -1,54 +1,109 @@
-#![cfg(target_arch = "wasm32")]
-
-use pitch_detection::detector::{yin::YINDetector, PitchDetector as PitchDetectorTrait};
+use pitch_detection::detector::{
+    autocorrelation::AutocorrelationDetector,
+    mcleod::McLeodDetector,
+    yin::YINDetector,
+    PitchDetector as PitchDetectorTrait,
+};
 use crate::app_config::{POWER_THRESHOLD, CLARITY_THRESHOLD};
+use crate::common::shared_types::PitchAlgorithm;
 
-use crate::app_config::BUFFER_SIZE;
+use crate::app_config::{AUDIO_CHUNK_SIZE, BUFFER_SIZE, MIN_ANALYSIS_WINDOW_SIZE, MAX_ANALYSIS_WINDOW_SIZE};
 
 pub type PitchDetectionError = String;
 
 #[derive(Debug, Clone)]
 pub struct PitchResult {
     pub frequency: f32,
+    /// Strength of the periodicity behind the estimate, 0-1
+    pub clarity: f32,
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PitchDetectorConfig {
+    pub algorithm: PitchAlgorithm,
     pub power_threshold: f32,
     pub clarity_threshold: f32,
     pub sample_window_size: usize,
     pub padding_size: usize,
+    /// Number of new samples between two analyses of the window
+    pub hop_size: usize,
 }
 
 impl Default for PitchDetectorConfig {
     fn default() -> Self {
         Self {
+            algorithm: PitchAlgorithm::default(),
             power_threshold: POWER_THRESHOLD,
             clarity_threshold: CLARITY_THRESHOLD,
             sample_window_size: BUFFER_SIZE,
             padding_size: BUFFER_SIZE / 2,
+            hop_size: BUFFER_SIZE,
         }
     }
 }
 
 pub struct PitchDetector {
     config: PitchDetectorConfig,
-    detector: YINDetector<f32>,
+    detector: Box<dyn PitchDetectorTrait<f32>>,
     sample_rate: u32,
 }
 
 impl PitchDetector {
     pub fn new(config: PitchDetectorConfig, sample_rate: u32) -> Result<Self, PitchDetectionError> {
-        if config.sample_window_size % 128 != 0 {
+        Self::validate(&config, sample_rate)?;
+
+        Ok(Self {
+            detector: create_detector(&config),
+            config,
+            sample_rate,
+        })
+    }
+
+    /// Replace the algorithm, thresholds or window, keeping the current detector on error
+    pub fn configure(&mut self, config: PitchDetectorConfig) -> Result<(), PitchDetectionError> {
+        Self::validate(&config, self.sample_rate)?;
+
+        self.detector = create_detector(&config);
+        self.config = config;
+        Ok(())
+    }
+
+    pub fn config(&self) -> &PitchDetectorConfig {
+        &self.config
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn validate(config: &PitchDetectorConfig, sample_rate: u32) -> Result<(), PitchDetectionError> {
+        if !config.sample_window_size.is_multiple_of(AUDIO_CHUNK_SIZE) {
+            return Err(format!(
+                "Sample window size must be a multiple of {}, got {}",
+                AUDIO_CHUNK_SIZE, config.sample_window_size
+            ));
+        }
+
+        if !(MIN_ANALYSIS_WINDOW_SIZE..=MAX_ANALYSIS_WINDOW_SIZE).contains(&config.sample_window_size) {
             return Err(format!(
-                "Sample window size must be a multiple of 128, got {}",
-                config.sample_window_size
+                "Sample window size must be between {} and {}, got {}",
+                MIN_ANALYSIS_WINDOW_SIZE, MAX_ANALYSIS_WINDOW_SIZE, config.sample_window_size
             ));
         }
 
-        if config.sample_window_size == 0 {
-            return Err("Sample window size cannot be zero".to_string());
+        if config.hop_size == 0 || !config.hop_size.is_multiple_of(AUDIO_CHUNK_SIZE) {
+            return Err(format!(
+                "Hop size must be a positive multiple of {}, got {}",
+                AUDIO_CHUNK_SIZE, config.hop_size
+            ));
+        }
+
+        if config.hop_size > config.sample_window_size {
+            return Err(format!(
+                "Hop size ({}) cannot be larger than sample window size ({})",
+                config.hop_size, config.sample_window_size
+            ));
         }
 
         if sample_rate == 0 {
@@ -69,14 +124,7 @@ impl PitchDetector {
             ));
         }
 
-
-        let yin_detector = YINDetector::new(config.sample_window_size, config.padding_size);
-
-        Ok(Self {
-            config,
-            detector: yin_detector,
-            sample_rate,
-        })
+        Ok(())
     }
 
     pub fn analyze(&mut self, samples: &[f32]) -> Option<PitchResult> {
@@ -87,7 +135,17 @@ impl PitchDetector {
 
         result.map(|pitch_info| PitchResult {
             frequency: pitch_info.frequency,
+            clarity: pitch_info.clarity,
         })
     }
 }
 
+fn create_detector(config: &PitchDetectorConfig) -> Box<dyn PitchDetectorTrait<f32>> {
+    let size = config.sample_window_size;
+    let padding = config.padding_size;
+    match config.algorithm {
+        PitchAlgorithm::McLeod => Box::new(McLeodDetector::new(size, padding)),
+        PitchAlgorithm::Yin => Box::new(YINDetector::new(size, padding)),
+        PitchAlgorithm::Autocorrelation => Box::new(AutocorrelationDetector::new(size, padding)),
+    }
+}
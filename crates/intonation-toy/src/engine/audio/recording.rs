@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use super::pitch_detector::{PitchDetector, PitchDetectorConfig};
+use crate::common::shared_types::RecordedPitchTrack;
+
+const WAV_HEADER_SIZE: usize = 44;
+const WAV_BITS_PER_SAMPLE: u16 = 16;
+
+/// Ring buffer of the most recent input samples
+///
+/// Samples are recorded as delivered by the AudioWorklet, i.e. after input
+/// conditioning. The oldest samples are dropped once the buffer is full.
+pub struct InputRecorder {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    sample_rate: u32,
+}
+
+impl InputRecorder {
+    pub fn new(max_seconds: f32, sample_rate: u32) -> Self {
+        let capacity = (max_seconds * sample_rate as f32) as usize;
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            sample_rate,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        let skip = samples.len().saturating_sub(self.capacity);
+        self.samples.extend(&samples[skip..]);
+        let overflow = self.samples.len().saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+    }
+
+    pub fn recorded_seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Samples between `from_seconds_ago` and `to_seconds_ago`, counted back
+    /// from the newest sample and clamped to what has been recorded
+    pub fn range(&self, from_seconds_ago: f32, to_seconds_ago: f32) -> Vec<f32> {
+        let len = self.samples.len();
+        let samples_ago = |seconds: f32| ((seconds * self.sample_rate as f32) as usize).min(len);
+        let start = len - samples_ago(from_seconds_ago);
+        let end = len - samples_ago(to_seconds_ago);
+        self.samples.range(start..end.max(start)).copied().collect()
+    }
+}
+
+/// Encode mono samples as a 16-bit PCM WAV file
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let bytes_per_sample = (WAV_BITS_PER_SAMPLE / 8) as u32;
+    let data_size = samples.len() as u32 * bytes_per_sample;
+
+    let mut wav = Vec::with_capacity(WAV_HEADER_SIZE + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * bytes_per_sample).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&(bytes_per_sample as u16).to_le_bytes()); // block align
+    wav.extend_from_slice(&WAV_BITS_PER_SAMPLE.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+/// Pitch of `samples` analyzed offline with the detector settings of the live
+/// input, one analysis window every `hop_ms`
+pub fn analyze_pitch_track(samples: &[f32], sample_rate: u32, config: PitchDetectorConfig, hop_ms: f64) -> Result<RecordedPitchTrack, String> {
+    let mut detector = PitchDetector::new(config, sample_rate)?;
+    let window_size = detector.config().sample_window_size;
+    let hop_size = ((hop_ms * sample_rate as f64 / 1000.0) as usize).max(1);
+    let frequencies = samples.windows(window_size)
+        .step_by(hop_size)
+        .map(|window| detector.analyze(window).map(|result| result.frequency))
+        .collect();
+    Ok(RecordedPitchTrack {
+        frame_ms: hop_size as f64 * 1000.0 / sample_rate as f64,
+        frequencies,
+    })
+}
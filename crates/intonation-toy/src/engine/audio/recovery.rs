@@ -0,0 +1,102 @@
+#![cfg(target_arch = "wasm32")]
+
+use crate::app_config::{RECOVERY_INITIAL_DELAY_MS, RECOVERY_MAX_DELAY_MS, RECOVERY_STABLE_MS, WORKLET_STALL_TIMEOUT_MS};
+use crate::common::shared_types::{RecoveryEvent, RecoveryReason};
+
+/// Watches the audio graph for failures and paces restarts
+///
+/// The engine reports progress every frame with `observe`, which returns the
+/// reason once a restart is due. Restart delays double with each attempt and
+/// start over once audio has run for a while after a restart.
+pub struct RecoverySupervisor {
+    last_batches: u32,
+    last_progress_ms: f64,
+    attempt: u32,
+    restarted_at_ms: Option<f64>,
+    scheduled: Option<(RecoveryReason, f64)>,
+    restarting: Option<RecoveryReason>,
+    events: Vec<RecoveryEvent>,
+}
+
+impl RecoverySupervisor {
+    pub fn new(now_ms: f64) -> Self {
+        Self {
+            last_batches: 0,
+            last_progress_ms: now_ms,
+            attempt: 0,
+            restarted_at_ms: None,
+            scheduled: None,
+            restarting: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Check the graph's health; returns the reason when a restart should start now
+    ///
+    /// Only frames where the AudioContext is running count towards a stall.
+    pub fn observe(&mut self, now_ms: f64, batches_processed: u32, input_live: bool, audio_running: bool) -> Option<RecoveryReason> {
+        if !audio_running || batches_processed != self.last_batches {
+            self.last_batches = batches_processed;
+            self.last_progress_ms = now_ms;
+        }
+
+        if self.restarting.is_some() {
+            return None;
+        }
+
+        if let Some((reason, due_ms)) = self.scheduled {
+            if now_ms < due_ms {
+                return None;
+            }
+            self.scheduled = None;
+            self.restarting = Some(reason);
+            return Some(reason);
+        }
+
+        if let Some(restarted_at_ms) = self.restarted_at_ms
+            && now_ms - restarted_at_ms >= RECOVERY_STABLE_MS
+        {
+            self.restarted_at_ms = None;
+            self.attempt = 0;
+        }
+
+        let reason = if !input_live {
+            RecoveryReason::InputEnded
+        } else if now_ms - self.last_progress_ms >= WORKLET_STALL_TIMEOUT_MS {
+            RecoveryReason::WorkletStalled
+        } else {
+            return None;
+        };
+        self.events.push(RecoveryEvent::Detected { reason });
+        self.schedule(now_ms, reason);
+        None
+    }
+
+    pub fn restart_succeeded(&mut self, now_ms: f64, batches_processed: u32) {
+        let Some(reason) = self.restarting.take() else { return };
+        self.last_batches = batches_processed;
+        self.last_progress_ms = now_ms;
+        self.restarted_at_ms = Some(now_ms);
+        self.events.push(RecoveryEvent::Restarted { reason, attempt: self.attempt });
+    }
+
+    pub fn restart_failed(&mut self, now_ms: f64, error: String) {
+        let Some(reason) = self.restarting.take() else { return };
+        self.events.push(RecoveryEvent::RestartFailed { reason, attempt: self.attempt, error });
+        self.schedule(now_ms, reason);
+    }
+
+    /// Events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<RecoveryEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn schedule(&mut self, now_ms: f64, reason: RecoveryReason) {
+        self.attempt += 1;
+        let delay_ms = RECOVERY_INITIAL_DELAY_MS
+            .saturating_mul(1 << (self.attempt - 1).min(16))
+            .min(RECOVERY_MAX_DELAY_MS);
+        self.scheduled = Some((reason, now_ms + delay_ms as f64));
+        self.events.push(RecoveryEvent::RestartScheduled { reason, attempt: self.attempt, delay_ms });
+    }
+}
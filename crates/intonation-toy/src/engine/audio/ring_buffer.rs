@@ -0,0 +1,97 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Lock-free sample transport from the AudioWorklet over shared memory
+//!
+//! The worklet writes conditioned samples into a SharedArrayBuffer and
+//! advances a write counter with `Atomics.store`; the main thread reads
+//! whatever has arrived each frame and advances the read counter. Unlike the
+//! message port path nothing is posted, transferred or allocated per batch.
+//! SharedArrayBuffer is only available when the page is cross-origin isolated
+//! (served with COOP `same-origin` and COEP `require-corp`).
+
+use js_sys::{Atomics, Float32Array, Int32Array, Reflect, SharedArrayBuffer};
+use wasm_bindgen::JsCast;
+
+/// Slots in the control block shared with `SharedSampleRing` in audio-processor.js
+const WRITE_TOTAL: u32 = 0;
+const READ_TOTAL: u32 = 1;
+const OVERFLOW_COUNT: u32 = 2;
+const CONTROL_SLOTS: u32 = 3;
+
+/// Single-producer single-consumer ring of samples
+///
+/// The counters are running totals that wrap at `i32`; the capacity is a
+/// power of two so positions are `total & (capacity - 1)`.
+pub struct SharedSampleRing {
+    samples: Float32Array,
+    control: Int32Array,
+    capacity: u32,
+}
+
+impl SharedSampleRing {
+    /// Whether the page can share memory with the worklet
+    pub fn is_supported() -> bool {
+        Reflect::get(&js_sys::global(), &"crossOriginIsolated".into())
+            .is_ok_and(|isolated| isolated.is_truthy())
+    }
+
+    pub fn new(capacity: usize) -> Result<Self, String> {
+        if !capacity.is_power_of_two() {
+            return Err(format!("Ring capacity {} is not a power of two", capacity));
+        }
+        let capacity = capacity as u32;
+        let sample_bytes = capacity * std::mem::size_of::<f32>() as u32;
+        let control_bytes = CONTROL_SLOTS * std::mem::size_of::<i32>() as u32;
+        Ok(Self {
+            samples: Float32Array::new(&SharedArrayBuffer::new(sample_bytes)),
+            control: Int32Array::new(&SharedArrayBuffer::new(control_bytes)),
+            capacity,
+        })
+    }
+
+    /// Shared sample storage, for handing to the worklet
+    pub fn sample_buffer(&self) -> SharedArrayBuffer {
+        self.samples.buffer().unchecked_into()
+    }
+
+    /// Shared control block, for handing to the worklet
+    pub fn control_buffer(&self) -> SharedArrayBuffer {
+        self.control.buffer().unchecked_into()
+    }
+
+    /// Samples written by the worklet and not yet read
+    pub fn available(&self) -> usize {
+        let write_total = self.load(WRITE_TOTAL);
+        let read_total = self.load(READ_TOTAL);
+        write_total.wrapping_sub(read_total) as u32 as usize
+    }
+
+    /// Move `count` samples into `samples`, which is resized to fit
+    ///
+    /// Callers check `available` first; reading more than is available
+    /// returns stale samples.
+    pub fn read(&self, count: usize, samples: &mut Vec<f32>) {
+        let read_total = self.load(READ_TOTAL);
+        let start = read_total as u32 & (self.capacity - 1);
+        let count = count.min(self.capacity as usize) as u32;
+        let first = count.min(self.capacity - start);
+
+        samples.resize(count as usize, 0.0);
+        let (head, tail) = samples.split_at_mut(first as usize);
+        self.samples.subarray(start, start + first).copy_to(head);
+        if !tail.is_empty() {
+            self.samples.subarray(0, count - first).copy_to(tail);
+        }
+
+        let _ = Atomics::store(&self.control, READ_TOTAL, read_total.wrapping_add(count as i32));
+    }
+
+    /// Chunks the worklet dropped because the ring was full
+    pub fn overflow_count(&self) -> u32 {
+        self.load(OVERFLOW_COUNT) as u32
+    }
+
+    fn load(&self, slot: u32) -> i32 {
+        Atomics::load(&self.control, slot).unwrap_or(0)
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Input capture for browsers without AudioWorklet
+//!
+//! A ScriptProcessorNode hands the input to the main thread in callbacks of
+//! `SCRIPT_PROCESSOR_BUFFER_SIZE` samples. They are collected into batches of
+//! the configured size and analyzed like the batches of the worklet, without
+//! the worklet's noise gate and filters.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{AudioProcessingEvent, ScriptProcessorNode};
+use super::VolumeDetector;
+use super::pitch_analyzer::PitchAnalyzer;
+use super::worklet_message_handling::{MessageHandlerState, process_audio_samples};
+
+struct CaptureState {
+    enabled: bool,
+    batch_size: usize,
+    /// Samples waiting for a full batch
+    pending: Vec<f32>,
+}
+
+pub(super) struct ScriptProcessorCapture {
+    node: ScriptProcessorNode,
+    state: Rc<RefCell<CaptureState>>,
+    _closure: Closure<dyn FnMut(AudioProcessingEvent)>,
+}
+
+impl ScriptProcessorCapture {
+    /// Analyze the input of `node` once enabled
+    pub(super) fn start(
+        node: ScriptProcessorNode,
+        handler_state: Rc<RefCell<MessageHandlerState>>,
+        volume_detector: Rc<RefCell<VolumeDetector>>,
+        pitch_analyzer: Rc<RefCell<PitchAnalyzer>>,
+        batch_size: usize,
+    ) -> Self {
+        let state = Rc::new(RefCell::new(CaptureState { enabled: false, batch_size, pending: Vec::new() }));
+        let callback_state = state.clone();
+        let closure = Closure::<dyn FnMut(AudioProcessingEvent)>::new(move |event: AudioProcessingEvent| {
+            let mut state = callback_state.borrow_mut();
+            if !state.enabled {
+                return;
+            }
+            let Ok(input) = event.input_buffer() else { return };
+            let offset = state.pending.len();
+            state.pending.resize(offset + input.length() as usize, 0.0);
+            if input.copy_from_channel(&mut state.pending[offset..], 0).is_err() {
+                state.pending.truncate(offset);
+                return;
+            }
+
+            let mut batch = std::mem::take(&mut handler_state.borrow_mut().sample_buffer);
+            while state.pending.len() >= state.batch_size {
+                let batch_size = state.batch_size;
+                batch.clear();
+                batch.extend(state.pending.drain(..batch_size));
+                process_audio_samples(&batch, &handler_state, &volume_detector, &pitch_analyzer);
+                handler_state.borrow_mut().batches_processed += 1;
+            }
+            handler_state.borrow_mut().sample_buffer = batch;
+        });
+        node.set_onaudioprocess(Some(closure.as_ref().unchecked_ref()));
+        Self { node, state, _closure: closure }
+    }
+
+    pub(super) fn set_enabled(&self, enabled: bool) {
+        let mut state = self.state.borrow_mut();
+        state.enabled = enabled;
+        state.pending.clear();
+    }
+
+    pub(super) fn set_batch_size(&self, batch_size: usize) {
+        self.state.borrow_mut().batch_size = batch_size.max(1);
+    }
+}
+
+impl Drop for ScriptProcessorCapture {
+    fn drop(&mut self) {
+        self.node.set_onaudioprocess(None);
+    }
+}
@@ -1,6 +1,23 @@
 #![cfg(target_arch = "wasm32")]
 
-use web_sys::{AudioContext, GainNode, AudioWorkletNode, MediaStreamAudioSourceNode, OscillatorNode, AnalyserNode};
+use web_sys::{AudioContext, AudioNode, GainNode, AudioWorkletNode, MediaStreamAudioSourceNode, OscillatorNode, AnalyserNode, ScriptProcessorNode};
+
+/// The node that hands input samples to the main thread
+#[derive(Clone)]
+pub enum CaptureNode {
+    Worklet(AudioWorkletNode),
+    /// Fallback for browsers without AudioWorklet
+    ScriptProcessor(ScriptProcessorNode),
+}
+
+impl CaptureNode {
+    pub fn node(&self) -> &AudioNode {
+        match self {
+            CaptureNode::Worklet(worklet) => worklet,
+            CaptureNode::ScriptProcessor(script_processor) => script_processor,
+        }
+    }
+}
 
 /// Represents the complete audio signal flow with all Web Audio API nodes
 /// 
@@ -9,56 +26,86 @@ use web_sys::{AudioContext, GainNode, AudioWorkletNode, MediaStreamAudioSourceNo
 pub struct AudioSignalPath {
     pub user_input: MediaStreamAudioSourceNode,
     pub user_input_mute: GainNode,
-    pub worklet: AudioWorkletNode,
+    pub input_gain: GainNode,
+    pub capture: CaptureNode,
     pub analyser: AnalyserNode,
+    pub spectrum_analyser: AnalyserNode,
     pub test_signal_osc: OscillatorNode,
     pub test_signal_gain: GainNode,
     pub test_signal_mute: GainNode,
     pub tonal_center_osc: OscillatorNode,
     pub tonal_center_gain: GainNode,
+    pub reference_tone_osc: OscillatorNode,
+    pub reference_tone_envelope: GainNode,
+    pub metronome_osc: OscillatorNode,
+    pub metronome_envelope: GainNode,
 }
 
 impl AudioSignalPath {
     pub fn new(
         context: AudioContext,
         user_input: MediaStreamAudioSourceNode,
-        worklet: AudioWorkletNode,
+        capture: CaptureNode,
     ) -> Self {
 
         // Create
         let user_input_mute = context.create_gain().unwrap();
+        let input_gain = context.create_gain().unwrap();
         let test_signal_osc = context.create_oscillator().unwrap();
         let test_signal_gain = context.create_gain().unwrap();
         let test_signal_mute = context.create_gain().unwrap();
         let analyser = context.create_analyser().unwrap();
+        let spectrum_analyser = context.create_analyser().unwrap();
         let tonal_center_osc = context.create_oscillator().unwrap();
         let tonal_center_gain = context.create_gain().unwrap();
+        let reference_tone_osc = context.create_oscillator().unwrap();
+        let reference_tone_envelope = context.create_gain().unwrap();
+        let metronome_osc = context.create_oscillator().unwrap();
+        let metronome_envelope = context.create_gain().unwrap();
 
         // Connect
         user_input.connect_with_audio_node(&user_input_mute).unwrap();
-        user_input_mute.connect_with_audio_node(&analyser).unwrap();
+        user_input_mute.connect_with_audio_node(&input_gain).unwrap();
+        input_gain.connect_with_audio_node(&analyser).unwrap();
         test_signal_osc.connect_with_audio_node(&test_signal_gain).unwrap();
         test_signal_gain.connect_with_audio_node(&test_signal_mute).unwrap();
         test_signal_mute.connect_with_audio_node(&context.destination()).unwrap();
         test_signal_mute.connect_with_audio_node(&analyser).unwrap();
-        analyser.connect_with_audio_node(&worklet).unwrap();
+        analyser.connect_with_audio_node(capture.node()).unwrap();
+        if let CaptureNode::ScriptProcessor(script_processor) = &capture {
+            // Only runs while connected to the destination; its output is silent
+            script_processor.connect_with_audio_node(&context.destination()).unwrap();
+        }
+        analyser.connect_with_audio_node(&spectrum_analyser).unwrap();
         tonal_center_osc.connect_with_audio_node(&tonal_center_gain).unwrap();
         tonal_center_gain.connect_with_audio_node(&context.destination()).unwrap();
+        reference_tone_osc.connect_with_audio_node(&reference_tone_envelope).unwrap();
+        reference_tone_envelope.connect_with_audio_node(&context.destination()).unwrap();
+        metronome_osc.connect_with_audio_node(&metronome_envelope).unwrap();
+        metronome_envelope.connect_with_audio_node(&context.destination()).unwrap();
 
-        // user_input -> user_intput_mute -> analyser -> worklet
-        // test_signal_osc -> test_signal_gain -> test_signal_mute -> [analyser -> worklet] // [destination]
+        // user_input -> user_input_mute -> input_gain -> analyser -> capture // [spectrum_analyser]
+        // test_signal_osc -> test_signal_gain -> test_signal_mute -> [analyser -> capture] // [destination]
         // tonal_center_osc -> tonal_center_gain -> destination
+        // reference_tone_osc -> reference_tone_envelope -> destination
+        // metronome_osc -> metronome_envelope -> destination
 
         Self {
             user_input,
             user_input_mute,
+            input_gain,
             test_signal_osc,
             test_signal_gain,
             test_signal_mute,
-            worklet,
+            capture,
             analyser,
+            spectrum_analyser,
             tonal_center_osc,
             tonal_center_gain,
+            reference_tone_osc,
+            reference_tone_envelope,
+            metronome_osc,
+            metronome_envelope,
         }
     }
 }
@@ -0,0 +1,76 @@
+#![cfg(target_arch = "wasm32")]
+
+use web_sys::AnalyserNode;
+use crate::common::shared_types::Spectrum;
+
+/// Reads magnitude spectra from an AnalyserNode and reduces them to a fixed number of bins
+///
+/// Each output bin is the mean of a group of adjacent analyser bins, with
+/// decibels mapped linearly from the analyser's range onto 0.0-1.0.
+pub struct SpectrumAnalyzer {
+    node: AnalyserNode,
+    decibels: Vec<f32>,
+    bin_count: usize,
+    sample_rate: f32,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(analyser_node: AnalyserNode, sample_rate: f32, bin_count: usize) -> Self {
+        let decibels = vec![0.0f32; analyser_node.frequency_bin_count() as usize];
+        Self {
+            node: analyser_node,
+            decibels,
+            bin_count,
+            sample_rate,
+        }
+    }
+
+    /// Number of bins the analyser provides, the upper limit for `set_bin_count`
+    pub fn max_bin_count(&self) -> usize {
+        self.decibels.len()
+    }
+
+    pub fn set_bin_count(&mut self, bin_count: usize) -> Result<(), String> {
+        if bin_count == 0 || bin_count > self.max_bin_count() || !self.max_bin_count().is_multiple_of(bin_count) {
+            return Err(format!("Bin count {} must divide {}", bin_count, self.max_bin_count()));
+        }
+        self.bin_count = bin_count;
+        Ok(())
+    }
+
+    /// Full-resolution decibel spectrum read by the last `analyze`
+    pub fn decibels(&self) -> &[f32] {
+        &self.decibels
+    }
+
+    pub fn min_decibels(&self) -> f32 {
+        self.node.min_decibels() as f32
+    }
+
+    /// Width of the full-resolution analyser bins
+    pub fn resolution_hz(&self) -> f32 {
+        self.sample_rate / self.node.fft_size() as f32
+    }
+
+    pub fn analyze(&mut self) -> Spectrum {
+        self.node.get_float_frequency_data(&mut self.decibels);
+
+        let min_db = self.node.min_decibels() as f32;
+        let range_db = self.node.max_decibels() as f32 - min_db;
+        let group_size = self.decibels.len() / self.bin_count;
+
+        let magnitudes = self.decibels
+            .chunks_exact(group_size)
+            .map(|group| {
+                let sum: f32 = group.iter().map(|db| ((db - min_db) / range_db).clamp(0.0, 1.0)).sum();
+                sum / group_size as f32
+            })
+            .collect();
+
+        Spectrum {
+            magnitudes,
+            bin_width_hz: self.resolution_hz() * group_size as f32,
+            range_db,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+#![cfg(target_arch = "wasm32")]
+#![cfg(debug_assertions)]
+
+//! Sweeps, vibrato and noise for the debug test signal, and how well the
+//! pitch detector follows them
+
+use web_sys::{AudioBufferSourceNode, AudioContext, AudioScheduledSourceNode, GainNode, OscillatorNode};
+use crate::presentation::{ConfigureTestSignal, SweepShape};
+use super::AudioSignalPath;
+
+/// Length of the looped white noise buffer
+const NOISE_BUFFER_SECONDS: f32 = 2.0;
+
+/// RMS of white noise uniformly distributed in -1..1
+const UNIFORM_NOISE_RMS: f32 = 0.577_350_3;
+
+/// A configured test signal and the extra nodes that modulate it
+pub struct ActiveTestSignal {
+    config: ConfigureTestSignal,
+    started_at: f64,
+    vibrato: Option<(OscillatorNode, GainNode)>,
+    noise: Option<(AudioBufferSourceNode, GainNode)>,
+}
+
+impl ActiveTestSignal {
+    /// Schedule the test oscillator and start the vibrato and noise sources
+    pub fn start(audio_context: &AudioContext, signal_path: &AudioSignalPath, config: &ConfigureTestSignal) -> Result<Self, String> {
+        let now = audio_context.current_time();
+        schedule_frequency(&signal_path.test_signal_osc, config, now)
+            .map_err(|e| format!("Failed to schedule test signal frequency: {:?}", e))?;
+
+        let vibrato = config.vibrato
+            .map(|vibrato| start_vibrato(audio_context, &signal_path.test_signal_osc, vibrato.rate_hz, vibrato.depth_cents, now))
+            .transpose()
+            .map_err(|e| format!("Failed to start vibrato: {:?}", e))?;
+
+        let signal_rms = config.volume / 100.0 / std::f32::consts::SQRT_2;
+        let noise = config.snr_db
+            .map(|snr_db| {
+                let noise_rms = signal_rms / 10f32.powf(snr_db / 20.0);
+                start_noise(audio_context, &signal_path.test_signal_mute, noise_rms / UNIFORM_NOISE_RMS, now)
+            })
+            .transpose()
+            .map_err(|e| format!("Failed to start noise: {:?}", e))?;
+
+        Ok(Self { config: config.clone(), started_at: now, vibrato, noise })
+    }
+
+    /// Stop and disconnect the vibrato and noise sources
+    pub fn stop(&self) {
+        if let Some((oscillator, depth)) = &self.vibrato {
+            let _ = oscillator.stop();
+            let _ = depth.disconnect();
+        }
+        if let Some((source, gain)) = &self.noise {
+            let source: &AudioScheduledSourceNode = source;
+            let _ = source.stop();
+            let _ = gain.disconnect();
+        }
+    }
+
+    /// Frequency the test oscillator produces at `time` on the AudioContext clock
+    pub fn frequency_at(&self, time: f64) -> f32 {
+        let elapsed = (time - self.started_at).max(0.0) as f32;
+
+        let center = match self.config.sweep {
+            Some(sweep) => {
+                let progress = (elapsed / sweep.duration_seconds).min(1.0);
+                match sweep.shape {
+                    SweepShape::Linear => sweep.start_frequency + (sweep.end_frequency - sweep.start_frequency) * progress,
+                    SweepShape::Logarithmic => sweep.start_frequency * (sweep.end_frequency / sweep.start_frequency).powf(progress),
+                }
+            }
+            None => self.config.frequency,
+        };
+
+        match self.config.vibrato {
+            Some(vibrato) => {
+                let cents = vibrato.depth_cents * (std::f32::consts::TAU * vibrato.rate_hz * elapsed).sin();
+                center * 2f32.powf(cents / 1200.0)
+            }
+            None => center,
+        }
+    }
+}
+
+fn schedule_frequency(oscillator: &OscillatorNode, config: &ConfigureTestSignal, now: f64) -> Result<(), wasm_bindgen::JsValue> {
+    let frequency = oscillator.frequency();
+    frequency.cancel_scheduled_values(now)?;
+
+    let Some(sweep) = config.sweep else {
+        frequency.set_value_at_time(config.frequency, now)?;
+        return Ok(());
+    };
+
+    let end_time = now + sweep.duration_seconds as f64;
+    frequency.set_value_at_time(sweep.start_frequency, now)?;
+    match sweep.shape {
+        SweepShape::Linear => frequency.linear_ramp_to_value_at_time(sweep.end_frequency, end_time)?,
+        SweepShape::Logarithmic => frequency.exponential_ramp_to_value_at_time(sweep.end_frequency, end_time)?,
+    };
+    Ok(())
+}
+
+/// Modulate the oscillator's detune with a sine LFO starting at zero phase
+fn start_vibrato(audio_context: &AudioContext, oscillator: &OscillatorNode, rate_hz: f32, depth_cents: f32, now: f64) -> Result<(OscillatorNode, GainNode), wasm_bindgen::JsValue> {
+    let lfo = audio_context.create_oscillator()?;
+    lfo.frequency().set_value(rate_hz);
+    let depth = audio_context.create_gain()?;
+    depth.gain().set_value(depth_cents);
+
+    lfo.connect_with_audio_node(&depth)?;
+    depth.connect_with_audio_param(&oscillator.detune())?;
+    lfo.start_with_when(now)?;
+    Ok((lfo, depth))
+}
+
+/// Loop white noise into `destination` at the given gain
+fn start_noise(audio_context: &AudioContext, destination: &GainNode, gain: f32, now: f64) -> Result<(AudioBufferSourceNode, GainNode), wasm_bindgen::JsValue> {
+    let sample_rate = audio_context.sample_rate();
+    let length = (sample_rate * NOISE_BUFFER_SECONDS) as u32;
+    let buffer = audio_context.create_buffer(1, length, sample_rate)?;
+    let samples: Vec<f32> = (0..length).map(|_| js_sys::Math::random() as f32 * 2.0 - 1.0).collect();
+    buffer.copy_to_channel(&samples, 0)?;
+
+    let source = audio_context.create_buffer_source()?;
+    source.set_buffer(Some(&buffer));
+    source.set_loop(true);
+    let noise_gain = audio_context.create_gain()?;
+    noise_gain.gain().set_value(gain);
+
+    source.connect_with_audio_node(&noise_gain)?;
+    noise_gain.connect_with_audio_node(destination)?;
+    source.start_with_when(now)?;
+    Ok((source, noise_gain))
+}
+
+/// Running comparison of detected pitch against the known test signal frequency
+///
+/// Readings lag the signal by about one analysis window, so fast sweeps show
+/// a systematic offset.
+#[derive(Debug, Clone, Default)]
+pub struct TestSignalAccuracy {
+    frames: u32,
+    detected_frames: u32,
+    total_abs_cents: f32,
+    max_abs_cents: f32,
+}
+
+impl TestSignalAccuracy {
+    pub fn record(&mut self, expected_hz: f32, detected_hz: Option<f32>) {
+        self.frames += 1;
+        if let Some(detected_hz) = detected_hz {
+            let abs_cents = (1200.0 * (detected_hz / expected_hz).log2()).abs();
+            self.detected_frames += 1;
+            self.total_abs_cents += abs_cents;
+            self.max_abs_cents = self.max_abs_cents.max(abs_cents);
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        if self.detected_frames == 0 {
+            return format!("{} frames, nothing detected", self.frames);
+        }
+        format!(
+            "{}/{} frames detected, mean error {:.1} cents, max {:.1} cents",
+            self.detected_frames,
+            self.frames,
+            self.total_abs_cents / self.detected_frames as f32,
+            self.max_abs_cents,
+        )
+    }
+}
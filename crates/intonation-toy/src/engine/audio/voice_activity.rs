@@ -0,0 +1,56 @@
+#![cfg(target_arch = "wasm32")]
+
+use crate::app_config::{VAD_HANGOVER_MS, VAD_MAX_SPECTRAL_FLATNESS, VAD_MIN_RMS, VAD_VOICE_BAND_HZ};
+
+/// Decides whether the input holds a voiced sound worth reporting a pitch for
+///
+/// A frame is voiced when it is loud enough and its spectrum is peaky rather
+/// than noise-like, which rejects silence, breaths and unvoiced consonants.
+/// Voicing is held for a moment so short dips do not break up a note.
+pub struct VoiceActivityDetector {
+    /// Time of the last voiced frame in milliseconds; `None` before the first
+    last_voiced_ms: Option<f64>,
+}
+
+impl VoiceActivityDetector {
+    pub fn new() -> Self {
+        Self {
+            last_voiced_ms: None,
+        }
+    }
+
+    /// `decibels` is a full-resolution magnitude spectrum with bins of `bin_width_hz`
+    pub fn update(&mut self, now_ms: f64, rms: f32, decibels: &[f32], bin_width_hz: f32, min_decibels: f32) -> bool {
+        let is_voiced = rms >= VAD_MIN_RMS
+            && spectral_flatness(decibels, bin_width_hz, min_decibels) <= VAD_MAX_SPECTRAL_FLATNESS;
+
+        if is_voiced {
+            self.last_voiced_ms = Some(now_ms);
+        }
+        self.last_voiced_ms.is_some_and(|last_voiced_ms| now_ms - last_voiced_ms < VAD_HANGOVER_MS)
+    }
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Geometric over arithmetic mean of the power spectrum in the voice band
+///
+/// Close to 1.0 for white noise and close to 0.0 for harmonic sounds.
+fn spectral_flatness(decibels: &[f32], bin_width_hz: f32, min_decibels: f32) -> f32 {
+    let first = ((VAD_VOICE_BAND_HZ.0 / bin_width_hz) as usize).max(1);
+    let last = ((VAD_VOICE_BAND_HZ.1 / bin_width_hz) as usize).min(decibels.len());
+    if first >= last {
+        return 1.0;
+    }
+
+    let band = &decibels[first..last];
+    // In decibels the log of the geometric mean is the mean of the values
+    let mean_db = band.iter().map(|db| db.max(min_decibels)).sum::<f32>() / band.len() as f32;
+    let geometric_mean = 10f32.powf(mean_db / 10.0);
+    let arithmetic_mean = band.iter().map(|db| 10f32.powf(db.max(min_decibels) / 10.0)).sum::<f32>() / band.len() as f32;
+    geometric_mean / arithmetic_mean
+}
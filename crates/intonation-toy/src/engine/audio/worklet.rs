@@ -10,44 +10,88 @@ use wasm_bindgen::JsCast;
 use crate::common::dev_log;
 use super::{AudioError, volume_detector::VolumeDetector};
 use super::message_protocol::{AudioWorkletMessageFactory, ToWorkletMessage, MessageSerializer};
-use super::worklet_message_handling::{MessageHandlerState, handle_worklet_message};
+use super::ring_buffer::SharedSampleRing;
+use super::script_processor::ScriptProcessorCapture;
+use super::signal_path::CaptureNode;
+use super::worklet_message_handling::{MessageHandlerState, handle_worklet_message, process_audio_samples};
 
+/// Analysis of the samples of the capture node, which is the AudioWorklet
+/// unless the browser lacks it
 pub struct AudioWorkletManager {
-    worklet_node: web_sys::AudioWorkletNode,
+    capture: CaptureNode,
     handler_state: Rc<RefCell<MessageHandlerState>>,
     message_factory: AudioWorkletMessageFactory,
+    pitch_analyzer: Option<Rc<RefCell<super::pitch_analyzer::PitchAnalyzer>>>,
+    volume_detector: Option<Rc<RefCell<VolumeDetector>>>,
+    sample_ring: Option<SharedSampleRing>,
+    batch_size: usize,
     _message_closure: Option<wasm_bindgen::closure::Closure<dyn FnMut(MessageEvent)>>,
+    script_processor: Option<ScriptProcessorCapture>,
 }
 
 impl AudioWorkletManager {
-    pub fn new(worklet_node: web_sys::AudioWorkletNode) -> Result<Self, String> {
+    pub fn new(capture: CaptureNode) -> Result<Self, String> {
         Ok(Self {
             _message_closure: None,
+            script_processor: None,
             handler_state: Rc::new(RefCell::new(MessageHandlerState {
                 batches_processed: 0,
                 buffer_pool_stats: None,
                 last_volume_analysis: None,
                 latest_pitch_data: None,
+                recorder: None,
+                is_recording: false,
+                sample_buffer: Vec::new(),
+                loudness: None,
+                #[cfg(feature = "formant-estimation")]
+                formants: None,
             })),
             message_factory: AudioWorkletMessageFactory::new(),
-            worklet_node,
+            pitch_analyzer: None,
+            volume_detector: None,
+            sample_ring: None,
+            batch_size: crate::app_config::BUFFER_SIZE,
+            capture,
         })
     }
     
     pub fn setup_message_handling(&mut self, pitch_analyzer: super::pitch_analyzer::PitchAnalyzer, volume_detector: VolumeDetector) -> Result<(), AudioError> {
-        let worklet = &self.worklet_node;
         // Clean up existing closure and port handler
         self._message_closure = None;
+        self.script_processor = None;
         
+        // Capture fields needed for the message handler
+        let handler_state_clone = self.handler_state.clone();
+        handler_state_clone.borrow_mut().loudness = Some(super::loudness::LoudnessMeter::new(pitch_analyzer.sample_rate()));
+        #[cfg(feature = "formant-estimation")]
+        {
+            handler_state_clone.borrow_mut().formants = Some(super::formants::FormantEstimator::new(pitch_analyzer.sample_rate()));
+        }
+        let volume_detector_clone = Rc::new(RefCell::new(volume_detector));
+        let pitch_analyzer_clone = Rc::new(RefCell::new(pitch_analyzer));
+        self.pitch_analyzer = Some(pitch_analyzer_clone.clone());
+        self.volume_detector = Some(volume_detector_clone.clone());
+
+        let worklet = match &self.capture {
+            CaptureNode::Worklet(worklet) => worklet,
+            CaptureNode::ScriptProcessor(script_processor) => {
+                self.script_processor = Some(ScriptProcessorCapture::start(
+                    script_processor.clone(),
+                    handler_state_clone,
+                    volume_detector_clone,
+                    pitch_analyzer_clone,
+                    self.batch_size,
+                ));
+                dev_log!("✓ ScriptProcessorNode capture setup complete");
+                return Ok(());
+            }
+        };
+
         // Clear port message handler to disconnect previous closures
         let port = worklet.port()
             .map_err(|e| AudioError::Generic(format!("Failed to get AudioWorklet port: {:?}", e)))?;
         port.set_onmessage(None);
         
-        // Capture fields needed for the message handler
-        let handler_state_clone = self.handler_state.clone();
-        let volume_detector_clone = Rc::new(RefCell::new(volume_detector));
-        let pitch_analyzer_clone = Rc::new(RefCell::new(pitch_analyzer));
         let worklet_node_clone = worklet.clone();
         let message_factory_clone = self.message_factory.clone();
         
@@ -62,8 +106,6 @@ impl AudioWorkletManager {
             );
         }) as Box<dyn FnMut(MessageEvent)>);
         
-        let port = worklet.port()
-            .map_err(|e| AudioError::Generic(format!("Failed to get AudioWorklet port: {:?}", e)))?;
         port.set_onmessage(Some(closure.as_ref().unchecked_ref()));
         
         // Store the closure to prevent it from being dropped
@@ -72,8 +114,30 @@ impl AudioWorkletManager {
         dev_log!("✓ AudioWorklet message handler setup complete");
         Ok(())
     }
+
+    /// Apply a control message of the worklet to the ScriptProcessorNode capture
+    fn apply_to_script_processor(&self, message: ToWorkletMessage) -> Result<(), AudioError> {
+        let script_processor = self.script_processor.as_ref()
+            .ok_or_else(|| AudioError::Generic("Message handling is not set up".to_string()))?;
+        match message {
+            ToWorkletMessage::StartProcessing => script_processor.set_enabled(true),
+            ToWorkletMessage::StopProcessing => script_processor.set_enabled(false),
+            ToWorkletMessage::UpdateBatchConfig { config } => script_processor.set_batch_size(config.batch_size),
+            ToWorkletMessage::UpdateInputConditioning { .. } => {
+                dev_log!("Input conditioning needs the AudioWorklet, not applied");
+            }
+            ToWorkletMessage::ReturnBuffer { .. } => {}
+            ToWorkletMessage::EnableSampleRing { .. } => {
+                return Err(AudioError::Generic("The shared sample ring needs the AudioWorklet".to_string()));
+            }
+        }
+        Ok(())
+    }
     
     fn send_typed_control_message(&self, message: ToWorkletMessage) -> Result<(), AudioError> {
+        let CaptureNode::Worklet(worklet_node) = &self.capture else {
+            return self.apply_to_script_processor(message);
+        };
         let envelope = match message {
             ToWorkletMessage::StartProcessing => {
                 self.message_factory.start_processing()
@@ -87,16 +151,24 @@ impl AudioWorkletManager {
                 self.message_factory.update_batch_config(config)
                     .map_err(|e| AudioError::Generic(format!("Failed to create batch config message: {:?}", e)))?
             }
+            ToWorkletMessage::UpdateInputConditioning { config } => {
+                self.message_factory.update_input_conditioning(config)
+                    .map_err(|e| AudioError::Generic(format!("Failed to create input conditioning message: {:?}", e)))?
+            }
             ToWorkletMessage::ReturnBuffer { buffer_id } => {
                 self.message_factory.return_buffer(buffer_id)
                     .map_err(|e| AudioError::Generic(format!("Failed to create return buffer message: {:?}", e)))?
             }
+            ToWorkletMessage::EnableSampleRing { samples, control } => {
+                self.message_factory.enable_sample_ring(samples, control)
+                    .map_err(|e| AudioError::Generic(format!("Failed to create sample ring message: {:?}", e)))?
+            }
         };
         
         let serializer = MessageSerializer::new();
         let js_message = serializer.serialize_envelope(&envelope)
             .map_err(|e| AudioError::Generic(format!("Failed to serialize message: {:?}", e)))?;
-        let port = &self.worklet_node.port()
+        let port = &worklet_node.port()
             .map_err(|e| AudioError::Generic(format!("Failed to get AudioWorklet port: {:?}", e)))?;
         port.post_message(&js_message)
             .map_err(|e| AudioError::Generic(format!("Failed to send message: {:?}", e)))?;
@@ -125,6 +197,51 @@ impl AudioWorkletManager {
         Ok(())
     }
     
+    /// Switch the worklet from posting batches to writing into a shared ring
+    ///
+    /// Requires cross-origin isolation. Samples then only reach the analyzers
+    /// through `drain_sample_ring`.
+    pub fn enable_sample_ring(&mut self) -> Result<(), AudioError> {
+        if !matches!(self.capture, CaptureNode::Worklet(_)) {
+            return Err(AudioError::Generic("The shared sample ring needs the AudioWorklet".to_string()));
+        }
+        let ring = SharedSampleRing::new(crate::app_config::SAMPLE_RING_CAPACITY)
+            .map_err(AudioError::Generic)?;
+        self.send_typed_control_message(ToWorkletMessage::EnableSampleRing {
+            samples: ring.sample_buffer(),
+            control: ring.control_buffer(),
+        })?;
+        self.sample_ring = Some(ring);
+        dev_log!("✓ Shared sample ring enabled");
+        Ok(())
+    }
+
+    /// Analyze every full batch waiting in the shared ring
+    pub fn drain_sample_ring(&self) {
+        let (Some(ring), Some(volume_detector), Some(pitch_analyzer)) =
+            (&self.sample_ring, &self.volume_detector, &self.pitch_analyzer) else {
+            return;
+        };
+
+        let mut samples = std::mem::take(&mut self.handler_state.borrow_mut().sample_buffer);
+        while ring.available() >= self.batch_size {
+            ring.read(self.batch_size, &mut samples);
+            process_audio_samples(&samples, &self.handler_state, volume_detector, pitch_analyzer);
+            self.handler_state.borrow_mut().batches_processed += 1;
+        }
+        self.handler_state.borrow_mut().sample_buffer = samples;
+    }
+
+    /// Whether input is captured with a ScriptProcessorNode instead of the worklet
+    pub fn uses_script_processor(&self) -> bool {
+        matches!(self.capture, CaptureNode::ScriptProcessor(_))
+    }
+
+    /// Chunks dropped by the worklet because the shared ring was full
+    pub fn get_sample_ring_overflows(&self) -> Option<u32> {
+        self.sample_ring.as_ref().map(SharedSampleRing::overflow_count)
+    }
+
     pub fn get_buffer_pool_statistics(&self) -> Option<super::message_protocol::BufferPoolStats> {
         self.handler_state.borrow().buffer_pool_stats.clone()
     }
@@ -136,13 +253,135 @@ impl AudioWorkletManager {
     
     pub fn get_volume_data(&self) -> Option<super::VolumeLevelData> {
         // Check if we have volume data from the handler state (from message handler)
-        self.handler_state.borrow().last_volume_analysis.as_ref().map(|analysis| super::VolumeLevelData {
+        let state = self.handler_state.borrow();
+        let loudness = state.loudness.as_ref();
+        state.last_volume_analysis.as_ref().map(|analysis| super::VolumeLevelData {
                 rms_amplitude: analysis.rms_amplitude,
                 peak_amplitude: analysis.peak_amplitude,
-                fft_data: None,  // No FFT data available from VolumeAnalysis
+                a_weighted_rms_amplitude: loudness.map_or(0.0, |loudness| loudness.a_weighted_rms()),
+                short_term_lufs: loudness.map_or(crate::app_config::LOUDNESS_FLOOR_LUFS, |loudness| loudness.short_term_lufs()),
             })
     }
 
+    /// Start recording into a fresh ring buffer, discarding any previous recording
+    pub fn start_recording(&mut self, max_seconds: f32, sample_rate: u32) {
+        let mut state = self.handler_state.borrow_mut();
+        state.recorder = Some(super::recording::InputRecorder::new(max_seconds, sample_rate));
+        state.is_recording = true;
+    }
+
+    /// Stop recording; the recorded samples stay available for export
+    pub fn stop_recording(&mut self) {
+        self.handler_state.borrow_mut().is_recording = false;
+    }
+
+    pub fn get_recording_status(&self) -> crate::common::shared_types::RecordingStatus {
+        let state = self.handler_state.borrow();
+        crate::common::shared_types::RecordingStatus {
+            is_recording: state.is_recording,
+            recorded_seconds: state.recorder.as_ref().map_or(0.0, |recorder| recorder.recorded_seconds()),
+        }
+    }
+
+    /// Recorded samples in the given range as a WAV file, or `None` if nothing was recorded
+    pub fn export_recording_wav(&self, from_seconds_ago: f32, to_seconds_ago: f32) -> Option<Vec<u8>> {
+        let state = self.handler_state.borrow();
+        let recorder = state.recorder.as_ref()?;
+        let samples = recorder.range(from_seconds_ago, to_seconds_ago);
+        if samples.is_empty() {
+            return None;
+        }
+        Some(super::recording::encode_wav(&samples, recorder.sample_rate()))
+    }
+
+    /// Pitch track of the whole recording, or `None` if nothing was recorded
+    pub fn analyze_recording(&self) -> Option<Result<crate::common::shared_types::RecordedPitchTrack, String>> {
+        let config = self.get_pitch_detector_config()?;
+        let state = self.handler_state.borrow();
+        let recorder = state.recorder.as_ref()?;
+        let samples = recorder.range(recorder.recorded_seconds(), 0.0);
+        if samples.is_empty() {
+            return None;
+        }
+        Some(super::recording::analyze_pitch_track(&samples, recorder.sample_rate(), config, crate::app_config::RECORDING_ANALYSIS_HOP_MS))
+    }
+
+    /// Change the pitch detection algorithm and thresholds; the analysis window is kept
+    pub fn configure_pitch_detection(&self, algorithm: crate::common::shared_types::PitchAlgorithm, power_threshold: f32, clarity_threshold: f32) -> Result<(), AudioError> {
+        let pitch_analyzer = self.pitch_analyzer.as_ref()
+            .ok_or_else(|| AudioError::Generic("Message handling is not set up".to_string()))?;
+
+        let mut pitch_analyzer = pitch_analyzer.borrow_mut();
+        let config = super::pitch_detector::PitchDetectorConfig {
+            algorithm,
+            power_threshold,
+            clarity_threshold,
+            ..pitch_analyzer.config().clone()
+        };
+        pitch_analyzer.configure(config).map_err(AudioError::Generic)
+    }
+
+    /// Change the analysis window and hop size
+    ///
+    /// The worklet batch size is set separately with `configure_batch_size`.
+    pub fn configure_analysis_window(&mut self, window_size: usize, hop_size: usize) -> Result<(), AudioError> {
+        let pitch_analyzer = self.pitch_analyzer.as_ref()
+            .ok_or_else(|| AudioError::Generic("Message handling is not set up".to_string()))?;
+
+        let mut pitch_analyzer = pitch_analyzer.borrow_mut();
+        let config = super::pitch_detector::PitchDetectorConfig {
+            sample_window_size: window_size,
+            padding_size: window_size / 2,
+            hop_size,
+            ..pitch_analyzer.config().clone()
+        };
+        pitch_analyzer.configure(config).map_err(AudioError::Generic)
+    }
+
+    /// Change the number of samples the worklet collects before sending a batch
+    ///
+    /// Each batch triggers at most one analysis, so batches larger than the
+    /// hop size lengthen the effective hop.
+    pub fn configure_batch_size(&mut self, batch_size: usize) -> Result<(), AudioError> {
+        let sample_rate = self.pitch_analyzer.as_ref()
+            .ok_or_else(|| AudioError::Generic("Message handling is not set up".to_string()))?
+            .borrow()
+            .sample_rate();
+
+        let default_config = super::message_protocol::BatchConfig::default();
+        // Leave room for a full batch before the worklet sends a partial one
+        let batch_duration_ms = (batch_size as u64 * 1000).div_ceil(sample_rate as u64) as u32;
+        let config = super::message_protocol::BatchConfig {
+            batch_size,
+            timeout_ms: default_config.timeout_ms.max(batch_duration_ms * 2),
+            ..default_config
+        };
+        self.send_typed_control_message(ToWorkletMessage::UpdateBatchConfig { config })?;
+        self.batch_size = batch_size;
+        Ok(())
+    }
+
+    /// Configure the noise gate and filters applied in the worklet before batching
+    pub fn configure_input_conditioning(&self, config: crate::common::shared_types::InputConditioning) -> Result<(), AudioError> {
+        self.send_typed_control_message(ToWorkletMessage::UpdateInputConditioning { config })
+    }
+
+    pub fn get_pitch_detector_config(&self) -> Option<super::pitch_detector::PitchDetectorConfig> {
+        self.pitch_analyzer.as_ref().map(|pitch_analyzer| pitch_analyzer.borrow().config().clone())
+    }
+
+    /// Latest F1/F2 estimate; always `None` without the `formant-estimation` feature
+    pub fn get_formants(&self) -> Option<crate::common::shared_types::Formants> {
+        #[cfg(feature = "formant-estimation")]
+        {
+            self.handler_state.borrow().formants.as_ref().and_then(super::formants::FormantEstimator::latest)
+        }
+        #[cfg(not(feature = "formant-estimation"))]
+        {
+            None
+        }
+    }
+
     pub fn get_pitch_data(&self) -> Option<super::pitch_detector::PitchResult> {
         self.handler_state.borrow().latest_pitch_data.clone()
     }
@@ -14,6 +14,13 @@ pub(super) struct MessageHandlerState {
     pub(super) buffer_pool_stats: Option<super::message_protocol::BufferPoolStats>,
     pub(super) last_volume_analysis: Option<super::VolumeAnalysis>,
     pub(super) latest_pitch_data: Option<super::pitch_detector::PitchResult>,
+    pub(super) recorder: Option<super::recording::InputRecorder>,
+    pub(super) is_recording: bool,
+    /// Batch samples copied into wasm memory, reused across batches
+    pub(super) sample_buffer: Vec<f32>,
+    pub(super) loudness: Option<super::loudness::LoudnessMeter>,
+    #[cfg(feature = "formant-estimation")]
+    pub(super) formants: Option<super::formants::FormantEstimator>,
 }
 
 /// Handle messages from the AudioWorklet processor (static version)
@@ -155,14 +162,18 @@ fn handle_typed_audio_data_batch(
             return;
         };
         
-        // Convert ArrayBuffer to Float32Array for processing
+        // The transferred buffer lives outside wasm memory, so the samples are
+        // copied once into a reused buffer; pooled buffers can be larger than
+        // the batch, so only the first sample_count values are audio
         let float32_array = js_sys::Float32Array::new(&array_buffer);
-        let array_length = float32_array.length() as usize;
-        let mut audio_samples = vec![0.0f32; array_length];
-        float32_array.copy_to(&mut audio_samples);
+        let sample_count = data.sample_count.min(float32_array.length() as usize);
+        let mut audio_samples = std::mem::take(&mut handler_state.borrow_mut().sample_buffer);
+        audio_samples.resize(sample_count, 0.0);
+        float32_array.subarray(0, sample_count as u32).copy_to(&mut audio_samples);
         
         // Perform actual audio processing
         process_audio_samples(&audio_samples, handler_state, volume_detector, pitch_analyzer);
+        handler_state.borrow_mut().sample_buffer = audio_samples;
         
         // Return buffer to worklet for recycling (ping-pong pattern is always enabled)
         let Some(buffer_id) = data.buffer_id else {
@@ -192,7 +203,7 @@ fn handle_typed_audio_data_batch(
 }
 
 /// Process audio samples for pitch and volume analysis
-fn process_audio_samples(
+pub(super) fn process_audio_samples(
     audio_samples: &[f32],
     handler_state: &Rc<RefCell<MessageHandlerState>>,
     volume_detector: &Rc<RefCell<VolumeDetector>>,
@@ -204,7 +215,21 @@ fn process_audio_samples(
     
     // Perform pitch analysis and store results in handler state
     let pitch_data = pitch_analyzer.borrow_mut().analyze_samples(audio_samples);
-    handler_state.borrow_mut().latest_pitch_data = pitch_data;
+    let mut state = handler_state.borrow_mut();
+    state.latest_pitch_data = pitch_data;
+    if let Some(loudness) = &mut state.loudness {
+        loudness.process(audio_samples);
+    }
+    #[cfg(feature = "formant-estimation")]
+    if let Some(formants) = &mut state.formants {
+        formants.process(audio_samples);
+    }
+
+    if state.is_recording
+        && let Some(recorder) = &mut state.recorder
+    {
+        recorder.push(audio_samples);
+    }
 }
 
 /// Return buffer to AudioWorklet for recycling (ping-pong pattern) - static version
@@ -0,0 +1,236 @@
+#![cfg(all(not(target_arch = "wasm32"), feature = "desktop"))]
+
+//! Audio engine of the desktop build
+//!
+//! `CpalAudioBackend` produces the same `EngineUpdateResult` as
+//! `AudioEngine::update`, from an input device of the system opened through
+//! cpal instead of the Web Audio API. The audio callback mixes each buffer to
+//! mono and hands it over a bounded channel, dropping buffers while updates
+//! stall rather than piling them up; an update analyzes everything that
+//! arrived since the previous one with the detectors of the simulation. The
+//! list of input devices is refreshed now and then rather than every update,
+//! as listing them is slow on some hosts. The spectrum, harmonics, MIDI and
+//! recording are left empty.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+
+use crate::common::shared_types::{
+    AudioAnalysis, AudioInputDevice, AudioLifecycleState, BatchSizeMode, BatchingStatus, EngineUpdateResult,
+    Error, InputDevices, InputGainStatus, MidiOutputs, RecordingStatus,
+};
+use crate::engine::audio::pitch_detector::PitchDetectorConfig;
+use crate::engine::simulation::FrameAnalyzer;
+use crate::engine::AudioBackend;
+use crate::model::ModelLayerActions;
+
+/// Buffers the audio callback may queue ahead of the updates; several
+/// seconds at common buffer sizes
+const MAX_QUEUED_BUFFERS: usize = 256;
+/// How long the list of input devices is kept before it is listed again
+const INPUT_DEVICES_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Names of the input devices of the system's default audio host
+pub fn input_device_names() -> Vec<String> {
+    let Ok(devices) = cpal::default_host().input_devices() else {
+        return Vec::new();
+    };
+    devices.filter_map(|device| device.name().ok()).collect()
+}
+
+pub struct CpalAudioBackend {
+    /// Stops the callbacks once dropped
+    stream: Stream,
+    device_name: String,
+    sample_rate: u32,
+    receiver: Receiver<Vec<f32>>,
+    input_devices: InputDevices,
+    input_devices_listed: Instant,
+    /// Stream errors reported by the audio thread since the last update
+    stream_errors: Arc<Mutex<Vec<String>>>,
+    analyzer: FrameAnalyzer,
+    frame: Vec<f32>,
+    /// Analysis of the last update that received input
+    last_analysis: Option<AudioAnalysis>,
+    suspended: bool,
+}
+
+impl CpalAudioBackend {
+    /// Start analyzing the input device named `device_name`, or the default
+    /// input device of the system
+    pub fn new(device_name: Option<&str>) -> Result<Self, String> {
+        let host = cpal::default_host();
+        let device = match device_name {
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| format!("Failed to list input devices: {}", e))?
+                .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+                .ok_or_else(|| format!("No input device named {}", name))?,
+            None => host.default_input_device().ok_or("No input device available")?,
+        };
+        let device_name = device.name().unwrap_or_default();
+        let supported = device
+            .default_input_config()
+            .map_err(|e| format!("Failed to get the input configuration of {}: {}", device_name, e))?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        let sample_rate = config.sample_rate.0;
+
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_BUFFERS);
+        let stream_errors = Arc::new(Mutex::new(Vec::new()));
+        let stream = match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, sender, stream_errors.clone()),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, sender, stream_errors.clone()),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, sender, stream_errors.clone()),
+            SampleFormat::I32 => build_stream::<i32>(&device, &config, sender, stream_errors.clone()),
+            format => return Err(format!("Unsupported sample format {}", format)),
+        }
+        .map_err(|e| format!("Failed to open {}: {}", device_name, e))?;
+        stream.play().map_err(|e| format!("Failed to start {}: {}", device_name, e))?;
+        crate::common::dev_log!("Desktop engine: ✓ Analyzing {} at {} Hz", device_name, sample_rate);
+
+        Ok(Self {
+            stream,
+            input_devices: list_input_devices(&device_name),
+            input_devices_listed: Instant::now(),
+            device_name,
+            sample_rate,
+            receiver,
+            stream_errors,
+            analyzer: FrameAnalyzer::new(sample_rate)?,
+            frame: Vec::new(),
+            last_analysis: None,
+            suspended: false,
+        })
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn configure_pitch_detection(&mut self, config: PitchDetectorConfig) -> Result<(), String> {
+        self.analyzer.configure_pitch_detection(config)
+    }
+
+    /// List the input devices again, as after the user plugged one in
+    pub fn refresh_input_devices(&mut self) {
+        self.input_devices = list_input_devices(&self.device_name);
+        self.input_devices_listed = Instant::now();
+    }
+
+    /// Stop analyzing until `resume`; updates report the suspension meanwhile
+    pub fn suspend(&mut self) -> Result<(), String> {
+        self.stream.pause().map_err(|e| e.to_string())?;
+        self.suspended = true;
+        self.last_analysis = None;
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<(), String> {
+        self.stream.play().map_err(|e| e.to_string())?;
+        self.suspended = false;
+        Ok(())
+    }
+
+    /// Analyze the input that arrived since the previous update
+    pub fn update(&mut self) -> EngineUpdateResult {
+        self.frame.clear();
+        while let Ok(buffer) = self.receiver.try_recv() {
+            self.frame.extend_from_slice(&buffer);
+        }
+        if !self.frame.is_empty() {
+            self.last_analysis = Some(self.analyzer.analyze(&self.frame));
+        }
+
+        if self.input_devices_listed.elapsed() >= INPUT_DEVICES_REFRESH_INTERVAL {
+            self.refresh_input_devices();
+        }
+
+        let audio_errors = self.stream_errors
+            .lock()
+            .map(|mut errors| errors.drain(..).map(Error::ProcessingError).collect())
+            .unwrap_or_default();
+
+        EngineUpdateResult {
+            timestamp_ms: crate::common::utils::now_ms(),
+            audio_analysis: self.last_analysis.clone(),
+            audio_errors,
+            input_devices: self.input_devices.clone(),
+            input_listening: !self.suspended,
+            recording: RecordingStatus::default(),
+            midi_events: Vec::new(),
+            midi_outputs: MidiOutputs::default(),
+            input_gain: InputGainStatus { gain: 1.0, automatic: false, calibration: None },
+            batching: BatchingStatus { mode: BatchSizeMode::default(), batch_size: self.frame.len() },
+            lifecycle: if self.suspended { AudioLifecycleState::Suspended } else { AudioLifecycleState::Running },
+            recovery_events: Vec::new(),
+            analyzed_recording: None,
+        }
+    }
+}
+
+impl AudioBackend for CpalAudioBackend {
+    fn update(&mut self) -> EngineUpdateResult {
+        CpalAudioBackend::update(self)
+    }
+
+    fn execute_actions(&mut self, model_actions: ModelLayerActions) {
+        if model_actions.resume_audio && self.suspended
+            && let Err(e) = self.resume() {
+            crate::common::warn_log!("Desktop: failed to resume input: {}", e);
+        }
+        if let Err(e) = self.analyzer.execute_actions(&model_actions) {
+            crate::common::warn_log!("Desktop: failed to configure pitch detection: {}", e);
+        }
+    }
+}
+
+fn list_input_devices(active_device_name: &str) -> InputDevices {
+    InputDevices {
+        available: input_device_names()
+            .into_iter()
+            .map(|name| AudioInputDevice { device_id: name.clone(), label: name })
+            .collect(),
+        active_device_id: Some(active_device_name.to_string()),
+    }
+}
+
+/// Input stream sending mono `f32` buffers, whatever the sample type of the device
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sender: SyncSender<Vec<f32>>,
+    stream_errors: Arc<Mutex<Vec<String>>>,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mono = data
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().map(|sample| sample.to_sample::<f32>()).sum::<f32>() / channels as f32)
+                .collect();
+            // The queue is full while updates stall, and the receiver is
+            // gone once the backend is dropped
+            let _ = sender.try_send(mono);
+        },
+        move |error| {
+            if let Ok(mut errors) = stream_errors.lock() {
+                errors.push(error.to_string());
+            }
+        },
+        None,
+    )
+}
@@ -0,0 +1,204 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Web MIDI input and output
+//!
+//! Listens to every connected MIDI input and queues note-on/note-off events
+//! until the engine collects them on its next update. Notes chosen by the
+//! model are sent to one selected output.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{MidiAccess, MidiInput, MidiMessageEvent, MidiOutput};
+use crate::common::shared_types::{MidiNote, MidiNoteEvent, MidiPortInfo};
+use crate::model::MidiOutputAction;
+
+const STATUS_NOTE_OFF: u8 = 0x80;
+const STATUS_NOTE_ON: u8 = 0x90;
+
+pub struct MidiManager {
+    access: Rc<RefCell<Option<MidiAccess>>>,
+    events: Rc<RefCell<Vec<MidiNoteEvent>>>,
+    outputs: Rc<RefCell<Vec<MidiPortInfo>>>,
+    active_output_id: Option<String>,
+    held_note: Option<MidiNote>,
+    _message_closure: Closure<dyn FnMut(MidiMessageEvent)>,
+    _state_change_closure: Closure<dyn FnMut()>,
+}
+
+impl MidiManager {
+    /// Request MIDI access; without browser support or permission MIDI is inactive
+    pub fn connect() -> Self {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let outputs = Rc::new(RefCell::new(Vec::new()));
+
+        let message_events = events.clone();
+        let message_closure = Closure::<dyn FnMut(MidiMessageEvent)>::new(move |event: MidiMessageEvent| {
+            if let Ok(data) = event.data()
+                && let Some(note_event) = parse_note_event(&data)
+            {
+                message_events.borrow_mut().push(note_event);
+            }
+        });
+
+        let access: Rc<RefCell<Option<MidiAccess>>> = Rc::new(RefCell::new(None));
+        let message_handler: js_sys::Function = message_closure.as_ref().unchecked_ref::<js_sys::Function>().clone();
+
+        // Newly connected inputs need the message handler too
+        let state_change_access = access.clone();
+        let state_change_outputs = outputs.clone();
+        let state_change_handler = message_handler.clone();
+        let state_change_closure = Closure::<dyn FnMut()>::new(move || {
+            if let Some(access) = state_change_access.borrow().as_ref() {
+                attach_to_inputs(access, &state_change_handler);
+                state_change_outputs.replace(list_outputs(access));
+            }
+        });
+        let state_change_function: js_sys::Function = state_change_closure.as_ref().unchecked_ref::<js_sys::Function>().clone();
+
+        let granted_access = access.clone();
+        let granted_outputs = outputs.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match request_access().await {
+                Ok(midi_access) => {
+                    attach_to_inputs(&midi_access, &message_handler);
+                    granted_outputs.replace(list_outputs(&midi_access));
+                    midi_access.set_onstatechange(Some(&state_change_function));
+                    crate::common::dev_log!(
+                        "✓ MIDI access granted, {} inputs, {} outputs",
+                        midi_access.inputs().size(), midi_access.outputs().size()
+                    );
+                    granted_access.replace(Some(midi_access));
+                }
+                Err(_e) => {
+                    crate::common::dev_log!("MIDI unavailable: {}", _e);
+                }
+            }
+        });
+
+        Self {
+            access,
+            events,
+            outputs,
+            active_output_id: None,
+            held_note: None,
+            _message_closure: message_closure,
+            _state_change_closure: state_change_closure,
+        }
+    }
+
+    /// Note events received since the last call, oldest first
+    pub fn take_events(&self) -> Vec<MidiNoteEvent> {
+        std::mem::take(&mut *self.events.borrow_mut())
+    }
+
+    pub fn outputs(&self) -> Vec<MidiPortInfo> {
+        self.outputs.borrow().clone()
+    }
+
+    pub fn active_output_id(&self) -> Option<&str> {
+        self.active_output_id.as_deref()
+    }
+
+    /// Send to `output_id` from now on, or stop sending; a held note is released first
+    pub fn select_output(&mut self, output_id: Option<String>) -> Result<(), String> {
+        self.send(MidiOutputAction::Hold(None))?;
+        self.active_output_id = output_id;
+        Ok(())
+    }
+
+    pub fn send(&mut self, action: MidiOutputAction) -> Result<(), String> {
+        let Some(output) = self.active_output() else {
+            self.held_note = None;
+            return Ok(());
+        };
+
+        match action {
+            MidiOutputAction::Hold(note) => {
+                if note == self.held_note {
+                    return Ok(());
+                }
+                if let Some(held_note) = self.held_note.take() {
+                    send_message(&output, &note_off(held_note), None)?;
+                }
+                if let Some(note) = note {
+                    send_message(&output, &note_on(note), None)?;
+                    self.held_note = Some(note);
+                }
+            }
+            MidiOutputAction::Play { note, duration_ms } => {
+                let now = web_sys::window()
+                    .and_then(|window| window.performance())
+                    .map_or(0.0, |performance| performance.now());
+                send_message(&output, &note_on(note), None)?;
+                send_message(&output, &note_off(note), Some(now + duration_ms as f64))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn active_output(&self) -> Option<MidiOutput> {
+        let output_id = self.active_output_id.as_deref()?;
+        self.access.borrow().as_ref()?.outputs().get(output_id)?.dyn_into::<MidiOutput>().ok()
+    }
+}
+
+async fn request_access() -> Result<MidiAccess, String> {
+    let navigator = web_sys::window().ok_or("No window object")?.navigator();
+    let promise = navigator.request_midi_access()
+        .map_err(|e| format!("Web MIDI not supported: {:?}", e))?;
+    let access = JsFuture::from(promise).await
+        .map_err(|e| format!("MIDI access denied: {:?}", e))?;
+    access.dyn_into::<MidiAccess>()
+        .map_err(|_| "requestMIDIAccess did not return a MIDIAccess".to_string())
+}
+
+fn attach_to_inputs(access: &MidiAccess, handler: &js_sys::Function) {
+    for input in access.inputs().values().into_iter().flatten() {
+        if let Ok(input) = input.dyn_into::<MidiInput>() {
+            input.set_onmidimessage(Some(handler));
+        }
+    }
+}
+
+fn list_outputs(access: &MidiAccess) -> Vec<MidiPortInfo> {
+    access.outputs().values().into_iter().flatten()
+        .filter_map(|output| output.dyn_into::<web_sys::MidiPort>().ok())
+        .map(|port| MidiPortInfo {
+            name: port.name().unwrap_or_else(|| port.id()),
+            id: port.id(),
+        })
+        .collect()
+}
+
+fn note_on(note: MidiNote) -> [u8; 3] {
+    [STATUS_NOTE_ON | crate::app_config::MIDI_OUTPUT_CHANNEL, note, crate::app_config::MIDI_OUTPUT_VELOCITY]
+}
+
+fn note_off(note: MidiNote) -> [u8; 3] {
+    [STATUS_NOTE_OFF | crate::app_config::MIDI_OUTPUT_CHANNEL, note, 0]
+}
+
+/// `timestamp_ms` is on the `performance.now()` clock; `None` sends immediately
+fn send_message(output: &MidiOutput, message: &[u8], timestamp_ms: Option<f64>) -> Result<(), String> {
+    let data = js_sys::Uint8Array::from(message);
+    match timestamp_ms {
+        Some(timestamp_ms) => output.send_with_timestamp(&data, timestamp_ms),
+        None => output.send(&data),
+    }
+    .map_err(|e| format!("Failed to send MIDI message: {:?}", e))
+}
+
+/// Note-on with velocity 0 is a note-off by convention; the channel is ignored
+fn parse_note_event(data: &[u8]) -> Option<MidiNoteEvent> {
+    let [status, note, velocity, ..] = *data else {
+        return None;
+    };
+    match (status & 0xF0, velocity) {
+        (STATUS_NOTE_ON, 0) | (STATUS_NOTE_OFF, _) => Some(MidiNoteEvent::NoteOff { note }),
+        (STATUS_NOTE_ON, velocity) => Some(MidiNoteEvent::NoteOn { note, velocity }),
+        _ => None,
+    }
+}
@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 //! Engine Layer - Raw audio processing and hardware interface
 //!
 //! The Engine layer is the lowest level of the three-layer architecture,
@@ -25,19 +23,62 @@
 //! - Provides audio analysis, error information, and permission state
 
 pub mod audio;
+pub mod desktop;
 pub(crate) mod platform;
+pub mod midi;
+pub mod simulation;
 
 use crate::common::shared_types::EngineUpdateResult;
 use crate::model::ModelLayerActions;
-use web_sys::AudioContext;
-use crate::engine::audio::worklet::AudioWorkletManager;
-use crate::engine::audio::volume_detector::VolumeDetector;
 
-#[cfg(debug_assertions)] 
+/// Engine interface the model is driven through, in the browser, the desktop
+/// build and native tests alike
+pub trait AudioBackend {
+    /// Analyze the input that arrived since the previous update
+    fn update(&mut self) -> EngineUpdateResult;
+
+    /// Carry out the model's actions; backends skip those they have no
+    /// hardware for
+    fn execute_actions(&mut self, model_actions: ModelLayerActions);
+}
+
+#[cfg(target_arch = "wasm32")]
+use {
+    crate::common::shared_types::{AudioLifecycleState, RecoveryReason},
+    web_sys::AudioContext,
+    crate::engine::audio::worklet::AudioWorkletManager,
+    crate::engine::audio::volume_detector::VolumeDetector,
+    crate::engine::audio::input_devices::InputDeviceManager,
+    crate::engine::audio::spectrum_analyzer::SpectrumAnalyzer,
+    crate::engine::midi::MidiManager,
+    crate::engine::audio::gain_control::InputGainControl,
+    crate::engine::audio::voice_activity::VoiceActivityDetector,
+    crate::engine::audio::batch_scheduler::BatchScheduler,
+    crate::engine::audio::lifecycle::AudioLifecycle,
+    crate::common::pitch_tracker::PitchTracker,
+    crate::engine::audio::recovery::RecoverySupervisor,
+};
+
+#[cfg(all(target_arch = "wasm32", debug_assertions))]
 use crate::engine::audio::message_protocol::BufferPoolStats;
-#[cfg(debug_assertions)] 
+#[cfg(all(target_arch = "wasm32", debug_assertions))]
 use crate::presentation::DebugLayerActions;
 
+/// Calibration storage key for inputs whose device id is unknown
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_DEVICE_KEY: &str = "default";
+
+#[cfg(target_arch = "wasm32")]
+fn stored_calibration(device_id: Option<&str>) -> Option<crate::common::shared_types::InputCalibration> {
+    crate::web::storage::settings().input_calibrations.get(device_id.unwrap_or(DEFAULT_DEVICE_KEY)).copied()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
 
 /// AudioEngine - The engine layer of the three-layer architecture
 /// 
@@ -48,12 +89,33 @@ use crate::presentation::DebugLayerActions;
 /// The engine provides raw audio data (frequencies in Hz, volume amplitudes) to
 /// the model layer, which handles all musical logic including tuning systems,
 /// root notes, and pitch relationships.
+#[cfg(target_arch = "wasm32")]
 pub struct AudioEngine {
     audio_context: AudioContext,
     audio_pipeline: audio::audio_pipeline::NewAudioPipeline,
     audioworklet_manager: AudioWorkletManager,
+    input_devices: InputDeviceManager,
+    spectrum_analyzer: SpectrumAnalyzer,
+    /// Connected when the user enables MIDI, so the browser asks for access only then
+    midi: Option<MidiManager>,
+    input_gain: InputGainControl,
+    last_update_ms: f64,
+    voice_activity: VoiceActivityDetector,
+    batch_scheduler: BatchScheduler,
+    lifecycle: AudioLifecycle,
+    /// Held while audio is analysed
+    wake_lock: Box<dyn crate::engine::platform::wake_lock::WakeLock>,
+    pitch_tracker: PitchTracker,
+    recovery: RecoverySupervisor,
+    /// Reapplied to a restarted worklet
+    input_conditioning: crate::common::shared_types::InputConditioning,
+    /// Analyzed on request, reported with the next update
+    analyzed_recording: Option<crate::common::shared_types::RecordedPitchTrack>,
+    #[cfg(debug_assertions)]
+    test_signal_accuracy: audio::test_signal::TestSignalAccuracy,
 }
 
+#[cfg(target_arch = "wasm32")]
 impl AudioEngine {
     /// Create a new AudioEngine for raw audio processing
     /// 
@@ -65,6 +127,8 @@ impl AudioEngine {
     /// 
     /// * `media_stream` - The MediaStream to connect to the audio worklet
     /// * `audio_context` - AudioContext from early worklet loading
+    /// * `use_worklet` - Whether to capture input with the AudioWorklet rather
+    ///   than the ScriptProcessorNode fallback
     /// 
     /// # Returns
     /// 
@@ -72,12 +136,13 @@ impl AudioEngine {
     /// if audio system initialization fails.
     pub fn new(
         media_stream: web_sys::MediaStream,
-        audio_context: web_sys::AudioContext
+        audio_context: web_sys::AudioContext,
+        use_worklet: bool,
     ) -> Result<Self, String> {
         crate::common::dev_log!("✓ AudioContext attached");
 
         // Create audio pipeline with all audio nodes and connect media stream
-        let audio_pipeline = audio::audio_pipeline::NewAudioPipeline::new(&audio_context, &media_stream)
+        let audio_pipeline = audio::audio_pipeline::NewAudioPipeline::new(&audio_context, &media_stream, use_worklet)
             .map_err(|e| {
                 let error_msg = format!("Failed to create AudioPipeline: {}", e);
                 crate::common::dev_log!("✗ {}", error_msg);
@@ -85,8 +150,8 @@ impl AudioEngine {
             })?;
         crate::common::dev_log!("✓ AudioPipeline created with audio nodes and media stream connected");
 
-        // Extract worklet_node for AudioWorkletManager
-        let worklet_node = audio_pipeline.signal_path.worklet.clone();
+        // Extract the capture node for AudioWorkletManager
+        let capture_node = audio_pipeline.signal_path.capture.clone();
         
         // Create PitchAnalyzer with audio context sample rate
         let sample_rate = audio_context.sample_rate() as u32;
@@ -97,7 +162,7 @@ impl AudioEngine {
                 error_msg
             })?;
         
-        let mut worklet_manager = audio::worklet::AudioWorkletManager::new(worklet_node)
+        let mut worklet_manager = audio::worklet::AudioWorkletManager::new(capture_node)
             .map_err(|e| {
                 let error_msg = format!("Failed to create AudioWorkletManager: {}", e);
                 crate::common::dev_log!("✗ {}", error_msg);
@@ -115,15 +180,49 @@ impl AudioEngine {
         
         crate::common::dev_log!("✓ VolumeDetector initialized and configured");
 
+        let input_devices = InputDeviceManager::new(&media_stream);
+        let hop_size = worklet_manager.get_pitch_detector_config()
+            .map_or(crate::app_config::BUFFER_SIZE, |config| config.hop_size);
+        let input_gain = InputGainControl::new(stored_calibration(input_devices.active_device_id()));
+        audio_pipeline.set_input_gain(input_gain.gain());
+        let spectrum_analyzer = SpectrumAnalyzer::new(
+            audio_pipeline.signal_path.spectrum_analyser.clone(),
+            audio_context.sample_rate(),
+            crate::app_config::DEFAULT_SPECTRUM_BIN_COUNT,
+        );
+
+        let lifecycle = AudioLifecycle::attach(&audio_context)?;
+
         // Create the engine struct with all initialized components
         let mut engine = Self {
             audio_context,
             audio_pipeline,
             audioworklet_manager: worklet_manager,
+            input_devices,
+            spectrum_analyzer,
+            midi: None,
+            input_gain,
+            last_update_ms: now_ms(),
+            voice_activity: VoiceActivityDetector::new(),
+            batch_scheduler: BatchScheduler::new(hop_size),
+            lifecycle,
+            wake_lock: crate::engine::platform::wake_lock::create(),
+            pitch_tracker: PitchTracker::new(),
+            recovery: RecoverySupervisor::new(now_ms()),
+            input_conditioning: Default::default(),
+            analyzed_recording: None,
+            #[cfg(debug_assertions)]
+            test_signal_accuracy: Default::default(),
         };
         
         engine.audio_pipeline.run()?;
         engine.audioworklet_manager.enable_data_processing().map_err(|e| e.to_string())?;
+        if audio::ring_buffer::SharedSampleRing::is_supported()
+            && use_worklet
+            && let Err(_e) = engine.audioworklet_manager.enable_sample_ring()
+        {
+            crate::common::dev_log!("Shared sample ring unavailable, using message batches: {:?}", _e);
+        }
 
         crate::common::dev_log!("✓ AudioEngine fully initialized");
         Ok(engine)
@@ -144,9 +243,68 @@ impl AudioEngine {
     /// Note: All musical interpretation (tuning systems, intervals, pitch relationships)
     /// is handled by the model layer that processes this raw data.
     pub fn update(&mut self) -> EngineUpdateResult {
+        self.apply_opened_input_stream();
+
+        for state in self.lifecycle.take_state_changes() {
+            crate::common::dev_log!("Engine layer: AudioContext {:?}", state);
+            // Time spent suspended is not a slow frame
+            if state == web_sys::AudioContextState::Running {
+                self.last_update_ms = now_ms();
+            }
+        }
+        self.audioworklet_manager.drain_sample_ring();
+        self.lifecycle.set_audible(self.audio_pipeline.is_audible());
+
+        // The last analysis is stale while no audio flows
+        let lifecycle = self.lifecycle.state();
+        self.supervise_audio_graph(lifecycle == AudioLifecycleState::Running);
+        self.keep_screen_on(lifecycle == AudioLifecycleState::Running);
+        let now = now_ms();
+        let audio_analysis = if lifecycle == AudioLifecycleState::Running {
+            self.collect_audio_analysis(now)
+        } else {
+            None
+        };
+        #[cfg(debug_assertions)]
+        if let Some(expected) = self.audio_pipeline.test_signal_frequency() {
+            let detected = audio_analysis.as_ref().and_then(|analysis| match analysis.pitch {
+                crate::common::shared_types::Pitch::Detected(frequency) => Some(frequency),
+                crate::common::shared_types::Pitch::NotDetected => None,
+            });
+            self.test_signal_accuracy.record(expected, detected);
+        }
+        let elapsed_seconds = ((now - self.last_update_ms) / 1000.0) as f32;
+        self.last_update_ms = now;
+        if let Some(analysis) = &audio_analysis
+            && let Some(gain) = self.input_gain.update(analysis.volume_level.rms_amplitude, elapsed_seconds)
+        {
+            self.audio_pipeline.set_input_gain(gain);
+        }
+        if let Some(batch_size) = self.batch_scheduler.observe_frame(elapsed_seconds * 1000.0) {
+            self.apply_batch_size(batch_size);
+        }
+
         EngineUpdateResult {
-            audio_analysis: self.collect_audio_analysis(),
+            timestamp_ms: js_sys::Date::now(),
+            audio_analysis,
             audio_errors: self.collect_audio_errors(),
+            input_devices: crate::common::shared_types::InputDevices {
+                available: self.input_devices.devices(),
+                active_device_id: self.input_devices.active_device_id().map(str::to_string),
+            },
+            input_listening: self.audio_pipeline.is_input_listening(),
+            recording: self.audioworklet_manager.get_recording_status(),
+            midi_events: self.midi.as_ref().map(MidiManager::take_events).unwrap_or_default(),
+            midi_outputs: crate::common::shared_types::MidiOutputs {
+                enabled: self.midi.is_some(),
+                available: self.midi.as_ref().map(MidiManager::outputs).unwrap_or_default(),
+                active_output_id: self.midi.as_ref().and_then(MidiManager::active_output_id).map(str::to_string),
+            },
+            input_gain: self.input_gain.status(),
+            batching: self.batch_scheduler.status(),
+            lifecycle,
+            recovery_events: self.recovery.take_events(),
+            analyzed_recording: self.analyzed_recording.take(),
         }
     }
     
@@ -180,6 +338,135 @@ impl AudioEngine {
                 config.frequency
             );
         };
+
+        if let Some(config) = model_actions.pitch_detection_configuration {
+            match self.audioworklet_manager.configure_pitch_detection(config.algorithm, config.power_threshold, config.clarity_threshold) {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ Pitch detection set to {:?}", config.algorithm);
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to configure pitch detection: {}", e);
+                }
+            }
+        }
+
+        if let Some(configuration) = model_actions.reference_tone_configuration {
+            match self.audio_pipeline.configure_reference_tone(configuration.settings) {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ Reference tone timbre {}", configuration.settings.timbre.name());
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to configure reference tone: {}", e);
+                }
+            }
+        }
+
+        if let Some(playback) = model_actions.reference_tone_playback
+            && let Err(e) = self.audio_pipeline.play_reference_tone(playback.frequency, playback.duration_ms)
+        {
+            crate::common::error_log!("Engine layer: Failed to play reference tone: {}", e);
+        }
+
+        if let Some(clicks) = &model_actions.metronome_clicks
+            && let Err(e) = self.audio_pipeline.schedule_metronome_clicks(clicks.cancel_scheduled, &clicks.clicks)
+        {
+            crate::common::error_log!("Engine layer: Failed to schedule metronome clicks: {}", e);
+        }
+
+        if let Some(conditioning) = model_actions.input_conditioning_configuration {
+            match self.audioworklet_manager.configure_input_conditioning(conditioning.config.clone()) {
+                Ok(()) => {
+                    self.input_conditioning = conditioning.config;
+                    crate::common::dev_log!("Engine layer: ✓ Input conditioning updated");
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to configure input conditioning: {}", e);
+                }
+            }
+        }
+
+        if let Some(config) = model_actions.spectrum_configuration {
+            match self.spectrum_analyzer.set_bin_count(config.bin_count) {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ Spectrum bin count {}", config.bin_count);
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to configure spectrum: {}", e);
+                }
+            }
+        }
+
+        if model_actions.enable_midi && self.midi.is_none() {
+            self.midi = Some(MidiManager::connect());
+        }
+
+        if let Some(selection) = model_actions.midi_output_selection
+            && let Some(midi) = self.midi.as_mut()
+            && let Err(e) = midi.select_output(selection.output_id)
+        {
+            crate::common::error_log!("Engine layer: Failed to switch MIDI output: {}", e);
+        }
+
+        if let Some(action) = model_actions.midi_output
+            && let Some(midi) = self.midi.as_mut()
+            && let Err(e) = midi.send(action)
+        {
+            crate::common::error_log!("Engine layer: Failed to send MIDI: {}", e);
+        }
+
+        if model_actions.resume_audio {
+            match self.lifecycle.resume() {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ Requested AudioContext resume");
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: {}", e);
+                }
+            }
+        }
+
+        if let Some(config) = model_actions.batch_size_configuration {
+            let batch_size = self.batch_scheduler.set_mode(config.mode);
+            self.apply_batch_size(batch_size);
+        }
+
+        if let Some(config) = model_actions.automatic_gain_configuration {
+            self.input_gain.set_automatic(config.enabled);
+            self.audio_pipeline.set_input_gain(self.input_gain.gain());
+            crate::common::dev_log!("Engine layer: ✓ Automatic gain control {}", if config.enabled { "on" } else { "off" });
+        }
+
+        if let Some(calibration) = model_actions.calibration {
+            self.execute_calibration_action(calibration);
+        }
+
+        if let Some(recording) = model_actions.recording {
+            self.execute_recording_action(recording);
+        }
+
+        if let Some(save) = model_actions.session_save {
+            self.execute_session_save(save);
+        }
+
+        if let Some(selection) = model_actions.input_device_selection
+            && self.input_devices.active_device_id() != Some(selection.device_id.as_str())
+        {
+            crate::common::dev_log!("Engine layer: Opening audio input {}", selection.device_id);
+            self.input_devices.request_device(selection.device_id);
+        }
+
+        if let Some(config) = model_actions.analysis_window_configuration {
+            match self.audioworklet_manager.configure_analysis_window(config.window_size, config.hop_size) {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ Analysis window {} / hop {}", config.window_size, config.hop_size);
+                    let batch_size = self.batch_scheduler.set_hop_size(config.hop_size);
+                    self.apply_batch_size(batch_size);
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to configure analysis window: {}", e);
+                }
+            }
+        }
     }
     
     
@@ -211,40 +498,229 @@ impl AudioEngine {
     pub fn execute_debug_actions_sync(&mut self, debug_actions: DebugLayerActions) -> Result<(), String> {
         if let Some(config) = &debug_actions.test_signal_configuration {
             self.audio_pipeline.execute_test_signal_configuration(config)?;
+            self.test_signal_accuracy = Default::default();
         }
         
         Ok(())
     }
 
     /// Collect audio analysis data from the engine components
-    fn collect_audio_analysis(&self) -> Option<crate::common::shared_types::AudioAnalysis> {
+    fn collect_audio_analysis(&mut self, now_ms: f64) -> Option<crate::common::shared_types::AudioAnalysis> {
         use crate::common::shared_types::{Volume, Pitch, AudioAnalysis};
         
         let volume_data = self.audioworklet_manager.get_volume_data();
         let volume = volume_data.as_ref().map(|data| Volume {
             peak_amplitude: data.peak_amplitude,
             rms_amplitude: data.rms_amplitude,
+            a_weighted_rms_amplitude: data.a_weighted_rms_amplitude,
+            short_term_lufs: data.short_term_lufs,
         });
         
-        // Extract FFT data from volume data when available
-        let fft_data = volume_data.and_then(|data| data.fft_data.clone());
+        let fft_data = Some(self.spectrum_analyzer.analyze());
         
-        let pitch_data = self.audioworklet_manager.get_pitch_data();
-        let pitch = pitch_data.map(|data| {
-            if data.frequency > 0.0 {
-                Pitch::Detected(data.frequency)
-            } else {
-                Pitch::NotDetected
-            }
-        });
-        
-        (volume.is_some() || pitch.is_some()).then(|| AudioAnalysis {
-            volume_level: volume.unwrap_or(Volume { peak_amplitude: 0.0, rms_amplitude: 0.0 }),
-            pitch: pitch.unwrap_or(Pitch::NotDetected),
+        let is_voiced = self.voice_activity.update(
+            now_ms,
+            volume.as_ref().map_or(0.0, |volume| volume.rms_amplitude),
+            self.spectrum_analyzer.decibels(),
+            self.spectrum_analyzer.resolution_hz(),
+            self.spectrum_analyzer.min_decibels(),
+        );
+
+        // Unvoiced input gives unreliable readings, so it is reported as no pitch
+        let pitch_data = self.audioworklet_manager.get_pitch_data()
+            .filter(|_| is_voiced);
+        let clarity = pitch_data.as_ref().map(|data| data.clarity);
+        let raw_frequency = pitch_data
+            .map(|data| data.frequency)
+            .filter(|&frequency| frequency > 0.0);
+        let frequency = self.pitch_tracker.update(raw_frequency);
+        let harmonics = frequency.and_then(|frequency| audio::harmonic_analysis::analyze_harmonics(
+            self.spectrum_analyzer.decibels(),
+            self.spectrum_analyzer.resolution_hz(),
+            frequency,
+            crate::app_config::HARMONIC_COUNT,
+        ));
+        let pitch = frequency.map_or(Pitch::NotDetected, Pitch::Detected);
+        let formants = self.audioworklet_manager.get_formants().filter(|_| is_voiced);
+
+        (volume.is_some() || frequency.is_some()).then(|| AudioAnalysis {
+            volume_level: volume.unwrap_or(Volume::SILENCE),
+            pitch,
+            clarity,
             fft_data,
+            harmonics,
+            formants,
         })
     }
     
+    fn apply_batch_size(&mut self, batch_size: usize) {
+        match self.audioworklet_manager.configure_batch_size(batch_size) {
+            Ok(()) => {
+                crate::common::dev_log!("Engine layer: ✓ Worklet batch size {}", batch_size);
+            }
+            Err(e) => {
+                crate::common::error_log!("Engine layer: Failed to configure batch size: {}", e);
+            }
+        }
+    }
+
+    fn execute_calibration_action(&mut self, action: crate::model::CalibrationAction) {
+        match action {
+            crate::model::CalibrationAction::Begin => {
+                self.input_gain.begin_calibration();
+            }
+            crate::model::CalibrationAction::Finish(calibration) => {
+                self.input_gain.finish_calibration(calibration);
+                if let Some(calibration) = calibration {
+                    let device_id = self.input_devices.active_device_id().unwrap_or(DEFAULT_DEVICE_KEY);
+                    crate::web::storage::update_settings(|settings| {
+                        settings.input_calibrations.insert(device_id.to_string(), calibration);
+                    });
+                    crate::common::dev_log!("Engine layer: ✓ Input calibrated with gain {:.2}", calibration.gain);
+                }
+            }
+        }
+        self.audio_pipeline.set_input_gain(self.input_gain.gain());
+    }
+
+    fn execute_recording_action(&mut self, action: crate::model::RecordingAction) {
+        match action {
+            crate::model::RecordingAction::Start { max_seconds } => {
+                let sample_rate = self.audio_context.sample_rate() as u32;
+                self.audioworklet_manager.start_recording(max_seconds, sample_rate);
+                crate::common::dev_log!("Engine layer: ✓ Recording started");
+            }
+            crate::model::RecordingAction::Stop => {
+                self.audioworklet_manager.stop_recording();
+                crate::common::dev_log!("Engine layer: ✓ Recording stopped");
+            }
+            crate::model::RecordingAction::ExportWav { from_seconds_ago, to_seconds_ago } => {
+                let Some(wav) = self.audioworklet_manager.export_recording_wav(from_seconds_ago, to_seconds_ago) else {
+                    crate::common::warn_log!("Engine layer: Nothing recorded to export");
+                    return;
+                };
+                match crate::web::download::download_bytes(crate::app_config::RECORDING_EXPORT_FILENAME, "audio/wav", &wav) {
+                    Ok(()) => {
+                        crate::common::dev_log!("Engine layer: ✓ Exported {} bytes of WAV", wav.len());
+                    }
+                    Err(e) => {
+                        crate::common::error_log!("Engine layer: Failed to export recording: {}", e);
+                    }
+                }
+            }
+            crate::model::RecordingAction::AnalyzePitch => {
+                match self.audioworklet_manager.analyze_recording() {
+                    Some(Ok(track)) => {
+                        crate::common::dev_log!("Engine layer: ✓ Analyzed {} frames of the recording", track.frequencies.len());
+                        self.analyzed_recording = Some(track);
+                    }
+                    Some(Err(e)) => {
+                        crate::common::error_log!("Engine layer: Failed to analyze recording: {}", e);
+                    }
+                    None => {
+                        crate::common::warn_log!("Engine layer: Nothing recorded to analyze");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keep the session in the browser, with the recording as a WAV file
+    fn execute_session_save(&mut self, action: crate::model::SaveSessionAction) {
+        let recording = if action.recording_seconds > 0.0 {
+            self.audioworklet_manager.export_recording_wav(action.recording_seconds, 0.0)
+        } else {
+            None
+        };
+        crate::web::saved_sessions::save_session(action.session, recording);
+    }
+
+    /// Hold the wake lock while audio is analysed, asking for it again when
+    /// the browser let it go, and let it go once audio is suspended
+    fn keep_screen_on(&mut self, analysing: bool) {
+        if !analysing {
+            self.wake_lock.release();
+        } else if !self.wake_lock.is_held() {
+            self.wake_lock.acquire();
+        }
+    }
+
+    /// Switch the pipeline input once a requested device stream has opened
+    ///
+    /// This also completes an input restart started by `supervise_audio_graph`.
+    fn apply_opened_input_stream(&mut self) {
+        match self.input_devices.take_opened_stream() {
+            Some(Ok((device_id, stream))) => match self.audio_pipeline.replace_user_input(stream) {
+                Ok(()) => {
+                    self.input_gain.set_calibration(stored_calibration(Some(&device_id)));
+                    self.audio_pipeline.set_input_gain(self.input_gain.gain());
+                    self.recovery.restart_succeeded(now_ms(), self.audioworklet_manager.get_batches_processed());
+                    crate::common::dev_log!("Engine layer: ✓ Audio input switched to {}", device_id);
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to switch audio input: {}", e);
+                    self.recovery.restart_failed(now_ms(), e);
+                }
+            },
+            Some(Err(e)) => {
+                crate::common::error_log!("Engine layer: Failed to open audio input: {}", e);
+                self.recovery.restart_failed(now_ms(), e.to_string());
+            }
+            None => {}
+        }
+    }
+
+    /// Restart the worklet or reopen the input when the supervisor finds them failed
+    fn supervise_audio_graph(&mut self, audio_running: bool) {
+        let now = now_ms();
+        let batches_processed = self.audioworklet_manager.get_batches_processed();
+        let input_live = self.audio_pipeline.is_input_live();
+        match self.recovery.observe(now, batches_processed, input_live, audio_running) {
+            Some(RecoveryReason::WorkletStalled) => match self.restart_worklet() {
+                Ok(()) => {
+                    crate::common::dev_log!("Engine layer: ✓ AudioWorklet restarted");
+                    self.recovery.restart_succeeded(now, self.audioworklet_manager.get_batches_processed());
+                }
+                Err(e) => {
+                    crate::common::error_log!("Engine layer: Failed to restart AudioWorklet: {}", e);
+                    self.recovery.restart_failed(now, e);
+                }
+            },
+            Some(RecoveryReason::InputEnded) => {
+                crate::common::dev_log!("Engine layer: Audio input ended, reopening");
+                self.input_devices.request_reopen();
+            }
+            None => {}
+        }
+    }
+
+    /// Replace the worklet, or its ScriptProcessorNode fallback, with a new one
+    /// configured like the old
+    ///
+    /// Samples recorded through the old worklet are lost.
+    fn restart_worklet(&mut self) -> Result<(), String> {
+        let pitch_config = self.audioworklet_manager.get_pitch_detector_config();
+        let use_sample_ring = self.audioworklet_manager.get_sample_ring_overflows().is_some();
+
+        let capture_node = self.audio_pipeline.replace_capture_node()?;
+        let mut worklet_manager = AudioWorkletManager::new(capture_node)?;
+        let mut pitch_analyzer = audio::pitch_analyzer::PitchAnalyzer::new(self.audio_context.sample_rate() as u32)?;
+        if let Some(config) = pitch_config {
+            pitch_analyzer.configure(config)?;
+        }
+        let volume_detector = VolumeDetector::new(self.audio_pipeline.signal_path.analyser.clone());
+        worklet_manager.setup_message_handling(pitch_analyzer, volume_detector).map_err(|e| e.to_string())?;
+        worklet_manager.configure_batch_size(self.batch_scheduler.status().batch_size).map_err(|e| e.to_string())?;
+        worklet_manager.configure_input_conditioning(self.input_conditioning.clone()).map_err(|e| e.to_string())?;
+        worklet_manager.enable_data_processing().map_err(|e| e.to_string())?;
+        if use_sample_ring {
+            worklet_manager.enable_sample_ring().map_err(|e| e.to_string())?;
+        }
+
+        self.audioworklet_manager = worklet_manager;
+        Ok(())
+    }
+
     /// Collect audio errors from the engine components
     fn collect_audio_errors(&self) -> Vec<crate::common::shared_types::Error> {
         use web_sys::AudioContextState;
@@ -265,4 +741,64 @@ impl AudioEngine {
         errors
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(target_arch = "wasm32")]
+impl AudioBackend for AudioEngine {
+    fn update(&mut self) -> EngineUpdateResult {
+        AudioEngine::update(self)
+    }
+
+    fn execute_actions(&mut self, model_actions: ModelLayerActions) {
+        AudioEngine::execute_actions(self, model_actions)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl crate::debug::inspector::Inspect for AudioEngine {
+    fn inspect(&self) -> Vec<(&'static str, String)> {
+        let signal_path = &self.audio_pipeline.signal_path;
+        let mut state = vec![
+            ("audio_context_state", format!("{:?}", self.audio_context.state())),
+            ("sample_rate", format!("{} Hz", self.audio_context.sample_rate())),
+            ("current_time", format!("{:.2} s", self.audio_context.current_time())),
+            ("input_device", self.input_devices.active_device_id().unwrap_or("-").to_string()),
+            ("user_input_gain", signal_path.user_input_mute.gain().value().to_string()),
+            ("test_signal_gain", signal_path.test_signal_mute.gain().value().to_string()),
+        ];
+
+        if let Some(config) = self.audioworklet_manager.get_pitch_detector_config() {
+            state.push(("pitch_algorithm", config.algorithm.name().to_string()));
+            state.push(("pitch_thresholds", format!("power {}, clarity {}", config.power_threshold, config.clarity_threshold)));
+            state.push(("analysis_window", format!("{} samples, hop {}", config.sample_window_size, config.hop_size)));
+        }
+
+        state.push(("lifecycle", format!("{:?}", self.lifecycle.state())));
+        state.push(("wake_lock", if self.wake_lock.is_held() { "held" } else { "released" }.to_string()));
+        if let Some(formants) = self.audioworklet_manager.get_formants() {
+            state.push(("formants", format!("F1 {:.0} Hz, F2 {:.0} Hz", formants.f1, formants.f2)));
+        }
+        #[cfg(debug_assertions)]
+        if self.audio_pipeline.test_signal_frequency().is_some() {
+            state.push(("test_signal_accuracy", self.test_signal_accuracy.summary()));
+        }
+        let batching = self.batch_scheduler.status();
+        state.push(("batch_size", format!("{} ({:?})", batching.batch_size, batching.mode)));
+        let transport = match self.audioworklet_manager.get_sample_ring_overflows() {
+            Some(overflows) => format!("shared ring ({} overflows)", overflows),
+            None if self.audioworklet_manager.uses_script_processor() => "script processor".to_string(),
+            None => "message batches".to_string(),
+        };
+        state.push(("transport", transport));
+
+        #[cfg(debug_assertions)]
+        if let Some(stats) = self.get_debug_buffer_pool_stats() {
+            state.push(("buffers_available", format!("{}/{} ({} in flight)", stats.available_buffers, stats.total_buffers, stats.in_flight_buffers)));
+            state.push(("buffer_allocations", stats.allocation_count.to_string()));
+            state.push(("buffer_recycles", stats.recycle_count.to_string()));
+            state.push(("buffer_misses", stats.miss_count.to_string()));
+        }
+
+        state
+    }
+}
@@ -1,11 +1,10 @@
 #![cfg(target_arch = "wasm32")]
-#![cfg(debug_assertions)]
 
 // Platform Console Commands
 // Commands for platform information and API status
 
-use egui_dev_console::{ConsoleCommandRegistry, ConsoleCommand, ConsoleCommandResult, ConsoleOutput};
-use crate::{common::{dev_log, shared_types::Theme}, dev_log_bold, engine::{platform::Platform, audio::audio_error::AudioError}};
+use egui_dev_console::{ArgSpec, CommandCategory, ConsoleCommandRegistry, ConsoleCommand, ConsoleCommandResult, ConsoleOutput, ParsedArgs};
+use crate::{common::{dev_log, shared_types::Theme}, engine::{platform::Platform, audio::audio_error::AudioError}};
 use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen::JsCast;
 
@@ -13,6 +12,7 @@ use wasm_bindgen::JsCast;
 pub fn register_platform_commands(registry: &mut ConsoleCommandRegistry) {
     registry.register(Box::new(ApiStatusCommand));
     registry.register(Box::new(ThemeCommand));
+    #[cfg(debug_assertions)]
     registry.register(Box::new(ErrorCommand));
     registry.register(Box::new(AudioDevicesCommand));
 }
@@ -24,12 +24,16 @@ impl ConsoleCommand for ApiStatusCommand {
     fn name(&self) -> &str {
         "api-status"
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
     
     fn description(&self) -> &str {
         "Show application and API status"
     }
     
-    fn execute(&self, _args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
         let mut outputs = Vec::new();
         
         // Application status
@@ -40,8 +44,8 @@ impl ConsoleCommand for ApiStatusCommand {
         let platform_info = Platform::get_platform_info();
         outputs.push(ConsoleOutput::info(format!("Platform: {}", platform_info)));
         
-        // Critical API status
-        outputs.push(ConsoleOutput::info("Critical API Status:"));
+        // API status
+        outputs.push(ConsoleOutput::info("API Status:"));
         
         let api_statuses = Platform::get_api_status();
         for status in api_statuses {
@@ -79,11 +83,15 @@ impl ConsoleCommand for ThemeCommand {
     }
     
     fn description(&self) -> &str {
-        "Switch UI color theme (light|dark|autumn|sunset)"
+        "Switch UI color theme, including color-blind-safe and high-contrast ones"
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::choice("theme_name", "Theme to apply", &Theme::BUILT_IN_NAMES).optional()]
     }
     
-    fn execute(&self, args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
-        if args.is_empty() {
+    fn execute(&self, args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let Some(theme_name) = args.text("theme_name") else {
             // Show current theme and available options
             let current = crate::common::theme::get_current_theme().name();
             let current_colors = crate::common::theme::get_current_color_scheme();
@@ -92,24 +100,17 @@ impl ConsoleCommand for ThemeCommand {
                 ConsoleOutput::info(format!("Current theme: {}", current)),
                 ConsoleOutput::info(format!("Current color scheme: background={:?}, surface={:?}, text={:?}", 
                     current_colors.background, current_colors.surface, current_colors.text)),
-                ConsoleOutput::info("Available themes: light, dark, autumn, sunset"),
+                ConsoleOutput::info(format!("Available themes: {}", Theme::BUILT_IN_NAMES.join(", "))),
                 ConsoleOutput::info("Usage: theme <theme_name>"),
             ];
             
             return ConsoleCommandResult::MultipleOutputs(outputs);
-        }
+        };
         
-        let theme_name = args[0].to_lowercase();
-        let new_theme = match theme_name.as_str() {
-            "light" => Theme::Light,
-            "dark" => Theme::Dark,
-            "autumn" => Theme::Autumn,
-            "sunset" => Theme::Sunset,
-            _ => {
-                return ConsoleCommandResult::MultipleOutputs(vec![
-                    ConsoleOutput::error(format!("Unknown theme '{}'. Available themes: light, dark, autumn, sunset", theme_name))
-                ]);
-            }
+        let Some(new_theme) = Theme::from_name(theme_name) else {
+            return ConsoleCommandResult::MultipleOutputs(vec![
+                ConsoleOutput::error(format!("Unknown theme '{}'. Available themes: {}", theme_name, Theme::BUILT_IN_NAMES.join(", ")))
+            ]);
         };
         
         crate::common::theme::set_current_theme(new_theme);
@@ -121,68 +122,83 @@ impl ConsoleCommand for ThemeCommand {
 }
 
 // Error Command
+#[cfg(debug_assertions)]
 struct ErrorCommand;
 
+#[cfg(debug_assertions)]
 impl ConsoleCommand for ErrorCommand {
     fn name(&self) -> &str {
         "error"
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Privileged
+    }
     
     fn description(&self) -> &str {
-        "Display actual error messages used by the application (browser-unsupported|mobile-unsupported|mic-unavailable|mic-permission|browser-error)"
+        "Display actual error messages used by the application (browser-unsupported|mic-unavailable|mic-permission|browser-error|context-lost|panic)"
+    }
+
+    fn arguments(&self) -> Vec<ArgSpec> {
+        vec![ArgSpec::choice(
+            "scenario",
+            "Error message to display",
+            &["browser-unsupported", "mic-unavailable", "mic-permission", "browser-error", "context-lost", "panic"],
+        ).optional()]
     }
     
-    fn execute(&self, args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
-        if args.is_empty() {
+    fn execute(&self, args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        let Some(scenario) = args.text("scenario") else {
             // Show help with available error scenarios
             let outputs = vec![
                 ConsoleOutput::info("Available error scenarios:"),
                 ConsoleOutput::info("  browser-unsupported  - Show browser compatibility error"),
-                ConsoleOutput::info("  mobile-unsupported   - Show mobile device not supported error"),
                 ConsoleOutput::info("  mic-unavailable      - Show microphone not available error"),
                 ConsoleOutput::info("  mic-permission       - Show microphone permission error"),
                 ConsoleOutput::info("  browser-error        - Show general browser error"),
+                ConsoleOutput::info("  context-lost         - Show WebGL context lost error"),
+                ConsoleOutput::info("  panic                - Panic, to show the internal error with its bug report"),
                 ConsoleOutput::info("Usage: error <scenario>"),
             ];
             
             return ConsoleCommandResult::MultipleOutputs(outputs);
-        }
+        };
         
-        let scenario = args[0].to_lowercase();
-        match scenario.as_str() {
+        match scenario {
             "browser-unsupported" => {
-                crate::web::error_message_box::show_error_with_params(&crate::common::shared_types::Error::BrowserApiNotSupported, &["required features"]);
+                crate::web::notifications::show_fatal_error_with_params(&crate::common::shared_types::Error::BrowserApiNotSupported, &["required features"]);
                 ConsoleCommandResult::MultipleOutputs(vec![
                     ConsoleOutput::success("Displayed browser unsupported error")
                 ])
             }
-            "mobile-unsupported" => {
-                crate::web::error_message_box::show_error(&crate::common::shared_types::Error::MobileDeviceNotSupported);
-                ConsoleCommandResult::MultipleOutputs(vec![
-                    ConsoleOutput::success("Displayed mobile unsupported error")
-                ])
-            }
             "mic-unavailable" => {
-                crate::web::error_message_box::show_error(&crate::common::shared_types::Error::MicrophoneNotAvailable);
+                crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::MicrophoneNotAvailable);
                 ConsoleCommandResult::MultipleOutputs(vec![
                     ConsoleOutput::success("Displayed microphone unavailable error")
                 ])
             }
             "mic-permission" => {
-                crate::web::error_message_box::show_error(&crate::common::shared_types::Error::MicrophonePermissionDenied);
+                crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::MicrophonePermissionDenied);
                 ConsoleCommandResult::MultipleOutputs(vec![
                     ConsoleOutput::success("Displayed microphone permission error")
                 ])
             }
             "browser-error" => {
-                crate::web::error_message_box::show_error(&crate::common::shared_types::Error::BrowserError);
+                crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::BrowserError);
                 ConsoleCommandResult::MultipleOutputs(vec![
                     ConsoleOutput::success("Displayed browser error")
                 ])
             }
+            "context-lost" => {
+                crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::GraphicsContextLost);
+                ConsoleCommandResult::MultipleOutputs(vec![
+                    ConsoleOutput::success("Displayed graphics context lost error")
+                ])
+            }
+            "panic" => panic!("Panic requested from the dev console"),
             _ => {
                 ConsoleCommandResult::MultipleOutputs(vec![
-                    ConsoleOutput::error(format!("Unknown error scenario '{}'. Available scenarios: browser-unsupported, mobile-unsupported, mic-unavailable, mic-permission, browser-error", scenario))
+                    ConsoleOutput::error(format!("Unknown error scenario '{}'. Available scenarios: browser-unsupported, mic-unavailable, mic-permission, browser-error, context-lost, panic", scenario))
                 ])
             }
         }
@@ -196,13 +212,19 @@ impl ConsoleCommand for AudioDevicesCommand {
     fn name(&self) -> &str {
         "audio"
     }
+
+    fn category(&self) -> CommandCategory {
+        CommandCategory::Diagnostic
+    }
     
     fn description(&self) -> &str {
         "List all available audio input and output devices (enumerates devices directly, not from cache)"
     }
     
-    fn execute(&self, _args: Vec<&str>, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
-        wasm_bindgen_futures::spawn_local(async move {    
+    fn execute(&self, _args: &ParsedArgs, _registry: &ConsoleCommandRegistry) -> ConsoleCommandResult {
+        ConsoleCommandResult::spawn(|output| async move {
+            output.send(ConsoleOutput::info("Enumerating audio devices..."));
+
             let result: Result<(), AudioError> = async {
                 let window = web_sys::window()
                     .ok_or(AudioError::Generic("No window object".to_string()))?;
@@ -216,61 +238,40 @@ impl ConsoleCommand for AudioDevicesCommand {
                 let devices_js = JsFuture::from(promise).await
                     .map_err(|e| AudioError::Generic(format!("Device enumeration failed: {:?}", e)))?;
                     
-                let devices = js_sys::Array::from(&devices_js);
+                let devices: Vec<web_sys::MediaDeviceInfo> = js_sys::Array::from(&devices_js)
+                    .iter()
+                    .filter_map(|device| device.dyn_into::<web_sys::MediaDeviceInfo>().ok())
+                    .collect();
 
                 // Check if we have permission by looking for any device with a non-empty label
-                let has_permission = (0..devices.length()).any(|i| {
-                    devices.get(i)
-                        .dyn_ref::<web_sys::MediaDeviceInfo>()
-                        .is_some_and(|d| !d.label().is_empty())
-                });
+                let has_permission = devices.iter().any(|d| !d.label().is_empty());
 
-                if !has_permission && devices.length() > 0 {
-                    dev_log_bold!("Found {} audio devices, but device details are unavailable", devices.length());
-                    dev_log_bold!("Grant microphone access to see device names and types");
+                if !has_permission && !devices.is_empty() {
+                    output.send(ConsoleOutput::warning(format!("Found {} audio devices, but device details are unavailable", devices.len())));
+                    output.send(ConsoleOutput::info("Grant microphone access to see device names and types"));
                     return Ok(());
                 }
 
-                let mut input_count = 0;
-                let mut output_count = 0;
+                let inputs: Vec<_> = devices.iter().filter(|d| d.kind() == web_sys::MediaDeviceKind::Audioinput).collect();
+                let outputs: Vec<_> = devices.iter().filter(|d| d.kind() == web_sys::MediaDeviceKind::Audiooutput).collect();
 
-                // First pass: count devices and log input devices
-                dev_log_bold!("Audio Input Devices:");
-                for i in 0..devices.length() {
-                    if let Some(device_info) = devices.get(i).dyn_ref::<web_sys::MediaDeviceInfo>() {
-                        if device_info.kind() == web_sys::MediaDeviceKind::Audioinput {
-                            input_count += 1;
-                            dev_log_bold!("    {}", device_info.label());
-                        }
-                    }
+                output.send(ConsoleOutput::info("Audio Input Devices:"));
+                for device in &inputs {
+                    output.send(ConsoleOutput::info(format!("    {}", device.label())));
                 }
 
-                // Second pass: log output devices
-                dev_log_bold!("Audio Output Devices:");
-                for i in 0..devices.length() {
-                    if let Some(device_info) = devices.get(i).dyn_ref::<web_sys::MediaDeviceInfo>() {
-                        if device_info.kind() == web_sys::MediaDeviceKind::Audiooutput {
-                            output_count += 1;
-                            dev_log_bold!("    {}", device_info.label());
-                        }
-                    }
+                output.send(ConsoleOutput::info("Audio Output Devices:"));
+                for device in &outputs {
+                    output.send(ConsoleOutput::info(format!("    {}", device.label())));
                 }
 
-                dev_log_bold!("Total: {} input devices, {} output devices", input_count, output_count);
+                output.send(ConsoleOutput::success(format!("Total: {} input devices, {} output devices", inputs.len(), outputs.len())));
                 Ok(())
             }.await;
 
-            match result {
-                Ok(()) => {},
-                Err(e) => {
-                    dev_log_bold!("Failed to enumerate audio devices: {:?}", e);
-                }
+            if let Err(e) = result {
+                output.send(ConsoleOutput::error(format!("Failed to enumerate audio devices: {:?}", e)));
             }
-        });
-        
-        ConsoleCommandResult::MultipleOutputs(vec![
-            ConsoleOutput::info("Enumerating audio devices..."),
-            ConsoleOutput::info("Results will appear in browser console (check dev tools)"),
-        ])
+        })
     }
 }
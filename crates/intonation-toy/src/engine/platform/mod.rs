@@ -3,7 +3,9 @@
 // Platform detection and feature support
 
 pub mod commands;
+pub mod wake_lock;
 
+use crate::common::capabilities::{BrowserApi, CapabilityReport, DegradationTier};
 use crate::common::dev_log;
 
 
@@ -12,58 +14,24 @@ use wasm_bindgen::JsCast;
 /// Platform feature validation results
 #[derive(Debug, Clone, PartialEq)]
 pub enum PlatformValidationResult {
-    AllSupported,
-    MissingCriticalApis(Vec<CriticalApi>),
-    MobileDevice,
-}
-
-/// Critical APIs that must be available for application startup
-#[derive(Debug, Clone, PartialEq)]
-pub enum CriticalApi {
-    WebAudioApi,
-    GetUserMedia,
-    AudioWorklet,
-    Canvas,
-    WebGL2,
+    /// Every required API is there; the report tells what else is missing
+    Supported(CapabilityReport),
+    MissingCriticalApis(Vec<BrowserApi>),
 }
 
 /// API support status
 #[derive(Debug, Clone)]
 pub struct ApiStatus {
-    pub api: CriticalApi,
+    pub api: BrowserApi,
     pub supported: bool,
-    #[cfg(debug_assertions)] 
     pub details: Option<String>,
 }
 
-impl std::fmt::Display for CriticalApi {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CriticalApi::WebAudioApi => write!(f, "Web Audio API"),
-            CriticalApi::GetUserMedia => write!(f, "getUserMedia API"),
-            CriticalApi::AudioWorklet => write!(f, "AudioWorklet"),
-            CriticalApi::Canvas => write!(f, "Canvas"),
-            CriticalApi::WebGL2 => write!(f, "WebGL2"),
-        }
-    }
-}
-
 /// Platform feature detection and initialization
 pub struct Platform;
 
 impl Platform {
-    /// Get list of all critical APIs that must be checked (single source of truth)
-    pub fn get_critical_apis() -> Vec<CriticalApi> {
-        vec![
-            CriticalApi::WebAudioApi,
-            CriticalApi::GetUserMedia,
-            CriticalApi::AudioWorklet,
-            CriticalApi::Canvas,
-            CriticalApi::WebGL2,
-        ]
-    }
-
-    /// Get detailed status of all critical APIs
+    /// Get detailed status of every API in `BrowserApi::ALL`
     /// Optimized to reuse shared contexts (AudioContext, Canvas) across multiple checks
     pub fn get_api_status() -> Vec<ApiStatus> {
         let mut results = Vec::new();
@@ -73,12 +41,11 @@ impl Platform {
             Some(w) => w,
             None => {
                 // If window fails, all browser APIs fail
-                return Self::get_critical_apis()
+                return BrowserApi::ALL
                     .into_iter()
                     .map(|api| ApiStatus {
                         api,
                         supported: false,
-                        #[cfg(debug_assertions)] 
                         details: Some("Window object not available".to_string()),
                     })
                     .collect();
@@ -90,9 +57,8 @@ impl Platform {
         // Check getUserMedia (safe, no popup)
         let is_supported = super::audio::permission::is_user_media_supported();
         results.push(ApiStatus {
-            api: CriticalApi::GetUserMedia,
+            api: BrowserApi::GetUserMedia,
             supported: is_supported,
-            #[cfg(debug_assertions)] 
             details: Some(format!("getUserMedia API {}", if is_supported { "available" } else { "not available" })),
         });
         
@@ -109,15 +75,13 @@ impl Platform {
         // WebAudio check using shared context
         results.push(match &audio_context {
             Some(_) => ApiStatus {
-                api: CriticalApi::WebAudioApi,
+                api: BrowserApi::WebAudioApi,
                 supported: true,
-                #[cfg(debug_assertions)] 
                 details: Some("AudioContext creation successful".to_string()),
             },
             None => ApiStatus {
-                api: CriticalApi::WebAudioApi,
+                api: BrowserApi::WebAudioApi,
                 supported: false,
-                #[cfg(debug_assertions)] 
                 details: Some("AudioContext creation failed".to_string()),
             },
         });
@@ -127,16 +91,14 @@ impl Platform {
             Some(ctx) => {
                 let has_audioworklet = js_sys::Reflect::has(ctx, &"audioWorklet".into()).unwrap_or(false);
                 ApiStatus {
-                    api: CriticalApi::AudioWorklet,
+                    api: BrowserApi::AudioWorklet,
                     supported: has_audioworklet,
-                    #[cfg(debug_assertions)] 
                     details: Some(format!("AudioWorklet {}", if has_audioworklet { "API available" } else { "not supported" })),
                 }
             },
             None => ApiStatus {
-                api: CriticalApi::AudioWorklet,
+                api: BrowserApi::AudioWorklet,
                 supported: false,
-                #[cfg(debug_assertions)] 
                 details: Some("AudioContext creation failed".to_string()),
             },
         });
@@ -151,15 +113,13 @@ impl Platform {
         // Canvas check using shared canvas
         results.push(match &canvas {
             Some(_) => ApiStatus {
-                api: CriticalApi::Canvas,
+                api: BrowserApi::Canvas,
                 supported: true,
-                #[cfg(debug_assertions)] 
                 details: Some("Canvas element creation successful".to_string()),
             },
             None => ApiStatus {
-                api: CriticalApi::Canvas,
+                api: BrowserApi::Canvas,
                 supported: false,
-                #[cfg(debug_assertions)] 
                 details: Some("Canvas element creation failed".to_string()),
             },
         });
@@ -167,57 +127,66 @@ impl Platform {
         // WebGL2 check using same canvas
         results.push(match &canvas {
             Some(canvas) => {
-                let (supported, _msg) = match canvas.get_context("webgl2") {
+                let (supported, msg) = match canvas.get_context("webgl2") {
                     Ok(Some(_)) => (true, "WebGL2 context creation successful"),
                     Ok(None) => (false, "WebGL2 context not available"),
                     Err(_) => (false, "WebGL2 not supported"),
                 };
                 ApiStatus {
-                    api: CriticalApi::WebGL2,
+                    api: BrowserApi::WebGL2,
                     supported,
-                    #[cfg(debug_assertions)] 
-                    details: Some(_msg.to_string()),
+                    details: Some(msg.to_string()),
                 }
             },
             None => ApiStatus {
-                api: CriticalApi::WebGL2,
+                api: BrowserApi::WebGL2,
                 supported: false,
-                #[cfg(debug_assertions)] 
                 details: Some("Canvas element creation failed".to_string()),
             },
         });
         
+        // Shared memory, which also needs the page to be cross-origin isolated
+        let has_shared_memory = js_sys::Reflect::has(&window, &"SharedArrayBuffer".into()).unwrap_or(false)
+            && super::audio::ring_buffer::SharedSampleRing::is_supported();
+        results.push(ApiStatus {
+            api: BrowserApi::SharedArrayBuffer,
+            supported: has_shared_memory,
+            details: Some(format!("SharedArrayBuffer {}", if has_shared_memory { "available, page cross-origin isolated" } else { "not available or page not cross-origin isolated" })),
+        });
+
+        let has_offscreen_canvas = js_sys::Reflect::has(&window, &"OffscreenCanvas".into()).unwrap_or(false);
+        results.push(ApiStatus {
+            api: BrowserApi::OffscreenCanvas,
+            supported: has_offscreen_canvas,
+            details: Some(format!("OffscreenCanvas {}", if has_offscreen_canvas { "available" } else { "not available" })),
+        });
+
         results
     }
 
     /// Validate all critical platform features required for application startup
-    /// Returns validation result that caller MUST handle - application should not start if APIs are missing
+    /// Returns validation result that caller MUST handle - application should not start if APIs are missing,
+    /// and runs with the limitations of the report otherwise
     pub fn check_feature_support() -> PlatformValidationResult {
         dev_log!("Validating critical platform APIs...");
         
-        // Check for mobile device first
-        if Self::is_mobile_device() {
-            dev_log!("✗ Mobile device detected - application not supported on mobile devices");
-            return PlatformValidationResult::MobileDevice;
-        }
-        
-        let api_statuses = Self::get_api_status();
-        let missing_apis: Vec<CriticalApi> = api_statuses
-            .iter()
-            .filter(|status| !status.supported)
-            .map(|status| status.api.clone())
-            .collect();
-        
-        if missing_apis.is_empty() {
-            dev_log!("✓ All critical platform APIs are supported");
-            PlatformValidationResult::AllSupported
-        } else {
-            dev_log!("✗ Critical platform APIs are missing: {:?}", missing_apis);
-            PlatformValidationResult::MissingCriticalApis(missing_apis)
+        let report = CapabilityReport::from_support(Self::get_api_status().into_iter().map(|status| (status.api, status.supported)));
+        match report.tier() {
+            DegradationTier::Full => {
+                dev_log!("✓ All platform APIs are supported");
+                PlatformValidationResult::Supported(report)
+            }
+            DegradationTier::Reduced => {
+                dev_log!("✓ All critical platform APIs are supported, running with {:?}", report.limitations());
+                PlatformValidationResult::Supported(report)
+            }
+            DegradationTier::Unsupported => {
+                dev_log!("✗ Critical platform APIs are missing: {:?}", report.missing_required());
+                PlatformValidationResult::MissingCriticalApis(report.missing_required())
+            }
         }
     }
 
-    #[cfg(debug_assertions)]
     /// Get platform information string for debugging
     pub fn get_platform_info() -> String {
         let user_agent = web_sys::window()
@@ -227,46 +196,11 @@ impl Platform {
         format!("UserAgent: {}", user_agent)
     }
 
-    /// Check if the current platform is a mobile device
-    pub fn is_mobile_device() -> bool {
-        let window = match web_sys::window() {
-            Some(w) => w,
-            None => return false,
-        };
-
-        let navigator = window.navigator();
-
-        // Check user agent string first
-        if let Ok(ua) = navigator.user_agent() {
-            let ua_lower = ua.to_lowercase();
-            if ["android", "iphone", "ipad", "ipod", "blackberry",
-                "windows phone", "webos", "opera mini", "iemobile",
-                "mobile", "tablet"]
-                .iter()
-                .any(|&pattern| ua_lower.contains(pattern))
-            {
-                return true;
-            }
-        }
-
-        // Check for Safari on iPad using desktop mode
-        // iPad with desktop mode has maxTouchPoints > 1 and Mac user agent
-        if let Ok(max_touch_points) = js_sys::Reflect::get(&navigator, &"maxTouchPoints".into()) {
-            if let Some(touch_points) = max_touch_points.as_f64() {
-                if touch_points > 1.0 {
-                    // Check if it claims to be Mac (iPad Safari in desktop mode)
-                    if let Ok(ua) = navigator.user_agent() {
-                        let ua_lower = ua.to_lowercase();
-                        if ua_lower.contains("macintosh") && ua_lower.contains("safari") {
-                            dev_log!("Detected Safari on iPad using desktop mode (maxTouchPoints: {})", touch_points);
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-
-        false
+    /// Whether the primary input is touch, as on phones and tablets, which
+    /// calls for the touch layout; touch screens on laptops report the mouse
+    pub fn is_touch_primary() -> bool {
+        web_sys::window()
+            .and_then(|window| window.match_media("(pointer: coarse)").ok().flatten())
+            .is_some_and(|query| query.matches())
     }
 }
-
@@ -0,0 +1,203 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Keeping the screen on during practice
+//!
+//! Singing or playing along leaves the screen untouched for minutes, long
+//! enough for it to dim and lock. The engine holds a wake lock while audio is
+//! analysed: the Screen Wake Lock API where the browser has it, else a muted
+//! video playing out of sight, which browsers without the API take as reason
+//! enough to keep the screen on. Browsers let go of either when the page is
+//! hidden, so the engine asks again once it is shown.
+//!
+//! Requests are asynchronous and may be refused, for instance on low battery;
+//! a refused request is not repeated before `WAKE_LOCK_RETRY_MS` has passed.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlVideoElement;
+use crate::app_config::WAKE_LOCK_RETRY_MS;
+use crate::common::dev_log;
+
+/// A way of keeping the screen on
+pub trait WakeLock {
+    /// Whether the screen is kept on
+    fn is_held(&self) -> bool;
+    /// Ask to keep the screen on; does nothing while a request is in flight
+    /// or shortly after one was refused
+    fn acquire(&mut self);
+    fn release(&mut self);
+}
+
+/// The best wake lock the browser has
+pub fn create() -> Box<dyn WakeLock> {
+    let has_api = web_sys::window()
+        .is_some_and(|window| js_sys::Reflect::has(&window.navigator(), &"wakeLock".into()).unwrap_or(false));
+    if has_api {
+        Box::new(ScreenWakeLock::default())
+    } else {
+        dev_log!("Screen Wake Lock API not available, keeping the screen on with a video");
+        Box::new(VideoWakeLock::default())
+    }
+}
+
+/// Request state shared with the callbacks of a pending request
+#[derive(Default)]
+struct Request {
+    pending: Cell<bool>,
+    /// Wanted once the pending request completes; a lock released before
+    /// then is let go as soon as it arrives
+    wanted: Cell<bool>,
+    retry_after_ms: Cell<f64>,
+}
+
+impl Request {
+    /// Whether a new request may start now, marking it as started
+    fn start(&self) -> bool {
+        self.wanted.set(true);
+        if self.pending.get() || js_sys::Date::now() < self.retry_after_ms.get() {
+            return false;
+        }
+        self.pending.set(true);
+        true
+    }
+
+    fn refused(&self) {
+        self.pending.set(false);
+        self.retry_after_ms.set(js_sys::Date::now() + WAKE_LOCK_RETRY_MS);
+    }
+}
+
+/// The Screen Wake Lock API, through `navigator.wakeLock.request("screen")`
+#[derive(Default)]
+pub struct ScreenWakeLock {
+    request: Rc<Request>,
+    /// The `WakeLockSentinel` of the lock
+    sentinel: Rc<RefCell<Option<JsValue>>>,
+}
+
+impl WakeLock for ScreenWakeLock {
+    fn is_held(&self) -> bool {
+        self.sentinel.borrow().as_ref().is_some_and(|sentinel| {
+            !js_sys::Reflect::get(sentinel, &"released".into()).is_ok_and(|released| released.is_truthy())
+        })
+    }
+
+    fn acquire(&mut self) {
+        if !self.request.start() {
+            return;
+        }
+        let promise = web_sys::window()
+            .ok_or_else(|| JsValue::from("No window"))
+            .and_then(|window| js_sys::Reflect::get(&window.navigator(), &"wakeLock".into()))
+            .and_then(|wake_lock| call_method(&wake_lock, "request", &"screen".into()));
+        let request = self.request.clone();
+        let sentinel = self.sentinel.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = match promise.and_then(|promise| promise.dyn_into::<js_sys::Promise>()) {
+                Ok(promise) => wasm_bindgen_futures::JsFuture::from(promise).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(lock) if request.wanted.get() => {
+                    request.pending.set(false);
+                    dev_log!("Screen wake lock acquired");
+                    *sentinel.borrow_mut() = Some(lock);
+                }
+                Ok(lock) => {
+                    request.pending.set(false);
+                    let _ = call_method(&lock, "release", &JsValue::UNDEFINED);
+                }
+                Err(_e) => {
+                    dev_log!("Screen wake lock refused: {:?}", _e);
+                    request.refused();
+                }
+            }
+        });
+    }
+
+    fn release(&mut self) {
+        self.request.wanted.set(false);
+        if let Some(sentinel) = self.sentinel.borrow_mut().take() {
+            let _ = call_method(&sentinel, "release", &JsValue::UNDEFINED);
+            dev_log!("Screen wake lock released");
+        }
+    }
+}
+
+/// A muted, looping video of a blank canvas, kept out of sight
+#[derive(Default)]
+pub struct VideoWakeLock {
+    request: Rc<Request>,
+    video: Option<HtmlVideoElement>,
+}
+
+impl VideoWakeLock {
+    fn video(&mut self) -> Result<HtmlVideoElement, JsValue> {
+        if let Some(video) = &self.video {
+            return Ok(video.clone());
+        }
+        let document = web_sys::window().and_then(|window| window.document()).ok_or("No document")?;
+        let canvas = document.create_element("canvas")?.dyn_into::<web_sys::HtmlCanvasElement>()?;
+        canvas.set_width(2);
+        canvas.set_height(2);
+        let video = document.create_element("video")?.dyn_into::<HtmlVideoElement>()?;
+        video.set_muted(true);
+        video.set_loop(true);
+        video.set_attribute("playsinline", "")?;
+        video.set_attribute("aria-hidden", "true")?;
+        video.style().set_css_text("position: fixed; width: 1px; height: 1px; opacity: 0; pointer-events: none;");
+        video.set_src_object(Some(&canvas.capture_stream()?));
+        document.body().ok_or("No body")?.append_child(&video)?;
+        self.video = Some(video.clone());
+        Ok(video)
+    }
+}
+
+impl WakeLock for VideoWakeLock {
+    fn is_held(&self) -> bool {
+        self.video.as_ref().is_some_and(|video| !video.paused())
+    }
+
+    fn acquire(&mut self) {
+        if !self.request.start() {
+            return;
+        }
+        let play = self.video().and_then(|video| video.play());
+        let request = self.request.clone();
+        let video = self.video.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = match play {
+                Ok(promise) => wasm_bindgen_futures::JsFuture::from(promise).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(_) => {
+                    request.pending.set(false);
+                    if !request.wanted.get()
+                        && let Some(video) = video
+                    {
+                        let _ = video.pause();
+                    }
+                }
+                Err(_e) => {
+                    dev_log!("Wake lock video refused to play: {:?}", _e);
+                    request.refused();
+                }
+            }
+        });
+    }
+
+    fn release(&mut self) {
+        self.request.wanted.set(false);
+        if let Some(video) = &self.video {
+            let _ = video.pause();
+        }
+    }
+}
+
+fn call_method(target: &JsValue, name: &str, argument: &JsValue) -> Result<JsValue, JsValue> {
+    js_sys::Reflect::get(target, &name.into())?
+        .dyn_into::<js_sys::Function>()?
+        .call1(target, argument)
+}
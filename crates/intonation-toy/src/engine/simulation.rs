@@ -0,0 +1,525 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+//! Headless stand-in for the audio engine
+//!
+//! `SimulatedAudioBackend` produces the same `EngineUpdateResult` as
+//! `AudioEngine::update`, but analyzes samples from a synthetic generator or
+//! a WAV fixture instead of the microphone. Each update advances the input by
+//! one display frame, so tests can run minutes of audio without a browser.
+//! Pitch and loudness go through the engine's own detectors; the spectrum,
+//! harmonics, MIDI and device state are left empty.
+
+use std::f32::consts::PI;
+use std::path::Path;
+
+use crate::common::shared_types::{
+    AudioAnalysis, AudioLifecycleState, BatchSizeMode, BatchingStatus, EngineUpdateResult,
+    InputDevices, InputGainStatus, MidiOutputs, Pitch, RecordingStatus, Volume,
+};
+use crate::engine::audio::loudness::LoudnessMeter;
+use crate::engine::audio::pitch_detector::{PitchDetector, PitchDetectorConfig};
+use crate::engine::AudioBackend;
+use crate::model::ModelLayerActions;
+
+/// Frame rate at which `update` is assumed to be called
+const SIMULATED_FRAME_RATE: u32 = 60;
+
+/// Where the simulated input comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulatedSource {
+    Silence,
+    Sine { frequency: f32, amplitude: f32 },
+    /// Exponential sweep from `from_hz` to `to_hz`, holding `to_hz` afterwards
+    Sweep { from_hz: f32, to_hz: f32, duration_seconds: f32, amplitude: f32 },
+    /// Sine whose pitch oscillates `depth_cents` around `center_hz`
+    Vibrato { center_hz: f32, depth_cents: f32, rate_hz: f32, amplitude: f32 },
+    /// Uniform white noise
+    Noise { amplitude: f32 },
+    /// Mono samples played once, followed by silence
+    Fixture(Vec<f32>),
+}
+
+/// Deterministic sample generator for a `SimulatedSource`
+struct Generator {
+    source: SimulatedSource,
+    sample_rate: f32,
+    position: usize,
+    phase: f32,
+    noise_state: u32,
+}
+
+impl Generator {
+    fn new(source: SimulatedSource, sample_rate: u32) -> Self {
+        Self {
+            source,
+            sample_rate: sample_rate as f32,
+            position: 0,
+            phase: 0.0,
+            noise_state: 0x9E37_79B9,
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let time = self.position as f32 / self.sample_rate;
+        self.position += 1;
+        match self.source {
+            SimulatedSource::Silence => 0.0,
+            SimulatedSource::Sine { frequency, amplitude } => amplitude * self.advance_phase(frequency),
+            SimulatedSource::Sweep { from_hz, to_hz, duration_seconds, amplitude } => {
+                let progress = (time / duration_seconds).min(1.0);
+                let frequency = from_hz * (to_hz / from_hz).powf(progress);
+                amplitude * self.advance_phase(frequency)
+            }
+            SimulatedSource::Vibrato { center_hz, depth_cents, rate_hz, amplitude } => {
+                let cents = depth_cents * (2.0 * PI * rate_hz * time).sin();
+                amplitude * self.advance_phase(center_hz * 2f32.powf(cents / 1200.0))
+            }
+            SimulatedSource::Noise { amplitude } => {
+                // xorshift32; reproducible across runs
+                self.noise_state ^= self.noise_state << 13;
+                self.noise_state ^= self.noise_state >> 17;
+                self.noise_state ^= self.noise_state << 5;
+                amplitude * (self.noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            }
+            SimulatedSource::Fixture(ref samples) => samples.get(self.position - 1).copied().unwrap_or(0.0),
+        }
+    }
+
+    /// Sine at the current phase, then step the phase by `frequency`
+    fn advance_phase(&mut self, frequency: f32) -> f32 {
+        let value = (2.0 * PI * self.phase).sin();
+        self.phase = (self.phase + frequency / self.sample_rate).fract();
+        value
+    }
+}
+
+/// Pitch and loudness of consecutive frames of input, as native backends
+/// analyze them
+pub struct FrameAnalyzer {
+    pitch_detector: PitchDetector,
+    loudness: LoudnessMeter,
+    /// Most recent input, oldest first, one pitch analysis window long
+    window: Vec<f32>,
+}
+
+impl FrameAnalyzer {
+    pub fn new(sample_rate: u32) -> Result<Self, String> {
+        let config = PitchDetectorConfig::default();
+        let window_size = config.sample_window_size;
+        let pitch_detector = PitchDetector::new(config, sample_rate)
+            .map_err(|e| format!("Failed to create pitch detector: {}", e))?;
+        Ok(Self {
+            pitch_detector,
+            loudness: LoudnessMeter::new(sample_rate),
+            window: Vec::with_capacity(window_size),
+        })
+    }
+
+    pub fn configure_pitch_detection(&mut self, config: PitchDetectorConfig) -> Result<(), String> {
+        self.pitch_detector.configure(config)?;
+        self.window.clear();
+        Ok(())
+    }
+
+    pub fn pitch_detector_config(&self) -> &PitchDetectorConfig {
+        self.pitch_detector.config()
+    }
+
+    /// Apply the pitch detection and window settings the model asks for,
+    /// keeping the rest of the configuration
+    pub fn execute_actions(&mut self, model_actions: &ModelLayerActions) -> Result<(), String> {
+        let mut config = self.pitch_detector_config().clone();
+        if let Some(detection) = &model_actions.pitch_detection_configuration {
+            config.algorithm = detection.algorithm;
+            config.power_threshold = detection.power_threshold;
+            config.clarity_threshold = detection.clarity_threshold;
+        }
+        if let Some(window) = &model_actions.analysis_window_configuration {
+            config.sample_window_size = window.window_size;
+            config.hop_size = window.hop_size;
+        }
+        if &config == self.pitch_detector_config() {
+            return Ok(());
+        }
+        self.configure_pitch_detection(config)
+    }
+
+    /// Analyze the input that followed the previous frame
+    pub fn analyze(&mut self, frame: &[f32]) -> AudioAnalysis {
+        let window_size = self.pitch_detector.config().sample_window_size;
+        self.window.extend_from_slice(frame);
+        let overflow = self.window.len().saturating_sub(window_size);
+        self.window.drain(..overflow);
+
+        let result = if self.window.len() == window_size {
+            self.pitch_detector.analyze(&self.window)
+        } else {
+            None
+        };
+        let pitch = result.as_ref().map_or(Pitch::NotDetected, |result| Pitch::Detected(result.frequency));
+
+        self.loudness.process(frame);
+        let peak_amplitude = frame.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        let rms_amplitude = (frame.iter().map(|sample| sample * sample).sum::<f32>()
+            / frame.len().max(1) as f32).sqrt();
+
+        AudioAnalysis {
+            volume_level: Volume {
+                peak_amplitude,
+                rms_amplitude,
+                a_weighted_rms_amplitude: self.loudness.a_weighted_rms(),
+                short_term_lufs: self.loudness.short_term_lufs(),
+            },
+            pitch,
+            clarity: result.map(|result| result.clarity),
+            fft_data: None,
+            harmonics: None,
+            formants: None,
+        }
+    }
+}
+
+/// Engine replacement for native builds and CI
+pub struct SimulatedAudioBackend {
+    generator: Generator,
+    sample_rate: u32,
+    frame_length: usize,
+    analyzer: FrameAnalyzer,
+    frame: Vec<f32>,
+    elapsed_samples: u64,
+}
+
+impl SimulatedAudioBackend {
+    pub fn new(source: SimulatedSource, sample_rate: u32) -> Result<Self, String> {
+        Ok(Self {
+            generator: Generator::new(source, sample_rate),
+            sample_rate,
+            frame_length: (sample_rate / SIMULATED_FRAME_RATE) as usize,
+            analyzer: FrameAnalyzer::new(sample_rate)?,
+            frame: Vec::new(),
+            elapsed_samples: 0,
+        })
+    }
+
+    /// Backend that plays a WAV fixture at the file's own sample rate
+    pub fn from_wav_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let (samples, sample_rate) = decode_wav(&bytes)?;
+        Self::new(SimulatedSource::Fixture(samples), sample_rate)
+    }
+
+    /// Switch to another source; analysis state carries over like a change of input
+    pub fn set_source(&mut self, source: SimulatedSource) {
+        self.generator = Generator::new(source, self.sample_rate);
+    }
+
+    pub fn configure_pitch_detection(&mut self, config: PitchDetectorConfig) -> Result<(), String> {
+        self.analyzer.configure_pitch_detection(config)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Seconds of input analyzed so far
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed_samples as f64 / self.sample_rate as f64
+    }
+
+    /// Analyze the next frame of input
+    pub fn update(&mut self) -> EngineUpdateResult {
+        self.frame.clear();
+        for _ in 0..self.frame_length {
+            let sample = self.generator.next_sample();
+            self.frame.push(sample);
+        }
+        self.elapsed_samples += self.frame_length as u64;
+
+        EngineUpdateResult {
+            timestamp_ms: self.elapsed_seconds() * 1000.0,
+            audio_analysis: Some(self.analyzer.analyze(&self.frame)),
+            audio_errors: Vec::new(),
+            input_devices: InputDevices::default(),
+            input_listening: true,
+            recording: RecordingStatus::default(),
+            midi_events: Vec::new(),
+            midi_outputs: MidiOutputs::default(),
+            input_gain: InputGainStatus { gain: 1.0, automatic: false, calibration: None },
+            batching: BatchingStatus { mode: BatchSizeMode::default(), batch_size: self.frame_length },
+            lifecycle: AudioLifecycleState::Running,
+            recovery_events: Vec::new(),
+            analyzed_recording: None,
+        }
+    }
+}
+
+impl AudioBackend for SimulatedAudioBackend {
+    fn update(&mut self) -> EngineUpdateResult {
+        SimulatedAudioBackend::update(self)
+    }
+
+    fn execute_actions(&mut self, model_actions: ModelLayerActions) {
+        if let Err(e) = self.analyzer.execute_actions(&model_actions) {
+            crate::common::warn_log!("Simulation: failed to configure pitch detection: {}", e);
+        }
+    }
+}
+
+/// Mono samples and sample rate of a PCM (16, 24 or 32 bit) or 32-bit float
+/// WAV file; channels are averaged
+pub fn decode_wav(bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a RIFF WAVE file".to_string());
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes([bytes[offset + 4], bytes[offset + 5], bytes[offset + 6], bytes[offset + 7]]) as usize;
+        let body = &bytes[offset + 8..(offset + 8 + size).min(bytes.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => format = Some(body),
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset += 8 + size + size % 2;
+    }
+
+    let format = format.ok_or("WAV file has no fmt chunk")?;
+    let data = data.ok_or("WAV file has no data chunk")?;
+    let format_tag = u16::from_le_bytes([format[0], format[1]]);
+    let channels = u16::from_le_bytes([format[2], format[3]]) as usize;
+    let sample_rate = u32::from_le_bytes([format[4], format[5], format[6], format[7]]);
+    let bits_per_sample = u16::from_le_bytes([format[14], format[15]]);
+    if channels == 0 {
+        return Err("WAV file has no channels".to_string());
+    }
+
+    let decode: fn(&[u8]) -> f32 = match (format_tag, bits_per_sample) {
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0,
+        (1, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(format!("Unsupported WAV format {} with {} bits per sample", format_tag, bits_per_sample)),
+    };
+    let bytes_per_sample = bits_per_sample as usize / 8;
+    let samples = data
+        .chunks_exact(bytes_per_sample * channels)
+        .map(|frame| frame.chunks_exact(bytes_per_sample).map(decode).sum::<f32>() / channels as f32)
+        .collect();
+    Ok((samples, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::audio::recording::encode_wav;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn detected_frequency(result: &EngineUpdateResult) -> Option<f32> {
+        match result.audio_analysis.as_ref()?.pitch {
+            Pitch::Detected(frequency) => Some(frequency),
+            Pitch::NotDetected => None,
+        }
+    }
+
+    #[test]
+    fn test_sine_is_detected() {
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Sine { frequency: 220.0, amplitude: 0.5 }, SAMPLE_RATE).unwrap();
+        let result = (0..30).map(|_| backend.update()).last().unwrap();
+        let frequency = detected_frequency(&result).expect("pitch should be detected");
+        assert!((frequency - 220.0).abs() < 1.0, "detected {}", frequency);
+        assert!((backend.elapsed_seconds() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_silence_is_not_detected() {
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Silence, SAMPLE_RATE).unwrap();
+        for _ in 0..30 {
+            let result = backend.update();
+            assert_eq!(detected_frequency(&result), None);
+            assert_eq!(result.audio_analysis.unwrap().volume_level.peak_amplitude, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_wav_fixture_round_trip() {
+        let samples: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|n| 0.5 * (2.0 * PI * 330.0 * n as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        let (decoded, sample_rate) = decode_wav(&encode_wav(&samples, SAMPLE_RATE)).unwrap();
+        assert_eq!(sample_rate, SAMPLE_RATE);
+        assert_eq!(decoded.len(), samples.len());
+
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Fixture(decoded), sample_rate).unwrap();
+        let result = (0..30).map(|_| backend.update()).last().unwrap();
+        let frequency = detected_frequency(&result).expect("pitch should be detected");
+        assert!((frequency - 330.0).abs() < 1.5, "detected {}", frequency);
+    }
+
+    #[test]
+    fn test_model_runs_against_the_simulation() {
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Sine { frequency: 440.0, amplitude: 0.5 }, SAMPLE_RATE).unwrap();
+        let mut model = crate::model::DataModel::default();
+        let mut result = None;
+        for _ in 0..30 {
+            let engine_data = AudioBackend::update(&mut backend);
+            result = Some(model.update(engine_data));
+            let model_actions = model.process_user_actions(Default::default());
+            AudioBackend::execute_actions(&mut backend, model_actions);
+        }
+        let result = result.unwrap();
+        assert_eq!(result.closest_midi_note, Some(69));
+        assert!(result.cents_offset.abs() < 5.0, "cents offset {}", result.cents_offset);
+    }
+
+    #[test]
+    fn test_target_note_ends_with_the_melody() {
+        use crate::common::presentation_actions::{PresentationLayerActions, ReferenceTrackControl};
+
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Silence, SAMPLE_RATE).unwrap();
+        let mut model = crate::model::DataModel::default();
+        let notes = crate::common::reference_track::parse_reference_notes("60:100 62:100").unwrap();
+        let mut presentation_actions = PresentationLayerActions {
+            reference_track_control: Some(ReferenceTrackControl::Start(notes)),
+            ..Default::default()
+        };
+        let mut targets = Vec::new();
+        for _ in 0..30 {
+            let result = model.update(AudioBackend::update(&mut backend));
+            targets.push(result.target_note);
+            let model_actions = model.process_user_actions(std::mem::take(&mut presentation_actions));
+            AudioBackend::execute_actions(&mut backend, model_actions);
+        }
+        assert!(targets.contains(&Some(60)));
+        assert!(targets.contains(&Some(62)));
+        assert_eq!(targets.last(), Some(&None));
+    }
+
+    #[test]
+    fn test_drone_is_configured_again_after_a_restart() {
+        use crate::common::presentation_actions::{ConfigureTonalCenter, PresentationLayerActions};
+        use crate::common::shared_types::{RecoveryEvent, RecoveryReason};
+
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Silence, SAMPLE_RATE).unwrap();
+        let mut model = crate::model::DataModel::default();
+        model.update(AudioBackend::update(&mut backend));
+        let model_actions = model.process_user_actions(PresentationLayerActions {
+            tonal_center_configuration: Some(ConfigureTonalCenter { note: 57, volume: 0.5 }),
+            ..Default::default()
+        });
+        assert!(model_actions.tonal_center_configuration.is_some());
+
+        model.update(AudioBackend::update(&mut backend));
+        assert_eq!(model.process_user_actions(Default::default()).tonal_center_configuration, None);
+
+        let mut engine_data = AudioBackend::update(&mut backend);
+        engine_data.recovery_events.push(RecoveryEvent::Restarted { reason: RecoveryReason::WorkletStalled, attempt: 1 });
+        model.update(engine_data);
+        let resent = model.process_user_actions(Default::default()).tonal_center_configuration;
+        assert_eq!(resent.map(|config| config.volume), Some(0.5));
+        assert_eq!(model.process_user_actions(Default::default()).tonal_center_configuration, None);
+    }
+
+    #[test]
+    fn test_metronome_clicks_are_scheduled_ahead() {
+        use crate::common::presentation_actions::{ConfigureMetronome, PresentationLayerActions};
+        use crate::common::metronome::MetronomeTick;
+        use crate::common::shared_types::MetronomeSettings;
+
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Silence, SAMPLE_RATE).unwrap();
+        let mut model = crate::model::DataModel::default();
+        let configure = |tempo_bpm: f32| PresentationLayerActions {
+            metronome_configuration: Some(ConfigureMetronome { settings: Some(MetronomeSettings { tempo_bpm, subdivision: 2, click: true }) }),
+            ..Default::default()
+        };
+
+        model.update(AudioBackend::update(&mut backend));
+        assert_eq!(model.process_user_actions(configure(1000.0)).metronome_clicks, None);
+
+        let engine_data = AudioBackend::update(&mut backend);
+        let start_ms = engine_data.timestamp_ms;
+        model.update(engine_data);
+        let started = model.process_user_actions(configure(120.0)).metronome_clicks.unwrap();
+        assert!(started.cancel_scheduled);
+        assert_eq!(started.clicks[0], MetronomeTick { time_ms: start_ms, accent: true });
+
+        // Every click is scheduled once, before it is due
+        let mut clicks = started.clicks;
+        for _ in 0..60 {
+            let engine_data = AudioBackend::update(&mut backend);
+            let now_ms = engine_data.timestamp_ms;
+            model.update(engine_data);
+            if let Some(scheduled) = model.process_user_actions(Default::default()).metronome_clicks {
+                assert!(!scheduled.cancel_scheduled);
+                assert!(scheduled.clicks.iter().all(|click| click.time_ms > now_ms));
+                clicks.extend(scheduled.clicks);
+            }
+        }
+        let times: Vec<f64> = clicks.iter().map(|click| click.time_ms).collect();
+        assert!(times.windows(2).all(|pair| (pair[1] - pair[0] - 250.0).abs() < 1e-6), "{:?}", times);
+        assert!(clicks.iter().step_by(2).all(|click| click.accent));
+
+        let stopped = model.process_user_actions(PresentationLayerActions {
+            metronome_configuration: Some(ConfigureMetronome { settings: None }),
+            ..Default::default()
+        });
+        assert_eq!(stopped.metronome_clicks.map(|clicks| clicks.cancel_scheduled), Some(true));
+    }
+
+    #[test]
+    fn test_only_unrequested_device_changes_are_errors() {
+        use crate::common::presentation_actions::{PresentationLayerActions, SelectInputDevice};
+        use crate::common::shared_types::{AudioInputDevice, Error};
+
+        let mut backend = SimulatedAudioBackend::new(SimulatedSource::Silence, SAMPLE_RATE).unwrap();
+        let mut model = crate::model::DataModel::default();
+        let mut update_with_active = |model: &mut crate::model::DataModel, device_id: &str| {
+            let mut engine_data = AudioBackend::update(&mut backend);
+            engine_data.input_devices = InputDevices {
+                available: ["built-in", "usb"].map(|id| AudioInputDevice { device_id: id.to_string(), label: id.to_string() }).to_vec(),
+                active_device_id: Some(device_id.to_string()),
+            };
+            model.update(engine_data).errors
+        };
+
+        update_with_active(&mut model, "built-in");
+        model.process_user_actions(PresentationLayerActions {
+            input_device_selection: Some(SelectInputDevice { device_id: "usb".to_string() }),
+            ..Default::default()
+        });
+        assert!(update_with_active(&mut model, "usb").is_empty());
+        // Falling back once the selected device is unplugged is reported
+        assert_eq!(update_with_active(&mut model, "built-in"), vec![Error::InputDeviceChanged("built-in".to_string())]);
+        assert!(update_with_active(&mut model, "usb").contains(&Error::InputDeviceChanged("usb".to_string())));
+    }
+
+    #[test]
+    fn test_melody_from_a_recording() {
+        use crate::common::music_theory::{frequency_to_midi_note_and_cents, midi_note_to_standard_frequency};
+        use crate::common::shared_types::{Scale, TuningSystem};
+        use crate::engine::audio::recording::analyze_pitch_track;
+
+        let tone = |note: Option<u8>, seconds: f32| -> Vec<f32> {
+            let frequency = note.map_or(0.0, midi_note_to_standard_frequency);
+            (0..(seconds * SAMPLE_RATE as f32) as usize)
+                .map(|n| 0.5 * (2.0 * PI * frequency * n as f32 / SAMPLE_RATE as f32).sin())
+                .collect()
+        };
+        let samples = [tone(Some(60), 0.5), tone(Some(64), 0.5), tone(None, 0.3), tone(Some(67), 0.5)].concat();
+
+        let track = analyze_pitch_track(&samples, SAMPLE_RATE, PitchDetectorConfig::default(), 10.0).unwrap();
+        let notes = crate::common::reference_track::reference_notes_from_pitch_track(&track, |frequency| {
+            frequency_to_midi_note_and_cents(frequency, 60, 440.0, &TuningSystem::EqualTemperament, Scale::Chromatic)
+        });
+        let pitches: Vec<_> = notes.iter().map(|note| note.note).collect();
+        assert_eq!(pitches, vec![Some(60), Some(64), None, Some(67)]);
+        assert!((notes[1].duration_ms - 500.0).abs() < 50.0, "duration {}", notes[1].duration_ms);
+    }
+}
@@ -1,43 +1,46 @@
 
-#![cfg(target_arch = "wasm32")]
-
 pub mod app_config;
 pub mod engine;
 pub mod model;
 pub mod presentation;
 pub mod web;
 pub mod common;
-#[cfg(debug_assertions)]
 pub(crate) mod debug;
 
+#[cfg(target_arch = "wasm32")]
 use {
     wasm_bindgen::JsCast,
     wasm_bindgen::closure::Closure,
     wasm_bindgen::prelude::wasm_bindgen,
     engine::platform::{Platform, PlatformValidationResult},
+    common::capabilities::{BrowserApi, Limitation},
     engine::audio::audio_context::{create_audio_context, load_worklet_module},
 };
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen(start)]
 pub async fn start() {
-    #[cfg(debug_assertions)]
-    console_error_panic_hook::set_once();
+    web::bug_report::setup_bug_reports();
 
     // Log version info
     crate::log!("Intonation Toy v{}", env!("CARGO_PKG_VERSION"));
 
-    crate::common::theme::initialize_theme(crate::app_config::DEFAULT_THEME);
+    let settings = web::storage::settings();
+    crate::common::theme::initialize_theme(settings.theme.clone().unwrap_or(crate::app_config::DEFAULT_THEME));
     crate::web::styling::apply_theme();
-
-    {
-        // Bail out if any required API is missing
-
-        let support = Platform::check_feature_support();
-        if support != PlatformValidationResult::AllSupported {
-            crate::common::error_handling::handle_platform_validation_error(support);
+    crate::common::localization::set_current_language(
+        settings.language.or_else(web::localization::browser_language).unwrap_or_default(),
+    );
+    crate::web::localization::apply_language();
+
+    // Bail out if any required API is missing, else run with what is there
+    let mut capabilities = match Platform::check_feature_support() {
+        PlatformValidationResult::Supported(report) => report,
+        failure => {
+            crate::common::error_handling::handle_platform_validation_error(failure);
             return;
         }
-    }
+    };
 
     {
         // Canvas resizing
@@ -48,29 +51,60 @@ pub async fn start() {
         
         web_sys::window().unwrap().add_event_listener_with_callback("resize", resize_canvas_callback.as_ref().unchecked_ref()).unwrap();
         resize_canvas_callback.forget();
+        web::layout::setup_layout_listeners();
+        web::layout::set_touch(Platform::is_touch_primary());
+    }
+
+    {
+        // Offline use and installing
+        web::pwa::register_service_worker();
+        web::pwa::setup_install_prompt();
+    }
+
+    {
+        // Usage telemetry, with the user's consent
+        web::telemetry::setup_telemetry();
+    }
+
+    {
+        // WebGL context loss
+        //
+        // GPU resources are owned by three-d and are not recreated on restore,
+        // so a lost context is reported instead of leaving a blank canvas.
+
+        let context_lost_callback = Closure::wrap(Box::new(move || {
+            crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::GraphicsContextLost);
+        }) as Box<dyn FnMut()>);
+
+        web::utils::get_canvas().add_event_listener_with_callback("webglcontextlost", context_lost_callback.as_ref().unchecked_ref()).unwrap();
+        context_lost_callback.forget();
     }
 
     let audio_context = create_audio_context()
         .expect("Failed to create audio context");
 
-    load_worklet_module(&audio_context).await
-        .expect("Failed to load worklet module");
+    if capabilities.is_supported(BrowserApi::AudioWorklet)
+        && let Err(_e) = load_worklet_module(&audio_context).await
+    {
+        crate::common::warn_log!("Capturing input without the AudioWorklet: {}", _e);
+        capabilities.mark_missing(BrowserApi::AudioWorklet);
+    }
+    let use_worklet = capabilities.is_supported(BrowserApi::AudioWorklet);
+    web::capabilities::set_report(capabilities);
 
     web::utils::resize_canvas();
     web::utils::show_first_click_overlay();
     web::utils::hide_preloader();
 
-    let media_stream = match web::user_media_permission::ask_for_permission().await {
+    let media_stream = match web::user_media_permission::request_microphone().await {
         Ok(stream) => stream,
-        Err(_) => {
-            crate::web::error_message_box::show_error(&crate::common::shared_types::Error::MicrophonePermissionDenied);
+        Err(err) => {
+            crate::web::notifications::show_fatal_error(&err);
             return;
         }
     };
 
-    web::utils::hide_first_click_overlay();
-
-    let engine = match engine::AudioEngine::new(media_stream, audio_context) {
+    let engine = match engine::AudioEngine::new(media_stream, audio_context, use_worklet) {
         Ok(engine) => engine,
         Err(err) => {
             crate::common::error_log!("Failed to create AudioEngine: {:?}", err);
@@ -78,19 +112,27 @@ pub async fn start() {
         }
     };
     
-    let (model, display_range) = if let Some(stored_config) = web::storage::load_config() {
+    let (mut model, display_range) = if let Some(tuning) = settings.tuning {
         (
             model::DataModel::new(
-                stored_config.tonal_center_note,
-                stored_config.tuning_system,
-                stored_config.scale
+                tuning.tonal_center_note,
+                tuning.concert_pitch_hz,
+                tuning.tuning_system,
+                tuning.scale,
+                tuning.transposition,
+                tuning.note_naming,
+                tuning.tolerance_profile
             ),
-            stored_config.display_range
+            tuning.display_range
         )
     } else {
         (model::DataModel::default(), crate::app_config::DEFAULT_DISPLAY_RANGE)
     };
 
+    if let Some(statistics) = web::storage::load_session_statistics() {
+        model.restore_session_statistics(&statistics);
+    }
+
     // Set the initial display range before creating the presenter
     web::sidebar_controls::set_initial_display_range(display_range.clone());
 
@@ -98,6 +140,32 @@ pub async fn start() {
         Ok(presenter) => {
             // Set the loaded display range
             presenter.borrow_mut().on_display_range_changed(display_range);
+            if !settings.tutorial_seen {
+                presenter.borrow_mut().on_tutorial_requested();
+            }
+            if settings.midi_enabled {
+                presenter.borrow_mut().on_midi_enabled();
+            }
+            if let Some(strategy) = settings.smoothing {
+                presenter.borrow_mut().on_smoothing_configured(strategy);
+            }
+            if let Some(pitch_detection) = settings.pitch_detection {
+                presenter.borrow_mut().on_pitch_detection_configured(
+                    pitch_detection.algorithm,
+                    pitch_detection.power_threshold,
+                    pitch_detection.clarity_threshold,
+                );
+            }
+            if let Some(mode) = settings.interval_mode {
+                presenter.borrow_mut().on_interval_mode_configured(mode);
+            }
+            if let Some(device_id) = settings.input_device_id {
+                presenter.borrow_mut().on_input_device_selected(device_id);
+            }
+            presenter.borrow_mut().on_deep_link(web::deep_link::startup_link());
+            web::tab_coordination::setup_tab_coordination(presenter.clone());
+            #[cfg(feature = "duet")]
+            web::duet::setup_duet(presenter.clone());
             presenter
         },
         Err(err) => {
@@ -109,19 +177,22 @@ pub async fn start() {
     start_render_loop(engine, model, presenter).await;
 }
 
+#[cfg(target_arch = "wasm32")]
 pub async fn start_render_loop(
-    mut engine: engine::AudioEngine,
-    mut model: model::DataModel,
+    engine: engine::AudioEngine,
+    model: model::DataModel,
     presenter: std::rc::Rc<std::cell::RefCell<presentation::Presenter>>,
 ) {
     #[cfg(debug_assertions)]
     use crate::common::fps_counter::FpsCounter;
-    use crate::common::error_handling::{handle_runtime_errors, ErrorSeverity};
     #[cfg(debug_assertions)]
 use crate::debug::debug_panel::DebugPanel;
 
     let dpr = web_sys::window().unwrap().device_pixel_ratio();
-    let render_size: u32 = if dpr <= 1.0 { app_config::VIEWPORT_RENDER_SIZE } else { app_config::VIEWPORT_RENDER_SIZE_RETINA };
+    let mut render_size: u32 = if dpr <= 1.0 { app_config::VIEWPORT_RENDER_SIZE } else { app_config::VIEWPORT_RENDER_SIZE_RETINA };
+    if web::capabilities::report().has_limitation(Limitation::ReducedRendering) {
+        render_size = render_size.min(app_config::VIEWPORT_RENDER_SIZE_REDUCED);
+    }
 
     let window = three_d::Window::new(three_d::WindowSettings {
         title: app_config::WINDOW_TITLE.to_string(),
@@ -132,17 +203,24 @@ use crate::debug::debug_panel::DebugPanel;
     
     let context = window.gl();
 
-    #[cfg(debug_assertions)]
     let mut gui = three_d::GUI::new(&context);
     
-    #[cfg(debug_assertions)]
+    // Release builds ship the console hidden and without the commands that
+    // inject test signals
     let mut dev_console = {
         use egui_dev_console::ConsoleCommandRegistry;
 
         let mut command_registry = ConsoleCommandRegistry::default();
         crate::engine::platform::commands::register_platform_commands(&mut command_registry);
+        crate::debug::inspector::register_inspector_commands(&mut command_registry);
+        crate::debug::audio_commands::register_audio_commands(&mut command_registry);
+        #[cfg(debug_assertions)]
+        crate::debug::test_signal_commands::register_test_signal_commands(&mut command_registry);
         egui_dev_console::DevConsole::new(command_registry)
     };
+
+    let watched_model_data = crate::debug::model_watches::WatchedModelData::default();
+    crate::debug::model_watches::register_model_watches(&mut dev_console, &watched_model_data);
     
     #[cfg(debug_assertions)]
     let mut debug_panel = DebugPanel::new(presenter.clone());
@@ -152,21 +230,59 @@ use crate::debug::debug_panel::DebugPanel;
     
     web::utils::resize_canvas();
 
+    let mut last_snapshot_ms = js_sys::Date::now();
+
+    let engine = std::rc::Rc::new(std::cell::RefCell::new(engine));
+    let model = std::rc::Rc::new(std::cell::RefCell::new(model));
+
+    {
+        // Animation frames stop in a hidden tab, so while the mini view shows
+        // the pitch over other tabs, it updates the app without rendering
+
+        let (engine, model, presenter) = (engine.clone(), model.clone(), presenter.clone());
+        web::picture_in_picture::set_background_update(move || {
+            let (Ok(mut engine), Ok(mut model)) = (engine.try_borrow_mut(), model.try_borrow_mut()) else { return };
+            let engine_data = engine.update();
+            web::tab_coordination::on_input_state(engine_data.input_listening);
+            if engine_data.audio_errors.iter().any(|error| error.is_fatal()) {
+                return;
+            }
+            if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
+                let model_actions = model.process_user_actions(presenter_ref.get_user_actions());
+                engine.execute_actions(model_actions);
+            }
+            let model_data = model.update(engine_data);
+            if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
+                presenter_ref.process_errors(&model_data.errors);
+                presenter_ref.update_mini_view(&model_data);
+            }
+        });
+    }
+
     window.render_loop(move |mut frame_input| {
         profile!("render_loop_frame", {
+            let mut engine = engine.borrow_mut();
+            let mut model = model.borrow_mut();
             web::three_d::compensate_positions_for_canvas_scaling(&mut frame_input.events, render_size);
 
             #[cfg(debug_assertions)]
             let fps = fps_counter.update(frame_input.accumulated_time);
             let engine_data = profile!("engine_update", engine.update());
+            web::tab_coordination::on_input_state(engine_data.input_listening);
 
-            if handle_runtime_errors(&engine_data.audio_errors) == ErrorSeverity::Fatal {
+            // A fatal error stops the app once the presenter has shown it
+            if engine_data.audio_errors.iter().any(|error| error.is_fatal()) {
+                let model_data = model.update(engine_data);
+                if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
+                    presenter_ref.process_errors(&model_data.errors);
+                }
                 return three_d::FrameOutput::default();
             }
 
             {
             let mut process_user_actions = || {
                 let user_actions = if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
+                    crate::debug::console_actions::apply_presenter_actions(&mut presenter_ref);
                     presenter_ref.get_user_actions()
                 } else {
                     debug_assert!(false, "Failed to borrow presenter for user actions");
@@ -182,6 +298,34 @@ use crate::debug::debug_panel::DebugPanel;
 
             let model_data = profile!("model_update", model.update(engine_data.clone()));
 
+            let now_ms = js_sys::Date::now();
+            web::telemetry::on_frame(now_ms);
+            #[cfg(feature = "duet")]
+            web::duet::on_frame(&model_data);
+            if now_ms - last_snapshot_ms >= app_config::SESSION_SNAPSHOT_INTERVAL_MS {
+                web::bug_report::set_snapshot(&model.snapshot());
+                web::storage::save_session_statistics(model.session_statistics());
+                last_snapshot_ms = now_ms;
+            }
+
+            #[cfg(debug_assertions)]
+            if debug_panel.take_snapshot_request() {
+                match serde_json::to_string(&model.snapshot()) {
+                    Ok(json) => web::utils::copy_to_clipboard(json),
+                    Err(_e) => {
+                        dev_log!("Failed to serialize snapshot: {:?}", _e);
+                    }
+                }
+            }
+
+            watched_model_data.replace(Some(model_data.clone()));
+
+            crate::debug::inspector::serve_requests(
+                &*engine,
+                &*model,
+                presenter.try_borrow().ok().as_deref().map(|p| p as &dyn crate::debug::inspector::Inspect),
+            );
+
             #[cfg(debug_assertions)]
         debug_panel.update_all_data(
             &engine_data,
@@ -205,7 +349,6 @@ use crate::debug::debug_panel::DebugPanel;
                 }
             }
 
-            #[cfg(debug_assertions)]
             gui.update(
             &mut frame_input.events,
             frame_input.accumulated_time,
@@ -214,18 +357,23 @@ use crate::debug::debug_panel::DebugPanel;
                 |gui_context| {
                     {
                         dev_console.render(gui_context);
+                        #[cfg(debug_assertions)]
                         debug_panel.render(gui_context, &model_data);
                     }
                 }
             );
 
+            // After the debug GUI, which marks the events it takes
+            if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
+                presenter_ref.process_input_events(&frame_input.events, &model_data);
+            }
+
             let mut screen = frame_input.screen();
 
             if let Ok(mut presenter_ref) = presenter.try_borrow_mut() {
                 presenter_ref.render(&context, &mut screen, &model_data);
             }
         
-            #[cfg(debug_assertions)]
             let _ = gui.render();
 
             three_d::FrameOutput::default()
@@ -0,0 +1,68 @@
+use crate::app_config::{
+    CALIBRATION_NOISE_DURATION_MS, CALIBRATION_PEAK_HEADROOM, CALIBRATION_TARGET_PEAK,
+    CALIBRATION_VOICE_DURATION_MS, MAX_INPUT_GAIN, MIN_INPUT_GAIN, VOLUME_PEAK_THRESHOLD,
+};
+use crate::common::shared_types::{CalibrationStep, InputCalibration, Volume};
+
+/// The loudest sung peak must exceed the noise RMS by this factor
+const MIN_SIGNAL_TO_NOISE: f32 = 10.0;
+
+pub enum CalibrationOutcome {
+    InProgress(CalibrationStep),
+    Done(InputCalibration),
+    Failed,
+}
+
+/// Guided input level calibration
+///
+/// First measures the room noise while the user is quiet, then the loudest
+/// peak while they sing. Levels are expected at unity input gain.
+pub struct CalibrationWizard {
+    started_ms: f64,
+    noise_rms_sum: f32,
+    noise_frames: u32,
+    voice_peak: f32,
+}
+
+impl CalibrationWizard {
+    pub fn start(now_ms: f64) -> Self {
+        Self {
+            started_ms: now_ms,
+            noise_rms_sum: 0.0,
+            noise_frames: 0,
+            voice_peak: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, volume: &Volume, now_ms: f64) -> CalibrationOutcome {
+        let elapsed_ms = now_ms - self.started_ms;
+
+        if elapsed_ms < CALIBRATION_NOISE_DURATION_MS {
+            self.noise_rms_sum += volume.rms_amplitude;
+            self.noise_frames += 1;
+            return CalibrationOutcome::InProgress(CalibrationStep::MeasuringNoise {
+                progress: (elapsed_ms / CALIBRATION_NOISE_DURATION_MS) as f32,
+            });
+        }
+
+        let voice_elapsed_ms = elapsed_ms - CALIBRATION_NOISE_DURATION_MS;
+        if voice_elapsed_ms < CALIBRATION_VOICE_DURATION_MS {
+            self.voice_peak = self.voice_peak.max(volume.peak_amplitude);
+            return CalibrationOutcome::InProgress(CalibrationStep::MeasuringVoice {
+                progress: (voice_elapsed_ms / CALIBRATION_VOICE_DURATION_MS) as f32,
+            });
+        }
+
+        let noise_rms = self.noise_rms_sum / self.noise_frames.max(1) as f32;
+        if self.voice_peak <= 0.0 || self.voice_peak < noise_rms * MIN_SIGNAL_TO_NOISE {
+            return CalibrationOutcome::Failed;
+        }
+
+        let gain = (CALIBRATION_TARGET_PEAK / self.voice_peak).clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN);
+        CalibrationOutcome::Done(InputCalibration {
+            gain,
+            noise_floor_rms: noise_rms * gain,
+            peak_threshold: (self.voice_peak * gain * CALIBRATION_PEAK_HEADROOM).min(VOLUME_PEAK_THRESHOLD),
+        })
+    }
+}
@@ -1,11 +1,22 @@
-#![cfg(target_arch = "wasm32")]
-
 //! Model layer - processes audio data and validates user actions
 
-use crate::common::shared_types::{EngineUpdateResult, ModelUpdateResult, Volume, Pitch, TuningSystem, Scale, MidiNote};
-use crate::presentation::PresentationLayerActions;
+mod calibration;
+
+use crate::common::shared_types::{EngineUpdateResult, Error, ModelUpdateResult, Volume, Pitch, PitchAlgorithm, TuningSystem, JustIntonationRatios, Scale, MidiNote, InputDevices, RecordingStatus, MidiOutputs, MidiOutputSource, InputGainStatus, InputCalibration, CalibrationStep, BatchSizeMode, BatchingStatus, AudioLifecycleState, PeakMeasure, IntervalMode, Transposition, NoteNaming, ToleranceProfile, SmoothingStrategy, ModelSnapshot, ReferenceNote, SessionExport, SessionExportFormat, SungNoteEvent, InputConditioning, ReferenceToneSettings, MetronomeSettings, RecoveryEvent};
+use crate::common::presentation_actions::{PresentationLayerActions, CalibrationControl, ExerciseControl, RecordingControl, ReferenceToneTarget, ReferenceTrackControl};
+use calibration::{CalibrationOutcome, CalibrationWizard};
+use crate::common::session_statistics::SessionTracker;
 use crate::common::smoothing::EmaSmoother;
 use crate::common::adaptive_ema::AdaptiveEMA;
+use crate::common::attempt_ghost::AttemptGhost;
+use crate::common::exercise::Exercise;
+use crate::common::key_estimation::KeyEstimator;
+use crate::common::metronome::{Metronome, MetronomeTick};
+use crate::common::note_segmentation::NoteSegmenter;
+use crate::common::pitch_filters::{MedianFilter, OneEuroFilter};
+use crate::common::pitch_history::{PitchHistory, PitchSample};
+use crate::common::reference_track::{ReferencePlayback, validate_reference_notes};
+use crate::common::saved_session::SavedSession;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConfigureTonalCenterAction {
@@ -13,34 +24,224 @@ pub struct ConfigureTonalCenterAction {
     pub volume: f32,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurePitchDetectionAction {
+    pub algorithm: PitchAlgorithm,
+    pub power_threshold: f32,
+    pub clarity_threshold: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureAnalysisWindowAction {
+    pub window_size: usize,
+    pub hop_size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureReferenceToneAction {
+    pub settings: ReferenceToneSettings,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayReferenceToneAction {
+    pub frequency: f32,
+    pub duration_ms: f32,
+}
+
+/// Upcoming clicks of the metronome, higher on the beat than on its
+/// subdivisions, at their times on the clock of the engine timestamps
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleMetronomeClicksAction {
+    /// Drop the clicks scheduled before, which are off the beat once the
+    /// metronome starts over or stops
+    pub cancel_scheduled: bool,
+    pub clicks: Vec<MetronomeTick>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureBatchSizeAction {
+    pub mode: BatchSizeMode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureAutomaticGainAction {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationAction {
+    /// Switch to unity gain for measuring
+    Begin,
+    /// Store and apply the new calibration, or restore the previous gain
+    Finish(Option<InputCalibration>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectMidiOutputAction {
+    pub output_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiOutputAction {
+    /// Hold `note` until the next action; `None` releases the held note
+    Hold(Option<MidiNote>),
+    Play { note: MidiNote, duration_ms: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureSpectrumAction {
+    pub bin_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordingAction {
+    Start { max_seconds: f32 },
+    Stop,
+    ExportWav { from_seconds_ago: f32, to_seconds_ago: f32 },
+    /// Analyze the pitch of the whole recording, to sing along with it
+    AnalyzePitch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSessionAction {
+    pub session: SavedSession,
+    /// Length of the input recording to keep with the session; 0.0 without one
+    pub recording_seconds: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureInputConditioningAction {
+    pub config: InputConditioning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectInputDeviceAction {
+    pub device_id: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct ModelLayerActions {
     pub tonal_center_configuration: Option<ConfigureTonalCenterAction>,
+    pub pitch_detection_configuration: Option<ConfigurePitchDetectionAction>,
+    pub analysis_window_configuration: Option<ConfigureAnalysisWindowAction>,
+    pub input_device_selection: Option<SelectInputDeviceAction>,
+    pub input_conditioning_configuration: Option<ConfigureInputConditioningAction>,
+    pub reference_tone_configuration: Option<ConfigureReferenceToneAction>,
+    pub reference_tone_playback: Option<PlayReferenceToneAction>,
+    pub metronome_clicks: Option<ScheduleMetronomeClicksAction>,
+    pub recording: Option<RecordingAction>,
+    pub session_save: Option<SaveSessionAction>,
+    pub spectrum_configuration: Option<ConfigureSpectrumAction>,
+    pub midi_output_selection: Option<SelectMidiOutputAction>,
+    pub midi_output: Option<MidiOutputAction>,
+    pub automatic_gain_configuration: Option<ConfigureAutomaticGainAction>,
+    pub calibration: Option<CalibrationAction>,
+    pub batch_size_configuration: Option<ConfigureBatchSizeAction>,
+    /// Connect to MIDI devices from now on
+    pub enable_midi: bool,
+    pub resume_audio: bool,
 }
 
 impl ModelLayerActions {
     /// Check if there are any actions to process
     pub fn has_actions(&self) -> bool {
-        self.tonal_center_configuration.is_some()
+        self.tonal_center_configuration.is_some() ||
+        self.pitch_detection_configuration.is_some() ||
+        self.analysis_window_configuration.is_some() ||
+        self.input_device_selection.is_some() ||
+        self.input_conditioning_configuration.is_some() ||
+        self.reference_tone_configuration.is_some() ||
+        self.reference_tone_playback.is_some() ||
+        self.metronome_clicks.is_some() ||
+        self.recording.is_some() ||
+        self.session_save.is_some() ||
+        self.spectrum_configuration.is_some() ||
+        self.midi_output_selection.is_some() ||
+        self.midi_output.is_some() ||
+        self.automatic_gain_configuration.is_some() ||
+        self.calibration.is_some() ||
+        self.batch_size_configuration.is_some() ||
+        self.enable_midi ||
+        self.resume_audio
     }
 }
 
 pub struct DataModel {
+    /// Time of the last engine update in milliseconds, the model's clock
+    now_ms: f64,
     tuning_system: TuningSystem,
     tonal_center_note: MidiNote,
+    concert_pitch_hz: f32,
+    /// Last tonal center configuration sent to the engine; its volume is reused
+    /// when the drone is retuned
+    tonal_center_configuration: Option<ConfigureTonalCenterAction>,
+    /// Set when the engine rebuilt its audio graph, so the drone configuration
+    /// is sent again even though it did not change
+    resend_tonal_center: bool,
     current_scale: Scale,
+    smoothing: SmoothingStrategy,
     frequency_smoother: Box<dyn PitchSmoother>,
+    /// Smooths the clarity of detected pitches, in percent
+    clarity_smoother: Box<dyn PitchSmoother>,
     last_detected_pitch: Option<f32>,
+    pitch_history: PitchHistory,
+    transposition: Transposition,
+    note_naming: NoteNaming,
+    tolerance_profile: ToleranceProfile,
+    session_tracker: SessionTracker,
+    note_segmenter: NoteSegmenter,
+    key_estimator: KeyEstimator,
+    /// Running or last finished exercise; a running one sets the target note
+    exercise: Option<Exercise>,
+    /// Running metronome; it starts over on the first and every new target
+    /// of an exercise
+    metronome: Option<Metronome>,
+    /// Pitch of the running exercise attempt and of the last finished one
+    attempt_ghost: AttemptGhost,
+    /// Running or last finished reference melody; a running one sets the target note
+    reference_track: Option<ReferencePlayback>,
+    /// Analysis latency relative to the default window, used to scale smoothing
+    latency_scale: f32,
+    /// Latest input device list from the engine, used to validate selections
+    input_devices: InputDevices,
+    /// Device last selected by the user, whose switch is not reported as a change
+    requested_input_device: Option<String>,
+    recording: RecordingStatus,
+    target_note: Option<MidiNote>,
+    midi_outputs: MidiOutputs,
+    midi_output_source: MidiOutputSource,
+    /// Closest note to the last detected pitch and the note currently held on the MIDI output
+    detected_note: Option<MidiNote>,
+    held_midi_note: Option<MidiNote>,
+    input_gain: InputGainStatus,
+    batching: BatchingStatus,
+    lifecycle: AudioLifecycleState,
+    peak_measure: PeakMeasure,
+    interval_mode: IntervalMode,
+    calibration_wizard: Option<CalibrationWizard>,
+    calibration_step: CalibrationStep,
+    /// Wizard result waiting to be sent to the engine
+    finished_calibration: Option<Option<InputCalibration>>,
+    /// Requested export waiting to be handed to the presentation layer
+    pending_session_export: Option<SessionExport>,
+}
+
+/// Upper bound for smoothing factors scaled by `latency_scale`
+const MAX_COMPENSATED_ALPHA: f32 = 0.95;
+
+/// Delay between a sound and its pitch reading: half a window plus up to one hop of waiting
+fn analysis_latency_samples(window_size: usize, hop_size: usize) -> usize {
+    window_size / 2 + hop_size
 }
 
-/// Trait for pitch smoothing algorithms
+/// Trait for the smoothing algorithms of the pitch and its clarity
 trait PitchSmoother: Send {
-    fn apply(&mut self, value: f32) -> f32;
+    fn apply(&mut self, value: f32, now_ms: f64) -> f32;
     fn reset(&mut self);
 }
 
 impl PitchSmoother for EmaSmoother {
-    fn apply(&mut self, value: f32) -> f32 {
+    fn apply(&mut self, value: f32, _now_ms: f64) -> f32 {
         self.apply(value)
     }
 
@@ -50,7 +251,7 @@ impl PitchSmoother for EmaSmoother {
 }
 
 impl PitchSmoother for AdaptiveEMA {
-    fn apply(&mut self, value: f32) -> f32 {
+    fn apply(&mut self, value: f32, _now_ms: f64) -> f32 {
         self.apply(value)
     }
 
@@ -59,40 +260,107 @@ impl PitchSmoother for AdaptiveEMA {
     }
 }
 
-/// Create a smoother based on configuration
-fn create_smoother() -> Box<dyn PitchSmoother> {
-    if crate::app_config::USE_ADAPTIVE_EMA {
-        let mut ema = AdaptiveEMA::new(
-            crate::app_config::ADAPTIVE_EMA_ALPHA_MIN,
-            crate::app_config::ADAPTIVE_EMA_ALPHA_MAX,
-            crate::app_config::ADAPTIVE_EMA_D,
-            crate::app_config::ADAPTIVE_EMA_S,
-        );
+impl PitchSmoother for MedianFilter {
+    fn apply(&mut self, value: f32, _now_ms: f64) -> f32 {
+        self.apply(value)
+    }
 
-        if crate::app_config::ADAPTIVE_EMA_USE_MEDIAN3 {
-            ema = ema.with_median3(true);
-        }
+    fn reset(&mut self) {
+        self.reset()
+    }
+}
+
+/// Filters in cents, so the response to a change does not depend on the register
+impl PitchSmoother for OneEuroFilter {
+    fn apply(&mut self, value: f32, now_ms: f64) -> f32 {
+        let cents = self.apply(1200.0 * value.log2(), now_ms / 1000.0);
+        2.0_f32.powf(cents / 1200.0)
+    }
+
+    fn reset(&mut self) {
+        self.reset()
+    }
+}
+
+/// One-euro filter on the value as is, for the clarity in percent
+struct LinearOneEuroFilter(OneEuroFilter);
 
-        if crate::app_config::ADAPTIVE_EMA_USE_HAMPEL {
-            ema = ema.with_hampel(
-                true,
-                crate::app_config::ADAPTIVE_EMA_HAMPEL_WINDOW,
-                crate::app_config::ADAPTIVE_EMA_HAMPEL_NSIGMA,
+impl PitchSmoother for LinearOneEuroFilter {
+    fn apply(&mut self, value: f32, now_ms: f64) -> f32 {
+        self.0.apply(value, now_ms / 1000.0)
+    }
+
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+}
+
+struct Unsmoothed;
+
+impl PitchSmoother for Unsmoothed {
+    fn apply(&mut self, value: f32, _now_ms: f64) -> f32 {
+        value
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// Create a smoother for the strategy
+///
+/// Smoothing factors are multiplied and median windows divided by
+/// `latency_scale`, so a slower analysis gets less smoothing and the overall
+/// response time stays about the same. The one-euro filter works in real time
+/// and needs no compensation.
+fn create_smoother(strategy: SmoothingStrategy, latency_scale: f32) -> Box<dyn PitchSmoother> {
+    let compensate = |alpha: f32| (alpha * latency_scale).min(MAX_COMPENSATED_ALPHA);
+
+    match strategy {
+        SmoothingStrategy::Ema { alpha } => Box::new(EmaSmoother::new(compensate(alpha))),
+        SmoothingStrategy::AdaptiveEma => {
+            let mut ema = AdaptiveEMA::new(
+                compensate(crate::app_config::ADAPTIVE_EMA_ALPHA_MIN),
+                compensate(crate::app_config::ADAPTIVE_EMA_ALPHA_MAX),
+                crate::app_config::ADAPTIVE_EMA_D,
+                crate::app_config::ADAPTIVE_EMA_S,
             );
-        }
 
-        if crate::app_config::ADAPTIVE_EMA_DEADBAND > 0.0 {
-            ema = ema.with_deadband(crate::app_config::ADAPTIVE_EMA_DEADBAND);
-        }
+            if crate::app_config::ADAPTIVE_EMA_USE_MEDIAN3 {
+                ema = ema.with_median3(true);
+            }
+
+            if crate::app_config::ADAPTIVE_EMA_USE_HAMPEL {
+                ema = ema.with_hampel(
+                    true,
+                    crate::app_config::ADAPTIVE_EMA_HAMPEL_WINDOW,
+                    crate::app_config::ADAPTIVE_EMA_HAMPEL_NSIGMA,
+                );
+            }
+
+            if crate::app_config::ADAPTIVE_EMA_DEADBAND > 0.0 {
+                ema = ema.with_deadband(crate::app_config::ADAPTIVE_EMA_DEADBAND);
+            }
+
+            ema = ema.with_hysteresis(
+                crate::app_config::ADAPTIVE_EMA_HYSTERESIS_DOWN,
+                crate::app_config::ADAPTIVE_EMA_HYSTERESIS_UP,
+            );
 
-        ema = ema.with_hysteresis(
-            crate::app_config::ADAPTIVE_EMA_HYSTERESIS_DOWN,
-            crate::app_config::ADAPTIVE_EMA_HYSTERESIS_UP,
-        );
+            Box::new(ema)
+        }
+        SmoothingStrategy::Median { window } => {
+            Box::new(MedianFilter::new(((window as f32 / latency_scale).round() as usize).max(1)))
+        }
+        SmoothingStrategy::OneEuro { min_cutoff_hz, beta } => Box::new(OneEuroFilter::new(min_cutoff_hz, beta)),
+        SmoothingStrategy::None => Box::new(Unsmoothed),
+    }
+}
 
-        Box::new(ema)
-    } else {
-        Box::new(EmaSmoother::new(crate::app_config::PITCH_SMOOTHING_FACTOR))
+/// Create a smoother for the clarity in percent; the one-euro filter's `beta`
+/// then reads per percent per second instead of per cent per second
+fn create_clarity_smoother(strategy: SmoothingStrategy, latency_scale: f32) -> Box<dyn PitchSmoother> {
+    match strategy {
+        SmoothingStrategy::OneEuro { min_cutoff_hz, beta } => Box::new(LinearOneEuroFilter(OneEuroFilter::new(min_cutoff_hz, beta))),
+        strategy => create_smoother(strategy, latency_scale),
     }
 }
 
@@ -101,34 +369,217 @@ impl Default for DataModel {
         Self {
             tuning_system: TuningSystem::EqualTemperament,
             tonal_center_note: crate::app_config::DEFAULT_TONAL_CENTER_NOTE,
+            concert_pitch_hz: crate::app_config::DEFAULT_CONCERT_PITCH_HZ,
+            tonal_center_configuration: None,
+            resend_tonal_center: false,
             current_scale: crate::app_config::DEFAULT_SCALE,
-            frequency_smoother: create_smoother(),
+            smoothing: SmoothingStrategy::default(),
+            frequency_smoother: create_smoother(SmoothingStrategy::default(), 1.0),
+            clarity_smoother: create_clarity_smoother(SmoothingStrategy::default(), 1.0),
             last_detected_pitch: None,
+            pitch_history: PitchHistory::new(crate::app_config::PITCH_HISTORY_RETENTION_MS),
+            transposition: Transposition::default(),
+            note_naming: NoteNaming::default(),
+            tolerance_profile: ToleranceProfile::default(),
+            session_tracker: SessionTracker::new(ToleranceProfile::default().cents()),
+            note_segmenter: NoteSegmenter::new(),
+            key_estimator: KeyEstimator::new(crate::app_config::ROOT_SUGGESTION_WINDOW_MS),
+            exercise: None,
+            metronome: None,
+            attempt_ghost: AttemptGhost::default(),
+            reference_track: None,
+            latency_scale: 1.0,
+            input_devices: InputDevices::default(),
+            requested_input_device: None,
+            recording: RecordingStatus::default(),
+            target_note: None,
+            midi_outputs: MidiOutputs::default(),
+            now_ms: 0.0,
+            midi_output_source: MidiOutputSource::default(),
+            detected_note: None,
+            held_midi_note: None,
+            input_gain: InputGainStatus { gain: 1.0, automatic: false, calibration: None },
+            batching: BatchingStatus { mode: BatchSizeMode::default(), batch_size: crate::app_config::BUFFER_SIZE },
+            lifecycle: AudioLifecycleState::default(),
+            peak_measure: PeakMeasure::default(),
+            interval_mode: IntervalMode::default(),
+            calibration_wizard: None,
+            calibration_step: CalibrationStep::Idle,
+            finished_calibration: None,
+            pending_session_export: None,
         }
     }
 }
 
 impl DataModel {
-    pub fn new(tonal_center_note: MidiNote, tuning_system: TuningSystem, scale: Scale) -> Self {
+    pub fn new(tonal_center_note: MidiNote, concert_pitch_hz: f32, tuning_system: TuningSystem, scale: Scale, transposition: Transposition, note_naming: NoteNaming, tolerance_profile: ToleranceProfile) -> Self {
         Self {
             tuning_system,
             tonal_center_note,
+            concert_pitch_hz,
+            tonal_center_configuration: None,
+            resend_tonal_center: false,
             current_scale: scale,
-            frequency_smoother: create_smoother(),
+            smoothing: SmoothingStrategy::default(),
+            frequency_smoother: create_smoother(SmoothingStrategy::default(), 1.0),
+            clarity_smoother: create_clarity_smoother(SmoothingStrategy::default(), 1.0),
             last_detected_pitch: None,
+            pitch_history: PitchHistory::new(crate::app_config::PITCH_HISTORY_RETENTION_MS),
+            transposition,
+            note_naming,
+            tolerance_profile,
+            session_tracker: SessionTracker::new(tolerance_profile.cents()),
+            note_segmenter: NoteSegmenter::new(),
+            key_estimator: KeyEstimator::new(crate::app_config::ROOT_SUGGESTION_WINDOW_MS),
+            exercise: None,
+            metronome: None,
+            attempt_ghost: AttemptGhost::default(),
+            reference_track: None,
+            latency_scale: 1.0,
+            input_devices: InputDevices::default(),
+            requested_input_device: None,
+            recording: RecordingStatus::default(),
+            target_note: None,
+            midi_outputs: MidiOutputs::default(),
+            now_ms: 0.0,
+            midi_output_source: MidiOutputSource::default(),
+            detected_note: None,
+            held_midi_note: None,
+            input_gain: InputGainStatus { gain: 1.0, automatic: false, calibration: None },
+            batching: BatchingStatus { mode: BatchSizeMode::default(), batch_size: crate::app_config::BUFFER_SIZE },
+            lifecycle: AudioLifecycleState::default(),
+            peak_measure: PeakMeasure::default(),
+            interval_mode: IntervalMode::default(),
+            calibration_wizard: None,
+            calibration_step: CalibrationStep::Idle,
+            finished_calibration: None,
+            pending_session_export: None,
+        }
+    }
+
+    /// Settings and session statistics, for saving the session or attaching it to a bug report
+    pub fn snapshot(&self) -> ModelSnapshot {
+        ModelSnapshot {
+            tonal_center_note: self.tonal_center_note,
+            concert_pitch_hz: self.concert_pitch_hz,
+            tuning_system: self.tuning_system.clone(),
+            scale: self.current_scale,
+            transposition: self.transposition,
+            note_naming: self.note_naming,
+            tolerance_profile: self.tolerance_profile,
+            smoothing: self.smoothing,
+            interval_mode: self.interval_mode,
+            session_statistics: self.session_tracker.statistics(),
+        }
+    }
+
+    /// Continue from a snapshot; nothing changes if any part of it is invalid
+    ///
+    /// Does not retune a playing drone, see `RestoreSnapshot` for that.
+    pub fn restore(&mut self, snapshot: ModelSnapshot) -> Result<(), String> {
+        validate_snapshot(&snapshot)?;
+
+        self.tonal_center_note = snapshot.tonal_center_note;
+        self.concert_pitch_hz = snapshot.concert_pitch_hz;
+        self.tuning_system = snapshot.tuning_system;
+        self.current_scale = snapshot.scale;
+        self.transposition = snapshot.transposition;
+        self.note_naming = snapshot.note_naming;
+        self.tolerance_profile = snapshot.tolerance_profile;
+        self.smoothing = snapshot.smoothing;
+        self.rebuild_smoothers();
+        self.interval_mode = snapshot.interval_mode;
+        self.session_tracker = SessionTracker::from_statistics(&snapshot.session_statistics);
+        self.session_tracker.set_tolerance_cents(self.tolerance_profile.cents());
+        self.reset_smoothers();
+        Ok(())
+    }
+
+    pub fn session_statistics(&self) -> crate::common::shared_types::SessionStatistics {
+        self.session_tracker.statistics()
+    }
+
+    /// Continue the statistics of an earlier session; the settings stay as
+    /// they are
+    pub fn restore_session_statistics(&mut self, statistics: &crate::common::shared_types::SessionStatistics) {
+        self.session_tracker = SessionTracker::from_statistics(statistics);
+        self.session_tracker.set_tolerance_cents(self.tolerance_profile.cents());
+    }
+
+    /// Session data in an export format
+    pub fn export_session(&self, format: SessionExportFormat) -> Result<String, String> {
+        let samples: Vec<PitchSample> = self.pitch_history.history(f64::INFINITY).copied().collect();
+        match format {
+            SessionExportFormat::PitchTimelineCsv => Ok(crate::common::session_export::pitch_timeline_csv(&samples)),
+            SessionExportFormat::DegreeStatisticsCsv => {
+                let namer = crate::common::note_naming::NoteNamer {
+                    naming: self.note_naming,
+                    transposition: self.transposition,
+                    tonal_center_note: self.tonal_center_note,
+                    scale: self.current_scale,
+                    language: crate::common::localization::current_language(),
+                };
+                Ok(crate::common::session_export::degree_statistics_csv(&self.session_tracker.statistics(), &namer))
+            }
+            SessionExportFormat::Json => crate::common::session_export::session_json(&self.snapshot(), &samples),
         }
     }
 
+    /// The session as kept in the browser
+    pub fn saved_session(&self) -> SavedSession {
+        SavedSession {
+            settings: self.snapshot(),
+            exercise: self.exercise.as_ref().map(|exercise| exercise.status(self.tonal_center_note)),
+            pitch_history: self.pitch_history.history(f64::INFINITY).copied().collect(),
+        }
+    }
+
+    /// Errors of the engine, and input moving to another device than the user
+    /// selected, compared with the previous update
+    fn collect_errors(&self, engine_data: &EngineUpdateResult) -> Vec<Error> {
+        let mut errors = engine_data.audio_errors.clone();
+        let active_device_id = engine_data.input_devices.active_device_id.as_ref();
+        if let (Some(previous), Some(active)) = (self.input_devices.active_device_id.as_ref(), active_device_id)
+            && previous != active
+            && self.requested_input_device.as_ref() != Some(active)
+        {
+            let label = engine_data.input_devices.available.iter()
+                .find(|device| &device.device_id == active && !device.label.is_empty())
+                .map_or(active.clone(), |device| device.label.clone());
+            errors.push(Error::InputDeviceChanged(label));
+        }
+        errors
+    }
+
+    /// Detected pitches of the last `PITCH_HISTORY_RETENTION_MS`
+    pub fn pitch_history(&self) -> &PitchHistory {
+        &self.pitch_history
+    }
+
     pub fn update(&mut self, engine_data: EngineUpdateResult) -> ModelUpdateResult {
-        let (volume, pitch) = if let Some(audio_analysis) = engine_data.audio_analysis {
-            let volume = Volume {
-                peak_amplitude: audio_analysis.volume_level.peak_amplitude,
-                rms_amplitude: audio_analysis.volume_level.rms_amplitude,
-            };
+        let errors = self.collect_errors(&engine_data);
+        // A selected device is expected only until the input next changes
+        if engine_data.input_devices.active_device_id != self.input_devices.active_device_id {
+            self.requested_input_device = None;
+        }
+        self.input_devices = engine_data.input_devices;
+        self.recording = engine_data.recording;
+        self.midi_outputs = engine_data.midi_outputs;
+        self.input_gain = engine_data.input_gain;
+        self.batching = engine_data.batching;
+        self.lifecycle = engine_data.lifecycle;
+        if engine_data.recovery_events.iter().any(|event| matches!(event, RecoveryEvent::Restarted { .. })) {
+            self.resend_tonal_center = true;
+        }
+
+        self.now_ms = engine_data.timestamp_ms;
+        let now_ms = self.now_ms;
+        let (volume, pitch, raw_frequency, clarity, spectrum, harmonics) = if let Some(audio_analysis) = engine_data.audio_analysis {
+            let volume = audio_analysis.volume_level;
 
             let pitch = match audio_analysis.pitch {
                 crate::common::shared_types::Pitch::Detected(frequency) => {
-                    let smoothed_frequency = self.frequency_smoother.apply(frequency);
+                    let smoothed_frequency = self.frequency_smoother.apply(frequency, now_ms);
                     self.last_detected_pitch = Some(frequency);
                     Pitch::Detected(smoothed_frequency)
                 }
@@ -138,18 +589,60 @@ impl DataModel {
                 }
             };
             
-            (volume, pitch)
+            let raw_frequency = match audio_analysis.pitch {
+                Pitch::Detected(frequency) => Some(frequency),
+                Pitch::NotDetected => None,
+            };
+            // Only the clarity of detected pitches is smoothed, as the smoothers reset in between
+            let clarity = match (audio_analysis.clarity, raw_frequency) {
+                (Some(clarity), Some(_)) => Some(self.clarity_smoother.apply(clarity * 100.0, now_ms) / 100.0),
+                (clarity, _) => clarity,
+            };
+            (volume, pitch, raw_frequency, clarity, audio_analysis.fft_data, audio_analysis.harmonics)
         } else {
-            (Volume { peak_amplitude: 0.0, rms_amplitude: 0.0 }, Pitch::NotDetected)
+            (Volume::SILENCE, Pitch::NotDetected, None, None, None, None)
         };
         
-        let is_peaking = volume.peak_amplitude >= crate::app_config::VOLUME_PEAK_THRESHOLD;
+        if let Some(wizard) = &mut self.calibration_wizard {
+            match wizard.update(&volume, now_ms) {
+                CalibrationOutcome::InProgress(step) => self.calibration_step = step,
+                CalibrationOutcome::Done(calibration) => {
+                    self.calibration_wizard = None;
+                    self.calibration_step = CalibrationStep::Idle;
+                    self.finished_calibration = Some(Some(calibration));
+                }
+                CalibrationOutcome::Failed => {
+                    crate::common::warn_log!("Model layer: Calibration failed, no clear voice above the noise");
+                    self.calibration_wizard = None;
+                    self.calibration_step = CalibrationStep::Failed;
+                    self.finished_calibration = Some(None);
+                }
+            }
+        }
+
+        // Calibrated thresholds were measured at the calibrated gain; follow any AGC change
+        let calibration = self.input_gain.calibration.filter(|_| self.calibration_wizard.is_none());
+        let peak_threshold = calibration.map_or(crate::app_config::VOLUME_PEAK_THRESHOLD, |calibration| calibration.peak_threshold);
+        let is_peaking = match self.peak_measure {
+            PeakMeasure::SamplePeak => volume.peak_amplitude >= peak_threshold,
+            PeakMeasure::Loudness => volume.short_term_lufs >= crate::app_config::LOUDNESS_PEAK_THRESHOLD_LUFS,
+        };
+        let below_noise_floor = calibration.is_some_and(|calibration| {
+            volume.rms_amplitude < calibration.noise_floor_rms * self.input_gain.gain / calibration.gain
+        });
+        let pitch = if below_noise_floor {
+            self.reset_smoothers();
+            Pitch::NotDetected
+        } else {
+            pitch
+        };
         
         let midi_note_result = match pitch {
             Pitch::Detected(frequency) => crate::common::music_theory::frequency_to_midi_note_and_cents(
                 frequency,
                 self.tonal_center_note,
-                self.tuning_system,
+                self.concert_pitch_hz,
+                &self.tuning_system,
                 self.current_scale,
             ),
             _ => None,
@@ -158,29 +651,196 @@ impl DataModel {
         let (closest_midi_note, cents_offset, interval_semitones) = match midi_note_result {
             Some((midi_note, cents)) => {
                 let interval = (midi_note as i32) - (self.tonal_center_note as i32);
+                let interval = match self.interval_mode {
+                    IntervalMode::Absolute => interval,
+                    IntervalMode::Wrapped => interval.rem_euclid(12),
+                };
                 (Some(midi_note), cents, interval)
             }
             None => (None, 0.0, 0),
         };
+        self.detected_note = closest_midi_note;
+
+        let interval_readout = match &pitch {
+            &Pitch::Detected(frequency) => {
+                let tonal_center_hz = crate::common::music_theory::midi_note_to_frequency(self.tonal_center_note, self.concert_pitch_hz);
+                // Against a drone in just intonation the chosen ratios are the reference
+                let just_ratios = match &self.tuning_system {
+                    TuningSystem::JustIntonation(ratios) => *ratios,
+                    _ => JustIntonationRatios::FIVE_LIMIT,
+                };
+                Some(crate::common::music_theory::interval_readout(frequency, tonal_center_hz, &just_ratios))
+            }
+            Pitch::NotDetected => None,
+        };
+
+        let sample = match (raw_frequency, closest_midi_note) {
+            (Some(frequency), Some(_)) => Some(PitchSample {
+                timestamp_ms: now_ms,
+                frequency,
+                clarity: clarity.unwrap_or(0.0),
+                cents_offset,
+            }),
+            _ => None,
+        };
+        self.pitch_history.record(now_ms, sample);
+        self.attempt_ghost.record(now_ms, sample);
+        let vibrato = sample.and_then(|_| {
+            let recent: Vec<PitchSample> = self.pitch_history.history(crate::app_config::VIBRATO_WINDOW_MS).copied().collect();
+            crate::common::vibrato::analyze_vibrato(&recent)
+        });
+        // Intentional vibrato is scored by its center rather than its extremes
+        let scored_cents = vibrato.map_or(cents_offset, |vibrato| vibrato.center_cents_offset);
+        let sung_degree = closest_midi_note
+            .map(|note| ((note as i32 - self.tonal_center_note as i32).rem_euclid(12) as usize, scored_cents));
+        self.session_tracker.update(now_ms, sung_degree);
+        let note_events = self.note_segmenter.update(now_ms, sample.map(|sample| sample.frequency), |frequency| {
+            crate::common::music_theory::frequency_to_midi_note_and_cents(
+                frequency,
+                self.tonal_center_note,
+                self.concert_pitch_hz,
+                &self.tuning_system,
+                self.current_scale,
+            )
+        });
+
+        for event in &note_events {
+            if let SungNoteEvent::Offset(note) = event {
+                self.key_estimator.add(note);
+            }
+        }
+        let suggested_tonal_center = self.key_estimator.estimate(now_ms).and_then(|key| {
+            // The closest note of the root's pitch class, which keeps the tonal center in its octave
+            let semitones = (key.root_pitch_class as i32 - self.tonal_center_note as i32).rem_euclid(12);
+            let semitones = if semitones > 6 { semitones - 12 } else { semitones };
+            Some(self.tonal_center_note as i32 + semitones)
+                .filter(|&note| semitones != 0 && crate::common::shared_types::is_valid_midi_note(note))
+                .map(|note| note as MidiNote)
+        });
+
+        if let Some(track) = &engine_data.analyzed_recording {
+            let notes = crate::common::reference_track::reference_notes_from_pitch_track(track, |frequency| {
+                crate::common::music_theory::frequency_to_midi_note_and_cents(
+                    frequency,
+                    self.tonal_center_note,
+                    self.concert_pitch_hz,
+                    &self.tuning_system,
+                    Scale::Chromatic,
+                )
+            });
+            self.start_reference_track(notes);
+        }
+        if let Some(exercise) = self.exercise.as_ref().filter(|exercise| !exercise.is_finished()) {
+            self.target_note = exercise.current_target(self.tonal_center_note);
+        }
+        if let Some(track) = self.reference_track.as_ref().filter(|track| !track.is_finished()) {
+            self.target_note = track.note_at(now_ms);
+        }
+        let target_cents_offset = match (&pitch, self.target_note) {
+            (&Pitch::Detected(frequency), Some(target_note)) => {
+                let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(self.tonal_center_note, self.concert_pitch_hz);
+                let semitones = target_note as i32 - self.tonal_center_note as i32;
+                let target_frequency = crate::common::music_theory::interval_frequency(&self.tuning_system, tonal_center_frequency, semitones);
+                Some(1200.0 * (frequency / target_frequency).log2())
+            }
+            _ => None,
+        };
+
+        let exercise = match &mut self.exercise {
+            Some(exercise) if !exercise.is_finished() => {
+                let reached_count = exercise.reached_count();
+                exercise.update(now_ms, target_cents_offset, self.tolerance_profile.cents(), self.tonal_center_note);
+                self.target_note = exercise.current_target(self.tonal_center_note);
+                if exercise.reached_count() > reached_count
+                    && let Some(metronome) = &mut self.metronome
+                {
+                    metronome.restart(now_ms);
+                }
+                if exercise.is_finished() {
+                    self.attempt_ghost.finish();
+                }
+                Some(exercise.status(self.tonal_center_note))
+            }
+            exercise => exercise.as_ref().map(|exercise| exercise.status(self.tonal_center_note)),
+        };
+
+        let reference_track = match &mut self.reference_track {
+            Some(track) if !track.is_finished() => {
+                track.update(now_ms, target_cents_offset);
+                // The melody lets go of the target once it is over
+                self.target_note = if track.is_finished() { None } else { track.note_at(now_ms) };
+                Some(track.status())
+            }
+            track => track.as_ref().map(|track| track.status()),
+        };
 
         ModelUpdateResult {
             volume,
             is_peaking,
+            peak_measure: self.peak_measure,
             pitch,
-            tuning_system: self.tuning_system,
+            clarity,
+            tuning_system: self.tuning_system.clone(),
             scale: self.current_scale,
             closest_midi_note,
             cents_offset,
             interval_semitones,
+            interval_mode: self.interval_mode,
+            transposition: self.transposition,
+            note_naming: self.note_naming,
+            interval_readout,
             tonal_center_note: self.tonal_center_note,
+            concert_pitch_hz: self.concert_pitch_hz,
+            input_devices: self.input_devices.clone(),
+            recording: self.recording,
+            spectrum,
+            harmonics,
+            midi_events: engine_data.midi_events,
+            target_note: self.target_note,
+            target_cents_offset,
+            pitch_trail: self.pitch_history.decimated(crate::app_config::PITCH_TRAIL_WINDOW_MS, crate::app_config::PITCH_TRAIL_POINTS),
+            ghost_trail: self.attempt_ghost.trail(now_ms, crate::app_config::PITCH_TRAIL_WINDOW_MS, crate::app_config::PITCH_TRAIL_POINTS),
+            vibrato,
+            note_events,
+            suggested_tonal_center,
+            midi_outputs: self.midi_outputs.clone(),
+            midi_output_source: self.midi_output_source,
+            input_gain: self.input_gain,
+            calibration_step: self.calibration_step,
+            tolerance_profile: self.tolerance_profile,
+            smoothing: self.smoothing,
+            session_statistics: self.session_tracker.statistics(),
+            exercise,
+            reference_track,
+            metronome: self.metronome.as_ref().map(|metronome| metronome.status(now_ms)),
+            session_export: self.pending_session_export.take(),
+            batching: self.batching,
+            lifecycle: self.lifecycle,
+            recovery_events: engine_data.recovery_events,
+            errors,
         }
     }
     
+    /// Each action category holds only the latest request of the frame. The
+    /// snapshot, tonal center and concert pitch all tune the drone, so they
+    /// result in at most one drone configuration, built from the final state
+    /// and left out when it matches the one already sent, unless the engine
+    /// rebuilt its audio graph since.
     pub fn process_user_actions(&mut self, presentation_actions: PresentationLayerActions) -> ModelLayerActions {
         let mut model_actions = ModelLayerActions::default();
-        
+        let mut tonal_center_volume = self.tonal_center_configuration.as_ref().map(|config| config.volume);
+
+        // The snapshot goes first so that settings changed in the same frame apply on top of it
+        if let Some(restore) = presentation_actions.snapshot_restore
+            && let Err(reason) = self.restore(restore.snapshot)
+        {
+            crate::common::warn_log!("Model layer: Ignoring snapshot: {}", reason);
+        }
+
         if let Some(tuning_change) = presentation_actions.tuning_system_change {
-            if tuning_change.tuning_system != self.tuning_system {
+            if let Err(reason) = validate_tuning_system(&tuning_change.tuning_system) {
+                crate::common::warn_log!("Model layer: Ignoring tuning system change: {}", reason);
+            } else if tuning_change.tuning_system != self.tuning_system {
                 crate::common::dev_log!(
                     "Model layer: Tuning system changed from {:?} to {:?}",
                     self.tuning_system, tuning_change.tuning_system
@@ -190,7 +850,9 @@ impl DataModel {
         }
         
         if let Some(scale_change) = presentation_actions.scale_change {
-            if scale_change.scale != self.current_scale {
+            if let Err(reason) = validate_scale(scale_change.scale) {
+                crate::common::warn_log!("Model layer: Ignoring scale change: {}", reason);
+            } else if scale_change.scale != self.current_scale {
                 crate::common::dev_log!(
                     "Model layer: Scale changed from {:?} to {:?}",
                     self.current_scale, scale_change.scale
@@ -208,12 +870,376 @@ impl DataModel {
                 self.tonal_center_note = tonal_center_config.note;
             }
             
-            model_actions.tonal_center_configuration = Some(
-                ConfigureTonalCenterAction {
-                    frequency: crate::common::music_theory::midi_note_to_standard_frequency(tonal_center_config.note),
-                    volume: tonal_center_config.volume,
+            tonal_center_volume = Some(tonal_center_config.volume);
+        }
+
+        if let Some(config) = presentation_actions.concert_pitch_configuration {
+            if !(crate::app_config::MIN_CONCERT_PITCH_HZ..=crate::app_config::MAX_CONCERT_PITCH_HZ).contains(&config.frequency_hz) {
+                crate::common::warn_log!("Model layer: Ignoring concert pitch {} Hz", config.frequency_hz);
+            } else if config.frequency_hz != self.concert_pitch_hz {
+                crate::common::dev_log!(
+                    "Model layer: Concert pitch changed from {} Hz to {} Hz",
+                    self.concert_pitch_hz, config.frequency_hz
+                );
+                self.concert_pitch_hz = config.frequency_hz;
+                self.reset_smoothers();
+            }
+        }
+
+        // The drone follows the tonal center, concert pitch and snapshot changes of this frame together
+        if let Some(volume) = tonal_center_volume {
+            let config = ConfigureTonalCenterAction {
+                frequency: crate::common::music_theory::midi_note_to_frequency(self.tonal_center_note, self.concert_pitch_hz),
+                volume,
+            };
+            if self.resend_tonal_center || self.tonal_center_configuration.as_ref() != Some(&config) {
+                self.resend_tonal_center = false;
+                self.tonal_center_configuration = Some(config.clone());
+                model_actions.tonal_center_configuration = Some(config);
+            }
+        }
+        
+        if let Some(config) = presentation_actions.pitch_detection_configuration {
+            if config.power_threshold > 0.0 && (0.0..=1.0).contains(&config.clarity_threshold) {
+                crate::common::dev_log!("Model layer: Pitch detection set to {:?}", config.algorithm);
+                // Readings from the previous detector should not bleed into the new one
+                self.reset_smoothers();
+                model_actions.pitch_detection_configuration = Some(ConfigurePitchDetectionAction {
+                    algorithm: config.algorithm,
+                    power_threshold: config.power_threshold,
+                    clarity_threshold: config.clarity_threshold,
+                });
+            } else {
+                crate::common::warn_log!(
+                    "Model layer: Ignoring pitch detection thresholds power={} clarity={}",
+                    config.power_threshold, config.clarity_threshold
+                );
+            }
+        }
+        
+        if let Some(selection) = presentation_actions.input_device_selection {
+            let is_known = self.input_devices.available.iter().any(|device| device.device_id == selection.device_id);
+            if !is_known {
+                crate::common::warn_log!("Model layer: Ignoring unknown audio input {}", selection.device_id);
+            } else if self.input_devices.active_device_id.as_deref() != Some(selection.device_id.as_str()) {
+                self.reset_smoothers();
+                self.requested_input_device = Some(selection.device_id.clone());
+                model_actions.input_device_selection = Some(SelectInputDeviceAction {
+                    device_id: selection.device_id,
+                });
+            }
+        }
+
+        if let Some(configuration) = presentation_actions.reference_tone_configuration {
+            match validate_reference_tone(&configuration.settings) {
+                Ok(()) => {
+                    model_actions.reference_tone_configuration = Some(ConfigureReferenceToneAction {
+                        settings: configuration.settings,
+                    });
                 }
-            );
+                Err(reason) => {
+                    crate::common::warn_log!("Model layer: Ignoring reference tone settings: {}", reason);
+                }
+            }
+        }
+
+        if let Some(playback) = presentation_actions.reference_tone_playback {
+            let semitones = match playback.target {
+                ReferenceToneTarget::Interval(semitones) => Some(semitones),
+                ReferenceToneTarget::ScaleDegree(degree) => crate::common::shared_types::scale_degree_to_semitones(self.current_scale, degree),
+            };
+
+            match semitones {
+                Some(semitones) if semitones.abs() <= MAX_REFERENCE_INTERVAL_SEMITONES => {
+                    let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(self.tonal_center_note, self.concert_pitch_hz);
+                    let midi_note = Some(self.tonal_center_note as i32 + semitones)
+                        .filter(|&note| crate::common::shared_types::is_valid_midi_note(note))
+                        .map(|note| note as MidiNote);
+                    if self.midi_output_source == MidiOutputSource::ReferenceTone
+                        && let Some(note) = midi_note
+                    {
+                        model_actions.midi_output = Some(MidiOutputAction::Play {
+                            note,
+                            duration_ms: crate::app_config::REFERENCE_TONE_DURATION_MS,
+                        });
+                    }
+                    model_actions.reference_tone_playback = Some(PlayReferenceToneAction {
+                        frequency: crate::common::music_theory::interval_frequency(&self.tuning_system, tonal_center_frequency, semitones),
+                        duration_ms: crate::app_config::REFERENCE_TONE_DURATION_MS,
+                    });
+                }
+                _ => {
+                    crate::common::warn_log!("Model layer: Ignoring reference tone request {:?}", playback.target);
+                }
+            }
+        }
+
+        model_actions.enable_midi = presentation_actions.enable_midi && !self.midi_outputs.enabled;
+        model_actions.resume_audio = presentation_actions.resume_audio && self.lifecycle == AudioLifecycleState::Suspended;
+
+        if let Some(config) = presentation_actions.batch_size_configuration {
+            match config.mode {
+                BatchSizeMode::Fixed(batch_size) if !is_valid_batch_size(batch_size) => {
+                    crate::common::warn_log!(
+                        "Model layer: Ignoring batch size {}, must be a multiple of {} up to {}",
+                        batch_size, crate::app_config::AUDIO_CHUNK_SIZE, crate::app_config::MAX_BATCH_SIZE
+                    );
+                }
+                mode => {
+                    model_actions.batch_size_configuration = Some(ConfigureBatchSizeAction { mode });
+                }
+            }
+        }
+
+        if let Some(config) = presentation_actions.peak_measure_configuration {
+            self.peak_measure = config.measure;
+        }
+
+        if let Some(config) = presentation_actions.interval_mode_configuration {
+            self.interval_mode = config.mode;
+        }
+
+        if let Some(config) = presentation_actions.transposition_configuration {
+            self.transposition = config.transposition;
+        }
+
+        if let Some(config) = presentation_actions.note_naming_configuration {
+            self.note_naming = config.naming;
+        }
+
+        if let Some(config) = presentation_actions.tolerance_profile_configuration {
+            if (crate::app_config::MIN_IN_TUNE_TOLERANCE_CENTS..=crate::app_config::MAX_IN_TUNE_TOLERANCE_CENTS).contains(&config.profile.cents()) {
+                self.tolerance_profile = config.profile;
+                self.session_tracker.set_tolerance_cents(config.profile.cents());
+            } else {
+                crate::common::warn_log!("Model layer: Ignoring tolerance of {} cents", config.profile.cents());
+            }
+        }
+
+        if presentation_actions.reset_session_statistics {
+            self.session_tracker.reset();
+            self.key_estimator.clear();
+        }
+
+        if let Some(request) = presentation_actions.session_export {
+            match self.export_session(request.format) {
+                Ok(contents) => self.pending_session_export = Some(SessionExport { format: request.format, contents }),
+                Err(reason) => {
+                    crate::common::warn_log!("Model layer: Session export failed: {}", reason);
+                }
+            }
+        }
+
+        if presentation_actions.session_save {
+            model_actions.session_save = Some(SaveSessionAction {
+                session: self.saved_session(),
+                recording_seconds: self.recording.recorded_seconds,
+            });
+        }
+
+        if let Some(config) = presentation_actions.automatic_gain_configuration {
+            model_actions.automatic_gain_configuration = Some(ConfigureAutomaticGainAction { enabled: config.enabled });
+        }
+
+        match presentation_actions.calibration_control {
+            Some(CalibrationControl::Start) => {
+                self.calibration_wizard = Some(CalibrationWizard::start(self.now_ms));
+                self.calibration_step = CalibrationStep::MeasuringNoise { progress: 0.0 };
+                self.finished_calibration = None;
+                model_actions.calibration = Some(CalibrationAction::Begin);
+            }
+            Some(CalibrationControl::Cancel) if self.calibration_wizard.is_some() => {
+                self.calibration_wizard = None;
+                self.calibration_step = CalibrationStep::Idle;
+                model_actions.calibration = Some(CalibrationAction::Finish(None));
+            }
+            _ => {
+                if let Some(calibration) = self.finished_calibration.take() {
+                    model_actions.calibration = Some(CalibrationAction::Finish(calibration));
+                }
+            }
+        }
+
+        if let Some(config) = presentation_actions.midi_output_configuration {
+            let is_known = config.output_id.as_ref()
+                .is_none_or(|output_id| self.midi_outputs.available.iter().any(|output| &output.id == output_id));
+            if is_known {
+                self.midi_output_source = config.source;
+                self.held_midi_note = None;
+                model_actions.midi_output_selection = Some(SelectMidiOutputAction { output_id: config.output_id });
+            } else {
+                crate::common::warn_log!("Model layer: Ignoring unknown MIDI output {:?}", config.output_id);
+            }
+        }
+
+        let wanted_midi_note = match self.midi_output_source {
+            MidiOutputSource::DetectedNote => self.detected_note,
+            _ => None,
+        };
+        if wanted_midi_note != self.held_midi_note {
+            self.held_midi_note = wanted_midi_note;
+            model_actions.midi_output = Some(MidiOutputAction::Hold(wanted_midi_note));
+        }
+
+        if let Some(target) = presentation_actions.target_note {
+            self.target_note = target.note;
+        }
+
+        match presentation_actions.exercise_control {
+            Some(ExerciseControl::Start(kind)) => {
+                self.reference_track = None;
+                let seed = exercise_seed(self.now_ms);
+                self.exercise = Some(Exercise::new(
+                    kind,
+                    self.current_scale,
+                    crate::app_config::EXERCISE_RANDOM_TARGET_COUNT,
+                    crate::app_config::EXERCISE_HOLD_MS,
+                    seed,
+                ));
+                self.attempt_ghost.start(kind, self.pitch_history.now_ms());
+                if let Some(metronome) = &mut self.metronome {
+                    metronome.restart(self.now_ms);
+                }
+            }
+            // A running exercise owns the target note
+            Some(ExerciseControl::Stop) if self.exercise.as_ref().is_some_and(|exercise| !exercise.is_finished()) => {
+                self.exercise = None;
+                self.attempt_ghost.abandon();
+                self.target_note = None;
+            }
+            Some(ExerciseControl::Stop) => self.exercise = None,
+            None => {}
+        }
+
+        match presentation_actions.reference_track_control {
+            Some(ReferenceTrackControl::Start(notes)) => self.start_reference_track(notes),
+            // A running melody owns the target note
+            Some(ReferenceTrackControl::Stop) if self.reference_track.as_ref().is_some_and(|track| !track.is_finished()) => {
+                self.reference_track = None;
+                self.target_note = None;
+            }
+            Some(ReferenceTrackControl::Stop) => self.reference_track = None,
+            None => {}
+        }
+
+        if let Some(track) = &mut self.reference_track
+            && let Some(ReferenceNote { note: Some(note), duration_ms }) = track.take_due_note(self.now_ms)
+        {
+            let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(self.tonal_center_note, self.concert_pitch_hz);
+            let semitones = note as i32 - self.tonal_center_note as i32;
+            if self.midi_output_source == MidiOutputSource::ReferenceTone {
+                model_actions.midi_output = Some(MidiOutputAction::Play { note, duration_ms: duration_ms as f32 });
+            }
+            model_actions.reference_tone_playback = Some(PlayReferenceToneAction {
+                frequency: crate::common::music_theory::interval_frequency(&self.tuning_system, tonal_center_frequency, semitones),
+                duration_ms: duration_ms as f32,
+            });
+        }
+
+        let mut cancel_scheduled_clicks = false;
+        if let Some(configuration) = presentation_actions.metronome_configuration {
+            match configuration.settings.as_ref().map_or(Ok(()), validate_metronome) {
+                Ok(()) => {
+                    self.metronome = configuration.settings.map(|settings| Metronome::new(settings, self.now_ms));
+                    cancel_scheduled_clicks = true;
+                }
+                Err(reason) => {
+                    crate::common::warn_log!("Model layer: Ignoring metronome change: {}", reason);
+                }
+            }
+        }
+        let mut clicks = Vec::new();
+        if let Some(metronome) = &mut self.metronome {
+            cancel_scheduled_clicks |= metronome.take_restarted();
+            let ticks = metronome.take_ticks(self.now_ms, self.now_ms + crate::app_config::METRONOME_LOOKAHEAD_MS);
+            if metronome.settings().click {
+                clicks = ticks;
+            }
+        }
+        if cancel_scheduled_clicks || !clicks.is_empty() {
+            model_actions.metronome_clicks = Some(ScheduleMetronomeClicksAction { cancel_scheduled: cancel_scheduled_clicks, clicks });
+        }
+
+        if let Some(config) = presentation_actions.spectrum_configuration {
+            let max_bin_count = crate::app_config::SPECTRUM_FFT_SIZE as usize / 2;
+            if (crate::app_config::MIN_SPECTRUM_BIN_COUNT..=max_bin_count).contains(&config.bin_count)
+                && config.bin_count.is_power_of_two()
+            {
+                model_actions.spectrum_configuration = Some(ConfigureSpectrumAction {
+                    bin_count: config.bin_count,
+                });
+            } else {
+                crate::common::warn_log!("Model layer: Ignoring spectrum bin count {}", config.bin_count);
+            }
+        }
+
+        if let Some(control) = presentation_actions.recording_control {
+            model_actions.recording = match control {
+                RecordingControl::Start => Some(RecordingAction::Start {
+                    max_seconds: crate::app_config::RECORDING_MAX_SECONDS,
+                }),
+                RecordingControl::Stop => Some(RecordingAction::Stop),
+                RecordingControl::ExportWav { from_seconds_ago, to_seconds_ago }
+                    if from_seconds_ago > to_seconds_ago
+                        && to_seconds_ago >= 0.0
+                        && to_seconds_ago < self.recording.recorded_seconds =>
+                {
+                    Some(RecordingAction::ExportWav { from_seconds_ago, to_seconds_ago })
+                }
+                RecordingControl::ExportWav { from_seconds_ago, to_seconds_ago } => {
+                    crate::common::warn_log!(
+                        "Model layer: Ignoring export of {}s..{}s ago from a {:.1}s recording",
+                        from_seconds_ago, to_seconds_ago, self.recording.recorded_seconds
+                    );
+                    None
+                }
+                RecordingControl::UseAsReferenceMelody if self.recording.recorded_seconds > 0.0 => Some(RecordingAction::AnalyzePitch),
+                RecordingControl::UseAsReferenceMelody => {
+                    crate::common::warn_log!("Model layer: Ignoring melody from an empty recording");
+                    None
+                }
+            };
+        }
+
+        if let Some(conditioning) = presentation_actions.input_conditioning_configuration {
+            match validate_input_conditioning(&conditioning.config) {
+                Ok(()) => {
+                    model_actions.input_conditioning_configuration = Some(ConfigureInputConditioningAction {
+                        config: conditioning.config,
+                    });
+                }
+                Err(reason) => {
+                    crate::common::warn_log!("Model layer: Ignoring input conditioning change: {}", reason);
+                }
+            }
+        }
+
+        if let Some(config) = presentation_actions.smoothing_configuration {
+            if let Err(reason) = validate_smoothing(config.strategy) {
+                crate::common::warn_log!("Model layer: Ignoring smoothing change: {}", reason);
+            } else {
+                self.smoothing = config.strategy;
+                self.rebuild_smoothers();
+            }
+        }
+
+        if let Some(config) = presentation_actions.analysis_window_configuration {
+            if let Err(reason) = validate_analysis_window(config.window_size, config.hop_size) {
+                crate::common::warn_log!("Model layer: Ignoring analysis window change: {}", reason);
+            } else {
+                let default_latency = analysis_latency_samples(crate::app_config::BUFFER_SIZE, crate::app_config::BUFFER_SIZE);
+                self.latency_scale = analysis_latency_samples(config.window_size, config.hop_size) as f32 / default_latency as f32;
+                self.rebuild_smoothers();
+                self.last_detected_pitch = None;
+                crate::common::dev_log!(
+                    "Model layer: Analysis window {} / hop {}, smoothing scaled by {:.2}",
+                    config.window_size, config.hop_size, self.latency_scale
+                );
+                model_actions.analysis_window_configuration = Some(ConfigureAnalysisWindowAction {
+                    window_size: config.window_size,
+                    hop_size: config.hop_size,
+                });
+            }
         }
         
         model_actions
@@ -222,7 +1248,199 @@ impl DataModel {
     fn reset_smoothers(&mut self) {
         self.last_detected_pitch = None;
         self.frequency_smoother.reset();
+        self.clarity_smoother.reset();
+    }
+
+    fn rebuild_smoothers(&mut self) {
+        self.frequency_smoother = create_smoother(self.smoothing, self.latency_scale);
+        self.clarity_smoother = create_clarity_smoother(self.smoothing, self.latency_scale);
+    }
+
+    /// Sing along with `notes` from now on, in place of a running exercise
+    fn start_reference_track(&mut self, notes: Vec<ReferenceNote>) {
+        if let Err(reason) = validate_reference_notes(&notes) {
+            crate::common::warn_log!("Model layer: Ignoring reference melody: {}", reason);
+            return;
+        }
+        self.exercise = None;
+        self.attempt_ghost.abandon();
+        self.reference_track = Some(ReferencePlayback::new(notes, self.now_ms));
     }
     
 }
 
+
+/// Seed for the random targets of an exercise, spread from the time it starts
+fn exercise_seed(now_ms: f64) -> u32 {
+    ((now_ms as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as u32
+}
+
+fn validate_analysis_window(window_size: usize, hop_size: usize) -> Result<(), String> {
+    use crate::app_config::{AUDIO_CHUNK_SIZE, MIN_ANALYSIS_WINDOW_SIZE, MAX_ANALYSIS_WINDOW_SIZE};
+
+    if !(MIN_ANALYSIS_WINDOW_SIZE..=MAX_ANALYSIS_WINDOW_SIZE).contains(&window_size) {
+        return Err(format!("window size {} is outside {}-{}", window_size, MIN_ANALYSIS_WINDOW_SIZE, MAX_ANALYSIS_WINDOW_SIZE));
+    }
+    if !window_size.is_multiple_of(AUDIO_CHUNK_SIZE) || hop_size == 0 || !hop_size.is_multiple_of(AUDIO_CHUNK_SIZE) {
+        return Err(format!("window and hop sizes must be multiples of {}", AUDIO_CHUNK_SIZE));
+    }
+    if hop_size > window_size {
+        return Err(format!("hop size {} is larger than window size {}", hop_size, window_size));
+    }
+    Ok(())
+}
+
+fn validate_snapshot(snapshot: &ModelSnapshot) -> Result<(), String> {
+    if !crate::common::shared_types::is_valid_midi_note(snapshot.tonal_center_note as i32) {
+        return Err(format!("tonal center {} is not a MIDI note", snapshot.tonal_center_note));
+    }
+    if !(crate::app_config::MIN_CONCERT_PITCH_HZ..=crate::app_config::MAX_CONCERT_PITCH_HZ).contains(&snapshot.concert_pitch_hz) {
+        return Err(format!("concert pitch {} Hz is out of range", snapshot.concert_pitch_hz));
+    }
+    if !(crate::app_config::MIN_IN_TUNE_TOLERANCE_CENTS..=crate::app_config::MAX_IN_TUNE_TOLERANCE_CENTS).contains(&snapshot.tolerance_profile.cents()) {
+        return Err(format!("tolerance of {} cents is out of range", snapshot.tolerance_profile.cents()));
+    }
+    validate_tuning_system(&snapshot.tuning_system)?;
+    validate_scale(snapshot.scale)?;
+    validate_smoothing(snapshot.smoothing)
+}
+
+fn validate_smoothing(strategy: SmoothingStrategy) -> Result<(), String> {
+    match strategy {
+        SmoothingStrategy::Ema { alpha } if !(alpha > 0.0 && alpha <= 1.0) => {
+            Err(format!("EMA factor {} is outside 0-1", alpha))
+        }
+        SmoothingStrategy::Median { window } if !(1..=crate::app_config::MAX_MEDIAN_SMOOTHING_WINDOW).contains(&window) => {
+            Err(format!("median window {} is outside 1-{}", window, crate::app_config::MAX_MEDIAN_SMOOTHING_WINDOW))
+        }
+        SmoothingStrategy::OneEuro { min_cutoff_hz, beta } if !(min_cutoff_hz > 0.0 && beta >= 0.0) => {
+            Err(format!("one-euro cutoff {} Hz and beta {} must be positive", min_cutoff_hz, beta))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Degrees start at the tonal center and rise strictly within one octave
+fn validate_tuning_system(tuning_system: &TuningSystem) -> Result<(), String> {
+    match tuning_system {
+        TuningSystem::JustIntonation(ratios) => {
+            if ratios.0.iter().any(|ratio| ratio.numerator == 0 || ratio.denominator == 0) {
+                return Err("ratios must have non-zero terms".to_string());
+            }
+            if ratios.0[0].value() != 1.0 {
+                return Err("the first degree must be the unison 1/1".to_string());
+            }
+            if ratios.0.windows(2).any(|pair| pair[1].value() <= pair[0].value()) || ratios.0[11].value() >= 2.0 {
+                return Err("ratios must rise strictly and stay below the octave".to_string());
+            }
+        }
+        TuningSystem::Custom(offsets) => {
+            if offsets.len() != 12 {
+                return Err(format!("custom tunings need 12 cent offsets, got {}", offsets.len()));
+            }
+            if offsets.iter().any(|offset| !offset.is_finite()) {
+                return Err("cent offsets must be finite".to_string());
+            }
+            if offsets[0] != 0.0 {
+                return Err("the first degree must be the tonal center".to_string());
+            }
+            let cents: Vec<f32> = offsets.iter()
+                .enumerate()
+                .map(|(degree, offset)| 100.0 * degree as f32 + offset)
+                .collect();
+            if cents.windows(2).any(|pair| pair[1] <= pair[0]) || cents[11] >= 1200.0 {
+                return Err("degrees must rise strictly and stay below the octave".to_string());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// User-defined scales must contain the root and only the 12 semitones of one octave
+fn validate_scale(scale: Scale) -> Result<(), String> {
+    if let Scale::Custom(mask) = scale {
+        if mask & 1 == 0 {
+            return Err("custom scales must include the root".to_string());
+        }
+        if mask >> 12 != 0 {
+            return Err(format!("custom scale mask {:#06x} has bits above the octave", mask));
+        }
+    }
+    Ok(())
+}
+
+fn is_valid_batch_size(batch_size: usize) -> bool {
+    batch_size > 0
+        && batch_size <= crate::app_config::MAX_BATCH_SIZE
+        && batch_size.is_multiple_of(crate::app_config::AUDIO_CHUNK_SIZE)
+}
+
+/// Reference tones are limited to three octaves either side of the tonal center
+const MAX_REFERENCE_INTERVAL_SEMITONES: i32 = 36;
+const MAX_ENVELOPE_STAGE_MS: f32 = 5000.0;
+
+fn validate_reference_tone(settings: &ReferenceToneSettings) -> Result<(), String> {
+    let envelope = &settings.envelope;
+    for stage_ms in [envelope.attack_ms, envelope.decay_ms, envelope.release_ms] {
+        if !(0.0..=MAX_ENVELOPE_STAGE_MS).contains(&stage_ms) {
+            return Err(format!("envelope times must be within 0-{} ms", MAX_ENVELOPE_STAGE_MS));
+        }
+    }
+    if !(0.0..=1.0).contains(&envelope.sustain_level) {
+        return Err(format!("sustain level {} is outside 0-1", envelope.sustain_level));
+    }
+    if !(0.0..=1.0).contains(&settings.volume) {
+        return Err(format!("volume {} is outside 0-1", settings.volume));
+    }
+    Ok(())
+}
+
+/// Subdivisions finer than sixteenths are not offered
+const MAX_METRONOME_SUBDIVISION: u32 = 4;
+
+fn validate_metronome(settings: &MetronomeSettings) -> Result<(), String> {
+    let (min_bpm, max_bpm) = (crate::app_config::MIN_METRONOME_TEMPO_BPM, crate::app_config::MAX_METRONOME_TEMPO_BPM);
+    if !(min_bpm..=max_bpm).contains(&settings.tempo_bpm) {
+        return Err(format!("tempo {} bpm is outside {}-{} bpm", settings.tempo_bpm, min_bpm, max_bpm));
+    }
+    if !(1..=MAX_METRONOME_SUBDIVISION).contains(&settings.subdivision) {
+        return Err(format!("subdivision {} is outside 1-{}", settings.subdivision, MAX_METRONOME_SUBDIVISION));
+    }
+    Ok(())
+}
+
+/// Gate thresholds below this are inaudible and would never close the gate
+const MIN_GATE_THRESHOLD_DB: f32 = -100.0;
+const MAX_GATE_TIME_MS: f32 = 2000.0;
+const MIN_HIGH_PASS_HZ: f32 = 20.0;
+const MAX_HIGH_PASS_HZ: f32 = 200.0;
+
+fn validate_input_conditioning(config: &InputConditioning) -> Result<(), String> {
+    if !(MIN_GATE_THRESHOLD_DB..=0.0).contains(&config.gate_threshold_db) {
+        return Err(format!("gate threshold {} dB is outside {}-0 dB", config.gate_threshold_db, MIN_GATE_THRESHOLD_DB));
+    }
+    for time_ms in [config.gate_attack_ms, config.gate_release_ms] {
+        if !(time_ms > 0.0 && time_ms <= MAX_GATE_TIME_MS) {
+            return Err(format!("gate attack and release must be within 0-{} ms", MAX_GATE_TIME_MS));
+        }
+    }
+    if !(MIN_HIGH_PASS_HZ..=MAX_HIGH_PASS_HZ).contains(&config.high_pass_hz) {
+        return Err(format!("high-pass cutoff {} Hz is outside {}-{} Hz", config.high_pass_hz, MIN_HIGH_PASS_HZ, MAX_HIGH_PASS_HZ));
+    }
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+impl crate::debug::inspector::Inspect for DataModel {
+    fn inspect(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("tuning_system", format!("{:?}", self.tuning_system)),
+            ("tonal_center_note", format!("{} ({})", crate::common::shared_types::midi_note_to_name(self.tonal_center_note), self.tonal_center_note)),
+            ("current_scale", format!("{:?}", self.current_scale)),
+            ("last_detected_pitch", self.last_detected_pitch.map_or("-".to_string(), |hz| format!("{:.2} Hz", hz))),
+            ("smoother", format!("{:?}", self.smoothing)),
+            ("latency_scale", format!("{:.2}", self.latency_scale)),
+        ]
+    }
+}
@@ -1,7 +1,6 @@
-#![cfg(target_arch = "wasm32")]
-
 use three_d::*;
 use crate::app_config::{NOTE_LINE_LEFT_MARGIN, NOTE_LINE_RIGHT_MARGIN};
+use crate::common::shared_types::ToleranceProfile;
 
 /// Width of the data texture used for historical data
 pub const DATA_TEXTURE_WIDTH: usize = 512;
@@ -16,6 +15,8 @@ pub struct BackgroundShaderMaterial {
     pub tint_color: Vec3,
     pub current_pitch_color: Vec3,
     pub latest_cents_offset: f32,
+    /// Largest offset that still highlights the latest pitch
+    pub tolerance_cents: f32,
 }
 
 impl Material for BackgroundShaderMaterial {
@@ -35,6 +36,7 @@ impl Material for BackgroundShaderMaterial {
             uniform vec3 tintColor;
             uniform vec3 currentPitchColor;
             uniform float latestCentsOffset;
+            uniform float toleranceCents;
 
             in vec2 uvs;
             out vec4 fragColor;
@@ -47,7 +49,7 @@ impl Material for BackgroundShaderMaterial {
                 vec4 latestData = texture(dataTexture, vec2(1.0, 0.5));
                 float latestDetected = latestData.r;
                 float latestPitch = latestData.g;
-                bool isAccurate = abs(latestCentsOffset) < 15.0;
+                bool isAccurate = abs(latestCentsOffset) < toleranceCents;
 
                 // Create a band around the latest pitch line (extends to full width including margins)
                 float bandThickness = 0.02; // Adjust band thickness as needed
@@ -117,6 +119,7 @@ impl Material for BackgroundShaderMaterial {
         program.use_uniform("tintColor", self.tint_color);
         program.use_uniform("currentPitchColor", self.current_pitch_color);
         program.use_uniform("latestCentsOffset", self.latest_cents_offset);
+        program.use_uniform("toleranceCents", self.tolerance_cents);
     }
 
     fn render_states(&self) -> RenderStates {
@@ -188,6 +191,7 @@ impl BackgroundShader {
             tint_color: Vec3::new(1.0, 0.0, 1.0), // Default magenta
             current_pitch_color: Vec3::new(0.88, 0.80, 0.62), // Default accent (sand)
             latest_cents_offset: 0.0,
+            tolerance_cents: ToleranceProfile::default().cents(),
         };
 
         Ok(Self {
@@ -0,0 +1,118 @@
+use three_d::{Circle, ColorMaterial, Context, Gm, Object, Viewport};
+use crate::app_config::RECORDING_LAMP_BLINK_MS;
+use crate::common::shared_types::ColorScheme;
+use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::materials::solid_material;
+
+const COUNT_TEXT_SIZE: f32 = 96.0;
+/// Radius of the disc behind the count as a part of the smaller side of the
+/// display; it shrinks by `COUNT_DISC_SHRINK` over every beat
+const COUNT_DISC_RADIUS: f32 = 0.18;
+const COUNT_DISC_SHRINK: f32 = 0.25;
+const COUNT_DISC_ALPHA: f32 = 0.85;
+const LAMP_RADIUS: f32 = 8.0;
+const LAMP_MARGIN: f32 = 16.0;
+const LAMP_TEXT_SIZE: f32 = 16.0;
+/// Gap between the lamp label and the lamp
+const LAMP_LABEL_GAP: f32 = 8.0;
+/// Opacity of a blinking lamp at the dark end of its blink
+const LAMP_DIM_ALPHA: f32 = 0.25;
+
+/// What the lamp in the corner of the display shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lamp {
+    pub label: String,
+    /// A recording lamp blinks in the error color, an exercise lamp stays lit
+    /// in the accent color
+    pub recording: bool,
+}
+
+/// Everything the texts depend on
+#[derive(Clone, PartialEq)]
+struct TextLayout {
+    count: Option<u32>,
+    lamp_label: Option<String>,
+    scheme: ColorScheme,
+    viewport: Viewport,
+}
+
+/// Count-in over the middle of the display and a lamp in its top right corner
+/// while recording or an exercise runs
+#[derive(Default)]
+pub struct CaptureOverlayView {
+    count_disc: Option<Gm<Circle, ColorMaterial>>,
+    lamp: Option<Gm<Circle, ColorMaterial>>,
+    /// Rendered again only when their layout changes
+    texts: Vec<Box<dyn Object>>,
+    text_layout: Option<TextLayout>,
+}
+
+impl CaptureOverlayView {
+    /// Show the beat of a count-in with the part of it that has passed, and
+    /// the lamp; either may be `None`
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &Context,
+        text_backend: &mut EguiTextBackend,
+        count_in: Option<(u32, f32)>,
+        lamp: Option<&Lamp>,
+        scheme: &ColorScheme,
+        viewport: Viewport,
+        now_ms: f64,
+    ) {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        let center = (width * 0.5, height * 0.5);
+
+        self.count_disc = count_in.map(|(_, beat_progress)| {
+            let radius = width.min(height) * COUNT_DISC_RADIUS * (1.0 - COUNT_DISC_SHRINK * beat_progress);
+            let mut disc = self.count_disc.take().unwrap_or_else(|| Gm::new(Circle::new(context, center, radius), ColorMaterial::default()));
+            disc.set_center(center);
+            disc.set_radius(radius);
+            disc.material = solid_material(scheme.surface, COUNT_DISC_ALPHA);
+            disc
+        });
+
+        let lamp_center = (width - LAMP_MARGIN - LAMP_RADIUS, height - LAMP_MARGIN - LAMP_RADIUS);
+        self.lamp = lamp.map(|lamp| {
+            let mut light = self.lamp.take().unwrap_or_else(|| Gm::new(Circle::new(context, lamp_center, LAMP_RADIUS), ColorMaterial::default()));
+            light.set_center(lamp_center);
+            light.material = if lamp.recording {
+                let blink = 0.5 + 0.5 * (std::f64::consts::TAU * now_ms / RECORDING_LAMP_BLINK_MS).cos() as f32;
+                solid_material(scheme.error, LAMP_DIM_ALPHA + (1.0 - LAMP_DIM_ALPHA) * blink)
+            } else {
+                solid_material(scheme.accent, 1.0)
+            };
+            light
+        });
+
+        let text_layout = TextLayout {
+            count: count_in.map(|(beat, _)| beat),
+            lamp_label: lamp.map(|lamp| lamp.label.clone()),
+            scheme: scheme.clone(),
+            viewport,
+        };
+        if self.text_layout.as_ref() != Some(&text_layout) {
+            let color = [scheme.text[0], scheme.text[1], scheme.text[2], 1.0];
+            // Text is placed by its top edge
+            let count: Vec<_> = text_layout.count
+                .map(|beat| (beat.to_string(), center.0, center.1 + COUNT_TEXT_SIZE * 0.6, COUNT_TEXT_SIZE, color, true))
+                .into_iter()
+                .collect();
+            let lamp_label: Vec<_> = text_layout.lamp_label.iter()
+                .map(|label| (label.clone(), lamp_center.0 - LAMP_RADIUS - LAMP_LABEL_GAP, lamp_center.1 + LAMP_TEXT_SIZE * 0.6, LAMP_TEXT_SIZE, color, false))
+                .collect();
+            self.texts = text_backend.render_texts(context, viewport, &count, three_d::egui::Align::Center);
+            self.texts.extend(text_backend.render_texts(context, viewport, &lamp_label, three_d::egui::Align::RIGHT));
+            self.text_layout = Some(text_layout);
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = Vec::new();
+        objects.extend(self.count_disc.iter().map(|disc| disc as &dyn Object));
+        objects.extend(self.lamp.iter().map(|lamp| lamp as &dyn Object));
+        objects.extend(self.texts.iter().map(|text| text.as_ref()));
+        objects
+    }
+}
@@ -1,5 +1,3 @@
-#![cfg(target_arch = "wasm32")]
-
 use three_d::*;
 
 /// Text rendering parameters: (text, x, y, size, color, is_bold)
@@ -148,12 +146,11 @@ impl EguiTextBackend {
         let mut render_objects = Vec::new();
         
         for primitive in primitives {
-            if let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive {
-                if let Some(texture) = &self.font_texture {
-                    if let Some(obj) = self.create_render_object(context, mesh, texture.clone(), viewport) {
-                        render_objects.push(obj);
-                    }
-                }
+            if let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive
+                && let Some(texture) = &self.font_texture
+                && let Some(obj) = self.create_render_object(context, mesh, texture.clone(), viewport)
+            {
+                render_objects.push(obj);
             }
         }
         
@@ -0,0 +1,97 @@
+use std::f32::consts::TAU;
+use three_d::{ColorMaterial, Context, Deg, Gm, Line, Object, PhysicalPoint, Rectangle};
+use crate::common::level_meter::LevelReading;
+use crate::common::shared_types::ColorScheme;
+use crate::presentation::materials::solid_material;
+
+const PANEL_WIDTH: f32 = 120.0;
+const PANEL_HEIGHT: f32 = 90.0;
+const PANEL_MARGIN: f32 = 10.0;
+const PANEL_PADDING: f32 = 10.0;
+const BAR_WIDTH: f32 = 14.0;
+const BAR_HEIGHT: f32 = 60.0;
+/// Height of the clip light above the bar
+const CLIP_LIGHT_HEIGHT: f32 = 8.0;
+const PEAK_MARKER_THICKNESS: f32 = 2.0;
+const RING_RADIUS: f32 = 26.0;
+const RING_THICKNESS: f32 = 6.0;
+const RING_SEGMENT_COUNT: usize = 36;
+
+/// Input level bar and detection clarity ring, in a panel at the bottom left
+/// of the canvas
+pub struct InputMetersView {
+    panel: Gm<Rectangle, ColorMaterial>,
+    bar_track: Gm<Rectangle, ColorMaterial>,
+    peak_bar: Gm<Rectangle, ColorMaterial>,
+    rms_bar: Gm<Rectangle, ColorMaterial>,
+    peak_marker: Gm<Rectangle, ColorMaterial>,
+    clip_light: Gm<Rectangle, ColorMaterial>,
+    /// Clockwise from the top; the first ones are lit in proportion to the clarity
+    ring_segments: Vec<Gm<Line, ColorMaterial>>,
+}
+
+impl InputMetersView {
+    pub fn new(context: &Context) -> Self {
+        let rectangle = || Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default());
+        let origin = PhysicalPoint { x: 0.0, y: 0.0 };
+        Self {
+            panel: rectangle(),
+            bar_track: rectangle(),
+            peak_bar: rectangle(),
+            rms_bar: rectangle(),
+            peak_marker: rectangle(),
+            clip_light: rectangle(),
+            ring_segments: (0..RING_SEGMENT_COUNT)
+                .map(|_| Gm::new(Line::new(context, origin, origin, RING_THICKNESS), ColorMaterial::default()))
+                .collect(),
+        }
+    }
+
+    /// Lay out the meters `bottom` pixels above the bottom of the canvas; the
+    /// ring is drawn in the accent color while a pitch is detected
+    pub fn update(&mut self, level: LevelReading, clarity: Option<f32>, pitch_detected: bool, scheme: &ColorScheme, bottom: f32) {
+        let left = PANEL_MARGIN;
+        let panel_bottom = bottom + PANEL_MARGIN;
+        self.panel.set_center((left + PANEL_WIDTH * 0.5, panel_bottom + PANEL_HEIGHT * 0.5));
+        self.panel.set_size(PANEL_WIDTH, PANEL_HEIGHT);
+        self.panel.material = solid_material(scheme.surface, 1.0);
+
+        let bar_x = left + PANEL_PADDING + BAR_WIDTH * 0.5;
+        let bar_bottom = panel_bottom + PANEL_PADDING;
+        let set_bar = |bar: &mut Gm<Rectangle, ColorMaterial>, fraction: f32, color: [f32; 3]| {
+            let height = BAR_HEIGHT * fraction;
+            bar.set_center((bar_x, bar_bottom + height * 0.5));
+            bar.set_size(BAR_WIDTH, height);
+            bar.material = solid_material(color, 1.0);
+        };
+        set_bar(&mut self.bar_track, 1.0, scheme.background);
+        set_bar(&mut self.peak_bar, level.peak, scheme.secondary);
+        set_bar(&mut self.rms_bar, level.rms, scheme.primary);
+
+        self.peak_marker.set_center((bar_x, bar_bottom + BAR_HEIGHT * level.peak_hold));
+        self.peak_marker.set_size(BAR_WIDTH, PEAK_MARKER_THICKNESS);
+        self.peak_marker.material = solid_material(scheme.text, 1.0);
+
+        self.clip_light.set_center((bar_x, bar_bottom + BAR_HEIGHT + CLIP_LIGHT_HEIGHT * 0.5 + PEAK_MARKER_THICKNESS));
+        self.clip_light.set_size(BAR_WIDTH, CLIP_LIGHT_HEIGHT);
+        self.clip_light.material = solid_material(if level.clipping { scheme.error } else { scheme.border }, 1.0);
+
+        let ring_center = (left + PANEL_WIDTH - PANEL_PADDING - RING_RADIUS - RING_THICKNESS * 0.5, panel_bottom + PANEL_HEIGHT * 0.5);
+        let ring_point = |segment: usize| {
+            let angle = TAU * segment as f32 / RING_SEGMENT_COUNT as f32;
+            PhysicalPoint { x: ring_center.0 + RING_RADIUS * angle.sin(), y: ring_center.1 + RING_RADIUS * angle.cos() }
+        };
+        let lit_segments = (clarity.unwrap_or(0.0).clamp(0.0, 1.0) * RING_SEGMENT_COUNT as f32).round() as usize;
+        let lit_color = if pitch_detected { scheme.accent } else { scheme.muted };
+        for (segment, line) in self.ring_segments.iter_mut().enumerate() {
+            line.set_endpoints(ring_point(segment), ring_point(segment + 1));
+            line.material = solid_material(if segment < lit_segments { lit_color } else { scheme.background }, 1.0);
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = vec![&self.panel, &self.bar_track, &self.peak_bar, &self.rms_bar, &self.peak_marker, &self.clip_light];
+        objects.extend(self.ring_segments.iter().map(|segment| segment as &dyn Object));
+        objects
+    }
+}
@@ -0,0 +1,17 @@
+use three_d::{Blend, ColorMaterial, RenderStates, WriteMask};
+use crate::common::theme::rgb_to_srgba_with_alpha;
+
+/// Untextured material of one color, blended over what is behind it when
+/// `alpha` is below 1
+pub fn solid_material(color: [f32; 3], alpha: f32) -> ColorMaterial {
+    ColorMaterial {
+        color: rgb_to_srgba_with_alpha(color, alpha),
+        texture: None,
+        is_transparent: alpha < 1.0,
+        render_states: RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        },
+    }
+}
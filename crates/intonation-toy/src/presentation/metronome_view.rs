@@ -0,0 +1,60 @@
+use three_d::{Circle, ColorMaterial, Context, Gm, Object, Viewport};
+use crate::common::shared_types::{ColorScheme, MetronomeStatus};
+use crate::common::tween::Easing;
+use crate::presentation::materials::solid_material;
+
+const BEAT_RADIUS: f32 = 14.0;
+/// Radius of the flash on a subdivision as a part of that on the beat
+const SUBDIVISION_RADIUS: f32 = 0.6;
+const MARGIN: f32 = 16.0;
+/// Dots under the flash, one per tick of the beat
+const DOT_RADIUS: f32 = 3.0;
+const DOT_SPACING: f32 = 10.0;
+const DOT_GAP: f32 = 10.0;
+/// Opacity of the dots of the ticks not sounding
+const DOT_DIM_ALPHA: f32 = 0.3;
+
+/// Flash in the top left corner of the display that lights up on every tick
+/// of the metronome and fades until the next, above a dot for every tick of
+/// the beat
+pub struct MetronomeView {
+    flash: Gm<Circle, ColorMaterial>,
+    dots: Vec<Gm<Circle, ColorMaterial>>,
+}
+
+impl MetronomeView {
+    pub fn new(context: &Context) -> Self {
+        Self {
+            flash: Gm::new(Circle::new(context, (0.0, 0.0), BEAT_RADIUS), ColorMaterial::default()),
+            dots: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, context: &Context, status: &MetronomeStatus, scheme: &ColorScheme, viewport: Viewport) {
+        let center = (MARGIN + BEAT_RADIUS, viewport.height as f32 - MARGIN - BEAT_RADIUS);
+        let on_beat = status.tick == 0;
+        let fade = 1.0 - Easing::EaseOutCubic.apply(status.tick_progress);
+        self.flash.set_center(center);
+        self.flash.set_radius(if on_beat { BEAT_RADIUS } else { BEAT_RADIUS * SUBDIVISION_RADIUS });
+        self.flash.material = solid_material(if on_beat { scheme.accent } else { scheme.muted }, fade);
+
+        let subdivision = status.settings.subdivision as usize;
+        if subdivision <= 1 {
+            self.dots.clear();
+            return;
+        }
+        self.dots.resize_with(subdivision, || Gm::new(Circle::new(context, (0.0, 0.0), DOT_RADIUS), ColorMaterial::default()));
+        let first_x = center.0 - DOT_SPACING * (subdivision - 1) as f32 * 0.5;
+        let y = center.1 - BEAT_RADIUS - DOT_GAP;
+        for (tick, dot) in self.dots.iter_mut().enumerate() {
+            dot.set_center((first_x + DOT_SPACING * tick as f32, y));
+            dot.material = solid_material(scheme.text, if tick == status.tick as usize { 1.0 } else { DOT_DIM_ALPHA });
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        std::iter::once(&self.flash as &dyn Object)
+            .chain(self.dots.iter().map(|dot| dot as &dyn Object))
+            .collect()
+    }
+}
@@ -1,4 +1,4 @@
-#![cfg(target_arch = "wasm32")]
+#![cfg(any(target_arch = "wasm32", feature = "desktop"))]
 
 //! Presentation Layer - Visualization and user interface
 //! 
@@ -10,69 +10,95 @@
 //! - Visual feedback and animations
 //! - Debug visualization and overlays
 //! 
-//! 
+//! The `Renderer` draws the display natively too, for the desktop build;
+//! the `Presenter` around it drives the page and is browser-only.
 
 mod audio_analysis;
 mod background_shader;
+mod capture_overlay_view;
 mod renderer;
 mod tuning_lines;
 mod egui_text_backend;
+mod input_meters_view;
+mod materials;
+mod metronome_view;
+mod needle_view;
+mod piano_view;
+mod pitch_graph;
+mod pitch_history_view;
+mod spectrogram_view;
+mod staff_view;
+mod statistics_view;
+mod strobe_shader;
 mod user_pitch_line;
 pub use audio_analysis::AudioAnalysis;
 pub use background_shader::BackgroundShader;
+pub use capture_overlay_view::Lamp;
 pub use renderer::Renderer;
 pub use tuning_lines::TuningLines;
 pub use egui_text_backend::EguiTextBackend;
 pub use user_pitch_line::UserPitchLine;
 
-use std::rc::Rc;
-use std::cell::RefCell;
-use three_d::{RenderTarget, Context, Viewport};
-use crate::common::shared_types::{ModelUpdateResult, TuningSystem, Scale, MidiNote, Pitch};
-
-use crate::web::sidebar_controls::{setup_sidebar_controls, cleanup_sidebar_controls, setup_event_listeners};
-
-/// Request to change the tuning system
-#[derive(Debug, Clone, PartialEq)]
-pub struct ChangeTuningSystem {
-    pub tuning_system: TuningSystem,
-}
-
-/// Action for changing the active scale
-#[derive(Debug, Clone, PartialEq)]
-pub struct ScaleChangeAction {
-    pub scale: Scale,
-}
+#[cfg(target_arch = "wasm32")]
+use {
+    std::rc::Rc,
+    std::cell::RefCell,
+    three_d::{RenderTarget, Context, Viewport},
+    crate::common::shared_types::{Error, ModelUpdateResult, TuningSystem, Scale, MidiNote, MidiNoteEvent, MidiInputMode, MidiOutputSource, BatchSizeMode, PeakMeasure, IntervalMode, Transposition, NoteNaming, ToleranceProfile, SmoothingStrategy, ModelSnapshot, SessionExport, SessionExportFormat, IntervalZoom, MetronomeSettings, AudioLifecycleState, RecoveryEvent, RecoveryReason, Pitch, PitchAlgorithm, InputConditioning, ReferenceToneSettings, increment_midi_note, decrement_midi_note, shift_midi_note_octaves},
+    crate::common::localization::{translate, translate_with},
+    crate::common::announcements::{Announcement, Deviation, PitchAnnouncer, Reading},
+    crate::common::countdown::Countdown,
+    crate::common::interval_axis::{IntervalAxis, wrap_octaves},
+    crate::common::deep_link::DeepLink,
+    crate::common::level_meter::LevelMeter,
+    crate::common::notifications::{NotificationChange, Notifications},
+    crate::common::pane_layout::{PaneKind, PaneLayout, Splitter},
+    crate::common::shortcuts::{KeyBindings, Shortcut},
+    crate::common::staff_notation::StaffNote,
+    crate::common::telemetry::TelemetryEvent,
+    crate::common::tutorial::Tutorial,
+    crate::common::tween::{Easing, Tween},
+    crate::web::picture_in_picture::MiniViewReading,
+    crate::web::sidebar_controls::{setup_sidebar_controls, cleanup_sidebar_controls, setup_event_listeners, current_tonal_center_amplitude},
+};
+pub use crate::common::presentation_actions::*;
 
 #[cfg(debug_assertions)]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct ConfigureTestSignal {
     pub enabled: bool,
     pub frequency: f32,
     pub volume: f32,
+    /// Glide from the start to the end frequency instead of holding `frequency`
+    pub sweep: Option<FrequencySweep>,
+    pub vibrato: Option<Vibrato>,
+    /// Mix white noise at this signal-to-noise ratio in dB
+    pub snr_db: Option<f32>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct ConfigureTonalCenter {
-    pub note: MidiNote,
-    pub volume: f32,
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepShape {
+    Linear,
+    /// Equal time per octave
+    Logarithmic,
 }
 
-/// Container for all collected user actions from the presentation layer
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct PresentationLayerActions {
-    pub tuning_system_change: Option<ChangeTuningSystem>,
-    pub scale_change: Option<ScaleChangeAction>,
-    pub tonal_center_configuration: Option<ConfigureTonalCenter>,
+/// Frequency glide; the end frequency is held once the sweep is over
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrequencySweep {
+    pub shape: SweepShape,
+    pub start_frequency: f32,
+    pub end_frequency: f32,
+    pub duration_seconds: f32,
 }
 
-impl PresentationLayerActions {
-    /// Check if there are any actions to process
-    pub fn has_actions(&self) -> bool {
-        self.tuning_system_change.is_some() ||
-        self.scale_change.is_some() ||
-        self.tonal_center_configuration.is_some()
-    }
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vibrato {
+    pub rate_hz: f32,
+    pub depth_cents: f32,
 }
 
 /// Container for all collected debug actions from the presentation layer
@@ -84,6 +110,7 @@ pub struct DebugLayerActions {
 
 /// Presenter - The presentation layer of the three-layer architecture
 /// 
+#[cfg(target_arch = "wasm32")]
 pub struct Presenter {
     renderer: Option<Box<Renderer>>,
     pending_user_actions: PresentationLayerActions,
@@ -92,11 +119,52 @@ pub struct Presenter {
     interval_position: f32,
     sidebar_ui_active: bool,
     display_range: crate::common::shared_types::DisplayRange,
+    interval_zoom: IntervalZoom,
+    /// Placement of intervals on the display, tweened towards the display
+    /// range or the zoom around the target note
+    interval_axis: Tween<IntervalAxis>,
+    display_scene: crate::common::shared_types::DisplayScene,
+    needle_damping: crate::common::shared_types::NeedleDamping,
+    /// Whether the spectrogram band along the bottom of the display is shown
+    spectrogram_shown: bool,
+    spectrogram_dynamic_range_db: f32,
+    /// Clef of the staff, `None` while it is hidden
+    staff_clef: Option<crate::common::shared_types::Clef>,
+    key_bindings: KeyBindings,
+    /// What a click on the piano keyboard selects, `None` while it is hidden
+    piano_mode: Option<MidiInputMode>,
+    /// Present while the input meters are shown
+    level_meter: Option<LevelMeter>,
+    /// Present while pitch readings are announced to screen readers
+    pitch_announcer: Option<PitchAnnouncer>,
+    /// Draw the previous exercise attempt behind the pitch graph
+    ghost_trail_shown: bool,
+    /// Running count-in and what starts when it is over
+    count_in: Option<(Countdown, CountInTarget)>,
+    /// Errors shown as toasts or over the app
+    notifications: Notifications,
+    /// Step of the tutorial while it is shown
+    tutorial: Option<Tutorial>,
+    /// Show the session summary with the next model data
+    session_summary_requested: bool,
+    /// Capture the next frame as an image to share
+    share_image_requested: bool,
+    /// Closing the open session summary starts a new session
+    session_summary_ends_session: bool,
+    immersive_layout: bool,
+    /// Whether the immersive layout currently shows the sidebar
+    controls_visible: bool,
+    pane_layout: PaneLayout,
+    /// Splitter being dragged with the pointer
+    dragged_splitter: Option<Splitter>,
+    midi_input_mode: MidiInputMode,
+    lifecycle: AudioLifecycleState,
     self_reference: Option<Rc<RefCell<Self>>>,
     ui_listeners_attached: bool,
     current_viewport: Option<Viewport>,
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Presenter {
     /// Create a new Presenter wrapped in Rc<RefCell>
     pub fn create() -> Result<Rc<RefCell<Self>>, String> {
@@ -110,11 +178,37 @@ impl Presenter {
             interval_position: 0.0,
             sidebar_ui_active: true,
             display_range: crate::app_config::DEFAULT_DISPLAY_RANGE,
+            interval_zoom: IntervalZoom::default(),
+            interval_axis: Tween::new(IntervalAxis::of_range(&crate::app_config::DEFAULT_DISPLAY_RANGE)),
+            display_scene: crate::common::shared_types::DisplayScene::default(),
+            needle_damping: crate::common::shared_types::NeedleDamping::default(),
+            spectrogram_shown: false,
+            spectrogram_dynamic_range_db: crate::app_config::DEFAULT_SPECTROGRAM_DYNAMIC_RANGE_DB,
+            staff_clef: None,
+            key_bindings: crate::web::storage::settings().key_bindings.unwrap_or_default(),
+            piano_mode: None,
+            level_meter: None,
+            pitch_announcer: Some(PitchAnnouncer::default()),
+            ghost_trail_shown: false,
+            count_in: None,
+            notifications: Notifications::default(),
+            tutorial: None,
+            session_summary_requested: false,
+            share_image_requested: false,
+            session_summary_ends_session: false,
+            immersive_layout: false,
+            controls_visible: true,
+            pane_layout: crate::web::storage::settings().pane_layout.map(PaneLayout::normalized).unwrap_or_default(),
+            dragged_splitter: None,
+            midi_input_mode: MidiInputMode::default(),
+            lifecycle: AudioLifecycleState::default(),
             self_reference: None,
             ui_listeners_attached: false,
             current_viewport: None,
         };
         
+        crate::web::sidebar_controls::update_shortcut_buttons(&presenter.key_bindings);
+        crate::web::sidebar_controls::update_pane_toggles(&presenter.pane_layout);
         let presenter_rc = Rc::new(RefCell::new(presenter));
         
         presenter_rc.borrow_mut().self_reference = Some(presenter_rc.clone());
@@ -126,9 +220,75 @@ impl Presenter {
 
     pub fn update(&mut self, viewport: Viewport, model_data: &ModelUpdateResult) {
         self.current_viewport = Some(viewport);
+        if let Some(renderer) = &mut self.renderer {
+            renderer.set_pane_layout(&self.pane_layout, viewport);
+        }
+        let viewport = self.display_viewport(viewport);
         self.refresh_color_scheme();
         self.process_data(model_data);
         self.update_graphics(viewport, model_data);
+        // Once per frame, since every call scrolls the spectrogram; its pane
+        // shows it even while the band is hidden
+        let spectrogram_dynamic_range_db = (self.spectrogram_shown || self.pane_layout.shows(PaneKind::Spectrogram))
+            .then_some(self.spectrogram_dynamic_range_db);
+        if let Some(renderer) = &mut self.renderer {
+            renderer.update_spectrogram(model_data.spectrum.as_ref(), spectrogram_dynamic_range_db, viewport);
+            renderer.update_pitch_history(&model_data.pitch_trail, js_sys::Date::now());
+            renderer.update_statistics(&model_data.session_statistics);
+        }
+        self.update_staff(viewport, model_data);
+        self.update_controls_visibility();
+        if let Some(tutorial) = self.tutorial {
+            // The highlighted element moves as the page scrolls or resizes
+            crate::web::tutorial::place_tutorial_highlight(tutorial);
+        }
+        self.announce_pitch(model_data);
+        self.update_mini_view(model_data);
+        if let Some(renderer) = &mut self.renderer {
+            let piano = self.piano_mode.map(|_| (model_data.tonal_center_note, model_data.target_note, model_data.scale));
+            renderer.update_piano(piano, viewport);
+            let pitch_detected = matches!(model_data.pitch, Pitch::Detected(_));
+            let meters = self.level_meter.as_mut().map(|meter| {
+                (meter.update(js_sys::Date::now(), &model_data.volume, model_data.is_peaking), model_data.clarity, pitch_detected)
+            });
+            renderer.update_input_meters(meters, viewport);
+            renderer.update_metronome(model_data.metronome.as_ref(), viewport);
+        }
+        self.update_capture_overlay(viewport, model_data);
+    }
+
+    /// Start what a count-in that is over was counting in to, and show the
+    /// count-in and the recording or exercise lamp
+    fn update_capture_overlay(&mut self, viewport: Viewport, model_data: &ModelUpdateResult) {
+        let now_ms = js_sys::Date::now();
+        if let Some((countdown, target)) = self.count_in
+            && countdown.is_over(now_ms)
+        {
+            self.count_in = None;
+            match target {
+                CountInTarget::Recording => self.on_recording_control(RecordingControl::Start),
+                CountInTarget::Exercise(kind) => self.on_exercise_control(ExerciseControl::Start(kind)),
+            }
+        }
+        let count_in = self.count_in.and_then(|(countdown, _)| {
+            countdown.remaining_beats(now_ms).map(|beat| (beat, countdown.beat_progress(now_ms)))
+        });
+
+        let running_exercise = model_data.exercise.as_ref().filter(|exercise| exercise.current_target.is_some());
+        let lamp = if model_data.recording.is_recording {
+            let seconds = model_data.recording.recorded_seconds as u32;
+            let duration = format!("{}:{:02}", seconds / 60, seconds % 60);
+            Some(Lamp { label: translate_with("recording-lamp", &[&duration]), recording: true })
+        } else {
+            running_exercise.map(|exercise| {
+                let target = (exercise.results.len() + 1).to_string();
+                Lamp { label: translate_with("exercise-lamp", &[&target, &exercise.target_count.to_string()]), recording: false }
+            })
+        };
+
+        if let Some(renderer) = &mut self.renderer {
+            renderer.update_capture_overlay(count_in, lamp, viewport);
+        }
     }
 
     fn update_graphics(&mut self, viewport: Viewport, model_data: &ModelUpdateResult) {
@@ -141,30 +301,293 @@ impl Presenter {
         if let Some(renderer) = &mut self.renderer {
             renderer.update_presentation_context(&crate::common::shared_types::PresentationContext {
                 tonal_center_note: model_data.tonal_center_note,
-                tuning_system: model_data.tuning_system,
+                concert_pitch_hz: model_data.concert_pitch_hz,
+                tuning_system: model_data.tuning_system.clone(),
                 current_scale: model_data.scale,
-                display_range: self.display_range.clone(),
+                interval_axis: self.interval_axis.value(js_sys::Date::now()),
+                transposition: model_data.transposition,
+                note_naming: model_data.note_naming,
+                language: crate::common::localization::current_language(),
             }, viewport);
             
-            let tonal_center_frequency = crate::common::music_theory::midi_note_to_standard_frequency(model_data.tonal_center_note);
+            let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(model_data.tonal_center_note, model_data.concert_pitch_hz);
 
             renderer.update_audio_analysis(AudioAnalysis {
                 pitch_detected,
                 cents_offset: model_data.cents_offset,
+                tolerance_cents: model_data.tolerance_profile.cents(),
                 interval: self.interval_position,
                 volume_peak: model_data.is_peaking,
                 frequency,
                 tonal_center_frequency,
             });
+
+            renderer.set_scene(self.display_scene);
+            renderer.set_needle_damping(self.needle_damping);
+            if self.display_scene == crate::common::shared_types::DisplayScene::PitchGraph {
+                let ghost_trail = self.ghost_trail_shown.then_some(model_data.ghost_trail.as_slice());
+                renderer.update_pitch_graph(&model_data.pitch_trail, ghost_trail, js_sys::Date::now(), viewport);
+            }
         }
     }
 
+    /// Tell screen readers the closest note and how far off it is, once the
+    /// reading has settled
+    fn announce_pitch(&mut self, model_data: &ModelUpdateResult) {
+        let Some(announcer) = &mut self.pitch_announcer else {
+            return;
+        };
+        let reading = match (&model_data.pitch, model_data.closest_midi_note) {
+            (Pitch::Detected(_), Some(note)) => Some(Reading {
+                note,
+                deviation: Deviation::from_cents(model_data.cents_offset, model_data.tolerance_profile.cents()),
+            }),
+            _ => None,
+        };
+        let text = match announcer.update(reading, js_sys::Date::now()) {
+            Some(Announcement::Pitch(reading)) => {
+                translate_with("announcement-pitch", &[&model_data.note_namer().name(reading.note), translate(reading.deviation.text_key())])
+            }
+            Some(Announcement::NoPitch) => translate("announcement-no-pitch").to_string(),
+            None => return,
+        };
+        crate::web::accessibility::announce(&text);
+    }
+
+    /// Show the closest note and its offset in the mini view, when it is open
+    pub fn update_mini_view(&self, model_data: &ModelUpdateResult) {
+        if !crate::web::picture_in_picture::is_open() {
+            return;
+        }
+        let reading = match (&model_data.pitch, model_data.closest_midi_note) {
+            (Pitch::Detected(_), Some(note)) => Some(MiniViewReading {
+                note_name: model_data.note_namer().name(note),
+                cents: model_data.cents_offset,
+                in_tune: model_data.cents_offset.abs() <= model_data.tolerance_profile.cents(),
+            }),
+            _ => None,
+        };
+        crate::web::picture_in_picture::update_mini_view(reading);
+    }
+
+    /// Show the target note, from the MIDI keyboard or the running exercise,
+    /// and the note closest to the detected pitch
+    fn update_staff(&mut self, viewport: Viewport, model_data: &ModelUpdateResult) {
+        let Some(renderer) = &mut self.renderer else {
+            return;
+        };
+        let Some(clef) = self.staff_clef else {
+            renderer.update_staff(None, viewport);
+            return;
+        };
+        let namer = model_data.note_namer();
+        let target = model_data.target_note
+            .or(model_data.exercise.as_ref().and_then(|exercise| exercise.current_target))
+            .map(|note| StaffNote::new(&namer, note, clef));
+        let detected = model_data.closest_midi_note
+            .filter(|_| matches!(model_data.pitch, Pitch::Detected(_)))
+            .map(|note| StaffNote::new(&namer, note, clef));
+        let in_tune = model_data.cents_offset.abs() < model_data.tolerance_profile.cents();
+        renderer.update_staff(Some((clef, target, detected, in_tune)), viewport);
+    }
+
     /// Update the presentation layer with model data
     fn process_data(&mut self, model_data: &ModelUpdateResult) {
         self.process_tuning_system(&model_data.tuning_system);
         self.sync_sidebar_ui(model_data);
         
-        self.interval_position = self.calculate_interval_position_from_frequency(&model_data.pitch, model_data.tonal_center_note);
+        self.update_interval_axis(model_data);
+        self.interval_position = self.calculate_interval_position_from_frequency(&model_data.pitch, model_data.tonal_center_note, model_data.concert_pitch_hz, model_data.interval_mode);
+        self.process_midi_events(model_data);
+        self.process_lifecycle(model_data.lifecycle);
+        self.process_recovery_events(&model_data.recovery_events);
+        self.process_errors(&model_data.errors);
+        if let Some(export) = &model_data.session_export {
+            Self::download_session_export(export);
+        }
+        if self.session_summary_requested {
+            crate::web::session_summary::show_session_summary(&model_data.session_statistics);
+            self.session_summary_requested = false;
+        }
+    }
+
+    fn download_session_export(export: &SessionExport) {
+        match crate::web::download::download_bytes(export.format.filename(), export.format.mime_type(), export.contents.as_bytes()) {
+            Ok(()) => {
+                crate::common::dev_log!("Exported {} bytes to {}", export.contents.len(), export.format.filename());
+            }
+            Err(e) => {
+                crate::common::error_log!("Failed to export session: {}", e);
+            }
+        }
+    }
+
+    /// Cover the display while no audio flows, so it does not look frozen
+    fn process_lifecycle(&mut self, lifecycle: AudioLifecycleState) {
+        if lifecycle == self.lifecycle {
+            return;
+        }
+        self.lifecycle = lifecycle;
+        match lifecycle {
+            AudioLifecycleState::Running => crate::web::utils::hide_audio_paused_overlay(),
+            AudioLifecycleState::Hidden => crate::web::utils::show_audio_paused_overlay(translate("audio-paused-background")),
+            AudioLifecycleState::Suspended => crate::web::utils::show_audio_paused_overlay(translate("audio-paused-suspended")),
+            AudioLifecycleState::Closed => crate::web::utils::show_audio_paused_overlay(translate("audio-stopped")),
+        }
+    }
+
+    /// Show fatal errors over the app and other errors as toasts, and let
+    /// toasts shown long enough go
+    pub fn process_errors(&mut self, errors: &[Error]) {
+        for change in self.notifications.update(errors, js_sys::Date::now()) {
+            match change {
+                NotificationChange::ShowFatal(error) => crate::web::notifications::show_fatal_error(&error),
+                NotificationChange::ShowToast(toast) => crate::web::notifications::show_toast(toast.id, &toast.error),
+                NotificationChange::RemoveToast(id) => crate::web::notifications::remove_toast(id),
+            }
+        }
+    }
+
+    /// Cover the display while the engine rebuilds the audio graph
+    fn process_recovery_events(&mut self, events: &[RecoveryEvent]) {
+        for event in events {
+            crate::common::dev_log!("Audio recovery: {:?}", event);
+            match event {
+                RecoveryEvent::Detected { reason } => {
+                    let message = match reason {
+                        RecoveryReason::WorkletStalled => translate("audio-worklet-stalled"),
+                        RecoveryReason::InputEnded => translate("audio-input-ended"),
+                    };
+                    crate::web::utils::show_audio_paused_overlay(message);
+                }
+                RecoveryEvent::RestartFailed { attempt, .. } => {
+                    crate::web::utils::show_audio_paused_overlay(&translate_with("audio-restart-failed", &[&(attempt + 1).to_string()]));
+                }
+                RecoveryEvent::Restarted { .. } if self.lifecycle == AudioLifecycleState::Running => {
+                    crate::web::utils::hide_audio_paused_overlay();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Run the shortcuts of keys pressed on the canvas and select notes
+    /// clicked on the piano keyboard; events taken by the debug GUI and key
+    /// presses with ctrl, alt or command are left alone
+    pub fn process_input_events(&mut self, events: &[three_d::Event], model_data: &ModelUpdateResult) {
+        for event in events {
+            if self.process_splitter_drag(event) {
+                continue;
+            }
+            if let three_d::Event::MousePress { button: three_d::MouseButton::Left, position, handled: false, .. } = event {
+                self.process_piano_click(position.x, position.y, model_data);
+                continue;
+            }
+            let three_d::Event::KeyPress { kind, modifiers, handled: false } = event else {
+                continue;
+            };
+            if modifiers.ctrl || modifiers.alt || modifiers.command {
+                continue;
+            }
+            let Some(shortcut) = self.key_bindings.shortcut(&format!("{:?}", kind)) else {
+                continue;
+            };
+            let tonal_center_note = model_data.tonal_center_note;
+            let new_tonal_center_note = match shortcut {
+                Shortcut::TonalCenterUp => increment_midi_note(tonal_center_note),
+                Shortcut::TonalCenterDown => decrement_midi_note(tonal_center_note),
+                Shortcut::TonalCenterOctaveUp => shift_midi_note_octaves(tonal_center_note, 1),
+                Shortcut::TonalCenterOctaveDown => shift_midi_note_octaves(tonal_center_note, -1),
+                Shortcut::ToggleDrone => {
+                    let amplitude = crate::web::sidebar_controls::toggle_tonal_center_mute();
+                    self.on_tonal_center_configured(true, tonal_center_note, amplitude);
+                    None
+                }
+                Shortcut::SelectScale(scale) => {
+                    self.on_scale_changed(scale);
+                    None
+                }
+            };
+            if let Some(note) = new_tonal_center_note {
+                self.on_tonal_center_configured(true, note, current_tonal_center_amplitude());
+            }
+        }
+    }
+
+    /// Grab a splitter between the panes, move it with the pointer and store
+    /// the layout on release; returns whether the event was taken
+    fn process_splitter_drag(&mut self, event: &three_d::Event) -> bool {
+        let Some(viewport) = self.current_viewport else {
+            return false;
+        };
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        match (event, self.dragged_splitter) {
+            (three_d::Event::MousePress { button: three_d::MouseButton::Left, position, handled: false, .. }, _) => {
+                self.dragged_splitter = self.pane_layout.splitter_at(position.x, position.y, width, height);
+                self.dragged_splitter.is_some()
+            }
+            (three_d::Event::MouseMotion { position, .. }, Some(splitter)) => {
+                self.pane_layout.drag(splitter, position.x, position.y, width, height);
+                true
+            }
+            (three_d::Event::MouseMotion { position, .. }, None) => {
+                crate::web::layout::set_splitter_cursor(self.pane_layout.splitter_at(position.x, position.y, width, height));
+                false
+            }
+            (three_d::Event::MouseRelease { button: three_d::MouseButton::Left, .. }, Some(_)) => {
+                self.dragged_splitter = None;
+                let pane_layout = self.pane_layout.clone();
+                crate::web::storage::update_settings(|settings| settings.pane_layout = Some(pane_layout));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Canvas area of the main display
+    fn display_viewport(&self, viewport: Viewport) -> Viewport {
+        self.renderer.as_ref()
+            .and_then(|renderer| renderer.pane_viewport(PaneKind::Display))
+            .unwrap_or(viewport)
+    }
+
+    /// A clicked key becomes the tonal center, or the target note; clicking
+    /// the target note again clears it
+    fn process_piano_click(&mut self, x: f32, y: f32, model_data: &ModelUpdateResult) {
+        let Some(mode) = self.piano_mode else {
+            return;
+        };
+        let Some(note) = self.renderer.as_ref().and_then(|renderer| renderer.piano_note_at(x, y)) else {
+            return;
+        };
+        match mode {
+            MidiInputMode::TonalCenter => self.on_tonal_center_configured(true, note, current_tonal_center_amplitude()),
+            MidiInputMode::TargetPitch => {
+                let target_note = (model_data.target_note != Some(note)).then_some(note);
+                self.on_target_note_changed(target_note);
+            }
+        }
+    }
+
+    /// Turn MIDI keys into tonal center or target note changes, depending on the input mode
+    fn process_midi_events(&mut self, model_data: &ModelUpdateResult) {
+        let mut target_note = model_data.target_note;
+        for event in &model_data.midi_events {
+            match (self.midi_input_mode, *event) {
+                (MidiInputMode::TonalCenter, MidiNoteEvent::NoteOn { note, .. }) => {
+                    self.on_tonal_center_configured(true, note, current_tonal_center_amplitude());
+                }
+                (MidiInputMode::TargetPitch, MidiNoteEvent::NoteOn { note, .. }) => {
+                    target_note = Some(note);
+                    self.on_target_note_changed(target_note);
+                }
+                (MidiInputMode::TargetPitch, MidiNoteEvent::NoteOff { note }) if target_note == Some(note) => {
+                    target_note = None;
+                    self.on_target_note_changed(target_note);
+                }
+                _ => {}
+            }
+        }
     }
 
     /// Retrieve and clear all pending user actions
@@ -174,11 +597,13 @@ impl Presenter {
 
     /// Handle user request to change the tuning system
     pub fn on_tuning_system_changed(&mut self, tuning_system: TuningSystem) {
+        crate::web::telemetry::record(TelemetryEvent::tuning_system(&tuning_system));
         self.pending_user_actions.tuning_system_change = Some(ChangeTuningSystem { tuning_system });
     }
 
     /// Handle scale change action
     pub fn on_scale_changed(&mut self, scale: Scale) {
+        crate::web::telemetry::record(TelemetryEvent::scale(scale));
         self.pending_user_actions.scale_change = Some(ScaleChangeAction { scale });
     }
 
@@ -186,18 +611,301 @@ impl Presenter {
         self.display_range = display_range;
     }
 
+    pub fn on_interval_zoom_changed(&mut self, zoom: IntervalZoom) {
+        self.interval_zoom = zoom;
+    }
+
+    pub fn on_display_scene_changed(&mut self, scene: crate::common::shared_types::DisplayScene) {
+        self.display_scene = scene;
+    }
+
+    pub fn on_needle_damping_changed(&mut self, damping: crate::common::shared_types::NeedleDamping) {
+        self.needle_damping = damping;
+    }
+
+    /// Show or hide the input level and detection clarity meters
+    pub fn on_input_meters_toggled(&mut self, shown: bool) {
+        if shown != self.level_meter.is_some() {
+            self.level_meter = shown.then(LevelMeter::default);
+        }
+    }
+
+    /// Start or stop announcing pitch readings to screen readers
+    pub fn on_pitch_announcements_toggled(&mut self, enabled: bool) {
+        if enabled != self.pitch_announcer.is_some() {
+            self.pitch_announcer = enabled.then(PitchAnnouncer::default);
+        }
+    }
+
+    /// Show or hide the previous exercise attempt behind the pitch graph
+    pub fn on_ghost_trail_toggled(&mut self, shown: bool) {
+        self.ghost_trail_shown = shown;
+    }
+
+    /// Show the piano keyboard selecting notes for `mode`, or hide it with
+    /// `None`; a target note chosen on it is cleared when it stops selecting targets
+    pub fn on_piano_mode_changed(&mut self, mode: Option<MidiInputMode>) {
+        if self.piano_mode == Some(MidiInputMode::TargetPitch) && mode != self.piano_mode && self.midi_input_mode != MidiInputMode::TargetPitch {
+            self.on_target_note_changed(None);
+        }
+        self.piano_mode = mode;
+    }
+
+    pub fn key_bindings(&self) -> &KeyBindings {
+        &self.key_bindings
+    }
+
+    /// Bind `key` to `shortcut`, taking it from any other shortcut
+    pub fn on_shortcut_bound(&mut self, shortcut: Shortcut, key: String) {
+        self.key_bindings.bind(shortcut, key);
+    }
+
+    /// Show the staff in `clef`, or hide it with `None`
+    pub fn on_staff_clef_changed(&mut self, clef: Option<crate::common::shared_types::Clef>) {
+        self.staff_clef = clef;
+    }
+
+    /// Show a view in a pane beside the display, or remove its pane
+    pub fn on_pane_toggled(&mut self, pane: PaneKind, shown: bool) {
+        self.pane_layout.set_shown(pane, shown);
+        let pane_layout = self.pane_layout.clone();
+        crate::web::storage::update_settings(|settings| settings.pane_layout = Some(pane_layout));
+    }
+
+    /// Show or hide the spectrogram band, shading the loudest `dynamic_range_db`
+    /// in the band and in the spectrogram pane
+    pub fn on_spectrogram_configured(&mut self, shown: bool, dynamic_range_db: f32) {
+        self.spectrogram_shown = shown;
+        self.spectrogram_dynamic_range_db = dynamic_range_db;
+    }
+
+    pub fn on_midi_input_mode_changed(&mut self, mode: MidiInputMode) {
+        self.midi_input_mode = mode;
+        if mode != MidiInputMode::TargetPitch {
+            self.on_target_note_changed(None);
+        }
+    }
+
+    pub fn midi_input_mode(&self) -> MidiInputMode {
+        self.midi_input_mode
+    }
+
+    /// Connect to MIDI devices, which stay untouched until the user asks for them
+    pub fn on_midi_enabled(&mut self) {
+        self.pending_user_actions.enable_midi = true;
+    }
+
+    pub fn on_calibration_control(&mut self, control: CalibrationControl) {
+        self.pending_user_actions.calibration_control = Some(control);
+    }
+
+    /// Handle a user gesture that may resume audio the browser suspended
+    pub fn on_audio_resume_requested(&mut self) {
+        self.pending_user_actions.resume_audio = true;
+    }
+
+    pub fn on_batch_size_configured(&mut self, mode: BatchSizeMode) {
+        self.pending_user_actions.batch_size_configuration = Some(ConfigureBatchSize { mode });
+    }
+
+    pub fn on_peak_measure_configured(&mut self, measure: PeakMeasure) {
+        self.pending_user_actions.peak_measure_configuration = Some(ConfigurePeakMeasure { measure });
+    }
+
+    pub fn on_interval_mode_configured(&mut self, mode: IntervalMode) {
+        self.pending_user_actions.interval_mode_configuration = Some(ConfigureIntervalMode { mode });
+    }
+
+    pub fn on_smoothing_configured(&mut self, strategy: SmoothingStrategy) {
+        self.pending_user_actions.smoothing_configuration = Some(ConfigureSmoothing { strategy });
+    }
+
+    pub fn on_transposition_configured(&mut self, transposition: Transposition) {
+        self.pending_user_actions.transposition_configuration = Some(ConfigureTransposition { transposition });
+    }
+
+    pub fn on_note_naming_configured(&mut self, naming: NoteNaming) {
+        self.pending_user_actions.note_naming_configuration = Some(ConfigureNoteNaming { naming });
+    }
+
+    pub fn on_tolerance_profile_configured(&mut self, profile: ToleranceProfile) {
+        self.pending_user_actions.tolerance_profile_configuration = Some(ConfigureToleranceProfile { profile });
+    }
+
+    pub fn on_session_statistics_reset(&mut self) {
+        self.pending_user_actions.reset_session_statistics = true;
+    }
+
+    /// Let the display fill the window and hide the sidebar while the
+    /// pointer rests, or go back to the normal layout
+    pub fn on_immersive_layout_toggled(&mut self, immersive: bool) {
+        if immersive {
+            crate::web::telemetry::record(TelemetryEvent::FeatureUsed("immersive-layout"));
+        }
+        self.immersive_layout = immersive;
+        crate::web::layout::set_immersive(immersive);
+        self.controls_visible = true;
+        crate::web::layout::set_controls_visible(true);
+    }
+
+    fn update_controls_visibility(&mut self) {
+        if !self.immersive_layout {
+            return;
+        }
+        let visible = self.tutorial.is_some_and(|tutorial| tutorial.step().in_sidebar())
+            || js_sys::Date::now() - crate::web::layout::last_pointer_move_ms() < crate::app_config::IMMERSIVE_CONTROLS_HIDE_MS;
+        if visible != self.controls_visible {
+            self.controls_visible = visible;
+            crate::web::layout::set_controls_visible(visible);
+        }
+    }
+
+    /// Start the tutorial from its first step
+    pub fn on_tutorial_requested(&mut self) {
+        crate::web::telemetry::record(TelemetryEvent::FeatureUsed("tutorial"));
+        self.show_tutorial(Some(Tutorial::default()));
+    }
+
+    pub fn on_tutorial_next(&mut self) {
+        self.show_tutorial(self.tutorial.and_then(Tutorial::next));
+    }
+
+    pub fn on_tutorial_back(&mut self) {
+        self.show_tutorial(self.tutorial.map(Tutorial::back));
+    }
+
+    pub fn on_tutorial_skipped(&mut self) {
+        self.show_tutorial(None);
+    }
+
+    /// Show a step of the tutorial, or end it with `None`; once ended, it is
+    /// not started again on the next start of the app
+    fn show_tutorial(&mut self, tutorial: Option<Tutorial>) {
+        let was_running = self.tutorial.is_some();
+        self.tutorial = tutorial;
+        match tutorial {
+            Some(tutorial) => {
+                self.update_controls_visibility();
+                crate::web::tutorial::show_tutorial_step(tutorial);
+            }
+            None if was_running => {
+                crate::web::tutorial::hide_tutorial();
+                crate::web::storage::update_settings(|settings| settings.tutorial_seen = true);
+            }
+            None => (),
+        }
+    }
+
+    /// Show the summary of the session so far; when `end_session` is set,
+    /// closing the summary resets the statistics for a new session
+    pub fn on_session_summary_requested(&mut self, end_session: bool) {
+        self.session_summary_requested = true;
+        self.session_summary_ends_session |= end_session;
+    }
+
+    pub fn on_toast_dismissed(&mut self, id: u32) {
+        if self.notifications.dismiss(id) {
+            crate::web::notifications::remove_toast(id);
+        }
+    }
+
+    pub fn on_session_summary_closed(&mut self) {
+        crate::web::session_summary::hide_session_summary();
+        if self.session_summary_ends_session {
+            self.on_session_statistics_reset();
+            self.session_summary_ends_session = false;
+        }
+    }
+
+    /// Share or download an image of the display with the session statistics
+    pub fn on_share_image_requested(&mut self) {
+        crate::web::telemetry::record(TelemetryEvent::FeatureUsed("share-image"));
+        self.share_image_requested = true;
+    }
+
+    pub fn on_session_export_requested(&mut self, format: SessionExportFormat) {
+        crate::web::telemetry::record(TelemetryEvent::FeatureUsed("session-export"));
+        self.pending_user_actions.session_export = Some(ExportSession { format });
+    }
+
+    pub fn on_session_save_requested(&mut self) {
+        crate::web::telemetry::record(TelemetryEvent::FeatureUsed("session-save"));
+        self.pending_user_actions.session_save = true;
+    }
+
+    pub fn on_snapshot_restore(&mut self, snapshot: ModelSnapshot) {
+        self.pending_user_actions.snapshot_restore = Some(RestoreSnapshot { snapshot });
+    }
+
+    /// Apply the settings of a link, and start its exercise
+    pub fn on_deep_link(&mut self, link: DeepLink) {
+        if let Some(note) = link.tonal_center_note {
+            self.on_tonal_center_configured(true, note, current_tonal_center_amplitude());
+        }
+        if let Some(scale) = link.scale {
+            self.on_scale_changed(scale);
+        }
+        if let Some(tuning_system) = link.tuning_system {
+            self.on_tuning_system_changed(tuning_system);
+        }
+        if let Some(kind) = link.exercise {
+            self.on_exercise_control(ExerciseControl::Start(kind));
+        }
+    }
+
+    /// Take the tuning of the controller tab, for a tab acting as its display.
+    /// Unlike the user's own selections it is not counted as usage.
+    pub fn on_controller_tuning(&mut self, tuning: crate::common::settings::TuningSettings) {
+        self.on_tonal_center_configured(true, tuning.tonal_center_note, current_tonal_center_amplitude());
+        self.pending_user_actions.scale_change = Some(ScaleChangeAction { scale: tuning.scale });
+        self.pending_user_actions.tuning_system_change = Some(ChangeTuningSystem { tuning_system: tuning.tuning_system });
+        self.on_concert_pitch_configured(tuning.concert_pitch_hz);
+        self.on_transposition_configured(tuning.transposition);
+        self.on_note_naming_configured(tuning.note_naming);
+        self.on_tolerance_profile_configured(tuning.tolerance_profile);
+        crate::web::sidebar_controls::set_display_range(tuning.display_range.clone());
+        self.on_display_range_changed(tuning.display_range);
+    }
+
+    pub fn on_exercise_control(&mut self, control: ExerciseControl) {
+        if matches!(control, ExerciseControl::Start(_)) {
+            crate::web::telemetry::record(TelemetryEvent::FeatureUsed("exercise"));
+        }
+        self.pending_user_actions.exercise_control = Some(control);
+    }
+
+    /// Start the metronome with new settings, from a beat, or stop it with `None`
+    pub fn on_metronome_configured(&mut self, settings: Option<MetronomeSettings>) {
+        if settings.is_some() {
+            crate::web::telemetry::record(TelemetryEvent::FeatureUsed("metronome"));
+        }
+        self.pending_user_actions.metronome_configuration = Some(ConfigureMetronome { settings });
+    }
+
+    pub fn on_reference_track_control(&mut self, control: ReferenceTrackControl) {
+        self.pending_user_actions.reference_track_control = Some(control);
+    }
+
+    pub fn on_automatic_gain_configured(&mut self, enabled: bool) {
+        self.pending_user_actions.automatic_gain_configuration = Some(ConfigureAutomaticGain { enabled });
+    }
+
+    pub fn on_midi_output_configured(&mut self, output_id: Option<String>, source: MidiOutputSource) {
+        self.pending_user_actions.midi_output_configuration = Some(ConfigureMidiOutput { output_id, source });
+    }
+
+    pub fn on_target_note_changed(&mut self, note: Option<MidiNote>) {
+        self.pending_user_actions.target_note = Some(SetTargetNote { note });
+    }
+
     #[cfg(debug_assertions)]
     pub fn get_debug_actions(&mut self) -> DebugLayerActions {
         std::mem::take(&mut self.pending_debug_actions)
     }
 
     #[cfg(debug_assertions)]
-    pub fn on_test_signal_configured(&mut self, enabled: bool, frequency: f32, volume: f32) {
-        self.pending_debug_actions.test_signal_configuration = Some(ConfigureTestSignal {
-            enabled,
-            frequency,
-            volume,
-        });
+    pub fn on_test_signal_configured(&mut self, config: ConfigureTestSignal) {
+        self.pending_debug_actions.test_signal_configuration = Some(config);
     }
     pub fn on_tonal_center_configured(&mut self, _enabled: bool, note: MidiNote, volume_amplitude: f32) {
         crate::common::dev_log!("PRESENTER: Tonal center audio configured - tonal_center: {}, volume: {}", 
@@ -209,8 +917,74 @@ impl Presenter {
         });
         crate::common::dev_log!("PRESENTER: Set tonal center configuration action");
     }
+
+    pub fn on_concert_pitch_configured(&mut self, frequency_hz: f32) {
+        self.pending_user_actions.concert_pitch_configuration = Some(ConfigureConcertPitch { frequency_hz });
+    }
     
 
+    pub fn on_pitch_detection_configured(&mut self, algorithm: PitchAlgorithm, power_threshold: f32, clarity_threshold: f32) {
+        self.pending_user_actions.pitch_detection_configuration = Some(ConfigurePitchDetection {
+            algorithm,
+            power_threshold,
+            clarity_threshold,
+        });
+    }
+
+    pub fn on_reference_tone_configured(&mut self, settings: ReferenceToneSettings) {
+        self.pending_user_actions.reference_tone_configuration = Some(ConfigureReferenceTone { settings });
+    }
+
+    /// Play the tone `semitones` above (or below) the tonal center
+    pub fn on_reference_interval_requested(&mut self, semitones: i32) {
+        self.pending_user_actions.reference_tone_playback = Some(PlayReferenceTone {
+            target: ReferenceToneTarget::Interval(semitones),
+        });
+    }
+
+    /// Play a 1-based degree of the current scale
+    pub fn on_scale_degree_requested(&mut self, degree: usize) {
+        self.pending_user_actions.reference_tone_playback = Some(PlayReferenceTone {
+            target: ReferenceToneTarget::ScaleDegree(degree),
+        });
+    }
+
+    pub fn on_spectrum_configured(&mut self, bin_count: usize) {
+        self.pending_user_actions.spectrum_configuration = Some(ConfigureSpectrum { bin_count });
+    }
+
+    pub fn on_recording_control(&mut self, control: RecordingControl) {
+        if control == RecordingControl::Start {
+            crate::web::telemetry::record(TelemetryEvent::FeatureUsed("recording"));
+        }
+        self.pending_user_actions.recording_control = Some(control);
+    }
+
+    /// Count in before starting `target`, replacing a count-in already running
+    pub fn on_count_in_started(&mut self, target: CountInTarget) {
+        let countdown = Countdown::new(js_sys::Date::now(), crate::app_config::COUNT_IN_BEATS, crate::app_config::COUNT_IN_BEAT_MS);
+        self.count_in = Some((countdown, target));
+    }
+
+    pub fn on_count_in_cancelled(&mut self) {
+        self.count_in = None;
+    }
+
+    pub fn on_input_conditioning_configured(&mut self, config: InputConditioning) {
+        self.pending_user_actions.input_conditioning_configuration = Some(ConfigureInputConditioning { config });
+    }
+
+    pub fn on_input_device_selected(&mut self, device_id: String) {
+        self.pending_user_actions.input_device_selection = Some(SelectInputDevice { device_id });
+    }
+
+    pub fn on_analysis_window_configured(&mut self, window_size: usize, hop_size: usize) {
+        self.pending_user_actions.analysis_window_configuration = Some(ConfigureAnalysisWindow {
+            window_size,
+            hop_size,
+        });
+    }
+
     /// Render the presentation layer to the screen
     pub fn render(&mut self, context: &Context, screen: &mut RenderTarget, model_data: &ModelUpdateResult) {
         if self.renderer.is_none() {
@@ -223,20 +997,30 @@ impl Presenter {
                 }
             };
             
-            self.renderer = Some(Box::new(renderer));
-            self.update_graphics(screen.viewport(), model_data);
+            let mut renderer = Box::new(renderer);
+            renderer.set_pane_layout(&self.pane_layout, screen.viewport());
+            self.renderer = Some(renderer);
+            self.update_graphics(self.display_viewport(screen.viewport()), model_data);
             
             self.sync_sidebar_ui(model_data);
         }
         
         let viewport = screen.viewport();
         self.current_viewport = Some(viewport);
+        if let Some(renderer) = &mut self.renderer {
+            renderer.set_pane_layout(&self.pane_layout, viewport);
+        }
 
         if self.renderer.is_some() {
-            self.update_graphics(viewport, model_data);
+            self.update_graphics(self.display_viewport(viewport), model_data);
         }
         
-        if let Some(renderer) = &mut self.renderer {
+        if let Some(renderer) = &mut self.renderer
+            && std::mem::take(&mut self.share_image_requested)
+        {
+            let pixels = renderer.render_and_capture(screen, viewport);
+            crate::web::share_image::share_image(&pixels, viewport.width, viewport.height, &model_data.session_statistics);
+        } else if let Some(renderer) = &mut self.renderer {
             crate::profile!("renderer_render", renderer.render(screen, viewport));
         } else {
             screen.clear(three_d::ClearState::color(0.0, 0.0, 0.0, 1.0));
@@ -246,33 +1030,55 @@ impl Presenter {
     fn process_tuning_system(&mut self, _tuning_system: &crate::common::shared_types::TuningSystem) {
     }
     
-    /// Calculate interval position from frequency and tonal center
-    fn calculate_interval_position_from_frequency(&self, pitch: &Pitch, note: MidiNote) -> f32 {
+    /// Calculate interval position from frequency and tonal center; wrapped
+    /// intervals are moved by whole octaves towards the middle of the display,
+    /// or towards the note zoomed in on
+    fn calculate_interval_position_from_frequency(&self, pitch: &Pitch, note: MidiNote, concert_pitch_hz: f32, interval_mode: IntervalMode) -> f32 {
         match pitch {
             Pitch::Detected(frequency) => {
-                let tonal_center_frequency = Self::midi_note_to_frequency(note);
-                (frequency / tonal_center_frequency).log2()
+                let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(note, concert_pitch_hz);
+                let octaves = (frequency / tonal_center_frequency).log2();
+                match interval_mode {
+                    IntervalMode::Absolute => octaves,
+                    IntervalMode::Wrapped if self.interval_zoom == IntervalZoom::Full => wrap_octaves(octaves, self.display_range.center_octaves()),
+                    IntervalMode::Wrapped => wrap_octaves(octaves, self.interval_axis.target().center_octaves),
+                }
             }
             Pitch::NotDetected => 0.0,
         }
     }
-    
-    fn midi_note_to_frequency(midi_note: MidiNote) -> f32 {
-        crate::common::music_theory::midi_note_to_standard_frequency(midi_note)
+
+    /// Zoom towards the display range, or around the target note, else the
+    /// closest note; without either the zoom stays where it is
+    fn update_interval_axis(&mut self, model_data: &ModelUpdateResult) {
+        let focus_octaves = match model_data.target_note.or(model_data.closest_midi_note) {
+            Some(focus_note) => {
+                let octaves = (focus_note as i32 - model_data.tonal_center_note as i32) as f32 / 12.0;
+                match model_data.interval_mode {
+                    IntervalMode::Absolute => octaves,
+                    IntervalMode::Wrapped => wrap_octaves(octaves, self.display_range.center_octaves()),
+                }
+            }
+            None => self.interval_axis.target().center_octaves,
+        };
+        let axis = IntervalAxis::zoomed(&self.display_range, self.interval_zoom, focus_octaves);
+        self.interval_axis.animate_to(axis, js_sys::Date::now(), crate::app_config::INTERVAL_ZOOM_MS, Easing::EaseInOutCubic);
     }
+    
     pub fn midi_note_to_frequency_with_tuning(
         &self,
         midi_note: MidiNote,
         note: MidiNote,
-        tuning_system: TuningSystem,
+        concert_pitch_hz: f32,
+        tuning_system: &TuningSystem,
     ) -> f32 {
-        let tonal_center_frequency = crate::common::music_theory::midi_note_to_standard_frequency(note);
+        let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(note, concert_pitch_hz);
         let interval_semitones = (midi_note as i32) - (note as i32);
         crate::common::music_theory::interval_frequency(tuning_system, tonal_center_frequency, interval_semitones)
     }
 
     fn sync_sidebar_ui(&self, model_data: &ModelUpdateResult) {
-        crate::web::sidebar_controls::sync_sidebar_with_presenter_state(model_data);
+        crate::web::sidebar_controls::sync_sidebar_with_presenter_state(model_data, self.count_in.map(|(_, target)| target));
     }
     
     fn cleanup_sidebar_ui_if_active(&mut self) {
@@ -283,15 +1089,34 @@ impl Presenter {
     }
 
     pub fn refresh_color_scheme(&mut self) {
-        if let (Some(renderer), Some(viewport)) = (self.renderer.as_mut(), self.current_viewport) {
+        let Some(viewport) = self.current_viewport.map(|viewport| self.display_viewport(viewport)) else {
+            return;
+        };
+        if let Some(renderer) = self.renderer.as_mut() {
             renderer.refresh_color_scheme(viewport);
         }
     }
 }
 
+#[cfg(target_arch = "wasm32")]
 impl Drop for Presenter {
     fn drop(&mut self) {
         self.cleanup_sidebar_ui_if_active();
     }
 }
 
+
+#[cfg(target_arch = "wasm32")]
+impl crate::debug::inspector::Inspect for Presenter {
+    fn inspect(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("renderer", if self.renderer.is_some() { "created" } else { "not created" }.to_string()),
+            ("viewport", self.current_viewport.map_or("-".to_string(), |v| format!("{}x{} at ({}, {})", v.width, v.height, v.x, v.y))),
+            ("display_range", format!("{:?}", self.display_range)),
+            ("interval_zoom", format!("{:?}", self.interval_zoom)),
+            ("interval_position", format!("{:.3}", self.interval_position)),
+            ("sidebar_ui_active", self.sidebar_ui_active.to_string()),
+            ("pending_user_actions", self.pending_user_actions.has_actions().to_string()),
+        ]
+    }
+}
@@ -0,0 +1,134 @@
+use three_d::{Circle, ColorMaterial, Context, Deg, Gm, Line, Object, PhysicalPoint, Rectangle, Viewport};
+use crate::app_config::NEEDLE_RANGE_CENTS;
+use crate::common::shared_types::ColorScheme;
+use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::materials::solid_material;
+
+/// Angle of the needle from upright at either end of the scale
+const SWEEP_DEGREES: f32 = 50.0;
+const TICK_STEP_CENTS: f32 = 10.0;
+/// Tick lengths as parts of the scale radius, for the center and ends and for the rest
+const MAJOR_TICK_LENGTH: f32 = 0.12;
+const MINOR_TICK_LENGTH: f32 = 0.06;
+const TICK_THICKNESS: f32 = 2.0;
+const NEEDLE_THICKNESS: f32 = 3.0;
+const PIVOT_RADIUS: f32 = 8.0;
+/// Thickness of the arc marking the in-tune zone, and the lines it is made of
+const ZONE_THICKNESS: f32 = 10.0;
+const ZONE_SEGMENT_COUNT: usize = 8;
+const LABEL_SIZE: f32 = 16.0;
+
+/// Everything the labels depend on
+#[derive(Clone, Copy, PartialEq)]
+struct LabelLayout {
+    color: [f32; 3],
+    viewport: Viewport,
+}
+
+/// Analog tuner scale from flat to sharp with a swinging needle
+pub struct NeedleView {
+    background: Gm<Rectangle, ColorMaterial>,
+    zone: Vec<Gm<Line, ColorMaterial>>,
+    ticks: Vec<Gm<Line, ColorMaterial>>,
+    needle: Gm<Line, ColorMaterial>,
+    pivot: Gm<Circle, ColorMaterial>,
+    /// Cents labels, rendered again only when their layout changes
+    labels: Vec<Box<dyn Object>>,
+    label_layout: Option<LabelLayout>,
+}
+
+impl NeedleView {
+    pub fn new(context: &Context) -> Self {
+        let origin = PhysicalPoint { x: 0.0, y: 0.0 };
+        let line = |thickness| Gm::new(Line::new(context, origin, origin, thickness), ColorMaterial::default());
+        let tick_count = (2.0 * NEEDLE_RANGE_CENTS / TICK_STEP_CENTS) as usize + 1;
+        Self {
+            background: Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default()),
+            zone: (0..ZONE_SEGMENT_COUNT).map(|_| line(ZONE_THICKNESS)).collect(),
+            ticks: (0..tick_count).map(|_| line(TICK_THICKNESS)).collect(),
+            needle: line(NEEDLE_THICKNESS),
+            pivot: Gm::new(Circle::new(context, (0.0, 0.0), PIVOT_RADIUS), ColorMaterial::default()),
+            labels: Vec::new(),
+            label_layout: None,
+        }
+    }
+
+    /// Lay out the scale and point the needle at `position_cents`; offsets
+    /// within `tolerance_cents` are marked and count as in tune
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &Context,
+        text_backend: &mut EguiTextBackend,
+        position_cents: f32,
+        tolerance_cents: f32,
+        pitch_detected: bool,
+        scheme: &ColorScheme,
+        viewport: Viewport,
+    ) {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.background.set_center((width * 0.5, height * 0.5));
+        self.background.set_size(width, height);
+        self.background.material = solid_material(scheme.background, 1.0);
+
+        let pivot = (width * 0.5, height * 0.2);
+        let radius = (width * 0.4).min(height * 0.65);
+        let point = |cents: f32, distance: f32| {
+            let angle = (cents / NEEDLE_RANGE_CENTS * SWEEP_DEGREES).to_radians();
+            PhysicalPoint { x: pivot.0 + distance * angle.sin(), y: pivot.1 + distance * angle.cos() }
+        };
+
+        let zone_cents = tolerance_cents.min(NEEDLE_RANGE_CENTS);
+        for (segment, line) in self.zone.iter_mut().enumerate() {
+            let cents = |segment: usize| -zone_cents + 2.0 * zone_cents * segment as f32 / ZONE_SEGMENT_COUNT as f32;
+            line.set_endpoints(point(cents(segment), radius), point(cents(segment + 1), radius));
+            line.material = solid_material(scheme.secondary, 1.0);
+        }
+
+        for (index, tick) in self.ticks.iter_mut().enumerate() {
+            let cents = -NEEDLE_RANGE_CENTS + index as f32 * TICK_STEP_CENTS;
+            let length = if cents == 0.0 || cents.abs() == NEEDLE_RANGE_CENTS { MAJOR_TICK_LENGTH } else { MINOR_TICK_LENGTH };
+            tick.set_endpoints(point(cents, radius * (1.0 - length)), point(cents, radius));
+            tick.material = solid_material(scheme.muted, 1.0);
+        }
+
+        let needle_color = if !pitch_detected {
+            scheme.muted
+        } else if position_cents.abs() <= tolerance_cents {
+            scheme.accent
+        } else {
+            scheme.error
+        };
+        self.needle.set_endpoints(PhysicalPoint { x: pivot.0, y: pivot.1 }, point(position_cents, radius * 0.95));
+        self.needle.material = solid_material(needle_color, 1.0);
+        self.pivot.set_center(pivot);
+        self.pivot.material = solid_material(scheme.text, 1.0);
+
+        let label_layout = LabelLayout { color: scheme.text, viewport };
+        if self.label_layout != Some(label_layout) {
+            let color = [scheme.text[0], scheme.text[1], scheme.text[2], 1.0];
+            // Text is placed by its top edge, so the labels sit just above the scale ends
+            let label_distance = radius + LABEL_SIZE * 1.5;
+            let labels: Vec<_> = [-NEEDLE_RANGE_CENTS, 0.0, NEEDLE_RANGE_CENTS]
+                .into_iter()
+                .map(|cents| {
+                    let position = point(cents, label_distance);
+                    let text = if cents == 0.0 { "0".to_string() } else { format!("{:+}", cents as i32) };
+                    (text, position.x, position.y, LABEL_SIZE, color, false)
+                })
+                .collect();
+            self.labels = text_backend.render_texts(context, viewport, &labels, three_d::egui::Align::Center);
+            self.label_layout = Some(label_layout);
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = vec![&self.background];
+        objects.extend(self.zone.iter().map(|line| line as &dyn Object));
+        objects.extend(self.ticks.iter().map(|line| line as &dyn Object));
+        objects.push(&self.needle);
+        objects.push(&self.pivot);
+        objects.extend(self.labels.iter().map(|label| label.as_ref()));
+        objects
+    }
+}
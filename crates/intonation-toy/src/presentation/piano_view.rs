@@ -0,0 +1,91 @@
+use three_d::{Blend, ColorMaterial, Context, Deg, Gm, Rectangle, RenderStates, Viewport, WriteMask};
+use crate::app_config::{PIANO_FIRST_NOTE, PIANO_HEIGHT_FRACTION, PIANO_KEY_FADE_MS, PIANO_LAST_NOTE};
+use crate::common::piano_keyboard::PianoLayout;
+use crate::common::shared_types::{ColorScheme, MidiNote, Scale};
+use crate::common::theme::rgb_to_srgba_with_alpha;
+use crate::common::tween::{Easing, Tween};
+
+/// Gap between neighbouring white keys in pixels
+const KEY_GAP: f32 = 1.0;
+/// How far keys outside the scale fade towards the muted color
+const OUTSIDE_SCALE_FADE: f32 = 0.6;
+
+/// Piano keyboard along the bottom of the canvas, marking the tonal center,
+/// the target note and the notes of the scale
+pub struct PianoView {
+    layout: PianoLayout,
+    viewport: Viewport,
+    /// One rectangle per key of the layout, in the same order
+    keys: Vec<Gm<Rectangle, ColorMaterial>>,
+    /// Colors of the keys, fading when the tonal center, target or scale changes
+    colors: Vec<Tween<[f32; 3]>>,
+}
+
+impl PianoView {
+    pub fn new(context: &Context, viewport: Viewport) -> Self {
+        let layout = Self::layout(viewport);
+        let keys = layout.keys()
+            .iter()
+            .map(|key| Gm::new(
+                Rectangle::new(context, (key.left + 0.5 * key.width, key.bottom + 0.5 * key.height), Deg(0.0), key.width - KEY_GAP, key.height),
+                ColorMaterial::default(),
+            ))
+            .collect();
+        Self { layout, viewport, keys, colors: Vec::new() }
+    }
+
+    fn layout(viewport: Viewport) -> PianoLayout {
+        let height = viewport.height as f32 * PIANO_HEIGHT_FRACTION;
+        PianoLayout::new(PIANO_FIRST_NOTE, PIANO_LAST_NOTE, 0.0, 0.0, viewport.width as f32, height)
+    }
+
+    pub fn update(&mut self, tonal_center_note: MidiNote, target_note: Option<MidiNote>, scale: Scale, scheme: &ColorScheme, viewport: Viewport, now_ms: f64) {
+        if viewport != self.viewport {
+            self.layout = Self::layout(viewport);
+            self.viewport = viewport;
+            for (quad, key) in self.keys.iter_mut().zip(self.layout.keys()) {
+                quad.set_center((key.left + 0.5 * key.width, key.bottom + 0.5 * key.height));
+                quad.set_size(key.width - KEY_GAP, key.height);
+            }
+        }
+
+        let pattern = scale.pattern();
+        for (index, (quad, key)) in self.keys.iter_mut().zip(self.layout.keys()).enumerate() {
+            let base = if key.black { scheme.background } else { scheme.text };
+            let in_scale = pattern[(key.note as i32 - tonal_center_note as i32).rem_euclid(12) as usize];
+            let color = if key.note == tonal_center_note {
+                scheme.accent
+            } else if Some(key.note) == target_note {
+                scheme.primary
+            } else if in_scale {
+                base
+            } else {
+                std::array::from_fn(|channel| base[channel] + OUTSIDE_SCALE_FADE * (scheme.muted[channel] - base[channel]))
+            };
+            // Keys start in their colors and fade from then on
+            if index == self.colors.len() {
+                self.colors.push(Tween::new(color));
+            }
+            self.colors[index].animate_to(color, now_ms, PIANO_KEY_FADE_MS, Easing::EaseOutCubic);
+            quad.material = ColorMaterial {
+                color: rgb_to_srgba_with_alpha(self.colors[index].value(now_ms), 1.0),
+                texture: None,
+                is_transparent: false,
+                render_states: RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                },
+            };
+        }
+    }
+
+    /// Note of the key under a point on the canvas
+    pub fn note_at(&self, x: f32, y: f32) -> Option<MidiNote> {
+        self.layout.note_at(x, y)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Gm<Rectangle, ColorMaterial>> {
+        self.keys.iter()
+    }
+}
@@ -0,0 +1,105 @@
+use three_d::{Blend, ColorMaterial, Context, Gm, Line, PhysicalPoint, RenderStates, Srgba, WriteMask};
+use crate::app_config::{GHOST_TRAIL_ALPHA, PITCH_GRAPH_LINE_THICKNESS, PITCH_TRAIL_POINTS, PITCH_TRAIL_WINDOW_MS};
+use crate::common::pitch_history::PitchSample;
+use crate::common::theme::rgb_to_srgba_with_alpha;
+
+/// Samples further apart than this many trail slots are not joined, so pauses show as gaps
+const MAX_JOINED_SLOTS: f64 = 3.0;
+
+/// Recent pitch as a curve scrolling to the left, newest at the right edge
+#[derive(Default)]
+pub struct PitchGraph {
+    /// Segments are reused between frames; only the first `visible` are drawn
+    segments: Vec<Gm<Line, ColorMaterial>>,
+    visible: usize,
+}
+
+impl PitchGraph {
+    /// Lay out `trail` between `left` and `right`, with `to_y` placing a
+    /// frequency on screen; segments are colored by how far their cents
+    /// deviation lies outside the tolerance, from `in_tune_color` within it
+    /// to `off_color` at three times the tolerance
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &Context,
+        trail: &[PitchSample],
+        now_ms: f64,
+        left: f32,
+        right: f32,
+        to_y: impl Fn(f32) -> f32,
+        tolerance_cents: f32,
+        in_tune_color: [f32; 3],
+        off_color: [f32; 3],
+    ) {
+        self.lay_out(context, trail, now_ms, left, right, to_y, |cents| {
+            let excess = ((cents.abs() - tolerance_cents) / (2.0 * tolerance_cents)).clamp(0.0, 1.0);
+            let color = std::array::from_fn(|channel| in_tune_color[channel] + excess * (off_color[channel] - in_tune_color[channel]));
+            rgb_to_srgba_with_alpha(color, 1.0)
+        });
+    }
+
+    /// Lay out `trail` like `update`, in one faint color, for drawing behind
+    /// the live graph
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_ghost(
+        &mut self,
+        context: &Context,
+        trail: &[PitchSample],
+        now_ms: f64,
+        left: f32,
+        right: f32,
+        to_y: impl Fn(f32) -> f32,
+        color: [f32; 3],
+    ) {
+        self.lay_out(context, trail, now_ms, left, right, to_y, |_| rgb_to_srgba_with_alpha(color, GHOST_TRAIL_ALPHA));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn lay_out(
+        &mut self,
+        context: &Context,
+        trail: &[PitchSample],
+        now_ms: f64,
+        left: f32,
+        right: f32,
+        to_y: impl Fn(f32) -> f32,
+        segment_color: impl Fn(f32) -> Srgba,
+    ) {
+        let slot_ms = PITCH_TRAIL_WINDOW_MS / PITCH_TRAIL_POINTS as f64;
+        let to_point = |sample: &PitchSample| {
+            let age = ((now_ms - sample.timestamp_ms) / PITCH_TRAIL_WINDOW_MS).clamp(0.0, 1.0) as f32;
+            PhysicalPoint { x: right - age * (right - left), y: to_y(sample.frequency) }
+        };
+
+        self.visible = 0;
+        for pair in trail.windows(2) {
+            if pair[1].timestamp_ms - pair[0].timestamp_ms > MAX_JOINED_SLOTS * slot_ms {
+                continue;
+            }
+            let (start, end) = (to_point(&pair[0]), to_point(&pair[1]));
+            let color = segment_color(0.5 * (pair[0].cents_offset + pair[1].cents_offset));
+
+            if self.visible == self.segments.len() {
+                self.segments.push(Gm::new(Line::new(context, start, end, PITCH_GRAPH_LINE_THICKNESS), ColorMaterial::default()));
+            }
+            let segment = &mut self.segments[self.visible];
+            segment.set_endpoints(start, end);
+            segment.material = ColorMaterial {
+                color,
+                texture: None,
+                is_transparent: color.a < 255,
+                render_states: RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                },
+            };
+            self.visible += 1;
+        }
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &Gm<Line, ColorMaterial>> {
+        self.segments[..self.visible].iter()
+    }
+}
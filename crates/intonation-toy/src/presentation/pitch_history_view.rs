@@ -0,0 +1,107 @@
+use three_d::{Blend, ColorMaterial, Context, Deg, Gm, Object, Rectangle, RenderStates, Viewport, WriteMask};
+use crate::app_config::{NOTE_LINE_LEFT_MARGIN, NOTE_LINE_RIGHT_MARGIN};
+use crate::common::note_naming::NoteNamer;
+use crate::common::pitch_history::PitchSample;
+use crate::common::shared_types::{ColorScheme, MidiNote};
+use crate::common::theme::rgb_to_srgba_with_alpha;
+use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::pitch_graph::PitchGraph;
+use crate::presentation::tuning_lines::{ColorMode, TuningLines};
+
+/// Everything the tuning lines and their labels depend on
+#[derive(Clone, PartialEq)]
+struct LineLayout {
+    viewport: Viewport,
+    lines: Vec<(f32, MidiNote, f32, i32)>,
+    note_names: Vec<String>,
+    scheme: ColorScheme,
+}
+
+/// Pitch graph over the tuning lines, for a pane of its own
+pub struct PitchHistoryView {
+    background: Gm<Rectangle, ColorMaterial>,
+    lines: TuningLines,
+    /// Note labels, rendered again only when the line layout changes
+    labels: Vec<Box<dyn Object>>,
+    line_layout: Option<LineLayout>,
+    graph: PitchGraph,
+}
+
+impl PitchHistoryView {
+    pub fn new(context: &Context) -> Self {
+        Self {
+            background: Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default()),
+            lines: TuningLines::new(context, three_d::Srgba::WHITE),
+            labels: Vec::new(),
+            line_layout: None,
+            graph: PitchGraph::default(),
+        }
+    }
+
+    /// Lay out the tuning lines given by their y position, note, thickness and
+    /// semitones above the tonal center, and the pitch graph of `trail`, with
+    /// `to_y` placing a frequency in the pane
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &Context,
+        text_backend: &mut EguiTextBackend,
+        line_data: &[(f32, MidiNote, f32, i32)],
+        note_namer: &NoteNamer,
+        trail: &[PitchSample],
+        now_ms: f64,
+        to_y: impl Fn(f32) -> f32,
+        tolerance_cents: f32,
+        scheme: &ColorScheme,
+        viewport: Viewport,
+    ) {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.background.set_center((width * 0.5, height * 0.5));
+        self.background.set_size(width, height);
+        self.background.material = ColorMaterial {
+            color: rgb_to_srgba_with_alpha(scheme.surface, 1.0),
+            texture: None,
+            is_transparent: false,
+            render_states: RenderStates {
+                write_mask: WriteMask::COLOR,
+                blend: Blend::TRANSPARENCY,
+                ..Default::default()
+            },
+        };
+
+        let line_layout = LineLayout {
+            viewport,
+            lines: line_data.to_vec(),
+            note_names: line_data.iter().map(|&(_, note, _, _)| note_namer.name(note)).collect(),
+            scheme: scheme.clone(),
+        };
+        if self.line_layout.as_ref() != Some(&line_layout) {
+            let regular_color = rgb_to_srgba_with_alpha(scheme.muted, 1.0);
+            let octave_color = rgb_to_srgba_with_alpha(scheme.primary, 1.0);
+            self.lines.update_lines(viewport, line_data, context, regular_color, octave_color);
+            let labels = self.lines.get_note_labels(ColorMode::Normal, note_namer);
+            self.labels = text_backend.render_texts(context, viewport, &labels, three_d::egui::Align::LEFT);
+            self.line_layout = Some(line_layout);
+        }
+
+        self.graph.update(
+            context,
+            trail,
+            now_ms,
+            NOTE_LINE_LEFT_MARGIN,
+            width - NOTE_LINE_RIGHT_MARGIN,
+            to_y,
+            tolerance_cents,
+            scheme.accent,
+            scheme.error,
+        );
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = vec![&self.background];
+        objects.extend(self.lines.lines().map(|line| line as &dyn Object));
+        objects.extend(self.labels.iter().map(|label| label.as_ref()));
+        objects.extend(self.graph.segments().map(|segment| segment as &dyn Object));
+        objects
+    }
+}
@@ -1,34 +1,45 @@
-#![cfg(target_arch = "wasm32")]
-
 // External crate imports
 use std::sync::Arc;
-use three_d::{Camera, ClearState, Context, CpuTexture, Deg, Gm, Object, RenderTarget, TextureData, Texture2DRef, Viewport};
+use three_d::{Blend, Camera, ClearState, ColorMaterial, ColorTexture, CopyEffect, Context, CpuTexture, Deg, Gm, Object, RenderStates, RenderTarget, TextureData, Texture2DRef, Viewport, WriteMask};
 use three_d::core::{DepthTexture2D, Interpolation, Texture2D, Wrapping};
 use three_d::renderer::geometry::Rectangle;
 
-use crate::app_config::{NOTE_LINE_LEFT_MARGIN, NOTE_LINE_RIGHT_MARGIN, OCTAVE_LINE_THICKNESS, REGULAR_LINE_THICKNESS};
+use crate::app_config::{BACKGROUND_FADE_MS, PIANO_HEIGHT_FRACTION, NOTE_LINE_LEFT_MARGIN, NOTE_LINE_RIGHT_MARGIN, OCTAVE_LINE_THICKNESS, REGULAR_LINE_THICKNESS, SPECTROGRAM_HEIGHT_FRACTION, SPLITTER_THICKNESS, STROBE_BAND_COUNT, STROBE_RING_COUNT};
 use crate::presentation::audio_analysis::AudioAnalysis;
+use crate::presentation::capture_overlay_view::{CaptureOverlayView, Lamp};
 use crate::presentation::background_shader::{BackgroundShaderMaterial, DATA_TEXTURE_WIDTH};
 use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::input_meters_view::InputMetersView;
+use crate::presentation::metronome_view::MetronomeView;
+use crate::presentation::needle_view::NeedleView;
+use crate::presentation::piano_view::PianoView;
+use crate::presentation::pitch_graph::PitchGraph;
+use crate::presentation::pitch_history_view::PitchHistoryView;
+use crate::presentation::spectrogram_view::SpectrogramView;
+use crate::presentation::staff_view::StaffView;
+use crate::presentation::statistics_view::StatisticsView;
+use crate::presentation::strobe_shader::StrobeMaterial;
 use crate::presentation::tuning_lines::{TuningLines, ColorMode};
-use crate::common::shared_types::{ColorScheme, MidiNote};
+use crate::common::pitch_history::PitchSample;
+use crate::common::level_meter::LevelReading;
+use crate::common::interval_axis::IntervalAxis;
+use crate::common::needle::NeedleMotion;
+use crate::common::pane_layout::{PaneKind, PaneLayout, PaneRect};
+use crate::common::shared_types::{Clef, ColorScheme, DisplayScene, MetronomeStatus, MidiNote, NeedleDamping, Scale, SessionStatistics, Spectrum};
+use crate::common::staff_notation::StaffNote;
+use crate::common::strobe::StrobePhase;
 use crate::common::theme::{get_current_color_scheme, rgb_to_srgba_with_alpha};
+use crate::common::tween::{Easing, Tween};
 
 /// Converts musical interval to screen Y position
-fn interval_to_screen_y_position(interval: f32, viewport_height: f32, display_range: &crate::common::shared_types::DisplayRange) -> f32 {
-    let (zoom_factor, y_offset) = match display_range {
-        crate::common::shared_types::DisplayRange::TwoOctaves => (0.92, 0.0),
-        crate::common::shared_types::DisplayRange::OneFullOctave => (1.84, -0.46),
-        crate::common::shared_types::DisplayRange::TwoHalfOctaves => (1.84, -0.077),
-    };
-
-    viewport_height * (0.5 + y_offset + interval * zoom_factor * 0.5)
+fn interval_to_screen_y_position(interval: f32, viewport_height: f32, axis: &IntervalAxis) -> f32 {
+    viewport_height * axis.y_fraction(interval)
 }
 
 /// Converts frequency to screen Y position
-fn frequency_to_screen_y_position(frequency: f32, tonal_center_frequency: f32, viewport_height: f32, display_range: &crate::common::shared_types::DisplayRange) -> f32 {
+fn frequency_to_screen_y_position(frequency: f32, tonal_center_frequency: f32, viewport_height: f32, axis: &IntervalAxis) -> f32 {
     let interval = (frequency / tonal_center_frequency).log2();
-    interval_to_screen_y_position(interval, viewport_height, display_range)
+    interval_to_screen_y_position(interval, viewport_height, axis)
 }
 
 /// Creates a textured quad for background rendering with custom shader
@@ -43,6 +54,7 @@ fn create_background_quad(
     tint_color: three_d::Vec3,
     current_pitch_color: three_d::Vec3,
     latest_cents_offset: f32,
+    tolerance_cents: f32,
 ) -> Gm<Rectangle, BackgroundShaderMaterial> {
     assert!(width > 0 && height > 0, "Dimensions must be positive: {}x{}", width, height);
 
@@ -59,10 +71,29 @@ fn create_background_quad(
             tint_color,
             current_pitch_color,
             latest_cents_offset,
+            tolerance_cents,
         }
     )
 }
 
+/// Part of the canvas covered by a pane, in whole pixels
+fn pane_viewport(canvas: Viewport, rect: PaneRect) -> Viewport {
+    let (left, bottom) = (rect.x.round() as i32, rect.y.round() as i32);
+    let (right, top) = ((rect.x + rect.width).round() as i32, (rect.y + rect.height).round() as i32);
+    Viewport {
+        x: canvas.x + left,
+        y: canvas.y + bottom,
+        width: (right - left).max(1) as u32,
+        height: (top - bottom).max(1) as u32,
+    }
+}
+
+/// Camera drawing into a pane with coordinates from its bottom left corner
+fn pane_camera(viewport: Viewport) -> Camera {
+    let mut camera = Camera::new_2d(viewport);
+    camera.disable_tone_and_color_mapping();
+    camera
+}
 
 pub struct Renderer {
     camera: Camera,
@@ -71,6 +102,45 @@ pub struct Renderer {
     three_d_context: Context,
     color_scheme: ColorScheme,
     background_quad: Option<Gm<Rectangle, BackgroundShaderMaterial>>,
+    /// Tuning lines and labels without the pitch overlays, behind the pitch graph
+    graph_background_quad: Option<Gm<Rectangle, ColorMaterial>>,
+    /// Previous background, fading out over the new one after the tuning
+    /// lines or colors change
+    fading_background: Option<Gm<Rectangle, ColorMaterial>>,
+    /// Opacity of the fading background
+    background_fade: Tween<f32>,
+    scene: DisplayScene,
+    pitch_graph: PitchGraph,
+    /// Trail of the previous exercise attempt, drawn faintly behind the pitch graph
+    ghost_graph: PitchGraph,
+    strobe_quad: Gm<Rectangle, StrobeMaterial>,
+    strobe_phase: StrobePhase,
+    needle: NeedleView,
+    needle_motion: NeedleMotion,
+    needle_damping: NeedleDamping,
+    /// Present while the spectrogram is shown
+    spectrogram: Option<SpectrogramView>,
+    /// Present while the staff is shown
+    staff: Option<StaffView>,
+    /// Present while the piano keyboard is shown
+    piano: Option<PianoView>,
+    /// Present while the input meters are shown
+    input_meters: Option<InputMetersView>,
+    /// Present while counting in, recording or running an exercise
+    capture_overlay: Option<CaptureOverlayView>,
+    /// Present while the metronome runs
+    metronome: Option<MetronomeView>,
+    /// Present while the pitch history pane is shown
+    pitch_history: Option<PitchHistoryView>,
+    /// Present while the statistics pane is shown
+    statistics: Option<StatisticsView>,
+    /// Canvas area of every shown pane, the main display first
+    pane_viewports: Vec<(PaneKind, Viewport)>,
+    /// Lines between the panes; only the first `visible_splitters` are drawn
+    splitters: Vec<Gm<Rectangle, ColorMaterial>>,
+    visible_splitters: usize,
+    /// Size the background textures were rendered at
+    background_size: Option<(u32, u32)>,
     presentation_context: Option<crate::common::shared_types::PresentationContext>,
     last_frame_time: f32,
     data_texture: Arc<Texture2D>,
@@ -106,6 +176,41 @@ impl Renderer {
             three_d_context: context.clone(),
             color_scheme: scheme,
             background_quad: None,
+            graph_background_quad: None,
+            fading_background: None,
+            background_fade: Tween::new(0.0),
+            scene: DisplayScene::default(),
+            pitch_graph: PitchGraph::default(),
+            ghost_graph: PitchGraph::default(),
+            strobe_quad: Gm::new(
+                Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0),
+                StrobeMaterial {
+                    phase: 0.0,
+                    aspect_ratio: 1.0,
+                    band_count: STROBE_BAND_COUNT,
+                    ring_count: STROBE_RING_COUNT,
+                    // Colors follow the color scheme when rendering
+                    band_color: three_d::Vec3::new(0.0, 0.0, 0.0),
+                    background_color: three_d::Vec3::new(0.0, 0.0, 0.0),
+                    detected: false,
+                },
+            ),
+            strobe_phase: StrobePhase::default(),
+            needle: NeedleView::new(context),
+            needle_motion: NeedleMotion::default(),
+            needle_damping: NeedleDamping::default(),
+            spectrogram: None,
+            staff: None,
+            piano: None,
+            input_meters: None,
+            capture_overlay: None,
+            metronome: None,
+            pitch_history: None,
+            statistics: None,
+            pane_viewports: Vec::new(),
+            splitters: Vec::new(),
+            visible_splitters: 0,
+            background_size: None,
             presentation_context: None,
             last_frame_time: 0.0,
             data_texture,
@@ -119,7 +224,7 @@ impl Renderer {
             return Vec::new();
         };
         
-        let tonal_center_frequency = crate::common::music_theory::midi_note_to_standard_frequency(context.tonal_center_note);
+        let tonal_center_frequency = crate::common::music_theory::midi_note_to_frequency(context.tonal_center_note, context.concert_pitch_hz);
         let mut line_data = Vec::new();
         
         for semitone in -12..=12 {
@@ -128,15 +233,15 @@ impl Renderer {
             }
             
             let y_position = if semitone == 0 {
-                interval_to_screen_y_position(0.0, viewport.height as f32, &context.display_range)
+                interval_to_screen_y_position(0.0, viewport.height as f32, &context.interval_axis)
             } else {
                 let frequency = crate::common::music_theory::interval_frequency(
-                    context.tuning_system,
+                    &context.tuning_system,
                     tonal_center_frequency,
                     semitone,
                 );
                 let interval = (frequency / tonal_center_frequency).log2();
-                interval_to_screen_y_position(interval, viewport.height as f32, &context.display_range)
+                interval_to_screen_y_position(interval, viewport.height as f32, &context.interval_axis)
             };
             
             let midi_note = (context.tonal_center_note as i32 + semitone).clamp(0, 127) as MidiNote;
@@ -149,9 +254,45 @@ impl Renderer {
     }
 
     
+    /// Place the panes of `layout` on a canvas of the given size
+    pub fn set_pane_layout(&mut self, layout: &PaneLayout, viewport: Viewport) {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.pane_viewports = layout.rects(width, height)
+            .into_iter()
+            .map(|(kind, rect)| (kind, pane_viewport(viewport, rect)))
+            .collect();
+
+        let splitter_rects = layout.splitter_rects(width, height, SPLITTER_THICKNESS);
+        while self.splitters.len() < splitter_rects.len() {
+            self.splitters.push(Gm::new(Rectangle::new(&self.three_d_context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default()));
+        }
+        for (splitter, rect) in self.splitters.iter_mut().zip(&splitter_rects) {
+            splitter.set_center((rect.x + rect.width * 0.5, rect.y + rect.height * 0.5));
+            splitter.set_size(rect.width, rect.height);
+            splitter.material = ColorMaterial {
+                color: rgb_to_srgba_with_alpha(self.color_scheme.border, 1.0),
+                texture: None,
+                is_transparent: false,
+                render_states: RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                },
+            };
+        }
+        self.visible_splitters = splitter_rects.len();
+    }
+
+    /// Canvas area of a shown pane
+    pub fn pane_viewport(&self, kind: PaneKind) -> Option<Viewport> {
+        self.pane_viewports.iter().find(|(pane, _)| *pane == kind).map(|&(_, viewport)| viewport)
+    }
+
     #[allow(clippy::arc_with_non_send_sync)] // Required by three_d API
     pub fn render(&mut self, screen: &mut RenderTarget, viewport: Viewport) {
-        self.camera.set_viewport(viewport);
+        let canvas_viewport = viewport;
+        let viewport = self.pane_viewport(PaneKind::Display).unwrap_or(viewport);
+        self.camera = Camera::new_2d(viewport);
 
         // Update background shader margins if viewport changed
         if let Some(ref mut background_quad) = self.background_quad {
@@ -159,6 +300,14 @@ impl Renderer {
             background_quad.material.right_margin = NOTE_LINE_RIGHT_MARGIN / viewport.width as f32;
         }
 
+        let now_ms = crate::common::utils::now_ms();
+        if let Some(fading_background) = &mut self.fading_background {
+            fading_background.material.color.a = (self.background_fade.value(now_ms) * 255.0) as u8;
+        }
+        if !self.background_fade.is_running(now_ms) {
+            self.fading_background = None;
+        }
+
         // Update time and render background quad with custom shader
         let delta_time = 1.0 / 60.0; // Simple frame time approximation (60 FPS assumed)
         self.last_frame_time += delta_time;
@@ -180,7 +329,7 @@ impl Renderer {
             let texture_data: Vec<[f32; 2]> = if let Some(context) = &self.presentation_context {
                 self.data_buffer.iter().map(|&[detected, frequency]| {
                     let screen_y = if detected > 0.0 {
-                        let y_pos = frequency_to_screen_y_position(frequency, self.audio_analysis.tonal_center_frequency, viewport.height as f32, &context.display_range);
+                        let y_pos = frequency_to_screen_y_position(frequency, self.audio_analysis.tonal_center_frequency, viewport.height as f32, &context.interval_axis);
                         y_pos / viewport.height as f32
                     } else {
                         0.0
@@ -204,19 +353,299 @@ impl Renderer {
                 },
             ));
 
-            // Update the material with new texture and latest cents offset
+            // Update the material with new texture, latest cents offset and tolerance
             background_quad.material.data_texture = Some(self.data_texture.clone().into());
             background_quad.material.latest_cents_offset = self.audio_analysis.cents_offset;
+            background_quad.material.tolerance_cents = self.audio_analysis.tolerance_cents;
 
             self.camera.disable_tone_and_color_mapping();
-            screen.render(&self.camera, [background_quad], &[]);
+            match self.scene {
+                DisplayScene::Intonation => {
+                    screen.render(&self.camera, [background_quad], &[]);
+                    if let Some(fading_background) = &self.fading_background {
+                        screen.render(&self.camera, [fading_background], &[]);
+                    }
+                }
+                DisplayScene::PitchGraph => {
+                    if let Some(graph_background_quad) = &self.graph_background_quad {
+                        screen.render(&self.camera, [graph_background_quad], &[]);
+                    }
+                    if let Some(fading_background) = &self.fading_background {
+                        screen.render(&self.camera, [fading_background], &[]);
+                    }
+                    screen.render(&self.camera, self.ghost_graph.segments(), &[]);
+                    screen.render(&self.camera, self.pitch_graph.segments(), &[]);
+                }
+                DisplayScene::Strobe => {
+                    let (width, height) = (viewport.width as f32, viewport.height as f32);
+                    self.strobe_quad.set_center((width * 0.5, height * 0.5));
+                    self.strobe_quad.set_size(width, height);
+                    self.strobe_quad.material.aspect_ratio = width / height;
+                    let pitch = self.audio_analysis.pitch_detected.then_some((self.audio_analysis.frequency, self.audio_analysis.cents_offset));
+                    self.strobe_quad.material.phase = self.strobe_phase.update(crate::common::utils::now_ms(), pitch);
+                    self.strobe_quad.material.detected = self.audio_analysis.pitch_detected;
+                    self.strobe_quad.material.band_color = three_d::Vec3::from(self.color_scheme.accent);
+                    self.strobe_quad.material.background_color = three_d::Vec3::from(self.color_scheme.background);
+                    screen.render(&self.camera, [&self.strobe_quad], &[]);
+                }
+                DisplayScene::Needle => {
+                    let cents_offset = self.audio_analysis.pitch_detected.then_some(self.audio_analysis.cents_offset);
+                    let position = self.needle_motion.update(crate::common::utils::now_ms(), cents_offset, self.needle_damping);
+                    self.needle.update(
+                        &self.three_d_context,
+                        &mut self.text_backend,
+                        position,
+                        self.audio_analysis.tolerance_cents,
+                        self.audio_analysis.pitch_detected,
+                        &self.color_scheme,
+                        viewport,
+                    );
+                    screen.render(&self.camera, self.needle.objects(), &[]);
+                }
+            }
+            if let Some(spectrogram) = &self.spectrogram
+                && self.pane_viewport(PaneKind::Spectrogram).is_none()
+            {
+                screen.render(&self.camera, [spectrogram.quad()], &[]);
+            }
+            if let Some(piano) = &self.piano {
+                screen.render(&self.camera, piano.keys(), &[]);
+            }
+            if let Some(staff) = &self.staff {
+                screen.render(&self.camera, staff.objects(), &[]);
+            }
+            if let Some(input_meters) = &self.input_meters {
+                screen.render(&self.camera, input_meters.objects(), &[]);
+            }
+            if let Some(capture_overlay) = &self.capture_overlay {
+                screen.render(&self.camera, capture_overlay.objects(), &[]);
+            }
+            if let Some(metronome) = &self.metronome {
+                screen.render(&self.camera, metronome.objects(), &[]);
+            }
+
+            if let (Some(spectrogram), Some(pane)) = (&self.spectrogram, self.pane_viewport(PaneKind::Spectrogram)) {
+                screen.render(pane_camera(pane), [spectrogram.quad()], &[]);
+            }
+            if let (Some(pitch_history), Some(pane)) = (&self.pitch_history, self.pane_viewport(PaneKind::PitchHistory)) {
+                screen.render(pane_camera(pane), pitch_history.objects(), &[]);
+            }
+            if let (Some(statistics), Some(pane)) = (&self.statistics, self.pane_viewport(PaneKind::Statistics)) {
+                screen.render(pane_camera(pane), statistics.objects(), &[]);
+            }
+            screen.render(pane_camera(canvas_viewport), &self.splitters[..self.visible_splitters], &[]);
             self.camera.set_default_tone_and_color_mapping();
         }
     }
+
+    /// Render into a texture instead, copy that to the screen and return its
+    /// pixels, bottom row first
+    pub fn render_and_capture(&mut self, screen: &mut RenderTarget, viewport: Viewport) -> Vec<[u8; 4]> {
+        let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
+            &self.three_d_context,
+            viewport.width,
+            viewport.height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.three_d_context,
+            viewport.width,
+            viewport.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let pixels = {
+            let [r, g, b] = self.color_scheme.background;
+            let mut target = RenderTarget::new(color_texture.as_color_target(None), depth_texture.as_depth_target());
+            target.clear(ClearState::color_and_depth(r, g, b, 1.0, 1.0));
+            self.render(&mut target, viewport);
+            target.read_color::<[u8; 4]>()
+        };
+        screen.apply_screen_effect(&CopyEffect::default(), pane_camera(viewport), &[], Some(ColorTexture::Single(&color_texture)), None);
+        pixels
+    }
     
     pub fn update_audio_analysis(&mut self, audio_analysis: AudioAnalysis) {
         self.audio_analysis = audio_analysis;
     }
+
+    pub fn set_scene(&mut self, scene: DisplayScene) {
+        self.scene = scene;
+    }
+
+    pub fn set_needle_damping(&mut self, damping: NeedleDamping) {
+        self.needle_damping = damping;
+    }
+
+    /// Show the spectrogram with the given dynamic range, or hide it with
+    /// `None`; hiding discards its history. It fills its pane while that is
+    /// shown, and a band along the bottom of the display otherwise
+    pub fn update_spectrogram(&mut self, spectrum: Option<&Spectrum>, dynamic_range_db: Option<f32>, viewport: Viewport) {
+        let Some(dynamic_range_db) = dynamic_range_db else {
+            self.spectrogram = None;
+            return;
+        };
+        let area = match self.pane_viewport(PaneKind::Spectrogram) {
+            Some(pane) => PaneRect { x: 0.0, y: 0.0, width: pane.width as f32, height: pane.height as f32 },
+            None => PaneRect {
+                x: 0.0,
+                y: self.piano_height(viewport),
+                width: viewport.width as f32,
+                height: viewport.height as f32 * SPECTROGRAM_HEIGHT_FRACTION,
+            },
+        };
+        self.spectrogram
+            .get_or_insert_with(|| SpectrogramView::new(&self.three_d_context))
+            .update(&self.three_d_context, spectrum, dynamic_range_db, area);
+    }
+
+    /// Show the piano keyboard marking the tonal center, target note and
+    /// scale, or hide it with `None`
+    pub fn update_piano(&mut self, piano: Option<(MidiNote, Option<MidiNote>, Scale)>, viewport: Viewport) {
+        let Some((tonal_center_note, target_note, scale)) = piano else {
+            self.piano = None;
+            return;
+        };
+        self.piano
+            .get_or_insert_with(|| PianoView::new(&self.three_d_context, viewport))
+            .update(tonal_center_note, target_note, scale, &self.color_scheme, viewport, crate::common::utils::now_ms());
+    }
+
+    /// Show the input level and the clarity of the detected pitch, or hide
+    /// them with `None`
+    pub fn update_input_meters(&mut self, meters: Option<(LevelReading, Option<f32>, bool)>, viewport: Viewport) {
+        let Some((level, clarity, pitch_detected)) = meters else {
+            self.input_meters = None;
+            return;
+        };
+        let bottom = self.piano_height(viewport);
+        self.input_meters
+            .get_or_insert_with(|| InputMetersView::new(&self.three_d_context))
+            .update(level, clarity, pitch_detected, &self.color_scheme, bottom);
+    }
+
+    /// Show the beat of a count-in with the part of it that has passed, and
+    /// the recording or exercise lamp; hidden when there is neither
+    pub fn update_capture_overlay(&mut self, count_in: Option<(u32, f32)>, lamp: Option<Lamp>, viewport: Viewport) {
+        if count_in.is_none() && lamp.is_none() {
+            self.capture_overlay = None;
+            return;
+        }
+        self.capture_overlay
+            .get_or_insert_with(CaptureOverlayView::default)
+            .update(&self.three_d_context, &mut self.text_backend, count_in, lamp.as_ref(), &self.color_scheme, viewport, crate::common::utils::now_ms());
+    }
+
+    /// Flash the ticks of the metronome, or hide it with `None`
+    pub fn update_metronome(&mut self, status: Option<&MetronomeStatus>, viewport: Viewport) {
+        let Some(status) = status else {
+            self.metronome = None;
+            return;
+        };
+        self.metronome
+            .get_or_insert_with(|| MetronomeView::new(&self.three_d_context))
+            .update(&self.three_d_context, status, &self.color_scheme, viewport);
+    }
+
+    /// Height of the piano keyboard, for placing views above it
+    fn piano_height(&self, viewport: Viewport) -> f32 {
+        if self.piano.is_some() { viewport.height as f32 * PIANO_HEIGHT_FRACTION } else { 0.0 }
+    }
+
+    /// Note of the piano key under a point on the canvas
+    pub fn piano_note_at(&self, x: f32, y: f32) -> Option<MidiNote> {
+        self.piano.as_ref()?.note_at(x, y)
+    }
+
+    /// Show the staff with its clef, target note, detected note and whether
+    /// the detected pitch is in tune, or hide it with `None`
+    pub fn update_staff(&mut self, staff: Option<(Clef, Option<StaffNote>, Option<StaffNote>, bool)>, viewport: Viewport) {
+        let Some((clef, target, detected, detected_in_tune)) = staff else {
+            self.staff = None;
+            return;
+        };
+        self.staff
+            .get_or_insert_with(|| StaffView::new(&self.three_d_context))
+            .update(&self.three_d_context, &mut self.text_backend, clef, target, detected, detected_in_tune, &self.color_scheme, viewport, crate::common::utils::now_ms());
+    }
+
+    /// Lay out the pitch history pane from the pitch trail while it is shown;
+    /// call after `update_audio_analysis`
+    pub fn update_pitch_history(&mut self, trail: &[PitchSample], now_ms: f64) {
+        let Some(viewport) = self.pane_viewport(PaneKind::PitchHistory) else {
+            self.pitch_history = None;
+            return;
+        };
+        let Some(context) = &self.presentation_context else {
+            return;
+        };
+        let viewport = Viewport::new_at_origo(viewport.width, viewport.height);
+        let line_data = self.get_tuning_line_positions(viewport);
+        let note_namer = context.note_namer();
+        let tonal_center_frequency = self.audio_analysis.tonal_center_frequency;
+        let viewport_height = viewport.height as f32;
+        self.pitch_history
+            .get_or_insert_with(|| PitchHistoryView::new(&self.three_d_context))
+            .update(
+                &self.three_d_context,
+                &mut self.text_backend,
+                &line_data,
+                &note_namer,
+                trail,
+                now_ms,
+                |frequency| frequency_to_screen_y_position(frequency, tonal_center_frequency, viewport_height, &context.interval_axis),
+                self.audio_analysis.tolerance_cents,
+                &self.color_scheme,
+                viewport,
+            );
+    }
+
+    /// Show the session statistics while their pane is shown
+    pub fn update_statistics(&mut self, statistics: &SessionStatistics) {
+        let Some(viewport) = self.pane_viewport(PaneKind::Statistics) else {
+            self.statistics = None;
+            return;
+        };
+        self.statistics
+            .get_or_insert_with(|| StatisticsView::new(&self.three_d_context))
+            .update(&self.three_d_context, &mut self.text_backend, statistics, &self.color_scheme, Viewport::new_at_origo(viewport.width, viewport.height));
+    }
+
+    /// Lay out the pitch graph from the pitch trail, over the trail of the
+    /// previous exercise attempt if given; call after `update_audio_analysis`
+    pub fn update_pitch_graph(&mut self, trail: &[PitchSample], ghost_trail: Option<&[PitchSample]>, now_ms: f64, viewport: Viewport) {
+        let Some(context) = &self.presentation_context else {
+            return;
+        };
+        let tonal_center_frequency = self.audio_analysis.tonal_center_frequency;
+        let viewport_height = viewport.height as f32;
+        let to_y = |frequency| frequency_to_screen_y_position(frequency, tonal_center_frequency, viewport_height, &context.interval_axis);
+        let (left, right) = (NOTE_LINE_LEFT_MARGIN, viewport.width as f32 - NOTE_LINE_RIGHT_MARGIN);
+        self.pitch_graph.update(
+            &self.three_d_context,
+            trail,
+            now_ms,
+            left,
+            right,
+            to_y,
+            self.audio_analysis.tolerance_cents,
+            self.color_scheme.accent,
+            self.color_scheme.error,
+        );
+        self.ghost_graph.update_ghost(
+            &self.three_d_context,
+            ghost_trail.unwrap_or_default(),
+            now_ms,
+            left,
+            right,
+            to_y,
+            self.color_scheme.muted,
+        );
+    }
     
     
     /// Renders tuning lines and note labels to the background texture
@@ -225,6 +654,9 @@ impl Renderer {
             crate::common::dev_log!("Warning: Invalid viewport dimensions for background texture");
             return;
         }
+        // The textures are drawn from their own origin, wherever the display pane is
+        let viewport = Viewport::new_at_origo(viewport.width, viewport.height);
+        self.background_size = Some((viewport.width, viewport.height));
 
         // Get the tuning line positions and create TuningLines on the fly
         let tuning_line_data = self.get_tuning_line_positions(viewport);
@@ -239,6 +671,9 @@ impl Renderer {
 
         let mut tuning_lines = TuningLines::new(&self.three_d_context, regular_color);
         tuning_lines.update_lines(viewport, &tuning_line_data, &self.three_d_context, regular_color, octave_color);
+        let Some(note_namer) = self.presentation_context.as_ref().map(|context| context.note_namer()) else {
+            return;
+        };
 
         let mut background_texture = Texture2D::new_empty::<[u8; 4]>(
             &self.three_d_context,
@@ -286,7 +721,7 @@ impl Renderer {
             let tuning_lines_objects: Vec<&dyn Object> = tuning_lines.lines().map(|line| line as &dyn Object).collect();
 
             // Render note labels on the left
-            let note_labels = tuning_lines.get_note_labels(ColorMode::Normal);
+            let note_labels = tuning_lines.get_note_labels(ColorMode::Normal, &note_namer);
             let note_text_models = self.text_backend.render_texts(&self.three_d_context, viewport, &note_labels, three_d::egui::Align::LEFT);
 
             // Render interval labels on the right (right-aligned)
@@ -314,7 +749,7 @@ impl Renderer {
             let highlight_lines_refs: Vec<&dyn Object> = highlight_lines.iter().map(|line| line.as_ref() as &dyn Object).collect();
 
             // Get labels with white color
-            let highlight_note_labels = tuning_lines.get_note_labels(ColorMode::Highlight);
+            let highlight_note_labels = tuning_lines.get_note_labels(ColorMode::Highlight, &note_namer);
             let highlight_note_text_models = self.text_backend.render_texts(&self.three_d_context, viewport, &highlight_note_labels, three_d::egui::Align::LEFT);
 
             let highlight_interval_labels = tuning_lines.get_interval_labels(viewport.width as f32, ColorMode::Highlight);
@@ -332,6 +767,14 @@ impl Renderer {
         }
 
         let texture_ref = Texture2DRef::from_texture(background_texture);
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.graph_background_quad = Some(Gm::new(
+            Rectangle::new(&self.three_d_context, (width * 0.5, height * 0.5), Deg(0.0), width, height),
+            ColorMaterial {
+                texture: Some(texture_ref.clone()),
+                ..Default::default()
+            },
+        ));
         let highlight_texture_ref = Texture2DRef::from_texture(highlight_texture);
 
         // Set tint color using theme primary color
@@ -351,15 +794,22 @@ impl Renderer {
             Some(self.data_texture.clone().into()),
             tint_color,
             extension_color,
-            self.audio_analysis.cents_offset
+            self.audio_analysis.cents_offset,
+            self.audio_analysis.tolerance_cents,
         ));
     }
     
-    /// Update the presentation context
+    /// Update the presentation context; the background is rendered again when
+    /// it or the size of the display changes, fading in unless only the
+    /// interval axis moved, as it does every frame of a zoom
     pub fn update_presentation_context(&mut self, context: &crate::common::shared_types::PresentationContext, viewport: Viewport) {
-        if self.presentation_context.as_ref() == Some(context) {
+        if self.presentation_context.as_ref() == Some(context) && self.background_size == Some((viewport.width, viewport.height)) {
             return;
         }
+        let zooming = self.presentation_context.as_ref().is_some_and(|current| {
+            context.interval_axis != current.interval_axis
+                && *context == crate::common::shared_types::PresentationContext { interval_axis: context.interval_axis, ..current.clone() }
+        });
 
         self.presentation_context = Some(context.clone());
 
@@ -368,15 +818,50 @@ impl Renderer {
             return;
         }
 
+        if zooming {
+            self.fading_background = None;
+        } else {
+            self.start_background_fade(viewport);
+        }
         self.render_to_background_texture(viewport);
     }
 
+    /// Keep the current background to fade out over the one about to be
+    /// rendered; a background of another size is dropped at once
+    fn start_background_fade(&mut self, viewport: Viewport) {
+        self.fading_background = None;
+        if self.background_size != Some((viewport.width, viewport.height)) {
+            return;
+        }
+        let Some(texture) = self.graph_background_quad.as_ref().and_then(|quad| quad.material.texture.clone()) else {
+            return;
+        };
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.fading_background = Some(Gm::new(
+            Rectangle::new(&self.three_d_context, (width * 0.5, height * 0.5), Deg(0.0), width, height),
+            ColorMaterial {
+                texture: Some(texture),
+                is_transparent: true,
+                render_states: RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ));
+        let now_ms = crate::common::utils::now_ms();
+        self.background_fade.jump_to(1.0);
+        self.background_fade.animate_to(0.0, now_ms, BACKGROUND_FADE_MS, Easing::EaseInOutCubic);
+    }
+
     /// Refresh theme colors - call when theme changes
     pub fn refresh_color_scheme(&mut self, viewport: Viewport) {
         let new_color_scheme = get_current_color_scheme();
         if self.color_scheme != new_color_scheme {
             self.color_scheme = new_color_scheme;
             if self.presentation_context.is_some() {
+                self.start_background_fade(viewport);
                 self.render_to_background_texture(viewport);
             }
         }
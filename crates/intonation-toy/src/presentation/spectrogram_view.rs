@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use three_d::{ColorMaterial, Context, CpuTexture, Deg, Gm, Interpolation, Rectangle, Texture2D, TextureData, Wrapping};
+use crate::app_config::{SPECTROGRAM_COLUMNS, SPECTROGRAM_MAX_HZ, SPECTROGRAM_MIN_HZ, SPECTROGRAM_ROWS};
+use crate::common::pane_layout::PaneRect;
+use crate::common::shared_types::Spectrum;
+use crate::common::spectrogram::Spectrogram;
+use crate::common::theme::get_current_color_scheme;
+
+/// Scrolling spectrogram, in a band along the bottom of the display or in a
+/// pane of its own
+pub struct SpectrogramView {
+    spectrogram: Spectrogram,
+    quad: Gm<Rectangle, ColorMaterial>,
+}
+
+impl SpectrogramView {
+    pub fn new(context: &Context) -> Self {
+        Self {
+            spectrogram: Spectrogram::new(SPECTROGRAM_COLUMNS, SPECTROGRAM_ROWS, SPECTROGRAM_MIN_HZ, SPECTROGRAM_MAX_HZ),
+            quad: Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default()),
+        }
+    }
+
+    /// Add the spectrum of this frame, shading the loudest `dynamic_range_db`
+    /// from the surface color up to the accent color, over `area`
+    #[allow(clippy::arc_with_non_send_sync)] // Required by three_d API
+    pub fn update(&mut self, context: &Context, spectrum: Option<&Spectrum>, dynamic_range_db: f32, area: PaneRect) {
+        self.spectrogram.push(spectrum, dynamic_range_db);
+
+        let scheme = get_current_color_scheme();
+        let pixels = self.spectrogram.levels()
+            .iter()
+            .map(|&level| {
+                let [r, g, b] = std::array::from_fn(|channel| {
+                    let value = scheme.surface[channel] + level * (scheme.accent[channel] - scheme.surface[channel]);
+                    (value.clamp(0.0, 1.0) * 255.0) as u8
+                });
+                [r, g, b, 255]
+            })
+            .collect();
+        let texture = Texture2D::new(
+            context,
+            &CpuTexture {
+                data: TextureData::RgbaU8(pixels),
+                width: self.spectrogram.columns() as u32,
+                height: self.spectrogram.rows() as u32,
+                min_filter: Interpolation::Linear,
+                mag_filter: Interpolation::Linear,
+                wrap_s: Wrapping::ClampToEdge,
+                wrap_t: Wrapping::ClampToEdge,
+                ..Default::default()
+            },
+        );
+        self.quad.material = ColorMaterial {
+            texture: Some(Arc::new(texture).into()),
+            ..Default::default()
+        };
+
+        self.quad.set_center((area.x + area.width * 0.5, area.y + area.height * 0.5));
+        self.quad.set_size(area.width, area.height);
+    }
+
+    pub fn quad(&self) -> &Gm<Rectangle, ColorMaterial> {
+        &self.quad
+    }
+}
@@ -0,0 +1,170 @@
+use three_d::{Circle, ColorMaterial, Context, Deg, Gm, Line, Object, PhysicalPoint, Rectangle, Viewport};
+use crate::app_config::{NOTEHEAD_FADE_MS, STAFF_LINE_SPACING, STAFF_PANEL_HEIGHT, STAFF_PANEL_MARGIN, STAFF_PANEL_WIDTH, TARGET_HIT_PULSE_MS, TARGET_HIT_PULSE_SCALE};
+use crate::common::shared_types::{Clef, ColorScheme};
+use crate::common::staff_notation::{Accidental, StaffNote, TOP_LINE_STEP};
+use crate::common::tween::{Easing, Tween};
+use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::materials::solid_material;
+
+const LINE_THICKNESS: f32 = 1.5;
+/// Horizontal distance from the target notehead to the detected one
+const DETECTED_NOTE_OFFSET: f32 = 48.0;
+/// Horizontal distance from a notehead center to its accidental
+const ACCIDENTAL_OFFSET: f32 = 22.0;
+
+/// Everything the text of the staff depends on
+#[derive(Clone, Copy, PartialEq)]
+struct TextLayout {
+    clef: Clef,
+    target: Option<StaffNote>,
+    detected: Option<StaffNote>,
+    color: [f32; 3],
+    viewport: Viewport,
+}
+
+/// Staff with a clef, the target note and the detected note, in a panel at
+/// the top of the canvas
+pub struct StaffView {
+    panel: Gm<Rectangle, ColorMaterial>,
+    /// Staff and ledger lines, reused between frames; only the first `visible_lines` are drawn
+    lines: Vec<Gm<Line, ColorMaterial>>,
+    visible_lines: usize,
+    target_notehead: Gm<Circle, ColorMaterial>,
+    detected_notehead: Gm<Circle, ColorMaterial>,
+    target_visible: bool,
+    detected_visible: bool,
+    /// `None` until the first detected note, which starts in its color
+    detected_color: Option<Tween<[f32; 3]>>,
+    /// Whether the detected note matched the target in tune in the last update
+    target_hit: bool,
+    /// Radius of the target notehead relative to its size at rest
+    target_pulse: Tween<f32>,
+    /// Clef and accidentals, rendered again only when their layout changes
+    texts: Vec<Box<dyn Object>>,
+    text_layout: Option<TextLayout>,
+}
+
+impl StaffView {
+    pub fn new(context: &Context) -> Self {
+        Self {
+            panel: Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default()),
+            lines: Vec::new(),
+            visible_lines: 0,
+            target_notehead: Gm::new(Circle::new(context, (0.0, 0.0), STAFF_LINE_SPACING * 0.5), ColorMaterial::default()),
+            detected_notehead: Gm::new(Circle::new(context, (0.0, 0.0), STAFF_LINE_SPACING * 0.5), ColorMaterial::default()),
+            target_visible: false,
+            detected_visible: false,
+            detected_color: None,
+            target_hit: false,
+            target_pulse: Tween::new(1.0),
+            texts: Vec::new(),
+            text_layout: None,
+        }
+    }
+
+    /// Lay out the staff; the detected note fades to the accent color when in
+    /// tune and to the error color otherwise, and the target note pulses when
+    /// the detected note hits it in tune
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        context: &Context,
+        text_backend: &mut EguiTextBackend,
+        clef: Clef,
+        target: Option<StaffNote>,
+        detected: Option<StaffNote>,
+        detected_in_tune: bool,
+        scheme: &ColorScheme,
+        viewport: Viewport,
+        now_ms: f64,
+    ) {
+        let center_x = viewport.width as f32 * 0.5;
+        let center_y = viewport.height as f32 - STAFF_PANEL_MARGIN - STAFF_PANEL_HEIGHT * 0.5;
+        let left = center_x - STAFF_PANEL_WIDTH * 0.5;
+        let right = center_x + STAFF_PANEL_WIDTH * 0.5;
+        let bottom_line_y = center_y - TOP_LINE_STEP as f32 * 0.25 * STAFF_LINE_SPACING;
+        let step_y = |step: i32| bottom_line_y + step as f32 * 0.5 * STAFF_LINE_SPACING;
+        let target_x = center_x - DETECTED_NOTE_OFFSET * 0.25;
+        let detected_x = target_x + DETECTED_NOTE_OFFSET;
+
+        self.panel.set_center((center_x, center_y));
+        self.panel.set_size(STAFF_PANEL_WIDTH, STAFF_PANEL_HEIGHT);
+        self.panel.material = solid_material(scheme.surface, 1.0);
+
+        self.visible_lines = 0;
+        for step in (0..=TOP_LINE_STEP).step_by(2) {
+            self.push_line(context, (left + STAFF_LINE_SPACING, step_y(step)), (right - STAFF_LINE_SPACING, step_y(step)), scheme.muted);
+        }
+        for (note, x) in [(target, target_x), (detected, detected_x)] {
+            for step in note.iter().flat_map(StaffNote::ledger_lines) {
+                self.push_line(context, (x - STAFF_LINE_SPACING, step_y(step)), (x + STAFF_LINE_SPACING, step_y(step)), scheme.muted);
+            }
+        }
+
+        let target_hit = target.is_some() && target == detected && detected_in_tune;
+        if target_hit && !self.target_hit {
+            self.target_pulse.jump_to(TARGET_HIT_PULSE_SCALE);
+            self.target_pulse.animate_to(1.0, now_ms, TARGET_HIT_PULSE_MS, Easing::EaseOutCubic);
+        }
+        self.target_hit = target_hit;
+
+        self.target_visible = target.is_some();
+        if let Some(target) = target {
+            self.target_notehead.set_center((target_x, step_y(target.step)));
+            self.target_notehead.set_radius(STAFF_LINE_SPACING * 0.5 * self.target_pulse.value(now_ms));
+            self.target_notehead.material = solid_material(if target_hit { scheme.accent } else { scheme.text }, 1.0);
+        }
+        self.detected_visible = detected.is_some();
+        if let Some(detected) = detected {
+            let color = if detected_in_tune { scheme.accent } else { scheme.error };
+            let detected_color = self.detected_color.get_or_insert_with(|| Tween::new(color));
+            detected_color.animate_to(color, now_ms, NOTEHEAD_FADE_MS, Easing::Linear);
+            self.detected_notehead.set_center((detected_x, step_y(detected.step)));
+            self.detected_notehead.material = solid_material(detected_color.value(now_ms), 1.0);
+        }
+
+        let text_layout = TextLayout { clef, target, detected, color: scheme.text, viewport };
+        if self.text_layout != Some(text_layout) {
+            let color = [scheme.text[0], scheme.text[1], scheme.text[2], 1.0];
+            let clef_size = STAFF_LINE_SPACING * 3.0;
+            let accidental_size = STAFF_LINE_SPACING * 2.0;
+            // Text is placed by its top edge, so raise it by about half its height to center it
+            let mut texts = vec![(clef.letter().to_string(), left + STAFF_LINE_SPACING * 1.5, step_y(clef.line_step()) + clef_size * 0.6, clef_size, color, true)];
+            for (note, x) in [(target, target_x), (detected, detected_x)] {
+                if let Some(StaffNote { step, accidental: Some(accidental) }) = note {
+                    let symbol = match accidental {
+                        Accidental::Sharp => "#",
+                        Accidental::Flat => "b",
+                    };
+                    texts.push((symbol.to_string(), x - ACCIDENTAL_OFFSET, step_y(step) + accidental_size * 0.6, accidental_size, color, false));
+                }
+            }
+            self.texts = text_backend.render_texts(context, viewport, &texts, three_d::egui::Align::LEFT);
+            self.text_layout = Some(text_layout);
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = vec![&self.panel];
+        objects.extend(self.lines[..self.visible_lines].iter().map(|line| line as &dyn Object));
+        if self.target_visible {
+            objects.push(&self.target_notehead);
+        }
+        if self.detected_visible {
+            objects.push(&self.detected_notehead);
+        }
+        objects.extend(self.texts.iter().map(|text| text.as_ref()));
+        objects
+    }
+
+    fn push_line(&mut self, context: &Context, start: (f32, f32), end: (f32, f32), color: [f32; 3]) {
+        let (start, end) = (PhysicalPoint { x: start.0, y: start.1 }, PhysicalPoint { x: end.0, y: end.1 });
+        if self.visible_lines == self.lines.len() {
+            self.lines.push(Gm::new(Line::new(context, start, end, LINE_THICKNESS), ColorMaterial::default()));
+        }
+        let line = &mut self.lines[self.visible_lines];
+        line.set_endpoints(start, end);
+        line.material = solid_material(color, 1.0);
+        self.visible_lines += 1;
+    }
+}
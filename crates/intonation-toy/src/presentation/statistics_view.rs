@@ -0,0 +1,100 @@
+use three_d::{ColorMaterial, Context, Deg, Gm, Object, Rectangle, Viewport};
+use crate::common::localization::{translate, translate_with};
+use crate::common::music_theory::semitone_to_interval_name;
+use crate::common::session_summary::in_tune_bars;
+use crate::common::shared_types::{ColorScheme, SessionStatistics};
+use crate::presentation::egui_text_backend::EguiTextBackend;
+use crate::presentation::materials::solid_material;
+
+const PADDING: f32 = 16.0;
+const TEXT_SIZE: f32 = 18.0;
+const LINE_SPACING: f32 = 1.5;
+const BAR_LABEL_SIZE: f32 = 12.0;
+/// Gap between bars as a part of the bar pitch
+const BAR_GAP: f32 = 0.25;
+/// Height of the bar of a degree never sung in tune, so it shows it was sung
+const EMPTY_BAR_HEIGHT: f32 = 2.0;
+
+/// Everything the texts depend on
+#[derive(Clone, PartialEq)]
+struct TextLayout {
+    lines: [String; 2],
+    color: [f32; 3],
+    viewport: Viewport,
+}
+
+/// Score and longest streak of the session so far, over a bar per degree
+/// filled with the part of its time sung in tune
+pub struct StatisticsView {
+    background: Gm<Rectangle, ColorMaterial>,
+    /// Indexed by semitones above the tonal center; only sung degrees are drawn
+    bars: Vec<Gm<Rectangle, ColorMaterial>>,
+    sung: [bool; 12],
+    /// Rendered again only when their layout changes
+    texts: Vec<Box<dyn Object>>,
+    text_layout: Option<TextLayout>,
+}
+
+impl StatisticsView {
+    pub fn new(context: &Context) -> Self {
+        let rectangle = || Gm::new(Rectangle::new(context, (0.0, 0.0), Deg(0.0), 1.0, 1.0), ColorMaterial::default());
+        Self {
+            background: rectangle(),
+            bars: (0..12).map(|_| rectangle()).collect(),
+            sung: [false; 12],
+            texts: Vec::new(),
+            text_layout: None,
+        }
+    }
+
+    pub fn update(&mut self, context: &Context, text_backend: &mut EguiTextBackend, statistics: &SessionStatistics, scheme: &ColorScheme, viewport: Viewport) {
+        let (width, height) = (viewport.width as f32, viewport.height as f32);
+        self.background.set_center((width * 0.5, height * 0.5));
+        self.background.set_size(width, height);
+        self.background.material = solid_material(scheme.surface, 1.0);
+
+        let text_height = TEXT_SIZE * LINE_SPACING;
+        let bar_bottom = PADDING + BAR_LABEL_SIZE * LINE_SPACING;
+        let bar_area_height = (height - bar_bottom - 2.0 * text_height - 2.0 * PADDING).max(0.0);
+        let pitch = (width - 2.0 * PADDING) / 12.0;
+        for (semitones, fraction) in in_tune_bars(statistics).into_iter().enumerate() {
+            self.sung[semitones] = fraction.is_some();
+            let Some(fraction) = fraction else {
+                continue;
+            };
+            let bar_height = (bar_area_height * fraction).max(EMPTY_BAR_HEIGHT);
+            let bar = &mut self.bars[semitones];
+            bar.set_center((PADDING + pitch * (semitones as f32 + 0.5), bar_bottom + bar_height * 0.5));
+            bar.set_size(pitch * (1.0 - BAR_GAP), bar_height);
+            bar.material = solid_material(scheme.accent, 1.0);
+        }
+
+        let score = match statistics.score {
+            Some(score) => translate_with("statistics-score", &[&format!("{:.0}", score)]),
+            None => translate("statistics-nothing-sung").to_string(),
+        };
+        let streak = translate_with("statistics-streak", &[&format!("{:.1}", statistics.longest_streak_seconds)]);
+        let text_layout = TextLayout { lines: [score, streak], color: scheme.text, viewport };
+        if self.text_layout.as_ref() != Some(&text_layout) {
+            let color = [scheme.text[0], scheme.text[1], scheme.text[2], 1.0];
+            let label_color = [scheme.muted[0], scheme.muted[1], scheme.muted[2], 1.0];
+            // Text is placed by its top edge
+            let mut texts: Vec<_> = text_layout.lines.iter()
+                .enumerate()
+                .map(|(line, text)| (text.clone(), PADDING, height - PADDING - line as f32 * text_height, TEXT_SIZE, color, line == 0))
+                .collect();
+            texts.extend((0..12).map(|semitones| {
+                (semitone_to_interval_name(semitones), PADDING + pitch * semitones as f32, PADDING + BAR_LABEL_SIZE, BAR_LABEL_SIZE, label_color, false)
+            }));
+            self.texts = text_backend.render_texts(context, viewport, &texts, three_d::egui::Align::LEFT);
+            self.text_layout = Some(text_layout);
+        }
+    }
+
+    pub fn objects(&self) -> Vec<&dyn Object> {
+        let mut objects: Vec<&dyn Object> = vec![&self.background];
+        objects.extend(self.bars.iter().zip(self.sung).filter(|(_, sung)| *sung).map(|(bar, _)| bar as &dyn Object));
+        objects.extend(self.texts.iter().map(|text| text.as_ref()));
+        objects
+    }
+}
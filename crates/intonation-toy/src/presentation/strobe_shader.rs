@@ -0,0 +1,86 @@
+use three_d::*;
+
+/// Concentric strobe rings drawn in the largest circle fitting the quad
+pub struct StrobeMaterial {
+    /// Drift of the innermost ring in bands; ring `k` drifts `2^k` times as far
+    pub phase: f32,
+    /// Width over height of the quad, to keep the rings round
+    pub aspect_ratio: f32,
+    pub band_count: u32,
+    pub ring_count: u32,
+    pub band_color: Vec3,
+    pub background_color: Vec3,
+    /// Bands fade towards the background while no pitch is detected
+    pub detected: bool,
+}
+
+impl Material for StrobeMaterial {
+    fn id(&self) -> EffectMaterialId {
+        EffectMaterialId(0x1235)
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        r#"
+            uniform float phase;
+            uniform float aspectRatio;
+            uniform float bandCount;
+            uniform float ringCount;
+            uniform vec3 bandColor;
+            uniform vec3 backgroundColor;
+            uniform float detected;
+
+            in vec2 uvs;
+            out vec4 fragColor;
+
+            const float PI = 3.14159265;
+            const float INNER_RADIUS = 0.3;
+            const float OUTER_RADIUS = 0.9;
+            // Fraction of each ring left empty to separate it from the next
+            const float RING_GAP = 0.15;
+
+            void main() {
+                // Radius 1.0 touches the shorter side of the quad
+                vec2 position = (uvs - 0.5) * 2.0 * vec2(max(aspectRatio, 1.0), max(1.0 / aspectRatio, 1.0));
+                float radius = length(position);
+                float ringPosition = (radius - INNER_RADIUS) / (OUTER_RADIUS - INNER_RADIUS) * ringCount;
+                float ring = floor(ringPosition);
+
+                float coverage = 0.0;
+                if (ring >= 0.0 && ring < ringCount && fract(ringPosition) > RING_GAP) {
+                    // Bands move clockwise as the phase grows
+                    float turn = atan(position.y, position.x) / (2.0 * PI);
+                    float band = turn * bandCount + phase * exp2(ring);
+                    // Soften band edges over about one pixel
+                    float edgeWidth = bandCount * length(fwidth(position)) / (2.0 * PI * radius);
+                    float distanceFromEdge = abs(fract(band) - 0.5) - 0.25;
+                    coverage = clamp(0.5 - distanceFromEdge / edgeWidth, 0.0, 1.0);
+                }
+
+                float strength = detected > 0.0 ? 1.0 : 0.3;
+                fragColor = vec4(mix(backgroundColor, bandColor, coverage * strength), 1.0);
+            }
+        "#.to_string()
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &dyn Viewer, _lights: &[&dyn Light]) {
+        program.use_uniform("phase", self.phase);
+        program.use_uniform("aspectRatio", self.aspect_ratio);
+        program.use_uniform("bandCount", self.band_count as f32);
+        program.use_uniform("ringCount", self.ring_count as f32);
+        program.use_uniform("bandColor", self.band_color);
+        program.use_uniform("backgroundColor", self.background_color);
+        program.use_uniform("detected", if self.detected { 1.0f32 } else { 0.0 });
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::LessOrEqual,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
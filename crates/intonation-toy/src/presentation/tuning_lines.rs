@@ -1,6 +1,6 @@
-#![cfg(target_arch = "wasm32")]
-
 use three_d::{Blend, ColorMaterial, Context, Gm, Line, Object, PhysicalPoint, RenderStates, Srgba, Viewport, WriteMask};
+use crate::common::label_layout::{place_labels, LabelAnchor, LabelPlacement};
+use crate::common::note_naming::NoteNamer;
 use crate::common::shared_types::MidiNote;
 use crate::common::theme::{get_current_color_scheme, rgb_to_rgba, rgb_to_srgba_with_alpha};
 use crate::app_config::{NOTE_LABEL_FONT_SIZE, NOTE_LABEL_X_OFFSET, NOTE_LABEL_Y_OFFSET, INTERVAL_LABEL_X_OFFSET, NOTE_LINE_LEFT_MARGIN, NOTE_LINE_RIGHT_MARGIN};
@@ -92,46 +92,48 @@ impl TuningLines {
     }
     
 
-    pub fn get_note_labels(&self, color_mode: ColorMode) -> Vec<(String, f32, f32, f32, [f32; 4], bool)> {
-        let scheme = get_current_color_scheme();
-
-        self.line_data.iter()
-            .map(|data| {
-                let note_name = crate::common::shared_types::midi_note_to_name(data.midi_note);
-                let text_y = data.y_position + NOTE_LABEL_Y_OFFSET;
-                let text_x = NOTE_LABEL_X_OFFSET;
-                let is_bold = data.semitone_offset % 12 == 0;
-
-                let text_color = match color_mode {
-                    ColorMode::Highlight => rgb_to_rgba(scheme.accent),
-                    ColorMode::Normal => if is_bold { rgb_to_rgba(scheme.primary) } else { rgb_to_rgba(scheme.muted) },
-                };
-
-                (note_name, text_x, text_y, NOTE_LABEL_FONT_SIZE, text_color, is_bold)
-            })
-            .collect()
+    /// Note names beside the lines, moved apart, faded or left out where the
+    /// lines crowd together
+    pub fn get_note_labels(&self, color_mode: ColorMode, note_namer: &NoteNamer) -> Vec<(String, f32, f32, f32, [f32; 4], bool)> {
+        self.labels(color_mode, NOTE_LABEL_X_OFFSET, |data| note_namer.name(data.midi_note))
     }
 
+    /// Interval names at the right end of the lines, placed like the note names
     pub fn get_interval_labels(&self, viewport_width: f32, color_mode: ColorMode) -> Vec<(String, f32, f32, f32, [f32; 4], bool)> {
+        self.labels(color_mode, viewport_width - INTERVAL_LABEL_X_OFFSET, |data| {
+            crate::common::music_theory::semitone_to_interval_name(data.semitone_offset)
+        })
+    }
+
+    fn labels(&self, color_mode: ColorMode, text_x: f32, text: impl Fn(&LineData) -> String) -> Vec<(String, f32, f32, f32, [f32; 4], bool)> {
         let scheme = get_current_color_scheme();
 
         self.line_data.iter()
-            .map(|data| {
-                let interval_name = crate::common::music_theory::semitone_to_interval_name(data.semitone_offset);
-                let text_y = data.y_position + NOTE_LABEL_Y_OFFSET;
-                let text_x = viewport_width - INTERVAL_LABEL_X_OFFSET;
+            .zip(self.label_placements())
+            .filter_map(|(data, placement)| {
+                let LabelPlacement { y, alpha } = placement?;
+                let text_y = y + NOTE_LABEL_Y_OFFSET;
                 let is_bold = data.semitone_offset % 12 == 0;
 
-                let text_color = match color_mode {
+                let [r, g, b, _] = match color_mode {
                     ColorMode::Highlight => rgb_to_rgba(scheme.accent),
                     ColorMode::Normal => if is_bold { rgb_to_rgba(scheme.primary) } else { rgb_to_rgba(scheme.muted) },
                 };
+                // Text colors are premultiplied
+                let text_color = [r * alpha, g * alpha, b * alpha, alpha];
 
-                (interval_name, text_x, text_y, NOTE_LABEL_FONT_SIZE, text_color, is_bold)
+                Some((text(data), text_x, text_y, NOTE_LABEL_FONT_SIZE, text_color, is_bold))
             })
             .collect()
     }
 
+    fn label_placements(&self) -> Vec<Option<LabelPlacement>> {
+        let anchors: Vec<_> = self.line_data.iter()
+            .map(|data| LabelAnchor { y: data.y_position, priority: data.semitone_offset % 12 == 0 })
+            .collect();
+        place_labels(&anchors, NOTE_LABEL_FONT_SIZE)
+    }
+
     pub fn get_lines(&self, context: &Context, viewport_width: f32, color_mode: ColorMode) -> Vec<Box<dyn Object>> {
         let scheme = get_current_color_scheme();
 
@@ -1,7 +1,5 @@
-#![cfg(target_arch = "wasm32")]
-
 use three_d::{Blend, ColorMaterial, Context, Gm, Line, PhysicalPoint, RenderStates, WriteMask};
-use crate::app_config::{INTONATION_ACCURACY_THRESHOLD, USER_PITCH_LINE_THICKNESS};
+use crate::app_config::USER_PITCH_LINE_THICKNESS;
 use crate::presentation::audio_analysis::AudioAnalysis;
 use crate::common::shared_types::ColorScheme;
 use crate::common::theme::rgb_to_srgba_with_alpha;
@@ -38,7 +36,7 @@ impl UserPitchLine {
     fn create_material(&self, color_scheme: &ColorScheme, audio_analysis: &AudioAnalysis) -> ColorMaterial {
         let color = if audio_analysis.volume_peak {
             color_scheme.error
-        } else if audio_analysis.cents_offset.abs() < INTONATION_ACCURACY_THRESHOLD {
+        } else if audio_analysis.cents_offset.abs() < audio_analysis.tolerance_cents {
             COLOR_SUCCESS
         } else {
             COLOR_WARNING
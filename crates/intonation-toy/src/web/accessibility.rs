@@ -0,0 +1,20 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Live regions that screen readers read out as their text changes
+
+fn set_region_text(id: &str, text: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(region) = document.get_element_by_id(id) {
+        region.set_text_content(Some(text));
+    }
+}
+
+/// Read out when the screen reader is idle
+pub fn announce(text: &str) {
+    set_region_text("pitch-announcements", text);
+}
+
+/// Read out at once, interrupting what is being read
+pub fn alert(text: &str) {
+    set_region_text("alert-announcements", text);
+}
@@ -0,0 +1,195 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Catching crashes and errors, and copying bug reports
+//!
+//! A panic is logged to the console as before, then stops the app with the
+//! error overlay, whose button copies a report of it. Errors the browser
+//! reports, thrown or unhandled in promises, are logged and make the report
+//! of the About section, while the app carries on. Where the clipboard is
+//! out of reach, the report shows in a text box to copy by hand.
+//!
+//! The panic hook only records and builds the report; the app cannot go on
+//! after a panic, so the copy button reads the finished report from the page.
+
+use std::cell::{Cell, RefCell};
+use std::panic::PanicHookInfo;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use crate::common::bug_report::{BugReport, CrashInfo, EventLog};
+use crate::common::localization::translate;
+
+thread_local! {
+    static LOG: RefCell<EventLog> = RefCell::new(EventLog::default());
+    /// The panic, or else the latest error the browser reported
+    static CRASH: RefCell<Option<CrashInfo>> = const { RefCell::new(None) };
+    static SNAPSHOT: RefCell<Option<String>> = const { RefCell::new(None) };
+    static PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Copy buttons, with the text box that shows their report where the
+/// clipboard is out of reach
+const COPY_BUTTONS: [(&str, &str); 2] = [
+    ("bug-report-copy", "bug-report-text"),
+    ("about-bug-report-copy", "about-bug-report-text"),
+];
+
+/// Catch panics and errors, and set up the copy buttons
+pub fn setup_bug_reports() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        on_panic(info);
+    }));
+
+    let Some(window) = web_sys::window() else { return };
+    let error_closure = Closure::wrap(Box::new(move |event: web_sys::ErrorEvent| {
+        let stack = js_sys::Reflect::get(&event.error(), &"stack".into()).ok().and_then(|stack| stack.as_string());
+        record_error(CrashInfo {
+            message: event.message(),
+            location: Some(format!("{}:{}:{}", event.filename(), event.lineno(), event.colno())),
+            stack,
+        });
+    }) as Box<dyn FnMut(_)>);
+    let _ = window.add_event_listener_with_callback("error", error_closure.as_ref().unchecked_ref());
+    error_closure.forget();
+
+    let rejection_closure = Closure::wrap(Box::new(move |event: web_sys::PromiseRejectionEvent| {
+        let reason = event.reason();
+        let stack = js_sys::Reflect::get(&reason, &"stack".into()).ok().and_then(|stack| stack.as_string());
+        let message = reason
+            .dyn_ref::<js_sys::Error>()
+            .map(|error| String::from(error.message()))
+            .or_else(|| reason.as_string())
+            .unwrap_or_else(|| format!("{:?}", reason));
+        record_error(CrashInfo { message: format!("Unhandled rejection: {}", message), location: None, stack });
+    }) as Box<dyn FnMut(_)>);
+    let _ = window.add_event_listener_with_callback("unhandledrejection", rejection_closure.as_ref().unchecked_ref());
+    rejection_closure.forget();
+
+    let Some(document) = window.document() else { return };
+    for (button_id, text_id) in COPY_BUTTONS {
+        let Some(button) = document.get_element_by_id(button_id) else { continue };
+        let copy_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            // A panic leaves its report in the page; otherwise it is made now
+            let report = if PANICKED.with(Cell::get) {
+                text_box(COPY_BUTTONS[0].1).map(|text| text.value()).unwrap_or_default()
+            } else {
+                build_report()
+            };
+            copy_report(button_id, text_id, report);
+        }) as Box<dyn FnMut(_)>);
+        let _ = button.add_event_listener_with_callback("click", copy_closure.as_ref().unchecked_ref());
+        copy_closure.forget();
+    }
+}
+
+/// Add an entry to the log of bug reports
+pub fn log_event(message: &str) {
+    let _ = LOG.try_with(|log| {
+        if let Ok(mut log) = log.try_borrow_mut() {
+            log.push(js_sys::Date::now(), message);
+        }
+    });
+}
+
+/// Keep the JSON of the latest session snapshot for bug reports
+pub fn set_snapshot(snapshot: &crate::common::shared_types::ModelSnapshot) {
+    let json = serde_json::to_string(snapshot).ok();
+    SNAPSHOT.with(|stored| *stored.borrow_mut() = json);
+}
+
+fn on_panic(info: &PanicHookInfo) {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Panic".to_string());
+    let crash = CrashInfo {
+        message,
+        location: info.location().map(|location| format!("{}:{}:{}", location.file(), location.line(), location.column())),
+        stack: js_sys::Reflect::get(&js_sys::Error::new(""), &"stack".into()).ok().and_then(|stack| stack.as_string()),
+    };
+    let _ = PANICKED.try_with(|panicked| panicked.set(true));
+    let _ = CRASH.try_with(|stored| {
+        if let Ok(mut stored) = stored.try_borrow_mut() {
+            *stored = Some(crash);
+        }
+    });
+
+    let report = build_report();
+    if let Some(text) = text_box(COPY_BUTTONS[0].1) {
+        text.set_value(&report);
+    }
+    crate::web::notifications::show_fatal_error(&crate::common::shared_types::Error::InternalError);
+}
+
+fn record_error(crash: CrashInfo) {
+    crate::common::warn_log!("Uncaught error: {}", crash.message);
+    // A panic is what stopped the app, so it stays
+    if !PANICKED.with(Cell::get) {
+        CRASH.with(|stored| *stored.borrow_mut() = Some(crash));
+    }
+}
+
+fn build_report() -> String {
+    let user_agent = web_sys::window().and_then(|window| window.navigator().user_agent().ok()).unwrap_or_default();
+    let crash = CRASH.try_with(|crash| crash.try_borrow().ok().and_then(|crash| crash.clone())).ok().flatten();
+    let snapshot = SNAPSHOT.try_with(|snapshot| snapshot.try_borrow().ok().and_then(|snapshot| snapshot.clone())).ok().flatten();
+    LOG.try_with(|log| {
+        let log = log.try_borrow().map(|log| log.clone()).unwrap_or_default();
+        BugReport {
+            version: env!("CARGO_PKG_VERSION"),
+            user_agent: &user_agent,
+            crash: crash.as_ref(),
+            log: &log,
+            snapshot: snapshot.as_deref(),
+            now_ms: js_sys::Date::now(),
+        }
+        .to_markdown()
+    })
+    .unwrap_or_default()
+}
+
+fn text_box(id: &str) -> Option<web_sys::HtmlTextAreaElement> {
+    web_sys::window()?.document()?.get_element_by_id(id)?.dyn_into().ok()
+}
+
+/// Copy the report, or show it to copy by hand where the clipboard is out of reach
+/// Copy the report to the clipboard, or show it in the text box when the
+/// clipboard is missing or refuses it
+fn copy_report(button_id: &'static str, text_id: &'static str, report: String) {
+    let Some(window) = web_sys::window() else { return };
+    let clipboard = js_sys::Reflect::get(&window.navigator(), &"clipboard".into()).unwrap_or(JsValue::UNDEFINED);
+    let write_text = js_sys::Reflect::get(&clipboard, &"writeText".into()).ok().and_then(|write_text| write_text.dyn_into::<js_sys::Function>().ok());
+    let promise = write_text
+        .and_then(|write_text| write_text.call1(&clipboard, &report.as_str().into()).ok())
+        .and_then(|promise| promise.dyn_into::<js_sys::Promise>().ok());
+    let Some(promise) = promise else {
+        show_report(text_id, &report);
+        return;
+    };
+
+    // Awaited so that a refused write is not left to the rejection handler
+    wasm_bindgen_futures::spawn_local(async move {
+        match wasm_bindgen_futures::JsFuture::from(promise).await {
+            Ok(_) => {
+                if let Some(button) = web_sys::window().and_then(|window| window.document()).and_then(|document| document.get_element_by_id(button_id)) {
+                    button.set_text_content(Some(translate("bug-report-copied")));
+                }
+                crate::web::accessibility::announce(translate("bug-report-copied"));
+            }
+            Err(_e) => {
+                crate::common::dev_log!("Failed to copy the bug report: {:?}", _e);
+                show_report(text_id, &report);
+            }
+        }
+    });
+}
+
+fn show_report(text_id: &str, report: &str) {
+    if let Some(text) = text_box(text_id) {
+        text.set_value(report);
+        let _ = text.style().set_property("display", "");
+        text.select();
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(target_arch = "wasm32")]
+
+//! The capability report of the running page, and the list of what is
+//! limited in this browser in the About section
+
+use std::cell::RefCell;
+use crate::common::capabilities::CapabilityReport;
+use crate::common::localization::translate;
+
+thread_local! {
+    static REPORT: RefCell<CapabilityReport> = RefCell::new(CapabilityReport::default());
+}
+
+/// Keep `report` for the rest of the page's life and show its limitations
+pub fn set_report(report: CapabilityReport) {
+    REPORT.with(|stored| *stored.borrow_mut() = report);
+    update_limitations();
+}
+
+pub fn report() -> CapabilityReport {
+    REPORT.with(|stored| stored.borrow().clone())
+}
+
+/// List the limitations in the current language; hidden without any
+pub fn update_limitations() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let limitations = report().limitations();
+    if let Some(list) = document.get_element_by_id("capability-limits-list") {
+        list.set_inner_html("");
+        for limitation in &limitations {
+            if let Ok(item) = document.create_element("li") {
+                item.set_text_content(Some(translate(limitation.key())));
+                let _ = list.append_child(&item);
+            }
+        }
+    }
+    if let Some(container) = document.get_element_by_id("capability-limits")
+        .and_then(|container| wasm_bindgen::JsCast::dyn_into::<web_sys::HtmlElement>(container).ok())
+    {
+        let _ = container.style().set_property("display", if limitations.is_empty() { "none" } else { "" });
+    }
+}
@@ -0,0 +1,52 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Settings in the address of the page: read once at startup, and written
+//! back as they change, so the address is always a link to share. An
+//! exercise in the address starts once and is not written back.
+
+use std::cell::RefCell;
+use crate::common::deep_link::DeepLink;
+use crate::common::dev_log;
+
+thread_local! {
+    /// Query last written to the address, to write only when it changes
+    static WRITTEN_QUERY: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Settings of the address the page was opened at; the query wins over the
+/// fragment where both set the same setting
+pub fn startup_link() -> DeepLink {
+    let Some(location) = web_sys::window().map(|window| window.location()) else { return DeepLink::default() };
+    let query = DeepLink::parse(&location.search().unwrap_or_default());
+    let fragment = DeepLink::parse(&location.hash().unwrap_or_default());
+    DeepLink {
+        tonal_center_note: query.tonal_center_note.or(fragment.tonal_center_note),
+        scale: query.scale.or(fragment.scale),
+        tuning_system: query.tuning_system.or(fragment.tuning_system),
+        exercise: query.exercise.or(fragment.exercise),
+    }
+}
+
+/// Replace the address with one carrying `link`, without adding to the history
+pub fn write_to_address(link: &DeepLink) {
+    let query = link.to_query();
+    if WRITTEN_QUERY.with(|written| *written.borrow() == query) {
+        return;
+    }
+    let Some(window) = web_sys::window() else { return };
+    let Ok(history) = window.history() else { return };
+    let path = window.location().pathname().unwrap_or_default();
+    if let Err(_e) = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&format!("{}{}", path, query))) {
+        dev_log!("Failed to write settings to the address: {:?}", _e);
+        return;
+    }
+    WRITTEN_QUERY.with(|written| *written.borrow_mut() = query);
+}
+
+/// Copy the address, with the current settings, to the clipboard
+pub fn copy_link() {
+    if let Some(href) = web_sys::window().and_then(|window| window.location().href().ok()) {
+        crate::web::utils::copy_to_clipboard(href);
+        crate::web::accessibility::announce(crate::common::localization::translate("share-link-copied"));
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+
+/// Offer `bytes` to the user as a file download
+pub fn download_bytes(filename: &str, mime_type: &str, bytes: &[u8]) -> Result<(), String> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|e| format!("Failed to create blob: {:?}", e))?;
+    download_blob(filename, &blob)
+}
+
+/// Offer `blob` to the user as a file download
+pub fn download_blob(filename: &str, blob: &web_sys::Blob) -> Result<(), String> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+
+    let url = web_sys::Url::create_object_url_with_blob(blob)
+        .map_err(|e| format!("Failed to create object URL: {:?}", e))?;
+
+    let anchor = document.create_element("a")
+        .map_err(|e| format!("Failed to create link: {:?}", e))?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "Created element is not an anchor")?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url)
+        .map_err(|e| format!("Failed to revoke object URL: {:?}", e))
+}
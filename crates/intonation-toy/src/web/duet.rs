@@ -0,0 +1,319 @@
+#![cfg(target_arch = "wasm32")]
+#![cfg(feature = "duet")]
+
+//! Duet mode over WebRTC
+//!
+//! The teacher's app offers a connection with a data channel and the
+//! student's app answers it. Without a signaling server the offer and the
+//! answer go between the apps as codes the users send each other, which
+//! `CodeSignaling` shows in the sidebar. Once the channel is open, every
+//! frame sends what the `DuetLink` has for the other app.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    HtmlElement, HtmlTextAreaElement, MessageEvent, RtcConfiguration, RtcDataChannel, RtcDataChannelEvent, RtcDataChannelInit,
+    RtcDataChannelState, RtcDataChannelType, RtcIceGatheringState, RtcIceServer, RtcPeerConnection, RtcPeerConnectionState,
+    RtcSdpType, RtcSessionDescriptionInit,
+};
+use crate::app_config::DUET_ICE_SERVERS;
+use crate::common::dev_log;
+use crate::common::duet::{DuetAction, DuetLink, DuetMessage, DuetRole, SignalMessage, Signaling};
+use crate::common::localization::{translate, translate_with};
+use crate::common::music_theory::frequency_to_midi_note_and_cents;
+use crate::common::shared_types::{ModelUpdateResult, Pitch};
+use crate::presentation::Presenter;
+
+thread_local! {
+    static DUET: RefCell<Option<Duet>> = const { RefCell::new(None) };
+}
+
+struct Duet {
+    connection: RtcPeerConnection,
+    channel: Option<RtcDataChannel>,
+    link: DuetLink,
+    /// Readout of the student's pitch as last shown
+    readout: String,
+}
+
+/// Shows the codes for the other app in the sidebar, for the user to send
+struct CodeSignaling;
+
+impl Signaling for CodeSignaling {
+    fn send(&self, message: &SignalMessage) {
+        if let Some(code) = text_area("duet-local-code") {
+            code.set_value(&message.to_code());
+            let _ = code.style().set_property("display", "");
+            code.select();
+        }
+        set_status(match message {
+            SignalMessage::Offer(_) => "duet-status-offer",
+            SignalMessage::Answer(_) => "duet-status-answer",
+        });
+    }
+}
+
+/// Show the duet section and handle its buttons
+pub fn setup_duet(presenter: Rc<RefCell<Presenter>>) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(section) = document.get_element_by_id("duet-section").and_then(|section| section.dyn_into::<HtmlElement>().ok()) {
+        let _ = section.style().set_property("display", "");
+    }
+
+    let host_presenter = presenter.clone();
+    add_listener(&document, "duet-host-button", move |_| {
+        let presenter = host_presenter.clone();
+        spawn_local(async move {
+            if let Err(_e) = host(presenter).await {
+                dev_log!("Failed to offer a duet: {:?}", _e);
+                leave();
+            }
+        });
+    });
+    add_listener(&document, "duet-connect-button", move |_| {
+        let code = text_area("duet-remote-code").map(|code| code.value()).unwrap_or_default();
+        let message = match SignalMessage::from_code(&code) {
+            Ok(message) => message,
+            Err(_e) => {
+                dev_log!("{}", _e);
+                set_status("duet-invalid-code");
+                return;
+            }
+        };
+        let presenter = presenter.clone();
+        spawn_local(async move {
+            if let Err(_e) = on_signal(message, presenter).await {
+                dev_log!("Failed to connect the duet: {:?}", _e);
+                leave();
+            }
+        });
+    });
+    add_listener(&document, "duet-leave-button", |_| leave());
+}
+
+/// Send the reference or the pitch, and show the student's pitch to the teacher
+pub fn on_frame(model_data: &ModelUpdateResult) {
+    let now_ms = js_sys::Date::now();
+    DUET.with(|duet| {
+        let mut duet = duet.borrow_mut();
+        let Some(Duet { channel: Some(channel), link, readout, .. }) = duet.as_mut() else { return };
+        if channel.ready_state() != RtcDataChannelState::Open {
+            return;
+        }
+
+        let message = match link.role() {
+            DuetRole::Teacher => link.reference_to_send(
+                now_ms,
+                model_data.tonal_center_note,
+                model_data.concert_pitch_hz,
+                crate::web::sidebar_controls::current_tonal_center_amplitude(),
+            ),
+            DuetRole::Student => {
+                let frequency_hz = match model_data.pitch {
+                    Pitch::Detected(frequency) => Some(frequency),
+                    Pitch::NotDetected => None,
+                };
+                link.pitch_to_send(now_ms, frequency_hz)
+            }
+        };
+        if let Some(message) = message
+            && let Err(_e) = channel.send_with_u8_array(&message.encode())
+        {
+            dev_log!("Failed to send a duet message: {:?}", _e);
+        }
+
+        if link.role() == DuetRole::Teacher {
+            let reading = link.remote_pitch(now_ms).and_then(|frequency| {
+                frequency_to_midi_note_and_cents(frequency, model_data.tonal_center_note, model_data.concert_pitch_hz, &model_data.tuning_system, model_data.scale)
+            });
+            let text = match reading {
+                Some((note, cents)) => translate_with("duet-remote-pitch", &[&model_data.note_namer().name(note), &format!("{:+.0}", cents)]),
+                None => translate("duet-remote-silent").to_string(),
+            };
+            if *readout != text {
+                set_text("duet-remote-pitch", &text);
+                *readout = text;
+            }
+        }
+    });
+}
+
+async fn host(presenter: Rc<RefCell<Presenter>>) -> Result<(), JsValue> {
+    let connection = connect(DuetRole::Teacher)?;
+    // Pitches are soon outdated, so a lost one is not sent again; the
+    // `DuetLink` resends the reference itself
+    let init = RtcDataChannelInit::new();
+    init.set_ordered(false);
+    init.set_max_retransmits(0);
+    setup_channel(connection.create_data_channel_with_data_channel_dict("duet", &init), presenter);
+
+    let offer = JsFuture::from(connection.create_offer()).await?;
+    JsFuture::from(connection.set_local_description(offer.unchecked_ref())).await?;
+    CodeSignaling.send(&SignalMessage::Offer(gathered_description(&connection).await));
+    Ok(())
+}
+
+async fn on_signal(message: SignalMessage, presenter: Rc<RefCell<Presenter>>) -> Result<(), JsValue> {
+    match message {
+        SignalMessage::Offer(sdp) => {
+            let connection = connect(DuetRole::Student)?;
+            let channel_closure = Closure::wrap(Box::new(move |event: RtcDataChannelEvent| {
+                setup_channel(event.channel(), presenter.clone());
+            }) as Box<dyn FnMut(_)>);
+            connection.set_ondatachannel(Some(channel_closure.as_ref().unchecked_ref()));
+            channel_closure.forget();
+
+            JsFuture::from(connection.set_remote_description(&description(RtcSdpType::Offer, &sdp))).await?;
+            let answer = JsFuture::from(connection.create_answer()).await?;
+            JsFuture::from(connection.set_local_description(answer.unchecked_ref())).await?;
+            CodeSignaling.send(&SignalMessage::Answer(gathered_description(&connection).await));
+        }
+        SignalMessage::Answer(sdp) => {
+            let connection = DUET.with(|duet| {
+                duet.borrow().as_ref().filter(|duet| duet.link.role() == DuetRole::Teacher).map(|duet| duet.connection.clone())
+            });
+            let Some(connection) = connection else {
+                set_status("duet-invalid-code");
+                return Ok(());
+            };
+            JsFuture::from(connection.set_remote_description(&description(RtcSdpType::Answer, &sdp))).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A new peer connection in the given role, replacing any other
+fn connect(role: DuetRole) -> Result<RtcPeerConnection, JsValue> {
+    leave();
+    let ice_servers = js_sys::Array::new();
+    for url in DUET_ICE_SERVERS {
+        let server = RtcIceServer::new();
+        server.set_urls_str(url);
+        ice_servers.push(&server);
+    }
+    let configuration = RtcConfiguration::new();
+    configuration.set_ice_servers(&ice_servers);
+    let connection = RtcPeerConnection::new_with_configuration(&configuration)?;
+
+    let watched = connection.clone();
+    let state_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        dev_log!("Duet connection is {:?}", watched.connection_state());
+        match watched.connection_state() {
+            RtcPeerConnectionState::Connected => set_status("duet-status-connected"),
+            RtcPeerConnectionState::Disconnected | RtcPeerConnectionState::Failed | RtcPeerConnectionState::Closed => {
+                set_status("duet-status-disconnected");
+                set_text("duet-remote-pitch", "");
+            }
+            _ => {}
+        }
+    }) as Box<dyn FnMut(_)>);
+    connection.set_onconnectionstatechange(Some(state_closure.as_ref().unchecked_ref()));
+    state_closure.forget();
+
+    DUET.with(|duet| {
+        *duet.borrow_mut() = Some(Duet { connection: connection.clone(), channel: None, link: DuetLink::new(role), readout: String::new() });
+    });
+    Ok(connection)
+}
+
+fn setup_channel(channel: RtcDataChannel, presenter: Rc<RefCell<Presenter>>) {
+    channel.set_binary_type(RtcDataChannelType::Arraybuffer);
+
+    let open_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        DUET.with(|duet| {
+            if let Some(duet) = duet.borrow_mut().as_mut() {
+                duet.link.resend_reference();
+            }
+        });
+        set_status("duet-status-connected");
+    }) as Box<dyn FnMut(_)>);
+    channel.set_onopen(Some(open_closure.as_ref().unchecked_ref()));
+    open_closure.forget();
+
+    let message_closure = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let bytes = js_sys::Uint8Array::new(&event.data()).to_vec();
+        let message = match DuetMessage::decode(&bytes) {
+            Ok(message) => message,
+            Err(_e) => {
+                dev_log!("Ignoring duet message: {}", _e);
+                return;
+            }
+        };
+        let action = DUET.with(|duet| duet.borrow_mut().as_mut().and_then(|duet| duet.link.on_message(message, js_sys::Date::now())));
+        if let Some(DuetAction::ApplyReference { tonal_center_note, concert_pitch_hz, drone_amplitude }) = action {
+            let mut presenter = presenter.borrow_mut();
+            presenter.on_tonal_center_configured(true, tonal_center_note, drone_amplitude);
+            presenter.on_concert_pitch_configured(concert_pitch_hz);
+        }
+    }) as Box<dyn FnMut(_)>);
+    channel.set_onmessage(Some(message_closure.as_ref().unchecked_ref()));
+    message_closure.forget();
+
+    DUET.with(|duet| {
+        if let Some(duet) = duet.borrow_mut().as_mut() {
+            duet.channel = Some(channel);
+        }
+    });
+}
+
+/// The local description once all its ICE candidates are gathered
+async fn gathered_description(connection: &RtcPeerConnection) -> String {
+    if connection.ice_gathering_state() != RtcIceGatheringState::Complete {
+        let gathered = js_sys::Promise::new(&mut |resolve, _reject| {
+            let watched = connection.clone();
+            let gathering_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+                if watched.ice_gathering_state() == RtcIceGatheringState::Complete {
+                    let _ = resolve.call0(&JsValue::NULL);
+                }
+            }) as Box<dyn FnMut(_)>);
+            connection.set_onicegatheringstatechange(Some(gathering_closure.as_ref().unchecked_ref()));
+            gathering_closure.forget();
+        });
+        let _ = JsFuture::from(gathered).await;
+    }
+    connection.local_description().map(|description| description.sdp()).unwrap_or_default()
+}
+
+fn description(sdp_type: RtcSdpType, sdp: &str) -> RtcSessionDescriptionInit {
+    let description = RtcSessionDescriptionInit::new(sdp_type);
+    description.set_sdp(sdp);
+    description
+}
+
+fn leave() {
+    if let Some(duet) = DUET.with(|duet| duet.borrow_mut().take()) {
+        if let Some(channel) = duet.channel {
+            channel.close();
+        }
+        duet.connection.close();
+    }
+    if let Some(code) = text_area("duet-local-code") {
+        let _ = code.style().set_property("display", "none");
+    }
+    set_text("duet-remote-pitch", "");
+    set_status("duet-status-disconnected");
+}
+
+fn set_status(key: &'static str) {
+    set_text("duet-status", translate(key));
+}
+
+fn set_text(element_id: &str, text: &str) {
+    if let Some(element) = web_sys::window().and_then(|window| window.document()).and_then(|document| document.get_element_by_id(element_id)) {
+        element.set_text_content(Some(text));
+    }
+}
+
+fn text_area(id: &str) -> Option<HtmlTextAreaElement> {
+    web_sys::window()?.document()?.get_element_by_id(id)?.dyn_into().ok()
+}
+
+fn add_listener(document: &web_sys::Document, element_id: &str, handler: impl FnMut(web_sys::Event) + 'static) {
+    let Some(element) = document.get_element_by_id(element_id) else { return };
+    let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+    let _ = element.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref());
+    closure.forget();
+}
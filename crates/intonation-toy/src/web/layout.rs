@@ -0,0 +1,142 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use crate::common::pane_layout::Splitter;
+
+/// Body class that lets the canvas fill the window and hides the sidebar
+const IMMERSIVE_CLASS: &str = "immersive-layout";
+/// Body class that shows the sidebar in the immersive layout
+const CONTROLS_VISIBLE_CLASS: &str = "immersive-controls-visible";
+/// Body class of the touch-first layout, with the controls in a drawer
+const TOUCH_CLASS: &str = "touch-layout";
+/// Body class that opens the controls drawer of the touch layout
+const TOUCH_CONTROLS_OPEN_CLASS: &str = "touch-controls-open";
+
+static IMMERSIVE: AtomicBool = AtomicBool::new(false);
+static TOUCH: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static LAST_POINTER_MOVE_MS: Cell<f64> = const { Cell::new(0.0) };
+    static CANVAS_CURSOR: Cell<&'static str> = const { Cell::new("") };
+}
+
+/// Track pointer movement for hiding the controls, keep the fullscreen
+/// button label in step with the fullscreen state, and open and close the
+/// controls drawer of the touch layout
+pub fn setup_layout_listeners() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+
+    let pointer_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        LAST_POINTER_MOVE_MS.with(|last_ms| last_ms.set(js_sys::Date::now()));
+    }) as Box<dyn FnMut(_)>);
+    let _ = document.add_event_listener_with_callback("mousemove", pointer_closure.as_ref().unchecked_ref());
+    pointer_closure.forget();
+
+    let fullscreen_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        update_fullscreen_button();
+    }) as Box<dyn FnMut(_)>);
+    let _ = document.add_event_listener_with_callback("fullscreenchange", fullscreen_closure.as_ref().unchecked_ref());
+    fullscreen_closure.forget();
+
+    if let Some(toggle) = document.get_element_by_id("touch-controls-toggle") {
+        let toggle_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            let open = web_sys::window()
+                .and_then(|window| window.document())
+                .and_then(|document| document.body())
+                .is_some_and(|body| body.class_list().contains(TOUCH_CONTROLS_OPEN_CLASS));
+            set_touch_controls_open(!open);
+        }) as Box<dyn FnMut(_)>);
+        let _ = toggle.add_event_listener_with_callback("click", toggle_closure.as_ref().unchecked_ref());
+        toggle_closure.forget();
+    }
+
+    // Some mobile browsers report the old window size on the resize event of
+    // a rotation, but the new one once it has finished
+    if let Some(window) = web_sys::window() {
+        let orientation_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            crate::web::utils::resize_canvas();
+        }) as Box<dyn FnMut(_)>);
+        let _ = window.add_event_listener_with_callback("orientationchange", orientation_closure.as_ref().unchecked_ref());
+        orientation_closure.forget();
+    }
+}
+
+/// Label the fullscreen button with what clicking it does
+pub fn update_fullscreen_button() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(button) = document.get_element_by_id("fullscreen-button") {
+        let key = if document.fullscreen_element().is_some() { "fullscreen-exit" } else { "fullscreen-button" };
+        button.set_text_content(Some(crate::common::localization::translate(key)));
+    }
+}
+
+/// Enter fullscreen, or leave it; must be called from a user gesture
+pub fn toggle_fullscreen() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if document.fullscreen_element().is_some() {
+        document.exit_fullscreen();
+    } else if let Some(root) = document.document_element()
+        && let Err(_e) = root.request_fullscreen()
+    {
+        crate::common::dev_log!("Fullscreen request failed: {:?}", _e);
+    }
+}
+
+pub fn is_immersive() -> bool {
+    IMMERSIVE.load(Ordering::Relaxed)
+}
+
+pub fn set_immersive(immersive: bool) {
+    IMMERSIVE.store(immersive, Ordering::Relaxed);
+    set_body_class(IMMERSIVE_CLASS, immersive);
+    crate::web::utils::resize_canvas();
+}
+
+pub fn set_controls_visible(visible: bool) {
+    set_body_class(CONTROLS_VISIBLE_CLASS, visible);
+}
+
+pub fn is_touch() -> bool {
+    TOUCH.load(Ordering::Relaxed)
+}
+
+/// Use the touch-first layout, or the sidebar layout for mouse and keyboard
+pub fn set_touch(touch: bool) {
+    TOUCH.store(touch, Ordering::Relaxed);
+    set_body_class(TOUCH_CLASS, touch);
+    crate::web::utils::resize_canvas();
+}
+
+fn set_touch_controls_open(open: bool) {
+    set_body_class(TOUCH_CONTROLS_OPEN_CLASS, open);
+    if let Some(toggle) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("touch-controls-toggle"))
+    {
+        let _ = toggle.set_attribute("aria-expanded", if open { "true" } else { "false" });
+    }
+}
+
+pub fn last_pointer_move_ms() -> f64 {
+    LAST_POINTER_MOVE_MS.with(Cell::get)
+}
+
+/// Show a resize cursor while the pointer is over a splitter between panes
+pub fn set_splitter_cursor(splitter: Option<Splitter>) {
+    let cursor = match splitter {
+        Some(Splitter::Column) => "col-resize",
+        Some(Splitter::Row(_)) => "row-resize",
+        None => "",
+    };
+    if CANVAS_CURSOR.with(|current| current.replace(cursor)) != cursor {
+        let _ = crate::web::utils::get_canvas().style().set_property("cursor", cursor);
+    }
+}
+
+fn set_body_class(class: &str, present: bool) {
+    let Some(body) = web_sys::window().and_then(|window| window.document()).and_then(|document| document.body()) else { return };
+    let _ = if present { body.class_list().add_1(class) } else { body.class_list().remove_1(class) };
+}
@@ -0,0 +1,54 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element};
+use crate::common::localization::{current_language, translate_in, Language};
+
+/// Language of the browser, if it is one with a translation
+pub fn browser_language() -> Option<Language> {
+    web_sys::window()
+        .and_then(|window| window.navigator().language())
+        .and_then(|code| Language::from_code(&code))
+}
+
+/// Show the page in the current language, falling back to English for texts
+/// without a translation
+pub fn apply_language() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let language = current_language();
+    if let Some(root) = document.document_element() {
+        let _ = root.set_attribute("lang", language.code());
+    }
+
+    for_each_element(&document, "data-i18n", |element, text| element.set_text_content(Some(text)));
+    for_each_element(&document, "data-i18n-title", |element, text| {
+        let _ = element.set_attribute("title", text);
+    });
+    for_each_element(&document, "data-i18n-alt", |element, text| {
+        let _ = element.set_attribute("alt", text);
+    });
+    for_each_element(&document, "data-i18n-aria-label", |element, text| {
+        let _ = element.set_attribute("aria-label", text);
+    });
+
+    crate::web::layout::update_fullscreen_button();
+    crate::web::picture_in_picture::update_mini_view_button();
+    crate::web::saved_sessions::refresh_list();
+    crate::web::capabilities::update_limitations();
+}
+
+/// Call `apply` with every element carrying a key in `attribute` and the text
+/// of that key
+fn for_each_element(document: &Document, attribute: &str, apply: impl Fn(&Element, &str)) {
+    let Ok(elements) = document.query_selector_all(&format!("[{}]", attribute)) else { return };
+    let language = current_language();
+    for index in 0..elements.length() {
+        let Some(element) = elements.item(index).and_then(|node| node.dyn_into::<Element>().ok()) else { continue };
+        let Some(key) = element.get_attribute(attribute) else { continue };
+        let Some(text) = translate_in(language, &key).or_else(|| translate_in(Language::English, &key)) else {
+            crate::common::dev_log!("No text for key {}", key);
+            continue;
+        };
+        apply(&element, text);
+    }
+}
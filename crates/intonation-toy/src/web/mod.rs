@@ -3,12 +3,29 @@
 //! Web platform specific functionality
 //! This module contains browser-specific code that handles web APIs and DOM interactions
 
-pub mod error_message_box;
+pub mod accessibility;
+pub mod bug_report;
+pub mod capabilities;
+pub mod deep_link;
+pub mod download;
+pub mod duet;
+pub mod layout;
+pub mod localization;
+pub mod notifications;
 pub mod sidebar_controls;
 pub mod storage;
 pub mod performance;
+pub mod picture_in_picture;
 pub mod profiling;
+pub mod pwa;
+pub mod saved_sessions;
+pub mod session_store;
+pub mod session_summary;
+pub mod share_image;
 pub mod styling;
+pub mod tab_coordination;
+pub mod telemetry;
 pub mod three_d;
+pub mod tutorial;
 pub mod utils;
 pub mod user_media_permission;
\ No newline at end of file
@@ -0,0 +1,89 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Fatal errors in an overlay over the app, other errors in toasts in its
+//! top right corner
+
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use crate::common::localization::translate;
+use crate::common::shared_types::Error;
+
+/// Attribute of a toast and its close button with the id of the toast
+pub const TOAST_ID_ATTRIBUTE: &str = "data-toast-id";
+
+fn show_fatal_error_box(title: &str, details: &str) {
+    crate::common::error_log!("Error: {} - {}", title, details);
+
+    let Some(window) = web_sys::window() else { return };
+    let Some(document) = window.document() else { return };
+
+    let Some(overlay) = document.get_element_by_id("error-message-overlay") else { return };
+    let Some(title_el) = document.get_element_by_id("error-title") else { return };
+    let Some(details_el) = document.get_element_by_id("error-details") else { return };
+    let Ok(overlay) = overlay.dyn_into::<HtmlElement>() else { return };
+
+    // The first fatal error is what stopped the app, so it stays
+    if overlay.style().get_property_value("display").is_ok_and(|display| display == "flex") {
+        return;
+    }
+
+    title_el.set_text_content(Some(title));
+    details_el.set_text_content(Some(details));
+    crate::web::accessibility::alert(&format!("{}. {}", title, details));
+    let _ = overlay.style().set_property("display", "flex");
+    if let Some(copy) = document.get_element_by_id("bug-report-copy").and_then(|copy| copy.dyn_into::<HtmlElement>().ok()) {
+        let _ = copy.focus();
+    }
+}
+
+pub fn show_fatal_error(error: &Error) {
+    show_fatal_error_box(error.title(), &error.message());
+}
+pub fn show_fatal_error_with_params(error: &Error, params: &[&str]) {
+    let details = error.details_with(params);
+    show_fatal_error_box(error.title(), &details);
+}
+
+/// Add a toast to the toast container, which reads it out as it appears
+pub fn show_toast(id: u32, error: &Error) {
+    crate::common::warn_log!("{} - {}", error.title(), error.message());
+
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let Some(container) = document.get_element_by_id("toast-container") else { return };
+    let id = id.to_string();
+    let build = || -> Result<web_sys::Element, wasm_bindgen::JsValue> {
+        let toast = document.create_element("div")?;
+        toast.set_class_name("toast");
+        toast.set_attribute("role", "status")?;
+        toast.set_attribute(TOAST_ID_ATTRIBUTE, &id)?;
+
+        let title = document.create_element("div")?;
+        title.set_class_name("toast-title");
+        title.set_text_content(Some(error.title()));
+        let details = document.create_element("div")?;
+        details.set_class_name("toast-details");
+        details.set_text_content(Some(&error.message()));
+        let close = document.create_element("button")?;
+        close.set_class_name("toast-close");
+        close.set_attribute("type", "button")?;
+        close.set_attribute("aria-label", translate("toast-dismiss"))?;
+        close.set_attribute(TOAST_ID_ATTRIBUTE, &id)?;
+        close.set_text_content(Some("×"));
+
+        toast.append_child(&close)?;
+        toast.append_child(&title)?;
+        toast.append_child(&details)?;
+        Ok(toast)
+    };
+    if let Err(_e) = build().and_then(|toast| container.append_child(&toast)) {
+        crate::common::dev_log!("Failed to show toast: {:?}", _e);
+    }
+}
+
+pub fn remove_toast(id: u32) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let selector = format!("#toast-container .toast[{}=\"{}\"]", TOAST_ID_ATTRIBUTE, id);
+    if let Ok(Some(toast)) = document.query_selector(&selector) {
+        toast.remove();
+    }
+}
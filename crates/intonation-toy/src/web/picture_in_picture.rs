@@ -0,0 +1,296 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Mini view of the tuner kept over other tabs and windows: the closest note
+//! and a bar of how far off it is, drawn on a small canvas of its own. The
+//! canvas goes into a document picture-in-picture window where the browser
+//! has one, else it plays as a stream in a video shown picture-in-picture.
+//!
+//! Browsers stop animation frames in hidden tabs, so while the view is open
+//! a timer runs the background update set by the app whenever the page is
+//! hidden, to keep the pitch coming.
+
+use std::cell::RefCell;
+use gloo_timers::callback::Interval;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlVideoElement};
+use crate::app_config::{MINI_VIEW_BACKGROUND_UPDATE_MS, MINI_VIEW_HEIGHT, MINI_VIEW_WIDTH, NEEDLE_RANGE_CENTS};
+use crate::common::localization::translate;
+use crate::common::theme::get_current_color_scheme;
+use crate::web::utils::rgb_to_css;
+
+const NOTE_FONT: &str = "bold 40px sans-serif";
+const CENTS_FONT: &str = "14px sans-serif";
+/// Cents bar along the bottom of the view, in pixels of the canvas
+const BAR_MARGIN: f64 = 16.0;
+const BAR_HEIGHT: f64 = 8.0;
+const BAR_BOTTOM: f64 = 20.0;
+const MARKER_WIDTH: f64 = 4.0;
+
+/// Closest note and its offset, as the mini view shows them
+pub struct MiniViewReading {
+    pub note_name: String,
+    pub cents: f32,
+    pub in_tune: bool,
+}
+
+/// Where the canvas is shown
+enum Host {
+    /// Waiting for the browser to open the window or the video
+    Opening,
+    Window(web_sys::Window),
+    Video(HtmlVideoElement),
+}
+
+struct MiniView {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    host: Host,
+    _background_timer: Interval,
+}
+
+thread_local! {
+    static MINI_VIEW: RefCell<Option<MiniView>> = const { RefCell::new(None) };
+    static BACKGROUND_UPDATE: RefCell<Option<Box<dyn FnMut()>>> = const { RefCell::new(None) };
+}
+
+/// Whether the browser can show the mini view in either way
+pub fn is_supported() -> bool {
+    let Some(window) = web_sys::window() else { return false };
+    let document_picture_in_picture = js_sys::Reflect::get(&window, &"documentPictureInPicture".into())
+        .is_ok_and(|api| !api.is_undefined());
+    document_picture_in_picture || video_picture_in_picture_enabled()
+}
+
+pub fn is_open() -> bool {
+    MINI_VIEW.with(|view| view.borrow().is_some())
+}
+
+/// Update to run on the timer while the view is open and the page is hidden
+pub fn set_background_update(update: impl FnMut() + 'static) {
+    BACKGROUND_UPDATE.with(|background_update| *background_update.borrow_mut() = Some(Box::new(update)));
+}
+
+/// Open the mini view, or close it; must be called from a user gesture
+pub fn toggle_mini_view() {
+    if is_open() {
+        close_mini_view();
+        return;
+    }
+    match create_canvas() {
+        Ok((canvas, context)) => {
+            draw(&canvas, &context, None);
+            let background_timer = Interval::new(MINI_VIEW_BACKGROUND_UPDATE_MS, run_background_update);
+            MINI_VIEW.with(|view| *view.borrow_mut() = Some(MiniView { canvas: canvas.clone(), context, host: Host::Opening, _background_timer: background_timer }));
+            update_mini_view_button();
+            wasm_bindgen_futures::spawn_local(open(canvas));
+        }
+        Err(e) => crate::common::error_log!("Failed to create mini view: {:?}", e),
+    }
+}
+
+pub fn close_mini_view() {
+    let Some(view) = MINI_VIEW.with(|view| view.borrow_mut().take()) else { return };
+    close_host(view.host);
+    update_mini_view_button();
+}
+
+/// Draw the reading, or a dash while no pitch is detected; does nothing
+/// while the view is closed
+pub fn update_mini_view(reading: Option<MiniViewReading>) {
+    MINI_VIEW.with(|view| {
+        if let Some(view) = view.borrow().as_ref() {
+            draw(&view.canvas, &view.context, reading.as_ref());
+        }
+    });
+}
+
+/// Label the mini view button with what clicking it does
+pub fn update_mini_view_button() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(button) = document.get_element_by_id("mini-view-button") {
+        let key = if is_open() { "mini-view-close" } else { "mini-view-button" };
+        button.set_text_content(Some(translate(key)));
+    }
+}
+
+fn close_host(host: Host) {
+    match host {
+        Host::Opening => {}
+        Host::Window(window) => {
+            let _ = window.close();
+        }
+        Host::Video(video) => {
+            let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+            let shown = js_sys::Reflect::get(&document, &"pictureInPictureElement".into()).is_ok_and(|element| element == JsValue::from(video));
+            if shown
+                && let Err(_e) = call_method(&document, "exitPictureInPicture")
+            {
+                crate::common::dev_log!("Failed to leave picture-in-picture: {:?}", _e);
+            }
+        }
+    }
+}
+
+fn run_background_update() {
+    let hidden = web_sys::window().and_then(|window| window.document()).is_some_and(|document| document.hidden());
+    if !hidden {
+        return;
+    }
+    let update = BACKGROUND_UPDATE.with(|background_update| background_update.borrow_mut().take());
+    if let Some(mut update) = update {
+        update();
+        BACKGROUND_UPDATE.with(|background_update| {
+            background_update.borrow_mut().get_or_insert(update);
+        });
+    }
+}
+
+fn create_canvas() -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let canvas = document.create_element("canvas")?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_width(MINI_VIEW_WIDTH);
+    canvas.set_height(MINI_VIEW_HEIGHT);
+    let context = canvas.get_context("2d")?
+        .ok_or("No 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    Ok((canvas, context))
+}
+
+async fn open(canvas: HtmlCanvasElement) {
+    let host = match open_window(&canvas).await {
+        Ok(Some(window)) => Ok(Host::Window(window)),
+        Ok(None) => open_video(&canvas).await.map(Host::Video),
+        Err(e) => Err(e),
+    };
+    match host {
+        Ok(host) => {
+            // Unless the view was closed while the browser was opening it
+            let host = MINI_VIEW.with(|view| match view.borrow_mut().as_mut() {
+                Some(view) if view.canvas == canvas => {
+                    view.host = host;
+                    None
+                }
+                _ => Some(host),
+            });
+            if let Some(host) = host {
+                close_host(host);
+            }
+        }
+        Err(e) => {
+            crate::common::error_log!("Failed to open mini view: {:?}", e);
+            forget_view(&canvas);
+        }
+    }
+}
+
+/// The document picture-in-picture window holding the canvas, `None` where
+/// the browser has no such windows
+async fn open_window(canvas: &HtmlCanvasElement) -> Result<Option<web_sys::Window>, JsValue> {
+    let window = web_sys::window().ok_or("No window")?;
+    let api = js_sys::Reflect::get(&window, &"documentPictureInPicture".into())?;
+    if api.is_undefined() {
+        return Ok(None);
+    }
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"width".into(), &MINI_VIEW_WIDTH.into())?;
+    js_sys::Reflect::set(&options, &"height".into(), &MINI_VIEW_HEIGHT.into())?;
+    let request_window = js_sys::Reflect::get(&api, &"requestWindow".into())?.dyn_into::<js_sys::Function>()?;
+    let promise = request_window.call1(&api, &options)?.dyn_into::<js_sys::Promise>()?;
+    let window = wasm_bindgen_futures::JsFuture::from(promise).await?.dyn_into::<web_sys::Window>()?;
+
+    let body = window.document().and_then(|document| document.body()).ok_or("No body in the mini view window")?;
+    let _ = body.style().set_property("margin", "0");
+    let _ = body.style().set_property("background", &rgb_to_css(get_current_color_scheme().background));
+    let _ = canvas.style().set_property("width", "100%");
+    let _ = canvas.style().set_property("height", "100%");
+    body.append_child(canvas)?;
+    on_closed_by_user(&window, "pagehide", canvas)?;
+    Ok(Some(window))
+}
+
+async fn open_video(canvas: &HtmlCanvasElement) -> Result<HtmlVideoElement, JsValue> {
+    if !video_picture_in_picture_enabled() {
+        return Err("No picture-in-picture".into());
+    }
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let video = document.create_element("video")?.dyn_into::<HtmlVideoElement>()?;
+    video.set_muted(true);
+    video.set_src_object(Some(&canvas.capture_stream()?));
+    wasm_bindgen_futures::JsFuture::from(video.play()?).await?;
+    let promise = call_method(&video, "requestPictureInPicture")?.dyn_into::<js_sys::Promise>()?;
+    wasm_bindgen_futures::JsFuture::from(promise).await?;
+    on_closed_by_user(&video, "leavepictureinpicture", canvas)?;
+    Ok(video)
+}
+
+fn video_picture_in_picture_enabled() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| js_sys::Reflect::get(&document, &"pictureInPictureEnabled".into()).ok())
+        .is_some_and(|enabled| enabled.is_truthy())
+}
+
+/// Forget the view of `canvas` when the user closes it from the browser
+fn on_closed_by_user(target: &web_sys::EventTarget, event: &str, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+    let canvas = canvas.clone();
+    let closure = Closure::once_into_js(move || forget_view(&canvas));
+    target.add_event_listener_with_callback(event, closure.unchecked_ref())
+}
+
+/// Forget the view of `canvas` unless another view took its place, as when
+/// the host of a closed view goes away only after a new one opened
+fn forget_view(canvas: &HtmlCanvasElement) {
+    let forgotten = MINI_VIEW.with(|view| {
+        let mut view = view.borrow_mut();
+        let current = view.as_ref().is_some_and(|view| view.canvas == *canvas);
+        if current {
+            view.take();
+        }
+        current
+    });
+    if forgotten {
+        update_mini_view_button();
+    }
+}
+
+fn call_method(target: &JsValue, name: &str) -> Result<JsValue, JsValue> {
+    js_sys::Reflect::get(target, &name.into())?
+        .dyn_into::<js_sys::Function>()?
+        .call0(target)
+}
+
+fn draw(canvas: &HtmlCanvasElement, context: &CanvasRenderingContext2d, reading: Option<&MiniViewReading>) {
+    let scheme = get_current_color_scheme();
+    let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+    context.set_fill_style_str(&rgb_to_css(scheme.background));
+    context.fill_rect(0.0, 0.0, width, height);
+
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+    context.set_font(NOTE_FONT);
+    context.set_fill_style_str(&rgb_to_css(scheme.text));
+    let note_name = reading.map_or("–", |reading| reading.note_name.as_str());
+    let _ = context.fill_text(note_name, width * 0.5, height * 0.35);
+
+    let bar_top = height - BAR_BOTTOM - BAR_HEIGHT;
+    let bar_width = width - 2.0 * BAR_MARGIN;
+    context.set_fill_style_str(&rgb_to_css(scheme.surface));
+    context.fill_rect(BAR_MARGIN, bar_top, bar_width, BAR_HEIGHT);
+    context.set_fill_style_str(&rgb_to_css(scheme.muted));
+    context.fill_rect(width * 0.5 - 1.0, bar_top - 2.0, 2.0, BAR_HEIGHT + 4.0);
+
+    let Some(reading) = reading else { return };
+    let cents = reading.cents.clamp(-NEEDLE_RANGE_CENTS, NEEDLE_RANGE_CENTS) as f64;
+    let marker_x = width * 0.5 + cents / NEEDLE_RANGE_CENTS as f64 * bar_width * 0.5;
+    let color = rgb_to_css(if reading.in_tune { scheme.accent } else { scheme.error });
+    context.set_fill_style_str(&color);
+    context.fill_rect(marker_x - MARKER_WIDTH * 0.5, bar_top - 4.0, MARKER_WIDTH, BAR_HEIGHT + 8.0);
+
+    context.set_font(CENTS_FONT);
+    let _ = context.fill_text(&format!("{:+.0}¢", reading.cents), width * 0.5, bar_top - 16.0);
+}
@@ -0,0 +1,96 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Installing the app and running it offline
+//!
+//! The service worker caches the files of the build it was registered for;
+//! it is registered with the file name of the wasm module, which Trunk
+//! hashes, so a new build brings a new worker and a new cache, which take
+//! over once no tab runs the older build any more. Browsers that
+//! offer to install the app fire `beforeinstallprompt`; the prompt is kept
+//! and the install button shown until the app is installed.
+
+use std::cell::RefCell;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use crate::app_config::SERVICE_WORKER_URL;
+use crate::common::dev_log;
+
+thread_local! {
+    /// The `beforeinstallprompt` event, kept to prompt from the install button
+    static INSTALL_PROMPT: RefCell<Option<web_sys::Event>> = const { RefCell::new(None) };
+}
+
+/// Register the service worker, where the browser has them and the page was
+/// built with a hashed wasm module
+pub fn register_service_worker() {
+    let Some(window) = web_sys::window() else { return };
+    let has_service_workers = js_sys::Reflect::has(&window.navigator(), &"serviceWorker".into()).unwrap_or(false);
+    if !has_service_workers {
+        dev_log!("Service workers are not available");
+        return;
+    }
+    let Some(build) = wasm_module_href() else {
+        dev_log!("No wasm module link to register the service worker with");
+        return;
+    };
+    let url = format!("{}?build={}", SERVICE_WORKER_URL, String::from(js_sys::encode_uri_component(&build)));
+    let promise = window.navigator().service_worker().register(&url);
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(_e) = wasm_bindgen_futures::JsFuture::from(promise).await {
+            dev_log!("Failed to register service worker: {:?}", _e);
+        }
+    });
+}
+
+/// Address of the wasm module as Trunk preloads it
+fn wasm_module_href() -> Option<String> {
+    web_sys::window()?
+        .document()?
+        .query_selector("link[rel=preload][type='application/wasm']")
+        .ok()??
+        .get_attribute("href")
+}
+
+/// Keep the install prompt when the browser offers one, and show the
+/// install button while it is kept
+pub fn setup_install_prompt() {
+    let Some(window) = web_sys::window() else { return };
+
+    let prompt_closure = Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+        // Keeps the browser from showing its own prompt
+        event.prevent_default();
+        INSTALL_PROMPT.with(|prompt| *prompt.borrow_mut() = Some(event));
+        show_install_button(true);
+    });
+    let _ = window.add_event_listener_with_callback("beforeinstallprompt", prompt_closure.as_ref().unchecked_ref());
+    prompt_closure.forget();
+
+    let installed_closure = Closure::<dyn FnMut()>::new(move || {
+        INSTALL_PROMPT.with(|prompt| prompt.borrow_mut().take());
+        show_install_button(false);
+    });
+    let _ = window.add_event_listener_with_callback("appinstalled", installed_closure.as_ref().unchecked_ref());
+    installed_closure.forget();
+}
+
+/// Show the kept install prompt; a prompt can only be shown once, so the
+/// button hides until the browser offers another
+pub fn prompt_install() {
+    let Some(event) = INSTALL_PROMPT.with(|prompt| prompt.borrow_mut().take()) else { return };
+    show_install_button(false);
+    let prompt = js_sys::Reflect::get(&event, &"prompt".into())
+        .and_then(|prompt| prompt.dyn_into::<js_sys::Function>())
+        .and_then(|prompt| prompt.call0(&event));
+    if let Err(_e) = prompt {
+        dev_log!("Failed to show install prompt: {:?}", _e);
+    }
+}
+
+fn show_install_button(shown: bool) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    for id in ["install-app-row", "install-app-help"] {
+        if let Some(element) = document.get_element_by_id(id).and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok()) {
+            let _ = element.style().set_property("display", if shown { "" } else { "none" });
+        }
+    }
+}
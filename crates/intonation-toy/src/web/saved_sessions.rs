@@ -0,0 +1,178 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Saving sessions in the browser, and the list of saved sessions in the
+//! sidebar with the space they take
+//!
+//! A session that does not fit is not saved; the user makes room by
+//! deleting saved sessions from the list.
+
+use wasm_bindgen::JsCast;
+use web_sys::Document;
+use crate::app_config::{RECORDING_EXPORT_FILENAME, SESSION_EXPORT_FILENAME};
+use crate::common::localization::{current_language, translate, translate_with};
+use crate::common::saved_session::{SavedSession, SavedSessionSummary};
+use crate::web::session_store::{self, StoreError};
+
+/// Save `session` with its recording, then show it in the list
+pub fn save_session(session: SavedSession, recording: Option<Vec<u8>>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match store_session(&session, recording.as_deref()).await {
+            Ok(()) => {
+                set_status(translate("saved-sessions-saved"));
+                crate::web::accessibility::announce(translate("saved-sessions-saved"));
+            }
+            Err(StoreError::QuotaExceeded) => {
+                set_status(translate("saved-sessions-full"));
+                crate::web::accessibility::alert(translate("saved-sessions-full"));
+            }
+            Err(StoreError::Other(_e)) => {
+                crate::common::error_log!("Failed to save session: {}", _e);
+                set_status(translate("saved-sessions-failed"));
+            }
+        }
+        show_sessions().await;
+    });
+}
+
+/// Fill the list of saved sessions and the space they take
+pub fn refresh_list() {
+    wasm_bindgen_futures::spawn_local(show_sessions());
+}
+
+/// Download or delete the session of a clicked list button
+pub fn on_list_click(event: web_sys::Event) {
+    let Some(button) = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()) else { return };
+    let Some(saved_ms) = button.get_attribute("data-saved-ms").and_then(|saved_ms| saved_ms.parse::<f64>().ok()) else { return };
+    match button.get_attribute("data-action").as_deref() {
+        Some("download") => wasm_bindgen_futures::spawn_local(download_session(saved_ms)),
+        Some("delete") => wasm_bindgen_futures::spawn_local(async move {
+            if let Err(_e) = session_store::delete_sessions(&[saved_ms]).await {
+                crate::common::error_log!("Failed to delete saved session: {:?}", _e);
+            }
+            show_sessions().await;
+        }),
+        _ => {}
+    }
+}
+
+async fn store_session(session: &SavedSession, recording: Option<&[u8]>) -> Result<(), StoreError> {
+    let json = session.to_json().map_err(StoreError::Other)?;
+    let summary = session.summary(js_sys::Date::now(), json.len(), recording.map(<[u8]>::len));
+
+    if let Some((usage, quota)) = session_store::storage_estimate().await
+        && summary.bytes as f64 > quota - usage
+    {
+        return Err(StoreError::QuotaExceeded);
+    }
+    session_store::put_session(&summary, &json, recording).await
+}
+
+async fn download_session(saved_ms: f64) {
+    let (session, recording) = match session_store::get_session(saved_ms).await {
+        Ok(stored) => stored,
+        Err(_e) => {
+            crate::common::error_log!("Failed to load saved session: {:?}", _e);
+            return;
+        }
+    };
+    let json = match serde_json::to_string_pretty(&session) {
+        Ok(json) => json,
+        Err(_e) => {
+            crate::common::error_log!("Failed to serialize saved session: {}", _e);
+            return;
+        }
+    };
+    if let Err(_e) = crate::web::download::download_bytes(SESSION_EXPORT_FILENAME, "application/json", json.as_bytes()) {
+        crate::common::error_log!("Failed to download saved session: {}", _e);
+    }
+    if let Some(recording) = recording
+        && let Err(_e) = crate::web::download::download_blob(RECORDING_EXPORT_FILENAME, &recording)
+    {
+        crate::common::error_log!("Failed to download saved recording: {}", _e);
+    }
+}
+
+async fn show_sessions() {
+    let summaries = match session_store::list_summaries().await {
+        Ok(summaries) => summaries,
+        Err(_e) => {
+            crate::common::dev_log!("Failed to list saved sessions: {:?}", _e);
+            Vec::new()
+        }
+    };
+    let estimate = session_store::storage_estimate().await;
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+
+    if let Some(usage) = document.get_element_by_id("saved-sessions-usage") {
+        let text = estimate.map(|(usage, quota)| translate_with("saved-sessions-usage", &[&megabytes(usage), &megabytes(quota)]));
+        usage.set_text_content(text.as_deref());
+    }
+
+    let Some(list) = document.get_element_by_id("saved-sessions-list") else { return };
+    list.set_inner_html("");
+    if summaries.is_empty() {
+        if let Ok(empty) = document.create_element("div") {
+            empty.set_class_name("help-text");
+            empty.set_text_content(Some(translate("saved-sessions-empty")));
+            let _ = list.append_child(&empty);
+        }
+        return;
+    }
+    for summary in summaries.iter().rev() {
+        if let Err(_e) = append_row(&document, &list, summary) {
+            crate::common::dev_log!("Failed to show saved session: {:?}", _e);
+        }
+    }
+}
+
+/// One row per session, newest first: when it was saved, what it holds, and its buttons
+fn append_row(document: &Document, list: &web_sys::Element, summary: &SavedSessionSummary) -> Result<(), wasm_bindgen::JsValue> {
+    let row = document.create_element("div")?;
+    row.set_class_name("control-row");
+
+    let label = document.create_element("span")?;
+    label.set_class_name("saved-session-label");
+    label.set_text_content(Some(&session_label(summary)));
+    row.append_child(&label)?;
+
+    for (action, key) in [("download", "saved-session-download"), ("delete", "saved-session-delete")] {
+        let button = document.create_element("button")?;
+        button.set_class_name("small-button");
+        button.set_text_content(Some(translate(key)));
+        button.set_attribute("data-action", action)?;
+        button.set_attribute("data-saved-ms", &summary.saved_ms.to_string())?;
+        row.append_child(&button)?;
+    }
+    list.append_child(&row)?;
+    Ok(())
+}
+
+/// Date and time of saving, the time sung, the score, and whether a recording is kept
+fn session_label(summary: &SavedSessionSummary) -> String {
+    let date = js_sys::Date::new(&summary.saved_ms.into());
+    let options = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&options, &"dateStyle".into(), &"short".into());
+    let _ = js_sys::Reflect::set(&options, &"timeStyle".into(), &"short".into());
+    let mut label = String::from(date.to_locale_string(current_language().code(), &options));
+
+    let seconds = summary.sung_seconds.round() as u32;
+    label.push_str(&format!(" · {}:{:02}", seconds / 60, seconds % 60));
+    if let Some(score) = summary.score {
+        label.push_str(&format!(" · {:.0}%", score));
+    }
+    if summary.has_recording {
+        label.push_str(&format!(" · {}", translate("saved-session-recording")));
+    }
+    label
+}
+
+fn megabytes(bytes: f64) -> String {
+    format!("{:.1}", bytes / 1_000_000.0)
+}
+
+fn set_status(text: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(status) = document.get_element_by_id("saved-sessions-status") {
+        status.set_text_content(Some(text));
+    }
+}
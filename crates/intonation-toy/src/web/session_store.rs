@@ -0,0 +1,181 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Saved sessions in IndexedDB
+//!
+//! The database has one object store each for the session summaries, the
+//! sessions and their recordings, all keyed by the time the session was
+//! saved. Summaries and sessions are stored as JSON, recordings as WAV blobs.
+//! Every operation runs in one transaction, and completes once the
+//! transaction does, so a session is never kept in part.
+
+use std::cell::RefCell;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, IdbDatabase, IdbRequest, IdbTransaction, IdbTransactionMode};
+use crate::app_config::{SESSION_DATABASE_NAME, SESSION_DATABASE_VERSION};
+use crate::common::saved_session::{SavedSession, SavedSessionSummary};
+
+const SUMMARY_STORE: &str = "summaries";
+const SESSION_STORE: &str = "sessions";
+const RECORDING_STORE: &str = "recordings";
+
+thread_local! {
+    static DATABASE: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// Why an operation failed
+#[derive(Debug)]
+pub enum StoreError {
+    /// The browser has no room left for the data
+    QuotaExceeded,
+    Other(String),
+}
+
+impl From<JsValue> for StoreError {
+    fn from(value: JsValue) -> Self {
+        let name = js_sys::Reflect::get(&value, &"name".into()).ok().and_then(|name| name.as_string());
+        match name.as_deref() {
+            Some("QuotaExceededError") => StoreError::QuotaExceeded,
+            _ => StoreError::Other(format!("{:?}", value)),
+        }
+    }
+}
+
+/// Space the page uses and may use, in bytes, where the browser tells
+pub async fn storage_estimate() -> Option<(f64, f64)> {
+    let storage = js_sys::Reflect::get(&web_sys::window()?.navigator(), &"storage".into()).ok()?;
+    let estimate = js_sys::Reflect::get(&storage, &"estimate".into()).ok()?.dyn_into::<js_sys::Function>().ok()?;
+    let promise = estimate.call0(&storage).ok()?.dyn_into::<js_sys::Promise>().ok()?;
+    let estimate = JsFuture::from(promise).await.ok()?;
+    let number = |name: &str| js_sys::Reflect::get(&estimate, &name.into()).ok().and_then(|value| value.as_f64());
+    Some((number("usage")?, number("quota")?))
+}
+
+/// Summaries of every saved session, oldest first
+pub async fn list_summaries() -> Result<Vec<SavedSessionSummary>, StoreError> {
+    let transaction = transaction(&[SUMMARY_STORE], IdbTransactionMode::Readonly).await?;
+    let values = request_result(&transaction.object_store(SUMMARY_STORE)?.get_all()?).await?;
+    let summaries = js_sys::Array::from(&values)
+        .iter()
+        .filter_map(|value| value.as_string())
+        .filter_map(|json| SavedSessionSummary::from_json(&json).ok())
+        .collect();
+    Ok(summaries)
+}
+
+/// Store a session with its summary and recording
+pub async fn put_session(summary: &SavedSessionSummary, session_json: &str, recording: Option<&[u8]>) -> Result<(), StoreError> {
+    let summary_json = summary.to_json().map_err(StoreError::Other)?;
+    let key = JsValue::from_f64(summary.saved_ms);
+    let transaction = transaction(&[SUMMARY_STORE, SESSION_STORE, RECORDING_STORE], IdbTransactionMode::Readwrite).await?;
+    transaction.object_store(SUMMARY_STORE)?.put_with_key(&summary_json.into(), &key)?;
+    transaction.object_store(SESSION_STORE)?.put_with_key(&session_json.into(), &key)?;
+    if let Some(recording) = recording {
+        transaction.object_store(RECORDING_STORE)?.put_with_key(&wav_blob(recording)?.into(), &key)?;
+    }
+    transaction_complete(&transaction).await
+}
+
+/// The session saved at `saved_ms` and its recording, if it has one
+pub async fn get_session(saved_ms: f64) -> Result<(SavedSession, Option<Blob>), StoreError> {
+    let key = JsValue::from_f64(saved_ms);
+    let transaction = transaction(&[SESSION_STORE, RECORDING_STORE], IdbTransactionMode::Readonly).await?;
+    let session_request = transaction.object_store(SESSION_STORE)?.get(&key)?;
+    let recording_request = transaction.object_store(RECORDING_STORE)?.get(&key)?;
+    let json = request_result(&session_request).await?.as_string().ok_or(StoreError::Other("No saved session".to_string()))?;
+    let recording = request_result(&recording_request).await?.dyn_into::<Blob>().ok();
+    Ok((SavedSession::from_json(&json).map_err(StoreError::Other)?, recording))
+}
+
+/// Delete the sessions saved at `saved_ms`, with their summaries and recordings
+pub async fn delete_sessions(saved_ms: &[f64]) -> Result<(), StoreError> {
+    let stores = [SUMMARY_STORE, SESSION_STORE, RECORDING_STORE];
+    let transaction = transaction(&stores, IdbTransactionMode::Readwrite).await?;
+    for key in saved_ms.iter().map(|&saved_ms| JsValue::from_f64(saved_ms)) {
+        for store in stores {
+            transaction.object_store(store)?.delete(&key)?;
+        }
+    }
+    transaction_complete(&transaction).await
+}
+
+fn wav_blob(bytes: &[u8]) -> Result<Blob, JsValue> {
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("audio/wav");
+    Blob::new_with_u8_array_sequence_and_options(&js_sys::Array::of1(&js_sys::Uint8Array::from(bytes)), &options)
+}
+
+async fn transaction(stores: &[&str], mode: IdbTransactionMode) -> Result<IdbTransaction, StoreError> {
+    let database = database().await?;
+    let names: js_sys::Array = stores.iter().map(|&store| JsValue::from_str(store)).collect();
+    Ok(database.transaction_with_str_sequence_and_mode(&names, mode)?)
+}
+
+/// The database, opened and upgraded on first use
+async fn database() -> Result<IdbDatabase, StoreError> {
+    if let Some(database) = DATABASE.with(|database| database.borrow().clone()) {
+        return Ok(database);
+    }
+    let factory = web_sys::window()
+        .and_then(|window| window.indexed_db().ok().flatten())
+        .ok_or(StoreError::Other("IndexedDB is not available".to_string()))?;
+    let request = factory.open_with_u32(SESSION_DATABASE_NAME, SESSION_DATABASE_VERSION)?;
+
+    let upgrade_request = request.clone();
+    let on_upgrade_needed = Closure::<dyn FnMut()>::new(move || {
+        let Ok(database) = upgrade_request.result().and_then(|result| result.dyn_into::<IdbDatabase>()) else { return };
+        let existing = database.object_store_names();
+        for store in [SUMMARY_STORE, SESSION_STORE, RECORDING_STORE] {
+            if !existing.contains(store)
+                && let Err(_e) = database.create_object_store(store)
+            {
+                crate::common::error_log!("Failed to create object store {}: {:?}", store, _e);
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade_needed.as_ref().unchecked_ref()));
+
+    let database = request_result(&request).await?.dyn_into::<IdbDatabase>()?;
+    request.set_onupgradeneeded(None);
+    drop(on_upgrade_needed);
+    DATABASE.with(|cached| *cached.borrow_mut() = Some(database.clone()));
+    Ok(database)
+}
+
+/// Result of a request once it succeeds
+async fn request_result(request: &IdbRequest) -> Result<JsValue, StoreError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let _ = resolve.call1(&JsValue::NULL, &succeeded.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = failed.error().ok().flatten().map(JsValue::from).unwrap_or(JsValue::UNDEFINED);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    Ok(JsFuture::from(promise).await?)
+}
+
+/// Completion of a transaction, failing when it is aborted; a failed request
+/// aborts its transaction
+async fn transaction_complete(transaction: &IdbTransaction) -> Result<(), StoreError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move || {
+            let _ = resolve.call0(&JsValue::NULL);
+        });
+        let failed = transaction.clone();
+        let on_abort = Closure::once_into_js(move || {
+            let error = failed.error().map(JsValue::from).unwrap_or(JsValue::UNDEFINED);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        transaction.set_oncomplete(Some(on_complete.unchecked_ref()));
+        transaction.set_onabort(Some(on_abort.unchecked_ref()));
+    });
+    JsFuture::from(promise).await?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsCast;
+use web_sys::{Document, HtmlElement};
+use crate::common::localization::{translate, translate_with};
+use crate::common::music_theory::semitone_to_interval_name;
+use crate::common::session_summary::{cents_histogram_bin_start, histogram_bars, in_tune_bars};
+use crate::common::shared_types::{SessionStatistics, CENTS_HISTOGRAM_BIN_CENTS};
+
+pub fn show_session_summary(statistics: &SessionStatistics) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+
+    let score = match statistics.score {
+        Some(score) => translate_with("session-summary-score", &[&format!("{:.0}", score), &statistics.tolerance_cents.to_string()]),
+        None => translate("session-summary-nothing-sung").to_string(),
+    };
+    set_text(&document, "session-summary-score", &score);
+    set_text(&document, "session-summary-streak", &translate_with("session-summary-streak", &[&format!("{:.1}", statistics.longest_streak_seconds)]));
+
+    let degree_bars: Vec<_> = in_tune_bars(statistics)
+        .into_iter()
+        .enumerate()
+        .map(|(semitones, fraction)| (semitone_to_interval_name(semitones as i32), fraction, true))
+        .collect();
+    fill_chart(&document, "session-summary-degrees", &degree_bars);
+
+    let histogram: Vec<_> = histogram_bars(statistics)
+        .into_iter()
+        .enumerate()
+        .map(|(bin, fraction)| {
+            let start = cents_histogram_bin_start(bin);
+            let in_tune = (start + CENTS_HISTOGRAM_BIN_CENTS * 0.5).abs() <= statistics.tolerance_cents;
+            (format!("{:+}", start), Some(fraction), in_tune)
+        })
+        .collect();
+    fill_chart(&document, "session-summary-histogram", &histogram);
+
+    if let Some(overlay) = document.get_element_by_id("session-summary-overlay") {
+        let _ = overlay.class_list().remove_1("session-summary-overlay-hidden");
+    }
+    // Escape or Enter closes it from the keyboard
+    if let Some(close) = document.get_element_by_id("session-summary-close").and_then(|close| close.dyn_into::<HtmlElement>().ok()) {
+        let _ = close.focus();
+    }
+}
+
+pub fn hide_session_summary() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(overlay) = document.get_element_by_id("session-summary-overlay") {
+        let _ = overlay.class_list().add_1("session-summary-overlay-hidden");
+    }
+}
+
+fn set_text(document: &Document, id: &str, text: &str) {
+    if let Some(element) = document.get_element_by_id(id) {
+        element.set_text_content(Some(text));
+    }
+}
+
+/// Replace the bars of a chart; a bar without a height is left out but keeps its label
+fn fill_chart(document: &Document, id: &str, bars: &[(String, Option<f32>, bool)]) {
+    let Some(chart) = document.get_element_by_id(id) else { return };
+    chart.set_inner_html("");
+    for (label, fraction, in_tune) in bars {
+        let Ok(column) = document.create_element("div") else { return };
+        column.set_class_name("session-summary-bar-column");
+        let Ok(bar_area) = document.create_element("div") else { return };
+        bar_area.set_class_name("session-summary-bar-area");
+        if let Some(fraction) = fraction
+            && let Ok(bar) = document.create_element("div")
+        {
+            bar.set_class_name(if *in_tune { "session-summary-bar session-summary-bar-in-tune" } else { "session-summary-bar" });
+            let _ = bar.set_attribute("style", &format!("height: {:.1}%", fraction * 100.0));
+            let _ = bar_area.append_child(&bar);
+        }
+        let _ = column.append_child(&bar_area);
+        if let Ok(label_element) = document.create_element("div") {
+            label_element.set_class_name("session-summary-bar-label");
+            label_element.set_text_content(Some(label));
+            let _ = column.append_child(&label_element);
+        }
+        let _ = chart.append_child(&column);
+    }
+}
@@ -0,0 +1,176 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Image of the display with the session statistics beneath it, shared
+//! through the Web Share API where the browser can share files, else
+//! downloaded as a PNG file. The share itself waits for a second click on the
+//! button, as the image is ready only once the first one no longer counts as a
+//! user activation
+
+use std::cell::RefCell;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use wasm_bindgen::closure::Closure;
+use web_sys::{Blob, CanvasRenderingContext2d, File, HtmlCanvasElement, ImageData};
+use crate::app_config::{SHARE_IMAGE_FILENAME, SHARE_IMAGE_LINE_SPACING};
+use crate::common::localization::{translate, translate_with};
+use crate::common::shared_types::SessionStatistics;
+use crate::common::theme::get_current_color_scheme;
+use crate::web::utils::rgb_to_css;
+
+/// Height of the band with the statistics and the margin of its text, in
+/// pixels of the image
+const FOOTER_HEIGHT: u32 = 72;
+const FOOTER_MARGIN: f64 = 16.0;
+const FOOTER_FONT: &str = "16px sans-serif";
+const FOOTER_TITLE_FONT: &str = "bold 20px sans-serif";
+const FOOTER_TITLE: &str = "Intonation Toy";
+
+/// Compose the image from the pixels of the display, bottom row first as read
+/// from the GPU, and share or download it
+pub fn share_image(pixels: &[[u8; 4]], width: u32, height: u32, statistics: &SessionStatistics) {
+    if let Err(e) = compose(pixels, width, height, statistics).and_then(|canvas| to_png(&canvas)) {
+        crate::common::error_log!("Failed to create share image: {:?}", e);
+    }
+}
+
+fn compose(pixels: &[[u8; 4]], width: u32, height: u32, statistics: &SessionStatistics) -> Result<HtmlCanvasElement, JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let canvas = document.create_element("canvas")?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_width(width);
+    canvas.set_height(height + FOOTER_HEIGHT);
+    let context = canvas.get_context("2d")?
+        .ok_or("No 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+
+    // The display is drawn over an opaque background, so the image is opaque too
+    let bytes: Vec<u8> = pixels.chunks(width as usize)
+        .rev()
+        .flatten()
+        .flat_map(|&[r, g, b, _]| [r, g, b, 255])
+        .collect();
+    let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&bytes), width, height)?;
+    context.put_image_data(&image, 0.0, 0.0)?;
+
+    let scheme = get_current_color_scheme();
+    context.set_fill_style_str(&rgb_to_css(scheme.surface));
+    context.fill_rect(0.0, height as f64, width as f64, FOOTER_HEIGHT as f64);
+
+    let score = match statistics.score {
+        Some(score) => translate_with("statistics-score", &[&format!("{:.0}", score)]),
+        None => translate("statistics-nothing-sung").to_string(),
+    };
+    let streak = translate_with("statistics-streak", &[&format!("{:.1}", statistics.longest_streak_seconds)]);
+    let text_top = height as f64 + FOOTER_MARGIN;
+    context.set_text_baseline("top");
+    context.set_fill_style_str(&rgb_to_css(scheme.text));
+    context.set_font(FOOTER_TITLE_FONT);
+    context.fill_text(FOOTER_TITLE, FOOTER_MARGIN, text_top)?;
+    context.set_font(FOOTER_FONT);
+    context.set_fill_style_str(&rgb_to_css(scheme.muted));
+    context.fill_text(&format!("{} · {}", score, streak), FOOTER_MARGIN, text_top + SHARE_IMAGE_LINE_SPACING)?;
+    Ok(canvas)
+}
+
+fn to_png(canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+    let callback = Closure::once_into_js(move |blob: JsValue| {
+        match blob.dyn_into::<Blob>() {
+            Ok(blob) => prepare(blob),
+            Err(_) => crate::common::error_log!("Failed to encode share image"),
+        }
+    });
+    canvas.to_blob_with_type(callback.unchecked_ref(), "image/png")
+}
+
+thread_local! {
+    /// Image waiting for the click that shares it, the encoding having outlasted
+    /// the user activation of the click that asked for it
+    static PREPARED: RefCell<Option<(Blob, js_sys::Object)>> = const { RefCell::new(None) };
+}
+
+/// Hold the image for the next click where the browser can share the file,
+/// else download it
+fn prepare(blob: Blob) {
+    let prepared = match share_data(&blob) {
+        Ok(Some(data)) => {
+            PREPARED.with(|prepared| *prepared.borrow_mut() = Some((blob.clone(), data)));
+            true
+        }
+        Ok(None) => false,
+        Err(_e) => {
+            crate::common::dev_log!("Failed to prepare image for sharing: {:?}", _e);
+            false
+        }
+    };
+    if !prepared {
+        download(&blob);
+    }
+}
+
+/// Whether an image waits to be shared
+pub fn is_prepared() -> bool {
+    PREPARED.with(|prepared| prepared.borrow().is_some())
+}
+
+/// Share the prepared image, returning whether there was one; to be called
+/// from the handler of a click, whose user activation the Web Share API needs
+pub fn share_prepared() -> bool {
+    let Some((blob, data)) = PREPARED.with(|prepared| prepared.borrow_mut().take()) else {
+        return false;
+    };
+    match share(&data) {
+        Ok(promise) => wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = wasm_bindgen_futures::JsFuture::from(promise).await {
+                on_share_failed(e, &blob);
+            }
+        }),
+        Err(e) => on_share_failed(e, &blob),
+    }
+    true
+}
+
+/// A share the user cancels is not downloaded instead, any other failure is
+fn on_share_failed(e: JsValue, blob: &Blob) {
+    let cancelled = js_sys::Reflect::get(&e, &"name".into()).ok().and_then(|name| name.as_string()).as_deref() == Some("AbortError");
+    if !cancelled {
+        crate::common::dev_log!("Failed to share image: {:?}", e);
+        download(blob);
+    }
+}
+
+fn download(blob: &Blob) {
+    if let Err(e) = crate::web::download::download_blob(SHARE_IMAGE_FILENAME, blob) {
+        crate::common::error_log!("Failed to download share image: {}", e);
+    }
+}
+
+fn navigator_function(navigator: &JsValue, name: &str) -> Option<js_sys::Function> {
+    js_sys::Reflect::get(navigator, &name.into()).ok().and_then(|function| function.dyn_into::<js_sys::Function>().ok())
+}
+
+/// The data to share the image with, where the browser can share the file
+fn share_data(blob: &Blob) -> Result<Option<js_sys::Object>, JsValue> {
+    let Some(window) = web_sys::window() else { return Ok(None) };
+    let navigator: JsValue = window.navigator().into();
+    let (Some(can_share), Some(_)) = (navigator_function(&navigator, "canShare"), navigator_function(&navigator, "share")) else {
+        return Ok(None);
+    };
+
+    let options = web_sys::FilePropertyBag::new();
+    options.set_type("image/png");
+    let file = File::new_with_blob_sequence_and_options(&js_sys::Array::of1(blob), SHARE_IMAGE_FILENAME, &options)?;
+    let data = js_sys::Object::new();
+    js_sys::Reflect::set(&data, &"files".into(), &js_sys::Array::of1(&file))?;
+    js_sys::Reflect::set(&data, &"title".into(), &FOOTER_TITLE.into())?;
+    if !can_share.call1(&navigator, &data)?.is_truthy() {
+        return Ok(None);
+    }
+    Ok(Some(data))
+}
+
+fn share(data: &js_sys::Object) -> Result<js_sys::Promise, JsValue> {
+    let window = web_sys::window().ok_or("No window")?;
+    let navigator: JsValue = window.navigator().into();
+    let share = navigator_function(&navigator, "share").ok_or("No Web Share API")?;
+    share.call1(&navigator, data)?.dyn_into::<js_sys::Promise>()
+}
@@ -3,12 +3,18 @@
 use {
     wasm_bindgen::JsCast,
     wasm_bindgen::closure::Closure,
-    web_sys::{window, HtmlSelectElement, HtmlInputElement, EventTarget},
+    web_sys::{window, HtmlSelectElement, HtmlInputElement, HtmlTextAreaElement, HtmlElement, EventTarget},
     std::rc::Rc,
     std::cell::RefCell,
-    std::sync::atomic::{AtomicU8, Ordering},
+    std::sync::atomic::{AtomicBool, AtomicU8, Ordering},
     crate::common::dev_log,
-    crate::common::shared_types::{TuningSystem, Scale, DisplayRange, increment_midi_note, decrement_midi_note},
+    crate::common::localization::{Language, translate, translate_with},
+    crate::common::shortcuts::{KeyBindings, Shortcut, key_name_from_code},
+    crate::common::pane_layout::{PaneKind, PaneLayout},
+    crate::common::shared_types::{TuningSystem, JustIntonationRatios, Ratio, Scale, DisplayRange, IntervalZoom, DisplayScene, NeedleDamping, SmoothingStrategy, MetronomeSettings, ReferenceToneSettings, PitchAlgorithm, ToneTimbre, PeakMeasure, AudioInputDevice, Clef, Theme, MidiInputMode, MidiOutputSource, MidiPortInfo, IntervalMode, Transposition, NoteNaming, ToleranceProfile, CalibrationStep, ExerciseKind, SessionExportFormat, increment_midi_note, decrement_midi_note, shift_midi_note_octaves},
+    crate::common::settings::{PitchDetectionSettings, TuningSettings},
+    crate::common::deep_link::DeepLink,
+    crate::presentation::{CalibrationControl, CountInTarget, ExerciseControl, RecordingControl, ReferenceTrackControl},
     crate::web::storage,
 };
 
@@ -23,18 +29,70 @@ static CURRENT_TONAL_CENTER_NOTE: AtomicU8 = AtomicU8::new(crate::app_config::DE
 
 static CURRENT_TONAL_CENTER_VOLUME_POSITION: AtomicU8 = AtomicU8::new(0);
 
+/// Tonal center offered by the root suggestion, `NO_SUGGESTED_TONAL_CENTER` when none is shown
+static SUGGESTED_TONAL_CENTER_NOTE: AtomicU8 = AtomicU8::new(NO_SUGGESTED_TONAL_CENTER);
+const NO_SUGGESTED_TONAL_CENTER: u8 = u8::MAX;
+
+/// Checkboxes showing the side panes
+const PANE_TOGGLES: [(&str, PaneKind); 3] = [
+    ("pitch-history-pane-toggle", PaneKind::PitchHistory),
+    ("spectrogram-pane-toggle", PaneKind::Spectrogram),
+    ("statistics-pane-toggle", PaneKind::Statistics),
+];
+
 // Default volume position when unmuting
 const DEFAULT_VOLUME_POSITION: u8 = 40;
 
 // Remembered volume position for toggle functionality
 static REMEMBERED_VOLUME_POSITION: AtomicU8 = AtomicU8::new(DEFAULT_VOLUME_POSITION);
 
+/// Tonal center, tuning system, scale, display range, concert pitch, transposition, note naming and tolerance profile
+type SavedConfig = (u8, TuningSystem, Scale, DisplayRange, f32, Transposition, NoteNaming, ToleranceProfile);
 // Track last saved configuration to avoid saving every frame
-static LAST_SAVED_CONFIG: std::sync::Mutex<Option<(u8, TuningSystem, Scale, DisplayRange)>> = std::sync::Mutex::new(None);
+static LAST_SAVED_CONFIG: std::sync::Mutex<Option<SavedConfig>> = std::sync::Mutex::new(None);
+
+/// Shortcut waiting for its new key after its button was clicked
+static REBINDING_SHORTCUT: std::sync::Mutex<Option<Shortcut>> = std::sync::Mutex::new(None);
+
+/// Input devices listed in the device select, to rebuild its options only when they change
+static SHOWN_INPUT_DEVICES: std::sync::Mutex<Vec<AudioInputDevice>> = std::sync::Mutex::new(Vec::new());
+
+/// MIDI outputs listed in the output select, to rebuild its options only when they change
+static SHOWN_MIDI_OUTPUTS: std::sync::Mutex<Vec<MidiPortInfo>> = std::sync::Mutex::new(Vec::new());
+
+/// Whether the calibration button cancels a running calibration
+static IS_CALIBRATING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the recording button stops a running recording
+static IS_RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Whether the exercise button stops a running exercise
+static IS_EXERCISING: AtomicBool = AtomicBool::new(false);
+
+/// Count-in running for the exercise or the recording, whose button then cancels it
+static COUNTING_IN: std::sync::Mutex<Option<CountInTarget>> = std::sync::Mutex::new(None);
+
+/// Whether the melody button stops a playing reference melody
+static IS_PLAYING_MELODY: AtomicBool = AtomicBool::new(false);
+
+/// Ratios shown in the just intonation editor, to refill it only when they change
+static SHOWN_JUST_RATIOS: std::sync::Mutex<Option<JustIntonationRatios>> = std::sync::Mutex::new(None);
+
+/// Offsets shown in the custom tuning editor, to refill it only when they change
+static SHOWN_CUSTOM_TUNING: std::sync::Mutex<Option<Vec<f32>>> = std::sync::Mutex::new(None);
+
+/// Mask shown in the custom scale builder, to refill it only when it changes
+static SHOWN_CUSTOM_SCALE: std::sync::Mutex<Option<u16>> = std::sync::Mutex::new(None);
 
 // Track current display range
 static CURRENT_DISPLAY_RANGE: std::sync::Mutex<DisplayRange> = std::sync::Mutex::new(crate::app_config::DEFAULT_DISPLAY_RANGE);
 
+/// Tolerance set on the sidebar slider
+fn tolerance_slider_cents(document: &web_sys::Document) -> Option<f32> {
+    let slider_element = document.get_element_by_id("tolerance-cents")?;
+    slider_element.dyn_ref::<HtmlInputElement>()?.value().parse().ok()
+}
+
 fn slider_position_to_amplitude(position: f32) -> f32 {
     if position <= 0.0 {
         0.0
@@ -46,6 +104,11 @@ fn slider_position_to_amplitude(position: f32) -> f32 {
     }
 }
 
+/// Amplitude of the tonal center drone as set by the volume slider
+pub fn current_tonal_center_amplitude() -> f32 {
+    slider_position_to_amplitude(CURRENT_TONAL_CENTER_VOLUME_POSITION.load(Ordering::Relaxed) as f32)
+}
+
 fn update_volume_icon_state(is_muted: bool) {
     let Some(window) = window() else { return; };
     let Some(document) = window.document() else { return; };
@@ -56,6 +119,7 @@ fn update_volume_icon_state(is_muted: bool) {
     } else {
         let _ = icon.class_list().remove_1("muted");
     }
+    let _ = icon.set_attribute("aria-pressed", if is_muted { "true" } else { "false" });
 }
 
 fn slider_position_to_db_display(position: f32) -> String {
@@ -75,12 +139,292 @@ fn slider_position_to_db_display(position: f32) -> String {
     }
 }
 
+/// Mute the drone, remembering its volume, or bring it back at the
+/// remembered volume; returns the new amplitude
+pub fn toggle_tonal_center_mute() -> f32 {
+    let current_position = CURRENT_TONAL_CENTER_VOLUME_POSITION.load(Ordering::Relaxed);
+    let new_position = if current_position == 0 {
+        REMEMBERED_VOLUME_POSITION.load(Ordering::Relaxed)
+    } else {
+        REMEMBERED_VOLUME_POSITION.store(current_position, Ordering::Relaxed);
+        0
+    };
+    CURRENT_TONAL_CENTER_VOLUME_POSITION.store(new_position, Ordering::Relaxed);
+    update_volume_icon_state(new_position == 0);
+
+    if let Some(document) = window().and_then(|window| window.document()) {
+        if let Some(html_slider) = document.get_element_by_id("tonal-center-volume").and_then(|element| element.dyn_into::<HtmlInputElement>().ok()) {
+            html_slider.set_value(&new_position.to_string());
+        }
+        if let Some(display_element) = document.get_element_by_id("tonal-center-volume-display") {
+            display_element.set_text_content(Some(&slider_position_to_db_display(new_position as f32)));
+        }
+    }
+
+    slider_position_to_amplitude(new_position as f32)
+}
+
+/// Reference tone sound set on the sidebar, with the default decay and sustain
+fn reference_tone_from_sidebar(document: &web_sys::Document) -> Option<ReferenceToneSettings> {
+    let slider = |id: &str| document.get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .and_then(|slider| slider.value().parse::<f32>().ok());
+    let select_element = document.get_element_by_id("reference-tone-timbre-select")?;
+    let html_select = select_element.dyn_ref::<HtmlSelectElement>()?;
+    let timbre = ToneTimbre::ALL.into_iter().find(|timbre| timbre.name() == html_select.value())?;
+
+    let mut settings = ReferenceToneSettings { timbre, ..ReferenceToneSettings::default() };
+    settings.volume = slider("reference-tone-volume")? / 100.0;
+    settings.envelope.attack_ms = slider("reference-tone-attack")?;
+    settings.envelope.release_ms = slider("reference-tone-release")?;
+    Some(settings)
+}
+
+/// Pitch detector and thresholds set on the sidebar
+fn pitch_detection_from_sidebar(document: &web_sys::Document) -> Option<PitchDetectionSettings> {
+    let slider = |id: &str| document.get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .and_then(|slider| slider.value().parse::<f32>().ok());
+    let select_element = document.get_element_by_id("pitch-detector-select")?;
+    let html_select = select_element.dyn_ref::<HtmlSelectElement>()?;
+
+    Some(PitchDetectionSettings {
+        algorithm: PitchAlgorithm::from_name(&html_select.value())?,
+        power_threshold: slider("pitch-detector-power")? / 100.0,
+        clarity_threshold: slider("pitch-detector-clarity")? / 100.0,
+    })
+}
+
+/// Shows the pitch detector and thresholds on the sidebar
+fn show_pitch_detection(document: &web_sys::Document, settings: &PitchDetectionSettings) {
+    if let Some(select_element) = document.get_element_by_id("pitch-detector-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(settings.algorithm.name());
+    }
+    for (slider_id, display_id, threshold) in [
+        ("pitch-detector-power", "pitch-detector-power-display", settings.power_threshold),
+        ("pitch-detector-clarity", "pitch-detector-clarity-display", settings.clarity_threshold),
+    ] {
+        if let Some(slider_element) = document.get_element_by_id(slider_id)
+            && let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>()
+        {
+            html_slider.set_value(&format!("{:.0}", threshold * 100.0));
+        }
+        if let Some(display_element) = document.get_element_by_id(display_id) {
+            display_element.set_text_content(Some(&format!("{:.2}", threshold)));
+        }
+    }
+}
+
+/// One row per degree in the just intonation editor: its name and the
+/// numerator and denominator of its ratio
+fn build_just_ratio_rows(document: &web_sys::Document) -> Result<(), wasm_bindgen::JsValue> {
+    let Some(rows) = document.get_element_by_id("just-ratios-rows") else { return Ok(()) };
+    for semitone in 0..12 {
+        let name = crate::common::music_theory::semitone_to_interval_name(semitone);
+        let row = document.create_element("div")?;
+        row.set_class_name("control-row");
+
+        let label = document.create_element("span")?;
+        label.set_class_name("shortcut-label");
+        label.set_text_content(Some(&name));
+        row.append_child(&label)?;
+
+        for (part, key) in [("numerator", "just-ratio-numerator"), ("denominator", "just-ratio-denominator")] {
+            if part == "denominator" {
+                let slash = document.create_element("span")?;
+                slash.set_text_content(Some("/"));
+                row.append_child(&slash)?;
+            }
+            let input = document.create_element("input")?;
+            input.set_id(&format!("just-ratio-{}-{}", part, semitone));
+            input.set_attribute("type", "number")?;
+            input.set_attribute("min", "1")?;
+            input.set_attribute("max", "1024")?;
+            input.set_attribute("aria-label", &translate_with(key, &[&name]))?;
+            row.append_child(&input)?;
+        }
+        rows.append_child(&row)?;
+    }
+    Ok(())
+}
+
+/// Ratios entered in the just intonation editor, if every term is a whole number from 1 to 1024
+fn just_ratios_from_sidebar(document: &web_sys::Document) -> Option<JustIntonationRatios> {
+    let term = |part: &str, semitone: usize| document.get_element_by_id(&format!("just-ratio-{}-{}", part, semitone))
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .and_then(|input| input.value().parse::<u32>().ok())
+        .filter(|term| (1..=1024).contains(term));
+    let mut ratios = JustIntonationRatios::default();
+    for (semitone, ratio) in ratios.0.iter_mut().enumerate() {
+        *ratio = Ratio::new(term("numerator", semitone)?, term("denominator", semitone)?);
+    }
+    Some(ratios)
+}
+
+/// Fill the just intonation editor with the ratios of the tuning
+fn show_just_ratios(document: &web_sys::Document, ratios: &JustIntonationRatios) {
+    for (semitone, ratio) in ratios.0.iter().enumerate() {
+        for (part, term) in [("numerator", ratio.numerator), ("denominator", ratio.denominator)] {
+            if let Some(input) = document.get_element_by_id(&format!("just-ratio-{}-{}", part, semitone))
+                .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+            {
+                input.set_value(&term.to_string());
+            }
+        }
+    }
+}
+
+/// One row per degree above the tonal center in the custom tuning editor:
+/// its name and its offset from equal temperament
+fn build_custom_tuning_rows(document: &web_sys::Document) -> Result<(), wasm_bindgen::JsValue> {
+    let Some(rows) = document.get_element_by_id("custom-tuning-rows") else { return Ok(()) };
+    for semitone in 1..12 {
+        let name = crate::common::music_theory::semitone_to_interval_name(semitone);
+        let row = document.create_element("div")?;
+        row.set_class_name("control-row");
+
+        let label = document.create_element("span")?;
+        label.set_class_name("shortcut-label");
+        label.set_text_content(Some(&name));
+        row.append_child(&label)?;
+
+        let input = document.create_element("input")?;
+        input.set_id(&format!("custom-tuning-offset-{}", semitone));
+        input.set_attribute("type", "number")?;
+        input.set_attribute("min", "-100")?;
+        input.set_attribute("max", "100")?;
+        input.set_attribute("step", "0.1")?;
+        input.set_attribute("aria-label", &translate_with("custom-tuning-offset", &[&name]))?;
+        row.append_child(&input)?;
+
+        let unit = document.create_element("span")?;
+        unit.set_text_content(Some("¢"));
+        row.append_child(&unit)?;
+        rows.append_child(&row)?;
+    }
+    Ok(())
+}
+
+/// Offsets entered in the custom tuning editor, the tonal center's first, if
+/// every offset is a number
+fn custom_tuning_from_sidebar(document: &web_sys::Document) -> Option<Vec<f32>> {
+    let mut offsets = vec![0.0];
+    for semitone in 1..12 {
+        let offset = document.get_element_by_id(&format!("custom-tuning-offset-{}", semitone))
+            .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+            .and_then(|input| input.value().parse::<f32>().ok())?;
+        offsets.push(offset);
+    }
+    Some(offsets)
+}
+
+/// Fill the custom tuning editor with the offsets of the tuning
+fn show_custom_tuning(document: &web_sys::Document, offsets: &[f32]) {
+    for (semitone, offset) in offsets.iter().enumerate().skip(1) {
+        if let Some(input) = document.get_element_by_id(&format!("custom-tuning-offset-{}", semitone))
+            .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        {
+            input.set_value(&format!("{:.1}", offset));
+        }
+    }
+}
+
+fn custom_scale_checkbox(document: &web_sys::Document, semitone: usize) -> Option<HtmlInputElement> {
+    document.get_element_by_id(&format!("custom-scale-degree-{}", semitone))?.dyn_into::<HtmlInputElement>().ok()
+}
+
+fn is_checked(document: &web_sys::Document, id: &str) -> bool {
+    document.get_element_by_id(id)
+        .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+        .is_some_and(|checkbox| checkbox.checked())
+}
+
+fn counting_in() -> Option<CountInTarget> {
+    COUNTING_IN.lock().ok().and_then(|counting_in| *counting_in)
+}
+
+fn scala_text_area(document: &web_sys::Document) -> Option<HtmlTextAreaElement> {
+    document.get_element_by_id("scala-text")?.dyn_into::<HtmlTextAreaElement>().ok()
+}
+
+/// Fill the device select with the available inputs; unnamed inputs get a numbered name
+fn update_input_device_options(document: &web_sys::Document, html_select: &HtmlSelectElement, devices: &[AudioInputDevice]) {
+    html_select.set_inner_html("");
+    for (index, device) in devices.iter().enumerate() {
+        let Ok(option) = document.create_element("option") else { continue; };
+        let _ = option.set_attribute("value", &device.device_id);
+        let name = if device.label.is_empty() { translate_with("input-device-unnamed", &[&(index + 1).to_string()]) } else { device.label.clone() };
+        option.set_text_content(Some(&name));
+        let _ = html_select.append_child(&option);
+    }
+}
+
+/// Fill the MIDI output select with the available outputs after a "none" option
+fn update_midi_output_options(document: &web_sys::Document, html_select: &HtmlSelectElement, outputs: &[MidiPortInfo]) {
+    html_select.set_inner_html("");
+    let options = std::iter::once((String::new(), translate("midi-output-none").to_string()))
+        .chain(outputs.iter().map(|output| (output.id.clone(), output.name.clone())));
+    for (value, name) in options {
+        let Ok(option) = document.create_element("option") else { continue; };
+        let _ = option.set_attribute("value", &value);
+        option.set_text_content(Some(&name));
+        let _ = html_select.append_child(&option);
+    }
+}
+
+/// Show the key of every shortcut on its button
+pub fn update_shortcut_buttons(bindings: &KeyBindings) {
+    let Some(document) = window().and_then(|window| window.document()) else { return; };
+    for shortcut in Shortcut::all() {
+        if let Some(button) = document.get_element_by_id(&format!("shortcut-{}", shortcut.id())) {
+            button.set_text_content(Some(bindings.key(shortcut).unwrap_or(translate("shortcut-unbound"))));
+        }
+    }
+}
+
+/// Check the pane toggles of the panes shown in `layout`
+pub fn update_pane_toggles(layout: &PaneLayout) {
+    let Some(document) = window().and_then(|window| window.document()) else { return; };
+    for (element_id, pane) in PANE_TOGGLES {
+        if let Some(toggle) = document.get_element_by_id(element_id)
+            && let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>()
+        {
+            toggle.set_checked(layout.shows(pane));
+        }
+    }
+}
+
 pub fn set_initial_display_range(display_range: DisplayRange) {
     if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
         *current = display_range;
     }
 }
 
+/// Select a display range that was not picked in the sidebar
+pub fn set_display_range(display_range: DisplayRange) {
+    if let Some(document) = window().and_then(|window| window.document()) {
+        check_display_range_radio(&document, &display_range);
+    }
+    set_initial_display_range(display_range);
+}
+
+fn check_display_range_radio(document: &web_sys::Document, display_range: &DisplayRange) {
+    let id = match display_range {
+        DisplayRange::TwoOctaves => "display-range-two-octaves",
+        DisplayRange::OneFullOctave => "display-range-one-octave",
+        DisplayRange::TwoHalfOctaves => "display-range-two-half-octaves",
+    };
+
+    // Find and check the appropriate radio button by ID
+    if let Some(radio_button) = document.get_element_by_id(id)
+        && let Some(input) = radio_button.dyn_ref::<HtmlInputElement>()
+    {
+        input.set_checked(true);
+    }
+}
+
 pub fn setup_sidebar_controls() {
     let Some(window) = window() else {
         dev_log!("Failed to get window");
@@ -113,23 +457,45 @@ pub fn setup_sidebar_controls() {
         dev_log!("Warning: tonal-center-volume element not found in HTML");
     }
 
+    if let Some(toggle) = document.get_element_by_id("midi-toggle")
+        && let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>()
+    {
+        toggle.set_checked(storage::settings().midi_enabled);
+    }
+
+    if let Some(pitch_detection) = storage::settings().pitch_detection {
+        show_pitch_detection(&document, &pitch_detection);
+    }
+
+    if let Err(_e) = build_just_ratio_rows(&document) {
+        dev_log!("Failed to build the just intonation editor: {:?}", _e);
+    }
+    if let Err(_e) = build_custom_tuning_rows(&document) {
+        dev_log!("Failed to build the custom tuning editor: {:?}", _e);
+    }
+
     // Initialize volume icon state
     update_volume_icon_state(true);
 
     // Set initial display range from stored value
     if let Ok(current) = CURRENT_DISPLAY_RANGE.try_lock() {
-        let id = match *current {
-            DisplayRange::TwoOctaves => "display-range-two-octaves",
-            DisplayRange::OneFullOctave => "display-range-one-octave",
-            DisplayRange::TwoHalfOctaves => "display-range-two-half-octaves",
-        };
+        check_display_range_radio(&document, &current);
+    }
 
-        // Find and check the appropriate radio button by ID
-        if let Some(radio_button) = document.get_element_by_id(id) {
-            if let Some(input) = radio_button.dyn_ref::<HtmlInputElement>() {
-                input.set_checked(true);
-            }
-        }
+    if let Some(select_element) = document.get_element_by_id("theme-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() {
+        html_select.set_value(crate::common::theme::get_current_theme().name());
+    }
+
+    if let Some(select_element) = document.get_element_by_id("language-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() {
+        html_select.set_value(crate::common::localization::current_language().code());
+    }
+
+    if !crate::web::picture_in_picture::is_supported()
+        && let Some(button) = document.get_element_by_id("mini-view-button")
+    {
+        let _ = button.set_attribute("disabled", "");
     }
 
     // Verify essential elements exist
@@ -142,9 +508,24 @@ pub fn setup_sidebar_controls() {
     if document.get_element_by_id("tuning-system-select").is_none() {
         dev_log!("Warning: tuning-system-select element not found in HTML");
     }
+    if document.get_element_by_id("concert-pitch-select").is_none() {
+        dev_log!("Warning: concert-pitch-select element not found in HTML");
+    }
+    if document.get_element_by_id("transposition-select").is_none() {
+        dev_log!("Warning: transposition-select element not found in HTML");
+    }
+    if document.get_element_by_id("note-naming-select").is_none() {
+        dev_log!("Warning: note-naming-select element not found in HTML");
+    }
+    if document.get_element_by_id("tolerance-profile-select").is_none() {
+        dev_log!("Warning: tolerance-profile-select element not found in HTML");
+    }
     if document.get_element_by_id("scale-select").is_none() {
         dev_log!("Warning: scale-select element not found in HTML");
     }
+    if document.get_element_by_id("export-button").is_none() {
+        dev_log!("Warning: export-button element not found in HTML");
+    }
     if document.get_element_by_id("volume-icon").is_none() {
         dev_log!("Warning: volume-icon element not found in HTML");
     }
@@ -172,42 +553,135 @@ where
     closure.forget();
 }
 
+/// Run `handler` on a click, and on Enter or Space while the element has
+/// focus, for elements acting as buttons without being one
+fn add_activation_listener<F>(element_id: &str, handler: F)
+where
+    F: Fn() + 'static,
+{
+    let handler = Rc::new(handler);
+    let click_handler = handler.clone();
+    add_event_listener(element_id, "click", move |_event: web_sys::Event| click_handler());
+    add_event_listener(element_id, "keydown", move |event: web_sys::Event| {
+        let Some(key_event) = event.dyn_ref::<web_sys::KeyboardEvent>() else { return; };
+        if matches!(key_event.key().as_str(), "Enter" | " ") {
+            event.prevent_default();
+            handler();
+        }
+    });
+}
+
+/// Run `handler` when Escape is pressed within the element
+fn add_escape_listener<F>(element_id: &str, handler: F)
+where
+    F: Fn() + 'static,
+{
+    add_event_listener(element_id, "keydown", move |event: web_sys::Event| {
+        if event.dyn_ref::<web_sys::KeyboardEvent>().is_some_and(|key_event| key_event.key() == "Escape") {
+            event.prevent_default();
+            handler();
+        }
+    });
+}
+
 pub fn setup_event_listeners(presenter: Rc<RefCell<crate::presentation::Presenter>>) {
     let presenter_clone = presenter.clone();
-    add_event_listener("volume-icon", "click", move |_event: web_sys::Event| {
-        let Some(window) = web_sys::window() else { return; };
-        let Some(document) = window.document() else { return; };
-        let Some(slider_element) = document.get_element_by_id("tonal-center-volume") else { return; };
-        let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>() else { return; };
+    add_activation_listener("audio-paused-overlay", move || {
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_audio_resume_requested();
+        }
+    });
 
-        let current_position = CURRENT_TONAL_CENTER_VOLUME_POSITION.load(Ordering::Relaxed);
-        let new_position = if current_position == 0 {
-            // Unmute: restore remembered volume
-            let remembered = REMEMBERED_VOLUME_POSITION.load(Ordering::Relaxed);
-            html_slider.set_value(&remembered.to_string());
-            CURRENT_TONAL_CENTER_VOLUME_POSITION.store(remembered, Ordering::Relaxed);
-            update_volume_icon_state(false);
-            remembered
-        } else {
-            // Mute: save current volume and set to 0
-            REMEMBERED_VOLUME_POSITION.store(current_position, Ordering::Relaxed);
-            html_slider.set_value("0");
-            CURRENT_TONAL_CENTER_VOLUME_POSITION.store(0, Ordering::Relaxed);
-            update_volume_icon_state(true);
-            0
-        };
+    let presenter_clone = presenter.clone();
+    add_event_listener("tutorial-next", "click", move |_event: web_sys::Event| {
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_tutorial_next();
+        }
+    });
 
-        // Update volume display
-        if let Some(display_element) = document.get_element_by_id("tonal-center-volume-display") {
-            display_element.set_text_content(Some(&slider_position_to_db_display(new_position as f32)));
+    let presenter_clone = presenter.clone();
+    add_event_listener("tutorial-back", "click", move |_event: web_sys::Event| {
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_tutorial_back();
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("tutorial-skip", "click", move |_event: web_sys::Event| {
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_tutorial_skipped();
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_escape_listener("tutorial-overlay", move || {
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_tutorial_skipped();
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("toast-container", "click", move |event: web_sys::Event| {
+        let Some(target) = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()) else { return; };
+        let Ok(Some(close)) = target.closest(".toast-close") else { return; };
+        let Some(id) = close.get_attribute(crate::web::notifications::TOAST_ID_ATTRIBUTE).and_then(|id| id.parse().ok()) else { return; };
+        if let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() {
+            presenter_mut.on_toast_dismissed(id);
         }
+    });
 
-        // Notify presenter
-        let amplitude = slider_position_to_amplitude(new_position as f32);
+    let presenter_clone = presenter.clone();
+    add_activation_listener("volume-icon", move || {
+        let amplitude = toggle_tonal_center_mute();
         let current_tonal_center = CURRENT_TONAL_CENTER_NOTE.load(Ordering::Relaxed);
         presenter_clone.borrow_mut().on_tonal_center_configured(true, current_tonal_center, amplitude);
     });
 
+    for shortcut in Shortcut::all() {
+        let button_id = format!("shortcut-{}", shortcut.id());
+        let clicked_id = button_id.clone();
+        let presenter_clone = presenter.clone();
+        add_event_listener(&button_id, "click", move |_event: web_sys::Event| {
+            // Restore the label of a shortcut still waiting for its key
+            if let Ok(presenter_ref) = presenter_clone.try_borrow() {
+                update_shortcut_buttons(presenter_ref.key_bindings());
+            }
+            if let Ok(mut rebinding) = REBINDING_SHORTCUT.lock() {
+                *rebinding = Some(shortcut);
+            }
+            if let Some(button) = window().and_then(|window| window.document()).and_then(|document| document.get_element_by_id(&clicked_id)) {
+                button.set_text_content(Some(translate("shortcut-press-key")));
+            }
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    let rebind_closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+        let Ok(mut rebinding) = REBINDING_SHORTCUT.lock() else { return; };
+        let Some(shortcut) = *rebinding else { return; };
+        event.prevent_default();
+
+        let key = key_name_from_code(&event.code());
+        // Keep waiting through modifier and other keys that cannot be bound
+        if key.is_none() && event.code() != "Escape" {
+            return;
+        }
+        *rebinding = None;
+        let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut() else { return; };
+        if let Some(key) = key {
+            presenter_mut.on_shortcut_bound(shortcut, key);
+            let key_bindings = presenter_mut.key_bindings().clone();
+            storage::update_settings(|settings| settings.key_bindings = Some(key_bindings));
+        }
+        update_shortcut_buttons(presenter_mut.key_bindings());
+    }) as Box<dyn FnMut(_)>);
+    if let Some(document) = window().and_then(|window| window.document())
+        && let Err(_e) = document.add_event_listener_with_callback("keydown", rebind_closure.as_ref().unchecked_ref())
+    {
+        dev_log!("Failed to add keydown listener for shortcut remapping: {:?}", _e);
+    }
+    rebind_closure.forget();
+
     let presenter_clone = presenter.clone();
     add_event_listener("tonal-center-plus", "click", move |_event: web_sys::Event| {
         let current_tonal_center_note = CURRENT_TONAL_CENTER_NOTE.load(Ordering::Relaxed);
@@ -232,117 +706,840 @@ pub fn setup_event_listeners(presenter: Rc<RefCell<crate::presentation::Presente
         }
     });
 
+    for (element_id, octaves) in [("tonal-center-octave-up", 1), ("tonal-center-octave-down", -1)] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "click", move |_event: web_sys::Event| {
+            let current_tonal_center_note = CURRENT_TONAL_CENTER_NOTE.load(Ordering::Relaxed);
+            if let Some(new_tonal_center_note) = shift_midi_note_octaves(current_tonal_center_note, octaves)
+                && let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut()
+            {
+                let position = CURRENT_TONAL_CENTER_VOLUME_POSITION.load(Ordering::Relaxed) as f32;
+                let amplitude = slider_position_to_amplitude(position);
+                presenter_mut.on_tonal_center_configured(true, new_tonal_center_note, amplitude);
+            }
+        });
+    }
+
     let presenter_clone = presenter.clone();
-    add_event_listener("tuning-system-select", "change", move |_event: web_sys::Event| {
+    add_event_listener("interval-mode-select", "change", move |_event: web_sys::Event| {
         let Some(window) = web_sys::window() else { return; };
         let Some(document) = window.document() else { return; };
-        let Some(select_element) = document.get_element_by_id("tuning-system-select") else { return; };
+        let Some(select_element) = document.get_element_by_id("interval-mode-select") else { return; };
         let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
-        
-        let tuning_system = match html_select.value().as_str() {
-            "equal" => TuningSystem::EqualTemperament,
-            "just" => TuningSystem::JustIntonation,
+
+        let mode = match html_select.value().as_str() {
+            "absolute" => IntervalMode::Absolute,
+            "wrapped" => IntervalMode::Wrapped,
             _ => {
-                dev_log!("Unknown tuning system value: {}", html_select.value());
+                dev_log!("Unknown interval mode value: {}", html_select.value());
                 return;
             }
         };
-        presenter_clone.borrow_mut().on_tuning_system_changed(tuning_system);
+        presenter_clone.borrow_mut().on_interval_mode_configured(mode);
+        storage::update_settings(|settings| settings.interval_mode = Some(mode));
     });
 
     let presenter_clone = presenter.clone();
-    add_event_listener("scale-select", "change", move |_event: web_sys::Event| {
+    add_event_listener("tuning-system-select", "change", move |_event: web_sys::Event| {
         let Some(window) = web_sys::window() else { return; };
         let Some(document) = window.document() else { return; };
-        let Some(select_element) = document.get_element_by_id("scale-select") else { return; };
+        let Some(select_element) = document.get_element_by_id("tuning-system-select") else { return; };
         let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
         
-        let scale = match html_select.value().as_str() {
-            "chromatic" => Scale::Chromatic,
-            "major" => Scale::Major,
-            "minor" => Scale::Minor,
-            "harmonic_minor" => Scale::HarmonicMinor,
-            "melodic_minor" => Scale::MelodicMinor,
-            "major_pentatonic" => Scale::MajorPentatonic,
-            "minor_pentatonic" => Scale::MinorPentatonic,
-            "blues" => Scale::Blues,
-            "dorian" => Scale::Dorian,
-            "phrygian" => Scale::Phrygian,
-            "lydian" => Scale::Lydian,
-            "mixolydian" => Scale::Mixolydian,
-            "locrian" => Scale::Locrian,
-            "whole_tone" => Scale::WholeTone,
-            "augmented" => Scale::Augmented,
-            "diminished_half_whole" => Scale::DiminishedHalfWhole,
-            "diminished_whole_half" => Scale::DiminishedWholeHalf,
-            "hungarian_minor" => Scale::HungarianMinor,
-            "neapolitan_minor" => Scale::NeapolitanMinor,
-            "neapolitan_major" => Scale::NeapolitanMajor,
-            "enigmatic" => Scale::Enigmatic,
-            "persian" => Scale::Persian,
-            "double_harmonic_major" => Scale::DoubleHarmonicMajor,
-            "altered" => Scale::Altered,
-            "bebop_major" => Scale::BebopMajor,
-            "bebop_dominant" => Scale::BebopDominant,
-            _ => {
-                dev_log!("Unknown scale value: {}", html_select.value());
-                return;
+        let tuning_system = match html_select.value().as_str() {
+            "custom" => TuningSystem::Custom(storage::settings().custom_tuning.unwrap_or_else(|| vec![0.0; 12])),
+            id => {
+                let Some(tuning_system) = TuningSystem::from_id(id) else {
+                    dev_log!("Unknown tuning system value: {}", id);
+                    return;
+                };
+                tuning_system
             }
         };
-        presenter_clone.borrow_mut().on_scale_changed(scale);
+        presenter_clone.borrow_mut().on_tuning_system_changed(tuning_system);
     });
 
-    // Add event listeners for display range radio buttons
-    let presenter_clone_1 = presenter.clone();
-    add_event_listener("display-range-two-octaves", "change", move |_event: web_sys::Event| {
-        let display_range = DisplayRange::TwoOctaves;
-        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
-            *current = display_range.clone();
-        }
-        presenter_clone_1.borrow_mut().on_display_range_changed(display_range);
+    let presenter_clone = presenter.clone();
+    add_event_listener("just-ratios", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(ratios) = just_ratios_from_sidebar(&document) else {
+            dev_log!("Ignoring just intonation ratios with a missing or out of range term");
+            return;
+        };
+        presenter_clone.borrow_mut().on_tuning_system_changed(TuningSystem::JustIntonation(ratios));
     });
 
-    let presenter_clone_2 = presenter.clone();
-    add_event_listener("display-range-one-octave", "change", move |_event: web_sys::Event| {
-        let display_range = DisplayRange::OneFullOctave;
-        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
-            *current = display_range.clone();
+    let presenter_clone = presenter.clone();
+    add_event_listener("custom-tuning-rows", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(offsets) = custom_tuning_from_sidebar(&document) else {
+            dev_log!("Ignoring custom tuning with a missing offset");
+            return;
+        };
+        presenter_clone.borrow_mut().on_tuning_system_changed(TuningSystem::Custom(offsets));
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("scala-import", "click", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(text_area) = scala_text_area(&document) else { return; };
+        let error = match crate::common::scala::parse_scl(&text_area.value()) {
+            Ok(offsets) => {
+                presenter_clone.borrow_mut().on_tuning_system_changed(TuningSystem::Custom(offsets));
+                None
+            }
+            Err(error) => Some(translate_with("scala-import-failed", &[&error])),
+        };
+        if let Some(element) = document.get_element_by_id("scala-error") {
+            element.set_text_content(error.as_deref());
         }
-        presenter_clone_2.borrow_mut().on_display_range_changed(display_range);
     });
 
-    let presenter_clone_3 = presenter.clone();
-    add_event_listener("display-range-two-half-octaves", "change", move |_event: web_sys::Event| {
-        let display_range = DisplayRange::TwoHalfOctaves;
-        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
-            *current = display_range.clone();
+    add_event_listener("scala-export", "click", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(offsets) = custom_tuning_from_sidebar(&document) else { return; };
+        let text = crate::common::scala::format_scl("Custom tuning", &offsets);
+        if let Some(text_area) = scala_text_area(&document) {
+            text_area.set_value(&text);
+        }
+        if let Err(_e) = crate::web::download::download_bytes(crate::app_config::SCALA_EXPORT_FILENAME, "text/plain", text.as_bytes()) {
+            dev_log!("Failed to download Scala file: {}", _e);
         }
-        presenter_clone_3.borrow_mut().on_display_range_changed(display_range);
     });
 
     let presenter_clone = presenter.clone();
-    add_event_listener("tonal-center-volume", "input", move |_event: web_sys::Event| {
+    add_event_listener("concert-pitch-select", "change", move |_event: web_sys::Event| {
         let Some(window) = web_sys::window() else { return; };
         let Some(document) = window.document() else { return; };
-        let Some(slider_element) = document.get_element_by_id("tonal-center-volume") else { return; };
-        let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>() else { return; };
-        let Ok(position) = html_slider.value().parse::<f32>() else { return; };
-        
-        CURRENT_TONAL_CENTER_VOLUME_POSITION.store(position as u8, Ordering::Relaxed);
-        let amplitude = slider_position_to_amplitude(position);
+        let Some(select_element) = document.get_element_by_id("concert-pitch-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
 
-        // Update icon state based on position
-        update_volume_icon_state(position == 0.0);
+        let Ok(frequency_hz) = html_select.value().parse::<f32>() else {
+            dev_log!("Unknown concert pitch value: {}", html_select.value());
+            return;
+        };
+        presenter_clone.borrow_mut().on_concert_pitch_configured(frequency_hz);
+    });
 
-        // If moving from 0, reset remembered volume to default
-        if position > 0.0 {
-            let previous_position = CURRENT_TONAL_CENTER_VOLUME_POSITION.swap(position as u8, Ordering::Relaxed);
-            if previous_position == 0 {
-                REMEMBERED_VOLUME_POSITION.store(DEFAULT_VOLUME_POSITION, Ordering::Relaxed);
+    let presenter_clone = presenter.clone();
+    add_event_listener("transposition-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("transposition-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let transposition = match html_select.value().as_str() {
+            "c" => Transposition::C,
+            "b-flat" => Transposition::BFlat,
+            "e-flat" => Transposition::EFlat,
+            "f" => Transposition::F,
+            "a" => Transposition::A,
+            _ => {
+                dev_log!("Unknown transposition value: {}", html_select.value());
+                return;
             }
-        }
+        };
+        presenter_clone.borrow_mut().on_transposition_configured(transposition);
+    });
 
-        if let Some(display_element) = document.get_element_by_id("tonal-center-volume-display") {
+    let presenter_clone = presenter.clone();
+    add_event_listener("note-naming-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("note-naming-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let naming = match html_select.value().as_str() {
+            "flats" => NoteNaming::Flats,
+            "sharps" => NoteNaming::Sharps,
+            "by-key" => NoteNaming::ByKey,
+            "solfege" => NoteNaming::Solfege,
+            "sargam" => NoteNaming::Sargam,
+            _ => {
+                dev_log!("Unknown note naming value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_note_naming_configured(naming);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("root-suggestion-apply", "click", move |_event: web_sys::Event| {
+        let suggested_note = SUGGESTED_TONAL_CENTER_NOTE.load(Ordering::Relaxed);
+        if suggested_note != NO_SUGGESTED_TONAL_CENTER
+            && let Ok(mut presenter_mut) = presenter_clone.try_borrow_mut()
+        {
+            presenter_mut.on_tonal_center_configured(true, suggested_note, current_tonal_center_amplitude());
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("exercise-button", "click", move |_event: web_sys::Event| {
+        if IS_EXERCISING.load(Ordering::Relaxed) {
+            presenter_clone.borrow_mut().on_exercise_control(ExerciseControl::Stop);
+            return;
+        }
+        if matches!(counting_in(), Some(CountInTarget::Exercise(_))) {
+            presenter_clone.borrow_mut().on_count_in_cancelled();
+            return;
+        }
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("exercise-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+        let Some(kind) = ExerciseKind::from_id(&html_select.value()) else {
+            dev_log!("Unknown exercise value: {}", html_select.value());
+            return;
+        };
+        if is_checked(&document, "exercise-count-in-toggle") {
+            presenter_clone.borrow_mut().on_count_in_started(CountInTarget::Exercise(kind));
+        } else {
+            presenter_clone.borrow_mut().on_exercise_control(ExerciseControl::Start(kind));
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("reference-melody-button", "click", move |_event: web_sys::Event| {
+        if IS_PLAYING_MELODY.load(Ordering::Relaxed) {
+            presenter_clone.borrow_mut().on_reference_track_control(ReferenceTrackControl::Stop);
+            return;
+        }
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(text_area) = document.get_element_by_id("reference-melody-text")
+            .and_then(|element| element.dyn_into::<HtmlTextAreaElement>().ok()) else { return; };
+        let error = match crate::common::reference_track::parse_reference_notes(&text_area.value()) {
+            Ok(notes) => {
+                presenter_clone.borrow_mut().on_reference_track_control(ReferenceTrackControl::Start(notes));
+                None
+            }
+            Err(error) => Some(translate_with("reference-melody-invalid", &[&error])),
+        };
+        if let Some(element) = document.get_element_by_id("reference-melody-error") {
+            element.set_text_content(error.as_deref());
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("reference-melody-from-recording", "click", move |_event: web_sys::Event| {
+        presenter_clone.borrow_mut().on_recording_control(RecordingControl::UseAsReferenceMelody);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("recording-button", "click", move |_event: web_sys::Event| {
+        if IS_RECORDING.load(Ordering::Relaxed) {
+            presenter_clone.borrow_mut().on_recording_control(RecordingControl::Stop);
+            return;
+        }
+        if counting_in() == Some(CountInTarget::Recording) {
+            presenter_clone.borrow_mut().on_count_in_cancelled();
+            return;
+        }
+        let Some(document) = web_sys::window().and_then(|window| window.document()) else { return; };
+        if is_checked(&document, "recording-count-in-toggle") {
+            presenter_clone.borrow_mut().on_count_in_started(CountInTarget::Recording);
+        } else {
+            presenter_clone.borrow_mut().on_recording_control(RecordingControl::Start);
+        }
+    });
+
+    for element_id in ["recording-export-from", "recording-export-to"] {
+        add_event_listener(element_id, "input", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let Some(slider_element) = document.get_element_by_id(element_id) else { return; };
+            let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>() else { return; };
+            if let Some(display_element) = document.get_element_by_id(&format!("{}-display", element_id)) {
+                display_element.set_text_content(Some(&format!("{} s", html_slider.value())));
+            }
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("recording-export-button", "click", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let slider = |id: &str| document.get_element_by_id(id)
+            .and_then(|element| element.dyn_into::<HtmlInputElement>().ok())
+            .and_then(|slider| slider.value().parse::<f32>().ok());
+        let (Some(from_seconds_ago), Some(to_seconds_ago)) = (slider("recording-export-from"), slider("recording-export-to")) else { return; };
+        presenter_clone.borrow_mut().on_recording_control(RecordingControl::ExportWav { from_seconds_ago, to_seconds_ago });
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("export-button", "click", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("export-format-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let format = match html_select.value().as_str() {
+            "pitch-csv" => SessionExportFormat::PitchTimelineCsv,
+            "statistics-csv" => SessionExportFormat::DegreeStatisticsCsv,
+            "json" => SessionExportFormat::Json,
+            _ => {
+                dev_log!("Unknown export format value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_session_export_requested(format);
+    });
+
+    add_event_listener("fullscreen-button", "click", move |_event: web_sys::Event| {
+        crate::web::layout::toggle_fullscreen();
+    });
+
+    add_event_listener("mini-view-button", "click", move |_event: web_sys::Event| {
+        crate::web::picture_in_picture::toggle_mini_view();
+    });
+
+    add_event_listener("share-link-button", "click", move |_event: web_sys::Event| {
+        crate::web::deep_link::copy_link();
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("immersive-layout-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("immersive-layout-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        presenter_clone.borrow_mut().on_immersive_layout_toggled(toggle.checked());
+    });
+
+    for (element_id, pane) in PANE_TOGGLES {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "change", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let Some(toggle) = document.get_element_by_id(element_id) else { return; };
+            let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+            presenter_clone.borrow_mut().on_pane_toggled(pane, toggle.checked());
+        });
+    }
+
+    for (element_id, end_session) in [("session-summary-button", false), ("end-session-button", true)] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "click", move |_event: web_sys::Event| {
+            presenter_clone.borrow_mut().on_session_summary_requested(end_session);
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("session-summary-export", "click", move |_event: web_sys::Event| {
+        presenter_clone.borrow_mut().on_session_export_requested(SessionExportFormat::Json);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("save-session-button", "click", move |_event: web_sys::Event| {
+        presenter_clone.borrow_mut().on_session_save_requested();
+    });
+
+    add_event_listener("saved-sessions-list", "click", crate::web::saved_sessions::on_list_click);
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("share-image-button", "click", move |_event: web_sys::Event| {
+        if !crate::web::share_image::share_prepared() {
+            presenter_clone.borrow_mut().on_share_image_requested();
+        }
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("tutorial-replay-button", "click", move |_event: web_sys::Event| {
+        presenter_clone.borrow_mut().on_tutorial_requested();
+    });
+
+    add_event_listener("install-app-button", "click", move |_event: web_sys::Event| {
+        crate::web::pwa::prompt_install();
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("session-summary-close", "click", move |_event: web_sys::Event| {
+        presenter_clone.borrow_mut().on_session_summary_closed();
+    });
+
+    let presenter_clone = presenter.clone();
+    add_escape_listener("session-summary-overlay", move || {
+        presenter_clone.borrow_mut().on_session_summary_closed();
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("tolerance-profile-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("tolerance-profile-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let profile = match html_select.value().as_str() {
+            "beginner" => ToleranceProfile::Beginner,
+            "intermediate" => ToleranceProfile::Intermediate,
+            "advanced" => ToleranceProfile::Advanced,
+            // A custom profile starts from the tolerance shown on the slider
+            "custom" => ToleranceProfile::Custom(tolerance_slider_cents(&document).unwrap_or(ToleranceProfile::default().cents())),
+            _ => {
+                dev_log!("Unknown tolerance profile value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_tolerance_profile_configured(profile);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("tolerance-cents", "input", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(cents) = tolerance_slider_cents(&document) else { return; };
+        presenter_clone.borrow_mut().on_tolerance_profile_configured(ToleranceProfile::Custom(cents));
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("scale-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("scale-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+        
+        let scale = match html_select.value().as_str() {
+            "custom" => storage::settings().custom_scale
+                .map_or_else(|| Scale::custom_from_pattern(Scale::Chromatic.pattern()), Scale::Custom),
+            id => {
+                let Some(scale) = Scale::from_id(id) else {
+                    dev_log!("Unknown scale value: {}", id);
+                    return;
+                };
+                scale
+            }
+        };
+        presenter_clone.borrow_mut().on_scale_changed(scale);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("custom-scale", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let mut pattern = [true; 12];
+        for (semitone, included) in pattern.iter_mut().enumerate().skip(1) {
+            *included = custom_scale_checkbox(&document, semitone).is_some_and(|checkbox| checkbox.checked());
+        }
+        presenter_clone.borrow_mut().on_scale_changed(Scale::custom_from_pattern(pattern));
+    });
+
+    // Add event listeners for display range radio buttons
+    let presenter_clone_1 = presenter.clone();
+    add_event_listener("display-range-two-octaves", "change", move |_event: web_sys::Event| {
+        let display_range = DisplayRange::TwoOctaves;
+        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
+            *current = display_range.clone();
+        }
+        presenter_clone_1.borrow_mut().on_display_range_changed(display_range);
+    });
+
+    let presenter_clone_2 = presenter.clone();
+    add_event_listener("display-range-one-octave", "change", move |_event: web_sys::Event| {
+        let display_range = DisplayRange::OneFullOctave;
+        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
+            *current = display_range.clone();
+        }
+        presenter_clone_2.borrow_mut().on_display_range_changed(display_range);
+    });
+
+    let presenter_clone_3 = presenter.clone();
+    add_event_listener("display-range-two-half-octaves", "change", move |_event: web_sys::Event| {
+        let display_range = DisplayRange::TwoHalfOctaves;
+        if let Ok(mut current) = CURRENT_DISPLAY_RANGE.try_lock() {
+            *current = display_range.clone();
+        }
+        presenter_clone_3.borrow_mut().on_display_range_changed(display_range);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("interval-zoom-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("interval-zoom-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let zoom = match html_select.value().as_str() {
+            "full" => IntervalZoom::Full,
+            "six-semitones" => IntervalZoom::SixSemitones,
+            "three-semitones" => IntervalZoom::ThreeSemitones,
+            _ => {
+                dev_log!("Unknown interval zoom value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_interval_zoom_changed(zoom);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("display-scene-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("display-scene-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let scene = match html_select.value().as_str() {
+            "intonation" => DisplayScene::Intonation,
+            "pitch-graph" => DisplayScene::PitchGraph,
+            "strobe" => DisplayScene::Strobe,
+            "needle" => DisplayScene::Needle,
+            _ => {
+                dev_log!("Unknown display scene value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_display_scene_changed(scene);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("needle-damping-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("needle-damping-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let damping = match html_select.value().as_str() {
+            "light" => NeedleDamping::Light,
+            "critical" => NeedleDamping::Critical,
+            "heavy" => NeedleDamping::Heavy,
+            _ => {
+                dev_log!("Unknown needle damping value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_needle_damping_changed(damping);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("smoothing-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("smoothing-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let Some(strategy) = SmoothingStrategy::from_id(&html_select.value()) else {
+            dev_log!("Unknown smoothing value: {}", html_select.value());
+            return;
+        };
+        presenter_clone.borrow_mut().on_smoothing_configured(strategy);
+        storage::update_settings(|settings| settings.smoothing = Some(strategy));
+    });
+
+    for element_id in ["pitch-detector-select", "pitch-detector-power", "pitch-detector-clarity"] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "input", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let Some(pitch_detection) = pitch_detection_from_sidebar(&document) else { return; };
+
+            show_pitch_detection(&document, &pitch_detection);
+            presenter_clone.borrow_mut().on_pitch_detection_configured(
+                pitch_detection.algorithm,
+                pitch_detection.power_threshold,
+                pitch_detection.clarity_threshold,
+            );
+            storage::update_settings(|settings| settings.pitch_detection = Some(pitch_detection));
+        });
+    }
+
+    add_event_listener("theme-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("theme-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let Some(theme) = Theme::from_name(&html_select.value()) else {
+            dev_log!("Unknown theme value: {}", html_select.value());
+            return;
+        };
+        storage::update_settings(|settings| settings.theme = Some(theme.clone()));
+        crate::common::theme::set_current_theme(theme);
+    });
+
+    add_event_listener("language-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("language-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let Some(language) = Language::from_code(&html_select.value()) else {
+            dev_log!("Unknown language value: {}", html_select.value());
+            return;
+        };
+        storage::update_settings(|settings| settings.language = Some(language));
+        crate::common::localization::set_current_language(language);
+        crate::web::localization::apply_language();
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("piano-mode-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("piano-mode-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let mode = match html_select.value().as_str() {
+            "none" => None,
+            "tonal-center" => Some(MidiInputMode::TonalCenter),
+            "target" => Some(MidiInputMode::TargetPitch),
+            _ => {
+                dev_log!("Unknown piano mode value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_piano_mode_changed(mode);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("staff-clef-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("staff-clef-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let clef = match html_select.value().as_str() {
+            "none" => None,
+            "treble" => Some(Clef::Treble),
+            "bass" => Some(Clef::Bass),
+            "alto" => Some(Clef::Alto),
+            _ => {
+                dev_log!("Unknown staff clef value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_staff_clef_changed(clef);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("input-meters-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("input-meters-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        presenter_clone.borrow_mut().on_input_meters_toggled(toggle.checked());
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("announce-pitch-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("announce-pitch-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        presenter_clone.borrow_mut().on_pitch_announcements_toggled(toggle.checked());
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("ghost-trail-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("ghost-trail-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        presenter_clone.borrow_mut().on_ghost_trail_toggled(toggle.checked());
+    });
+
+    for element_id in ["spectrogram-toggle", "spectrogram-range"] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "input", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let Some(toggle) = document.get_element_by_id("spectrogram-toggle") else { return; };
+            let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+            let Some(slider) = document.get_element_by_id("spectrogram-range") else { return; };
+            let Some(slider) = slider.dyn_ref::<HtmlInputElement>() else { return; };
+            let dynamic_range_db = slider.value().parse::<f32>().unwrap_or(crate::app_config::DEFAULT_SPECTROGRAM_DYNAMIC_RANGE_DB);
+
+            if let Some(display_element) = document.get_element_by_id("spectrogram-range-display") {
+                display_element.set_text_content(Some(&format!("{} dB", dynamic_range_db)));
+            }
+            presenter_clone.borrow_mut().on_spectrogram_configured(toggle.checked(), dynamic_range_db);
+        });
+    }
+
+    for element_id in ["metronome-toggle", "metronome-tempo", "metronome-subdivision-select", "metronome-click-toggle"] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "input", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let input = |id: &str| document.get_element_by_id(id).and_then(|element| element.dyn_into::<HtmlInputElement>().ok());
+            let (Some(toggle), Some(tempo), Some(click)) = (input("metronome-toggle"), input("metronome-tempo"), input("metronome-click-toggle")) else { return; };
+            let Some(select_element) = document.get_element_by_id("metronome-subdivision-select") else { return; };
+            let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+            let Ok(subdivision) = html_select.value().parse::<u32>() else {
+                dev_log!("Unknown metronome subdivision value: {}", html_select.value());
+                return;
+            };
+            let tempo_bpm = tempo.value().parse::<f32>()
+                .unwrap_or(crate::app_config::DEFAULT_METRONOME_TEMPO_BPM)
+                .clamp(crate::app_config::MIN_METRONOME_TEMPO_BPM, crate::app_config::MAX_METRONOME_TEMPO_BPM);
+            if let Some(display_element) = document.get_element_by_id("metronome-tempo-display") {
+                display_element.set_text_content(Some(&format!("{} BPM", tempo_bpm)));
+            }
+            let settings = toggle.checked().then_some(MetronomeSettings { tempo_bpm, subdivision, click: click.checked() });
+            presenter_clone.borrow_mut().on_metronome_configured(settings);
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("input-device-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("input-device-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+        let device_id = html_select.value();
+        presenter_clone.borrow_mut().on_input_device_selected(device_id.clone());
+        storage::update_settings(|settings| settings.input_device_id = Some(device_id));
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("automatic-gain-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("automatic-gain-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        presenter_clone.borrow_mut().on_automatic_gain_configured(toggle.checked());
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("calibration-button", "click", move |_event: web_sys::Event| {
+        let control = if IS_CALIBRATING.load(Ordering::Relaxed) { CalibrationControl::Cancel } else { CalibrationControl::Start };
+        presenter_clone.borrow_mut().on_calibration_control(control);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("midi-toggle", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(toggle) = document.get_element_by_id("midi-toggle") else { return; };
+        let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>() else { return; };
+        // MIDI stays connected for the rest of the visit; unchecking skips it on the next one
+        if toggle.checked() {
+            presenter_clone.borrow_mut().on_midi_enabled();
+        }
+        storage::update_settings(|settings| settings.midi_enabled = toggle.checked());
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("midi-input-mode-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("midi-input-mode-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let mode = match html_select.value().as_str() {
+            "tonal-center" => MidiInputMode::TonalCenter,
+            "target-pitch" => MidiInputMode::TargetPitch,
+            _ => {
+                dev_log!("Unknown MIDI input mode value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_midi_input_mode_changed(mode);
+    });
+
+    for element_id in ["midi-output-select", "midi-output-source-select"] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "change", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let select = |id: &str| document.get_element_by_id(id).and_then(|element| element.dyn_into::<HtmlSelectElement>().ok());
+            let (Some(output_select), Some(source_select)) = (select("midi-output-select"), select("midi-output-source-select")) else { return; };
+
+            let source = match source_select.value().as_str() {
+                "off" => MidiOutputSource::Off,
+                "detected-note" => MidiOutputSource::DetectedNote,
+                "reference-tone" => MidiOutputSource::ReferenceTone,
+                _ => {
+                    dev_log!("Unknown MIDI output source value: {}", source_select.value());
+                    return;
+                }
+            };
+            let output_id = Some(output_select.value()).filter(|output_id| !output_id.is_empty());
+            presenter_clone.borrow_mut().on_midi_output_configured(output_id, source);
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("peak-measure-select", "change", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(select_element) = document.get_element_by_id("peak-measure-select") else { return; };
+        let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() else { return; };
+
+        let peak_measure = match html_select.value().as_str() {
+            "sample-peak" => PeakMeasure::SamplePeak,
+            "loudness" => PeakMeasure::Loudness,
+            _ => {
+                dev_log!("Unknown peak measure value: {}", html_select.value());
+                return;
+            }
+        };
+        presenter_clone.borrow_mut().on_peak_measure_configured(peak_measure);
+    });
+
+    for element_id in ["reference-tone-timbre-select", "reference-tone-volume", "reference-tone-attack", "reference-tone-release"] {
+        let presenter_clone = presenter.clone();
+        add_event_listener(element_id, "input", move |_event: web_sys::Event| {
+            let Some(window) = web_sys::window() else { return; };
+            let Some(document) = window.document() else { return; };
+            let Some(settings) = reference_tone_from_sidebar(&document) else { return; };
+
+            for (display_id, text) in [
+                ("reference-tone-volume-display", format!("{:.0}%", settings.volume * 100.0)),
+                ("reference-tone-attack-display", format!("{:.0} ms", settings.envelope.attack_ms)),
+                ("reference-tone-release-display", format!("{:.0} ms", settings.envelope.release_ms)),
+            ] {
+                if let Some(display_element) = document.get_element_by_id(display_id) {
+                    display_element.set_text_content(Some(&text));
+                }
+            }
+            presenter_clone.borrow_mut().on_reference_tone_configured(settings);
+        });
+    }
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("reference-tone-degrees", "click", move |event: web_sys::Event| {
+        let Some(button) = event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()) else { return; };
+        let Some(degree) = button.get_attribute("data-degree").and_then(|degree| degree.parse::<usize>().ok()) else { return; };
+        presenter_clone.borrow_mut().on_scale_degree_requested(degree);
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("reference-interval-play", "click", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(input_element) = document.get_element_by_id("reference-interval") else { return; };
+        let Some(html_input) = input_element.dyn_ref::<HtmlInputElement>() else { return; };
+        let Ok(semitones) = html_input.value().parse::<i32>() else { return; };
+        presenter_clone.borrow_mut().on_reference_interval_requested(semitones.clamp(-36, 36));
+    });
+
+    let presenter_clone = presenter.clone();
+    add_event_listener("tonal-center-volume", "input", move |_event: web_sys::Event| {
+        let Some(window) = web_sys::window() else { return; };
+        let Some(document) = window.document() else { return; };
+        let Some(slider_element) = document.get_element_by_id("tonal-center-volume") else { return; };
+        let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>() else { return; };
+        let Ok(position) = html_slider.value().parse::<f32>() else { return; };
+        
+        CURRENT_TONAL_CENTER_VOLUME_POSITION.store(position as u8, Ordering::Relaxed);
+        let amplitude = slider_position_to_amplitude(position);
+
+        // Update icon state based on position
+        update_volume_icon_state(position == 0.0);
+
+        // If moving from 0, reset remembered volume to default
+        if position > 0.0 {
+            let previous_position = CURRENT_TONAL_CENTER_VOLUME_POSITION.swap(position as u8, Ordering::Relaxed);
+            if previous_position == 0 {
+                REMEMBERED_VOLUME_POSITION.store(DEFAULT_VOLUME_POSITION, Ordering::Relaxed);
+            }
+        }
+
+        if let Some(display_element) = document.get_element_by_id("tonal-center-volume-display") {
             display_element.set_text_content(Some(&slider_position_to_db_display(position)));
         }
         
@@ -352,7 +1549,7 @@ pub fn setup_event_listeners(presenter: Rc<RefCell<crate::presentation::Presente
 }
 
 
-pub fn sync_sidebar_with_presenter_state(model_data: &crate::common::shared_types::ModelUpdateResult) {
+pub fn sync_sidebar_with_presenter_state(model_data: &crate::common::shared_types::ModelUpdateResult, count_in: Option<CountInTarget>) {
     let Some(window) = window() else {
         return;
     };
@@ -371,64 +1568,293 @@ pub fn sync_sidebar_with_presenter_state(model_data: &crate::common::shared_type
     };
 
     // Save configuration to local storage only if it changed
-    let current_config = (model_data.tonal_center_note, model_data.tuning_system, model_data.scale, display_range.clone());
+    let current_config = (model_data.tonal_center_note, model_data.tuning_system.clone(), model_data.scale, display_range.clone(), model_data.concert_pitch_hz, model_data.transposition, model_data.note_naming, model_data.tolerance_profile);
     if let Ok(mut last_saved) = LAST_SAVED_CONFIG.try_lock() {
         if last_saved.as_ref() != Some(&current_config) {
-            storage::save_config(
-                model_data.tonal_center_note,
-                model_data.tuning_system,
-                model_data.scale,
-                display_range
-            );
+            let tuning = TuningSettings {
+                tonal_center_note: model_data.tonal_center_note,
+                tuning_system: model_data.tuning_system.clone(),
+                scale: model_data.scale,
+                display_range,
+                concert_pitch_hz: model_data.concert_pitch_hz,
+                transposition: model_data.transposition,
+                note_naming: model_data.note_naming,
+                tolerance_profile: model_data.tolerance_profile,
+                saved_ms: js_sys::Date::now(),
+            };
+            crate::web::tab_coordination::on_tuning_changed(&tuning);
+            storage::update_settings(|settings| {
+                settings.tuning = Some(tuning);
+                if let TuningSystem::Custom(offsets) = &model_data.tuning_system {
+                    settings.custom_tuning = Some(offsets.clone());
+                }
+                if let Scale::Custom(mask) = model_data.scale {
+                    settings.custom_scale = Some(mask);
+                }
+            });
+            crate::web::deep_link::write_to_address(&DeepLink {
+                tonal_center_note: Some(model_data.tonal_center_note),
+                scale: Some(model_data.scale),
+                tuning_system: Some(model_data.tuning_system.clone()),
+                exercise: None,
+            });
             *last_saved = Some(current_config);
         }
     }
 
     if let Some(display) = document.get_element_by_id("tonal-center-display") {
-        let formatted_note = crate::common::shared_types::midi_note_to_name(model_data.tonal_center_note);
+        let formatted_note = model_data.note_namer().letter_name(model_data.tonal_center_note);
         display.set_text_content(Some(&formatted_note));
     }
+    SUGGESTED_TONAL_CENTER_NOTE.store(model_data.suggested_tonal_center.unwrap_or(NO_SUGGESTED_TONAL_CENTER), Ordering::Relaxed);
+    if let Some(text) = document.get_element_by_id("root-suggestion-text") {
+        let name = model_data.suggested_tonal_center.map(|note| model_data.note_namer().letter_name(note));
+        text.set_text_content(name.map(|name| translate_with("root-suggestion", &[&name])).as_deref());
+    }
+    if let Some(row) = document.get_element_by_id("root-suggestion").and_then(|row| row.dyn_into::<HtmlElement>().ok()) {
+        let display = if model_data.suggested_tonal_center.is_some() { "flex" } else { "none" };
+        let _ = row.style().set_property("display", display);
+    }
     if let Some(select_element) = document.get_element_by_id("tuning-system-select") {
         if let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() {
-            let value = match model_data.tuning_system {
-                TuningSystem::EqualTemperament => "equal",
-                TuningSystem::JustIntonation => "just",
-            };
-            html_select.set_value(value);
+            html_select.set_value(model_data.tuning_system.id());
+        }
+    }
+    let just_ratios = match &model_data.tuning_system {
+        TuningSystem::JustIntonation(ratios) => Some(*ratios),
+        _ => None,
+    };
+    if let Ok(mut shown) = SHOWN_JUST_RATIOS.try_lock()
+        && *shown != just_ratios
+    {
+        if let Some(ratios) = &just_ratios {
+            show_just_ratios(&document, ratios);
+        }
+        if let Some(editor) = document.get_element_by_id("just-ratios").and_then(|editor| editor.dyn_into::<HtmlElement>().ok()) {
+            let _ = editor.style().set_property("display", if just_ratios.is_some() { "block" } else { "none" });
+        }
+        *shown = just_ratios;
+    }
+    let custom_tuning = match &model_data.tuning_system {
+        TuningSystem::Custom(offsets) => Some(offsets.clone()),
+        _ => None,
+    };
+    if let Ok(mut shown) = SHOWN_CUSTOM_TUNING.try_lock()
+        && *shown != custom_tuning
+    {
+        if let Some(offsets) = &custom_tuning {
+            show_custom_tuning(&document, offsets);
+        }
+        if let Some(editor) = document.get_element_by_id("custom-tuning").and_then(|editor| editor.dyn_into::<HtmlElement>().ok()) {
+            let _ = editor.style().set_property("display", if custom_tuning.is_some() { "block" } else { "none" });
         }
+        *shown = custom_tuning;
+    }
+    if let Some(select_element) = document.get_element_by_id("interval-mode-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.interval_mode {
+            IntervalMode::Absolute => "absolute",
+            IntervalMode::Wrapped => "wrapped",
+        });
+    }
+    if let Some(select_element) = document.get_element_by_id("smoothing-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(model_data.smoothing.id());
+    }
+    if let Some(select_element) = document.get_element_by_id("concert-pitch-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(&format!("{}", model_data.concert_pitch_hz));
+    }
+    if let Some(select_element) = document.get_element_by_id("transposition-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.transposition {
+            Transposition::C => "c",
+            Transposition::BFlat => "b-flat",
+            Transposition::EFlat => "e-flat",
+            Transposition::F => "f",
+            Transposition::A => "a",
+        });
+    }
+    if let Some(select_element) = document.get_element_by_id("note-naming-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.note_naming {
+            NoteNaming::Flats => "flats",
+            NoteNaming::Sharps => "sharps",
+            NoteNaming::ByKey => "by-key",
+            NoteNaming::Solfege => "solfege",
+            NoteNaming::Sargam => "sargam",
+        });
+    }
+    if let Some(select_element) = document.get_element_by_id("tolerance-profile-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.tolerance_profile {
+            ToleranceProfile::Beginner => "beginner",
+            ToleranceProfile::Intermediate => "intermediate",
+            ToleranceProfile::Advanced => "advanced",
+            ToleranceProfile::Custom(_) => "custom",
+        });
+    }
+    let tolerance_cents = model_data.tolerance_profile.cents();
+    if let Some(slider_element) = document.get_element_by_id("tolerance-cents")
+        && let Some(html_slider) = slider_element.dyn_ref::<HtmlInputElement>()
+    {
+        html_slider.set_value(&tolerance_cents.to_string());
+    }
+    if let Some(display_element) = document.get_element_by_id("tolerance-cents-display") {
+        display_element.set_text_content(Some(&format!("±{}¢", tolerance_cents)));
     }
     if let Some(select_element) = document.get_element_by_id("scale-select") {
         if let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>() {
-            let value = match model_data.scale {
-                Scale::Chromatic => "chromatic",
-                Scale::Major => "major",
-                Scale::Minor => "minor",
-                Scale::HarmonicMinor => "harmonic_minor",
-                Scale::MelodicMinor => "melodic_minor",
-                Scale::MajorPentatonic => "major_pentatonic",
-                Scale::MinorPentatonic => "minor_pentatonic",
-                Scale::Blues => "blues",
-                Scale::Dorian => "dorian",
-                Scale::Phrygian => "phrygian",
-                Scale::Lydian => "lydian",
-                Scale::Mixolydian => "mixolydian",
-                Scale::Locrian => "locrian",
-                Scale::WholeTone => "whole_tone",
-                Scale::Augmented => "augmented",
-                Scale::DiminishedHalfWhole => "diminished_half_whole",
-                Scale::DiminishedWholeHalf => "diminished_whole_half",
-                Scale::HungarianMinor => "hungarian_minor",
-                Scale::NeapolitanMinor => "neapolitan_minor",
-                Scale::NeapolitanMajor => "neapolitan_major",
-                Scale::Enigmatic => "enigmatic",
-                Scale::Persian => "persian",
-                Scale::DoubleHarmonicMajor => "double_harmonic_major",
-                Scale::Altered => "altered",
-                Scale::BebopMajor => "bebop_major",
-                Scale::BebopDominant => "bebop_dominant",
-            };
-            html_select.set_value(value);
+            html_select.set_value(model_data.scale.id());
+        }
+    }
+    let custom_scale = match model_data.scale {
+        Scale::Custom(mask) => Some(mask),
+        _ => None,
+    };
+    if let Ok(mut shown) = SHOWN_CUSTOM_SCALE.try_lock()
+        && *shown != custom_scale
+    {
+        if let Some(mask) = custom_scale {
+            for (semitone, included) in Scale::Custom(mask).pattern().into_iter().enumerate() {
+                if let Some(checkbox) = custom_scale_checkbox(&document, semitone) {
+                    checkbox.set_checked(included);
+                }
+            }
+        }
+        if let Some(builder) = document.get_element_by_id("custom-scale").and_then(|builder| builder.dyn_into::<HtmlElement>().ok()) {
+            let _ = builder.style().set_property("display", if custom_scale.is_some() { "block" } else { "none" });
+        }
+        *shown = custom_scale;
+    }
+    if let Some(select_element) = document.get_element_by_id("input-device-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+        && let Ok(mut shown_devices) = SHOWN_INPUT_DEVICES.try_lock()
+    {
+        let devices = &model_data.input_devices;
+        if *shown_devices != devices.available {
+            update_input_device_options(&document, html_select, &devices.available);
+            *shown_devices = devices.available.clone();
         }
+        if let Some(active_device_id) = &devices.active_device_id {
+            html_select.set_value(active_device_id);
+        }
+    }
+    if let Some(select_element) = document.get_element_by_id("peak-measure-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.peak_measure {
+            PeakMeasure::SamplePeak => "sample-peak",
+            PeakMeasure::Loudness => "loudness",
+        });
+    }
+    let input_gain = model_data.input_gain;
+    if let Some(toggle) = document.get_element_by_id("automatic-gain-toggle")
+        && let Some(toggle) = toggle.dyn_ref::<HtmlInputElement>()
+    {
+        toggle.set_checked(input_gain.automatic);
+    }
+    if let Some(display_element) = document.get_element_by_id("input-gain-display") {
+        display_element.set_text_content(Some(&format!("{:+.1} dB", 20.0 * input_gain.gain.log10())));
+    }
+    let calibration_status = match model_data.calibration_step {
+        CalibrationStep::MeasuringNoise { progress } => translate_with("calibration-measuring-noise", &[&format!("{:.0}", progress * 100.0)]),
+        CalibrationStep::MeasuringVoice { progress } => translate_with("calibration-measuring-voice", &[&format!("{:.0}", progress * 100.0)]),
+        CalibrationStep::Failed => translate("calibration-failed").to_string(),
+        CalibrationStep::Idle if input_gain.calibration.is_some() => translate("calibration-done").to_string(),
+        CalibrationStep::Idle => translate("calibration-none").to_string(),
+    };
+    let is_calibrating = matches!(model_data.calibration_step, CalibrationStep::MeasuringNoise { .. } | CalibrationStep::MeasuringVoice { .. });
+    IS_CALIBRATING.store(is_calibrating, Ordering::Relaxed);
+    if let Some(button) = document.get_element_by_id("calibration-button") {
+        button.set_text_content(Some(translate(if is_calibrating { "calibration-cancel" } else { "calibration-start" })));
+    }
+    if let Some(status) = document.get_element_by_id("calibration-status")
+        && status.text_content().as_deref() != Some(calibration_status.as_str())
+    {
+        status.set_text_content(Some(&calibration_status));
+    }
+    let midi_outputs = &model_data.midi_outputs;
+    if let Some(controls) = document.get_element_by_id("midi-controls").and_then(|controls| controls.dyn_into::<HtmlElement>().ok()) {
+        let _ = controls.style().set_property("display", if midi_outputs.enabled { "block" } else { "none" });
+    }
+    if let Some(select_element) = document.get_element_by_id("midi-output-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+        && let Ok(mut shown_outputs) = SHOWN_MIDI_OUTPUTS.try_lock()
+    {
+        if *shown_outputs != midi_outputs.available {
+            update_midi_output_options(&document, html_select, &midi_outputs.available);
+            *shown_outputs = midi_outputs.available.clone();
+        }
+        html_select.set_value(midi_outputs.active_output_id.as_deref().unwrap_or(""));
+    }
+    if let Some(select_element) = document.get_element_by_id("midi-output-source-select")
+        && let Some(html_select) = select_element.dyn_ref::<HtmlSelectElement>()
+    {
+        html_select.set_value(match model_data.midi_output_source {
+            MidiOutputSource::Off => "off",
+            MidiOutputSource::DetectedNote => "detected-note",
+            MidiOutputSource::ReferenceTone => "reference-tone",
+        });
+    }
+    if let Ok(mut counting_in) = COUNTING_IN.lock() {
+        *counting_in = count_in;
+    }
+    let running_exercise = model_data.exercise.as_ref().filter(|exercise| exercise.current_target.is_some());
+    IS_EXERCISING.store(running_exercise.is_some(), Ordering::Relaxed);
+    if let Some(button) = document.get_element_by_id("exercise-button") {
+        let key = match count_in {
+            _ if running_exercise.is_some() => "exercise-stop",
+            Some(CountInTarget::Exercise(_)) => "count-in-cancel",
+            _ => "exercise-start",
+        };
+        button.set_text_content(Some(translate(key)));
+    }
+    if let Some(status) = document.get_element_by_id("exercise-status") {
+        let text = model_data.exercise.as_ref()
+            .map(|exercise| translate_with("exercise-status", &[&exercise.results.len().to_string(), &exercise.target_count.to_string()]));
+        status.set_text_content(text.as_deref());
+    }
+    if let Some(button) = document.get_element_by_id("share-image-button") {
+        button.set_text_content(Some(translate(if crate::web::share_image::is_prepared() { "share-image-ready" } else { "share-image-button" })));
+    }
+    let playing_melody = model_data.reference_track.as_ref().filter(|track| track.position_ms < track.duration_ms);
+    IS_PLAYING_MELODY.store(playing_melody.is_some(), Ordering::Relaxed);
+    if let Some(button) = document.get_element_by_id("reference-melody-button") {
+        button.set_text_content(Some(translate(if playing_melody.is_some() { "reference-melody-stop" } else { "reference-melody-start" })));
+    }
+    if let Some(status) = document.get_element_by_id("reference-melody-status") {
+        let text = model_data.reference_track.as_ref().map(|track| {
+            let progress = translate_with("reference-melody-progress", &[&format!("{:.0}", 100.0 * track.position_ms / track.duration_ms)]);
+            let results = track.results.iter().map(|result| {
+                let deviation = result.mean_cents.map_or("-".to_string(), |cents| format!("{:+.0} ¢", cents));
+                format!("{} {}", model_data.note_namer().name(result.note), deviation)
+            });
+            std::iter::once(progress).chain(results).collect::<Vec<_>>().join(" · ")
+        });
+        status.set_text_content(text.as_deref());
+    }
+    let recording = model_data.recording;
+    IS_RECORDING.store(recording.is_recording, Ordering::Relaxed);
+    if let Some(button) = document.get_element_by_id("recording-button") {
+        let key = match count_in {
+            _ if recording.is_recording => "recording-stop",
+            Some(CountInTarget::Recording) => "count-in-cancel",
+            _ => "recording-start",
+        };
+        button.set_text_content(Some(translate(key)));
+    }
+    if let Some(status) = document.get_element_by_id("recording-status") {
+        let key = if recording.is_recording { "recording-status-recording" } else { "recording-status-recorded" };
+        let text = (recording.is_recording || recording.recorded_seconds > 0.0)
+            .then(|| translate_with(key, &[&format!("{:.1}", recording.recorded_seconds)]));
+        status.set_text_content(text.as_deref());
     }
     let current_position = CURRENT_TONAL_CENTER_VOLUME_POSITION.load(Ordering::Relaxed) as f32;
     if let Some(slider_element) = document.get_element_by_id("tonal-center-volume") {
@@ -1,97 +1,244 @@
 #![cfg(target_arch = "wasm32")]
 
 use {
-    web_sys::window,
+    web_sys::{window, Storage},
     serde::{Serialize, Deserialize},
-    crate::common::shared_types::{TuningSystem, Scale, MidiNote, DisplayRange},
-    crate::common::dev_log,
+    serde_json::{Map, Value},
+    std::cell::RefCell,
+    std::rc::Rc,
+    wasm_bindgen::{closure::Closure, JsCast},
+    crate::app_config::TUNING_SETTINGS_EXPIRATION_MS,
+    crate::common::shared_types::{SessionStatistics, Theme},
+    crate::common::settings::Settings,
+    crate::common::localization::Language,
+    crate::common::{dev_log, warn_log},
 };
 
-const STORAGE_KEY: &str = "intonation_toy_config";
-const EXPIRATION_MS: i64 = 24 * 60 * 60 * 1000; // 24 hours in milliseconds
+/// All settings in one versioned document
+const SETTINGS_STORAGE_KEY: &str = "intonation_toy_settings";
+/// Statistics of the last session, which expire like the tuning; the
+/// settings of the session are kept in the settings document only
+const SESSION_STORAGE_KEY: &str = "intonation_toy_session";
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct StoredConfig {
-    pub tonal_center_note: MidiNote,
-    pub tuning_system: TuningSystem,
-    pub scale: Scale,
-    pub display_range: DisplayRange,
-    pub timestamp: i64,
+/// Keys the settings were kept under before they were unified, each with
+/// the field it becomes in a settings document of version 0
+const LEGACY_STORAGE_KEYS: [(&str, &str); 9] = [
+    ("intonation_toy_config", "config"),
+    ("intonation_toy_input_calibration", "input_calibrations"),
+    ("intonation_toy_custom_tuning", "custom_tuning"),
+    ("intonation_toy_custom_scale", "custom_scale"),
+    ("intonation_toy_theme", "theme"),
+    ("intonation_toy_language", "language"),
+    ("intonation_toy_key_bindings", "key_bindings"),
+    ("intonation_toy_pane_layout", "pane_layout"),
+    ("intonation_toy_tutorial_seen", "tutorial_seen"),
+];
+
+thread_local! {
+    /// Settings as last read or stored, read from local storage on first use
+    static SETTINGS: RefCell<Option<StoredSettings>> = const { RefCell::new(None) };
+    /// Called with the settings whenever they change, in this tab or another
+    static SUBSCRIBERS: RefCell<Vec<SettingsSubscriber>> = const { RefCell::new(Vec::new()) };
 }
 
-impl StoredConfig {
-    pub fn new(tonal_center_note: MidiNote, tuning_system: TuningSystem, scale: Scale, display_range: DisplayRange) -> Self {
-        let timestamp = js_sys::Date::now() as i64;
-        Self {
-            tonal_center_note,
-            tuning_system,
-            scale,
-            display_range,
-            timestamp,
-        }
+type SettingsSubscriber = Rc<dyn Fn(&Settings)>;
+
+struct StoredSettings {
+    settings: Settings,
+    /// Whether the stored document may be written over; not when it could
+    /// not be read, as one of a newer version, so that it is kept for the
+    /// build that can
+    writable: bool,
+}
+
+fn local_storage() -> Option<Storage> {
+    window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+/// The stored settings, or the defaults where none are stored
+pub fn settings() -> Settings {
+    SETTINGS.with(|stored| stored.borrow_mut().get_or_insert_with(read_settings).settings.clone())
+}
+
+/// Change the settings, store them and tell the subscribers
+pub fn update_settings(update: impl FnOnce(&mut Settings)) {
+    let (settings, writable) = SETTINGS.with(|stored| {
+        let mut stored = stored.borrow_mut();
+        let stored = stored.get_or_insert_with(read_settings);
+        update(&mut stored.settings);
+        (stored.settings.clone(), stored.writable)
+    });
+    if writable {
+        write_settings(&settings);
+    } else {
+        dev_log!("Not storing settings over a document that could not be read");
     }
+    notify_subscribers(&settings);
+}
 
-    pub fn is_expired(&self) -> bool {
-        let current_time = js_sys::Date::now() as i64;
-        (current_time - self.timestamp) > EXPIRATION_MS
+/// Call `subscriber` with the settings whenever they change, including when
+/// another tab changes them
+pub fn subscribe_settings(subscriber: impl Fn(&Settings) + 'static) {
+    let first = SUBSCRIBERS.with(|subscribers| {
+        let mut subscribers = subscribers.borrow_mut();
+        subscribers.push(Rc::new(subscriber));
+        subscribers.len() == 1
+    });
+    if first {
+        listen_to_other_tabs();
     }
 }
 
-pub fn save_config(tonal_center_note: MidiNote, tuning_system: TuningSystem, scale: Scale, display_range: DisplayRange) {
-    let Some(window) = window() else {
-        dev_log!("Failed to get window for storage");
-        return;
-    };
+fn notify_subscribers(settings: &Settings) {
+    // Subscribers may read or change the settings themselves
+    let subscribers = SUBSCRIBERS.with(|subscribers| subscribers.borrow().clone());
+    for subscriber in subscribers {
+        subscriber(settings);
+    }
+}
 
-    let Some(storage) = window.local_storage().ok().flatten() else {
+/// Read the settings again when another tab stores them
+fn listen_to_other_tabs() {
+    let Some(window) = window() else { return };
+    let closure = Closure::<dyn FnMut(web_sys::StorageEvent)>::new(|event: web_sys::StorageEvent| {
+        // No key when the storage was cleared
+        if event.key().is_some_and(|key| key != SETTINGS_STORAGE_KEY) {
+            return;
+        }
+        let stored = read_settings();
+        let settings = stored.settings.clone();
+        SETTINGS.with(|current| *current.borrow_mut() = Some(stored));
+        notify_subscribers(&settings);
+    });
+    if let Err(_e) = window.add_event_listener_with_callback("storage", closure.as_ref().unchecked_ref()) {
+        dev_log!("Failed to listen for settings of other tabs: {:?}", _e);
+    }
+    closure.forget();
+}
+
+fn read_settings() -> StoredSettings {
+    let defaults = StoredSettings { settings: Settings::default(), writable: true };
+    let Some(storage) = local_storage() else {
         dev_log!("Failed to get local storage");
-        return;
+        return defaults;
     };
 
-    let config = StoredConfig::new(tonal_center_note, tuning_system, scale, display_range);
-    
-    match serde_json::to_string(&config) {
-        Ok(json) => {
-            if let Err(_e) = storage.set_item(STORAGE_KEY, &json) {
-                dev_log!("Failed to save config to local storage: {:?}", _e);
+    let (json, legacy) = match storage.get_item(SETTINGS_STORAGE_KEY).ok().flatten() {
+        Some(json) => (json, false),
+        None => match read_legacy_settings(&storage) {
+            Some(json) => (json, true),
+            None => return defaults,
+        },
+    };
+
+    let mut settings = match Settings::from_json_dropping(&json) {
+        Ok((settings, dropped)) => {
+            if !dropped.is_empty() {
+                warn_log!("Stored settings {} could not be read and take their defaults", dropped.join(", "));
+            }
+            settings
+        }
+        Err(e) => {
+            warn_log!("Failed to read stored settings, using defaults without storing them: {}", e);
+            return StoredSettings { writable: false, ..defaults };
+        }
+    };
+    settings.expire_tuning(js_sys::Date::now());
+    if legacy {
+        dev_log!("Moving stored settings to version {}", settings.version);
+        // The legacy keys stay until the settings are safely stored
+        if write_settings(&settings) {
+            for (key, _) in LEGACY_STORAGE_KEYS {
+                let _ = storage.remove_item(key);
             }
         }
+    }
+    StoredSettings { settings, writable: true }
+}
+
+/// Settings document of version 0 from the legacy keys, if any is set
+fn read_legacy_settings(storage: &Storage) -> Option<String> {
+    let mut document = Map::new();
+    for (key, field) in LEGACY_STORAGE_KEYS {
+        let Some(item) = storage.get_item(key).ok().flatten() else { continue };
+        let value = match field {
+            // Stored as plain text rather than JSON
+            "theme" => Theme::from_name(&item).map(|_| Value::String(item)),
+            "language" => Language::from_code(&item).map(|language| Value::String(language.code().to_string())),
+            "custom_scale" => item.parse::<u16>().ok().map(Value::from),
+            "tutorial_seen" => Some(Value::Bool(true)),
+            _ => serde_json::from_str(&item).ok(),
+        };
+        if let Some(value) = value {
+            document.insert(field.to_string(), value);
+        }
+    }
+    (!document.is_empty()).then(|| Value::Object(document).to_string())
+}
+
+/// Whether the settings were stored
+fn write_settings(settings: &Settings) -> bool {
+    let Some(storage) = local_storage() else {
+        dev_log!("Failed to get local storage");
+        return false;
+    };
+
+    match settings.to_json() {
+        Ok(json) => match storage.set_item(SETTINGS_STORAGE_KEY, &json) {
+            Ok(()) => true,
+            Err(_e) => {
+                dev_log!("Failed to save settings to local storage: {:?}", _e);
+                false
+            }
+        },
         Err(_e) => {
-            dev_log!("Failed to serialize config: {:?}", _e);
+            dev_log!("Failed to serialize settings: {}", _e);
+            false
         }
     }
 }
 
-pub fn load_config() -> Option<StoredConfig> {
-    let window = window()?;
-    let storage = window.local_storage().ok().flatten()?;
-    let json = storage.get_item(STORAGE_KEY).ok().flatten()?;
-    
-    match serde_json::from_str::<StoredConfig>(&json) {
-        Ok(config) => {
-            if config.is_expired() {
-                dev_log!("Stored config is expired, using defaults");
-                let _ = storage.remove_item(STORAGE_KEY);
-                None
-            } else {
-                dev_log!("Loaded config from local storage: tonal_center={}, tuning_system={:?}, scale={:?}, display_range={:?}",
-                    config.tonal_center_note, config.tuning_system, config.scale, config.display_range);
-                Some(config)
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    session_statistics: SessionStatistics,
+    timestamp: i64,
+}
+
+pub fn save_session_statistics(session_statistics: SessionStatistics) {
+    let Some(storage) = local_storage() else {
+        dev_log!("Failed to get local storage");
+        return;
+    };
+
+    let session = StoredSession { session_statistics, timestamp: js_sys::Date::now() as i64 };
+    match serde_json::to_string(&session) {
+        Ok(json) => {
+            if let Err(_e) = storage.set_item(SESSION_STORAGE_KEY, &json) {
+                dev_log!("Failed to save session to local storage: {:?}", _e);
             }
         }
         Err(_e) => {
-            dev_log!("Failed to deserialize config: {:?}", _e);
-            let _ = storage.remove_item(STORAGE_KEY);
-            None
+            dev_log!("Failed to serialize session: {:?}", _e);
         }
     }
 }
 
-pub fn clear_config() {
-    if let Some(window) = window() {
-        if let Some(storage) = window.local_storage().ok().flatten() {
-            let _ = storage.remove_item(STORAGE_KEY);
+/// Statistics of the last session, unless they are older than the tuning expiration
+pub fn load_session_statistics() -> Option<SessionStatistics> {
+    let storage = local_storage()?;
+    let json = storage.get_item(SESSION_STORAGE_KEY).ok().flatten()?;
+
+    match serde_json::from_str::<StoredSession>(&json) {
+        Ok(session) if js_sys::Date::now() - session.timestamp as f64 <= TUNING_SETTINGS_EXPIRATION_MS => Some(session.session_statistics),
+        Ok(_) => {
+            dev_log!("Stored session is expired");
+            let _ = storage.remove_item(SESSION_STORAGE_KEY);
+            None
+        }
+        Err(_e) => {
+            dev_log!("Failed to deserialize session: {:?}", _e);
+            let _ = storage.remove_item(SESSION_STORAGE_KEY);
+            None
         }
     }
 }
-
@@ -0,0 +1,156 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Coordinating the tabs of the app over a `BroadcastChannel`
+//!
+//! Each tab announces itself on the channel every heartbeat and whenever its
+//! microphone input is lost, muted or regained, and says goodbye as the page
+//! goes away. The sidebar warns while another tab listens to the
+//! microphone too, and lets the user make this tab the controller of others
+//! or the display of one. Browsers without `BroadcastChannel` run every tab
+//! on its own.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use gloo_timers::callback::Interval;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{BroadcastChannel, HtmlElement, HtmlSelectElement, MessageEvent};
+use crate::app_config::TAB_HEARTBEAT_MS;
+use crate::common::dev_log;
+use crate::common::settings::TuningSettings;
+use crate::common::tab_coordination::{TabAction, TabCoordinator, TabMessage, TabRole};
+use crate::presentation::Presenter;
+
+const CHANNEL_NAME: &str = "intonation-toy";
+
+thread_local! {
+    static CHANNEL: RefCell<Option<BroadcastChannel>> = const { RefCell::new(None) };
+    static COORDINATOR: RefCell<Option<TabCoordinator>> = const { RefCell::new(None) };
+    /// Tuning of this tab, sent to displays as they appear
+    static TUNING: RefCell<Option<TuningSettings>> = const { RefCell::new(None) };
+}
+
+/// Join the other tabs; the microphone is this tab's by now
+pub fn setup_tab_coordination(presenter: Rc<RefCell<Presenter>>) {
+    let channel = match BroadcastChannel::new(CHANNEL_NAME) {
+        Ok(channel) => channel,
+        Err(_e) => {
+            dev_log!("BroadcastChannel is not available: {:?}", _e);
+            return;
+        }
+    };
+    let tab_id = format!("{}-{}", js_sys::Date::now() as u64, (js_sys::Math::random() * 1e9) as u64);
+    let mut coordinator = TabCoordinator::new(tab_id);
+    coordinator.set_listening(true);
+    COORDINATOR.with(|stored| *stored.borrow_mut() = Some(coordinator));
+
+    let message_closure = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let Some(message) = event.data().as_string().and_then(|json| serde_json::from_str::<TabMessage>(&json).ok()) else {
+            return;
+        };
+        let action = with_coordinator(|coordinator| coordinator.on_message(message, js_sys::Date::now())).flatten();
+        match action {
+            Some(TabAction::SendTuning) => send_tuning(),
+            Some(TabAction::ApplyTuning(tuning)) => presenter.borrow_mut().on_controller_tuning(tuning),
+            None => {}
+        }
+        update_warning();
+    }) as Box<dyn FnMut(_)>);
+    channel.set_onmessage(Some(message_closure.as_ref().unchecked_ref()));
+    message_closure.forget();
+    CHANNEL.with(|stored| *stored.borrow_mut() = Some(channel));
+    announce();
+
+    Interval::new(TAB_HEARTBEAT_MS, || {
+        with_coordinator(|coordinator| coordinator.expire(js_sys::Date::now()));
+        announce();
+        update_warning();
+    })
+    .forget();
+
+    let Some(window) = web_sys::window() else { return };
+    let pagehide_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        if let Some(message) = with_coordinator(|coordinator| coordinator.leaving()) {
+            post(&message);
+        }
+    }) as Box<dyn FnMut(_)>);
+    let _ = window.add_event_listener_with_callback("pagehide", pagehide_closure.as_ref().unchecked_ref());
+    pagehide_closure.forget();
+
+    let Some(select) = window
+        .document()
+        .and_then(|document| document.get_element_by_id("tab-role-select"))
+        .and_then(|select| select.dyn_into::<HtmlSelectElement>().ok())
+    else {
+        return;
+    };
+    let role_select = select.clone();
+    let change_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        let role = TabRole::from_id(&role_select.value()).unwrap_or_default();
+        with_coordinator(|coordinator| coordinator.set_role(role));
+        announce();
+        send_tuning();
+    }) as Box<dyn FnMut(_)>);
+    let _ = select.add_event_listener_with_callback("change", change_closure.as_ref().unchecked_ref());
+    change_closure.forget();
+}
+
+/// Tell the other tabs whether this tab still listens to the microphone, as
+/// the input is lost, muted or regained
+pub fn on_input_state(listening: bool) {
+    let changed = with_coordinator(|coordinator| {
+        let changed = coordinator.is_listening() != listening;
+        coordinator.set_listening(listening);
+        changed
+    });
+    if changed == Some(true) {
+        announce();
+        update_warning();
+    }
+}
+
+/// Keep the tuning of this tab, and send it to the displays of a controller
+pub fn on_tuning_changed(tuning: &TuningSettings) {
+    TUNING.with(|stored| *stored.borrow_mut() = Some(tuning.clone()));
+    send_tuning();
+}
+
+fn send_tuning() {
+    let Some(tuning) = TUNING.with(|stored| stored.borrow().clone()) else { return };
+    if let Some(message) = with_coordinator(|coordinator| coordinator.tuning(tuning)).flatten() {
+        post(&message);
+    }
+}
+
+fn announce() {
+    if let Some(message) = with_coordinator(|coordinator| coordinator.presence()) {
+        post(&message);
+    }
+}
+
+fn post(message: &TabMessage) {
+    let Ok(json) = serde_json::to_string(message) else { return };
+    CHANNEL.with(|channel| {
+        if let Some(channel) = channel.borrow().as_ref()
+            && let Err(_e) = channel.post_message(&JsValue::from_str(&json))
+        {
+            dev_log!("Failed to message the other tabs: {:?}", _e);
+        }
+    });
+}
+
+fn update_warning() {
+    let contention = with_coordinator(|coordinator| coordinator.has_microphone_contention()).unwrap_or(false);
+    let Some(warning) = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id("tab-contention-warning"))
+        .and_then(|warning| warning.dyn_into::<HtmlElement>().ok())
+    else {
+        return;
+    };
+    let _ = warning.style().set_property("display", if contention { "" } else { "none" });
+}
+
+fn with_coordinator<T>(f: impl FnOnce(&mut TabCoordinator) -> T) -> Option<T> {
+    COORDINATOR.with(|coordinator| coordinator.borrow_mut().as_mut().map(f))
+}
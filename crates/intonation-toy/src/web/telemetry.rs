@@ -0,0 +1,165 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Usage telemetry in the browser
+//!
+//! Builds with a telemetry endpoint send consented events to it as beacons,
+//! which reach it even as the page closes. They ask for consent in a banner
+//! on the first visit, and the About section keeps a checkbox to change the
+//! answer. Builds without an endpoint neither ask nor send.
+
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use crate::app_config::{TELEMETRY_ENDPOINT, TELEMETRY_FLUSH_INTERVAL_MS};
+use crate::common::telemetry::{batch_to_json, NoopBackend, Telemetry, TelemetryBackend, TelemetryEvent};
+
+thread_local! {
+    static TELEMETRY: RefCell<Option<Telemetry>> = const { RefCell::new(None) };
+    /// Start of the current flush interval, and the frames rendered since
+    static INTERVAL_START_MS: Cell<f64> = const { Cell::new(0.0) };
+    static FRAMES: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Sends each batch as one `navigator.sendBeacon` POST of a JSON array
+pub struct BeaconBackend {
+    endpoint: &'static str,
+}
+
+impl TelemetryBackend for BeaconBackend {
+    fn send(&self, events: &[TelemetryEvent]) {
+        let Some(window) = web_sys::window() else { return };
+        if !window.navigator().send_beacon_with_opt_str(self.endpoint, Some(&batch_to_json(events))).unwrap_or(false) {
+            crate::common::dev_log!("Telemetry beacon of {} events was not queued", events.len());
+        }
+    }
+}
+
+/// Start telemetry with the stored consent, and ask for it if unanswered
+pub fn setup_telemetry() {
+    let consent = crate::web::storage::settings().telemetry_consent;
+    let backend: Box<dyn TelemetryBackend> = match TELEMETRY_ENDPOINT {
+        Some(endpoint) => Box::new(BeaconBackend { endpoint }),
+        None => Box::new(NoopBackend),
+    };
+    TELEMETRY.with(|telemetry| *telemetry.borrow_mut() = Some(Telemetry::new(backend, consent)));
+    INTERVAL_START_MS.with(|start| start.set(js_sys::Date::now()));
+    record(TelemetryEvent::PageView);
+
+    if TELEMETRY_ENDPOINT.is_none() {
+        return;
+    }
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(setting) = document.get_element_by_id("telemetry-setting") {
+        let _ = setting.dyn_ref::<web_sys::HtmlElement>().map(|setting| setting.style().set_property("display", ""));
+    }
+    update_setting(consent == Some(true));
+    set_banner_visible(consent.is_none());
+
+    // The answer may also come from another tab
+    let answered = Cell::new(consent);
+    crate::web::storage::subscribe_settings(move |settings| {
+        if let Some(consent) = settings.telemetry_consent
+            && answered.replace(Some(consent)) != Some(consent)
+        {
+            apply_consent(consent);
+        }
+    });
+
+    add_listener(&document, "telemetry-consent-accept", "click", |_| set_consent(true));
+    add_listener(&document, "telemetry-consent-decline", "click", |_| set_consent(false));
+    add_listener(&document, "telemetry-toggle", "change", |event| {
+        let checked = event
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+            .is_some_and(|input| input.checked());
+        set_consent(checked);
+    });
+
+    // The last events go out as the page is hidden, which may be for good.
+    // No frames render while it is hidden, so the frame rate is sampled anew
+    // once it shows again.
+    let visibility_document = document.clone();
+    let visibility_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+        if visibility_document.hidden() {
+            flush();
+        } else {
+            INTERVAL_START_MS.with(|start| start.set(js_sys::Date::now()));
+            FRAMES.with(|frames| frames.set(0));
+        }
+    }) as Box<dyn FnMut(_)>);
+    let _ = document.add_event_listener_with_callback("visibilitychange", visibility_closure.as_ref().unchecked_ref());
+    visibility_closure.forget();
+}
+
+/// Queue a usage event, which the log of bug reports also keeps
+pub fn record(event: TelemetryEvent) {
+    crate::web::bug_report::log_event(&format!("{:?}", event));
+    TELEMETRY.with(|telemetry| {
+        if let Some(telemetry) = telemetry.borrow_mut().as_mut() {
+            telemetry.record(event);
+        }
+    });
+}
+
+/// Count a rendered frame; every flush interval this samples the frame rate
+/// and sends what was recorded
+pub fn on_frame(now_ms: f64) {
+    let frames = FRAMES.with(|frames| frames.get() + 1);
+    let elapsed_ms = now_ms - INTERVAL_START_MS.with(Cell::get);
+    if elapsed_ms < TELEMETRY_FLUSH_INTERVAL_MS {
+        FRAMES.with(|count| count.set(frames));
+        return;
+    }
+    FRAMES.with(|count| count.set(0));
+    INTERVAL_START_MS.with(|start| start.set(now_ms));
+    record(TelemetryEvent::frame_rate(frames as f64 * 1000.0 / elapsed_ms));
+    flush();
+}
+
+fn flush() {
+    TELEMETRY.with(|telemetry| {
+        if let Some(telemetry) = telemetry.borrow_mut().as_mut() {
+            telemetry.flush();
+        }
+    });
+}
+
+fn set_consent(consent: bool) {
+    crate::web::storage::update_settings(|settings| settings.telemetry_consent = Some(consent));
+}
+
+fn apply_consent(consent: bool) {
+    TELEMETRY.with(|telemetry| {
+        if let Some(telemetry) = telemetry.borrow_mut().as_mut() {
+            telemetry.set_consent(consent);
+        }
+    });
+    update_setting(consent);
+    set_banner_visible(false);
+    flush();
+}
+
+fn update_setting(consent: bool) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(toggle) = document.get_element_by_id("telemetry-toggle").and_then(|toggle| toggle.dyn_into::<web_sys::HtmlInputElement>().ok()) {
+        toggle.set_checked(consent);
+    }
+}
+
+fn set_banner_visible(visible: bool) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(banner) = document.get_element_by_id("telemetry-consent-banner") {
+        let _ = if visible {
+            banner.class_list().remove_1("telemetry-consent-banner-hidden")
+        } else {
+            banner.class_list().add_1("telemetry-consent-banner-hidden")
+        };
+    }
+}
+
+fn add_listener(document: &web_sys::Document, element_id: &str, event_type: &str, handler: impl FnMut(web_sys::Event) + 'static) {
+    let Some(element) = document.get_element_by_id(element_id) else { return };
+    let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut(_)>);
+    let _ = element.add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref());
+    closure.forget();
+}
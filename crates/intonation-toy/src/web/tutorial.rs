@@ -0,0 +1,90 @@
+#![cfg(target_arch = "wasm32")]
+
+//! Overlay of the tutorial: a highlight around the element a step explains,
+//! dimming the rest of the page, and a card with the explanation beside it
+
+use wasm_bindgen::JsCast;
+use web_sys::{Document, HtmlElement};
+use crate::common::localization::{translate, translate_with};
+use crate::common::tutorial::{Tutorial, TutorialStep};
+
+/// Space between the highlight and the element, and between the highlight
+/// and the card, in CSS pixels
+const HIGHLIGHT_PADDING: f64 = 6.0;
+const CARD_GAP: f64 = 16.0;
+
+pub fn show_tutorial_step(tutorial: Tutorial) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let step = tutorial.step();
+
+    set_text(&document, "tutorial-progress", &translate_with("tutorial-progress", &[&tutorial.number().to_string(), &TutorialStep::ALL.len().to_string()]));
+    set_text(&document, "tutorial-title", translate(step.title_key()));
+    set_text(&document, "tutorial-text", translate(step.text_key()));
+    set_text(&document, "tutorial-next", translate(if tutorial.is_last() { "tutorial-done" } else { "tutorial-next" }));
+
+    if step.in_sidebar()
+        && let Some(target) = document.get_element_by_id(step.target_id())
+    {
+        target.scroll_into_view();
+    }
+    if let Some(overlay) = document.get_element_by_id("tutorial-overlay") {
+        let _ = overlay.class_list().remove_1("tutorial-overlay-hidden");
+    }
+    place_tutorial_highlight(tutorial);
+    crate::web::accessibility::announce(&format!("{}. {}", translate(step.title_key()), translate(step.text_key())));
+    if let Some(next) = document.get_element_by_id("tutorial-next").and_then(|next| next.dyn_into::<HtmlElement>().ok()) {
+        let _ = next.focus();
+    }
+}
+
+/// Move the highlight and the card to where the element of the step is now
+pub fn place_tutorial_highlight(tutorial: Tutorial) {
+    let Some(window) = web_sys::window() else { return };
+    let Some(document) = window.document() else { return };
+    let Some(target) = document.get_element_by_id(tutorial.step().target_id()) else { return };
+    let Some(highlight) = html_element(&document, "tutorial-highlight") else { return };
+    let Some(card) = html_element(&document, "tutorial-card") else { return };
+    let window_width = window.inner_width().ok().and_then(|width| width.as_f64()).unwrap_or_default();
+    let window_height = window.inner_height().ok().and_then(|height| height.as_f64()).unwrap_or_default();
+
+    let rect = target.get_bounding_client_rect();
+    set_box(&highlight, rect.left() - HIGHLIGHT_PADDING, rect.top() - HIGHLIGHT_PADDING, Some((rect.width() + 2.0 * HIGHLIGHT_PADDING, rect.height() + 2.0 * HIGHLIGHT_PADDING)));
+
+    // A large element has the card over its middle, a small one beside it
+    let (card_width, card_height) = (card.offset_width() as f64, card.offset_height() as f64);
+    let (left, top) = if rect.width() > window_width * 0.5 {
+        (rect.left() + (rect.width() - card_width) * 0.5, rect.top() + (rect.height() - card_height) * 0.5)
+    } else {
+        (rect.right() + HIGHLIGHT_PADDING + CARD_GAP, rect.top())
+    };
+    let left = left.min(window_width - card_width - CARD_GAP).max(CARD_GAP);
+    let top = top.min(window_height - card_height - CARD_GAP).max(CARD_GAP);
+    set_box(&card, left, top, None);
+}
+
+pub fn hide_tutorial() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(overlay) = document.get_element_by_id("tutorial-overlay") {
+        let _ = overlay.class_list().add_1("tutorial-overlay-hidden");
+    }
+}
+
+fn html_element(document: &Document, id: &str) -> Option<HtmlElement> {
+    document.get_element_by_id(id).and_then(|element| element.dyn_into::<HtmlElement>().ok())
+}
+
+fn set_box(element: &HtmlElement, left: f64, top: f64, size: Option<(f64, f64)>) {
+    let style = element.style();
+    let _ = style.set_property("left", &format!("{}px", left));
+    let _ = style.set_property("top", &format!("{}px", top));
+    if let Some((width, height)) = size {
+        let _ = style.set_property("width", &format!("{}px", width));
+        let _ = style.set_property("height", &format!("{}px", height));
+    }
+}
+
+fn set_text(document: &Document, id: &str, text: &str) {
+    if let Some(element) = document.get_element_by_id(id) {
+        element.set_text_content(Some(text));
+    }
+}
@@ -1,59 +1,168 @@
 #![cfg(target_arch = "wasm32")]
 
+//! Getting the microphone, one `MicPermission` step at a time
+//!
+//! Requests that need a user gesture call `getUserMedia` inside the click or
+//! key press of the first-click overlay or of the retry button of the
+//! guidance overlay. While the guidance shows, a change of the reported
+//! permission, such as the user allowing the microphone in the site settings,
+//! moves on without a click.
+
 use wasm_bindgen::closure::Closure;
-use wasm_bindgen::JsCast;
-
-pub async fn ask_for_permission() -> Result<web_sys::MediaStream, String> {
-    // Wait for user click on the overlay
-    let document = web_sys::window().unwrap().document().unwrap();
-    let overlay = document.query_selector(".first-click-overlay").unwrap().unwrap();
-    
-    // Create a promise that resolves with the MediaStream
-    let (promise, resolve) = {
-        let mut resolve_holder = None;
-        let promise = js_sys::Promise::new(&mut |resolve, _reject| {
-            resolve_holder = Some(resolve);
-        });
-        (promise, resolve_holder.unwrap())
-    };
-    
-    // Set up click handler that calls getUserMedia INSIDE the callback
-    let resolve_clone = resolve.clone();
-    let click_closure = Closure::<dyn FnMut(_)>::new(move |_event: web_sys::MouseEvent| {
-        // Request media access INSIDE the click callback - critical for security!
-        let constraints = web_sys::MediaStreamConstraints::new();
-        constraints.set_audio(&true.into());
-        constraints.set_video(&false.into());
-        
-        let navigator = web_sys::window().and_then(|w| w.navigator().media_devices().ok()).unwrap();
-        let media_promise = navigator.get_user_media_with_constraints(&constraints).unwrap();
-        
-        // Resolve our promise with the media promise
-        resolve_clone.call1(&wasm_bindgen::JsValue::NULL, &media_promise).unwrap();
-    });
-    
-    overlay.add_event_listener_with_callback("click", click_closure.as_ref().unchecked_ref()).unwrap();
-    
-    // Wait for click - this will resolve with the getUserMedia promise
-    let media_promise_js = match wasm_bindgen_futures::JsFuture::from(promise).await {
-        Ok(result) => result,
-        Err(e) => {
-            return Err(format!("Microphone access denied or failed: {:?}", e));
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, EventTarget, MediaStream, PermissionStatus};
+use crate::common::dev_log;
+use crate::common::localization::translate;
+use crate::common::mic_permission::{Browser, MicPermission, PermissionState, PermissionStep};
+use crate::common::shared_types::Error;
+
+/// The microphone, asked for until it is granted or known to be missing
+pub async fn request_microphone() -> Result<MediaStream, Error> {
+    let status = permission_status().await;
+    let mut permission = MicPermission::new(status.as_ref().and_then(reported_state));
+    let document = web_sys::window().and_then(|window| window.document()).ok_or(Error::BrowserError)?;
+
+    loop {
+        let result = match permission.next_step() {
+            PermissionStep::WaitForClick => {
+                crate::web::utils::show_first_click_overlay();
+                let overlay: EventTarget = document.query_selector(".first-click-overlay").ok().flatten().ok_or(Error::BrowserError)?.into();
+                let result = wait_for(&[(&overlay, "click"), (&overlay, "keydown")]).await;
+                crate::web::utils::hide_first_click_overlay();
+                permission.on_click();
+                result
+            }
+            PermissionStep::Request => JsFuture::from(get_user_media()).await,
+            PermissionStep::ShowGuidance => {
+                show_guidance();
+                let retry: EventTarget = document.get_element_by_id("permission-guidance-retry").ok_or(Error::BrowserError)?.into();
+                let mut listeners = vec![(&retry, "click")];
+                if let Some(status) = &status {
+                    listeners.push((status.as_ref(), "change"));
+                }
+                let result = wait_for(&listeners).await;
+                hide_guidance();
+                match result.as_ref().ok().and_then(JsValue::as_string).and_then(|state| PermissionState::parse(&state)) {
+                    Some(state) => {
+                        dev_log!("Microphone permission changed to {:?}", state);
+                        permission.on_state_changed(state);
+                        continue;
+                    }
+                    None => permission.on_retry(),
+                }
+                result
+            }
+        };
+
+        match result.and_then(|stream| stream.dyn_into::<MediaStream>()) {
+            Ok(stream) => {
+                permission.on_granted();
+                return Ok(stream);
+            }
+            Err(e) => {
+                let name = js_sys::Reflect::get(&e, &"name".into()).ok().and_then(|name| name.as_string()).unwrap_or_default();
+                dev_log!("Microphone request failed: {:?}", e);
+                permission.on_request_failed(&name)?;
+            }
         }
-    };
-    
-    // Clean up the event listener
-    overlay.remove_event_listener_with_callback("click", click_closure.as_ref().unchecked_ref()).unwrap();
-    click_closure.forget();
-    
-    // Check if it's already a MediaStream or if it's a Promise we need to await
-    if media_promise_js.has_type::<web_sys::MediaStream>() {
-        // It's already a MediaStream
-        Ok(media_promise_js.dyn_into::<web_sys::MediaStream>().unwrap())
-    } else {
-        // It's a Promise that resolves to a MediaStream
-        let media_promise = media_promise_js.dyn_into::<js_sys::Promise>().unwrap();
-        let media_stream_js = wasm_bindgen_futures::JsFuture::from(media_promise).await.unwrap();
-        Ok(media_stream_js.dyn_into::<web_sys::MediaStream>().unwrap())
-    }
-}
\ No newline at end of file
+    }
+}
+
+/// The microphone's `PermissionStatus`, where the browser reports one
+async fn permission_status() -> Option<PermissionStatus> {
+    let permissions = web_sys::window()?.navigator().permissions().ok()?;
+    let descriptor = js_sys::Object::new();
+    js_sys::Reflect::set(&descriptor, &"name".into(), &"microphone".into()).ok()?;
+    // Browsers that do not know the microphone permission reject the query
+    let status = JsFuture::from(permissions.query(&descriptor).ok()?).await.ok()?;
+    status.dyn_into::<PermissionStatus>().ok()
+}
+
+fn reported_state(status: &PermissionStatus) -> Option<PermissionState> {
+    match status.state() {
+        web_sys::PermissionState::Prompt => Some(PermissionState::Prompt),
+        web_sys::PermissionState::Granted => Some(PermissionState::Granted),
+        web_sys::PermissionState::Denied => Some(PermissionState::Denied),
+        _ => None,
+    }
+}
+
+/// What an event means for the request: a changed permission is answered by
+/// its state, a click or Enter or Space by a `getUserMedia` promise started
+/// inside it, which is critical for security
+fn request_on_activation(event: &Event) -> Option<JsValue> {
+    if let Some(status) = event.target().and_then(|target| target.dyn_into::<PermissionStatus>().ok()) {
+        let state = match reported_state(&status)? {
+            PermissionState::Prompt => "prompt",
+            PermissionState::Granted => "granted",
+            PermissionState::Denied => return None,
+        };
+        return Some(state.into());
+    }
+    if let Some(key_event) = event.dyn_ref::<web_sys::KeyboardEvent>()
+        && !matches!(key_event.key().as_str(), "Enter" | " ")
+    {
+        return None;
+    }
+    Some(get_user_media().into())
+}
+
+fn get_user_media() -> js_sys::Promise {
+    let constraints = web_sys::MediaStreamConstraints::new();
+    constraints.set_audio(&true.into());
+    constraints.set_video(&false.into());
+    web_sys::window()
+        .ok_or_else(|| JsValue::from("No window"))
+        .and_then(|window| window.navigator().media_devices())
+        .and_then(|media_devices| media_devices.get_user_media_with_constraints(&constraints))
+        .unwrap_or_else(|e| js_sys::Promise::reject(&e))
+}
+
+/// Settle with the first answer of `request_on_activation` to an event of
+/// `listeners`; a `getUserMedia` answer settles as its promise does
+async fn wait_for(listeners: &[(&EventTarget, &str)]) -> Result<JsValue, JsValue> {
+    let mut closure = None;
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        closure = Some(Closure::<dyn FnMut(_)>::new(move |event: Event| {
+            if let Some(answer) = request_on_activation(&event) {
+                let _ = resolve.call1(&JsValue::NULL, &answer);
+            }
+        }));
+    });
+    let closure = closure.expect("Promise executors run synchronously");
+    for (target, event) in listeners {
+        let _ = target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+    }
+    let result = JsFuture::from(promise).await;
+    for (target, event) in listeners {
+        let _ = target.remove_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+    }
+    result
+}
+
+/// Show the steps to allow the microphone again in this browser
+fn show_guidance() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    let user_agent = web_sys::window().and_then(|window| window.navigator().user_agent().ok()).unwrap_or_default();
+    let key = Browser::from_user_agent(&user_agent).guidance_key();
+    if let Some(steps) = document.get_element_by_id("permission-guidance-steps") {
+        // Kept in the current language as it changes
+        let _ = steps.set_attribute("data-i18n", key);
+        steps.set_text_content(Some(translate(key)));
+    }
+    if let Some(overlay) = document.get_element_by_id("permission-guidance-overlay") {
+        let _ = overlay.class_list().remove_1("error-overlay-hidden");
+    }
+    crate::web::accessibility::alert(translate("permission-guidance-title"));
+    if let Some(retry) = document.get_element_by_id("permission-guidance-retry").and_then(|retry| retry.dyn_into::<web_sys::HtmlElement>().ok()) {
+        let _ = retry.focus();
+    }
+}
+
+fn hide_guidance() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(overlay) = document.get_element_by_id("permission-guidance-overlay") {
+        let _ = overlay.class_list().add_1("error-overlay-hidden");
+    }
+}
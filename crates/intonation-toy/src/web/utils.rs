@@ -21,9 +21,13 @@ pub fn rgb_to_hex(rgb: [f32; 3]) -> String {
 }
 
 pub fn show_first_click_overlay() {
-    web_sys::window().unwrap().document().unwrap()
-        .query_selector(".first-click-overlay").unwrap().unwrap()
-        .class_list().remove_1("first-click-overlay-hidden").unwrap();
+    let overlay = web_sys::window().unwrap().document().unwrap()
+        .query_selector(".first-click-overlay").unwrap().unwrap();
+    overlay.class_list().remove_1("first-click-overlay-hidden").unwrap();
+    // Focused so keyboard users can start with Enter or Space
+    if let Some(overlay) = overlay.dyn_ref::<web_sys::HtmlElement>() {
+        let _ = overlay.focus();
+    }
 }
 
 pub fn hide_first_click_overlay() {
@@ -32,6 +36,27 @@ pub fn hide_first_click_overlay() {
         .class_list().add_1("first-click-overlay-hidden").unwrap();
 }
 
+pub fn show_audio_paused_overlay(message: &str) {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(message_el) = document.get_element_by_id("audio-paused-message") {
+        message_el.set_text_content(Some(message));
+    }
+    if let Some(overlay) = document.get_element_by_id("audio-paused-overlay") {
+        let _ = overlay.class_list().remove_1("audio-paused-overlay-hidden");
+        // Focused so keyboard users can resume with Enter or Space
+        if let Some(overlay) = overlay.dyn_ref::<web_sys::HtmlElement>() {
+            let _ = overlay.focus();
+        }
+    }
+}
+
+pub fn hide_audio_paused_overlay() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else { return };
+    if let Some(overlay) = document.get_element_by_id("audio-paused-overlay") {
+        let _ = overlay.class_list().add_1("audio-paused-overlay-hidden");
+    }
+}
+
 pub fn hide_preloader() {
     let document = web_sys::window().unwrap().document().unwrap();
     
@@ -53,13 +78,31 @@ pub fn get_canvas() -> web_sys::HtmlCanvasElement {
 pub fn get_canvas_style_size() -> f32 {
     let window_obj = web_sys::window().unwrap();
     
-    let available_width = window_obj.inner_width().unwrap().as_f64().unwrap() as i32 - crate::web::styling::SIDEBAR_WIDTH - (crate::web::styling::CANVAS_MARGIN * 2);
+    if crate::web::layout::is_touch() {
+        return touch_placement().size as f32;
+    }
+    
+    // The sidebar floats over the canvas in the immersive layout
+    let sidebar_width = if crate::web::layout::is_immersive() { 0 } else { crate::web::styling::SIDEBAR_WIDTH };
+    let available_width = window_obj.inner_width().unwrap().as_f64().unwrap() as i32 - sidebar_width - (crate::web::styling::CANVAS_MARGIN * 2);
     let available_height = window_obj.inner_height().unwrap().as_f64().unwrap() as i32 - (crate::web::styling::CANVAS_MARGIN * 2);
     
     std::cmp::min(available_width, available_height)
         .clamp(crate::app_config::CANVAS_MIN_SIZE, crate::app_config::CANVAS_MAX_SIZE) as f32
 }
 
+fn window_width() -> i32 {
+    web_sys::window().unwrap().inner_width().unwrap().as_f64().unwrap() as i32
+}
+
+fn window_height() -> i32 {
+    web_sys::window().unwrap().inner_height().unwrap().as_f64().unwrap() as i32
+}
+
+fn touch_placement() -> crate::common::touch_layout::TouchPlacement {
+    crate::common::touch_layout::TouchPlacement::new(window_width(), window_height())
+}
+
 pub fn resize_canvas() {
     let canvas = get_canvas();
     let document = web_sys::window().unwrap().document().unwrap();
@@ -68,9 +111,22 @@ pub fn resize_canvas() {
     
     let scene_wrapper = document.get_element_by_id("scene-wrapper").unwrap();
     
+    // Centered in the window in the immersive layout, and placed by
+    // orientation in the touch layout
+    let (left, top) = if crate::web::layout::is_touch() {
+        let placement = touch_placement();
+        if let Some(root) = document.document_element().and_then(|root| root.dyn_into::<web_sys::HtmlElement>().ok()) {
+            let _ = root.style().set_property("--touch-controls-height", &format!("{}px", placement.controls_height));
+        }
+        (placement.left, placement.top)
+    } else if crate::web::layout::is_immersive() {
+        ((window_width() - canvas_size).max(0) / 2, crate::web::styling::CANVAS_MARGIN)
+    } else {
+        (crate::web::styling::CANVAS_MARGIN, crate::web::styling::CANVAS_MARGIN)
+    };
     scene_wrapper.set_attribute("style", &format!(
         "position: absolute; top: {}px; left: {}px; width: {}px; height: {}px;",
-        crate::web::styling::CANVAS_MARGIN, crate::web::styling::CANVAS_MARGIN, canvas_size, canvas_size
+        top, left, canvas_size, canvas_size
     )).unwrap();
     
     let html_element = canvas.dyn_ref::<web_sys::HtmlElement>().unwrap();
@@ -8,7 +8,7 @@
  * Key Features:
  * - Fixed 128-sample chunk processing (Web Audio API standard)
  * - Batched audio data transfer (default: 4096 samples / 32 chunks)
- * - Transferable ArrayBuffers for zero-copy message passing
+ * - Transferable ArrayBuffers, so batches cross threads without a copy
  * - Configurable batch size and timeout for low-latency scenarios
  * - Buffer pool management with ping-pong recycling pattern
  * - Error handling and processor lifecycle management
@@ -28,12 +28,14 @@
  * processor.port.postMessage(message);
  * 
  * // Buffer pool usage with ping-pong pattern
- * const buffer = processor.bufferPool.acquire();
- * if (buffer) {
- *     // Fill buffer with audio data
- *     processor.port.postMessage(message, [buffer]);
- *     processor.bufferPool.markTransferred(buffer);
+ * const acquisition = processor.bufferPool.acquire();
+ * if (acquisition) {
+ *     // Fill acquisition.buffer with audio data
+ *     processor.port.postMessage(message, [acquisition.buffer]);
+ *     processor.bufferPool.markTransferred(acquisition.bufferId);
  * }
+ * // ...and when the main thread sends it back
+ * processor.bufferPool.recycle(bufferId, buffer);
  * ```
  */
 
@@ -49,192 +51,261 @@ function getCurrentTime() {
 
 // TransferableBufferPool class (inlined for AudioWorklet compatibility)
 // Note: importScripts is not available in AudioWorklet context
+/**
+ * Pool of ArrayBuffers that travel to the main thread as transferables and
+ * come back with returnBuffer, so batches cross threads without a copy and
+ * are never reallocated. The main thread still copies each batch once into
+ * wasm memory, which cannot view a transferred buffer in place.
+ * The pool grows up to maxBuffers when the main thread holds on to buffers;
+ * beyond that a batch is dropped and counted as a miss.
+ */
 class TransferableBufferPool {
-    constructor(poolSize = 4, bufferCapacity = BUFFER_SIZE) {
-        this.poolSize = poolSize;
+    constructor(initialBuffers, maxBuffers, bufferCapacity) {
+        this.maxBuffers = maxBuffers;
         this.bufferCapacity = bufferCapacity;
-        this.buffers = [];
-        this.availableIndices = [];
-        this.inUseBuffers = new Map();
-        
-        // Buffer lifecycle management
-        this.bufferStates = [];
-        this.bufferIds = [];
+        this.freeBuffers = [];
+        this.inFlightIds = new Set();
+        this.totalBuffers = 0;
         this.nextBufferId = 1;
-        
-        // Buffer states enum
-        this.BUFFER_STATES = {
-            AVAILABLE: 'available',
-            IN_FLIGHT: 'in_flight',
-            PROCESSING: 'processing'
-        };
-        
-        // Performance counters
-        this.perfCounters = {
-            allocationCount: 0,
-            totalAcquisitionTime: 0,
-            fastestAcquisition: Infinity,
-            slowestAcquisition: 0,
-            poolHitRate: 0
-        };
-        
-        // Statistics
+
         this.stats = {
-            acquireCount: 0,
+            allocationCount: 0,
+            recycleCount: 0,
+            missCount: 0,
             transferCount: 0,
-            poolExhaustedCount: 0,
         };
-        
-        // Initialize pool with pre-allocated buffers
-        for (let i = 0; i < poolSize; i++) {
-            this.buffers.push(new ArrayBuffer(bufferCapacity * 4));
-            this.availableIndices.push(i);
-            this.bufferStates.push(this.BUFFER_STATES.AVAILABLE);
-            this.bufferIds.push(0);
-            this.perfCounters.allocationCount++;
+
+        for (let i = 0; i < initialBuffers; i++) {
+            this.freeBuffers.push(this.allocate());
         }
     }
-    
+
+    allocate() {
+        this.totalBuffers++;
+        this.stats.allocationCount++;
+        return new ArrayBuffer(this.bufferCapacity * 4);
+    }
+
+    /**
+     * Take a buffer for the next batch, or null when the pool is at its limit
+     */
     acquire() {
-        const startTime = getCurrentTime();
-        this.stats.acquireCount++;
-        
-        if (this.availableIndices.length === 0) {
-            this.stats.poolExhaustedCount++;
-            console.warn('TransferableBufferPool: Pool exhausted, no buffers available');
-            
-            const acquisitionTime = getCurrentTime() - startTime;
-            this.updateAcquisitionMetrics(acquisitionTime);
-            
-            return null;
+        let buffer = this.freeBuffers.pop();
+        if (!buffer) {
+            if (this.totalBuffers >= this.maxBuffers) {
+                this.stats.missCount++;
+                return null;
+            }
+            buffer = this.allocate();
         }
-        
-        const index = this.availableIndices.pop();
-        const buffer = this.buffers[index];
-        
-        // Track buffer usage
-        this.inUseBuffers.set(buffer, index);
-        
-        // Update buffer lifecycle tracking
-        this.bufferStates[index] = this.BUFFER_STATES.IN_FLIGHT;
-        this.bufferIds[index] = this.nextBufferId++;
-        
-        // Track acquisition time
-        const acquisitionTime = getCurrentTime() - startTime;
-        this.updateAcquisitionMetrics(acquisitionTime);
-        
-        return {
-            buffer: buffer,
-            bufferId: this.bufferIds[index]
-        };
+        return { buffer: buffer, bufferId: this.nextBufferId++ };
     }
-    
-    markTransferred(buffer) {
+
+    markTransferred(bufferId) {
+        this.inFlightIds.add(bufferId);
         this.stats.transferCount++;
-        
-        const index = this.inUseBuffers.get(buffer);
-        if (index === undefined) {
-            console.error('TransferableBufferPool: Attempting to mark unknown buffer as transferred');
-            return;
-        }
-        
-        // Remove from in-use tracking
-        this.inUseBuffers.delete(buffer);
-        
-        // Update buffer lifecycle tracking
-        this.bufferStates[index] = this.BUFFER_STATES.PROCESSING;
-        // Keep timestamp for timeout tracking
     }
-    
+
+    /**
+     * Put back a buffer that was acquired but not sent. Buffers from before a
+     * resize are dropped and replaced on demand.
+     */
     release(buffer) {
-        const index = this.inUseBuffers.get(buffer);
-        if (index === undefined) {
-            console.error('TransferableBufferPool: Attempting to release unknown buffer');
-            return;
+        if (buffer.byteLength === this.bufferCapacity * 4) {
+            this.freeBuffers.push(buffer);
+        } else {
+            this.totalBuffers--;
         }
-        
-        // Remove from in-use tracking
-        this.inUseBuffers.delete(buffer);
-        
-        // Update buffer lifecycle tracking
-        this.bufferStates[index] = this.BUFFER_STATES.AVAILABLE;
-        this.bufferIds[index] = 0;
-        
-        // Mark index as available again
-        this.availableIndices.push(index);
     }
-    
-    returnBuffer(bufferId, buffer) {
-        
-        // Find buffer by ID for proper lifecycle tracking
-        let targetIndex = -1;
-        for (let i = 0; i < this.poolSize; i++) {
-            if (this.bufferIds[i] === bufferId && this.bufferStates[i] === this.BUFFER_STATES.PROCESSING) {
-                targetIndex = i;
-                break;
-            }
-        }
-        
-        if (targetIndex !== -1) {
-            // Found the buffer, return it to its original slot
-            this.buffers[targetIndex] = buffer;
-            this.bufferStates[targetIndex] = this.BUFFER_STATES.AVAILABLE;
-            this.bufferIds[targetIndex] = 0;
-            
-            if (!this.availableIndices.includes(targetIndex)) {
-                this.availableIndices.push(targetIndex);
-            }
-                        
-            // Buffer returned to pool successfully
-            return true;
+
+    /**
+     * Take back a buffer the main thread has finished with
+     */
+    recycle(bufferId, buffer) {
+        if (!this.inFlightIds.delete(bufferId)) {
+            return false;
         }
-        
-        console.warn('TransferableBufferPool: Could not return buffer to pool');
-        return false;
+        this.stats.recycleCount++;
+        this.release(buffer);
+        return true;
     }
-    
+
+    /**
+     * Change the capacity of pooled buffers. Free buffers are dropped now;
+     * buffers held by the main thread are dropped when they come back.
+     */
+    resize(bufferCapacity) {
+        this.bufferCapacity = bufferCapacity;
+        this.totalBuffers -= this.freeBuffers.length;
+        this.freeBuffers = [];
+    }
+
     getStats() {
-        // Always return current stats, not cached values
-        const stats = {
-            acquireCount: this.stats.acquireCount,
-            transferCount: this.stats.transferCount,
-            poolExhaustedCount: this.stats.poolExhaustedCount,
-            availableBuffers: this.availableIndices.length,
-            inUseBuffers: this.inUseBuffers.size,
-            totalBuffers: this.poolSize
+        return {
+            available_buffers: this.freeBuffers.length,
+            in_flight_buffers: this.inFlightIds.size,
+            total_buffers: this.totalBuffers,
+            allocation_count: this.stats.allocationCount,
+            recycle_count: this.stats.recycleCount,
+            miss_count: this.stats.missCount,
+            transfer_count: this.stats.transferCount,
         };
-        return stats;
+    }
+}
+
+// Control block slots, matching engine/audio/ring_buffer.rs
+const RING_WRITE_TOTAL = 0;
+const RING_READ_TOTAL = 1;
+const RING_OVERFLOW_COUNT = 2;
+
+/**
+ * Producer side of the shared sample ring. The main thread allocates both
+ * SharedArrayBuffers and reads from them each frame. Counters are running
+ * totals wrapping at 32 bits; the capacity is a power of two.
+ */
+class SharedSampleRing {
+    constructor(sampleBuffer, controlBuffer) {
+        this.samples = new Float32Array(sampleBuffer);
+        this.control = new Int32Array(controlBuffer);
+        this.capacity = this.samples.length;
+        this.mask = this.capacity - 1;
+    }
+
+    /**
+     * Append a chunk, or drop it and count an overflow when the reader has
+     * fallen too far behind
+     */
+    write(chunk) {
+        const writeTotal = Atomics.load(this.control, RING_WRITE_TOTAL);
+        const readTotal = Atomics.load(this.control, RING_READ_TOTAL);
+        const used = (writeTotal - readTotal) | 0;
+        if (this.capacity - used < chunk.length) {
+            Atomics.add(this.control, RING_OVERFLOW_COUNT, 1);
+            return false;
+        }
+
+        const start = writeTotal & this.mask;
+        const first = Math.min(chunk.length, this.capacity - start);
+        this.samples.set(chunk.subarray(0, first), start);
+        if (first < chunk.length) {
+            this.samples.set(chunk.subarray(first), 0);
+        }
+        Atomics.store(this.control, RING_WRITE_TOTAL, (writeTotal + chunk.length) | 0);
+        return true;
+    }
+}
+
+// Biquad filter (RBJ Audio EQ Cookbook), direct form I
+class BiquadFilter {
+    constructor() {
+        this.b0 = 1; this.b1 = 0; this.b2 = 0; this.a1 = 0; this.a2 = 0;
+        this.reset();
     }
     
-    updateAcquisitionMetrics(acquisitionTime) {
-        this.perfCounters.totalAcquisitionTime += acquisitionTime;
-        this.perfCounters.fastestAcquisition = Math.min(this.perfCounters.fastestAcquisition, acquisitionTime);
-        this.perfCounters.slowestAcquisition = Math.max(this.perfCounters.slowestAcquisition, acquisitionTime);
+    reset() {
+        this.x1 = 0; this.x2 = 0; this.y1 = 0; this.y2 = 0;
+    }
+    
+    setHighPass(frequency, q, sampleRate) {
+        const w0 = 2 * Math.PI * frequency / sampleRate;
+        const cos = Math.cos(w0);
+        const alpha = Math.sin(w0) / (2 * q);
+        this.setCoefficients((1 + cos) / 2, -(1 + cos), (1 + cos) / 2, 1 + alpha, -2 * cos, 1 - alpha);
+    }
+    
+    setNotch(frequency, q, sampleRate) {
+        const w0 = 2 * Math.PI * frequency / sampleRate;
+        const cos = Math.cos(w0);
+        const alpha = Math.sin(w0) / (2 * q);
+        this.setCoefficients(1, -2 * cos, 1, 1 + alpha, -2 * cos, 1 - alpha);
+    }
+    
+    setCoefficients(b0, b1, b2, a0, a1, a2) {
+        this.b0 = b0 / a0; this.b1 = b1 / a0; this.b2 = b2 / a0;
+        this.a1 = a1 / a0; this.a2 = a2 / a0;
+        this.reset();
+    }
+    
+    process(x) {
+        const y = this.b0 * x + this.b1 * this.x1 + this.b2 * this.x2 - this.a1 * this.y1 - this.a2 * this.y2;
+        this.x2 = this.x1; this.x1 = x;
+        this.y2 = this.y1; this.y1 = y;
+        return y;
+    }
+}
+
+const HIGH_PASS_Q = Math.SQRT1_2;
+const NOTCH_Q = 10;
+const GATE_ENVELOPE_RELEASE_MS = 10;
+
+// Input conditioning: high-pass, optional mains hum notch, then noise gate.
+// Everything is disabled until configured by the main thread.
+class InputConditioner {
+    constructor(sampleRate) {
+        this.sampleRate = sampleRate;
+        this.highPass = new BiquadFilter();
+        this.notch = new BiquadFilter();
+        this.highPassEnabled = false;
+        this.notchEnabled = false;
+        this.gateEnabled = false;
+        this.gateThreshold = 0;
+        this.gateAttackCoef = 0;
+        this.gateReleaseCoef = 0;
+        this.envelopeReleaseCoef = this.timeCoefficient(GATE_ENVELOPE_RELEASE_MS);
+        this.envelope = 0;
+        this.gateGain = 1;
+    }
+    
+    timeCoefficient(ms) {
+        return Math.exp(-1 / (Math.max(ms, 0.01) / 1000 * this.sampleRate));
+    }
+    
+    configure(config) {
+        this.highPassEnabled = !!config.highPassEnabled;
+        if (this.highPassEnabled) {
+            this.highPass.setHighPass(config.highPassHz, HIGH_PASS_Q, this.sampleRate);
+        }
         
-        // Calculate pool hit rate
-        if (this.stats.acquireCount > 0) {
-            this.perfCounters.poolHitRate = ((this.stats.acquireCount - this.stats.poolExhaustedCount) / this.stats.acquireCount) * 100;
+        this.notchEnabled = config.notchHz > 0;
+        if (this.notchEnabled) {
+            this.notch.setNotch(config.notchHz, NOTCH_Q, this.sampleRate);
         }
+        
+        this.gateEnabled = !!config.gateEnabled;
+        this.gateThreshold = Math.pow(10, config.gateThresholdDb / 20);
+        this.gateAttackCoef = this.timeCoefficient(config.gateAttackMs);
+        this.gateReleaseCoef = this.timeCoefficient(config.gateReleaseMs);
+        this.envelope = 0;
+        this.gateGain = 1;
     }
     
-    getPerformanceMetrics() {
-        const avgAcquisitionTime = this.stats.acquireCount > 0 ? 
-            this.perfCounters.totalAcquisitionTime / this.stats.acquireCount : 0;
-            
-        return {
-            allocationCount: this.perfCounters.allocationCount,
-            poolHitRate: this.perfCounters.poolHitRate.toFixed(2) + '%',
-            acquisitionMetrics: {
-                average: avgAcquisitionTime.toFixed(3) + 'ms',
-                fastest: this.perfCounters.fastestAcquisition === Infinity ? 'N/A' : 
-                    this.perfCounters.fastestAcquisition.toFixed(3) + 'ms',
-                slowest: this.perfCounters.slowestAcquisition.toFixed(3) + 'ms',
-                total: this.perfCounters.totalAcquisitionTime.toFixed(3) + 'ms'
-            }
-        };
+    isActive() {
+        return this.highPassEnabled || this.notchEnabled || this.gateEnabled;
     }
     
-    
+    // Processes samples in place
+    process(samples) {
+        for (let i = 0; i < samples.length; i++) {
+            let x = samples[i];
+            if (this.highPassEnabled) {
+                x = this.highPass.process(x);
+            }
+            if (this.notchEnabled) {
+                x = this.notch.process(x);
+            }
+            if (this.gateEnabled) {
+                const level = Math.abs(x);
+                this.envelope = level > this.envelope
+                    ? level
+                    : level + this.envelopeReleaseCoef * (this.envelope - level);
+                const target = this.envelope >= this.gateThreshold ? 1 : 0;
+                const coef = target > this.gateGain ? this.gateAttackCoef : this.gateReleaseCoef;
+                this.gateGain = target + coef * (this.gateGain - target);
+                x *= this.gateGain;
+            }
+            samples[i] = x;
+        }
+    }
 }
 
 // Message Protocol (inlined for AudioWorklet compatibility)
@@ -243,7 +314,9 @@ const ToWorkletMessageType = {
     START_PROCESSING: 'startProcessing',
     STOP_PROCESSING: 'stopProcessing',
     UPDATE_BATCH_CONFIG: 'updateBatchConfig',
-    RETURN_BUFFER: 'returnBuffer'
+    UPDATE_INPUT_CONDITIONING: 'updateInputConditioning',
+    RETURN_BUFFER: 'returnBuffer',
+    ENABLE_SAMPLE_RING: 'enableSampleRing'
 };
 
 const FromWorkletMessageType = {
@@ -415,19 +488,20 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
         this.batchSize = BUFFER_SIZE; // 32 chunks of 128 samples
         this.chunksPerBatch = this.batchSize / this.chunkSize;
         
+        // Pre-processing applied before batching
+        this.inputConditioner = new InputConditioner(sampleRate);
+        this.processedAudio = new Float32Array(this.chunkSize);
+        
         // Initialize buffer pool for ping-pong recycling
-        this.bufferPool = new TransferableBufferPool(16, this.batchSize); // 16 buffers in pool
+        this.bufferPool = new TransferableBufferPool(4, 16, this.batchSize);
         this.bufferPoolConfig = {
-            maxConsecutiveFailures: 3, // Max consecutive pool failures before warning
-            warningThreshold: 10       // Warn if pool exhausted count exceeds this
+            maxConsecutiveFailures: 3 // Max consecutive pool misses before warning
         };
         this.consecutivePoolFailures = 0;
         
-        // Enhanced buffer management with lifecycle tracking
+        // Transfer statistics
         this.bufferStats = {
-            acquireCount: 0,
             transferCount: 0,
-            poolExhaustedCount: 0,
             totalBytesTransferred: 0,
             averageBufferUtilization: 0.0,
             bufferLifecycle: {
@@ -464,6 +538,9 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
         this.bufferTimeout = 100; // 100ms timeout for partial buffers (allows natural buffer filling)
         this.lastBufferStartTime = 0;
         
+        // Shared memory transport, replaces batch messages once enabled
+        this.sampleRing = null;
+        
         // Processing state
         this.isProcessing = false;
         this.chunkCounter = 0;
@@ -485,9 +562,6 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
      * Acquire a new buffer for batching with lifecycle tracking
      */
     acquireNewBuffer() {
-        this.bufferStats.acquireCount++;
-        
-        // Try to acquire from pool first
         const acquisition = this.bufferPool.acquire();
         
         if (acquisition) {
@@ -502,8 +576,7 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
             
             // Buffer acquired from pool successfully
         } else {
-            // Pool exhausted - skip processing rather than fallback allocation
-            this.bufferStats.poolExhaustedCount++;
+            // Pool at its limit - skip processing, the miss is counted by the pool
             this.consecutivePoolFailures++;
             
             // Clear buffer references to indicate no buffer available
@@ -513,10 +586,8 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
             this.writePosition = 0;
             
             // Log warning based on failure frequency
-            if (this.consecutivePoolFailures >= this.bufferPoolConfig.maxConsecutiveFailures) {
+            if (this.consecutivePoolFailures === this.bufferPoolConfig.maxConsecutiveFailures) {
                 console.warn('PitchDetectionProcessor: Pool exhausted for', this.consecutivePoolFailures, 'consecutive attempts, skipping analysis data');
-            } else if (this.bufferStats.poolExhaustedCount >= this.bufferPoolConfig.warningThreshold) {
-                console.warn('PitchDetectionProcessor: Pool exhaustion count exceeded threshold:', this.bufferStats.poolExhaustedCount);
             }
         }
     }
@@ -558,20 +629,8 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                 }
                 
                 // Create buffer pool statistics to include with the audio data
-                const poolStats = this.bufferPool.getStats();
                 const bufferPoolStats = {
-                    pool_size: this.bufferPool.poolSize,
-                    available_buffers: poolStats.availableBuffers,
-                    in_use_buffers: poolStats.inUseBuffers,
-                    total_buffers: poolStats.totalBuffers,
-                    acquire_count: poolStats.acquireCount,
-                    transfer_count: poolStats.transferCount,
-                    pool_exhausted_count: poolStats.poolExhaustedCount,
-                    consecutive_pool_failures: this.consecutivePoolFailures,
-                    pool_hit_rate: poolStats.acquireCount > 0 ? 
-                        ((poolStats.acquireCount - poolStats.poolExhaustedCount) / poolStats.acquireCount) * 100 : 0.0,
-                    pool_efficiency: poolStats.transferCount > 0 ? 
-                        (poolStats.transferCount / (poolStats.transferCount + poolStats.poolExhaustedCount)) * 100 : 0.0,
+                    ...this.bufferPool.getStats(),
                     buffer_utilization_percent: this.bufferStats.averageBufferUtilization * 100,
                     total_megabytes_transferred: this.bufferStats.totalBytesTransferred / 1024 / 1024
                 };
@@ -589,11 +648,8 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                 const transferables = this.messageProtocol.getTransferableObjects(batchMessage);
                 this.port.postMessage(batchMessage, transferables);
                 
-                // Mark buffer as transferred in pool (this creates a replacement)
-                // Only mark if buffer was acquired from pool
-                if (this.consecutivePoolFailures === 0) {
-                    this.bufferPool.markTransferred(this.currentBuffer);
-                }
+                // The buffer is now detached here until the main thread returns it
+                this.bufferPool.markTransferred(this.currentBufferId);
                 
                 
                 // Track buffer transfer statistics
@@ -694,9 +750,15 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                                 this.sendCurrentBuffer();
                             }
                             
+                            // An acquired but still empty buffer goes back to the pool
+                            if (this.currentBuffer && this.currentBufferId > 0) {
+                                this.bufferPool.release(this.currentBuffer);
+                            }
+                            
                             // Update configuration
                             this.batchSize = newBatchSize;
                             this.chunksPerBatch = this.batchSize / this.chunkSize;
+                            this.bufferPool.resize(this.batchSize);
                             
                             // Reset buffer state with new size
                             this.currentBuffer = null;
@@ -708,11 +770,19 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                         // Update timeout if provided
                         if (actualMessage.config.bufferTimeout !== undefined) {
                             this.bufferTimeout = Math.max(0, actualMessage.config.bufferTimeout);
+                        } else if (actualMessage.config.timeoutMs !== undefined) {
+                            this.bufferTimeout = Math.max(0, actualMessage.config.timeoutMs);
                         }
                         
                     }
                     break;
                 
+                case ToWorkletMessageType.UPDATE_INPUT_CONDITIONING:
+                    if (actualMessage.config) {
+                        this.inputConditioner.configure(actualMessage.config);
+                    }
+                    break;
+                
                 case ToWorkletMessageType.RETURN_BUFFER:
                     if (actualMessage.bufferId !== undefined) {
                         // Extract buffer from message envelope if present
@@ -727,7 +797,7 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                         
                         if (returnedBuffer) {
                             // Return buffer to pool for reuse
-                            const success = this.bufferPool.returnBuffer(actualMessage.bufferId, returnedBuffer);
+                            const success = this.bufferPool.recycle(actualMessage.bufferId, returnedBuffer);
                             if (success) {
                                 // Buffer successfully returned to pool
                             } else {
@@ -739,6 +809,24 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
                     }
                     break;
                 
+                case ToWorkletMessageType.ENABLE_SAMPLE_RING:
+                    if (actualMessage.samples && actualMessage.control) {
+                        // Flush the batch in progress; later samples go to the ring
+                        if (this.currentBuffer && this.writePosition > 0) {
+                            this.sendCurrentBuffer();
+                        }
+                        if (this.currentBuffer && this.currentBufferId > 0) {
+                            this.bufferPool.release(this.currentBuffer);
+                        }
+                        this.currentBuffer = null;
+                        this.currentBufferArray = null;
+                        this.currentBufferId = 0;
+                        this.writePosition = 0;
+                        
+                        this.sampleRing = new SharedSampleRing(actualMessage.samples, actualMessage.control);
+                    }
+                    break;
+                
                 default:
                     console.warn('PitchDetectionProcessor: Unknown message type:', actualMessage.type);
                     this.sendErrorMessage(`Unknown message type: ${actualMessage.type}`, WorkletErrorCode.INVALID_CONFIGURATION);
@@ -804,8 +892,12 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
             return true;
         }
         
-        // Process microphone input audio
-        const processedAudio = new Float32Array(inputChannel);
+        // Process microphone input audio in a reused scratch buffer
+        const processedAudio = this.processedAudio;
+        processedAudio.set(inputChannel);
+        if (this.inputConditioner.isActive()) {
+            this.inputConditioner.process(processedAudio);
+        }
         
         // Pass-through processed audio to output
         if (output && output.length > 0 && output[0]) {
@@ -818,7 +910,12 @@ class PitchDetectionProcessor extends AudioWorkletProcessor {
         }
         
         // Accumulate processed audio data for batching
-        if (this.isProcessing) {
+        if (this.isProcessing && this.sampleRing) {
+            if (!this.sampleRing.write(processedAudio)) {
+                this.performanceMonitoring.metrics.droppedChunks++;
+            }
+            this.chunkCounter++;
+        } else if (this.isProcessing) {
                 try {
                 // Ensure we have a buffer to write to
                 if (!this.currentBuffer || !this.currentBufferArray) {
@@ -0,0 +1,113 @@
+/**
+ * Service Worker for Offline Use
+ *
+ * Keeps the files of one build of the app in a cache of its own, so the app
+ * starts without a network once it has been loaded.
+ *
+ * The page registers this worker with the file name of its wasm module in
+ * the `build` query parameter. Trunk puts a hash of the build in that name,
+ * so every new build registers as a new worker, which fills a new cache. It
+ * waits until no tab runs the older build before it takes over and deletes
+ * the caches of older builds, so a running page never loses its files.
+ *
+ * Requests:
+ * - Pages come from the network first, so a new build is found as soon as
+ *   the network is there, and from the cache without one
+ * - The wasm module and its glue have the hash in their names and come from
+ *   the cache first
+ * - The other files keep their names from build to build; they come from the
+ *   network first as well, so a page of a newer build than this worker's
+ *   never gets the files of this one, and from the cache of this build
+ *   without a network, where the page comes from the same cache
+ * - Only successful responses are cached
+ */
+
+const CACHE_PREFIX = 'intonation-toy-';
+const BUILD = new URL(self.location.href).searchParams.get('build') || 'unknown';
+const CACHE_NAME = CACHE_PREFIX + BUILD;
+
+const APP_SHELL = [
+    './',
+    'style.css',
+    'audio-processor.js',
+    'manifest.webmanifest',
+    'icons/tuning_fork.png',
+    'icons/headphones.png',
+    'icons/microphone.png',
+    'icons/speaker.png',
+    'icons/speaker-off.png',
+    'icons/display_range_1.png',
+    'icons/display_range_2.png',
+    'icons/display_range_3.png',
+];
+
+/** The wasm module of the build and the JavaScript glue loading it */
+function buildFiles() {
+    if (!BUILD.endsWith('_bg.wasm')) {
+        return [];
+    }
+    return [BUILD, BUILD.replace(/_bg\.wasm$/, '.js')];
+}
+
+/** Whether the file has the hash of the build in its name */
+function isHashed(request) {
+    return buildFiles().some((file) => new URL(file, self.location.href).href === request.url);
+}
+
+/** Store a copy of a successful response in the cache of this build */
+function cacheResponse(key, response) {
+    if (response.ok) {
+        const copy = response.clone();
+        caches.open(CACHE_NAME).then((cache) => cache.put(key, copy));
+    }
+    return response;
+}
+
+self.addEventListener('install', (event) => {
+    // Past the HTTP cache, which may still hold the files of an older build
+    const requests = APP_SHELL.concat(buildFiles()).map((url) => new Request(url, { cache: 'reload' }));
+    event.waitUntil(caches.open(CACHE_NAME).then((cache) => cache.addAll(requests)));
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches.keys()
+            .then((names) => Promise.all(
+                names
+                    .filter((name) => name.startsWith(CACHE_PREFIX) && name !== CACHE_NAME)
+                    .map((name) => caches.delete(name))
+            ))
+    );
+});
+
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+
+    if (request.mode === 'navigate') {
+        event.respondWith(
+            fetch(request)
+                .then((response) => cacheResponse('./', response))
+                .catch(() => caches.open(CACHE_NAME).then((cache) => cache.match('./')))
+        );
+        return;
+    }
+
+    if (!isHashed(request)) {
+        event.respondWith(
+            fetch(request)
+                .then((response) => cacheResponse(request, response))
+                .catch(() => caches.open(CACHE_NAME).then((cache) => cache.match(request)))
+                .then((response) => response || Response.error())
+        );
+        return;
+    }
+
+    event.respondWith(
+        caches.open(CACHE_NAME)
+            .then((cache) => cache.match(request))
+            .then((cached) => cached || fetch(request).then((response) => cacheResponse(request, response)))
+    );
+});